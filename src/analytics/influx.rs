@@ -7,14 +7,17 @@ use influxdb::{InfluxDbWriteable, WriteQuery};
 
 use super::{
     ledger::{
-        AddressActivityMeasurement, AddressBalanceMeasurement, BaseTokenActivityMeasurement, LedgerOutputMeasurement,
-        LedgerSizeMeasurement, OutputActivityMeasurement, TransactionSizeMeasurement, UnclaimedTokenMeasurement,
-        UnlockConditionMeasurement,
+        AddressActivityMeasurement, AddressBalanceMeasurement, BaseTokenActivityMeasurement, CollectionSales,
+        LedgerOutputMeasurement, LedgerSizeMeasurement, NftSalesMeasurement, OutputActivityMeasurement,
+        TimelockHorizonMeasurement, TransactionSizeMeasurement, UnclaimedTokenMeasurement, UnlockConditionMeasurement,
     },
-    tangle::{BlockActivityMeasurement, MilestoneSizeMeasurement},
-    AnalyticsInterval, PerInterval, PerMilestone,
+    tangle::{BlockActivityMeasurement, MilestoneSizeMeasurement, SpamDetectionMeasurement},
+    AnalyticsInterval, PerInterval, PerMilestone, Sampled,
+};
+use crate::{
+    db::{influxdb::InfluxDb, mongodb::collections::TIMELOCK_HORIZONS},
+    model::ProtocolParameters,
 };
-use crate::{db::influxdb::InfluxDb, model::ProtocolParameters};
 
 /// A trait that defines an InfluxDb measurement.
 trait Measurement {
@@ -88,6 +91,38 @@ where
     }
 }
 
+/// Sampled points state how many milestones they stand for, so that they can be interpolated or scaled.
+impl PrepareQuery for Sampled {
+    fn prepare_query(&self) -> Vec<WriteQuery> {
+        self.inner
+            .prepare_query()
+            .into_iter()
+            .map(|query| {
+                query
+                    .add_field("sampling_interval", self.interval)
+                    .add_field("sampled_milestones", self.milestones)
+            })
+            .collect()
+    }
+}
+
+/// NFT sales are written as one point per collection, which is stored as a tag.
+impl PrepareQuery for PerMilestone<NftSalesMeasurement> {
+    fn prepare_query(&self) -> Vec<WriteQuery> {
+        self.inner
+            .collections
+            .iter()
+            .map(|(collection, sales)| {
+                influxdb::Timestamp::from(self.at.milestone_timestamp)
+                    .into_query(CollectionSales::NAME)
+                    .add_tag("collection", collection.as_str())
+                    .add_field("milestone_index", self.at.milestone_index)
+                    .add_fields(sales)
+            })
+            .collect()
+    }
+}
+
 impl<M: Send + Sync> PrepareQuery for PerInterval<M>
 where
     M: IntervalMeasurement,
@@ -145,7 +180,11 @@ impl Measurement for AddressActivityMeasurement {
     const NAME: &'static str = "stardust_active_addresses";
 
     fn add_fields(&self, query: WriteQuery) -> WriteQuery {
-        query.add_field("count", self.count as u64)
+        query
+            .add_field("count", self.count as u64)
+            .add_field("ed25519_count", self.ed25519_count as u64)
+            .add_field("alias_count", self.alias_count as u64)
+            .add_field("nft_count", self.nft_count as u64)
     }
 }
 
@@ -179,6 +218,18 @@ impl Measurement for TransactionSizeMeasurement {
     }
 }
 
+impl Measurement for CollectionSales {
+    const NAME: &'static str = "stardust_nft_sales";
+
+    fn add_fields(&self, query: WriteQuery) -> WriteQuery {
+        query
+            .add_field("sales_count", self.sales_count as u64)
+            .add_field("volume", self.volume.0)
+            .add_field("min_price", self.min_price.0)
+            .add_field("max_price", self.max_price.0)
+    }
+}
+
 impl Measurement for LedgerOutputMeasurement {
     const NAME: &'static str = "stardust_ledger_outputs";
 
@@ -293,6 +344,43 @@ impl Measurement for UnlockConditionMeasurement {
     }
 }
 
+impl Measurement for TimelockHorizonMeasurement {
+    const NAME: &'static str = "stardust_timelock_horizon";
+
+    fn add_fields(&self, query: WriteQuery) -> WriteQuery {
+        let query = TIMELOCK_HORIZONS
+            .iter()
+            .zip(self.horizons)
+            .fold(query, |query, ((name, _), unlocks)| {
+                query
+                    .add_field(format!("{name}_count"), unlocks.count as u64)
+                    .add_field(format!("{name}_amount"), unlocks.amount.0)
+            });
+        query
+            .add_field("later_count", self.later.count as u64)
+            .add_field("later_amount", self.later.amount.0)
+    }
+}
+
+impl Measurement for SpamDetectionMeasurement {
+    const NAME: &'static str = "stardust_spam_detection";
+
+    fn add_fields(&self, query: WriteQuery) -> WriteQuery {
+        let query = query
+            .add_field("block_count", self.block_count as u64)
+            .add_field("duplicate_payload_count", self.duplicate_payload_count as u64)
+            .add_field("transaction_count", self.transaction_count as u64)
+            .add_field("top_issuer_transaction_count", self.top_issuer_transaction_count as u64)
+            .add_field("block_spike", self.flags.block_spike)
+            .add_field("duplicate_payloads", self.flags.duplicate_payloads)
+            .add_field("issuer_dominance", self.flags.issuer_dominance);
+        match self.baseline_block_count {
+            Some(baseline) => query.add_field("baseline_block_count", baseline),
+            None => query,
+        }
+    }
+}
+
 impl InfluxDb {
     /// Writes a [`Measurement`] to the InfluxDB database.
     pub(super) async fn insert_measurement(&self, measurement: impl PrepareQuery) -> Result<(), influxdb::Error> {
@@ -6,13 +6,30 @@ use std::collections::HashSet;
 use super::*;
 use crate::{
     analytics::{AnalyticsInterval, IntervalAnalytics},
-    db::{mongodb::collections::OutputCollection, MongoDb},
+    db::{
+        mongodb::collections::{AddressActivityByKind, OutputCollection},
+        MongoDb,
+    },
     model::utxo::Address,
 };
 
 #[derive(Debug, Default)]
 pub(crate) struct AddressActivityMeasurement {
     pub(crate) count: usize,
+    pub(crate) ed25519_count: usize,
+    pub(crate) alias_count: usize,
+    pub(crate) nft_count: usize,
+}
+
+impl From<AddressActivityByKind> for AddressActivityMeasurement {
+    fn from(value: AddressActivityByKind) -> Self {
+        Self {
+            count: value.total(),
+            ed25519_count: value.ed25519_count,
+            alias_count: value.alias_count,
+            nft_count: value.nft_count,
+        }
+    }
 }
 
 /// Computes the number of addresses that were active during a given time interval.
@@ -32,11 +49,11 @@ impl IntervalAnalytics for AddressActivityMeasurement {
         interval: AnalyticsInterval,
         db: &MongoDb,
     ) -> eyre::Result<Self::Measurement> {
-        let count = db
+        Ok(db
             .collection::<OutputCollection>()
             .get_address_activity_count_in_range(start_date, interval.end_date(&start_date))
-            .await?;
-        Ok(AddressActivityMeasurement { count })
+            .await?
+            .into())
     }
 }
 
@@ -58,8 +75,14 @@ impl Analytics for AddressActivityAnalytics {
     }
 
     fn take_measurement(&mut self, _ctx: &dyn AnalyticsContext) -> Self::Measurement {
-        AddressActivityMeasurement {
-            count: std::mem::take(self).addresses.len(),
+        let mut activity = AddressActivityByKind::default();
+        for address in std::mem::take(self).addresses {
+            match address {
+                Address::Ed25519(_) => activity.ed25519_count += 1,
+                Address::Alias(_) => activity.alias_count += 1,
+                Address::Nft(_) => activity.nft_count += 1,
+            }
         }
+        activity.into()
     }
 }
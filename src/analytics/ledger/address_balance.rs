@@ -1,10 +1,13 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashMap;
+use std::path::Path;
 
-use super::*;
-use crate::model::utxo::{Address, TokenAmount};
+use super::{balance_store::BalanceStore, *};
+use crate::{
+    analytics::AnalyticsError,
+    model::utxo::{Address, TokenAmount},
+};
 
 #[derive(Debug)]
 pub(crate) struct AddressBalanceMeasurement {
@@ -24,59 +27,146 @@ pub(crate) struct DistributionStat {
 /// Computes the number of addresses the currently hold a balance.
 #[derive(Serialize, Deserialize)]
 pub(crate) struct AddressBalancesAnalytics {
-    balances: HashMap<Address, TokenAmount>,
+    balances: BalanceStore,
+    /// The distribution of the balances, kept up to date so that measuring it does not scan all balances. It is
+    /// rebuilt at the next measurement if unknown.
+    #[serde(skip)]
+    distribution: Option<Vec<DistributionStat>>,
+    /// The error that kept the balances from being updated. Once set, the balances are left as they are.
+    #[serde(skip)]
+    error: Option<sled::Error>,
 }
 
 impl AddressBalancesAnalytics {
     /// Initialize the analytics by reading the current ledger state.
     pub(crate) fn init<'a>(unspent_outputs: impl IntoIterator<Item = &'a LedgerOutput>) -> Self {
-        let mut balances = HashMap::new();
+        // Unwrap: the balances in memory are accessed infallibly.
+        Self::init_with_store(BalanceStore::default(), unspent_outputs).unwrap()
+    }
+
+    /// Initialize the analytics by reading the current ledger state, keeping the balances on disk within `dir`
+    /// instead of in memory.
+    pub(crate) fn init_on_disk<'a>(
+        dir: &Path,
+        unspent_outputs: impl IntoIterator<Item = &'a LedgerOutput>,
+    ) -> sled::Result<Self> {
+        Self::init_with_store(BalanceStore::on_disk(dir)?, unspent_outputs)
+    }
+
+    fn init_with_store<'a>(
+        balances: BalanceStore,
+        unspent_outputs: impl IntoIterator<Item = &'a LedgerOutput>,
+    ) -> sled::Result<Self> {
+        let mut analytics = Self {
+            balances,
+            distribution: Some(Vec::new()),
+            error: None,
+        };
         for output in unspent_outputs {
             if let Some(&a) = output.owning_address() {
-                *balances.entry(a).or_default() += output.amount();
+                analytics.add(a, output.amount())?;
             }
         }
-        Self { balances }
+        Ok(analytics)
     }
-}
 
-impl Analytics for AddressBalancesAnalytics {
-    type Measurement = AddressBalanceMeasurement;
+    fn add(&mut self, address: Address, amount: TokenAmount) -> sled::Result<()> {
+        let old = self.balances.get(&address)?;
+        let new = old.unwrap_or_default() + amount;
+        self.balances.insert(address, new)?;
+        self.update_distribution(old, Some(new));
+        Ok(())
+    }
 
-    fn handle_transaction(&mut self, consumed: &[LedgerSpent], created: &[LedgerOutput], _ctx: &dyn AnalyticsContext) {
+    fn subtract(&mut self, address: &Address, amount: TokenAmount) -> sled::Result<()> {
+        // All inputs should be present in `balances`. If not, we skip its value.
+        if let Some(old) = self.balances.get(address)? {
+            let mut new = old;
+            new -= amount;
+            if new.0 == 0 {
+                self.balances.remove(address)?;
+                self.update_distribution(Some(old), None);
+            } else {
+                self.balances.insert(*address, new)?;
+                self.update_distribution(Some(old), Some(new));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_transaction(&mut self, consumed: &[LedgerSpent], created: &[LedgerOutput]) -> sled::Result<()> {
         for output in consumed {
             if let Some(a) = output.output.owning_address() {
-                // All inputs should be present in `addresses`. If not, we skip it's value.
-                if let Some(amount) = self.balances.get_mut(a) {
-                    *amount -= output.amount();
-                    if amount.0 == 0 {
-                        self.balances.remove(a);
-                    }
-                }
+                self.subtract(a, output.amount())?;
             }
         }
 
         for output in created {
             if let Some(&a) = output.owning_address() {
-                // All inputs should be present in `addresses`. If not, we skip it's value.
-                *self.balances.entry(a).or_default() += output.amount();
+                self.add(a, output.amount())?;
             }
         }
+        Ok(())
+    }
+
+    fn update_distribution(&mut self, old: Option<TokenAmount>, new: Option<TokenAmount>) {
+        if let Some(distribution) = &mut self.distribution {
+            if let Some(old) = old.filter(|amount| amount.0 > 0) {
+                let stat = &mut distribution[bucket(old)];
+                stat.address_count -= 1;
+                stat.total_amount -= old;
+            }
+            if let Some(new) = new.filter(|amount| amount.0 > 0) {
+                add_to_distribution(distribution, new);
+            }
+        }
+    }
+}
+
+/// Balances are partitioned into ranges defined by: [10^index..10^(index+1)).
+fn bucket(amount: TokenAmount) -> usize {
+    amount.0.ilog10() as usize
+}
+
+fn add_to_distribution(distribution: &mut Vec<DistributionStat>, amount: TokenAmount) {
+    let index = bucket(amount);
+    if distribution.len() <= index {
+        distribution.resize(index + 1, DistributionStat::default());
+    }
+    distribution[index].address_count += 1;
+    distribution[index].total_amount += amount;
+}
+
+impl Analytics for AddressBalancesAnalytics {
+    type Measurement = AddressBalanceMeasurement;
+
+    fn handle_transaction(&mut self, consumed: &[LedgerSpent], created: &[LedgerOutput], _ctx: &dyn AnalyticsContext) {
+        if self.error.is_none() {
+            self.error = self.apply_transaction(consumed, created).err();
+        }
     }
 
     fn take_measurement(&mut self, ctx: &dyn AnalyticsContext) -> Self::Measurement {
         let bucket_max = ctx.protocol_params().token_supply.ilog10() as usize + 1;
-        let mut token_distribution = vec![DistributionStat::default(); bucket_max];
-
-        for amount in self.balances.values() {
-            // Balances are partitioned into ranges defined by: [10^index..10^(index+1)).
-            let index = amount.0.ilog10() as usize;
-            token_distribution[index].address_count += 1;
-            token_distribution[index].total_amount += *amount;
+        if self.distribution.is_none() && self.error.is_none() {
+            let mut distribution = Vec::new();
+            match self
+                .balances
+                .for_each_amount(|amount| add_to_distribution(&mut distribution, amount))
+            {
+                Ok(()) => self.distribution = Some(distribution),
+                Err(e) => self.error = Some(e),
+            }
         }
+        let mut token_distribution = self.distribution.clone().unwrap_or_default();
+        token_distribution.resize(bucket_max.max(token_distribution.len()), DistributionStat::default());
         AddressBalanceMeasurement {
             address_with_balance_count: self.balances.len(),
             token_distribution,
         }
     }
+
+    fn error(&self) -> Option<AnalyticsError> {
+        self.error.clone().map(Into::into)
+    }
 }
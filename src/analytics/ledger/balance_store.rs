@@ -0,0 +1,165 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::HashMap, path::Path};
+
+use iota_sdk::types::block::address as iota;
+use packable::PackableExt;
+use serde::{
+    de::Deserializer,
+    ser::{Error, SerializeMap, Serializer},
+    Deserialize, Serialize,
+};
+
+use crate::model::utxo::{Address, TokenAmount};
+
+/// The balance of every address, kept either in memory or, for ledgers too large for that, in a temporary database
+/// on disk.
+///
+/// Accessing the store in memory never fails.
+pub(crate) enum BalanceStore {
+    Memory(HashMap<Address, TokenAmount>),
+    Disk {
+        db: sled::Db,
+        /// The number of addresses, which the database can only count by scanning all of them.
+        len: usize,
+    },
+}
+
+impl Default for BalanceStore {
+    fn default() -> Self {
+        Self::Memory(HashMap::new())
+    }
+}
+
+impl BalanceStore {
+    /// Creates an empty store in a new directory within `dir`. The directory is removed once the store is dropped.
+    pub(crate) fn on_disk(dir: &Path) -> sled::Result<Self> {
+        let db = sled::Config::new()
+            .path(dir.join(format!("address_balances_{}", uuid::Uuid::new_v4())))
+            .temporary(true)
+            .open()?;
+        Ok(Self::Disk { db, len: 0 })
+    }
+
+    /// The number of addresses with a balance.
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Memory(balances) => balances.len(),
+            Self::Disk { len, .. } => *len,
+        }
+    }
+
+    pub(crate) fn get(&self, address: &Address) -> sled::Result<Option<TokenAmount>> {
+        Ok(match self {
+            Self::Memory(balances) => balances.get(address).copied(),
+            Self::Disk { db, .. } => db.get(key(address))?.map(|value| decode_amount(&value)),
+        })
+    }
+
+    pub(crate) fn insert(&mut self, address: Address, amount: TokenAmount) -> sled::Result<()> {
+        match self {
+            Self::Memory(balances) => {
+                balances.insert(address, amount);
+            }
+            Self::Disk { db, len } => {
+                if db.insert(key(&address), &amount.0.to_be_bytes())?.is_none() {
+                    *len += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, address: &Address) -> sled::Result<()> {
+        match self {
+            Self::Memory(balances) => {
+                balances.remove(address);
+            }
+            Self::Disk { db, len } => {
+                if db.remove(key(address))?.is_some() {
+                    *len -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with the balance of every address, in no particular order.
+    pub(crate) fn for_each_amount(&self, mut f: impl FnMut(TokenAmount)) -> sled::Result<()> {
+        match self {
+            Self::Memory(balances) => balances.values().copied().for_each(f),
+            Self::Disk { db, .. } => {
+                for value in db.iter().values() {
+                    f(decode_amount(&value?));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn key(address: &Address) -> Vec<u8> {
+    iota::Address::from(*address).pack_to_vec()
+}
+
+fn decode_amount(value: &[u8]) -> TokenAmount {
+    // Unwrap: only 8 byte values are ever written.
+    TokenAmount(u64::from_be_bytes(value.try_into().unwrap()))
+}
+
+// The store is serialized as a map regardless of where it is kept, and always deserialized into memory.
+impl Serialize for BalanceStore {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Memory(balances) => balances.serialize(serializer),
+            Self::Disk { db, len } => {
+                let mut map = serializer.serialize_map(Some(*len))?;
+                for entry in db.iter() {
+                    let (key, value) = entry.map_err(S::Error::custom)?;
+                    let address = iota::Address::unpack_unverified(&key)
+                        .map_err(|e| S::Error::custom(format!("invalid address key: {e:?}")))?;
+                    map.serialize_entry(&Address::from(address), &decode_amount(&value))?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BalanceStore {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::deserialize(deserializer).map(Self::Memory)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::model::utxo::Ed25519Address;
+
+    #[test]
+    fn disk_store() {
+        let dir = std::env::temp_dir();
+        let mut store = BalanceStore::on_disk(&dir).unwrap();
+        let a = Address::Ed25519(Ed25519Address([1; 32]));
+        let b = Address::Ed25519(Ed25519Address([2; 32]));
+
+        store.insert(a, TokenAmount(10)).unwrap();
+        store.insert(b, TokenAmount(20)).unwrap();
+        store.insert(a, TokenAmount(15)).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&a).unwrap(), Some(TokenAmount(15)));
+
+        store.remove(&b).unwrap();
+        store.remove(&b).unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&b).unwrap(), None);
+
+        let mut total = 0;
+        store.for_each_amount(|amount| total += amount.0).unwrap();
+        assert_eq!(total, 15);
+    }
+}
@@ -11,7 +11,9 @@ pub(super) use self::{
     base_token::BaseTokenActivityMeasurement,
     ledger_outputs::LedgerOutputMeasurement,
     ledger_size::{LedgerSizeAnalytics, LedgerSizeMeasurement},
+    nft_sales::{CollectionSales, NftSalesMeasurement},
     output_activity::OutputActivityMeasurement,
+    timelock_horizon::{TimelockHorizonAnalytics, TimelockHorizonMeasurement},
     transaction_size::TransactionSizeMeasurement,
     unclaimed_tokens::UnclaimedTokenMeasurement,
     unlock_conditions::UnlockConditionMeasurement,
@@ -26,10 +28,13 @@ use crate::{
 
 mod active_addresses;
 mod address_balance;
+mod balance_store;
 mod base_token;
 mod ledger_outputs;
 mod ledger_size;
+mod nft_sales;
 mod output_activity;
+mod timelock_horizon;
 mod transaction_size;
 mod unclaimed_tokens;
 mod unlock_conditions;
@@ -533,4 +538,66 @@ mod test {
         // Address 1 has delta +175, Address 2 has delta +70, Address 3 has delta -255
         assert_eq!(base_tokens_measurement.transferred_amount.0, 245)
     }
+
+    #[test]
+    fn test_timelock_horizon() {
+        const DAY: u32 = 24 * 60 * 60;
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        let milestone = MilestoneIndexTimestamp {
+            milestone_index: 1.into(),
+            milestone_timestamp: 10000.into(),
+        };
+        let locked_until = |timestamp: u32, amount: u64| {
+            let mut output = BasicOutput::rand(&protocol_params);
+            output.amount = amount.into();
+            output.timelock_unlock_condition = Some(
+                iota_sdk::types::block::output::unlock_condition::TimelockUnlockCondition::new(timestamp)
+                    .unwrap()
+                    .into(),
+            );
+            LedgerOutput {
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output: Output::Basic(output),
+                block_id: BlockId::rand(),
+                booked: milestone,
+            }
+        };
+
+        let mut timelocks = TimelockHorizonAnalytics::init(&[locked_until(10100, 10)]);
+        let ctx = TestContext {
+            at: milestone,
+            params: protocol_params.clone().into(),
+        };
+        timelocks.handle_transaction(
+            &[],
+            &[
+                locked_until(10000 + 40 * DAY, 20),
+                locked_until(5000, 40),
+                locked_until(10000 + 800 * DAY, 30),
+            ],
+            &ctx,
+        );
+        let measurement = timelocks.take_measurement(&ctx);
+        assert_eq!(
+            measurement.horizons.map(|unlocks| (unlocks.count, unlocks.amount.0)),
+            [(1, 10), (0, 0), (0, 0), (1, 20), (0, 0)]
+        );
+        assert_eq!((measurement.later.count, measurement.later.amount.0), (1, 30));
+
+        // The first output has unlocked a few minutes later.
+        let ctx = TestContext {
+            at: MilestoneIndexTimestamp {
+                milestone_index: 2.into(),
+                milestone_timestamp: 10200.into(),
+            },
+            params: protocol_params.into(),
+        };
+        let measurement = timelocks.take_measurement(&ctx);
+        assert_eq!(measurement.horizons[0].count, 0);
+        assert_eq!(measurement.horizons[3].count, 1);
+    }
 }
@@ -0,0 +1,57 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use iota_sdk::types::block::address::ToBech32Ext;
+
+use super::*;
+use crate::model::ledger::nft_sales;
+
+/// The sales of the NFTs of a single collection.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CollectionSales {
+    pub(crate) sales_count: usize,
+    pub(crate) volume: TokenAmount,
+    pub(crate) min_price: TokenAmount,
+    pub(crate) max_price: TokenAmount,
+}
+
+impl CollectionSales {
+    fn add_sale(&mut self, price: TokenAmount) {
+        if self.sales_count == 0 || price.0 < self.min_price.0 {
+            self.min_price = price;
+        }
+        if price.0 > self.max_price.0 {
+            self.max_price = price;
+        }
+        self.sales_count += 1;
+        self.volume += price;
+    }
+}
+
+/// Measures the NFT sales per collection, which is identified by the bech32 address of the NFT issuer. NFTs without an
+/// issuer do not belong to a collection and are not counted.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NftSalesMeasurement {
+    pub(crate) collections: BTreeMap<String, CollectionSales>,
+}
+
+impl Analytics for NftSalesMeasurement {
+    type Measurement = Self;
+
+    fn handle_transaction(&mut self, consumed: &[LedgerSpent], created: &[LedgerOutput], ctx: &dyn AnalyticsContext) {
+        for sale in nft_sales(consumed, created, ctx.at().milestone_timestamp) {
+            if let Some(collection) = sale.collection {
+                let collection = iota_sdk::types::block::address::Address::from(collection)
+                    .to_bech32_unchecked(&ctx.protocol_params().bech32_hrp)
+                    .to_string();
+                self.collections.entry(collection).or_default().add_sale(sale.price);
+            }
+        }
+    }
+
+    fn take_measurement(&mut self, _ctx: &dyn AnalyticsContext) -> Self::Measurement {
+        std::mem::take(self)
+    }
+}
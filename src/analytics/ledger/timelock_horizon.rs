@@ -0,0 +1,73 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::{db::mongodb::collections::TIMELOCK_HORIZONS, model::tangle::MilestoneTimestamp};
+
+/// The outputs that are still timelocked, by how soon they unlock.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct TimelockHorizonMeasurement {
+    /// The outputs that unlock within each of the [`TIMELOCK_HORIZONS`], but not within the previous one.
+    pub(crate) horizons: [CountAndAmount; TIMELOCK_HORIZONS.len()],
+    /// The outputs that unlock after the longest horizon.
+    pub(crate) later: CountAndAmount,
+}
+
+/// Tracks the unspent outputs that are timelocked by the time at which they unlock.
+///
+/// Spent outputs do not need to be tracked, because an output can only be spent once its timelock has expired.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct TimelockHorizonAnalytics {
+    unlocks: BTreeMap<MilestoneTimestamp, CountAndAmount>,
+}
+
+fn timelock(output: &Output) -> Option<MilestoneTimestamp> {
+    match output {
+        Output::Basic(basic) => basic.timelock_unlock_condition.map(|timelock| timelock.timestamp()),
+        Output::Nft(nft) => nft.timelock_unlock_condition.map(|timelock| timelock.timestamp()),
+        _ => None,
+    }
+}
+
+impl TimelockHorizonAnalytics {
+    /// Initialize the analytics by reading the current ledger state.
+    pub(crate) fn init<'a>(unspent_outputs: impl IntoIterator<Item = &'a LedgerOutput>) -> Self {
+        let mut unlocks = BTreeMap::<_, CountAndAmount>::new();
+        for output in unspent_outputs {
+            if let Some(timestamp) = timelock(&output.output) {
+                unlocks.entry(timestamp).or_default().add_output(output);
+            }
+        }
+        Self { unlocks }
+    }
+}
+
+impl Analytics for TimelockHorizonAnalytics {
+    type Measurement = TimelockHorizonMeasurement;
+
+    fn handle_transaction(&mut self, _consumed: &[LedgerSpent], created: &[LedgerOutput], ctx: &dyn AnalyticsContext) {
+        for output in created {
+            if let Some(timestamp) = timelock(&output.output).filter(|&t| t > ctx.at().milestone_timestamp) {
+                self.unlocks.entry(timestamp).or_default().add_output(output);
+            }
+        }
+    }
+
+    fn take_measurement(&mut self, ctx: &dyn AnalyticsContext) -> Self::Measurement {
+        let now = ctx.at().milestone_timestamp;
+        // Outputs that have unlocked are no longer of interest.
+        self.unlocks = self.unlocks.split_off(&MilestoneTimestamp(now.0 + 1));
+
+        let mut measurement = TimelockHorizonMeasurement::default();
+        for (timestamp, unlocks) in &self.unlocks {
+            let until = timestamp.0 - now.0;
+            match TIMELOCK_HORIZONS.iter().position(|(_, horizon)| until <= *horizon) {
+                Some(i) => measurement.horizons[i].wrapping_add(*unlocks),
+                None => measurement.later.wrapping_add(*unlocks),
+            }
+        }
+        measurement
+    }
+}
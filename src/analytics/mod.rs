@@ -3,6 +3,8 @@
 
 //! Various analytics that give insight into the usage of the tangle.
 
+use std::path::Path;
+
 use futures::TryStreamExt;
 use thiserror::Error;
 
@@ -10,8 +12,8 @@ use self::{
     influx::PrepareQuery,
     ledger::{
         AddressActivityAnalytics, AddressActivityMeasurement, AddressBalancesAnalytics, BaseTokenActivityMeasurement,
-        LedgerOutputMeasurement, LedgerSizeAnalytics, OutputActivityMeasurement, TransactionSizeMeasurement,
-        UnclaimedTokenMeasurement, UnlockConditionMeasurement,
+        LedgerOutputMeasurement, LedgerSizeAnalytics, NftSalesMeasurement, OutputActivityMeasurement,
+        TimelockHorizonAnalytics, TransactionSizeMeasurement, UnclaimedTokenMeasurement, UnlockConditionMeasurement,
     },
     tangle::{BlockActivityMeasurement, MilestoneSizeMeasurement, ProtocolParamsAnalytics},
 };
@@ -31,6 +33,8 @@ use crate::{
     tangle::{BlockData, InputSource, Milestone},
 };
 
+pub use self::tangle::{SpamDetectionAnalytics, SpamDetectionMeasurement, SpamThresholds};
+
 mod influx;
 mod ledger;
 mod tangle;
@@ -69,6 +73,11 @@ pub trait Analytics {
     fn handle_block(&mut self, _block_data: &BlockData, _ctx: &dyn AnalyticsContext) {}
     /// Take the measurement from the analytic. This should prepare the analytic for the next milestone.
     fn take_measurement(&mut self, ctx: &dyn AnalyticsContext) -> Self::Measurement;
+    /// The error that kept the analytic from handling a milestone. The handlers cannot return errors, so an analytic
+    /// whose state can fail to update reports it here, and its measurements are not written anymore.
+    fn error(&self) -> Option<AnalyticsError> {
+        None
+    }
 }
 
 // This trait allows using the above implementation dynamically
@@ -76,6 +85,7 @@ trait DynAnalytics: Send {
     fn handle_transaction(&mut self, consumed: &[LedgerSpent], created: &[LedgerOutput], ctx: &dyn AnalyticsContext);
     fn handle_block(&mut self, block_data: &BlockData, ctx: &dyn AnalyticsContext);
     fn take_measurement(&mut self, ctx: &dyn AnalyticsContext) -> Box<dyn PrepareQuery>;
+    fn error(&self) -> Option<AnalyticsError>;
 }
 
 impl<T: Analytics + Send> DynAnalytics for T
@@ -96,6 +106,10 @@ where
             inner: Analytics::take_measurement(self, ctx),
         }) as _
     }
+
+    fn error(&self) -> Option<AnalyticsError> {
+        Analytics::error(self)
+    }
 }
 
 #[async_trait::async_trait]
@@ -144,17 +158,27 @@ where
 }
 
 #[allow(missing_docs)]
-pub struct Analytic(Box<dyn DynAnalytics>);
+pub struct Analytic {
+    inner: Box<dyn DynAnalytics>,
+    sampling_interval: u32,
+    /// The number of milestones handled since the last measurement.
+    sampled_milestones: u32,
+}
 
 impl Analytic {
-    /// Init an analytic from a choice and ledger state.
+    /// Init an analytic from a choice and ledger state. If a spill directory is given, analytics whose state grows
+    /// with the ledger keep it on disk within that directory instead of in memory.
     pub fn init<'a>(
         choice: &AnalyticsChoice,
         protocol_params: &ProtocolParameters,
         unspent_outputs: impl IntoIterator<Item = &'a LedgerOutput>,
-    ) -> Self {
-        Self(match choice {
-            AnalyticsChoice::AddressBalance => Box::new(AddressBalancesAnalytics::init(unspent_outputs)) as _,
+        spill_dir: Option<&Path>,
+    ) -> Result<Self, AnalyticsError> {
+        let inner = match choice {
+            AnalyticsChoice::AddressBalance => match spill_dir {
+                Some(dir) => Box::new(AddressBalancesAnalytics::init_on_disk(dir, unspent_outputs)?) as _,
+                None => Box::new(AddressBalancesAnalytics::init(unspent_outputs)) as _,
+            },
             AnalyticsChoice::BaseTokenActivity => Box::<BaseTokenActivityMeasurement>::default() as _,
             AnalyticsChoice::BlockActivity => Box::<BlockActivityMeasurement>::default() as _,
             AnalyticsChoice::ActiveAddresses => Box::<AddressActivityAnalytics>::default() as _,
@@ -163,36 +187,70 @@ impl Analytic {
                 Box::new(LedgerSizeAnalytics::init(protocol_params.clone(), unspent_outputs)) as _
             }
             AnalyticsChoice::MilestoneSize => Box::<MilestoneSizeMeasurement>::default() as _,
+            AnalyticsChoice::NftSales => Box::<NftSalesMeasurement>::default() as _,
             AnalyticsChoice::OutputActivity => Box::<OutputActivityMeasurement>::default() as _,
             AnalyticsChoice::ProtocolParameters => Box::<ProtocolParamsAnalytics>::default() as _,
+            AnalyticsChoice::TimelockHorizon => Box::new(TimelockHorizonAnalytics::init(unspent_outputs)) as _,
             AnalyticsChoice::TransactionSizeDistribution => Box::<TransactionSizeMeasurement>::default() as _,
             AnalyticsChoice::UnclaimedTokens => Box::new(UnclaimedTokenMeasurement::init(unspent_outputs)) as _,
             AnalyticsChoice::UnlockConditions => Box::new(UnlockConditionMeasurement::init(unspent_outputs)) as _,
+        };
+        Ok(Self {
+            inner,
+            sampling_interval: 1,
+            sampled_milestones: 0,
         })
     }
+
+    /// Only measure the analytic at milestones whose index is a multiple of the interval. The milestones in between
+    /// are still handled, so activity adds up until the next measurement, which is then marked as sampled.
+    pub fn with_sampling_interval(mut self, interval: u32) -> Self {
+        self.sampling_interval = interval.max(1);
+        self
+    }
+
+    fn take_measurement(&mut self, ctx: &dyn AnalyticsContext) -> Option<Box<dyn PrepareQuery>> {
+        self.sampled_milestones += 1;
+        if self.sampling_interval == 1 {
+            self.sampled_milestones = 0;
+            return Some(self.inner.take_measurement(ctx));
+        }
+        if ctx.at().milestone_index.0 % self.sampling_interval != 0 {
+            return None;
+        }
+        Some(Box::new(Sampled {
+            interval: self.sampling_interval,
+            milestones: std::mem::take(&mut self.sampled_milestones),
+            inner: self.inner.take_measurement(ctx),
+        }))
+    }
 }
 
-impl<T: AsMut<[Analytic]>> Analytics for T {
+impl<T: AsRef<[Analytic]> + AsMut<[Analytic]>> Analytics for T {
     type Measurement = Vec<Box<dyn PrepareQuery>>;
 
     fn handle_block(&mut self, block_data: &BlockData, ctx: &dyn AnalyticsContext) {
         for analytic in self.as_mut().iter_mut() {
-            analytic.0.handle_block(block_data, ctx);
+            analytic.inner.handle_block(block_data, ctx);
         }
     }
 
     fn handle_transaction(&mut self, consumed: &[LedgerSpent], created: &[LedgerOutput], ctx: &dyn AnalyticsContext) {
         for analytic in self.as_mut().iter_mut() {
-            analytic.0.handle_transaction(consumed, created, ctx);
+            analytic.inner.handle_transaction(consumed, created, ctx);
         }
     }
 
     fn take_measurement(&mut self, ctx: &dyn AnalyticsContext) -> Self::Measurement {
         self.as_mut()
             .iter_mut()
-            .map(|analytic| analytic.0.take_measurement(ctx))
+            .filter_map(|analytic| analytic.take_measurement(ctx))
             .collect()
     }
+
+    fn error(&self) -> Option<AnalyticsError> {
+        self.as_ref().iter().find_map(|analytic| analytic.inner.error())
+    }
 }
 
 #[allow(missing_docs)]
@@ -220,6 +278,8 @@ pub enum AnalyticsError {
         output_id: String,
         milestone_index: MilestoneIndex,
     },
+    #[error("failed to keep the analytics state on disk: {0}")]
+    Spill(#[from] sled::Error),
 }
 
 impl<'a, I: InputSource> Milestone<'a, I> {
@@ -232,16 +292,53 @@ impl<'a, I: InputSource> Milestone<'a, I> {
     where
         PerMilestone<A::Measurement>: 'static + PrepareQuery,
     {
-        let mut cone_stream = self.cone_stream().await?;
+        self.handle_cone(analytics).await?;
 
-        while let Some(block_data) = cone_stream.try_next().await? {
-            self.handle_block(analytics, &block_data)?;
+        let measurement = (analytics as &mut dyn DynAnalytics).take_measurement(self);
+        if let Some(e) = Analytics::error(analytics) {
+            return Err(e.into());
         }
+        influxdb.insert_measurement(measurement).await?;
+
+        Ok(())
+    }
+
+    /// Check this milestone for spam, and write the measurement to InfluxDb. The measurement is returned, so that
+    /// flagged milestones can be stored as well.
+    pub async fn detect_spam(
+        &self,
+        analytics: &mut SpamDetectionAnalytics,
+        influxdb: &InfluxDb,
+    ) -> eyre::Result<SpamDetectionMeasurement> {
+        self.handle_cone(analytics).await?;
+        let measurement = Analytics::take_measurement(analytics, self);
 
         influxdb
-            .insert_measurement((analytics as &mut dyn DynAnalytics).take_measurement(self))
+            .insert_measurement(PerMilestone {
+                at: self.at,
+                inner: measurement.clone(),
+            })
             .await?;
 
+        Ok(measurement)
+    }
+
+    /// Feed this milestone to the spam detection without writing its measurement, so that the milestones after it are
+    /// checked against the right baseline.
+    pub async fn replay_spam_detection(&self, analytics: &mut SpamDetectionAnalytics) -> eyre::Result<()> {
+        self.handle_cone(analytics).await?;
+        Analytics::take_measurement(analytics, self);
+
+        Ok(())
+    }
+
+    async fn handle_cone<A: Analytics + Send>(&self, analytics: &mut A) -> eyre::Result<()> {
+        let mut cone_stream = self.cone_stream().await?;
+
+        while let Some(block_data) = cone_stream.try_next().await? {
+            self.handle_block(analytics, &block_data)?;
+        }
+
         Ok(())
     }
 
@@ -357,6 +454,16 @@ pub struct PerMilestone<M> {
     inner: M,
 }
 
+/// A measurement that is only taken every few milestones.
+struct Sampled {
+    /// The number of milestones between measurements.
+    interval: u32,
+    /// The number of milestones handled since the previous measurement, which is less than the interval right after
+    /// the analytic was initialized.
+    milestones: u32,
+    inner: Box<dyn PrepareQuery>,
+}
+
 #[derive(Clone, Debug)]
 #[allow(missing_docs)]
 struct PerInterval<M> {
@@ -379,6 +486,7 @@ mod test {
     use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
     use super::{
+        influx::PrepareQuery,
         ledger::{
             AddressActivityAnalytics, AddressActivityMeasurement, AddressBalanceMeasurement,
             BaseTokenActivityMeasurement, LedgerSizeMeasurement, OutputActivityMeasurement, TransactionSizeMeasurement,
@@ -529,6 +637,51 @@ mod test {
         }
     }
 
+    #[test]
+    fn sampled_analytics_are_marked() {
+        use influxdb::Query;
+
+        use crate::{analytics::Analytic, db::influxdb::AnalyticsChoice};
+
+        let params: ProtocolParameters = iota_sdk::types::block::protocol::protocol_parameters().into();
+        let mut analytics = [
+            Analytic::init(&AnalyticsChoice::BlockActivity, &params, [], None).unwrap(),
+            Analytic::init(&AnalyticsChoice::MilestoneSize, &params, [], None)
+                .unwrap()
+                .with_sampling_interval(3),
+        ];
+        let mut lines = Vec::new();
+        for index in 2..=7 {
+            let ctx = TestContext {
+                at: MilestoneIndexTimestamp {
+                    milestone_index: index.into(),
+                    milestone_timestamp: (index * 10).into(),
+                },
+                params: params.clone(),
+            };
+            lines.extend(
+                analytics
+                    .take_measurement(&ctx)
+                    .iter()
+                    .flat_map(|measurement| measurement.prepare_query())
+                    .map(|query| query.build().unwrap().get()),
+            );
+        }
+
+        let sampled = lines
+            .iter()
+            .filter(|line| line.starts_with("stardust_milestone_size"))
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len() - sampled.len(), 6);
+        assert_eq!(sampled.len(), 2);
+        // The first point only covers the milestones since the analytic was initialized.
+        assert!(sampled[0].contains("milestone_index=3i,"));
+        assert!(sampled[0].contains("sampling_interval=3i,sampled_milestones=2i"));
+        assert!(sampled[1].contains("milestone_index=6i,"));
+        assert!(sampled[1].contains("sampling_interval=3i,sampled_milestones=3i"));
+        assert!(!lines[0].contains("sampling_interval"));
+    }
+
     #[tokio::test]
     async fn test_in_memory_analytics() {
         let analytics_map = gather_in_memory_analytics().await.unwrap();
@@ -3,6 +3,7 @@
 
 //! Statistics about the tangle.
 
+pub use self::spam_detection::{SpamDetectionAnalytics, SpamDetectionMeasurement, SpamThresholds};
 pub(crate) use self::{
     block_activity::BlockActivityMeasurement, milestone_size::MilestoneSizeMeasurement,
     protocol_params::ProtocolParamsAnalytics,
@@ -16,17 +17,21 @@ use crate::{
 mod block_activity;
 mod milestone_size;
 mod protocol_params;
+mod spam_detection;
 
 #[cfg(test)]
 mod test {
     use pretty_assertions::assert_eq;
 
-    use super::BlockActivityMeasurement;
+    use super::{BlockActivityMeasurement, SpamDetectionAnalytics, SpamThresholds};
     use crate::{
         analytics::{tangle::MilestoneSizeMeasurement, test::TestContext, Analytics},
         model::{
-            metadata::{BlockMetadata, ConflictReason, LedgerInclusionState},
+            ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
+            metadata::{BlockMetadata, ConflictReason, LedgerInclusionState, SpentMetadata},
+            payload::TransactionId,
             tangle::MilestoneIndex,
+            utxo::{Address, BasicOutput, Output, OutputId},
             Block, BlockId,
         },
         tangle::BlockData,
@@ -104,4 +109,131 @@ mod test {
         assert_eq!(milestone_size_measurement.total_tagged_data_payload_bytes, 400);
         assert_eq!(milestone_size_measurement.total_milestone_bytes, 1500);
     }
+
+    #[test]
+    fn test_spam_detection() {
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        let ctx = TestContext {
+            at: MilestoneIndex(1).with_timestamp(12345.into()),
+            params: protocol_params.into(),
+        };
+        let block_data = |block: Block| {
+            let parents = block.parents.clone();
+            BlockData {
+                block_id: BlockId::rand(),
+                block,
+                raw: Vec::new(),
+                metadata: BlockMetadata {
+                    parents,
+                    is_solid: true,
+                    should_promote: false,
+                    should_reattach: false,
+                    referenced_by_milestone_index: 1.into(),
+                    milestone_index: 0.into(),
+                    inclusion_state: LedgerInclusionState::NoTransaction,
+                    conflict_reason: ConflictReason::None,
+                    white_flag_index: 0,
+                },
+            }
+        };
+
+        let mut spam_detection = SpamDetectionAnalytics::new(SpamThresholds {
+            window: 2,
+            block_spike_factor: 3.0,
+            duplicate_payload_ratio: 0.5,
+            issuer_dominance_ratio: 0.8,
+            min_block_count: 4,
+            min_transaction_count: 1,
+        });
+
+        // The window is not filled yet, so no spike can be flagged.
+        for _ in 0..5 {
+            spam_detection.handle_block(&block_data(Block::rand_tagged_data()), &ctx);
+        }
+        let measurement = spam_detection.take_measurement(&ctx);
+        assert_eq!(measurement.block_count, 5);
+        assert_eq!(measurement.baseline_block_count, None);
+        assert_eq!(measurement.duplicate_payload_count, 1);
+        assert!(!measurement.flags.any());
+
+        spam_detection.handle_block(&block_data(Block::rand_no_payload()), &ctx);
+        assert!(!spam_detection.take_measurement(&ctx).flags.any());
+
+        let spam = Block::rand_tagged_data();
+        for _ in 0..8 {
+            spam_detection.handle_block(&block_data(Block::rand_no_payload()), &ctx);
+        }
+        for _ in 0..10 {
+            spam_detection.handle_block(&block_data(spam.clone()), &ctx);
+        }
+        let measurement = spam_detection.take_measurement(&ctx);
+        assert_eq!(measurement.block_count, 18);
+        assert_eq!(measurement.baseline_block_count, Some(3.0));
+        assert_eq!(measurement.duplicate_payload_count, 10);
+        assert!(measurement.flags.block_spike);
+        assert!(measurement.flags.duplicate_payloads);
+        assert!(!measurement.flags.issuer_dominance);
+    }
+
+    #[test]
+    fn test_spam_detection_issuer_dominance() {
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        let ctx = TestContext {
+            at: MilestoneIndex(1).with_timestamp(12345.into()),
+            params: protocol_params.clone().into(),
+        };
+        // Spends an output of the given address.
+        let spent_from = |address: Address| {
+            let mut output = BasicOutput::rand(&protocol_params);
+            output.address_unlock_condition.address = address;
+            output.timelock_unlock_condition = None;
+            output.expiration_unlock_condition = None;
+            LedgerSpent {
+                output: LedgerOutput {
+                    output_id: OutputId::rand(),
+                    rent_structure: RentStructureBytes {
+                        num_key_bytes: 0,
+                        num_data_bytes: 100,
+                    },
+                    output: Output::Basic(output),
+                    block_id: BlockId::rand(),
+                    booked: MilestoneIndex(0).with_timestamp(10000.into()),
+                },
+                spent_metadata: SpentMetadata {
+                    transaction_id: TransactionId::rand(),
+                    spent: ctx.at,
+                },
+            }
+        };
+
+        let mut spam_detection = SpamDetectionAnalytics::new(SpamThresholds {
+            window: 2,
+            block_spike_factor: 3.0,
+            duplicate_payload_ratio: 0.5,
+            issuer_dominance_ratio: 0.8,
+            min_block_count: 4,
+            min_transaction_count: 5,
+        });
+
+        let issuer = Address::rand_ed25519();
+        for _ in 0..4 {
+            spam_detection.handle_transaction(&[spent_from(issuer)], &[], &ctx);
+        }
+        // Below the minimum number of transactions, the issuer is not flagged.
+        let measurement = spam_detection.take_measurement(&ctx);
+        assert_eq!(measurement.top_issuer, Some(issuer));
+        assert!(!measurement.flags.issuer_dominance);
+
+        for _ in 0..4 {
+            spam_detection.handle_transaction(&[spent_from(issuer)], &[], &ctx);
+        }
+        spam_detection.handle_transaction(&[spent_from(Address::rand_ed25519())], &[], &ctx);
+        let measurement = spam_detection.take_measurement(&ctx);
+        assert_eq!(measurement.transaction_count, 5);
+        assert_eq!(measurement.top_issuer, Some(issuer));
+        assert_eq!(measurement.top_issuer_transaction_count, 4);
+        assert!(measurement.flags.issuer_dominance);
+        assert!(!measurement.flags.block_spike);
+        assert!(!measurement.flags.duplicate_payloads);
+    }
 }
@@ -0,0 +1,181 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use super::*;
+use crate::{
+    db::mongodb::collections::{SpamFlagDocument, SpamFlags},
+    model::{
+        ledger::{LedgerOutput, LedgerSpent},
+        payload::TransactionId,
+        tangle::MilestoneIndexTimestamp,
+        utxo::Address,
+    },
+};
+
+/// The thresholds above which a milestone is flagged as spam.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpamThresholds {
+    /// The number of previous milestones whose average block count is the baseline of a spike.
+    pub window: usize,
+    /// How many times the baseline the block count of a milestone has to be to count as a spike.
+    pub block_spike_factor: f64,
+    /// The share of blocks that have to carry the same payload.
+    pub duplicate_payload_ratio: f64,
+    /// The share of transactions that have to be issued by the same address.
+    pub issuer_dominance_ratio: f64,
+    /// The number of blocks below which a milestone is never flagged for its blocks.
+    pub min_block_count: usize,
+    /// The number of transactions below which a milestone is never flagged for its issuers.
+    pub min_transaction_count: usize,
+}
+
+/// Identifies a payload, so that blocks carrying the same one can be counted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum PayloadKey {
+    TaggedData(u64),
+    Transaction(TransactionId),
+}
+
+/// The statistics of a milestone, and the patterns it was flagged for.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpamDetectionMeasurement {
+    pub flags: SpamFlags,
+    pub block_count: usize,
+    /// The average number of blocks of the previous milestones, once the window is filled.
+    pub baseline_block_count: Option<f64>,
+    /// The number of blocks that carried the most common payload.
+    pub duplicate_payload_count: usize,
+    pub transaction_count: usize,
+    /// The address that issued the most transactions.
+    pub top_issuer: Option<Address>,
+    pub top_issuer_transaction_count: usize,
+}
+
+impl SpamDetectionMeasurement {
+    /// Converts the measurement of a flagged milestone into the document it is stored as.
+    pub fn to_document(&self, at: MilestoneIndexTimestamp) -> SpamFlagDocument {
+        SpamFlagDocument {
+            milestone_index: at.milestone_index,
+            milestone_timestamp: at.milestone_timestamp,
+            flags: self.flags,
+            block_count: self.block_count as u64,
+            baseline_block_count: self.baseline_block_count,
+            duplicate_payload_count: self.duplicate_payload_count as u64,
+            transaction_count: self.transaction_count as u64,
+            top_issuer: self.top_issuer,
+            top_issuer_transaction_count: self.top_issuer_transaction_count as u64,
+        }
+    }
+}
+
+/// Flags milestones with anomalous patterns: a spike in the number of blocks, many blocks carrying the same payload,
+/// or most transactions being issued by a single address.
+///
+/// Stardust blocks do not name their issuer, so the issuer of a transaction is the address that owned its first input.
+#[derive(Clone, Debug)]
+pub struct SpamDetectionAnalytics {
+    thresholds: SpamThresholds,
+    /// The block counts of the previous milestones, up to the window.
+    history: VecDeque<usize>,
+    block_count: usize,
+    payloads: HashMap<PayloadKey, usize>,
+    issuers: HashMap<Address, usize>,
+    transaction_count: usize,
+}
+
+impl SpamDetectionAnalytics {
+    /// Creates the analytic. Spikes are only flagged once the window of previous milestones is filled.
+    pub fn new(thresholds: SpamThresholds) -> Self {
+        Self {
+            thresholds,
+            history: VecDeque::with_capacity(thresholds.window),
+            block_count: 0,
+            payloads: Default::default(),
+            issuers: Default::default(),
+            transaction_count: 0,
+        }
+    }
+
+    fn baseline_block_count(&self) -> Option<f64> {
+        (self.thresholds.window > 0 && self.history.len() == self.thresholds.window)
+            .then(|| self.history.iter().sum::<usize>() as f64 / self.history.len() as f64)
+    }
+}
+
+impl Analytics for SpamDetectionAnalytics {
+    type Measurement = SpamDetectionMeasurement;
+
+    fn handle_transaction(&mut self, consumed: &[LedgerSpent], _created: &[LedgerOutput], _ctx: &dyn AnalyticsContext) {
+        self.transaction_count += 1;
+        if let Some(&address) = consumed.first().and_then(|spent| spent.output.owning_address()) {
+            *self.issuers.entry(address).or_default() += 1;
+        }
+    }
+
+    fn handle_block(&mut self, BlockData { block, .. }: &BlockData, _ctx: &dyn AnalyticsContext) {
+        self.block_count += 1;
+        let key = match &block.payload {
+            Some(Payload::TaggedData(payload)) => {
+                let mut hasher = DefaultHasher::new();
+                payload.tag.hash(&mut hasher);
+                payload.data.hash(&mut hasher);
+                PayloadKey::TaggedData(hasher.finish())
+            }
+            Some(Payload::Transaction(payload)) => PayloadKey::Transaction(payload.transaction_id),
+            _ => return,
+        };
+        *self.payloads.entry(key).or_default() += 1;
+    }
+
+    fn take_measurement(&mut self, _ctx: &dyn AnalyticsContext) -> Self::Measurement {
+        let thresholds = self.thresholds;
+        let baseline_block_count = self.baseline_block_count();
+        let duplicate_payload_count = self.payloads.values().copied().max().unwrap_or_default();
+        let (top_issuer, top_issuer_transaction_count) = self
+            .issuers
+            .iter()
+            .max_by_key(|(_, &count)| count)
+            .map_or((None, 0), |(&address, &count)| (Some(address), count));
+
+        let enough_blocks = self.block_count >= thresholds.min_block_count;
+        let flags = SpamFlags {
+            block_spike: enough_blocks
+                && baseline_block_count.map_or(false, |baseline| {
+                    self.block_count as f64 > baseline * thresholds.block_spike_factor
+                }),
+            duplicate_payloads: enough_blocks
+                && duplicate_payload_count > 1
+                && duplicate_payload_count as f64 >= self.block_count as f64 * thresholds.duplicate_payload_ratio,
+            issuer_dominance: self.transaction_count >= thresholds.min_transaction_count
+                && top_issuer_transaction_count as f64
+                    >= self.transaction_count as f64 * thresholds.issuer_dominance_ratio,
+        };
+        let measurement = SpamDetectionMeasurement {
+            flags,
+            block_count: self.block_count,
+            baseline_block_count,
+            duplicate_payload_count,
+            transaction_count: self.transaction_count,
+            top_issuer,
+            top_issuer_transaction_count,
+        };
+
+        if thresholds.window > 0 {
+            if self.history.len() == thresholds.window {
+                self.history.pop_front();
+            }
+            self.history.push_back(self.block_count);
+        }
+        self.block_count = 0;
+        self.payloads.clear();
+        self.issuers.clear();
+        self.transaction_count = 0;
+        measurement
+    }
+}
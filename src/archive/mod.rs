@@ -0,0 +1,354 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A cold archive that keeps the complete data of milestones available after it is pruned from the database.
+//!
+//! Every milestone is stored as a zlib-compressed BSON document, either in a directory or in an S3-compatible bucket,
+//! along with a manifest of the range of archived milestones. The archive implements
+//! [`InputSource`](crate::tangle::InputSource), so that archived milestones can be re-ingested or replayed through the
+//! analytics.
+
+mod s3;
+mod store;
+
+use std::sync::{Arc, Mutex};
+
+use futures::TryStreamExt;
+use packable::PackableExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub use self::{
+    s3::{S3Bucket, S3Config},
+    store::ArchiveLocation,
+};
+use crate::{
+    model::{
+        ledger::{LedgerOutput, LedgerSpent},
+        metadata::BlockMetadata,
+        node::NodeConfiguration,
+        payload::{MilestoneId, MilestonePayload},
+        protocol::ProtocolParameters,
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+        BlockId,
+    },
+    tangle::{BlockData, InputSource, MilestoneData},
+};
+
+/// The key of the manifest within the archive.
+const MANIFEST_KEY: &str = "manifest.json";
+
+#[allow(missing_docs)]
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("failed to access the archive: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid S3 endpoint: {0}")]
+    InvalidEndpoint(#[from] url::ParseError),
+    #[error("S3 request failed: {0}")]
+    S3(#[from] reqwest::Error),
+    #[error("S3 request for `{key}` failed with status {status}")]
+    S3Status { key: String, status: reqwest::StatusCode },
+    #[error("invalid archive manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+    #[error("failed to encode milestone {0}: {1}")]
+    Encode(MilestoneIndex, String),
+    #[error("corrupt archive entry `{0}`: {1}")]
+    Corrupt(String, String),
+    #[error("milestone {0} is not in the archive")]
+    MissingMilestone(MilestoneIndex),
+    #[error("milestone {index} cannot be archived after milestone {last}, as the archive must be contiguous")]
+    NotContiguous { index: MilestoneIndex, last: MilestoneIndex },
+}
+
+/// The range of milestones in the archive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// The oldest archived milestone.
+    pub first: MilestoneIndex,
+    /// The newest archived milestone. All milestones from the oldest to this one are archived.
+    pub last: MilestoneIndex,
+}
+
+/// A block as it is stored in the archive. The block itself is only stored as raw bytes, from which it is restored.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedBlock {
+    pub block_id: BlockId,
+    #[serde(with = "serde_bytes")]
+    pub raw: Vec<u8>,
+    pub metadata: BlockMetadata,
+}
+
+impl ArchivedBlock {
+    fn into_block_data(self) -> Result<BlockData, ArchiveError> {
+        let block = iota_sdk::types::block::Block::unpack_unverified(&self.raw)
+            .map_err(|e| ArchiveError::Corrupt(self.block_id.to_hex(), format!("{e:?}")))?;
+        Ok(BlockData {
+            block_id: self.block_id,
+            block: block.into(),
+            raw: self.raw,
+            metadata: self.metadata,
+        })
+    }
+}
+
+/// The complete data of a milestone, as it is stored in the archive.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedMilestone {
+    pub milestone_id: MilestoneId,
+    pub at: MilestoneIndexTimestamp,
+    pub payload: MilestonePayload,
+    pub protocol_params: ProtocolParameters,
+    pub node_config: NodeConfiguration,
+    /// The blocks referenced by the milestone, in white-flag order.
+    pub cone: Vec<ArchivedBlock>,
+    pub consumed: Vec<LedgerSpent>,
+    pub created: Vec<LedgerOutput>,
+}
+
+impl ArchivedMilestone {
+    /// Collects the cone and the ledger updates of a milestone from a source.
+    pub async fn collect<I: InputSource>(source: &I, milestone: MilestoneData) -> Result<Self, I::Error> {
+        let index = milestone.at.milestone_index;
+        let cone = source
+            .cone_stream(index)
+            .await?
+            .map_ok(|block| ArchivedBlock {
+                block_id: block.block_id,
+                raw: block.raw,
+                metadata: block.metadata,
+            })
+            .try_collect()
+            .await?;
+        let ledger_updates = source.ledger_updates(index).await?;
+        Ok(Self {
+            milestone_id: milestone.milestone_id,
+            at: milestone.at,
+            payload: milestone.payload,
+            protocol_params: milestone.protocol_params,
+            node_config: milestone.node_config,
+            cone,
+            consumed: ledger_updates.consumed_outputs().to_vec(),
+            created: ledger_updates.created_outputs().to_vec(),
+        })
+    }
+
+    /// The milestone without its cone and ledger updates.
+    pub fn milestone_data(&self) -> MilestoneData {
+        MilestoneData {
+            milestone_id: self.milestone_id,
+            at: self.at,
+            payload: self.payload.clone(),
+            protocol_params: self.protocol_params.clone(),
+            node_config: self.node_config.clone(),
+        }
+    }
+
+    /// Restores the blocks of the cone, in white-flag order.
+    pub fn cone_blocks(&self) -> Result<Vec<BlockData>, ArchiveError> {
+        self.cone.iter().cloned().map(ArchivedBlock::into_block_data).collect()
+    }
+}
+
+fn entry_key(index: MilestoneIndex) -> String {
+    // Padded, so that the entries are listed in order.
+    format!("milestones/{:010}.bson.zz", index.0)
+}
+
+fn encode(milestone: &ArchivedMilestone) -> Result<Vec<u8>, ArchiveError> {
+    let index = milestone.at.milestone_index;
+    let bytes = mongodb::bson::to_vec(milestone).map_err(|e| ArchiveError::Encode(index, e.to_string()))?;
+    yazi::compress(&bytes, yazi::Format::Zlib, yazi::CompressionLevel::Default)
+        .map_err(|e| ArchiveError::Encode(index, format!("{e:?}")))
+}
+
+fn decode(key: &str, bytes: &[u8]) -> Result<ArchivedMilestone, ArchiveError> {
+    let (bytes, _) = yazi::decompress(bytes, yazi::Format::Zlib)
+        .map_err(|e| ArchiveError::Corrupt(key.to_string(), format!("{e:?}")))?;
+    mongodb::bson::from_slice(&bytes).map_err(|e| ArchiveError::Corrupt(key.to_string(), e.to_string()))
+}
+
+/// An archive of milestones.
+#[derive(Clone, Debug)]
+pub struct MilestoneArchive {
+    location: ArchiveLocation,
+    /// The milestone that was read last. Its cone and its ledger updates are usually read right after it, so this
+    /// saves fetching and decoding the entry again.
+    last_read: Arc<Mutex<Option<Arc<ArchivedMilestone>>>>,
+}
+
+impl MilestoneArchive {
+    /// Opens the archive at the given location. Nothing is read until it is used.
+    pub fn new(location: ArchiveLocation) -> Self {
+        Self {
+            location,
+            last_read: Default::default(),
+        }
+    }
+
+    /// Reads the range of archived milestones, if any were archived.
+    pub async fn manifest(&self) -> Result<Option<ArchiveManifest>, ArchiveError> {
+        Ok(self
+            .location
+            .get(MANIFEST_KEY)
+            .await?
+            .map(|bytes| serde_json::from_slice(&bytes))
+            .transpose()?)
+    }
+
+    /// Reads an archived milestone.
+    pub async fn read(&self, index: MilestoneIndex) -> Result<Arc<ArchivedMilestone>, ArchiveError> {
+        if let Some(milestone) = self.last_read.lock().unwrap().as_ref() {
+            if milestone.at.milestone_index == index {
+                return Ok(milestone.clone());
+            }
+        }
+        let key = entry_key(index);
+        let bytes = self
+            .location
+            .get(&key)
+            .await?
+            .ok_or(ArchiveError::MissingMilestone(index))?;
+        let milestone = Arc::new(decode(&key, &bytes)?);
+        self.last_read.lock().unwrap().replace(milestone.clone());
+        Ok(milestone)
+    }
+
+    /// Writes a milestone to the archive, which must be the one after the newest archived milestone, unless the
+    /// archive is empty. Milestones that are already archived are skipped. Returns the updated manifest.
+    pub async fn write(&self, milestone: &ArchivedMilestone) -> Result<ArchiveManifest, ArchiveError> {
+        let index = milestone.at.milestone_index;
+        let manifest = match self.manifest().await? {
+            Some(manifest) if (manifest.first..=manifest.last).contains(&index) => return Ok(manifest),
+            Some(manifest) if index == manifest.last + 1 => ArchiveManifest { last: index, ..manifest },
+            Some(manifest) => {
+                return Err(ArchiveError::NotContiguous {
+                    index,
+                    last: manifest.last,
+                });
+            }
+            None => ArchiveManifest {
+                first: index,
+                last: index,
+            },
+        };
+        // The entry goes first, so that the manifest never lists a milestone that is not archived.
+        self.location.put(&entry_key(index), encode(milestone)?).await?;
+        self.location
+            .put(MANIFEST_KEY, serde_json::to_vec_pretty(&manifest)?)
+            .await?;
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, fs::File, io::BufReader};
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct MilestoneFixture {
+        milestone_id: MilestoneId,
+        at: MilestoneIndexTimestamp,
+        payload: MilestonePayload,
+        protocol_params: ProtocolParameters,
+        node_config: NodeConfiguration,
+    }
+
+    #[derive(Deserialize)]
+    struct Fixture {
+        milestone_data: MilestoneFixture,
+        cone: BTreeMap<String, ArchivedBlock>,
+        created: Vec<LedgerOutput>,
+        consumed: Vec<LedgerSpent>,
+    }
+
+    fn milestones() -> Vec<ArchivedMilestone> {
+        let file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/in_memory_data.json")).unwrap();
+        let data: mongodb::bson::Bson = serde_json::from_reader(BufReader::new(file)).unwrap();
+        mongodb::bson::from_bson::<BTreeMap<String, Fixture>>(data)
+            .unwrap()
+            .into_values()
+            .map(|fixture| ArchivedMilestone {
+                milestone_id: fixture.milestone_data.milestone_id,
+                at: fixture.milestone_data.at,
+                payload: fixture.milestone_data.payload,
+                protocol_params: fixture.milestone_data.protocol_params,
+                node_config: fixture.milestone_data.node_config,
+                cone: fixture.cone.into_values().collect(),
+                consumed: fixture.consumed,
+                created: fixture.created,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn directory_archive() {
+        let dir = std::env::temp_dir().join(format!("chronicle_archive_{}", uuid::Uuid::new_v4()));
+        let archive = MilestoneArchive::new(ArchiveLocation::Directory(dir.clone()));
+        let mut milestones = milestones();
+        milestones.sort_by_key(|milestone| milestone.at.milestone_index);
+        let (first, second) = (&milestones[0], &milestones[1]);
+        assert_eq!(archive.manifest().await.unwrap(), None);
+
+        archive.write(first).await.unwrap();
+        assert!(matches!(
+            archive.write(&milestones[2]).await,
+            Err(ArchiveError::NotContiguous { .. })
+        ));
+        let manifest = archive.write(second).await.unwrap();
+        assert_eq!(
+            manifest,
+            ArchiveManifest {
+                first: first.at.milestone_index,
+                last: second.at.milestone_index,
+            }
+        );
+        assert_eq!(archive.write(first).await.unwrap(), manifest);
+
+        // A new handle, so that nothing is served from memory.
+        let archive = MilestoneArchive::new(ArchiveLocation::Directory(dir.clone()));
+        assert_eq!(archive.manifest().await.unwrap(), Some(manifest));
+        assert_eq!(&*archive.read(first.at.milestone_index).await.unwrap(), first);
+        assert_eq!(&*archive.read(second.at.milestone_index).await.unwrap(), second);
+        assert_eq!(
+            archive.read(second.at.milestone_index).await.unwrap().cone_blocks().unwrap().len(),
+            second.cone.len()
+        );
+        assert!(matches!(
+            archive.read(second.at.milestone_index + 1).await,
+            Err(ArchiveError::MissingMilestone(_))
+        ));
+
+        let stream_indexes = |range: std::ops::Range<MilestoneIndex>| {
+            let archive = &archive;
+            async move {
+                archive
+                    .milestone_stream(range)
+                    .await
+                    .unwrap()
+                    .map_ok(|data| data.at.milestone_index)
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(
+            stream_indexes(first.at.milestone_index..second.at.milestone_index + 1).await,
+            vec![first.at.milestone_index, second.at.milestone_index]
+        );
+        assert_eq!(
+            stream_indexes(first.at.milestone_index..second.at.milestone_index).await,
+            vec![first.at.milestone_index]
+        );
+        // An empty range must not read milestone 0, which is not archived.
+        assert!(stream_indexes(MilestoneIndex(0)..MilestoneIndex(0)).await.is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
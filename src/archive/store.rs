@@ -0,0 +1,67 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{io::ErrorKind, path::PathBuf};
+
+use super::{
+    s3::{S3Bucket, S3Config},
+    ArchiveError,
+};
+
+/// Where the archive is kept.
+#[derive(Clone, Debug)]
+pub enum ArchiveLocation {
+    /// A local directory.
+    Directory(PathBuf),
+    /// An S3-compatible bucket.
+    S3(S3Bucket),
+}
+
+impl ArchiveLocation {
+    /// Creates the location of an archive in an S3-compatible bucket.
+    pub fn s3(config: S3Config) -> Result<Self, ArchiveError> {
+        Ok(Self::S3(S3Bucket::new(config)?))
+    }
+
+    pub(super) async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), ArchiveError> {
+        match self {
+            Self::Directory(dir) => {
+                let path = dir.join(key);
+                blocking(move || {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    // Written to a temporary file first, so that an interrupted write never leaves a partial entry.
+                    let tmp = path.with_extension("tmp");
+                    std::fs::write(&tmp, bytes)?;
+                    std::fs::rename(tmp, path)
+                })
+                .await
+            }
+            Self::S3(bucket) => bucket.put(key, bytes).await,
+        }
+    }
+
+    pub(super) async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        match self {
+            Self::Directory(dir) => {
+                let path = dir.join(key);
+                blocking(move || match std::fs::read(path) {
+                    Ok(bytes) => Ok(Some(bytes)),
+                    Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(e),
+                })
+                .await
+            }
+            Self::S3(bucket) => bucket.get(key).await,
+        }
+    }
+}
+
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> std::io::Result<T> + Send + 'static,
+) -> Result<T, ArchiveError> {
+    Ok(tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| std::io::Error::new(ErrorKind::Other, e))??)
+}
@@ -0,0 +1,33 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_ENABLED: bool = false;
+/// A day of milestones at the usual rate of one milestone every ten seconds.
+pub const DEFAULT_INTERVAL: u32 = 8640;
+pub const DEFAULT_POLL_INTERVAL: &str = "1m";
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// Configuration of the address filter worker.
+#[derive(Clone, Debug)]
+pub struct AddressFilterConfig {
+    pub enabled: bool,
+    /// The number of milestones after which the filter is built again.
+    pub interval: u32,
+    /// How often to check whether the filter is due to be built again.
+    pub poll_interval: Duration,
+    /// The share of unknown addresses that the filter reports as known.
+    pub false_positive_rate: f64,
+}
+
+impl Default for AddressFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            interval: DEFAULT_INTERVAL,
+            poll_interval: DEFAULT_POLL_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+            false_positive_rate: DEFAULT_FALSE_POSITIVE_RATE,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::f64::consts::LN_2;
+
+use chronicle::model::{tangle::MilestoneIndex, utxo::Address};
+use crypto::hashes::{blake2b::Blake2b256, Digest};
+use packable::PackableExt;
+
+/// The version of the serialized format.
+const VERSION: u8 = 1;
+/// The most hash functions a filter uses, which are only needed for false positive rates below one in a billion.
+const MAX_HASH_COUNT: u8 = 32;
+
+/// A bloom filter of the addresses known to the archive, which lets light clients check whether an address is worth
+/// querying for.
+///
+/// The serialized filter consists of:
+/// - the version of the format, `1` (u8),
+/// - the number of hash functions `k` (u8),
+/// - the milestone index the filter was built at (u32, little endian),
+/// - the number of addresses in the filter (u64, little endian),
+/// - the `m` bits of the filter, where bit `i` is bit `i % 8` of byte `i / 8`.
+///
+/// An address is hashed by taking the BLAKE2b-256 digest of its packed bytes, the address kind followed by its 32
+/// bytes, and reading the first two little-endian u64 of the digest as `h1` and `h2`. The address is unknown unless the
+/// bits `(h1 + i * h2) mod m` are set for every `i` in `0..k`, where the sum and product wrap around at 64 bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressFilter {
+    milestone_index: MilestoneIndex,
+    address_count: u64,
+    hash_count: u8,
+    bits: Vec<u8>,
+}
+
+impl AddressFilter {
+    /// Creates an empty filter that holds the given number of addresses at the given false positive rate.
+    pub fn new(milestone_index: MilestoneIndex, expected_addresses: u64, false_positive_rate: f64) -> Self {
+        let expected_addresses = expected_addresses.max(1) as f64;
+        let bit_count = (-expected_addresses * false_positive_rate.ln() / (LN_2 * LN_2)).ceil();
+        let len = ((bit_count / 8.0).ceil() as usize).max(1);
+        let hash_count = ((len * 8) as f64 / expected_addresses * LN_2)
+            .round()
+            .clamp(1.0, MAX_HASH_COUNT as f64) as u8;
+        Self {
+            milestone_index,
+            address_count: 0,
+            hash_count,
+            bits: vec![0; len],
+        }
+    }
+
+    pub fn insert(&mut self, address: Address) {
+        for bit in bit_positions(address, self.hash_count, self.bits.len()) {
+            self.bits[bit / 8] |= 1 << (bit % 8);
+        }
+        self.address_count += 1;
+    }
+
+    #[cfg(test)]
+    fn contains(&self, address: Address) -> bool {
+        bit_positions(address, self.hash_count, self.bits.len()).all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    pub fn address_count(&self) -> u64 {
+        self.address_count
+    }
+
+    pub fn hash_count(&self) -> u8 {
+        self.hash_count
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(14 + self.bits.len());
+        bytes.push(VERSION);
+        bytes.push(self.hash_count);
+        bytes.extend_from_slice(&self.milestone_index.0.to_le_bytes());
+        bytes.extend_from_slice(&self.address_count.to_le_bytes());
+        bytes.extend_from_slice(&self.bits);
+        bytes
+    }
+}
+
+/// The positions of the bits that are set for an address in a filter of `len` bytes.
+fn bit_positions(address: Address, hash_count: u8, len: usize) -> impl Iterator<Item = usize> {
+    let digest = Blake2b256::digest(iota_sdk::types::block::address::Address::from(address).pack_to_vec());
+    // Unwrap: the digest is 32 bytes long.
+    let h1 = u64::from_le_bytes(digest[..8].try_into().unwrap());
+    let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap());
+    let bit_count = len as u64 * 8;
+    (0..hash_count as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bit_count) as usize)
+}
+
+#[cfg(test)]
+mod test {
+    use chronicle::model::utxo::{AliasAddress, AliasId, Ed25519Address};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn bytes(n: u32) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes[..4].copy_from_slice(&n.to_le_bytes());
+        bytes
+    }
+
+    fn address(n: u32) -> Address {
+        Address::Ed25519(Ed25519Address(bytes(n)))
+    }
+
+    #[test]
+    fn filter_is_sized_for_false_positive_rate() {
+        let filter = AddressFilter::new(5.into(), 1000, 0.01);
+        // About 9.6 bits and 7 hash functions per address are needed for one false positive in a hundred.
+        assert_eq!(filter.bits.len(), 1199);
+        assert_eq!(filter.hash_count(), 7);
+
+        let filter = AddressFilter::new(5.into(), 0, 0.01);
+        assert_eq!(filter.bits.len(), 2);
+    }
+
+    #[test]
+    fn contains_inserted_addresses() {
+        let mut filter = AddressFilter::new(5.into(), 1000, 0.01);
+        for n in 0..1000 {
+            filter.insert(address(n));
+        }
+        assert_eq!(filter.address_count(), 1000);
+        assert!((0..1000).all(|n| filter.contains(address(n))));
+        let false_positives = (1000..11000).filter(|&n| filter.contains(address(n))).count();
+        assert!(false_positives < 200, "{false_positives} false positives");
+        // The kind of the address is hashed along with its bytes.
+        let alias = Address::Alias(AliasAddress(AliasId(bytes(0))));
+        assert!(bit_positions(address(0), 7, 1199).ne(bit_positions(alias, 7, 1199)));
+    }
+
+    #[test]
+    fn serializes_header_and_bits() {
+        let mut filter = AddressFilter::new(258.into(), 10, 0.01);
+        filter.insert(address(1));
+        let bytes = filter.to_bytes();
+        assert_eq!(bytes[0], VERSION);
+        assert_eq!(bytes[1], filter.hash_count());
+        assert_eq!(&bytes[2..6], &258u32.to_le_bytes());
+        assert_eq!(&bytes[6..14], &1u64.to_le_bytes());
+        assert_eq!(&bytes[14..], &filter.bits[..]);
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+mod filter;
+
+use chronicle::{
+    db::{
+        mongodb::collections::{AddressFilterCollection, MilestoneCollection, OutputCollection},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+use eyre::Result;
+use futures::TryStreamExt;
+use tracing::{debug, error};
+
+pub use self::{config::AddressFilterConfig, filter::AddressFilter};
+
+/// Builds a filter of the addresses known to the archive whenever the ledger advanced by the interval since the latest
+/// one was built, so that light clients can download it instead of querying for every address.
+pub struct AddressFilterWorker {
+    db: MongoDb,
+    config: AddressFilterConfig,
+}
+
+/// Whether a filter is due at the ledger index, given the milestone index of the latest one.
+fn is_due(latest: Option<MilestoneIndex>, ledger_index: MilestoneIndex, interval: u32) -> bool {
+    latest.map_or(true, |latest| {
+        latest
+            .0
+            .checked_add(interval)
+            .map_or(false, |due| ledger_index.0 >= due)
+    })
+}
+
+impl AddressFilterWorker {
+    pub fn new(db: MongoDb, config: AddressFilterConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let mut latest = self
+            .db
+            .collection::<AddressFilterCollection>()
+            .get_latest_filter_index()
+            .await?;
+
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+            let ledger_index = match self.db.collection::<MilestoneCollection>().get_ledger_index().await? {
+                Some(ledger_index) => ledger_index,
+                None => continue,
+            };
+            if !is_due(latest, ledger_index, self.config.interval) {
+                continue;
+            }
+            let start_time = std::time::Instant::now();
+            // A failed filter is retried at the next poll.
+            match self.update(ledger_index).await {
+                Ok(filter) => {
+                    debug!(
+                        "Built the filter of {} addresses at milestone {ledger_index} in {}ms.",
+                        filter.address_count(),
+                        start_time.elapsed().as_millis()
+                    );
+                    latest = Some(ledger_index);
+                }
+                Err(e) => error!("Failed to build the address filter at milestone {ledger_index}: {e}"),
+            }
+        }
+    }
+
+    async fn update(&self, ledger_index: MilestoneIndex) -> Result<AddressFilter> {
+        let filter = build_filter(&self.db, ledger_index, self.config.false_positive_rate).await?;
+        self.db
+            .collection::<AddressFilterCollection>()
+            .upsert_filter(ledger_index, &filter.to_bytes())
+            .await?;
+        Ok(filter)
+    }
+}
+
+/// Builds a filter of every address that ever owned an output. The addresses are streamed twice, first to size the
+/// filter and then to fill it, so that they are never held in memory.
+pub async fn build_filter(
+    db: &MongoDb,
+    milestone_index: MilestoneIndex,
+    false_positive_rate: f64,
+) -> Result<AddressFilter> {
+    let outputs = db.collection::<OutputCollection>();
+    let address_count = outputs
+        .get_distinct_addresses()
+        .await?
+        .try_fold(0, |count, _| async move { Ok(count + 1) })
+        .await?;
+    let mut filter = AddressFilter::new(milestone_index, address_count, false_positive_rate);
+    let mut addresses = outputs.get_distinct_addresses().await?;
+    while let Some(address) = addresses.try_next().await? {
+        filter.insert(address);
+    }
+    Ok(filter)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn filter_is_due_after_interval() {
+        assert!(is_due(None, 5.into(), 10));
+        assert!(!is_due(Some(20.into()), 29.into(), 10));
+        assert!(is_due(Some(20.into()), 30.into(), 10));
+        assert!(!is_due(Some(u32::MAX.into()), u32::MAX.into(), 10));
+    }
+}
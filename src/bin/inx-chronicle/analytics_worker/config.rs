@@ -0,0 +1,24 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_ENABLED: bool = false;
+pub const DEFAULT_POLL_INTERVAL: &str = "5s";
+
+/// Configuration of the analytics worker.
+#[derive(Clone, Debug)]
+pub struct AnalyticsWorkerConfig {
+    pub enabled: bool,
+    /// How often the database is checked for milestones that have no analytics yet.
+    pub poll_interval: Duration,
+}
+
+impl Default for AnalyticsWorkerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            poll_interval: DEFAULT_POLL_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+        }
+    }
+}
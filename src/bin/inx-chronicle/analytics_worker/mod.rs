@@ -0,0 +1,136 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use std::collections::HashSet;
+
+use chronicle::{
+    analytics::Analytic,
+    db::{
+        influxdb::{AnalyticsChoice, InfluxDb},
+        mongodb::collections::{ApplicationStateCollection, MilestoneCollection, OutputCollection},
+        MongoDb,
+    },
+    tangle::Tangle,
+};
+use eyre::Result;
+use futures::TryStreamExt;
+use tracing::{debug, info};
+
+pub use self::config::AnalyticsWorkerConfig;
+use crate::cli::analytics::AnalyticsState;
+
+/// Computes the per-milestone analytics from the milestones stored in MongoDB, separately from the INX ingestion, so
+/// that slow analytics cannot hold back the ledger.
+pub struct AnalyticsWorker {
+    db: MongoDb,
+    influx_db: InfluxDb,
+    config: AnalyticsWorkerConfig,
+    analytics_choices: HashSet<AnalyticsChoice>,
+    state: Option<AnalyticsState>,
+}
+
+impl AnalyticsWorker {
+    pub fn new(db: MongoDb, influx_db: InfluxDb, config: AnalyticsWorkerConfig) -> Self {
+        let analytics_choices = if influx_db.config().analytics.is_empty() {
+            chronicle::db::influxdb::config::all_analytics()
+        } else {
+            influx_db.config().analytics.iter().copied().collect()
+        };
+        Self {
+            db,
+            influx_db,
+            config,
+            analytics_choices,
+            state: None,
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        let app_state = self.db.collection::<ApplicationStateCollection>();
+        let milestones = self.db.collection::<MilestoneCollection>();
+        info!(
+            "Computing analytics from the database into `{}`.",
+            self.influx_db.analytics().database_name()
+        );
+
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+            let ledger_index = match milestones.get_ledger_index().await? {
+                Some(ledger_index) => ledger_index,
+                None => continue,
+            };
+            let next_index = match app_state.get_analytics_index().await? {
+                Some(analytics_index) => analytics_index + 1,
+                None => match app_state.get_starting_index().await? {
+                    Some(starting_index) => starting_index.milestone_index,
+                    None => continue,
+                },
+            };
+            if next_index > ledger_index {
+                continue;
+            }
+
+            let tangle = Tangle::from(self.db.clone());
+            let mut milestone_stream = tangle.milestone_stream(next_index..=ledger_index).await?;
+            while let Some(milestone) = milestone_stream.try_next().await? {
+                let start_time = std::time::Instant::now();
+                // Check if the protocol params changed (or we just started)
+                if !matches!(&self.state, Some(state) if state.prev_protocol_params == milestone.protocol_params) {
+                    let ledger_state = self
+                        .db
+                        .collection::<OutputCollection>()
+                        .get_unspent_output_stream(milestone.at.milestone_index - 1)
+                        .await?
+                        .try_collect::<Vec<_>>()
+                        .await?;
+
+                    let analytics = self
+                        .analytics_choices
+                        .iter()
+                        .map(|choice| {
+                            Ok(Analytic::init(
+                                choice,
+                                &milestone.protocol_params,
+                                &ledger_state,
+                                self.influx_db.config().analytics_spill_dir.as_deref(),
+                            )?
+                            .with_sampling_interval(self.influx_db.config().sampling_interval(choice)))
+                        })
+                        .collect::<eyre::Result<Vec<_>>>()?;
+                    self.state = Some(AnalyticsState {
+                        analytics,
+                        prev_protocol_params: milestone.protocol_params.clone(),
+                    });
+                }
+
+                // Unwrap: safe because we guarantee it is initialized above
+                milestone
+                    .update_analytics(&mut self.state.as_mut().unwrap().analytics, &self.influx_db)
+                    .await?;
+                app_state.set_analytics_index(milestone.at.milestone_index).await?;
+
+                let elapsed = start_time.elapsed();
+                #[cfg(feature = "metrics")]
+                if self.influx_db.config().metrics_enabled {
+                    self.influx_db
+                        .metrics()
+                        .insert(chronicle::metrics::AnalyticsMetrics {
+                            time: chrono::Utc::now(),
+                            milestone_index: milestone.at.milestone_index,
+                            analytics_time: elapsed.as_millis() as u64,
+                            chronicle_version: std::env!("CARGO_PKG_VERSION").to_string(),
+                        })
+                        .await?;
+                }
+                debug!(
+                    "Computed analytics for milestone {} in {}ms.",
+                    milestone.at.milestone_index,
+                    elapsed.as_millis()
+                );
+            }
+        }
+    }
+}
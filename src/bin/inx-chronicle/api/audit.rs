@@ -0,0 +1,313 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{
+    body::HttpBody,
+    headers::{authorization::Bearer, Authorization, HeaderMapExt},
+    http::{header::CONTENT_LENGTH, Request},
+    middleware::Next,
+    response::Response,
+};
+use chronicle::db::{
+    mongodb::collections::{ApiAuditLogCollection, ApiAuditRecord},
+    MongoDb,
+};
+use crypto::macs::hmac::HMAC_SHA256;
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc, time::Instant};
+use tracing::warn;
+
+/// The number of bytes of a hash that are kept in a record, which is enough to tell callers and parameters apart
+/// without storing what they were.
+const HASH_PREFIX_LEN: usize = 8;
+
+/// The number of records that wait to be written before further ones are dropped.
+const QUEUE_LENGTH: usize = 1024;
+
+/// The fields of an audit record that can be left out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditField {
+    /// The hashed bearer token of the caller.
+    Caller,
+    /// The path of the request, which may contain addresses.
+    Path,
+    /// The hashed query string of the request.
+    Params,
+}
+
+/// Where the API audit log is written to, and what is left out of it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditLogConfig {
+    /// Whether records are inserted into the `api_audit_log` collection.
+    pub collection: bool,
+    /// The file that records are appended to as JSON lines.
+    pub file: Option<PathBuf>,
+    pub redact: Vec<AuditField>,
+    /// How long records are kept in the collection.
+    pub retention: Duration,
+}
+
+impl AuditLogConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.collection || self.file.is_some()
+    }
+}
+
+/// Records the requests handled by the API.
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    /// The records to write, and whether they go into the collection as well.
+    sender: mpsc::Sender<(ApiAuditRecord, bool)>,
+    dropped: Arc<AtomicU64>,
+    redact: Arc<[AuditField]>,
+    hash_key: Arc<[u8; 32]>,
+    retention: Duration,
+    #[cfg(feature = "inx")]
+    standby: Option<crate::inx::Standby>,
+}
+
+impl AuditLog {
+    /// Opens the audit log, if it is enabled, and starts writing its records in the background. The file is opened
+    /// right away, so that a bad path is reported on startup.
+    ///
+    /// The hashes are keyed with the given secret, so that short values such as addresses cannot be recovered from
+    /// them by hashing every candidate.
+    pub fn new(db: &MongoDb, config: &AuditLogConfig, secret: &[u8]) -> std::io::Result<Option<Self>> {
+        if !config.is_enabled() {
+            return Ok(None);
+        }
+        let file = config
+            .file
+            .as_ref()
+            .map(|path| OpenOptions::new().create(true).append(true).open(path))
+            .transpose()?;
+        let (sender, receiver) = mpsc::channel(QUEUE_LENGTH);
+        let dropped = Arc::new(AtomicU64::new(0));
+        tokio::spawn(write_records(
+            receiver,
+            config.collection.then(|| db.clone()),
+            file,
+            dropped.clone(),
+        ));
+        // Derive a key of its own, so that the hashes do not reveal anything about the secret.
+        let mut hash_key = [0; 32];
+        HMAC_SHA256(b"api_audit_log", secret, &mut hash_key);
+        Ok(Some(Self {
+            sender,
+            dropped,
+            redact: config.redact.as_slice().into(),
+            hash_key: Arc::new(hash_key),
+            retention: config.retention,
+            #[cfg(feature = "inx")]
+            standby: None,
+        }))
+    }
+
+    /// Keeps the records out of the collection while the instance is a standby, which must not write to the
+    /// database. They are still appended to the file.
+    #[cfg(feature = "inx")]
+    pub fn set_standby(&mut self, standby: crate::inx::Standby) {
+        self.standby.replace(standby);
+    }
+
+    fn redacts(&self, field: AuditField) -> bool {
+        self.redact.contains(&field)
+    }
+
+    fn hash(&self, value: &str) -> String {
+        let mut mac = [0; 32];
+        HMAC_SHA256(value.as_bytes(), self.hash_key.as_ref(), &mut mac);
+        hex::encode(&mac[..HASH_PREFIX_LEN])
+    }
+
+    /// Starts a record of a request, which is completed once the response is known.
+    fn start<B>(&self, req: &Request<B>) -> ApiAuditRecord {
+        let requested_at = DateTime::now();
+        ApiAuditRecord {
+            requested_at,
+            expires_at: DateTime::from_system_time(requested_at.to_system_time() + self.retention),
+            method: req.method().to_string(),
+            path: (!self.redacts(AuditField::Path)).then(|| req.uri().path().to_string()),
+            params_hash: req
+                .uri()
+                .query()
+                .filter(|_| !self.redacts(AuditField::Params))
+                .map(|query| self.hash(query)),
+            caller: req
+                .headers()
+                .typed_get::<Authorization<Bearer>>()
+                .filter(|_| !self.redacts(AuditField::Caller))
+                .map(|auth| self.hash(auth.token())),
+            status: 0,
+            duration_ms: 0,
+            response_size: None,
+        }
+    }
+
+    /// Queues the record to be written, so that the response is not held up by it. The record is dropped if the
+    /// writer falls too far behind.
+    fn write(&self, record: ApiAuditRecord) {
+        #[cfg(feature = "inx")]
+        let to_collection = self.standby.as_ref().map_or(true, |standby| standby.is_promoted());
+        #[cfg(not(feature = "inx"))]
+        let to_collection = true;
+        if self.sender.try_send((record, to_collection)).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+async fn write_records(
+    mut receiver: mpsc::Receiver<(ApiAuditRecord, bool)>,
+    db: Option<MongoDb>,
+    mut file: Option<File>,
+    dropped: Arc<AtomicU64>,
+) {
+    // The indexes are created along with the first record, so that nothing is written before that is allowed.
+    let mut indexed = false;
+    while let Some((record, to_collection)) = receiver.recv().await {
+        if let Some(db) = db.as_ref().filter(|_| to_collection) {
+            if !indexed {
+                if let Err(e) = db.create_indexes::<ApiAuditLogCollection>().await {
+                    warn!("Failed to create the indexes of the API audit log: {e}");
+                }
+                indexed = true;
+            }
+            if let Err(e) = db.collection::<ApiAuditLogCollection>().insert_record(&record).await {
+                warn!("Failed to insert API audit record: {e}");
+            }
+        }
+        if let Some(mut f) = file.take() {
+            // The file is handed to a blocking thread and back, so that the writes do not block the runtime.
+            let (f, res) = match tokio::task::spawn_blocking(move || {
+                let res = append_line(&mut f, &record);
+                (f, res)
+            })
+            .await
+            {
+                Ok(res) => res,
+                Err(e) => {
+                    warn!("Stopped writing the API audit log file: {e}");
+                    continue;
+                }
+            };
+            if let Err(e) = res {
+                warn!("Failed to write API audit record: {e}");
+            }
+            file = Some(f);
+        }
+        let dropped = dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            warn!("Dropped {dropped} API audit records, because they could not be written fast enough.");
+        }
+    }
+}
+
+fn append_line(file: &mut File, record: &ApiAuditRecord) -> std::io::Result<()> {
+    let mut line = serde_json::to_value(record)?;
+    // The JSON of a `DateTime` is extended JSON, which is meant for MongoDb rather than for people reading the file.
+    line["requested_at"] = record
+        .requested_at
+        .try_to_rfc3339_string()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        .into();
+    // Only the collection expires its records.
+    if let Some(line) = line.as_object_mut() {
+        line.remove("expires_at");
+    }
+    let mut line = serde_json::to_vec(&line)?;
+    line.push(b'\n');
+    file.write_all(&line)
+}
+
+/// Records the request in the audit log, if it is enabled.
+pub async fn audit<B>(req: Request<B>, next: Next<B>, audit_log: Option<AuditLog>) -> Response {
+    let audit_log = match audit_log {
+        Some(audit_log) => audit_log,
+        None => return next.run(req).await,
+    };
+    let mut record = audit_log.start(&req);
+    let start = Instant::now();
+    let res = next.run(req).await;
+    record.duration_ms = start.elapsed().as_millis() as u64;
+    record.status = res.status().as_u16();
+    record.response_size = res.body().size_hint().exact().or_else(|| {
+        res.headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok()?.parse().ok())
+    });
+    audit_log.write(record);
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use axum::body::Body;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn audit_log(redact: Vec<AuditField>, hash_key: [u8; 32]) -> AuditLog {
+        AuditLog {
+            sender: mpsc::channel(1).0,
+            dropped: Default::default(),
+            redact: redact.into(),
+            hash_key: Arc::new(hash_key),
+            retention: Duration::from_secs(60),
+            #[cfg(feature = "inx")]
+            standby: None,
+        }
+    }
+
+    fn request() -> Request<Body> {
+        Request::get("/api/explorer/v2/balance/iota1abc?pageSize=10")
+            .header("Authorization", "Bearer secret-token")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn records_hashes() {
+        let audit_log = audit_log(vec![], [1; 32]);
+        let record = audit_log.start(&request());
+        assert_eq!(record.method, "GET");
+        assert_eq!(record.path.as_deref(), Some("/api/explorer/v2/balance/iota1abc"));
+        assert_eq!(record.params_hash, Some(audit_log.hash("pageSize=10")));
+        assert_eq!(record.caller, Some(audit_log.hash("secret-token")));
+        assert_eq!(record.caller.unwrap().len(), 2 * HASH_PREFIX_LEN);
+        assert_eq!(
+            record.expires_at.timestamp_millis() - record.requested_at.timestamp_millis(),
+            60_000
+        );
+    }
+
+    #[test]
+    fn keys_hashes() {
+        let record = audit_log(vec![], [1; 32]).start(&request());
+        let other_record = audit_log(vec![], [2; 32]).start(&request());
+        assert_ne!(record.params_hash, other_record.params_hash);
+        assert_ne!(record.caller, other_record.caller);
+    }
+
+    #[test]
+    fn redacts_fields() {
+        let record =
+            audit_log(vec![AuditField::Path, AuditField::Params, AuditField::Caller], [1; 32]).start(&request());
+        assert_eq!(record.path, None);
+        assert_eq!(record.params_hash, None);
+        assert_eq!(record.caller, None);
+    }
+}
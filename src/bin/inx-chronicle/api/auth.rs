@@ -27,6 +27,35 @@ impl<B: Send> FromRequest<B> for Auth {
             return Ok(Auth);
         }
 
+        Auth::validate_jwt(req, &config).await?;
+
+        Ok(Auth)
+    }
+}
+
+/// Requires a valid bearer token, even if the requested route is public. This guards administrative endpoints.
+pub struct Admin;
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for Admin {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        Auth::validate_jwt(req, &config).await?;
+
+        Ok(Admin)
+    }
+}
+
+impl Auth {
+    /// Validates the bearer token of a request, even if the requested route is public. This is used to restrict
+    /// privileged request options to authenticated users.
+    pub async fn validate_jwt<B: Send>(
+        req: &mut axum::extract::RequestParts<B>,
+        config: &ApiConfigData,
+    ) -> Result<(), ApiError> {
         let TypedHeader(Authorization(bearer)) = TypedHeader::<Authorization<Bearer>>::from_request(req)
             .await
             .map_err(RequestError::from)?;
@@ -41,6 +70,6 @@ impl<B: Send> FromRequest<B> for Auth {
         )
         .map_err(AuthError::InvalidJwt)?;
 
-        Ok(Auth)
+        Ok(())
     }
 }
@@ -1,23 +1,41 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use derive_more::From;
 use regex::RegexSet;
 use serde::{Deserialize, Serialize};
 use tower_http::cors::AllowOrigin;
 
-use super::{error::ConfigError, SecretKey};
+use super::{
+    audit::{AuditField, AuditLogConfig},
+    error::ConfigError,
+    tls::TlsFiles,
+    SecretKey,
+};
 
 pub const DEFAULT_ENABLED: bool = true;
 pub const DEFAULT_PORT: u16 = 8042;
 pub const DEFAULT_ALLOW_ORIGINS: &str = "0.0.0.0";
 pub const DEFAULT_PUBLIC_ROUTES: &str = "api/core/v2/*";
 pub const DEFAULT_MAX_PAGE_SIZE: usize = 1000;
+pub const DEFAULT_REQUEST_TIMEOUT: &str = "30s";
+pub const DEFAULT_QUERY_CONCURRENCY: usize = 8;
+pub const DEFAULT_QUERY_QUEUE_LENGTH: usize = 64;
+pub const DEFAULT_COMPRESSION_ENABLED: bool = true;
+pub const DEFAULT_ETAG_ENABLED: bool = true;
+pub const DEFAULT_QUERY_CACHE_ENABLED: bool = false;
+pub const DEFAULT_QUERY_CACHE_TTL: &str = "10m";
+pub const DEFAULT_STRICT_CONSISTENCY: bool = false;
+pub const DEFAULT_CONSISTENCY_MAX_WAIT: &str = "10s";
+pub const DEFAULT_HTTP2_ENABLED: bool = true;
+pub const DEFAULT_TLS_RELOAD_INTERVAL: &str = "1m";
 pub const DEFAULT_JWT_PASSWORD: &str = "password";
 pub const DEFAULT_JWT_SALT: &str = "saltines";
 pub const DEFAULT_JWT_EXPIRATION: &str = "72h";
+pub const DEFAULT_AUDIT_LOG_COLLECTION: bool = false;
+pub const DEFAULT_AUDIT_LOG_RETENTION: &str = "30d";
 
 /// API configuration
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -28,6 +46,51 @@ pub struct ApiConfig {
     pub allow_origins: SingleOrMultiple<String>,
     pub public_routes: Vec<String>,
     pub max_page_size: usize,
+    #[serde(with = "humantime_serde")]
+    pub request_timeout: Duration,
+    /// The number of expensive queries that run at once.
+    pub query_concurrency: usize,
+    /// The number of expensive queries that wait for others to finish before further ones are rejected.
+    pub query_queue_length: usize,
+    /// Whether responses are compressed with gzip or brotli, if the client accepts it.
+    pub compression_enabled: bool,
+    /// Whether the explorer routes that only change with the ledger send an `ETag` and handle `If-None-Match`.
+    pub etag_enabled: bool,
+    /// Whether the results of the ledger analytics are stored in the `query_cache` collection and served from there
+    /// until the ledger index advances.
+    pub query_cache_enabled: bool,
+    /// How long a cached result is served at most, in case the ledger index does not advance.
+    #[serde(with = "humantime_serde")]
+    pub query_cache_ttl: Duration,
+    /// Whether responses carry the ledger index they reflect, and requests can wait for a minimum ledger index.
+    pub strict_consistency: bool,
+    /// How long a request waits for the ledger index to reach the requested minimum.
+    #[serde(with = "humantime_serde")]
+    pub consistency_max_wait: Duration,
+    /// The RFC 3339 date from which the API versions before the latest are deprecated, announced in their
+    /// `Deprecation` header. They are not deprecated if this is not set.
+    pub deprecated_since: Option<String>,
+    /// The RFC 3339 date after which deprecated API versions may be removed, announced in their `Sunset` header.
+    pub sunset: Option<String>,
+    /// Whether HTTP/2 is served along with HTTP/1.1.
+    pub http2_enabled: bool,
+    /// The PEM file with the certificate chain to serve the API over TLS with. TLS is used if this and the key file
+    /// are set.
+    pub tls_cert_file: Option<String>,
+    /// The PEM file with the private key of the TLS certificate.
+    pub tls_key_file: Option<String>,
+    /// How often the TLS certificate and key files are checked for changes, which are then served without a restart.
+    #[serde(with = "humantime_serde")]
+    pub tls_reload_interval: Duration,
+    /// Whether the requests handled by the API are recorded in the `api_audit_log` collection.
+    pub audit_log_collection: bool,
+    /// The file that the requests handled by the API are appended to as JSON lines.
+    pub audit_log_file: Option<String>,
+    /// The fields that are left out of the audit log.
+    pub audit_log_redact: Vec<AuditField>,
+    /// How long records are kept in the `api_audit_log` collection.
+    #[serde(with = "humantime_serde")]
+    pub audit_log_retention: Duration,
     pub jwt_password: String,
     pub jwt_salt: String,
     pub jwt_identity_file: Option<String>,
@@ -43,6 +106,34 @@ impl Default for ApiConfig {
             allow_origins: SingleOrMultiple::Single(DEFAULT_ALLOW_ORIGINS.to_string()),
             public_routes: vec![DEFAULT_PUBLIC_ROUTES.to_string()],
             max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT.parse::<humantime::Duration>().unwrap().into(),
+            query_concurrency: DEFAULT_QUERY_CONCURRENCY,
+            query_queue_length: DEFAULT_QUERY_QUEUE_LENGTH,
+            compression_enabled: DEFAULT_COMPRESSION_ENABLED,
+            etag_enabled: DEFAULT_ETAG_ENABLED,
+            query_cache_enabled: DEFAULT_QUERY_CACHE_ENABLED,
+            query_cache_ttl: DEFAULT_QUERY_CACHE_TTL.parse::<humantime::Duration>().unwrap().into(),
+            strict_consistency: DEFAULT_STRICT_CONSISTENCY,
+            consistency_max_wait: DEFAULT_CONSISTENCY_MAX_WAIT
+                .parse::<humantime::Duration>()
+                .unwrap()
+                .into(),
+            deprecated_since: None,
+            sunset: None,
+            http2_enabled: DEFAULT_HTTP2_ENABLED,
+            tls_cert_file: None,
+            tls_key_file: None,
+            tls_reload_interval: DEFAULT_TLS_RELOAD_INTERVAL
+                .parse::<humantime::Duration>()
+                .unwrap()
+                .into(),
+            audit_log_collection: DEFAULT_AUDIT_LOG_COLLECTION,
+            audit_log_file: None,
+            audit_log_redact: Vec::new(),
+            audit_log_retention: DEFAULT_AUDIT_LOG_RETENTION
+                .parse::<humantime::Duration>()
+                .unwrap()
+                .into(),
             jwt_identity_file: None,
             jwt_password: DEFAULT_JWT_PASSWORD.to_string(),
             jwt_salt: DEFAULT_JWT_SALT.to_string(),
@@ -57,6 +148,25 @@ pub struct ApiConfigData {
     pub allow_origins: AllowOrigin,
     pub public_routes: RegexSet,
     pub max_page_size: usize,
+    pub request_timeout: Duration,
+    pub query_concurrency: usize,
+    pub query_queue_length: usize,
+    pub compression_enabled: bool,
+    #[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+    pub etag_enabled: bool,
+    /// How long the results of the ledger analytics are cached, if at all.
+    #[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+    pub query_cache_ttl: Option<Duration>,
+    pub strict_consistency: bool,
+    pub consistency_max_wait: Duration,
+    #[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+    pub deprecated_since: Option<SystemTime>,
+    #[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+    pub sunset: Option<SystemTime>,
+    pub http2_enabled: bool,
+    pub tls: Option<TlsFiles>,
+    pub tls_reload_interval: Duration,
+    pub audit_log: AuditLogConfig,
     pub jwt_password_hash: Vec<u8>,
     pub jwt_password_salt: String,
     pub jwt_secret_key: SecretKey,
@@ -78,6 +188,39 @@ impl TryFrom<ApiConfig> for ApiConfigData {
             allow_origins: AllowOrigin::try_from(config.allow_origins)?,
             public_routes: RegexSet::new(config.public_routes.iter().map(route_to_regex).collect::<Vec<_>>())?,
             max_page_size: config.max_page_size,
+            request_timeout: config.request_timeout,
+            query_concurrency: config.query_concurrency,
+            query_queue_length: config.query_queue_length,
+            compression_enabled: config.compression_enabled,
+            etag_enabled: config.etag_enabled,
+            query_cache_ttl: config.query_cache_enabled.then(|| config.query_cache_ttl),
+            strict_consistency: config.strict_consistency,
+            consistency_max_wait: config.consistency_max_wait,
+            deprecated_since: config
+                .deprecated_since
+                .as_deref()
+                .map(humantime::parse_rfc3339_weak)
+                .transpose()?,
+            sunset: config
+                .sunset
+                .as_deref()
+                .map(humantime::parse_rfc3339_weak)
+                .transpose()?,
+            http2_enabled: config.http2_enabled,
+            tls: config
+                .tls_cert_file
+                .zip(config.tls_key_file)
+                .map(|(cert_file, key_file)| TlsFiles {
+                    cert_file: cert_file.into(),
+                    key_file: key_file.into(),
+                }),
+            tls_reload_interval: config.tls_reload_interval,
+            audit_log: AuditLogConfig {
+                collection: config.audit_log_collection,
+                file: config.audit_log_file.map(Into::into),
+                redact: config.audit_log_redact,
+                retention: config.audit_log_retention,
+            },
             jwt_password_hash: argon2::hash_raw(
                 config.jwt_password.as_bytes(),
                 config.jwt_salt.as_bytes(),
@@ -0,0 +1,153 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use axum::{
+    http::{header::HeaderName, HeaderValue, Request, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chronicle::{
+    db::{mongodb::collections::MilestoneCollection, MongoDb},
+    model::tangle::MilestoneIndex,
+};
+use tokio::time::Instant;
+
+use super::{
+    config::ApiConfigData,
+    error::{ApiError, LaggingLedgerError, RequestError},
+    ApiResult,
+};
+
+/// The query parameter with which a client asks for a response that reflects at least the given ledger index.
+const MIN_LEDGER_INDEX_PARAM: &str = "min_ledger_index";
+
+/// The header with the ledger index that a response reflects at least.
+pub const LEDGER_INDEX_HEADER: HeaderName = HeaderName::from_static("x-ledger-index");
+
+/// How often the ledger index is checked while waiting for it to reach the requested minimum.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Lets clients read their own writes in the strict consistency mode.
+///
+/// A client that submitted a transaction via the node can pass the milestone that confirmed it as
+/// `?min_ledger_index=`, and the request then waits until Chronicle synced that milestone. Every response carries the
+/// ledger index it reflects at least, which was synced before the handler ran. The parameter is removed before the
+/// request is routed, so that it can be added to any route.
+pub async fn read_after_write<B>(mut req: Request<B>, next: Next<B>) -> Response {
+    let (strict, max_wait) = req
+        .extensions()
+        .get::<ApiConfigData>()
+        .map_or((false, Duration::ZERO), |config| {
+            (config.strict_consistency, config.consistency_max_wait)
+        });
+    let min_ledger_index = match take_min_ledger_index(req.uri_mut()) {
+        Ok(min_ledger_index) => min_ledger_index,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    if !strict {
+        if min_ledger_index.is_some() {
+            return ApiError::from(RequestError::ConsistencyDisabled).into_response();
+        }
+        return next.run(req).await;
+    }
+    let db = match req.extensions().get::<MongoDb>().cloned() {
+        Some(db) => db,
+        None => return next.run(req).await,
+    };
+    let ledger_index = match wait_for_ledger_index(&db, min_ledger_index, max_wait).await {
+        Ok(ledger_index) => ledger_index,
+        Err(e) => return e.into_response(),
+    };
+    let mut res = next.run(req).await;
+    if let Some(ledger_index) = ledger_index {
+        res.headers_mut()
+            .insert(LEDGER_INDEX_HEADER, HeaderValue::from(ledger_index.0));
+    }
+    res
+}
+
+/// Waits until the ledger index reaches the minimum, if one is given, and returns it.
+async fn wait_for_ledger_index(
+    db: &MongoDb,
+    min_ledger_index: Option<MilestoneIndex>,
+    max_wait: Duration,
+) -> ApiResult<Option<MilestoneIndex>> {
+    let deadline = Instant::now() + max_wait;
+    loop {
+        let ledger_index = db.collection::<MilestoneCollection>().get_ledger_index().await?;
+        match (min_ledger_index, ledger_index) {
+            (None, _) => return Ok(ledger_index),
+            (Some(required), Some(current)) if current >= required => return Ok(ledger_index),
+            (Some(required), current) if Instant::now() + POLL_INTERVAL > deadline => {
+                return Err(LaggingLedgerError {
+                    required,
+                    current: current.unwrap_or_default(),
+                }
+                .into());
+            }
+            _ => tokio::time::sleep(POLL_INTERVAL).await,
+        }
+    }
+}
+
+/// Removes the minimum ledger index from the query of the uri and returns it. The other parameters are kept as they
+/// are.
+fn take_min_ledger_index(uri: &mut Uri) -> Result<Option<MilestoneIndex>, RequestError> {
+    let query = match uri.query() {
+        Some(query) => query,
+        None => return Ok(None),
+    };
+    let mut min_ledger_index = None;
+    let rest = query
+        .split('&')
+        .filter(|pair| match pair.split_once('=') {
+            Some((MIN_LEDGER_INDEX_PARAM, value)) => {
+                min_ledger_index = Some(value.to_string());
+                false
+            }
+            _ => true,
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    let min_ledger_index = match min_ledger_index {
+        Some(min_ledger_index) => min_ledger_index,
+        None => return Ok(None),
+    };
+    let path_and_query = if rest.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{rest}", uri.path())
+    };
+    let mut parts = std::mem::take(uri).into_parts();
+    // Unwrap: the path and the remaining parameters were part of a valid uri.
+    parts.path_and_query = Some(path_and_query.parse().unwrap());
+    *uri = Uri::from_parts(parts).unwrap();
+    Ok(Some(MilestoneIndex(min_ledger_index.parse()?)))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn takes_min_ledger_index() {
+        let mut uri = Uri::from_static("/api/explorer/v2/balance/addr?min_ledger_index=42&pageSize=10");
+        assert_eq!(take_min_ledger_index(&mut uri).unwrap(), Some(MilestoneIndex(42)));
+        assert_eq!(uri, "/api/explorer/v2/balance/addr?pageSize=10");
+
+        let mut uri = Uri::from_static("/api/info?min_ledger_index=7");
+        assert_eq!(take_min_ledger_index(&mut uri).unwrap(), Some(MilestoneIndex(7)));
+        assert_eq!(uri, "/api/info");
+
+        let mut uri = Uri::from_static("/api/info?pageSize=10");
+        assert_eq!(take_min_ledger_index(&mut uri).unwrap(), None);
+        assert_eq!(uri, "/api/info?pageSize=10");
+
+        let mut uri = Uri::from_static("/api/info?min_ledger_index=latest");
+        assert!(take_min_ledger_index(&mut uri).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use axum::{body::HttpBody, extract::FromRequest, BoxError, Json};
+use chronicle::model::utxo::OutputId;
+
+use crate::api::error::{ApiError, RequestError};
+
+/// The maximum number of outputs whose metadata can be requested at once.
+pub const MAX_OUTPUT_METADATA_QUERIES: usize = 1000;
+
+/// A list of outputs whose metadata should be returned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputIdsRequest(pub Vec<OutputId>);
+
+#[async_trait]
+impl<B> FromRequest<B> for OutputIdsRequest
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Json(output_ids) = Json::<Vec<String>>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        if output_ids.is_empty() || output_ids.len() > MAX_OUTPUT_METADATA_QUERIES {
+            return Err(ApiError::from(RequestError::OutputIdCount(MAX_OUTPUT_METADATA_QUERIES)));
+        }
+        Ok(OutputIdsRequest(
+            output_ids
+                .iter()
+                .map(|output_id| OutputId::from_str(output_id))
+                .collect::<Result<_, _>>()
+                .map_err(RequestError::from)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{extract::RequestParts, http::Request};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn output_ids_request() {
+        fn request(body: String) -> RequestParts<axum::body::Body> {
+            RequestParts::new(
+                Request::builder()
+                    .method("POST")
+                    .uri("/outputs/metadata/batch")
+                    .header("content-type", "application/json")
+                    .body(body.into())
+                    .unwrap(),
+            )
+        }
+
+        let output_id = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a20100";
+        let mut req = request(format!(r#"["{output_id}"]"#));
+        assert_eq!(
+            OutputIdsRequest::from_request(&mut req).await.unwrap(),
+            OutputIdsRequest(vec![OutputId::from_str(output_id).unwrap()])
+        );
+
+        let mut req = request("[]".to_string());
+        assert!(OutputIdsRequest::from_request(&mut req).await.is_err());
+
+        let mut req = request(r#"["0x01"]"#.to_string());
+        assert!(OutputIdsRequest::from_request(&mut req).await.is_err());
+
+        let item = format!(r#""{output_id}""#);
+        let mut req = request(format!("[{}]", vec![item; MAX_OUTPUT_METADATA_QUERIES + 1].join(",")));
+        assert!(OutputIdsRequest::from_request(&mut req).await.is_err());
+    }
+}
@@ -4,7 +4,11 @@
 use iota_sdk::types::{api::core::response as iota, block::protocol::ProtocolParameters};
 use serde::{Deserialize, Serialize};
 
-use crate::api::responses::impl_success_response;
+use crate::api::{
+    dto::{BlockMetadataDto, OutputDto, OutputMetadataDto, ReceiptDto},
+    responses::impl_success_response,
+    routes::BYTE_CONTENT_HEADER,
+};
 
 /// Response of `GET /api/info`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -19,6 +23,61 @@ pub struct InfoResponse {
 
 impl_success_response!(InfoResponse);
 
+/// Response of `GET /api/core/v2/outputs/<output_id>/metadata`.
+///
+/// Outputs that were pruned after they were spent are marked, since their spent metadata is no longer known.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMetadataResponse {
+    #[serde(flatten)]
+    pub metadata: OutputMetadataDto,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pruned: bool,
+    /// The first milestone whose spent outputs of the same kind are still stored, if the output was pruned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub earliest_available_milestone_index: Option<u32>,
+}
+
+impl_success_response!(OutputMetadataResponse, BlockMetadataDto);
+
+/// Response of `POST /api/core/v2/outputs/metadata/batch`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputsMetadataResponse {
+    /// The metadata of the outputs that were found, in the order in which they were requested.
+    pub items: Vec<OutputMetadataResponse>,
+    /// The requested outputs that do not exist at the ledger index.
+    pub missing: Vec<String>,
+}
+
+impl_success_response!(OutputsMetadataResponse);
+
+/// Response of `GET /api/core/v2/outputs/<output_id>`.
+///
+/// The metadata of pruned outputs is marked as in [`OutputMetadataResponse`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputWithMetadataResponse {
+    pub metadata: OutputMetadataResponse,
+    pub output: OutputDto,
+}
+
+/// Response of `GET /api/core/v2/receipts` and `GET /api/core/v2/receipts/<migrated_at>`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptsResponse {
+    pub receipts: Vec<ReceiptResponse>,
+}
+
+impl_success_response!(ReceiptsResponse);
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptResponse {
+    pub receipt: ReceiptDto,
+    pub milestone_index: u32,
+}
+
 /// A wrapper struct that allows us to implement [`IntoResponse`](axum::response::IntoResponse) for the foreign
 /// responses from [`iota_types`](iota_sdk::types::api::core::response).
 #[derive(Clone, Debug, Serialize, derive_more::From)]
@@ -43,7 +102,9 @@ impl<T: Serialize> axum::response::IntoResponse for IotaRawResponse<T> {
     fn into_response(self) -> axum::response::Response {
         match self {
             Self::Json(res) => axum::Json(res).into_response(),
-            Self::Raw(bytes) => bytes.into_response(),
+            Self::Raw(bytes) => {
+                ([(axum::http::header::CONTENT_TYPE, BYTE_CONTENT_HEADER.clone())], bytes).into_response()
+            }
         }
     }
 }
@@ -1,13 +1,13 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 use axum::{
     extract::{Extension, Path},
     handler::Handler,
     http::header::HeaderMap,
-    routing::get,
+    routing::{get, post},
 };
 use chronicle::{
     db::{
@@ -19,7 +19,6 @@ use chronicle::{
         MongoDb,
     },
     model::{
-        metadata::BlockMetadata,
         payload::{MilestoneId, TransactionId},
         tangle::MilestoneIndex,
         utxo::OutputId,
@@ -29,24 +28,27 @@ use chronicle::{
 use futures::TryStreamExt;
 use iota_sdk::types::{
     api::core::response::{
-        self as iota, BaseTokenResponse, BlockMetadataResponse, ConfirmedMilestoneResponse, LatestMilestoneResponse,
-        OutputWithMetadataResponse, ReceiptResponse, ReceiptsResponse, StatusResponse, TreasuryResponse,
-        UtxoChangesResponse,
-    },
-    block::{
-        output::{OutputMetadata, RentStructure},
-        payload::{dto::MilestonePayloadDto, milestone::option::dto::MilestoneOptionDto},
-        protocol::ProtocolParameters,
-        BlockDto,
+        self as iota, BaseTokenResponse, ConfirmedMilestoneResponse, LatestMilestoneResponse, StatusResponse,
+        TreasuryResponse, UtxoChangesResponse,
     },
+    block::{output::RentStructure, protocol::ProtocolParameters},
 };
 use packable::PackableExt;
+use serde_json::Value;
 
-use super::responses::{InfoResponse, IotaRawResponse, IotaResponse};
+use super::{
+    extractors::OutputIdsRequest,
+    responses::{
+        InfoResponse, IotaRawResponse, IotaResponse, OutputMetadataResponse, OutputWithMetadataResponse,
+        OutputsMetadataResponse, ReceiptResponse, ReceiptsResponse,
+    },
+};
 use crate::api::{
+    dto::{BlockDto, BlockMetadataDto, MilestoneOptionDto, MilestonePayloadDto, OutputMetadataDto},
     error::{ApiError, CorruptStateError, MissingError, RequestError},
+    extractors::Fields,
     router::Router,
-    routes::{is_healthy, not_implemented, BYTE_CONTENT_HEADER},
+    routes::{accepts_raw, is_healthy, not_implemented},
     ApiResult,
 };
 
@@ -64,6 +66,7 @@ pub fn routes() -> Router {
         .nest(
             "/outputs",
             Router::new()
+                .route("/metadata/batch", post(outputs_metadata))
                 .route("/:output_id", get(output))
                 .route("/:output_id/metadata", get(output_metadata)),
         )
@@ -186,10 +189,11 @@ async fn block(
     database: Extension<MongoDb>,
     Path(block_id): Path<String>,
     headers: HeaderMap,
-) -> ApiResult<IotaRawResponse<BlockDto>> {
+    fields: Fields,
+) -> ApiResult<IotaRawResponse<Value>> {
     let block_id = BlockId::from_str(&block_id).map_err(RequestError::from)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    if accepts_raw(&headers) {
         return Ok(IotaRawResponse::Raw(
             database
                 .collection::<BlockCollection>()
@@ -199,34 +203,19 @@ async fn block(
         ));
     }
 
-    let block = database
-        .collection::<BlockCollection>()
-        .get_block(&block_id)
-        .await?
-        .ok_or(MissingError::NoResults)?;
-
-    Ok(IotaRawResponse::Json(block.try_into()?))
-}
-
-fn create_block_metadata_response(block_id: BlockId, metadata: BlockMetadata) -> iota::BlockMetadataResponse {
-    iota::BlockMetadataResponse {
-        block_id: block_id.into(),
-        parents: metadata.parents.into_vec().into_iter().map(Into::into).collect(),
-        is_solid: metadata.is_solid,
-        referenced_by_milestone_index: Some(*metadata.referenced_by_milestone_index),
-        milestone_index: Some(*metadata.milestone_index),
-        ledger_inclusion_state: Some(metadata.inclusion_state.into()),
-        conflict_reason: Some(metadata.conflict_reason as u8),
-        should_promote: Some(metadata.should_promote),
-        should_reattach: Some(metadata.should_reattach),
-        white_flag_index: Some(metadata.white_flag_index),
+    let blocks = database.collection::<BlockCollection>();
+    // The payload makes up most of a block, so it is only fetched if it is requested.
+    let block = if fields.includes("payload") {
+        blocks.get_block(&block_id).await?
+    } else {
+        blocks.get_block_without_payload(&block_id).await?
     }
+    .ok_or(MissingError::NoResults)?;
+
+    Ok(IotaRawResponse::Json(fields.prune(BlockDto::from(block))?))
 }
 
-async fn block_metadata(
-    database: Extension<MongoDb>,
-    Path(block_id_str): Path<String>,
-) -> ApiResult<IotaResponse<BlockMetadataResponse>> {
+async fn block_metadata(database: Extension<MongoDb>, Path(block_id_str): Path<String>) -> ApiResult<BlockMetadataDto> {
     let block_id = BlockId::from_str(&block_id_str).map_err(RequestError::from)?;
     let metadata = database
         .collection::<BlockCollection>()
@@ -234,57 +223,55 @@ async fn block_metadata(
         .await?
         .ok_or(MissingError::NoResults)?;
 
-    Ok(create_block_metadata_response(block_id, metadata).into())
+    Ok(BlockMetadataDto::new(block_id, metadata))
 }
 
 fn create_output_metadata_response(
     metadata: OutputMetadataResult,
     ledger_index: MilestoneIndex,
-) -> ApiResult<OutputMetadata> {
-    Ok(OutputMetadata::new(
-        metadata.block_id.into(),
-        metadata.output_id.try_into()?,
-        metadata.spent_metadata.is_some(),
-        metadata
-            .spent_metadata
-            .as_ref()
-            .map(|spent_md| *spent_md.spent.milestone_index),
-        metadata
-            .spent_metadata
-            .as_ref()
-            .map(|spent_md| *spent_md.spent.milestone_timestamp),
-        metadata
-            .spent_metadata
-            .as_ref()
-            .map(|spent_md| spent_md.transaction_id.into()),
-        *metadata.booked.milestone_index,
-        *metadata.booked.milestone_timestamp,
-        *ledger_index,
-    ))
+) -> OutputMetadataResponse {
+    OutputMetadataResponse {
+        metadata: OutputMetadataDto::new(&metadata, ledger_index),
+        pruned: metadata.pruned_before.is_some(),
+        earliest_available_milestone_index: metadata.pruned_before.map(|index| index.0),
+    }
 }
 
 async fn output(
     database: Extension<MongoDb>,
     Path(output_id): Path<String>,
     headers: HeaderMap,
-) -> ApiResult<IotaRawResponse<OutputWithMetadataResponse>> {
+    fields: Fields,
+) -> ApiResult<IotaRawResponse<Value>> {
     let ledger_index = database
         .collection::<MilestoneCollection>()
         .get_ledger_index()
         .await?
         .ok_or(MissingError::NoResults)?;
     let output_id = OutputId::from_str(&output_id).map_err(RequestError::from)?;
+    let is_raw = accepts_raw(&headers);
 
-    let OutputWithMetadataResult { output, metadata } = database
-        .collection::<OutputCollection>()
-        .get_output_with_metadata(&output_id, ledger_index)
-        .await?
-        .ok_or(MissingError::NoResults)?;
+    if !is_raw && !fields.includes("output") {
+        let metadata = database
+            .collection::<OutputCollection>()
+            .get_output_metadata(&output_id, ledger_index)
+            .await?
+            .ok_or(MissingError::NoResults)?;
+        let metadata = create_output_metadata_response(metadata, ledger_index);
+        return Ok(IotaRawResponse::Json(
+            fields.prune(serde_json::json!({ "metadata": metadata }))?,
+        ));
+    }
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    if is_raw {
+        let (output, booked_index) = database
+            .collection::<OutputCollection>()
+            .get_output_with_booked_index(&output_id, ledger_index)
+            .await?
+            .ok_or(MissingError::NoResults)?;
         let ctx = database
             .collection::<ProtocolUpdateCollection>()
-            .get_protocol_parameters_for_ledger_index(metadata.booked.milestone_index)
+            .get_protocol_parameters_for_ledger_index(booked_index)
             .await?
             .ok_or(MissingError::NoResults)?
             .parameters;
@@ -292,18 +279,24 @@ async fn output(
         return Ok(IotaRawResponse::Raw(output.raw(ctx)?));
     }
 
-    let metadata = create_output_metadata_response(metadata, ledger_index)?;
+    let OutputWithMetadataResult { output, metadata } = database
+        .collection::<OutputCollection>()
+        .get_output_with_metadata(&output_id, ledger_index)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+
+    let metadata = create_output_metadata_response(metadata, ledger_index);
 
-    Ok(IotaRawResponse::Json(OutputWithMetadataResponse {
+    Ok(IotaRawResponse::Json(fields.prune(OutputWithMetadataResponse {
         metadata,
-        output: output.try_into()?,
-    }))
+        output: output.into(),
+    })?))
 }
 
 async fn output_metadata(
     database: Extension<MongoDb>,
     Path(output_id): Path<String>,
-) -> ApiResult<IotaResponse<OutputMetadata>> {
+) -> ApiResult<OutputMetadataResponse> {
     let ledger_index = database
         .collection::<MilestoneCollection>()
         .get_ledger_index()
@@ -316,17 +309,51 @@ async fn output_metadata(
         .await?
         .ok_or(MissingError::NoResults)?;
 
-    Ok(create_output_metadata_response(metadata, ledger_index)?.into())
+    Ok(create_output_metadata_response(metadata, ledger_index))
+}
+
+async fn outputs_metadata(
+    database: Extension<MongoDb>,
+    OutputIdsRequest(output_ids): OutputIdsRequest,
+) -> ApiResult<OutputsMetadataResponse> {
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let metadata = database
+        .collection::<OutputCollection>()
+        .get_outputs_metadata(&output_ids, ledger_index)
+        .await?
+        .into_iter()
+        .map(|metadata| (metadata.output_id, metadata))
+        .collect::<HashMap<_, _>>();
+
+    let mut res = OutputsMetadataResponse {
+        items: Vec::with_capacity(output_ids.len()),
+        missing: Vec::new(),
+    };
+    for output_id in output_ids {
+        // Repeated output ids are answered repeatedly, so that the response follows the request.
+        match metadata.get(&output_id) {
+            Some(metadata) => res
+                .items
+                .push(create_output_metadata_response(metadata.clone(), ledger_index)),
+            None => res.missing.push(output_id.to_hex()),
+        }
+    }
+    Ok(res)
 }
 
 async fn included_block(
     database: Extension<MongoDb>,
     Path(transaction_id): Path<String>,
     headers: HeaderMap,
-) -> ApiResult<IotaRawResponse<BlockDto>> {
+    fields: Fields,
+) -> ApiResult<IotaRawResponse<Value>> {
     let transaction_id = TransactionId::from_str(&transaction_id).map_err(RequestError::from)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    if accepts_raw(&headers) {
         return Ok(IotaRawResponse::Raw(
             database
                 .collection::<BlockCollection>()
@@ -343,13 +370,13 @@ async fn included_block(
         .ok_or(MissingError::NoResults)?
         .block;
 
-    Ok(IotaRawResponse::Json(block.try_into()?))
+    Ok(IotaRawResponse::Json(fields.prune(BlockDto::from(block))?))
 }
 
 async fn included_block_metadata(
     database: Extension<MongoDb>,
     Path(transaction_id): Path<String>,
-) -> ApiResult<IotaResponse<BlockMetadataResponse>> {
+) -> ApiResult<BlockMetadataDto> {
     let transaction_id = TransactionId::from_str(&transaction_id).map_err(RequestError::from)?;
 
     let res = database
@@ -360,10 +387,10 @@ async fn included_block_metadata(
     let block_id = res.block_id;
     let metadata = res.metadata;
 
-    Ok(create_block_metadata_response(block_id, metadata).into())
+    Ok(BlockMetadataDto::new(block_id, metadata))
 }
 
-async fn receipts(database: Extension<MongoDb>) -> ApiResult<IotaResponse<ReceiptsResponse>> {
+async fn receipts(database: Extension<MongoDb>) -> ApiResult<ReceiptsResponse> {
     let mut receipts_at = database.collection::<MilestoneCollection>().get_all_receipts().await?;
     let mut receipts = Vec::new();
     while let Some((receipt, at)) = receipts_at.try_next().await? {
@@ -376,13 +403,10 @@ async fn receipts(database: Extension<MongoDb>) -> ApiResult<IotaResponse<Receip
             unreachable!("the query only returns receipt milestone options");
         }
     }
-    Ok(iota::ReceiptsResponse { receipts }.into())
+    Ok(ReceiptsResponse { receipts })
 }
 
-async fn receipts_migrated_at(
-    database: Extension<MongoDb>,
-    Path(index): Path<u32>,
-) -> ApiResult<IotaResponse<ReceiptsResponse>> {
+async fn receipts_migrated_at(database: Extension<MongoDb>, Path(index): Path<u32>) -> ApiResult<ReceiptsResponse> {
     let mut receipts_at = database
         .collection::<MilestoneCollection>()
         .get_receipts_migrated_at(index.into())
@@ -398,7 +422,7 @@ async fn receipts_migrated_at(
             unreachable!("the query only returns receipt milestone options");
         }
     }
-    Ok(iota::ReceiptsResponse { receipts }.into())
+    Ok(ReceiptsResponse { receipts })
 }
 
 async fn treasury(database: Extension<MongoDb>) -> ApiResult<IotaResponse<TreasuryResponse>> {
@@ -428,7 +452,7 @@ async fn milestone(
         .await?
         .ok_or(MissingError::NoResults)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    if accepts_raw(&headers) {
         let protocol_params = database
             .collection::<ProtocolUpdateCollection>()
             .get_protocol_parameters_for_ledger_index(milestone_payload.essence.index)
@@ -459,7 +483,7 @@ async fn milestone_by_index(
         .await?
         .ok_or(MissingError::NoResults)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    if accepts_raw(&headers) {
         let protocol_params = database
             .collection::<ProtocolUpdateCollection>()
             .get_protocol_parameters_for_ledger_index(milestone_payload.essence.index)
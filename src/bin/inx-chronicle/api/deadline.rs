@@ -0,0 +1,27 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use axum::{
+    http::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chronicle::db::mongodb::deadline::with_deadline;
+use tokio::time::Instant;
+
+use super::error::{ApiError, TimeoutError};
+
+/// Bounds the time spent handling a request.
+///
+/// Every database query issued by the handler is given the time left as its `maxTimeMS`, so that the server stops
+/// working on it once the client would no longer receive the result. When the timeout is hit, or the client
+/// disconnects, the handler is dropped along with any open cursors, which are then killed by the driver.
+pub async fn request_deadline<B>(req: Request<B>, next: Next<B>, timeout: Duration) -> Response {
+    let deadline = Instant::now() + timeout;
+    match tokio::time::timeout_at(deadline, with_deadline(deadline, next.run(req))).await {
+        Ok(res) => res,
+        Err(_) => ApiError::from(TimeoutError).into_response(),
+    }
+}
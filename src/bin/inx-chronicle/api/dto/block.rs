@@ -0,0 +1,419 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle::model::{
+    payload::{
+        milestone::MigratedFundsEntry, MilestoneOption, MilestonePayload, Payload, TaggedDataPayload,
+        TransactionEssence, TransactionPayload, TreasuryTransactionPayload,
+    },
+    utxo::{Input, Unlock},
+    Block, BlockId, Signature,
+};
+use serde::Serialize;
+
+use super::{hex_bytes, AddressDto, OutputDto};
+
+/// A block, as it is represented in the core API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockDto {
+    pub protocol_version: u8,
+    pub parents: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<PayloadDto>,
+    pub nonce: String,
+}
+
+impl From<Block> for BlockDto {
+    fn from(value: Block) -> Self {
+        Self {
+            protocol_version: value.protocol_version,
+            parents: value.parents.iter().map(BlockId::to_hex).collect(),
+            payload: value.payload.map(Into::into),
+            nonce: value.nonce.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum PayloadDto {
+    Transaction(Box<TransactionPayloadDto>),
+    Milestone(Box<MilestonePayloadDto>),
+    TreasuryTransaction(Box<TreasuryTransactionPayloadDto>),
+    TaggedData(Box<TaggedDataPayloadDto>),
+}
+
+impl From<Payload> for PayloadDto {
+    fn from(value: Payload) -> Self {
+        match value {
+            Payload::Transaction(p) => Self::Transaction(Box::new((*p).into())),
+            Payload::Milestone(p) => Self::Milestone(Box::new((*p).into())),
+            Payload::TreasuryTransaction(p) => Self::TreasuryTransaction(Box::new((*p).into())),
+            Payload::TaggedData(p) => Self::TaggedData(Box::new((*p).into())),
+        }
+    }
+}
+
+/// A transaction payload, as it is represented in the core API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TransactionPayloadDto {
+    #[serde(rename = "type")]
+    pub kind: u32,
+    pub essence: TransactionEssenceDto,
+    pub unlocks: Vec<UnlockDto>,
+}
+
+impl From<TransactionPayload> for TransactionPayloadDto {
+    fn from(value: TransactionPayload) -> Self {
+        Self {
+            kind: 6,
+            essence: value.essence.into(),
+            unlocks: value.unlocks.into_vec().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum TransactionEssenceDto {
+    Regular {
+        #[serde(rename = "type")]
+        kind: u8,
+        network_id: String,
+        inputs: Vec<InputDto>,
+        inputs_commitment: String,
+        outputs: Vec<OutputDto>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<PayloadDto>,
+    },
+}
+
+impl From<TransactionEssence> for TransactionEssenceDto {
+    fn from(value: TransactionEssence) -> Self {
+        match value {
+            TransactionEssence::Regular {
+                network_id,
+                inputs,
+                inputs_commitment,
+                outputs,
+                payload,
+            } => Self::Regular {
+                kind: 1,
+                network_id: network_id.to_string(),
+                inputs: inputs.into_vec().into_iter().map(Into::into).collect(),
+                inputs_commitment: prefix_hex::encode(inputs_commitment),
+                outputs: outputs.into_vec().into_iter().map(Into::into).collect(),
+                payload: payload.map(Into::into),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum InputDto {
+    Utxo {
+        #[serde(rename = "type")]
+        kind: u8,
+        transaction_id: String,
+        transaction_output_index: u16,
+    },
+    Treasury {
+        #[serde(rename = "type")]
+        kind: u8,
+        milestone_id: String,
+    },
+}
+
+impl From<Input> for InputDto {
+    fn from(value: Input) -> Self {
+        match value {
+            Input::Utxo(output_id) => Self::Utxo {
+                kind: 0,
+                transaction_id: output_id.transaction_id.to_hex(),
+                transaction_output_index: output_id.index,
+            },
+            Input::Treasury { milestone_id } => Self::Treasury {
+                kind: 1,
+                milestone_id: milestone_id.to_hex(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum UnlockDto {
+    Signature {
+        #[serde(rename = "type")]
+        kind: u8,
+        signature: SignatureDto,
+    },
+    /// A reference, alias or nft unlock, which only refer to the unlock at the given index.
+    Reference {
+        #[serde(rename = "type")]
+        kind: u8,
+        reference: u16,
+    },
+}
+
+impl From<Unlock> for UnlockDto {
+    fn from(value: Unlock) -> Self {
+        match value {
+            Unlock::Signature { signature } => Self::Signature {
+                kind: 0,
+                signature: signature.into(),
+            },
+            Unlock::Reference { index } => Self::Reference {
+                kind: 1,
+                reference: index,
+            },
+            Unlock::Alias { index } => Self::Reference {
+                kind: 2,
+                reference: index,
+            },
+            Unlock::Nft { index } => Self::Reference {
+                kind: 3,
+                reference: index,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum SignatureDto {
+    Ed25519 {
+        #[serde(rename = "type")]
+        kind: u8,
+        public_key: String,
+        signature: String,
+    },
+}
+
+impl From<Signature> for SignatureDto {
+    fn from(value: Signature) -> Self {
+        match value {
+            Signature::Ed25519 { public_key, signature } => Self::Ed25519 {
+                kind: 0,
+                public_key: prefix_hex::encode(public_key),
+                signature: prefix_hex::encode(signature),
+            },
+        }
+    }
+}
+
+/// A milestone payload, as it is represented in the core API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MilestonePayloadDto {
+    #[serde(rename = "type")]
+    pub kind: u32,
+    pub index: u32,
+    pub timestamp: u32,
+    pub protocol_version: u8,
+    pub previous_milestone_id: String,
+    pub parents: Vec<String>,
+    pub inclusion_merkle_root: String,
+    pub applied_merkle_root: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<MilestoneOptionDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+    pub signatures: Vec<SignatureDto>,
+}
+
+impl From<MilestonePayload> for MilestonePayloadDto {
+    fn from(value: MilestonePayload) -> Self {
+        let essence = value.essence;
+        Self {
+            kind: 7,
+            index: essence.index.0,
+            timestamp: essence.timestamp.0,
+            protocol_version: essence.protocol_version,
+            previous_milestone_id: essence.previous_milestone_id.to_hex(),
+            parents: essence.parents.iter().map(BlockId::to_hex).collect(),
+            inclusion_merkle_root: prefix_hex::encode(essence.inclusion_merkle_root),
+            applied_merkle_root: prefix_hex::encode(essence.applied_merkle_root),
+            options: essence.options.into_vec().into_iter().map(Into::into).collect(),
+            metadata: hex_bytes(&essence.metadata),
+            signatures: value.signatures.into_vec().into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum MilestoneOptionDto {
+    Receipt(ReceiptDto),
+    #[serde(rename_all = "camelCase")]
+    Parameters {
+        #[serde(rename = "type")]
+        kind: u8,
+        target_milestone_index: u32,
+        protocol_version: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        params: Option<String>,
+    },
+}
+
+impl From<MilestoneOption> for MilestoneOptionDto {
+    fn from(value: MilestoneOption) -> Self {
+        match value {
+            MilestoneOption::Receipt {
+                migrated_at,
+                last,
+                funds,
+                transaction,
+            } => Self::Receipt(ReceiptDto {
+                kind: 0,
+                migrated_at: migrated_at.0,
+                funds: funds.into_vec().into_iter().map(Into::into).collect(),
+                transaction: PayloadDto::TreasuryTransaction(Box::new(transaction.into())),
+                last,
+            }),
+            MilestoneOption::Parameters {
+                target_milestone_index,
+                protocol_version,
+                binary_parameters,
+            } => Self::Parameters {
+                kind: 1,
+                target_milestone_index: target_milestone_index.0,
+                protocol_version,
+                params: hex_bytes(&binary_parameters),
+            },
+        }
+    }
+}
+
+/// A receipt milestone option, which lists the funds migrated from the legacy network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptDto {
+    #[serde(rename = "type")]
+    pub kind: u8,
+    pub migrated_at: u32,
+    pub funds: Vec<MigratedFundsEntryDto>,
+    pub transaction: PayloadDto,
+    #[serde(rename = "final")]
+    pub last: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigratedFundsEntryDto {
+    pub tail_transaction_hash: String,
+    pub address: AddressDto,
+    pub deposit: u64,
+}
+
+impl From<MigratedFundsEntry> for MigratedFundsEntryDto {
+    fn from(value: MigratedFundsEntry) -> Self {
+        Self {
+            tail_transaction_hash: prefix_hex::encode(value.tail_transaction_hash),
+            address: value.address.into(),
+            deposit: value.amount,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TaggedDataPayloadDto {
+    #[serde(rename = "type")]
+    pub kind: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl From<TaggedDataPayload> for TaggedDataPayloadDto {
+    fn from(value: TaggedDataPayload) -> Self {
+        Self {
+            kind: 5,
+            tag: hex_bytes(&value.tag),
+            data: hex_bytes(&value.data),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TreasuryTransactionPayloadDto {
+    #[serde(rename = "type")]
+    pub kind: u32,
+    pub input: InputDto,
+    pub output: OutputDto,
+}
+
+impl From<TreasuryTransactionPayload> for TreasuryTransactionPayloadDto {
+    fn from(value: TreasuryTransactionPayload) -> Self {
+        Self {
+            kind: 4,
+            input: InputDto::Treasury {
+                kind: 1,
+                milestone_id: value.input_milestone_id.to_hex(),
+            },
+            output: OutputDto::Treasury {
+                kind: 2,
+                amount: value.output_amount.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use chronicle::model::payload::MilestoneId;
+    use iota_sdk::types::block::{
+        payload::dto::MilestonePayloadDto as SdkMilestonePayloadDto, BlockDto as SdkBlockDto,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn blocks_match_sdk_format() {
+        let ctx = iota_sdk::types::block::protocol::protocol_parameters();
+        for block in [
+            Block::rand_no_payload(),
+            Block::rand_transaction(&ctx),
+            Block::rand_milestone(&ctx),
+            Block::rand_tagged_data(),
+            Block::rand_treasury_transaction(&ctx),
+        ] {
+            assert_eq!(
+                serde_json::to_value(BlockDto::from(block.clone())).unwrap(),
+                serde_json::to_value(SdkBlockDto::try_from(block).unwrap()).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn milestone_options_match_sdk_format() {
+        let ctx = iota_sdk::types::block::protocol::protocol_parameters();
+        let mut milestone = MilestonePayload::rand(&ctx);
+        milestone.essence.options =
+            vec![MilestoneOption::rand_receipt(&ctx), MilestoneOption::rand_parameters()].into();
+        assert_eq!(
+            serde_json::to_value(MilestonePayloadDto::from(milestone.clone())).unwrap(),
+            serde_json::to_value(SdkMilestonePayloadDto::from(milestone)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn treasury_transaction_format() {
+        let payload = TreasuryTransactionPayload {
+            input_milestone_id: MilestoneId([1; 32]),
+            output_amount: 42,
+        };
+        assert_eq!(
+            serde_json::to_value(TreasuryTransactionPayloadDto::from(payload)).unwrap(),
+            serde_json::json!({
+                "type": 4,
+                "input": { "type": 1, "milestoneId": format!("0x{}", "01".repeat(32)) },
+                "output": { "type": 2, "amount": "42" },
+            }),
+        );
+    }
+}
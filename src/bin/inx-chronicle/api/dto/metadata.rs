@@ -0,0 +1,186 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle::{
+    db::mongodb::collections::OutputMetadataResult,
+    model::{
+        metadata::{BlockMetadata, LedgerInclusionState},
+        tangle::MilestoneIndex,
+        BlockId,
+    },
+};
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LedgerInclusionStateDto {
+    Conflicting,
+    Included,
+    NoTransaction,
+}
+
+impl From<LedgerInclusionState> for LedgerInclusionStateDto {
+    fn from(value: LedgerInclusionState) -> Self {
+        match value {
+            LedgerInclusionState::Conflicting => Self::Conflicting,
+            LedgerInclusionState::Included => Self::Included,
+            LedgerInclusionState::NoTransaction => Self::NoTransaction,
+        }
+    }
+}
+
+/// The metadata of a block, as it is represented in the core API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockMetadataDto {
+    pub block_id: String,
+    pub parents: Vec<String>,
+    pub is_solid: bool,
+    pub referenced_by_milestone_index: u32,
+    pub milestone_index: u32,
+    pub ledger_inclusion_state: LedgerInclusionStateDto,
+    pub conflict_reason: u8,
+    pub white_flag_index: u32,
+    pub should_promote: bool,
+    pub should_reattach: bool,
+}
+
+impl BlockMetadataDto {
+    pub fn new(block_id: BlockId, metadata: BlockMetadata) -> Self {
+        Self {
+            block_id: block_id.to_hex(),
+            parents: metadata.parents.iter().map(BlockId::to_hex).collect(),
+            is_solid: metadata.is_solid,
+            referenced_by_milestone_index: metadata.referenced_by_milestone_index.0,
+            milestone_index: metadata.milestone_index.0,
+            ledger_inclusion_state: metadata.inclusion_state.into(),
+            conflict_reason: metadata.conflict_reason as u8,
+            white_flag_index: metadata.white_flag_index,
+            should_promote: metadata.should_promote,
+            should_reattach: metadata.should_reattach,
+        }
+    }
+}
+
+/// The metadata of an output, as it is represented in the core API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMetadataDto {
+    pub block_id: String,
+    pub transaction_id: String,
+    pub output_index: u16,
+    pub is_spent: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone_index_spent: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone_timestamp_spent: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_id_spent: Option<String>,
+    pub milestone_index_booked: u32,
+    pub milestone_timestamp_booked: u32,
+    pub ledger_index: u32,
+}
+
+impl OutputMetadataDto {
+    pub fn new(metadata: &OutputMetadataResult, ledger_index: MilestoneIndex) -> Self {
+        let spent = metadata.spent_metadata.as_ref();
+        Self {
+            block_id: metadata.block_id.to_hex(),
+            transaction_id: metadata.output_id.transaction_id.to_hex(),
+            output_index: metadata.output_id.index,
+            // A pruned output was spent, even though it is no longer known how.
+            is_spent: spent.is_some() || metadata.pruned_before.is_some(),
+            milestone_index_spent: spent.map(|spent| spent.spent.milestone_index.0),
+            milestone_timestamp_spent: spent.map(|spent| spent.spent.milestone_timestamp.0),
+            transaction_id_spent: spent.map(|spent| spent.transaction_id.to_hex()),
+            milestone_index_booked: metadata.booked.milestone_index.0,
+            milestone_timestamp_booked: metadata.booked.milestone_timestamp.0,
+            ledger_index: ledger_index.0,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use chronicle::model::{
+        metadata::{ConflictReason, SpentMetadata},
+        payload::TransactionId,
+        tangle::MilestoneIndexTimestamp,
+        utxo::OutputId,
+    };
+    use iota_sdk::types::{api::core::response::BlockMetadataResponse, block::output::OutputMetadata};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn block_metadata_matches_sdk_format() {
+        let block_id = BlockId::rand();
+        let metadata = BlockMetadata {
+            parents: BlockId::rand_parents(),
+            is_solid: true,
+            should_promote: false,
+            should_reattach: false,
+            referenced_by_milestone_index: 5.into(),
+            milestone_index: 4.into(),
+            inclusion_state: LedgerInclusionState::NoTransaction,
+            conflict_reason: ConflictReason::InvalidSignature,
+            white_flag_index: 2,
+        };
+        let sdk = BlockMetadataResponse {
+            block_id: block_id.into(),
+            parents: metadata.parents.iter().map(|&id| id.into()).collect(),
+            is_solid: true,
+            referenced_by_milestone_index: Some(5),
+            milestone_index: Some(4),
+            ledger_inclusion_state: Some(LedgerInclusionState::NoTransaction.into()),
+            conflict_reason: Some(5),
+            white_flag_index: Some(2),
+            should_promote: Some(false),
+            should_reattach: Some(false),
+        };
+        assert_eq!(
+            serde_json::to_value(BlockMetadataDto::new(block_id, metadata)).unwrap(),
+            serde_json::to_value(sdk).unwrap(),
+        );
+    }
+
+    #[test]
+    fn output_metadata_matches_sdk_format() {
+        let booked = MilestoneIndexTimestamp {
+            milestone_index: 3.into(),
+            milestone_timestamp: 30.into(),
+        };
+        let spent = SpentMetadata {
+            transaction_id: TransactionId::rand(),
+            spent: MilestoneIndexTimestamp {
+                milestone_index: 4.into(),
+                milestone_timestamp: 40.into(),
+            },
+        };
+        for spent_metadata in [None, Some(spent)] {
+            let metadata = OutputMetadataResult {
+                output_id: OutputId::rand(),
+                block_id: BlockId::rand(),
+                booked,
+                spent_metadata,
+                pruned_before: None,
+            };
+            let sdk = OutputMetadata::new(
+                metadata.block_id.into(),
+                metadata.output_id.try_into().unwrap(),
+                spent_metadata.is_some(),
+                spent_metadata.map(|_| 4),
+                spent_metadata.map(|_| 40),
+                spent_metadata.map(|spent| spent.transaction_id.into()),
+                3,
+                30,
+                6,
+            );
+            assert_eq!(
+                serde_json::to_value(OutputMetadataDto::new(&metadata, 6.into())).unwrap(),
+                serde_json::to_value(sdk).unwrap(),
+            );
+        }
+    }
+}
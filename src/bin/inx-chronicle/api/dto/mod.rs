@@ -0,0 +1,25 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The JSON representation of blocks, outputs and their metadata that the API serves.
+//!
+//! These types are built from the Chronicle model rather than serialized from the SDK types, so that the format of
+//! the responses is owned by Chronicle and does not change along with the SDK. The conversion tests compare them with
+//! the SDK representation that the API was originally modelled after.
+
+mod block;
+mod metadata;
+mod output;
+
+#[cfg(feature = "explorer")]
+pub use self::block::TransactionPayloadDto;
+pub use self::{
+    block::{BlockDto, MilestoneOptionDto, MilestonePayloadDto, ReceiptDto},
+    metadata::{BlockMetadataDto, OutputMetadataDto},
+    output::{AddressDto, OutputDto},
+};
+
+/// Encodes bytes as hex, leaving out empty ones.
+fn hex_bytes(bytes: &[u8]) -> Option<String> {
+    (!bytes.is_empty()).then(|| prefix_hex::encode(bytes))
+}
@@ -0,0 +1,449 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle::model::utxo::{
+    Address, AddressUnlockCondition, AliasOutput, BasicOutput, ExpirationUnlockCondition, Feature, FoundryOutput,
+    GovernorAddressUnlockCondition, ImmutableAliasAddressUnlockCondition, NativeToken, NftOutput, Output,
+    StateControllerAddressUnlockCondition, StorageDepositReturnUnlockCondition, TimelockUnlockCondition, TokenScheme,
+    TreasuryOutput,
+};
+use primitive_types::U256;
+use serde::Serialize;
+
+use super::hex_bytes;
+
+/// An address, as it is represented in the core API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum AddressDto {
+    Ed25519 {
+        #[serde(rename = "type")]
+        kind: u8,
+        pub_key_hash: String,
+    },
+    Alias {
+        #[serde(rename = "type")]
+        kind: u8,
+        alias_id: String,
+    },
+    Nft {
+        #[serde(rename = "type")]
+        kind: u8,
+        nft_id: String,
+    },
+}
+
+impl From<Address> for AddressDto {
+    fn from(value: Address) -> Self {
+        match value {
+            Address::Ed25519(a) => Self::Ed25519 {
+                kind: 0,
+                pub_key_hash: prefix_hex::encode(a.0),
+            },
+            Address::Alias(a) => Self::Alias {
+                kind: 8,
+                alias_id: prefix_hex::encode(a.0 .0),
+            },
+            Address::Nft(a) => Self::Nft {
+                kind: 16,
+                nft_id: prefix_hex::encode(a.0 .0),
+            },
+        }
+    }
+}
+
+/// An amount of native tokens held by an output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct NativeTokenDto {
+    pub id: String,
+    pub amount: String,
+}
+
+impl From<NativeToken> for NativeTokenDto {
+    fn from(value: NativeToken) -> Self {
+        Self {
+            id: prefix_hex::encode(value.token_id.0),
+            amount: prefix_hex::encode(U256::from(value.amount)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum TokenSchemeDto {
+    Simple {
+        #[serde(rename = "type")]
+        kind: u8,
+        minted_tokens: String,
+        melted_tokens: String,
+        maximum_supply: String,
+    },
+}
+
+impl From<TokenScheme> for TokenSchemeDto {
+    fn from(value: TokenScheme) -> Self {
+        match value {
+            TokenScheme::Simple {
+                minted_tokens,
+                melted_tokens,
+                maximum_supply,
+            } => Self::Simple {
+                kind: 0,
+                minted_tokens: prefix_hex::encode(U256::from(minted_tokens)),
+                melted_tokens: prefix_hex::encode(U256::from(melted_tokens)),
+                maximum_supply: prefix_hex::encode(U256::from(maximum_supply)),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum UnlockConditionDto {
+    Address {
+        #[serde(rename = "type")]
+        kind: u8,
+        address: AddressDto,
+    },
+    StorageDepositReturn {
+        #[serde(rename = "type")]
+        kind: u8,
+        return_address: AddressDto,
+        amount: String,
+    },
+    Timelock {
+        #[serde(rename = "type")]
+        kind: u8,
+        unix_time: u32,
+    },
+    Expiration {
+        #[serde(rename = "type")]
+        kind: u8,
+        return_address: AddressDto,
+        unix_time: u32,
+    },
+    StateControllerAddress {
+        #[serde(rename = "type")]
+        kind: u8,
+        address: AddressDto,
+    },
+    GovernorAddress {
+        #[serde(rename = "type")]
+        kind: u8,
+        address: AddressDto,
+    },
+    ImmutableAliasAddress {
+        #[serde(rename = "type")]
+        kind: u8,
+        address: AddressDto,
+    },
+}
+
+impl From<AddressUnlockCondition> for UnlockConditionDto {
+    fn from(value: AddressUnlockCondition) -> Self {
+        Self::Address {
+            kind: 0,
+            address: value.address.into(),
+        }
+    }
+}
+
+impl From<StorageDepositReturnUnlockCondition> for UnlockConditionDto {
+    fn from(value: StorageDepositReturnUnlockCondition) -> Self {
+        Self::StorageDepositReturn {
+            kind: 1,
+            return_address: value.return_address.into(),
+            amount: value.amount.0.to_string(),
+        }
+    }
+}
+
+impl From<TimelockUnlockCondition> for UnlockConditionDto {
+    fn from(value: TimelockUnlockCondition) -> Self {
+        Self::Timelock {
+            kind: 2,
+            unix_time: value.timestamp.0,
+        }
+    }
+}
+
+impl From<ExpirationUnlockCondition> for UnlockConditionDto {
+    fn from(value: ExpirationUnlockCondition) -> Self {
+        Self::Expiration {
+            kind: 3,
+            return_address: value.return_address.into(),
+            unix_time: value.timestamp.0,
+        }
+    }
+}
+
+impl From<StateControllerAddressUnlockCondition> for UnlockConditionDto {
+    fn from(value: StateControllerAddressUnlockCondition) -> Self {
+        Self::StateControllerAddress {
+            kind: 4,
+            address: value.address.into(),
+        }
+    }
+}
+
+impl From<GovernorAddressUnlockCondition> for UnlockConditionDto {
+    fn from(value: GovernorAddressUnlockCondition) -> Self {
+        Self::GovernorAddress {
+            kind: 5,
+            address: value.address.into(),
+        }
+    }
+}
+
+impl From<ImmutableAliasAddressUnlockCondition> for UnlockConditionDto {
+    fn from(value: ImmutableAliasAddressUnlockCondition) -> Self {
+        Self::ImmutableAliasAddress {
+            kind: 6,
+            address: value.address.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FeatureDto {
+    Sender {
+        #[serde(rename = "type")]
+        kind: u8,
+        address: AddressDto,
+    },
+    Issuer {
+        #[serde(rename = "type")]
+        kind: u8,
+        address: AddressDto,
+    },
+    Metadata {
+        #[serde(rename = "type")]
+        kind: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        data: Option<String>,
+    },
+    Tag {
+        #[serde(rename = "type")]
+        kind: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+    },
+}
+
+impl From<Feature> for FeatureDto {
+    fn from(value: Feature) -> Self {
+        match value {
+            Feature::Sender { address } => Self::Sender {
+                kind: 0,
+                address: address.into(),
+            },
+            Feature::Issuer { address } => Self::Issuer {
+                kind: 1,
+                address: address.into(),
+            },
+            Feature::Metadata { data } => Self::Metadata {
+                kind: 2,
+                data: hex_bytes(&data),
+            },
+            Feature::Tag { data } => Self::Tag {
+                kind: 3,
+                tag: hex_bytes(&data),
+            },
+        }
+    }
+}
+
+/// An output, as it is represented in the core API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum OutputDto {
+    Treasury {
+        #[serde(rename = "type")]
+        kind: u8,
+        amount: String,
+    },
+    Basic {
+        #[serde(rename = "type")]
+        kind: u8,
+        amount: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        native_tokens: Vec<NativeTokenDto>,
+        unlock_conditions: Vec<UnlockConditionDto>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        features: Vec<FeatureDto>,
+    },
+    Alias {
+        #[serde(rename = "type")]
+        kind: u8,
+        amount: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        native_tokens: Vec<NativeTokenDto>,
+        alias_id: String,
+        state_index: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        state_metadata: Option<String>,
+        foundry_counter: u32,
+        unlock_conditions: Vec<UnlockConditionDto>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        features: Vec<FeatureDto>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        immutable_features: Vec<FeatureDto>,
+    },
+    Foundry {
+        #[serde(rename = "type")]
+        kind: u8,
+        amount: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        native_tokens: Vec<NativeTokenDto>,
+        serial_number: u32,
+        token_scheme: TokenSchemeDto,
+        unlock_conditions: Vec<UnlockConditionDto>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        features: Vec<FeatureDto>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        immutable_features: Vec<FeatureDto>,
+    },
+    Nft {
+        #[serde(rename = "type")]
+        kind: u8,
+        amount: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        native_tokens: Vec<NativeTokenDto>,
+        nft_id: String,
+        unlock_conditions: Vec<UnlockConditionDto>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        features: Vec<FeatureDto>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        immutable_features: Vec<FeatureDto>,
+    },
+}
+
+fn convert<T, U: From<T>>(values: Box<[T]>) -> Vec<U> {
+    values.into_vec().into_iter().map(Into::into).collect()
+}
+
+impl From<TreasuryOutput> for OutputDto {
+    fn from(value: TreasuryOutput) -> Self {
+        Self::Treasury {
+            kind: 2,
+            amount: value.amount.0.to_string(),
+        }
+    }
+}
+
+impl From<BasicOutput> for OutputDto {
+    fn from(value: BasicOutput) -> Self {
+        let mut unlock_conditions = vec![value.address_unlock_condition.into()];
+        unlock_conditions.extend(value.storage_deposit_return_unlock_condition.map(Into::into));
+        unlock_conditions.extend(value.timelock_unlock_condition.map(Into::into));
+        unlock_conditions.extend(value.expiration_unlock_condition.map(Into::into));
+        Self::Basic {
+            kind: 3,
+            amount: value.amount.0.to_string(),
+            native_tokens: convert(value.native_tokens),
+            unlock_conditions,
+            features: convert(value.features),
+        }
+    }
+}
+
+impl From<AliasOutput> for OutputDto {
+    fn from(value: AliasOutput) -> Self {
+        Self::Alias {
+            kind: 4,
+            amount: value.amount.0.to_string(),
+            native_tokens: convert(value.native_tokens),
+            alias_id: prefix_hex::encode(value.alias_id.0),
+            state_index: value.state_index,
+            state_metadata: hex_bytes(&value.state_metadata),
+            foundry_counter: value.foundry_counter,
+            unlock_conditions: vec![
+                value.state_controller_address_unlock_condition.into(),
+                value.governor_address_unlock_condition.into(),
+            ],
+            features: convert(value.features),
+            immutable_features: convert(value.immutable_features),
+        }
+    }
+}
+
+impl From<FoundryOutput> for OutputDto {
+    fn from(value: FoundryOutput) -> Self {
+        Self::Foundry {
+            kind: 5,
+            amount: value.amount.0.to_string(),
+            native_tokens: convert(value.native_tokens),
+            serial_number: value.serial_number,
+            token_scheme: value.token_scheme.into(),
+            unlock_conditions: vec![value.immutable_alias_address_unlock_condition.into()],
+            features: convert(value.features),
+            immutable_features: convert(value.immutable_features),
+        }
+    }
+}
+
+impl From<NftOutput> for OutputDto {
+    fn from(value: NftOutput) -> Self {
+        let mut unlock_conditions = vec![value.address_unlock_condition.into()];
+        unlock_conditions.extend(value.storage_deposit_return_unlock_condition.map(Into::into));
+        unlock_conditions.extend(value.timelock_unlock_condition.map(Into::into));
+        unlock_conditions.extend(value.expiration_unlock_condition.map(Into::into));
+        Self::Nft {
+            kind: 6,
+            amount: value.amount.0.to_string(),
+            native_tokens: convert(value.native_tokens),
+            nft_id: prefix_hex::encode(value.nft_id.0),
+            unlock_conditions,
+            features: convert(value.features),
+            immutable_features: convert(value.immutable_features),
+        }
+    }
+}
+
+impl From<Output> for OutputDto {
+    fn from(value: Output) -> Self {
+        match value {
+            Output::Treasury(o) => o.into(),
+            Output::Basic(o) => o.into(),
+            Output::Alias(o) => o.into(),
+            Output::Foundry(o) => o.into(),
+            Output::Nft(o) => o.into(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use chronicle::model::utxo::Ed25519Address;
+    use iota_sdk::types::block::output::dto::OutputDto as SdkOutputDto;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn outputs_match_sdk_format() {
+        let ctx = iota_sdk::types::block::protocol::protocol_parameters();
+        for output in [
+            Output::rand_treasury(&ctx),
+            Output::rand_basic(&ctx),
+            Output::rand_alias(&ctx),
+            Output::rand_foundry(&ctx),
+            Output::rand_nft(&ctx),
+        ] {
+            assert_eq!(
+                serde_json::to_value(OutputDto::from(output.clone())).unwrap(),
+                serde_json::to_value(SdkOutputDto::try_from(output).unwrap()).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn address_format() {
+        let address = Address::Ed25519(Ed25519Address([0xab; 32]));
+        assert_eq!(
+            serde_json::to_value(AddressDto::from(address)).unwrap(),
+            serde_json::json!({ "type": 0, "pubKeyHash": format!("0x{}", "ab".repeat(32)) }),
+        );
+    }
+}
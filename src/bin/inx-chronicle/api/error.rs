@@ -4,10 +4,13 @@
 use std::{num::ParseIntError, str::ParseBoolError};
 
 use axum::{
-    extract::rejection::{QueryRejection, TypedHeaderRejection},
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection, TypedHeaderRejection},
     response::IntoResponse,
 };
-use chronicle::db::mongodb::collections::ParseSortError;
+use chronicle::{
+    db::mongodb::collections::{ParseAmountOrderError, ParseSortError, ParseSortKeyError},
+    model::tangle::MilestoneIndex,
+};
 use hyper::{header::InvalidHeaderValue, StatusCode};
 use serde::Serialize;
 use thiserror::Error;
@@ -56,26 +59,59 @@ macro_rules! impl_internal_error {
 }
 
 impl_internal_error!(
-    mongodb::error::Error,
     axum::extract::rejection::ExtensionRejection,
     auth_helper::jwt::Error,
     argon2::Error,
-    iota_sdk::types::block::Error
+    iota_sdk::types::block::Error,
+    serde_json::Error,
+    tracing_subscriber::reload::Error
 );
 
-impl IntoResponse for ApiError {
-    fn into_response(self) -> axum::response::Response {
-        // Hide internal errors from the client, but print them to the server.
-        let message = if self.code == StatusCode::INTERNAL_SERVER_ERROR {
+#[cfg(feature = "search")]
+impl_internal_error!(crate::search::SearchError);
+
+/// The server error code of a query that exceeded its `maxTimeMS`.
+const MAX_TIME_MS_EXPIRED_CODE: i32 = 50;
+
+impl From<mongodb::error::Error> for ApiError {
+    fn from(error: mongodb::error::Error) -> Self {
+        match &*error.kind {
+            mongodb::error::ErrorKind::Command(e) if e.code == MAX_TIME_MS_EXPIRED_CODE => TimeoutError.into(),
+            _ => Self {
+                code: StatusCode::INTERNAL_SERVER_ERROR,
+                error: Box::new(error) as _,
+            },
+        }
+    }
+}
+
+impl ApiError {
+    /// The message that is shown to the client. Internal errors are hidden from the client, but printed to the server.
+    fn client_message(&self) -> String {
+        if self.code == StatusCode::INTERNAL_SERVER_ERROR {
             tracing::error!("Internal API error: {}", self.error);
             "internal server error".to_string()
         } else {
             self.error.to_string()
-        };
+        }
+    }
+
+    /// Converts the error into a GraphQL error, which carries the status code in its `code` extension.
+    #[cfg(feature = "graphql")]
+    pub fn into_graphql_error(self) -> async_graphql::Error {
+        use async_graphql::ErrorExtensions;
+
+        let code = self.code.as_u16();
+        async_graphql::Error::new(self.client_message()).extend_with(|_, extensions| extensions.set("code", code))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
         ErrorBody {
             status: self.code,
             code: self.code.as_u16(),
-            message,
+            message: self.client_message(),
         }
         .into_response()
     }
@@ -133,6 +169,42 @@ impl IntoResponse for UnimplementedError {
     }
 }
 
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+#[error("request timed out")]
+pub struct TimeoutError;
+
+impl ErrorStatus for TimeoutError {
+    fn status(&self) -> StatusCode {
+        StatusCode::GATEWAY_TIMEOUT
+    }
+}
+
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+#[error("ledger index {current} did not reach the requested minimum of {required} in time")]
+pub struct LaggingLedgerError {
+    pub required: MilestoneIndex,
+    pub current: MilestoneIndex,
+}
+
+impl ErrorStatus for LaggingLedgerError {
+    fn status(&self) -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+#[error("too many requests, try again later")]
+pub struct SaturatedError;
+
+impl ErrorStatus for SaturatedError {
+    fn status(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
 #[derive(Error, Debug)]
 #[allow(missing_docs)]
 pub enum MissingError {
@@ -159,10 +231,40 @@ pub enum RequestError {
     #[error("invalid cursor")]
     BadPagingState,
     #[error("invalid time range")]
+    #[cfg_attr(not(any(feature = "explorer", feature = "inx")), allow(dead_code))]
     BadTimeRange,
+    #[cfg(feature = "explorer")]
+    #[error("interval must be at least one milestone")]
+    BadInterval,
+    #[error("invalid field selection: {0}")]
+    BadFields(String),
+    #[cfg(feature = "explorer")]
+    #[error("between 1 and {0} balance queries must be provided")]
+    BalanceQueryCount(usize),
+    #[error("between 1 and {0} output ids must be provided")]
+    OutputIdCount(usize),
+    #[cfg(feature = "search")]
+    #[error("search query must contain between 1 and {0} characters")]
+    BadSearchQuery(usize),
+    #[cfg(feature = "explorer")]
+    #[error("exactly one of `timestamp` and `milestoneIndex` must be provided")]
+    ConversionInput,
+    #[cfg(feature = "explorer")]
+    #[error("milestone index {0} is beyond the ledger index {1}")]
+    FutureMilestone(MilestoneIndex, MilestoneIndex),
+    #[error("`min_ledger_index` requires the strict consistency mode")]
+    ConsistencyDisabled,
+    #[cfg(feature = "explorer")]
+    #[error("exactly one of `payload` and `essence` must be provided")]
+    TransactionInput,
+    #[cfg(feature = "explorer")]
+    #[error("invalid transaction bytes: {0}")]
+    TransactionBytes(String),
 
     #[error("invalid IOTA Stardust data: {0}")]
     IotaStardust(#[from] iota_sdk::types::block::Error),
+    #[error("invalid duration provided: {0}")]
+    Duration(#[from] humantime::DurationError),
     #[error("invalid bool value provided: {0}")]
     Bool(#[from] ParseBoolError),
     #[error("invalid U256 value provided: {0}")]
@@ -173,13 +275,25 @@ pub enum RequestError {
     Int(#[from] ParseIntError),
     #[error("invalid authorization header provided: {0}")]
     InvalidAuthHeader(#[from] TypedHeaderRejection),
+    #[error("invalid path parameters provided: {0}")]
+    InvalidPathParams(#[from] PathRejection),
     #[error("invalid query parameters provided: {0}")]
     InvalidQueryParams(#[from] QueryRejection),
+    #[error("invalid request body provided: {0}")]
+    InvalidJsonBody(#[from] JsonRejection),
     #[cfg(feature = "poi")]
     #[error(transparent)]
     PoI(#[from] crate::api::poi::RequestError),
     #[error("invalid sort order provided: {0}")]
     SortOrder(#[from] ParseSortError),
+    #[error("invalid sort key provided: {0}")]
+    SortKey(#[from] ParseSortKeyError),
+    #[error("invalid amount order provided: {0}")]
+    AmountOrder(#[from] ParseAmountOrderError),
+    #[error("`{0}` cannot be used when sorting by `{1}`")]
+    SortParameter(&'static str, &'static str),
+    #[error("invalid log filter directives provided: {0}")]
+    LogFilterDirectives(#[from] tracing_subscriber::filter::ParseError),
 }
 
 impl ErrorStatus for RequestError {
@@ -200,6 +314,12 @@ pub enum ConfigError {
     Jwt(#[from] argon2::Error),
     #[error("invalid secret key: {0}")]
     SecretKey(#[from] super::secret_key::SecretKeyError),
+    #[error("invalid date: {0}")]
+    Date(#[from] humantime::TimestampError),
+    #[error("invalid TLS config: {0}")]
+    Tls(#[from] super::tls::TlsError),
+    #[error("failed to open the API audit log: {0}")]
+    AuditLog(#[from] std::io::Error),
 }
 
 #[derive(Clone, Debug, Serialize)]
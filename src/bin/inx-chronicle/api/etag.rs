@@ -0,0 +1,109 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{
+    extract::OriginalUri,
+    http::{
+        header::{ETAG, IF_NONE_MATCH},
+        HeaderValue, Method, Request, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chronicle::{
+    db::{mongodb::collections::MilestoneCollection, MongoDb},
+    model::tangle::MilestoneIndex,
+};
+use crypto::hashes::{blake2b::Blake2b256, Digest};
+
+use super::{config::ApiConfigData, error::ApiError};
+
+/// Handles `If-None-Match` for responses that only change with the ledger.
+///
+/// The entity tag is derived from the current ledger index and the request uri, so a client that revalidates a
+/// response gets a `304 Not Modified` without the handler running, as long as no milestone was synced in between.
+/// The tag is weak, because the same response may be sent with different content encodings.
+pub async fn conditional<B>(req: Request<B>, next: Next<B>) -> Response {
+    let enabled = req
+        .extensions()
+        .get::<ApiConfigData>()
+        .map_or(false, |config| config.etag_enabled);
+    let db = match req
+        .extensions()
+        .get::<MongoDb>()
+        .filter(|_| enabled && req.method() == Method::GET)
+    {
+        Some(db) => db,
+        None => return next.run(req).await,
+    };
+    let ledger_index = match db.collection::<MilestoneCollection>().get_ledger_index().await {
+        Ok(Some(ledger_index)) => ledger_index,
+        Ok(None) => return next.run(req).await,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+    let uri = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| req.uri().to_string(), |uri| uri.0.to_string());
+    let etag = entity_tag(ledger_index, &uri);
+
+    if req
+        .headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| matches(value, &etag))
+    {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response();
+    }
+
+    let mut res = next.run(req).await;
+    if res.status() == StatusCode::OK {
+        res.headers_mut().insert(ETAG, etag);
+    }
+    res
+}
+
+/// Derives a weak entity tag from the ledger index and the request uri.
+fn entity_tag(ledger_index: MilestoneIndex, uri: &str) -> HeaderValue {
+    let hash = Blake2b256::new()
+        .chain_update(ledger_index.0.to_le_bytes())
+        .chain_update(uri.as_bytes())
+        .finalize();
+    // Unwrap: hex digits are always a valid header value.
+    HeaderValue::from_str(&format!("W/\"{}\"", hex::encode(&hash[..16]))).unwrap()
+}
+
+/// Checks whether an `If-None-Match` header value matches the entity tag, using the weak comparison.
+fn matches(if_none_match: &str, etag: &HeaderValue) -> bool {
+    let etag = etag.to_str().unwrap_or_default().trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weak_comparison() {
+        let etag = entity_tag(MilestoneIndex(5), "/api/explorer/v2/milestones?pageSize=10");
+        let value = etag.to_str().unwrap().to_string();
+        assert!(value.starts_with("W/\""));
+        assert!(matches(&value, &etag));
+        assert!(matches(value.trim_start_matches("W/"), &etag));
+        assert!(matches(&format!("\"other\", {value}"), &etag));
+        assert!(matches("*", &etag));
+        assert!(!matches("\"other\"", &etag));
+
+        assert_ne!(
+            etag,
+            entity_tag(MilestoneIndex(6), "/api/explorer/v2/milestones?pageSize=10")
+        );
+        assert_ne!(
+            etag,
+            entity_tag(MilestoneIndex(5), "/api/explorer/v2/milestones?pageSize=11")
+        );
+    }
+}
@@ -1,23 +1,29 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, str::FromStr, time::Duration};
 
 use async_trait::async_trait;
 use axum::{
+    body::HttpBody,
     extract::{FromRequest, Query},
-    Extension,
+    BoxError, Extension, Json,
 };
 use chronicle::{
     db::mongodb::collections::SortOrder,
     model::{
+        payload::TransactionId,
         tangle::{MilestoneIndex, MilestoneTimestamp},
         utxo::OutputId,
     },
 };
 use serde::Deserialize;
 
-use crate::api::{config::ApiConfigData, error::RequestError, ApiError, DEFAULT_PAGE_SIZE};
+use super::graph::GraphFormat;
+use crate::{
+    api::{config::ApiConfigData, error::RequestError, ApiError, DEFAULT_PAGE_SIZE},
+    storage_forecast::config as storage_forecast,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LedgerUpdatesByAddressPagination {
@@ -111,34 +117,36 @@ impl<B: Send> FromRequest<B> for LedgerUpdatesByAddressPagination {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct LedgerUpdatesByMilestonePagination {
+pub struct OutputHistoryPagination {
     pub page_size: usize,
-    pub cursor: Option<(OutputId, bool)>,
+    pub sort: SortOrder,
+    pub cursor: Option<(MilestoneIndex, OutputId)>,
 }
 
 #[derive(Clone, Deserialize, Default)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct LedgerUpdatesByMilestonePaginationQuery {
+pub struct OutputHistoryPaginationQuery {
     pub page_size: Option<usize>,
+    pub sort: Option<String>,
     pub cursor: Option<String>,
 }
 
 #[derive(Clone)]
-pub struct LedgerUpdatesByMilestoneCursor {
+pub struct OutputHistoryCursor {
+    pub milestone_index: MilestoneIndex,
     pub output_id: OutputId,
-    pub is_spent: bool,
     pub page_size: usize,
 }
 
-impl FromStr for LedgerUpdatesByMilestoneCursor {
+impl FromStr for OutputHistoryCursor {
     type Err = ApiError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<_> = s.split('.').collect();
         Ok(match parts[..] {
-            [o, sp, ps] => LedgerUpdatesByMilestoneCursor {
+            [ms, o, ps] => OutputHistoryCursor {
+                milestone_index: ms.parse().map_err(RequestError::from)?,
                 output_id: o.parse().map_err(RequestError::from)?,
-                is_spent: sp.parse().map_err(RequestError::from)?,
                 page_size: ps.parse().map_err(RequestError::from)?,
             },
             _ => return Err(ApiError::from(RequestError::BadPagingState)),
@@ -146,68 +154,80 @@ impl FromStr for LedgerUpdatesByMilestoneCursor {
     }
 }
 
-impl Display for LedgerUpdatesByMilestoneCursor {
+impl Display for OutputHistoryCursor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}.{}", self.output_id.to_hex(), self.is_spent, self.page_size)
+        write!(
+            f,
+            "{}.{}.{}",
+            self.milestone_index,
+            self.output_id.to_hex(),
+            self.page_size
+        )
     }
 }
 
 #[async_trait]
-impl<B: Send> FromRequest<B> for LedgerUpdatesByMilestonePagination {
+impl<B: Send> FromRequest<B> for OutputHistoryPagination {
     type Rejection = ApiError;
 
     async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let Query(query) = Query::<LedgerUpdatesByMilestonePaginationQuery>::from_request(req)
+        let Query(query) = Query::<OutputHistoryPaginationQuery>::from_request(req)
             .await
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
+        let sort = query
+            .sort
+            .as_deref()
+            .map_or(Ok(Default::default()), str::parse)
+            .map_err(RequestError::SortOrder)?;
+
         let (page_size, cursor) = if let Some(cursor) = query.cursor {
-            let cursor: LedgerUpdatesByMilestoneCursor = cursor.parse()?;
-            (cursor.page_size, Some((cursor.output_id, cursor.is_spent)))
+            let cursor: OutputHistoryCursor = cursor.parse()?;
+            (cursor.page_size, Some((cursor.milestone_index, cursor.output_id)))
         } else {
             (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
         };
 
-        Ok(LedgerUpdatesByMilestonePagination {
+        Ok(OutputHistoryPagination {
             page_size: page_size.min(config.max_page_size),
             cursor,
+            sort,
         })
     }
 }
 
-pub struct MilestonesPagination {
-    pub start_timestamp: Option<MilestoneTimestamp>,
-    pub end_timestamp: Option<MilestoneTimestamp>,
-    pub sort: SortOrder,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressTransactionsPagination {
     pub page_size: usize,
-    pub cursor: Option<MilestoneIndex>,
+    pub sort: SortOrder,
+    pub cursor: Option<(MilestoneIndex, TransactionId)>,
 }
 
 #[derive(Clone, Deserialize, Default)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct MilestonesPaginationQuery {
-    pub start_timestamp: Option<u32>,
-    pub end_timestamp: Option<u32>,
-    pub sort: Option<String>,
+pub struct AddressTransactionsPaginationQuery {
     pub page_size: Option<usize>,
+    pub sort: Option<String>,
     pub cursor: Option<String>,
 }
 
 #[derive(Clone)]
-pub struct MilestonesCursor {
+pub struct AddressTransactionsCursor {
     pub milestone_index: MilestoneIndex,
+    pub transaction_id: TransactionId,
     pub page_size: usize,
 }
 
-impl FromStr for MilestonesCursor {
+impl FromStr for AddressTransactionsCursor {
     type Err = ApiError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<_> = s.split('.').collect();
         Ok(match parts[..] {
-            [m, ps] => MilestonesCursor {
-                milestone_index: m.parse().map_err(RequestError::from)?,
+            [ms, t, ps] => AddressTransactionsCursor {
+                milestone_index: ms.parse().map_err(RequestError::from)?,
+                transaction_id: t.parse().map_err(RequestError::from)?,
                 page_size: ps.parse().map_err(RequestError::from)?,
             },
             _ => return Err(ApiError::from(RequestError::BadPagingState)),
@@ -215,26 +235,28 @@ impl FromStr for MilestonesCursor {
     }
 }
 
-impl Display for MilestonesCursor {
+impl Display for AddressTransactionsCursor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}", self.milestone_index, self.page_size)
+        write!(
+            f,
+            "{}.{}.{}",
+            self.milestone_index,
+            self.transaction_id.to_hex(),
+            self.page_size
+        )
     }
 }
 
 #[async_trait]
-impl<B: Send> FromRequest<B> for MilestonesPagination {
+impl<B: Send> FromRequest<B> for AddressTransactionsPagination {
     type Rejection = ApiError;
 
     async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let Query(query) = Query::<MilestonesPaginationQuery>::from_request(req)
+        let Query(query) = Query::<AddressTransactionsPaginationQuery>::from_request(req)
             .await
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
-        if matches!((query.start_timestamp, query.end_timestamp), (Some(start), Some(end)) if end < start) {
-            return Err(ApiError::from(RequestError::BadTimeRange));
-        }
-
         let sort = query
             .sort
             .as_deref()
@@ -242,122 +264,209 @@ impl<B: Send> FromRequest<B> for MilestonesPagination {
             .map_err(RequestError::SortOrder)?;
 
         let (page_size, cursor) = if let Some(cursor) = query.cursor {
-            let cursor: MilestonesCursor = cursor.parse()?;
-            (cursor.page_size, Some(cursor.milestone_index))
+            let cursor: AddressTransactionsCursor = cursor.parse()?;
+            (cursor.page_size, Some((cursor.milestone_index, cursor.transaction_id)))
         } else {
             (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
         };
 
-        Ok(MilestonesPagination {
-            start_timestamp: query.start_timestamp.map(Into::into),
-            end_timestamp: query.end_timestamp.map(Into::into),
-            sort,
+        Ok(AddressTransactionsPagination {
             page_size: page_size.min(config.max_page_size),
             cursor,
+            sort,
         })
     }
 }
 
-const DEFAULT_TOP_RICHLIST: usize = 100;
+/// An hour of milestones at the usual rate of one milestone every ten seconds.
+pub const DEFAULT_BALANCE_HISTORY_INTERVAL: u32 = 360;
 
-#[derive(Clone, Deserialize)]
-#[serde(default, deny_unknown_fields)]
-pub struct RichestAddressesQuery {
-    pub top: usize,
-    pub ledger_index: Option<MilestoneIndex>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceHistoryPagination {
+    /// The number of milestones in a range.
+    pub interval: u32,
+    /// The first milestone of the first range that is returned, which is a multiple of the interval.
+    pub start_index: MilestoneIndex,
+    pub end_index: Option<MilestoneIndex>,
+    pub page_size: usize,
 }
 
-impl Default for RichestAddressesQuery {
-    fn default() -> Self {
-        Self {
-            top: DEFAULT_TOP_RICHLIST,
-            ledger_index: None,
-        }
+#[derive(Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct BalanceHistoryPaginationQuery {
+    pub interval: Option<u32>,
+    pub start_index: Option<MilestoneIndex>,
+    pub end_index: Option<MilestoneIndex>,
+    pub page_size: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct BalanceHistoryCursor {
+    pub start_index: MilestoneIndex,
+    pub interval: u32,
+    pub page_size: usize,
+}
+
+impl FromStr for BalanceHistoryCursor {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split('.').collect();
+        Ok(match parts[..] {
+            [ms, i, ps] => BalanceHistoryCursor {
+                start_index: ms.parse().map_err(RequestError::from)?,
+                interval: i.parse().map_err(RequestError::from)?,
+                page_size: ps.parse().map_err(RequestError::from)?,
+            },
+            _ => return Err(ApiError::from(RequestError::BadPagingState)),
+        })
+    }
+}
+
+impl Display for BalanceHistoryCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.start_index, self.interval, self.page_size)
     }
 }
 
 #[async_trait]
-impl<B: Send> FromRequest<B> for RichestAddressesQuery {
+impl<B: Send> FromRequest<B> for BalanceHistoryPagination {
     type Rejection = ApiError;
 
     async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let Query(mut query) = Query::<RichestAddressesQuery>::from_request(req)
+        let Query(query) = Query::<BalanceHistoryPaginationQuery>::from_request(req)
             .await
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
-        query.top = query.top.min(config.max_page_size);
-        Ok(query)
+
+        let (start_index, interval, page_size) = if let Some(cursor) = query.cursor {
+            let cursor: BalanceHistoryCursor = cursor.parse()?;
+            (cursor.start_index, cursor.interval, cursor.page_size)
+        } else {
+            let interval = query.interval.unwrap_or(DEFAULT_BALANCE_HISTORY_INTERVAL);
+            let start_index = query.start_index.unwrap_or_default();
+            // The range that contains the start index is the first one returned.
+            (
+                (start_index.0 - start_index.0 % interval.max(1)).into(),
+                interval,
+                query.page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+            )
+        };
+        if interval == 0 {
+            return Err(ApiError::from(RequestError::BadInterval));
+        }
+        if matches!(query.end_index, Some(end_index) if end_index < start_index) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+
+        Ok(BalanceHistoryPagination {
+            interval,
+            start_index,
+            end_index: query.end_index,
+            page_size: page_size.min(config.max_page_size),
+        })
     }
 }
 
-#[derive(Copy, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerUpdatesByMilestonePagination {
+    pub page_size: usize,
+    pub cursor: Option<(OutputId, bool)>,
+}
+
+#[derive(Clone, Deserialize, Default)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct LedgerIndex {
-    pub ledger_index: Option<MilestoneIndex>,
+pub struct LedgerUpdatesByMilestonePaginationQuery {
+    pub page_size: Option<usize>,
+    pub cursor: Option<String>,
 }
 
-#[async_trait]
-impl<B: Send> FromRequest<B> for LedgerIndex {
-    type Rejection = ApiError;
+#[derive(Clone)]
+pub struct LedgerUpdatesByMilestoneCursor {
+    pub output_id: OutputId,
+    pub is_spent: bool,
+    pub page_size: usize,
+}
 
-    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let Query(query) = Query::<LedgerIndex>::from_request(req)
-            .await
-            .map_err(RequestError::from)?;
-        Ok(query)
+impl FromStr for LedgerUpdatesByMilestoneCursor {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split('.').collect();
+        Ok(match parts[..] {
+            [o, sp, ps] => LedgerUpdatesByMilestoneCursor {
+                output_id: o.parse().map_err(RequestError::from)?,
+                is_spent: sp.parse().map_err(RequestError::from)?,
+                page_size: ps.parse().map_err(RequestError::from)?,
+            },
+            _ => return Err(ApiError::from(RequestError::BadPagingState)),
+        })
     }
 }
 
-#[derive(Copy, Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct MilestoneRange {
-    pub start_index: Option<MilestoneIndex>,
-    pub end_index: Option<MilestoneIndex>,
+impl Display for LedgerUpdatesByMilestoneCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.output_id.to_hex(), self.is_spent, self.page_size)
+    }
 }
 
 #[async_trait]
-impl<B: Send> FromRequest<B> for MilestoneRange {
+impl<B: Send> FromRequest<B> for LedgerUpdatesByMilestonePagination {
     type Rejection = ApiError;
 
     async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let Query(MilestoneRange { start_index, end_index }) = Query::<MilestoneRange>::from_request(req)
+        let Query(query) = Query::<LedgerUpdatesByMilestonePaginationQuery>::from_request(req)
             .await
             .map_err(RequestError::from)?;
-        if matches!((start_index, end_index), (Some(start), Some(end)) if end < start) {
-            return Err(ApiError::from(RequestError::BadTimeRange));
-        }
-        Ok(MilestoneRange { start_index, end_index })
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        let (page_size, cursor) = if let Some(cursor) = query.cursor {
+            let cursor: LedgerUpdatesByMilestoneCursor = cursor.parse()?;
+            (cursor.page_size, Some((cursor.output_id, cursor.is_spent)))
+        } else {
+            (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
+        };
+
+        Ok(LedgerUpdatesByMilestonePagination {
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+        })
     }
 }
 
-pub struct BlocksByMilestoneIndexPagination {
+pub struct MilestonesPagination {
+    pub start_timestamp: Option<MilestoneTimestamp>,
+    pub end_timestamp: Option<MilestoneTimestamp>,
     pub sort: SortOrder,
     pub page_size: usize,
-    pub cursor: Option<u32>,
+    pub cursor: Option<MilestoneIndex>,
 }
 
 #[derive(Clone, Deserialize, Default)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct BlocksByMilestoneIndexPaginationQuery {
+pub struct MilestonesPaginationQuery {
+    pub start_timestamp: Option<u32>,
+    pub end_timestamp: Option<u32>,
     pub sort: Option<String>,
     pub page_size: Option<usize>,
     pub cursor: Option<String>,
 }
 
 #[derive(Clone)]
-pub struct BlocksByMilestoneCursor {
-    pub white_flag_index: u32,
+pub struct MilestonesCursor {
+    pub milestone_index: MilestoneIndex,
     pub page_size: usize,
 }
 
-impl FromStr for BlocksByMilestoneCursor {
+impl FromStr for MilestonesCursor {
     type Err = ApiError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<_> = s.split('.').collect();
         Ok(match parts[..] {
-            [wfi, ps] => BlocksByMilestoneCursor {
-                white_flag_index: wfi.parse().map_err(RequestError::from)?,
+            [m, ps] => MilestonesCursor {
+                milestone_index: m.parse().map_err(RequestError::from)?,
                 page_size: ps.parse().map_err(RequestError::from)?,
             },
             _ => return Err(ApiError::from(RequestError::BadPagingState)),
@@ -365,22 +474,26 @@ impl FromStr for BlocksByMilestoneCursor {
     }
 }
 
-impl Display for BlocksByMilestoneCursor {
+impl Display for MilestonesCursor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}", self.white_flag_index, self.page_size)
+        write!(f, "{}.{}", self.milestone_index, self.page_size)
     }
 }
 
 #[async_trait]
-impl<B: Send> FromRequest<B> for BlocksByMilestoneIndexPagination {
+impl<B: Send> FromRequest<B> for MilestonesPagination {
     type Rejection = ApiError;
 
     async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let Query(query) = Query::<BlocksByMilestoneIndexPaginationQuery>::from_request(req)
+        let Query(query) = Query::<MilestonesPaginationQuery>::from_request(req)
             .await
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
+        if matches!((query.start_timestamp, query.end_timestamp), (Some(start), Some(end)) if end < start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+
         let sort = query
             .sort
             .as_deref()
@@ -388,13 +501,15 @@ impl<B: Send> FromRequest<B> for BlocksByMilestoneIndexPagination {
             .map_err(RequestError::SortOrder)?;
 
         let (page_size, cursor) = if let Some(cursor) = query.cursor {
-            let cursor: BlocksByMilestoneCursor = cursor.parse()?;
-            (cursor.page_size, Some(cursor.white_flag_index))
+            let cursor: MilestonesCursor = cursor.parse()?;
+            (cursor.page_size, Some(cursor.milestone_index))
         } else {
             (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
         };
 
-        Ok(BlocksByMilestoneIndexPagination {
+        Ok(MilestonesPagination {
+            start_timestamp: query.start_timestamp.map(Into::into),
+            end_timestamp: query.end_timestamp.map(Into::into),
             sort,
             page_size: page_size.min(config.max_page_size),
             cursor,
@@ -402,30 +517,39 @@ impl<B: Send> FromRequest<B> for BlocksByMilestoneIndexPagination {
     }
 }
 
-pub struct BlocksByMilestoneIdPagination {
+#[derive(Clone)]
+pub struct FlaggedMilestonesPagination {
+    pub start_index: Option<MilestoneIndex>,
+    pub end_index: Option<MilestoneIndex>,
     pub sort: SortOrder,
     pub page_size: usize,
-    pub cursor: Option<u32>,
+    pub cursor: Option<MilestoneIndex>,
 }
 
 #[derive(Clone, Deserialize, Default)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct BlocksByMilestoneIdPaginationQuery {
+pub struct FlaggedMilestonesPaginationQuery {
+    pub start_index: Option<MilestoneIndex>,
+    pub end_index: Option<MilestoneIndex>,
     pub sort: Option<String>,
     pub page_size: Option<usize>,
     pub cursor: Option<String>,
 }
 
 #[async_trait]
-impl<B: Send> FromRequest<B> for BlocksByMilestoneIdPagination {
+impl<B: Send> FromRequest<B> for FlaggedMilestonesPagination {
     type Rejection = ApiError;
 
     async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
-        let Query(query) = Query::<BlocksByMilestoneIdPaginationQuery>::from_request(req)
+        let Query(query) = Query::<FlaggedMilestonesPaginationQuery>::from_request(req)
             .await
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
+        if matches!((query.start_index, query.end_index), (Some(start), Some(end)) if end < start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+
         let sort = query
             .sort
             .as_deref()
@@ -433,13 +557,15 @@ impl<B: Send> FromRequest<B> for BlocksByMilestoneIdPagination {
             .map_err(RequestError::SortOrder)?;
 
         let (page_size, cursor) = if let Some(cursor) = query.cursor {
-            let cursor: BlocksByMilestoneCursor = cursor.parse()?;
-            (cursor.page_size, Some(cursor.white_flag_index))
+            let cursor: MilestonesCursor = cursor.parse()?;
+            (cursor.page_size, Some(cursor.milestone_index))
         } else {
             (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
         };
 
-        Ok(BlocksByMilestoneIdPagination {
+        Ok(FlaggedMilestonesPagination {
+            start_index: query.start_index,
+            end_index: query.end_index,
             sort,
             page_size: page_size.min(config.max_page_size),
             cursor,
@@ -447,7 +573,593 @@ impl<B: Send> FromRequest<B> for BlocksByMilestoneIdPagination {
     }
 }
 
-#[cfg(test)]
+const DEFAULT_TOP_RICHLIST: usize = 100;
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RichestAddressesQuery {
+    pub top: usize,
+    pub ledger_index: Option<MilestoneIndex>,
+}
+
+impl Default for RichestAddressesQuery {
+    fn default() -> Self {
+        Self {
+            top: DEFAULT_TOP_RICHLIST,
+            ledger_index: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for RichestAddressesQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(mut query) = Query::<RichestAddressesQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+        query.top = query.top.min(config.max_page_size);
+        Ok(query)
+    }
+}
+
+const DEFAULT_TOP_TAGS: usize = 100;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct TopTagsQuery {
+    pub top: usize,
+    pub ledger_index: Option<MilestoneIndex>,
+}
+
+impl Default for TopTagsQuery {
+    fn default() -> Self {
+        Self {
+            top: DEFAULT_TOP_TAGS,
+            ledger_index: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for TopTagsQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(mut query) = Query::<TopTagsQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+        query.top = query.top.min(config.max_page_size);
+        Ok(query)
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct LedgerIndex {
+    pub ledger_index: Option<MilestoneIndex>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for LedgerIndex {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<LedgerIndex>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        Ok(query)
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct MilestoneRange {
+    pub start_index: Option<MilestoneIndex>,
+    pub end_index: Option<MilestoneIndex>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for MilestoneRange {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(MilestoneRange { start_index, end_index }) = Query::<MilestoneRange>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        if matches!((start_index, end_index), (Some(start), Some(end)) if end < start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+        Ok(MilestoneRange { start_index, end_index })
+    }
+}
+
+const DEFAULT_GRAPH_DEPTH: usize = 1;
+const MAX_GRAPH_DEPTH: usize = 3;
+
+/// What to convert into a milestone: either a timestamp or a milestone index.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MilestoneConversionQuery {
+    Timestamp(MilestoneTimestamp),
+    Index(MilestoneIndex),
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for MilestoneConversionQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields, rename_all = "camelCase")]
+        struct Params {
+            timestamp: Option<MilestoneTimestamp>,
+            milestone_index: Option<MilestoneIndex>,
+        }
+
+        let Query(params) = Query::<Params>::from_request(req).await.map_err(RequestError::from)?;
+        match (params.timestamp, params.milestone_index) {
+            (Some(timestamp), None) => Ok(Self::Timestamp(timestamp)),
+            (None, Some(index)) => Ok(Self::Index(index)),
+            _ => Err(ApiError::from(RequestError::ConversionInput)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct TransferGraphQuery {
+    pub depth: usize,
+    pub start_index: Option<MilestoneIndex>,
+    pub end_index: Option<MilestoneIndex>,
+    pub format: GraphFormat,
+}
+
+impl Default for TransferGraphQuery {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_GRAPH_DEPTH,
+            start_index: None,
+            end_index: None,
+            format: Default::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for TransferGraphQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(mut query) = Query::<TransferGraphQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        if matches!((query.start_index, query.end_index), (Some(start), Some(end)) if end < start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+        query.depth = query.depth.clamp(1, MAX_GRAPH_DEPTH);
+        Ok(query)
+    }
+}
+
+/// The size of the buckets transfer volume is grouped into.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeInterval {
+    Hour,
+    Day,
+}
+
+impl Default for VolumeInterval {
+    fn default() -> Self {
+        Self::Hour
+    }
+}
+
+impl VolumeInterval {
+    pub fn seconds(&self) -> u32 {
+        match self {
+            Self::Hour => 60 * 60,
+            Self::Day => 24 * 60 * 60,
+        }
+    }
+
+    /// The number of buckets returned if no start timestamp is given.
+    pub fn default_bucket_count(&self) -> u32 {
+        match self {
+            Self::Hour => 24,
+            Self::Day => 30,
+        }
+    }
+}
+
+pub const MAX_VOLUME_BUCKETS: u32 = 1000;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct TransferVolumeQuery {
+    pub interval: VolumeInterval,
+    pub start_timestamp: Option<u32>,
+    pub end_timestamp: Option<u32>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for TransferVolumeQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<TransferVolumeQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        if matches!((query.start_timestamp, query.end_timestamp), (Some(start), Some(end)) if end < start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+        Ok(query)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct DataSizeQuery {
+    pub interval: VolumeInterval,
+    pub start_timestamp: Option<u32>,
+    pub end_timestamp: Option<u32>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for DataSizeQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<DataSizeQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        if matches!((query.start_timestamp, query.end_timestamp), (Some(start), Some(end)) if end < start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+        Ok(query)
+    }
+}
+
+#[cfg(feature = "search")]
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+#[cfg(feature = "search")]
+const MAX_SEARCH_LIMIT: usize = 100;
+#[cfg(feature = "search")]
+const MAX_SEARCH_QUERY_LEN: usize = 256;
+
+#[cfg(feature = "search")]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct SearchQuery {
+    pub q: String,
+    #[serde(default = "default_search_limit")]
+    pub limit: usize,
+}
+
+#[cfg(feature = "search")]
+fn default_search_limit() -> usize {
+    DEFAULT_SEARCH_LIMIT
+}
+
+#[cfg(feature = "search")]
+#[async_trait]
+impl<B: Send> FromRequest<B> for SearchQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(mut query) = Query::<SearchQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        query.q = query.q.trim().to_string();
+        if query.q.is_empty() || query.q.len() > MAX_SEARCH_QUERY_LEN {
+            return Err(ApiError::from(RequestError::BadSearchQuery(MAX_SEARCH_QUERY_LEN)));
+        }
+        query.limit = query.limit.clamp(1, MAX_SEARCH_LIMIT);
+        Ok(query)
+    }
+}
+
+pub const MAX_ACTIVITY_DAYS: u32 = 366;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct ActiveAddressesQuery {
+    pub start_timestamp: Option<u32>,
+    pub end_timestamp: Option<u32>,
+    pub per_day: bool,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for ActiveAddressesQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<ActiveAddressesQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        if matches!((query.start_timestamp, query.end_timestamp), (Some(start), Some(end)) if end <= start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+        Ok(query)
+    }
+}
+
+const DEFAULT_NFT_SALES_MILESTONES: u32 = 10;
+const MAX_NFT_SALES_MILESTONES: u32 = 100;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct NftSalesQuery {
+    pub collection: Option<String>,
+    pub milestones: u32,
+}
+
+impl Default for NftSalesQuery {
+    fn default() -> Self {
+        Self {
+            collection: None,
+            milestones: DEFAULT_NFT_SALES_MILESTONES,
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for NftSalesQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(mut query) = Query::<NftSalesQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        query.milestones = query.milestones.clamp(1, MAX_NFT_SALES_MILESTONES);
+        Ok(query)
+    }
+}
+
+pub const MAX_BALANCE_QUERIES: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct BalanceAtMilestone {
+    pub address: String,
+    pub milestone_index: MilestoneIndex,
+}
+
+/// A list of addresses whose balances should be computed at the given milestones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BalancesAtMilestoneRequest(pub Vec<BalanceAtMilestone>);
+
+#[async_trait]
+impl<B> FromRequest<B> for BalancesAtMilestoneRequest
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Json(items) = Json::<Vec<BalanceAtMilestone>>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        if items.is_empty() || items.len() > MAX_BALANCE_QUERIES {
+            return Err(ApiError::from(RequestError::BalanceQueryCount(MAX_BALANCE_QUERIES)));
+        }
+        Ok(BalancesAtMilestoneRequest(items))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionBytes {
+    /// A signed transaction payload.
+    Payload(Vec<u8>),
+    /// The essence of a transaction that was not signed yet.
+    Essence(Vec<u8>),
+}
+
+#[derive(Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct SimulateTransactionBody {
+    pub payload: Option<String>,
+    pub essence: Option<String>,
+    pub milestone_index: Option<MilestoneIndex>,
+}
+
+/// A transaction to validate against the ledger state at a milestone, which defaults to the ledger index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulateTransactionRequest {
+    pub transaction: TransactionBytes,
+    pub milestone_index: Option<MilestoneIndex>,
+}
+
+#[async_trait]
+impl<B> FromRequest<B> for SimulateTransactionRequest
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Json(body) = Json::<SimulateTransactionBody>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let transaction = match (body.payload, body.essence) {
+            (Some(payload), None) => {
+                TransactionBytes::Payload(prefix_hex::decode(payload).map_err(RequestError::from)?)
+            }
+            (None, Some(essence)) => {
+                TransactionBytes::Essence(prefix_hex::decode(essence).map_err(RequestError::from)?)
+            }
+            _ => return Err(ApiError::from(RequestError::TransactionInput)),
+        };
+        Ok(SimulateTransactionRequest {
+            transaction,
+            milestone_index: body.milestone_index,
+        })
+    }
+}
+
+pub struct BlocksByMilestoneIndexPagination {
+    pub sort: SortOrder,
+    pub page_size: usize,
+    pub cursor: Option<u32>,
+}
+
+#[derive(Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct BlocksByMilestoneIndexPaginationQuery {
+    pub sort: Option<String>,
+    pub page_size: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct BlocksByMilestoneCursor {
+    pub white_flag_index: u32,
+    pub page_size: usize,
+}
+
+impl FromStr for BlocksByMilestoneCursor {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split('.').collect();
+        Ok(match parts[..] {
+            [wfi, ps] => BlocksByMilestoneCursor {
+                white_flag_index: wfi.parse().map_err(RequestError::from)?,
+                page_size: ps.parse().map_err(RequestError::from)?,
+            },
+            _ => return Err(ApiError::from(RequestError::BadPagingState)),
+        })
+    }
+}
+
+impl Display for BlocksByMilestoneCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.white_flag_index, self.page_size)
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for BlocksByMilestoneIndexPagination {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<BlocksByMilestoneIndexPaginationQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        let sort = query
+            .sort
+            .as_deref()
+            .map_or(Ok(Default::default()), str::parse)
+            .map_err(RequestError::SortOrder)?;
+
+        let (page_size, cursor) = if let Some(cursor) = query.cursor {
+            let cursor: BlocksByMilestoneCursor = cursor.parse()?;
+            (cursor.page_size, Some(cursor.white_flag_index))
+        } else {
+            (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
+        };
+
+        Ok(BlocksByMilestoneIndexPagination {
+            sort,
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+        })
+    }
+}
+
+pub struct BlocksByMilestoneIdPagination {
+    pub sort: SortOrder,
+    pub page_size: usize,
+    pub cursor: Option<u32>,
+}
+
+#[derive(Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct BlocksByMilestoneIdPaginationQuery {
+    pub sort: Option<String>,
+    pub page_size: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for BlocksByMilestoneIdPagination {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<BlocksByMilestoneIdPaginationQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        let sort = query
+            .sort
+            .as_deref()
+            .map_or(Ok(Default::default()), str::parse)
+            .map_err(RequestError::SortOrder)?;
+
+        let (page_size, cursor) = if let Some(cursor) = query.cursor {
+            let cursor: BlocksByMilestoneCursor = cursor.parse()?;
+            (cursor.page_size, Some(cursor.white_flag_index))
+        } else {
+            (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
+        };
+
+        Ok(BlocksByMilestoneIdPagination {
+            sort,
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+        })
+    }
+}
+
+#[derive(Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct StorageForecastQueryParams {
+    window: Option<String>,
+    horizons: Option<String>,
+}
+
+/// The period over which the growth of the collections is measured, and how far ahead their storage is projected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageForecastQuery {
+    pub window: Duration,
+    pub horizons: Vec<Duration>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for StorageForecastQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(StorageForecastQueryParams { window, horizons }) =
+            Query::<StorageForecastQueryParams>::from_request(req)
+                .await
+                .map_err(RequestError::from)?;
+        Ok(StorageForecastQuery {
+            window: window
+                .as_deref()
+                .unwrap_or(storage_forecast::DEFAULT_WINDOW)
+                .parse::<humantime::Duration>()
+                .map_err(RequestError::from)?
+                .into(),
+            horizons: storage_forecast::parse_horizons(
+                horizons.as_deref().unwrap_or(storage_forecast::DEFAULT_HORIZONS),
+            )
+            .map_err(RequestError::from)?,
+        })
+    }
+}
+
+#[cfg(test)]
 mod test {
     use axum::{extract::RequestParts, http::Request};
     use pretty_assertions::assert_eq;
@@ -467,6 +1179,70 @@ mod test {
         assert_eq!(parsed.to_string(), cursor);
     }
 
+    #[test]
+    fn output_history_cursor_from_to_str() {
+        let milestone_index = 164338324u32;
+        let output_id_str = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a20100";
+        let page_size_str = "1337";
+
+        let cursor = format!("{milestone_index}.{output_id_str}.{page_size_str}",);
+        let parsed: OutputHistoryCursor = cursor.parse().unwrap();
+        assert_eq!(parsed.to_string(), cursor);
+        assert!("1.2".parse::<OutputHistoryCursor>().is_err());
+    }
+
+    #[test]
+    fn address_transactions_cursor_from_to_str() {
+        let transaction_id_str = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a2";
+        let cursor = format!("164338324.{transaction_id_str}.1337");
+        let parsed: AddressTransactionsCursor = cursor.parse().unwrap();
+        assert_eq!(parsed.to_string(), cursor);
+        assert!("164338324.0x00.1337".parse::<AddressTransactionsCursor>().is_err());
+    }
+
+    #[test]
+    fn balance_history_cursor_from_to_str() {
+        let cursor = "8640.360.100";
+        let parsed: BalanceHistoryCursor = cursor.parse().unwrap();
+        assert_eq!(parsed.to_string(), cursor);
+        assert!("8640.360".parse::<BalanceHistoryCursor>().is_err());
+    }
+
+    #[tokio::test]
+    async fn balance_history_pagination() {
+        let request = |uri: &str| {
+            RequestParts::new(
+                Request::builder()
+                    .method("GET")
+                    .uri(uri)
+                    .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                    .body(())
+                    .unwrap(),
+            )
+        };
+        assert_eq!(
+            BalanceHistoryPagination::from_request(&mut request("/balance/0x00/history?interval=100&startIndex=250"))
+                .await
+                .unwrap(),
+            BalanceHistoryPagination {
+                interval: 100,
+                start_index: 200.into(),
+                end_index: None,
+                page_size: DEFAULT_PAGE_SIZE,
+            }
+        );
+        assert!(
+            BalanceHistoryPagination::from_request(&mut request("/balance/0x00/history?interval=0"))
+                .await
+                .is_err()
+        );
+        assert!(BalanceHistoryPagination::from_request(&mut request(
+            "/balance/0x00/history?startIndex=400&endIndex=10"
+        ))
+        .await
+        .is_err());
+    }
+
     #[test]
     fn ledger_updates_by_milestone_cursor_from_to_str() {
         let output_id_str = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a20100";
@@ -478,6 +1254,144 @@ mod test {
         assert_eq!(parsed.to_string(), cursor);
     }
 
+    #[tokio::test]
+    async fn nft_sales_milestones_clamped() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/ledger/nft-sales?milestones=9999")
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            NftSalesQuery::from_request(&mut req).await.unwrap(),
+            NftSalesQuery {
+                collection: None,
+                milestones: MAX_NFT_SALES_MILESTONES,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn active_addresses_query() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/ledger/active-addresses?startTimestamp=10&perDay=true")
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            ActiveAddressesQuery::from_request(&mut req).await.unwrap(),
+            ActiveAddressesQuery {
+                start_timestamp: Some(10),
+                end_timestamp: None,
+                per_day: true,
+            }
+        );
+
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/ledger/active-addresses?startTimestamp=10&endTimestamp=10")
+                .body(())
+                .unwrap(),
+        );
+        assert!(ActiveAddressesQuery::from_request(&mut req).await.is_err());
+    }
+
+    #[cfg(feature = "search")]
+    #[tokio::test]
+    async fn search_query() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/search?q=%20shimmer%20&limit=1000")
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            SearchQuery::from_request(&mut req).await.unwrap(),
+            SearchQuery {
+                q: "shimmer".to_string(),
+                limit: MAX_SEARCH_LIMIT,
+            }
+        );
+
+        let mut req = RequestParts::new(Request::builder().method("GET").uri("/search?q=%20").body(()).unwrap());
+        assert!(SearchQuery::from_request(&mut req).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn transfer_volume_query() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/ledger/transfer-volume?interval=day&startTimestamp=10")
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            TransferVolumeQuery::from_request(&mut req).await.unwrap(),
+            TransferVolumeQuery {
+                interval: VolumeInterval::Day,
+                start_timestamp: Some(10),
+                end_timestamp: None,
+            }
+        );
+
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/ledger/transfer-volume?startTimestamp=10&endTimestamp=5")
+                .body(())
+                .unwrap(),
+        );
+        assert!(TransferVolumeQuery::from_request(&mut req).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn balances_at_milestone_request() {
+        fn request(body: String) -> RequestParts<axum::body::Body> {
+            RequestParts::new(
+                Request::builder()
+                    .method("POST")
+                    .uri("/balance/at-milestone")
+                    .header("content-type", "application/json")
+                    .body(body.into())
+                    .unwrap(),
+            )
+        }
+
+        let mut req = request(r#"[{"address":"iota1abc","milestoneIndex":5}]"#.to_string());
+        assert_eq!(
+            BalancesAtMilestoneRequest::from_request(&mut req).await.unwrap(),
+            BalancesAtMilestoneRequest(vec![BalanceAtMilestone {
+                address: "iota1abc".to_string(),
+                milestone_index: 5.into(),
+            }])
+        );
+
+        let mut req = request("[]".to_string());
+        assert!(BalancesAtMilestoneRequest::from_request(&mut req).await.is_err());
+
+        let mut req = request(r#"{"essence":"0x0102","milestoneIndex":5}"#.to_string());
+        assert_eq!(
+            SimulateTransactionRequest::from_request(&mut req).await.unwrap(),
+            SimulateTransactionRequest {
+                transaction: TransactionBytes::Essence(vec![1, 2]),
+                milestone_index: Some(5.into()),
+            }
+        );
+
+        let mut req = request(r#"{"payload":"0x01","essence":"0x02"}"#.to_string());
+        assert!(SimulateTransactionRequest::from_request(&mut req).await.is_err());
+
+        let item = r#"{"address":"iota1abc","milestoneIndex":5}"#;
+        let mut req = request(format!("[{}]", vec![item; MAX_BALANCE_QUERIES + 1].join(",")));
+        assert!(BalancesAtMilestoneRequest::from_request(&mut req).await.is_err());
+    }
+
     #[tokio::test]
     async fn page_size_clamped() {
         let mut req = RequestParts::new(
@@ -515,4 +1429,91 @@ mod test {
             }
         );
     }
+
+    #[tokio::test]
+    async fn top_tags_query() {
+        let request = |uri: &str| {
+            RequestParts::new(
+                Request::builder()
+                    .method("GET")
+                    .uri(uri)
+                    .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                    .body(())
+                    .unwrap(),
+            )
+        };
+        assert_eq!(
+            TopTagsQuery::from_request(&mut request("/ledger/tags")).await.unwrap(),
+            TopTagsQuery {
+                top: DEFAULT_TOP_TAGS,
+                ledger_index: None,
+            }
+        );
+        assert_eq!(
+            TopTagsQuery::from_request(&mut request("/ledger/tags?top=9999999&ledgerIndex=10"))
+                .await
+                .unwrap(),
+            TopTagsQuery {
+                top: 1000,
+                ledger_index: Some(10.into()),
+            }
+        );
+        assert!(TopTagsQuery::from_request(&mut request("/ledger/tags?limit=10"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn storage_forecast_query() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/storage/forecast?window=2d&horizons=1d,%201week")
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            StorageForecastQuery::from_request(&mut req).await.unwrap(),
+            StorageForecastQuery {
+                window: Duration::from_secs(2 * 24 * 60 * 60),
+                horizons: vec![Duration::from_secs(24 * 60 * 60), Duration::from_secs(7 * 24 * 60 * 60)],
+            }
+        );
+
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/storage/forecast?horizons=soon")
+                .body(())
+                .unwrap(),
+        );
+        assert!(StorageForecastQuery::from_request(&mut req).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn milestone_conversion_query() {
+        let request = |uri: &str| RequestParts::new(Request::builder().method("GET").uri(uri).body(()).unwrap());
+        assert_eq!(
+            MilestoneConversionQuery::from_request(&mut request("/milestones/convert?timestamp=1660065130"))
+                .await
+                .unwrap(),
+            MilestoneConversionQuery::Timestamp(1660065130.into())
+        );
+        assert_eq!(
+            MilestoneConversionQuery::from_request(&mut request("/milestones/convert?milestoneIndex=500000"))
+                .await
+                .unwrap(),
+            MilestoneConversionQuery::Index(500000.into())
+        );
+        assert!(
+            MilestoneConversionQuery::from_request(&mut request("/milestones/convert"))
+                .await
+                .is_err()
+        );
+        assert!(MilestoneConversionQuery::from_request(&mut request(
+            "/milestones/convert?timestamp=1&milestoneIndex=1"
+        ))
+        .await
+        .is_err());
+    }
 }
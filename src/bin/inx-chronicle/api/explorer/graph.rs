@@ -0,0 +1,187 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use chronicle::model::tangle::MilestoneIndex;
+use serde::Deserialize;
+
+/// The formats a [`TransferGraph`] can be exported in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    Dot,
+    GraphMl,
+}
+
+impl Default for GraphFormat {
+    fn default() -> Self {
+        Self::Dot
+    }
+}
+
+impl GraphFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Dot => "text/vnd.graphviz",
+            Self::GraphMl => "application/graphml+xml",
+        }
+    }
+}
+
+/// A bipartite graph of addresses and the transactions that moved funds between them.
+///
+/// Edges point from an address to the transactions that consumed its outputs, and from a transaction to the addresses
+/// that own its outputs. Node identifiers are bech32 addresses and hex transaction ids, neither of which need escaping.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransferGraph {
+    addresses: BTreeSet<String>,
+    transactions: BTreeMap<String, MilestoneIndex>,
+    edges: BTreeMap<(String, String), u64>,
+    pub truncated: bool,
+}
+
+impl TransferGraph {
+    pub fn transaction_count(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Records that the `transaction` consumed `amount` tokens from the `address`.
+    pub fn add_input(&mut self, transaction: &str, milestone_index: MilestoneIndex, address: String, amount: u64) {
+        self.transactions.insert(transaction.to_string(), milestone_index);
+        *self
+            .edges
+            .entry((address.clone(), transaction.to_string()))
+            .or_default() += amount;
+        self.addresses.insert(address);
+    }
+
+    /// Records that the `transaction` sent `amount` tokens to the `address`.
+    pub fn add_output(&mut self, transaction: &str, milestone_index: MilestoneIndex, address: String, amount: u64) {
+        self.transactions.insert(transaction.to_string(), milestone_index);
+        *self
+            .edges
+            .entry((transaction.to_string(), address.clone()))
+            .or_default() += amount;
+        self.addresses.insert(address);
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::GraphMl => self.to_graphml(),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph transfers {\n");
+        if self.truncated {
+            out.push_str("  // truncated: the transaction limit was reached\n");
+        }
+        for address in &self.addresses {
+            writeln!(out, "  \"{address}\" [shape=ellipse];").unwrap();
+        }
+        for (transaction, milestone_index) in &self.transactions {
+            writeln!(
+                out,
+                "  \"{transaction}\" [shape=box, label=\"{transaction}\\nmilestone {milestone_index}\"];"
+            )
+            .unwrap();
+        }
+        for ((source, target), amount) in &self.edges {
+            writeln!(out, "  \"{source}\" -> \"{target}\" [label=\"{amount}\"];").unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"truncated\" for=\"graph\" attr.name=\"truncated\" attr.type=\"boolean\"/>\n",
+            "  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+            "  <key id=\"milestone_index\" for=\"node\" attr.name=\"milestone_index\" attr.type=\"long\"/>\n",
+            "  <key id=\"amount\" for=\"edge\" attr.name=\"amount\" attr.type=\"string\"/>\n",
+            "  <graph id=\"transfers\" edgedefault=\"directed\">\n",
+        ));
+        writeln!(out, "    <data key=\"truncated\">{}</data>", self.truncated).unwrap();
+        for address in &self.addresses {
+            writeln!(
+                out,
+                "    <node id=\"{address}\"><data key=\"kind\">address</data></node>"
+            )
+            .unwrap();
+        }
+        for (transaction, milestone_index) in &self.transactions {
+            writeln!(
+                out,
+                "    <node id=\"{transaction}\"><data key=\"kind\">transaction</data>\
+                 <data key=\"milestone_index\">{milestone_index}</data></node>"
+            )
+            .unwrap();
+        }
+        for ((source, target), amount) in &self.edges {
+            writeln!(
+                out,
+                "    <edge source=\"{source}\" target=\"{target}\"><data key=\"amount\">{amount}</data></edge>"
+            )
+            .unwrap();
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn graph() -> TransferGraph {
+        let mut graph = TransferGraph::default();
+        graph.add_input("0x01", 5.into(), "iota1a".to_string(), 10);
+        graph.add_input("0x01", 5.into(), "iota1a".to_string(), 5);
+        graph.add_output("0x01", 5.into(), "iota1b".to_string(), 15);
+        graph
+    }
+
+    #[test]
+    fn dot_export() {
+        assert_eq!(
+            graph().render(GraphFormat::Dot),
+            "digraph transfers {\n\
+             \x20 \"iota1a\" [shape=ellipse];\n\
+             \x20 \"iota1b\" [shape=ellipse];\n\
+             \x20 \"0x01\" [shape=box, label=\"0x01\\nmilestone 5\"];\n\
+             \x20 \"0x01\" -> \"iota1b\" [label=\"15\"];\n\
+             \x20 \"iota1a\" -> \"0x01\" [label=\"15\"];\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn graphml_export() {
+        let graphml = graph().render(GraphFormat::GraphMl);
+        assert!(graphml.contains("<data key=\"truncated\">false</data>"));
+        assert!(graphml.contains("<node id=\"iota1a\"><data key=\"kind\">address</data></node>"));
+        assert!(graphml.contains(
+            "<node id=\"0x01\"><data key=\"kind\">transaction</data><data key=\"milestone_index\">5</data></node>"
+        ));
+        assert!(graphml.contains("<edge source=\"iota1a\" target=\"0x01\"><data key=\"amount\">15</data></edge>"));
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+    }
+
+    #[test]
+    fn parse_format() {
+        assert_eq!(
+            serde_json::from_str::<GraphFormat>("\"graphml\"").unwrap(),
+            GraphFormat::GraphMl
+        );
+        assert!(serde_json::from_str::<GraphFormat>("\"svg\"").is_err());
+    }
+}
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod extractors;
+mod graph;
 mod responses;
 mod routes;
+mod simulation;
 
 pub use self::routes::routes;
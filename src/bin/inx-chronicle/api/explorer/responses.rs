@@ -1,25 +1,32 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::ops::Range;
+use std::{ops::Range, time::Duration};
 
 use chronicle::{
     db::mongodb::collections::{
-        DistributionStat, LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, MilestoneResult,
+        AddressActivityByKind, AddressTransactionRecord, CollectionGrowth, DailyAddressActivity, DataSizeBucket,
+        DistributionStat, EntityRelation, LedgerSnapshotResult, LedgerUpdateByAddressRecord,
+        LedgerUpdateByMilestoneRecord, MilestoneResult, OutputHistoryRecord, PendingTransactionResult, TagStat,
+        TokenDistributionResult, TransferVolumeBucket, UnspentOutputDigest, ViewResult,
     },
     model::{
         tangle::{MilestoneIndex, MilestoneTimestamp},
-        utxo::Address,
+        utxo::{Address, NativeTokenId},
     },
 };
 use serde::{Deserialize, Serialize};
 
-use crate::api::responses::impl_success_response;
+use super::graph::GraphFormat;
+use crate::api::{dto::TransactionPayloadDto, responses::impl_success_response};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LedgerUpdatesByAddressResponse {
     pub address: String,
+    /// The name of the address, if a naming service knows one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     pub items: Vec<LedgerUpdateByAddressDto>,
     pub cursor: Option<String>,
 }
@@ -35,6 +42,56 @@ pub struct LedgerUpdateByAddressDto {
     pub milestone_timestamp: MilestoneTimestamp,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressTransactionsResponse {
+    pub address: String,
+    pub items: Vec<AddressTransactionDto>,
+    pub cursor: Option<String>,
+}
+
+impl_success_response!(AddressTransactionsResponse);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionDirection {
+    /// The transaction only created outputs for the address.
+    Incoming,
+    /// The transaction consumed outputs of the address, even if it created some for it as well.
+    Outgoing,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressTransactionDto {
+    pub transaction_id: String,
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub direction: TransactionDirection,
+    pub received: String,
+    pub sent: String,
+    /// The change of the balance of the address, which is negative if it sent more than it received.
+    pub amount_delta: String,
+}
+
+impl From<AddressTransactionRecord> for AddressTransactionDto {
+    fn from(value: AddressTransactionRecord) -> Self {
+        Self {
+            transaction_id: value.transaction_id.to_hex(),
+            milestone_index: value.at.milestone_index,
+            milestone_timestamp: value.at.milestone_timestamp,
+            direction: if value.is_spent {
+                TransactionDirection::Outgoing
+            } else {
+                TransactionDirection::Incoming
+            },
+            received: value.received.to_string(),
+            sent: value.sent.to_string(),
+            amount_delta: (value.received as i128 - value.sent as i128).to_string(),
+        }
+    }
+}
+
 impl From<LedgerUpdateByAddressRecord> for LedgerUpdateByAddressDto {
     fn from(value: LedgerUpdateByAddressRecord) -> Self {
         Self {
@@ -80,10 +137,70 @@ pub struct BalanceResponse {
     pub total_balance: String,
     pub available_balance: String,
     pub ledger_index: MilestoneIndex,
+    /// The name of the address, if a naming service knows one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 impl_success_response!(BalanceResponse);
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceHistoryResponse {
+    pub address: String,
+    pub ledger_index: MilestoneIndex,
+    pub interval: u32,
+    pub items: Vec<BalanceHistoryDto>,
+    pub cursor: Option<String>,
+}
+
+impl_success_response!(BalanceHistoryResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceHistoryDto {
+    pub start_index: MilestoneIndex,
+    pub end_index: MilestoneIndex,
+    pub received: String,
+    pub sent: String,
+    /// The amount of base tokens the address owned at the end of the range.
+    pub balance: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancesAtMilestoneResponse {
+    pub items: Vec<BalanceAtMilestoneDto>,
+    pub ledger_index: MilestoneIndex,
+}
+
+impl_success_response!(BalancesAtMilestoneResponse);
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionSimulationResponse {
+    /// The id of the transaction, which is only known for signed transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>,
+    pub milestone_index: MilestoneIndex,
+    pub is_valid: bool,
+    /// Whether the unlocks were checked, which requires a signed transaction.
+    pub signatures_checked: bool,
+    pub issues: Vec<super::simulation::Issue>,
+}
+
+impl_success_response!(TransactionSimulationResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceAtMilestoneDto {
+    pub address: String,
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub total_balance: String,
+    pub available_balance: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BlockChildrenResponse {
@@ -104,6 +221,32 @@ pub struct MilestonesResponse {
 
 impl_success_response!(MilestonesResponse);
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlaggedMilestonesResponse {
+    pub items: Vec<FlaggedMilestoneDto>,
+    pub cursor: Option<String>,
+}
+
+impl_success_response!(FlaggedMilestonesResponse);
+
+/// A milestone that was flagged by the spam detection, with the patterns it was flagged for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlaggedMilestoneDto {
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub block_spike: bool,
+    pub duplicate_payloads: bool,
+    pub issuer_dominance: bool,
+    pub block_count: u64,
+    pub baseline_block_count: Option<f64>,
+    pub duplicate_payload_count: u64,
+    pub transaction_count: u64,
+    pub top_issuer: Option<String>,
+    pub top_issuer_transaction_count: u64,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BlockPayloadTypeDto {
@@ -152,6 +295,33 @@ pub struct AddressStatDto {
     pub balance: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopTagsResponse {
+    pub tags: Vec<TagStatDto>,
+    pub ledger_index: MilestoneIndex,
+}
+
+impl_success_response!(TopTagsResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagStatDto {
+    pub tag: String,
+    pub output_count: u64,
+    pub total_amount: String,
+}
+
+impl From<TagStat> for TagStatDto {
+    fn from(value: TagStat) -> Self {
+        Self {
+            tag: value.tag.to_hex(),
+            output_count: value.output_count,
+            total_amount: value.total_amount,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenDistributionResponse {
@@ -161,6 +331,123 @@ pub struct TokenDistributionResponse {
 
 impl_success_response!(TokenDistributionResponse);
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelockedValueResponse {
+    pub ledger_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub locked_count: u64,
+    pub locked_amount: String,
+    pub horizons: Vec<TimelockHorizonDto>,
+}
+
+impl_success_response!(TimelockedValueResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelockHorizonDto {
+    pub horizon: String,
+    /// The time until which the outputs unlock, or `None` for the outputs that unlock after the longest horizon.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlocks_until: Option<MilestoneTimestamp>,
+    pub count: u64,
+    pub amount: String,
+    /// The amount that is still locked once the horizon has passed.
+    pub locked_after: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnspentOutputDigestResponse {
+    pub ledger_index: MilestoneIndex,
+    /// The first byte of the output ids in this bucket.
+    pub prefix: String,
+    pub count: usize,
+    pub digest: String,
+}
+
+impl_success_response!(UnspentOutputDigestResponse);
+
+impl UnspentOutputDigestResponse {
+    pub fn new(ledger_index: MilestoneIndex, digest: UnspentOutputDigest) -> Self {
+        Self {
+            ledger_index,
+            prefix: prefix_hex::encode([digest.prefix]),
+            count: digest.count,
+            digest: prefix_hex::encode(digest.digest),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerSnapshotsResponse {
+    pub items: Vec<LedgerSnapshotDto>,
+}
+
+impl_success_response!(LedgerSnapshotsResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerSnapshotDto {
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub output_count: usize,
+    pub total_amount: String,
+    /// The hash of the digests of all unspent output buckets, as returned by the digest route of each prefix.
+    pub digest: String,
+}
+
+impl From<LedgerSnapshotResult> for LedgerSnapshotDto {
+    fn from(value: LedgerSnapshotResult) -> Self {
+        Self {
+            milestone_index: value.milestone_index,
+            milestone_timestamp: value.milestone_timestamp,
+            output_count: value.digest.output_count,
+            total_amount: value.digest.total_amount.to_string(),
+            digest: prefix_hex::encode(value.digest.digest),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolChangesResponse {
+    pub items: Vec<ProtocolChangeDto>,
+}
+
+impl_success_response!(ProtocolChangesResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolChangeDto {
+    /// The first milestone the protocol parameters were in effect for.
+    pub milestone_index: MilestoneIndex,
+    pub protocol_version: u8,
+    /// The parameters that differ from the previous version. For the first known version, these are all parameters.
+    pub changes: Vec<ProtocolParameterChangeDto>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolParameterChangeDto {
+    /// The path of the parameter, with the names of nested parameters separated by dots.
+    pub field: String,
+    /// The previous value, which is `null` for the first known version and for added parameters.
+    pub old: Option<serde_json::Value>,
+    /// The new value, which is `null` for removed parameters.
+    pub new: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnspentOutputIdsResponse {
+    pub ledger_index: MilestoneIndex,
+    pub items: Vec<String>,
+}
+
+impl_success_response!(UnspentOutputIdsResponse);
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DistributionStatDto {
@@ -178,3 +465,551 @@ impl From<DistributionStat> for DistributionStatDto {
         }
     }
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveAddressesResponse {
+    pub start_timestamp: MilestoneTimestamp,
+    pub end_timestamp: MilestoneTimestamp,
+    #[serde(flatten)]
+    pub total: ActiveAddressesDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<Vec<DailyActiveAddressesDto>>,
+}
+
+impl_success_response!(ActiveAddressesResponse);
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveAddressesDto {
+    pub count: usize,
+    pub ed25519_count: usize,
+    pub alias_count: usize,
+    pub nft_count: usize,
+}
+
+impl From<AddressActivityByKind> for ActiveAddressesDto {
+    fn from(value: AddressActivityByKind) -> Self {
+        Self {
+            count: value.total(),
+            ed25519_count: value.ed25519_count,
+            alias_count: value.alias_count,
+            nft_count: value.nft_count,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyActiveAddressesDto {
+    pub start_timestamp: MilestoneTimestamp,
+    #[serde(flatten)]
+    pub activity: ActiveAddressesDto,
+}
+
+impl From<DailyAddressActivity> for DailyActiveAddressesDto {
+    fn from(value: DailyAddressActivity) -> Self {
+        Self {
+            start_timestamp: value.start_timestamp,
+            activity: value.activity.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferVolumeResponse {
+    pub items: Vec<TransferVolumeDto>,
+}
+
+impl_success_response!(TransferVolumeResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferVolumeDto {
+    pub start_timestamp: MilestoneTimestamp,
+    pub volume: String,
+    pub max_transfer: String,
+    pub transaction_count: usize,
+}
+
+impl TransferVolumeDto {
+    pub fn empty(start_timestamp: MilestoneTimestamp) -> Self {
+        Self {
+            start_timestamp,
+            volume: "0".to_string(),
+            max_transfer: "0".to_string(),
+            transaction_count: 0,
+        }
+    }
+}
+
+impl From<TransferVolumeBucket> for TransferVolumeDto {
+    fn from(b: TransferVolumeBucket) -> Self {
+        Self {
+            start_timestamp: b.start_timestamp,
+            volume: b.volume,
+            max_transfer: b.max_transfer,
+            transaction_count: b.transaction_count,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftSalesResponse {
+    pub items: Vec<NftSaleDto>,
+    pub ledger_index: MilestoneIndex,
+}
+
+impl_success_response!(NftSalesResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftSaleDto {
+    pub nft_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<String>,
+    pub seller: String,
+    pub buyer: String,
+    pub price: String,
+    pub transaction_id: String,
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewsResponse {
+    pub views: Vec<ViewDto>,
+}
+
+impl_success_response!(ViewsResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewDto {
+    pub name: String,
+    pub source: String,
+    pub incremental: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refreshed_at_ledger_index: Option<MilestoneIndex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refreshed_at_time: Option<i64>,
+}
+
+impl From<ViewResult> for ViewDto {
+    fn from(value: ViewResult) -> Self {
+        Self {
+            name: value.definition.name,
+            source: value.definition.source,
+            incremental: value.definition.incremental,
+            refreshed_at_ledger_index: value.last_refresh.map(|r| r.ledger_index),
+            refreshed_at_time: value.last_refresh.map(|r| r.time.unix_timestamp()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewResultsResponse {
+    #[serde(flatten)]
+    pub view: ViewDto,
+    pub items: Vec<serde_json::Value>,
+}
+
+impl_success_response!(ViewResultsResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedTransactionResponse {
+    pub transaction_id: String,
+    pub block_id: String,
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub inputs: Vec<DecodedOutputDto>,
+    pub outputs: Vec<DecodedOutputDto>,
+    pub unlocks: Vec<DecodedUnlockDto>,
+    pub net_transfers: Vec<NetTransferDto>,
+}
+
+impl_success_response!(DecodedTransactionResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedOutputDto {
+    pub output_id: String,
+    pub kind: String,
+    pub address: Option<String>,
+    pub amount: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedUnlockDto {
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<u16>,
+    pub address: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetTransferDto {
+    pub address: String,
+    pub amount: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityRelationshipsResponse {
+    pub address: String,
+    pub ledger_index: MilestoneIndex,
+    pub nodes: Vec<EntityNodeDto>,
+    pub edges: Vec<EntityEdgeDto>,
+    /// Whether the entity limit was reached, in which case the graph is incomplete.
+    pub truncated: bool,
+}
+
+impl_success_response!(EntityRelationshipsResponse);
+
+/// An address or entity in the graph. Aliases and NFTs are identified by their bech32 address and foundries by their
+/// hex id.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityNodeDto {
+    pub id: String,
+    pub kind: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityEdgeDto {
+    pub from: String,
+    pub to: String,
+    pub relation: EntityRelation,
+    /// The output that records the relationship.
+    pub output_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MilestoneConversionResponse {
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    /// The version of the protocol parameters that were in effect at the milestone.
+    pub protocol_version: u8,
+    /// The first milestone the protocol parameters were in effect for.
+    pub protocol_start_index: MilestoneIndex,
+    /// The last milestone the protocol parameters were in effect for, if they have been changed since.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_end_index: Option<MilestoneIndex>,
+}
+
+impl_success_response!(MilestoneConversionResponse);
+
+pub struct TransferGraphResponse {
+    pub format: GraphFormat,
+    pub graph: String,
+}
+
+impl axum::response::IntoResponse for TransferGraphResponse {
+    fn into_response(self) -> axum::response::Response {
+        (
+            [(axum::http::header::CONTENT_TYPE, self.format.content_type())],
+            self.graph,
+        )
+            .into_response()
+    }
+}
+
+/// The serialized filter of the addresses known to the archive.
+pub struct AddressFilterResponse(pub Vec<u8>);
+
+impl axum::response::IntoResponse for AddressFilterResponse {
+    fn into_response(self) -> axum::response::Response {
+        ([(axum::http::header::CONTENT_TYPE, "application/octet-stream")], self.0).into_response()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressActivityResponse {
+    pub address: String,
+    pub ledger_index: MilestoneIndex,
+    pub items: Vec<AddressActivityDto>,
+}
+
+impl_success_response!(AddressActivityResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressActivityDto {
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub created_count: usize,
+    pub consumed_count: usize,
+    pub received: String,
+    pub sent: String,
+    pub balance: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressFlowResponse {
+    pub address: String,
+    pub start_index: MilestoneIndex,
+    pub end_index: MilestoneIndex,
+    pub created_count: usize,
+    pub consumed_count: usize,
+    /// The number of outputs that were pruned, whose amounts are missing from the sums.
+    pub pruned_count: usize,
+    pub received: String,
+    pub sent: String,
+    pub net: String,
+}
+
+impl_success_response!(AddressFlowResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeTokenDistributionResponse {
+    pub token_id: String,
+    pub ledger_index: MilestoneIndex,
+    pub holder_count: usize,
+    pub total_amount: String,
+    pub top10_share: f64,
+    pub gini: f64,
+}
+
+impl_success_response!(NativeTokenDistributionResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputHistoryResponse {
+    /// The alias or NFT id, as hex.
+    pub id: String,
+    pub items: Vec<OutputHistoryDto>,
+    pub cursor: Option<String>,
+}
+
+impl_success_response!(OutputHistoryResponse);
+
+/// An output that represented an alias or NFT between two state transitions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputHistoryDto {
+    pub output_id: String,
+    /// The transaction that created the output.
+    pub transaction_id: String,
+    pub block_id: String,
+    pub milestone_index_booked: MilestoneIndex,
+    pub milestone_timestamp_booked: MilestoneTimestamp,
+    /// The transaction that consumed the output, if it was transitioned or destroyed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spent_by_transaction_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone_index_spent: Option<MilestoneIndex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone_timestamp_spent: Option<MilestoneTimestamp>,
+}
+
+impl From<OutputHistoryRecord> for OutputHistoryDto {
+    fn from(value: OutputHistoryRecord) -> Self {
+        Self {
+            output_id: value.output_id.to_hex(),
+            transaction_id: value.output_id.transaction_id.to_hex(),
+            block_id: value.block_id.to_hex(),
+            milestone_index_booked: value.booked.milestone_index,
+            milestone_timestamp_booked: value.booked.milestone_timestamp,
+            spent_by_transaction_id: value.spent_metadata.map(|spent| spent.transaction_id.to_hex()),
+            milestone_index_spent: value.spent_metadata.map(|spent| spent.spent.milestone_index),
+            milestone_timestamp_spent: value.spent_metadata.map(|spent| spent.spent.milestone_timestamp),
+        }
+    }
+}
+
+impl NativeTokenDistributionResponse {
+    pub fn new(token_id: NativeTokenId, value: TokenDistributionResult) -> Self {
+        Self {
+            token_id: prefix_hex::encode(token_id.0),
+            ledger_index: value.ledger_index,
+            holder_count: value.distribution.holder_count,
+            total_amount: value.distribution.total_amount,
+            top10_share: value.distribution.top_holders_share,
+            gini: value.distribution.gini,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransactionsResponse {
+    pub items: Vec<PendingTransactionResponse>,
+}
+
+impl_success_response!(PendingTransactionsResponse);
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransactionResponse {
+    pub transaction_id: String,
+    pub block_id: String,
+    /// The unix timestamp at which the transaction was first seen.
+    pub received_at: i64,
+    pub payload: TransactionPayloadDto,
+}
+
+impl_success_response!(PendingTransactionResponse);
+
+impl From<PendingTransactionResult> for PendingTransactionResponse {
+    fn from(value: PendingTransactionResult) -> Self {
+        Self {
+            transaction_id: value.transaction_id.to_hex(),
+            block_id: value.block_id.to_hex(),
+            received_at: value.received_at.timestamp_millis() / 1000,
+            payload: value.payload.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSizeResponse {
+    pub items: Vec<DataSizeDto>,
+}
+
+impl_success_response!(DataSizeResponse);
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSizeDto {
+    pub start_timestamp: MilestoneTimestamp,
+    /// The number of milestones whose data size is known.
+    pub milestone_count: u64,
+    pub block_count: u64,
+    pub block_bytes: u64,
+    pub output_count: u64,
+    pub output_bytes: u64,
+}
+
+impl DataSizeDto {
+    pub fn empty(start_timestamp: MilestoneTimestamp) -> Self {
+        Self {
+            start_timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<DataSizeBucket> for DataSizeDto {
+    fn from(b: DataSizeBucket) -> Self {
+        Self {
+            start_timestamp: b.start_timestamp,
+            milestone_count: b.milestone_count,
+            block_count: b.data_size.block_count,
+            block_bytes: b.data_size.block_bytes,
+            output_count: b.data_size.output_count,
+            output_bytes: b.data_size.output_bytes,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageForecastResponse {
+    /// The unix timestamp of the latest storage sample.
+    pub sampled_at: i64,
+    /// The unix timestamp of the sample that the growth is measured from.
+    pub growth_since: i64,
+    pub ledger_index: Option<MilestoneIndex>,
+    pub collections: Vec<CollectionForecastDto>,
+    /// The projected storage of all collections combined.
+    pub total: Vec<StorageProjectionDto>,
+}
+
+impl_success_response!(StorageForecastResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionForecastDto {
+    pub name: String,
+    pub count: u64,
+    pub size: u64,
+    pub documents_per_milestone: Option<f64>,
+    pub bytes_per_milestone: Option<f64>,
+    pub documents_per_day: f64,
+    pub bytes_per_day: f64,
+    pub projections: Vec<StorageProjectionDto>,
+}
+
+impl CollectionForecastDto {
+    pub fn new(growth: CollectionGrowth, horizons: &[Duration]) -> Self {
+        Self {
+            projections: horizons
+                .iter()
+                .map(|horizon| {
+                    let projected = growth.project(*horizon);
+                    StorageProjectionDto::new(*horizon, projected.count, projected.size)
+                })
+                .collect(),
+            name: growth.storage.name,
+            count: growth.storage.count,
+            size: growth.storage.size,
+            documents_per_milestone: growth.documents_per_milestone,
+            bytes_per_milestone: growth.bytes_per_milestone,
+            documents_per_day: growth.documents_per_day,
+            bytes_per_day: growth.bytes_per_day,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageProjectionDto {
+    pub horizon: String,
+    pub count: u64,
+    pub size: u64,
+}
+
+impl StorageProjectionDto {
+    pub fn new(horizon: Duration, count: u64, size: u64) -> Self {
+        Self {
+            horizon: humantime::format_duration(horizon).to_string(),
+            count,
+            size,
+        }
+    }
+}
+
+#[cfg(feature = "search")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse {
+    pub items: Vec<SearchResultDto>,
+}
+
+#[cfg(feature = "search")]
+impl_success_response!(SearchResponse);
+
+#[cfg(feature = "search")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultDto {
+    pub kind: crate::search::SearchDocumentKind,
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    pub milestone_index: MilestoneIndex,
+}
+
+#[cfg(feature = "search")]
+impl From<crate::search::SearchDocument> for SearchResultDto {
+    fn from(value: crate::search::SearchDocument) -> Self {
+        Self {
+            kind: value.kind,
+            key: value.key,
+            text: value.text,
+            milestone_index: value.milestone_index,
+        }
+    }
+}
@@ -1,72 +1,225 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::str::FromStr;
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet},
+    str::FromStr,
+};
 
-use axum::{extract::Path, routing::get, Extension};
+use axum::{
+    extract::Path,
+    middleware::{from_extractor, from_fn},
+    routing::{get, post},
+    Extension,
+};
 use chronicle::{
     db::{
         mongodb::collections::{
-            BlockCollection, LedgerUpdateCollection, MilestoneCollection, OutputCollection, ProtocolUpdateCollection,
+            AddressFilterCollection, BlockCollection, EntityRelationship, IndexedId, LedgerSnapshotCollection,
+            LedgerUpdateCollection, MaterializedViewCollection, MilestoneCollection, OutputCollection,
+            ParentsCollection, PendingTransactionCollection, ProtocolUpdateCollection, SortOrder, SpamFlagCollection,
+            StorageSampleCollection, TokenDistributionCollection, TIMELOCK_HORIZONS,
         },
         MongoDb,
     },
     model::{
-        payload::{MilestoneId, MilestonePayload, TaggedDataPayload, TransactionPayload, TreasuryTransactionPayload},
-        tangle::MilestoneIndex,
-        utxo::Address,
-        BlockId,
+        ledger::{self, LedgerOutput, LedgerSpent},
+        payload::{
+            MilestoneId, MilestonePayload, Payload, TaggedDataPayload, TransactionEssence, TransactionId,
+            TransactionPayload, TreasuryTransactionPayload,
+        },
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+        utxo::{
+            unlock_addresses, Address, AliasAddress, AliasId, Input, NativeTokenId, NftAddress, NftId, Output,
+            OutputId, Unlock,
+        },
+        BlockId, TryFromWithContext,
     },
 };
 use futures::{StreamExt, TryStreamExt};
 use iota_sdk::types::block::address::ToBech32Ext;
+use packable::PackableExt;
+use tracing::debug;
 
 use super::{
     extractors::{
-        BlocksByMilestoneCursor, BlocksByMilestoneIdPagination, BlocksByMilestoneIndexPagination, LedgerIndex,
+        ActiveAddressesQuery, AddressTransactionsCursor, AddressTransactionsPagination, BalanceHistoryCursor,
+        BalanceHistoryPagination, BalancesAtMilestoneRequest, BlocksByMilestoneCursor, BlocksByMilestoneIdPagination,
+        BlocksByMilestoneIndexPagination, DataSizeQuery, FlaggedMilestonesPagination, LedgerIndex,
         LedgerUpdatesByAddressCursor, LedgerUpdatesByAddressPagination, LedgerUpdatesByMilestoneCursor,
-        LedgerUpdatesByMilestonePagination, MilestonesCursor, MilestonesPagination, RichestAddressesQuery,
+        LedgerUpdatesByMilestonePagination, MilestoneConversionQuery, MilestoneRange, MilestonesCursor,
+        MilestonesPagination, NftSalesQuery, OutputHistoryCursor, OutputHistoryPagination, RichestAddressesQuery,
+        SimulateTransactionRequest, StorageForecastQuery, TopTagsQuery, TransactionBytes, TransferGraphQuery,
+        TransferVolumeQuery, VolumeInterval, MAX_ACTIVITY_DAYS, MAX_VOLUME_BUCKETS,
     },
+    graph::TransferGraph,
     responses::{
-        AddressStatDto, BalanceResponse, BlockChildrenResponse, BlockPayloadTypeDto, BlocksByMilestoneResponse,
-        LedgerUpdatesByAddressResponse, LedgerUpdatesByMilestoneResponse, MilestonesResponse, RichestAddressesResponse,
-        TokenDistributionResponse,
+        ActiveAddressesResponse, AddressActivityDto, AddressActivityResponse, AddressFilterResponse,
+        AddressFlowResponse, AddressStatDto, AddressTransactionsResponse, BalanceAtMilestoneDto, BalanceHistoryDto,
+        BalanceHistoryResponse, BalanceResponse, BalancesAtMilestoneResponse, BlockChildrenResponse,
+        BlockPayloadTypeDto, BlocksByMilestoneResponse, CollectionForecastDto, DataSizeDto, DataSizeResponse,
+        DecodedOutputDto, DecodedTransactionResponse, DecodedUnlockDto, EntityEdgeDto, EntityNodeDto,
+        EntityRelationshipsResponse, FlaggedMilestoneDto, FlaggedMilestonesResponse, LedgerSnapshotsResponse,
+        LedgerUpdatesByAddressResponse, LedgerUpdatesByMilestoneResponse, MilestoneConversionResponse,
+        MilestonesResponse, NativeTokenDistributionResponse, NetTransferDto, NftSaleDto, NftSalesResponse,
+        OutputHistoryResponse, PendingTransactionResponse, PendingTransactionsResponse, ProtocolChangeDto,
+        ProtocolChangesResponse, ProtocolParameterChangeDto, RichestAddressesResponse, StorageForecastResponse,
+        StorageProjectionDto, TimelockHorizonDto, TimelockedValueResponse, TokenDistributionResponse, TopTagsResponse,
+        TransactionSimulationResponse, TransferGraphResponse, TransferVolumeDto, TransferVolumeResponse,
+        UnspentOutputDigestResponse, UnspentOutputIdsResponse, ViewResultsResponse, ViewsResponse,
     },
+    simulation::{check_transaction, Issue, IssueKind},
 };
-use crate::api::{
-    error::{CorruptStateError, MissingError, RequestError},
-    extractors::Pagination,
-    router::Router,
-    ApiResult,
+use crate::{
+    api::{
+        auth::Admin,
+        error::{CorruptStateError, MissingError, RequestError},
+        etag,
+        extractors::Pagination,
+        query_cache, query_pool,
+        router::Router,
+        ApiResult,
+    },
+    naming::SharedNameResolver,
+    storage_forecast::time_before,
 };
 
 pub fn routes() -> Router {
-    Router::new()
-        .route("/balance/:address", get(balance))
+    let router = Router::new()
+        .route("/balance/at-milestone", post(balances_at_milestone))
+        .route("/balance/:address/history", get(balance_history))
+        .route_layer(from_fn(query_pool::limit))
         .route("/blocks/:block_id/children", get(block_children))
+        .route("/transactions/:transaction_id/decoded", get(decoded_transaction))
         .nest(
             "/milestones",
             Router::new()
                 .route("/", get(milestones))
+                .route("/convert", get(convert_milestone))
+                .route("/flagged", get(flagged_milestones))
                 .route("/:milestone_id/blocks", get(blocks_by_milestone_id))
                 .route("/by-index/:milestone_index/blocks", get(blocks_by_milestone_index)),
         )
+        .route("/aliases/:alias_id/history", get(alias_history))
+        .route("/nfts/:nft_id/history", get(nft_history))
+        .route("/protocol-changes", get(protocol_changes))
+        // The routes above only change with the ledger, so their responses can be revalidated by ledger index.
+        .route_layer(from_fn(etag::conditional))
+        // The balance and the updates of an address are sent with its name, which can change without the ledger.
+        .route("/balance/:address", get(balance))
         .nest(
             "/ledger",
             Router::new()
                 .route("/richest-addresses", get(richest_addresses_ledger_analytics))
                 .route("/token-distribution", get(token_distribution_ledger_analytics))
+                .route("/tags", get(top_tags))
+                .route("/timelocked-value", get(timelocked_value))
+                .route("/graph/:address", get(transfer_graph))
+                .route("/relationships/:address", get(entity_relationships))
+                .route("/transfer-volume", get(transfer_volume))
+                .route("/active-addresses", get(active_addresses))
+                .route("/flow/by-address/:address", get(address_flow))
+                .route("/transactions/by-address/:address", get(address_transactions))
+                // The aggregations above are run in the query pool, unless their result is cached.
+                .route_layer(from_fn(query_pool::limit))
+                .route_layer(from_fn(query_cache::cached))
+                .route("/snapshots", get(ledger_snapshots))
+                .route("/address-filter", get(address_filter))
                 .nest(
-                    "/updates",
+                    "/unspent-outputs",
                     Router::new()
-                        .route("/by-address/:address", get(ledger_updates_by_address))
-                        .route("/by-milestone/:milestone_id", get(ledger_updates_by_milestone)),
-                ),
+                        .route("/by-prefix/:prefix", get(unspent_output_ids_by_prefix))
+                        .route("/by-prefix/:prefix/digest", get(unspent_output_digest_by_prefix)),
+                )
+                .route("/updates/by-milestone/:milestone_id", get(ledger_updates_by_milestone))
+                .route_layer(from_fn(etag::conditional))
+                // Sales are read from outputs that can be pruned, and the activity of an address is only served to
+                // admins, so neither can be revalidated by the ledger index alone. The admin is checked before the
+                // query cache, which would otherwise serve the activity without running the handler.
+                .route(
+                    "/nft-sales",
+                    get(nft_sales)
+                        .route_layer(from_fn(query_pool::limit))
+                        .route_layer(from_fn(query_cache::cached)),
+                )
+                .route(
+                    "/activity/by-address/:address",
+                    get(address_activity)
+                        .route_layer(from_fn(query_pool::limit))
+                        .route_layer(from_fn(query_cache::cached))
+                        .route_layer(from_extractor::<Admin>()),
+                )
+                .route("/updates/by-address/:address", get(ledger_updates_by_address)),
+        )
+        .nest(
+            "/views",
+            Router::new().route("/", get(views)).route("/:name", get(view_results)),
+        )
+        .route("/tokens/:token_id/distribution", get(native_token_distribution))
+        .nest(
+            "/pending-transactions",
+            Router::new()
+                .route("/", get(pending_transactions))
+                .route("/:transaction_id", get(pending_transaction)),
         )
+        .nest(
+            "/storage",
+            Router::new()
+                .route("/data-size", get(data_size))
+                .route_layer(from_fn(query_pool::limit))
+                .route("/forecast", get(storage_forecast)),
+        )
+        .route("/transactions/simulate", post(simulate_transaction));
+
+    #[cfg(feature = "search")]
+    let router = router.route("/search", get(search));
+
+    router
+}
+
+#[cfg(feature = "search")]
+async fn search(
+    database: Extension<MongoDb>,
+    Extension(search_client): Extension<Option<crate::search::SearchClient>>,
+    Extension(name_resolver): Extension<Option<SharedNameResolver>>,
+    super::extractors::SearchQuery { q, limit }: super::extractors::SearchQuery,
+) -> ApiResult<super::responses::SearchResponse> {
+    if search_client.is_none() && name_resolver.is_none() {
+        return Err(crate::api::error::UnimplementedError.into());
+    }
+    let mut items = Vec::new();
+    // A registered name is the most relevant result, so it is listed first.
+    if let Some(name_resolver) = &name_resolver {
+        match name_resolver.resolve(&q).await {
+            Ok(Some(address)) => items.push(super::responses::SearchResultDto {
+                kind: crate::search::SearchDocumentKind::Address,
+                key: address,
+                text: Some(q.clone()),
+                milestone_index: database
+                    .collection::<MilestoneCollection>()
+                    .get_ledger_index()
+                    .await?
+                    .unwrap_or_default(),
+            }),
+            Ok(None) => (),
+            Err(e) => debug!("Failed to resolve the name `{q}`: {e}"),
+        }
+    }
+    if let Some(search_client) = search_client {
+        items.extend(
+            search_client
+                .search(&q, limit.saturating_sub(items.len()))
+                .await?
+                .into_iter()
+                .map(Into::into),
+        );
+    }
+    Ok(super::responses::SearchResponse { items })
 }
 
 async fn ledger_updates_by_address(
     database: Extension<MongoDb>,
+    Extension(name_resolver): Extension<Option<SharedNameResolver>>,
     Path(address): Path<String>,
     LedgerUpdatesByAddressPagination {
         page_size,
@@ -75,6 +228,11 @@ async fn ledger_updates_by_address(
     }: LedgerUpdatesByAddressPagination,
 ) -> ApiResult<LedgerUpdatesByAddressResponse> {
     let address_dto = Address::from_str(&address).map_err(RequestError::from)?;
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
 
     let mut record_stream = database
         .collection::<LedgerUpdateCollection>()
@@ -84,6 +242,7 @@ async fn ledger_updates_by_address(
             page_size + 1,
             cursor,
             sort,
+            ledger_index,
         )
         .await?;
 
@@ -106,7 +265,55 @@ async fn ledger_updates_by_address(
         .to_string()
     });
 
-    Ok(LedgerUpdatesByAddressResponse { address, items, cursor })
+    Ok(LedgerUpdatesByAddressResponse {
+        name: address_name(&name_resolver, &address).await,
+        address,
+        items,
+        cursor,
+    })
+}
+
+async fn address_transactions(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    AddressTransactionsPagination {
+        page_size,
+        sort,
+        cursor,
+    }: AddressTransactionsPagination,
+) -> ApiResult<AddressTransactionsResponse> {
+    let address_dto = Address::from_str(&address).map_err(RequestError::from)?;
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+
+    let mut record_stream = database
+        .collection::<LedgerUpdateCollection>()
+        // Get one extra record so that we can create the cursor.
+        .get_address_transactions(&address_dto, page_size + 1, cursor, sort, ledger_index)
+        .await?;
+
+    // Take all of the requested records first
+    let items = record_stream
+        .by_ref()
+        .take(page_size)
+        .map_ok(Into::into)
+        .try_collect()
+        .await?;
+
+    // If any record is left, use it to make the cursor
+    let cursor = record_stream.try_next().await?.map(|rec| {
+        AddressTransactionsCursor {
+            milestone_index: rec.at.milestone_index,
+            transaction_id: rec.transaction_id,
+            page_size,
+        }
+        .to_string()
+    });
+
+    Ok(AddressTransactionsResponse { address, items, cursor })
 }
 
 async fn ledger_updates_by_milestone(
@@ -154,13 +361,107 @@ async fn ledger_updates_by_milestone(
     })
 }
 
-async fn balance(database: Extension<MongoDb>, Path(address): Path<String>) -> ApiResult<BalanceResponse> {
+async fn address_activity(
+    database: Extension<MongoDb>,
+    _: Admin,
+    Path(address): Path<String>,
+    MilestoneRange { start_index, end_index }: MilestoneRange,
+) -> ApiResult<AddressActivityResponse> {
+    let address_dto = Address::from_str(&address).map_err(RequestError::from)?;
+    let ledger_index = match end_index {
+        Some(end_index) => end_index,
+        None => database
+            .collection::<MilestoneCollection>()
+            .get_ledger_index()
+            .await?
+            .ok_or(MissingError::NoResults)?,
+    };
+    let mut records = database
+        .collection::<LedgerUpdateCollection>()
+        .get_address_activity(&address_dto, ledger_index)
+        .await?;
+
+    // The balance is accumulated from the start of the ledger, even if only a part of the history is requested.
+    let mut balance = 0i128;
+    let mut items = Vec::new();
+    while let Some(record) = records.try_next().await? {
+        balance += record.received as i128 - record.sent as i128;
+        if start_index.map_or(true, |start_index| record.milestone_index >= start_index) {
+            items.push(AddressActivityDto {
+                milestone_index: record.milestone_index,
+                milestone_timestamp: record.milestone_timestamp,
+                created_count: record.created_count,
+                consumed_count: record.consumed_count,
+                received: record.received.to_string(),
+                sent: record.sent.to_string(),
+                balance: balance.to_string(),
+            });
+        }
+    }
+
+    Ok(AddressActivityResponse {
+        address,
+        ledger_index,
+        items,
+    })
+}
+
+async fn address_flow(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    MilestoneRange { start_index, end_index }: MilestoneRange,
+) -> ApiResult<AddressFlowResponse> {
+    let address_dto = Address::from_str(&address).map_err(RequestError::from)?;
+    let end_index = match end_index {
+        Some(end_index) => end_index,
+        None => database
+            .collection::<MilestoneCollection>()
+            .get_ledger_index()
+            .await?
+            .ok_or(MissingError::NoResults)?,
+    };
+    let start_index = start_index.unwrap_or_default();
+    let flow = database
+        .collection::<LedgerUpdateCollection>()
+        .get_address_flow(&address_dto, start_index, end_index)
+        .await?;
+
+    Ok(AddressFlowResponse {
+        address,
+        start_index,
+        end_index,
+        created_count: flow.created_count,
+        consumed_count: flow.consumed_count,
+        pruned_count: flow.pruned_count,
+        received: flow.received.to_string(),
+        sent: flow.sent.to_string(),
+        net: flow.net().to_string(),
+    })
+}
+
+/// Looks up the name of an address, if a name resolver is configured. A failing lookup only leaves out the name.
+async fn address_name(name_resolver: &Option<SharedNameResolver>, address: &str) -> Option<String> {
+    match name_resolver.as_ref()?.reverse(address).await {
+        Ok(name) => name,
+        Err(e) => {
+            debug!("Failed to look up the name of `{address}`: {e}");
+            None
+        }
+    }
+}
+
+async fn balance(
+    database: Extension<MongoDb>,
+    Extension(name_resolver): Extension<Option<SharedNameResolver>>,
+    Path(address): Path<String>,
+) -> ApiResult<BalanceResponse> {
     let ledger_ms = database
         .collection::<MilestoneCollection>()
         .get_newest_milestone()
         .await?
         .ok_or(MissingError::NoResults)?;
-    let address = Address::from_str(&address).map_err(RequestError::from)?;
+    let address_str = address;
+    let address = Address::from_str(&address_str).map_err(RequestError::from)?;
     let res = database
         .collection::<OutputCollection>()
         .get_address_balance(address, ledger_ms)
@@ -171,33 +472,291 @@ async fn balance(database: Extension<MongoDb>, Path(address): Path<String>) -> A
         total_balance: res.total_balance,
         available_balance: res.available_balance,
         ledger_index: ledger_ms.milestone_index,
+        name: address_name(&name_resolver, &address_str).await,
+    })
+}
+
+async fn balance_history(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    BalanceHistoryPagination {
+        interval,
+        start_index,
+        end_index,
+        page_size,
+    }: BalanceHistoryPagination,
+) -> ApiResult<BalanceHistoryResponse> {
+    let address_dto = Address::from_str(&address).map_err(RequestError::from)?;
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let end_index = end_index.map_or(ledger_index, |end_index| end_index.min(ledger_index));
+
+    // The changes are added to the balance the address had before the first range, rather than accumulated from the
+    // start of the ledger, so that only the requested page is read.
+    let mut balance = match start_index.0.checked_sub(1) {
+        Some(index) => {
+            database
+                .collection::<OutputCollection>()
+                .get_address_amount_at(&address_dto, index.into())
+                .await? as i128
+        }
+        None => 0,
+    };
+    let mut records = database
+        .collection::<LedgerUpdateCollection>()
+        // Get one extra record so that we can create the cursor.
+        .get_address_balance_changes(&address_dto, interval, start_index, end_index, page_size + 1)
+        .await?;
+
+    let mut items = Vec::new();
+    let mut cursor = None;
+    while let Some(record) = records.try_next().await? {
+        if items.len() == page_size {
+            cursor = Some(
+                BalanceHistoryCursor {
+                    start_index: record.start_index,
+                    interval,
+                    page_size,
+                }
+                .to_string(),
+            );
+            break;
+        }
+        balance += record.received as i128 - record.sent as i128;
+        items.push(BalanceHistoryDto {
+            start_index: record.start_index,
+            end_index: record
+                .start_index
+                .0
+                .saturating_add(interval - 1)
+                .min(end_index.0)
+                .into(),
+            received: record.received.to_string(),
+            sent: record.sent.to_string(),
+            balance: balance.to_string(),
+        });
+    }
+
+    Ok(BalanceHistoryResponse {
+        address,
+        ledger_index: end_index,
+        interval,
+        items,
+        cursor,
+    })
+}
+
+/// The number of balance aggregations that are run against the database at the same time.
+const BALANCE_QUERY_CONCURRENCY: usize = 16;
+
+async fn simulate_transaction(
+    database: Extension<MongoDb>,
+    SimulateTransactionRequest {
+        transaction,
+        milestone_index,
+    }: SimulateTransactionRequest,
+) -> ApiResult<TransactionSimulationResponse> {
+    use iota_sdk::types::block::{
+        input::Input,
+        payload::transaction::{TransactionEssence, TransactionPayload},
+    };
+
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let milestone_index = milestone_index.unwrap_or(ledger_index);
+    if milestone_index > ledger_index {
+        return Err(RequestError::FutureMilestone(milestone_index, ledger_index).into());
+    }
+    let milestone_timestamp = database
+        .collection::<MilestoneCollection>()
+        .get_milestone_timestamp(milestone_index)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let protocol_params = iota_sdk::types::block::protocol::ProtocolParameters::try_from(
+        database
+            .collection::<ProtocolUpdateCollection>()
+            .get_protocol_parameters_for_ledger_index(milestone_index)
+            .await?
+            .ok_or(CorruptStateError::ProtocolParams)?
+            .parameters,
+    )?;
+
+    let invalid_bytes = |e| RequestError::TransactionBytes(format!("{e:?}"));
+    let (essence, payload) = match transaction {
+        TransactionBytes::Payload(bytes) => {
+            let payload = TransactionPayload::unpack_verified(bytes, &protocol_params).map_err(invalid_bytes)?;
+            (payload.essence().clone(), Some(payload))
+        }
+        TransactionBytes::Essence(bytes) => (
+            TransactionEssence::unpack_verified(bytes, &protocol_params).map_err(invalid_bytes)?,
+            None,
+        ),
+    };
+    let TransactionEssence::Regular(essence) = &essence;
+
+    let mut issues = Vec::new();
+    if essence.network_id() != protocol_params.network_id() {
+        issues.push(Issue::new(
+            IssueKind::NetworkMismatch,
+            None,
+            format!(
+                "the transaction is meant for the network with id {}",
+                essence.network_id()
+            ),
+        ));
+    }
+
+    // The remaining checks need all inputs, so they only run if every input was unspent at the milestone.
+    let mut inputs = Vec::with_capacity(essence.inputs().len());
+    for (index, input) in essence.inputs().iter().enumerate() {
+        let input = match input {
+            Input::Utxo(input) => input,
+            _ => {
+                issues.push(Issue::new(
+                    IssueKind::UnsupportedInput,
+                    index,
+                    "only UTXO inputs can be consumed",
+                ));
+                continue;
+            }
+        };
+        let output_id = *input.output_id();
+        match database
+            .collection::<OutputCollection>()
+            .get_output_with_metadata(&output_id.into(), milestone_index)
+            .await?
+        {
+            None => issues.push(Issue::new(
+                IssueKind::InputNotFound,
+                index,
+                format!("output {output_id} did not exist at the milestone"),
+            )),
+            Some(res) => match res.metadata.spent_metadata {
+                Some(spent) if spent.spent.milestone_index <= milestone_index => issues.push(Issue::new(
+                    IssueKind::InputSpent,
+                    index,
+                    format!(
+                        "output {output_id} was spent by transaction {} in milestone {}",
+                        spent.transaction_id.to_hex(),
+                        spent.spent.milestone_index
+                    ),
+                )),
+                _ => inputs.push((
+                    output_id,
+                    iota_sdk::types::block::output::Output::try_from_with_context(&protocol_params, res.output)?,
+                )),
+            },
+        }
+    }
+    let complete = inputs.len() == essence.inputs().len();
+    if complete {
+        issues.extend(check_transaction(
+            essence,
+            &inputs,
+            payload.as_ref().map(|payload| (payload.id(), payload.unlocks())),
+            milestone_timestamp.0,
+        ));
+    }
+
+    Ok(TransactionSimulationResponse {
+        transaction_id: payload.as_ref().map(|payload| payload.id().to_string()),
+        milestone_index,
+        is_valid: issues.is_empty(),
+        signatures_checked: complete && payload.is_some(),
+        issues,
     })
 }
 
+async fn balances_at_milestone(
+    database: Extension<MongoDb>,
+    BalancesAtMilestoneRequest(items): BalancesAtMilestoneRequest,
+) -> ApiResult<BalancesAtMilestoneResponse> {
+    // All balances are computed against the same ledger index, so that milestones being ingested while the request is
+    // handled cannot affect the result.
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+
+    let mut queries = Vec::with_capacity(items.len());
+    for item in items {
+        if item.milestone_index > ledger_index {
+            return Err(RequestError::FutureMilestone(item.milestone_index, ledger_index).into());
+        }
+        let address = Address::from_str(&item.address).map_err(RequestError::from)?;
+        queries.push((item.address, address, item.milestone_index));
+    }
+
+    // Many queries typically share a milestone, so only look up each timestamp once.
+    let mut milestones = BTreeMap::new();
+    for &(_, _, milestone_index) in &queries {
+        if let Entry::Vacant(entry) = milestones.entry(milestone_index) {
+            entry.insert(
+                database
+                    .collection::<MilestoneCollection>()
+                    .get_milestone_timestamp(milestone_index)
+                    .await?
+                    .ok_or(MissingError::NoResults)?,
+            );
+        }
+    }
+
+    let output_collection = database.collection::<OutputCollection>();
+    let items = futures::stream::iter(queries)
+        .map(|(bech32, address, milestone_index)| {
+            let milestone_timestamp = milestones[&milestone_index];
+            let output_collection = &output_collection;
+            async move {
+                let balance = output_collection
+                    .get_address_balance(
+                        address,
+                        MilestoneIndexTimestamp {
+                            milestone_index,
+                            milestone_timestamp,
+                        },
+                    )
+                    .await?;
+                let (total_balance, available_balance) = balance
+                    .map(|res| (res.total_balance, res.available_balance))
+                    .unwrap_or_else(|| ("0".to_string(), "0".to_string()));
+                Ok::<_, mongodb::error::Error>(BalanceAtMilestoneDto {
+                    address: bech32,
+                    milestone_index,
+                    milestone_timestamp,
+                    total_balance,
+                    available_balance,
+                })
+            }
+        })
+        .buffered(BALANCE_QUERY_CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    Ok(BalancesAtMilestoneResponse { items, ledger_index })
+}
+
 async fn block_children(
     database: Extension<MongoDb>,
     Path(block_id): Path<String>,
     Pagination { page_size, page }: Pagination,
 ) -> ApiResult<BlockChildrenResponse> {
     let block_id = BlockId::from_str(&block_id).map_err(RequestError::from)?;
-    let block_referenced_index = database
+    database
         .collection::<BlockCollection>()
         .get_block_metadata(&block_id)
         .await?
-        .ok_or(MissingError::NoResults)?
-        .referenced_by_milestone_index;
-    let below_max_depth = database
-        .collection::<ProtocolUpdateCollection>()
-        .get_protocol_parameters_for_ledger_index(block_referenced_index)
-        .await?
-        .ok_or(MissingError::NoResults)?
-        .parameters
-        .below_max_depth;
+        .ok_or(MissingError::NoResults)?;
     let mut block_children = database
-        .collection::<BlockCollection>()
-        .get_block_children(&block_id, block_referenced_index, below_max_depth, page_size, page)
-        .await
-        .map_err(|_| MissingError::NoResults)?;
+        .collection::<ParentsCollection>()
+        .get_block_children(&block_id, page_size, page)
+        .await?;
 
     let mut children = Vec::new();
     while let Some(block_id) = block_children.try_next().await? {
@@ -212,6 +771,125 @@ async fn block_children(
     })
 }
 
+async fn decoded_transaction(
+    database: Extension<MongoDb>,
+    Path(transaction_id): Path<String>,
+) -> ApiResult<DecodedTransactionResponse> {
+    let transaction_id = TransactionId::from_str(&transaction_id).map_err(RequestError::from)?;
+    let included = database
+        .collection::<BlockCollection>()
+        .get_block_for_transaction(&transaction_id)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let payload = match included.block.payload {
+        Some(Payload::Transaction(payload)) => payload,
+        _ => return Err(MissingError::NoResults.into()),
+    };
+    let TransactionEssence::Regular { inputs, outputs, .. } = payload.essence;
+
+    let milestone_index = database
+        .collection::<BlockCollection>()
+        .get_block_metadata_for_transaction(&transaction_id)
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .metadata
+        .referenced_by_milestone_index;
+    let milestone_timestamp = database
+        .collection::<MilestoneCollection>()
+        .get_milestone_timestamp(milestone_index)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let hrp = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(milestone_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?
+        .parameters
+        .bech32_hrp
+        .parse()?;
+    let to_bech32 = |address: Address| {
+        iota_sdk::types::block::address::Address::from(address)
+            .to_bech32(hrp)
+            .to_string()
+    };
+
+    let mut consumed = Vec::with_capacity(inputs.len());
+    for input in inputs.iter() {
+        if let Input::Utxo(output_id) = input {
+            let output = database
+                .collection::<OutputCollection>()
+                .get_output(output_id)
+                .await?
+                .ok_or(MissingError::NoResults)?;
+            consumed.push((*output_id, output));
+        }
+    }
+    let created = outputs
+        .into_vec()
+        .into_iter()
+        .enumerate()
+        .map(|(index, output)| ((transaction_id, index as u16).into(), output))
+        .collect::<Vec<(OutputId, Output)>>();
+
+    let mut net_transfers = BTreeMap::<String, i128>::new();
+    let mut decode = |outputs: &[(OutputId, Output)], sign: i128| {
+        outputs
+            .iter()
+            .map(|(output_id, output)| {
+                let address = output.owning_address(milestone_timestamp).copied().map(to_bech32);
+                if let Some(address) = &address {
+                    *net_transfers.entry(address.clone()).or_default() += sign * output.amount().0 as i128;
+                }
+                DecodedOutputDto {
+                    output_id: output_id.to_hex(),
+                    kind: output.kind().to_string(),
+                    address,
+                    amount: output.amount().0.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let decoded_inputs = decode(&consumed, -1);
+    let decoded_outputs = decode(&created, 1);
+
+    let unlocks = payload
+        .unlocks
+        .iter()
+        .zip(unlock_addresses(&payload.unlocks, &consumed))
+        .map(|(unlock, address)| {
+            let (kind, reference) = match *unlock {
+                Unlock::Signature { .. } => ("signature", None),
+                Unlock::Reference { index } => ("reference", Some(index)),
+                Unlock::Alias { index } => ("alias", Some(index)),
+                Unlock::Nft { index } => ("nft", Some(index)),
+            };
+            DecodedUnlockDto {
+                kind: kind.to_string(),
+                reference,
+                address: address.map(to_bech32),
+            }
+        })
+        .collect();
+
+    Ok(DecodedTransactionResponse {
+        transaction_id: transaction_id.to_hex(),
+        block_id: included.block_id.to_hex(),
+        milestone_index,
+        milestone_timestamp,
+        inputs: decoded_inputs,
+        outputs: decoded_outputs,
+        unlocks,
+        net_transfers: net_transfers
+            .into_iter()
+            .filter(|(_, amount)| *amount != 0)
+            .map(|(address, amount)| NetTransferDto {
+                address,
+                amount: amount.to_string(),
+            })
+            .collect(),
+    })
+}
+
 async fn milestones(
     database: Extension<MongoDb>,
     MilestonesPagination {
@@ -247,6 +925,67 @@ async fn milestones(
     Ok(MilestonesResponse { items, cursor })
 }
 
+async fn flagged_milestones(
+    database: Extension<MongoDb>,
+    FlaggedMilestonesPagination {
+        start_index,
+        end_index,
+        sort,
+        page_size,
+        cursor,
+    }: FlaggedMilestonesPagination,
+) -> ApiResult<FlaggedMilestonesResponse> {
+    let ledger_index = resolve_ledger_index(&database, None).await?;
+    let hrp = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(ledger_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?
+        .parameters
+        .bech32_hrp
+        .parse()?;
+
+    let mut record_stream = database
+        .collection::<SpamFlagCollection>()
+        .get_flagged_milestones(start_index, end_index, sort, page_size + 1, cursor)
+        .await?;
+
+    // Take all of the requested records first
+    let items = record_stream
+        .by_ref()
+        .take(page_size)
+        .map_ok(|doc| FlaggedMilestoneDto {
+            milestone_index: doc.milestone_index,
+            milestone_timestamp: doc.milestone_timestamp,
+            block_spike: doc.flags.block_spike,
+            duplicate_payloads: doc.flags.duplicate_payloads,
+            issuer_dominance: doc.flags.issuer_dominance,
+            block_count: doc.block_count,
+            baseline_block_count: doc.baseline_block_count,
+            duplicate_payload_count: doc.duplicate_payload_count,
+            transaction_count: doc.transaction_count,
+            top_issuer: doc.top_issuer.map(|address| {
+                iota_sdk::types::block::address::Address::from(address)
+                    .to_bech32(hrp)
+                    .to_string()
+            }),
+            top_issuer_transaction_count: doc.top_issuer_transaction_count,
+        })
+        .try_collect()
+        .await?;
+
+    // If any record is left, use it to make the paging state
+    let cursor = record_stream.try_next().await?.map(|doc| {
+        MilestonesCursor {
+            milestone_index: doc.milestone_index,
+            page_size,
+        }
+        .to_string()
+    });
+
+    Ok(FlaggedMilestonesResponse { items, cursor })
+}
+
 async fn blocks_by_milestone_index(
     database: Extension<MongoDb>,
     Path(milestone_index): Path<MilestoneIndex>,
@@ -353,6 +1092,22 @@ async fn richest_addresses_ledger_analytics(
     })
 }
 
+async fn top_tags(
+    database: Extension<MongoDb>,
+    TopTagsQuery { top, ledger_index }: TopTagsQuery,
+) -> ApiResult<TopTagsResponse> {
+    let ledger_index = resolve_ledger_index(&database, ledger_index).await?;
+    let tags = database
+        .collection::<OutputCollection>()
+        .get_top_tags(ledger_index, top)
+        .await?;
+
+    Ok(TopTagsResponse {
+        tags: tags.into_iter().map(Into::into).collect(),
+        ledger_index,
+    })
+}
+
 async fn token_distribution_ledger_analytics(
     database: Extension<MongoDb>,
     LedgerIndex { ledger_index }: LedgerIndex,
@@ -369,6 +1124,779 @@ async fn token_distribution_ledger_analytics(
     })
 }
 
+async fn timelocked_value(
+    database: Extension<MongoDb>,
+    LedgerIndex { ledger_index }: LedgerIndex,
+) -> ApiResult<TimelockedValueResponse> {
+    let ledger_index = resolve_ledger_index(&database, ledger_index).await?;
+    let milestone_timestamp = database
+        .collection::<MilestoneCollection>()
+        .get_milestone_timestamp(ledger_index)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let buckets = database
+        .collection::<OutputCollection>()
+        .get_timelocked_value(ledger_index, milestone_timestamp)
+        .await?;
+
+    let locked_count = buckets.iter().map(|bucket| bucket.count).sum();
+    let locked_amount = buckets.iter().map(|bucket| bucket.amount).sum::<u64>();
+    let mut locked_after = locked_amount;
+    let horizons = TIMELOCK_HORIZONS
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(["later"])
+        .zip(buckets)
+        .map(|(horizon, bucket)| {
+            locked_after -= bucket.amount;
+            TimelockHorizonDto {
+                horizon: horizon.to_string(),
+                unlocks_until: bucket.unlocks_until,
+                count: bucket.count,
+                amount: bucket.amount.to_string(),
+                locked_after: locked_after.to_string(),
+            }
+        })
+        .collect();
+
+    Ok(TimelockedValueResponse {
+        ledger_index,
+        milestone_timestamp,
+        locked_count,
+        locked_amount: locked_amount.to_string(),
+        horizons,
+    })
+}
+
+async fn active_addresses(
+    database: Extension<MongoDb>,
+    ActiveAddressesQuery {
+        start_timestamp,
+        end_timestamp,
+        per_day,
+    }: ActiveAddressesQuery,
+) -> ApiResult<ActiveAddressesResponse> {
+    const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+    let end_timestamp = match end_timestamp {
+        Some(end_timestamp) => end_timestamp,
+        None => {
+            database
+                .collection::<MilestoneCollection>()
+                .get_newest_milestone()
+                .await?
+                .ok_or(MissingError::NoResults)?
+                .milestone_timestamp
+                .0
+                + 1
+        }
+    };
+    let start_timestamp = start_timestamp.unwrap_or_else(|| end_timestamp.saturating_sub(SECONDS_PER_DAY));
+    if per_day && (end_timestamp - start_timestamp + SECONDS_PER_DAY - 1) / SECONDS_PER_DAY > MAX_ACTIVITY_DAYS {
+        return Err(RequestError::BadTimeRange.into());
+    }
+
+    let output_collection = database.collection::<OutputCollection>();
+    let total = output_collection
+        .get_address_activity_in_range(start_timestamp.into(), end_timestamp.into())
+        .await?;
+    let days = if per_day {
+        Some(
+            output_collection
+                .get_daily_address_activity_in_range(start_timestamp.into(), end_timestamp.into())
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    Ok(ActiveAddressesResponse {
+        start_timestamp: start_timestamp.into(),
+        end_timestamp: end_timestamp.into(),
+        total: total.into(),
+        days,
+    })
+}
+
+async fn transfer_volume(
+    database: Extension<MongoDb>,
+    TransferVolumeQuery {
+        interval,
+        start_timestamp,
+        end_timestamp,
+    }: TransferVolumeQuery,
+) -> ApiResult<TransferVolumeResponse> {
+    let bucket_seconds = interval.seconds();
+    let (start_bucket, bucket_count) = bucket_range(&database, interval, start_timestamp, end_timestamp).await?;
+
+    let mut buckets = database
+        .collection::<OutputCollection>()
+        .get_transfer_volume(
+            start_bucket.into(),
+            (start_bucket + bucket_count * bucket_seconds).into(),
+            bucket_seconds,
+        )
+        .await?;
+
+    // Fill in the buckets without any transactions so that the series is contiguous.
+    let mut items = Vec::with_capacity(bucket_count as usize);
+    let mut next = buckets.try_next().await?;
+    for i in 0..bucket_count {
+        let start_timestamp = (start_bucket + i * bucket_seconds).into();
+        match next {
+            Some(bucket) if bucket.start_timestamp == start_timestamp => {
+                items.push(bucket.into());
+                next = buckets.try_next().await?;
+            }
+            _ => items.push(TransferVolumeDto::empty(start_timestamp)),
+        }
+    }
+
+    Ok(TransferVolumeResponse { items })
+}
+
+/// Computes the first bucket and the number of buckets for a time series. Buckets are aligned to the interval, and
+/// the bucket containing the end timestamp, which defaults to the newest milestone, is included.
+async fn bucket_range(
+    database: &MongoDb,
+    interval: VolumeInterval,
+    start_timestamp: Option<u32>,
+    end_timestamp: Option<u32>,
+) -> ApiResult<(u32, u32)> {
+    let end_timestamp = match end_timestamp {
+        Some(end_timestamp) => end_timestamp,
+        None => {
+            database
+                .collection::<MilestoneCollection>()
+                .get_newest_milestone()
+                .await?
+                .ok_or(MissingError::NoResults)?
+                .milestone_timestamp
+                .0
+        }
+    };
+    Ok(bucket_bounds(interval, start_timestamp, end_timestamp)?)
+}
+
+/// Computes the first bucket and the number of buckets for a time series that ends at the given timestamp. Fails if
+/// the start is after the end, or if the series would have too many buckets or end past the representable time.
+fn bucket_bounds(
+    interval: VolumeInterval,
+    start_timestamp: Option<u32>,
+    end_timestamp: u32,
+) -> Result<(u32, u32), RequestError> {
+    let bucket_seconds = interval.seconds();
+    let end_bucket = end_timestamp - end_timestamp % bucket_seconds;
+    let start_bucket = match start_timestamp {
+        Some(start_timestamp) => start_timestamp - start_timestamp % bucket_seconds,
+        None => end_bucket.saturating_sub((interval.default_bucket_count() - 1) * bucket_seconds),
+    };
+    let bucket_count = end_bucket.checked_sub(start_bucket).ok_or(RequestError::BadTimeRange)? / bucket_seconds + 1;
+    // The end of the last bucket is used as the exclusive upper bound of the query.
+    if bucket_count > MAX_VOLUME_BUCKETS || end_bucket.checked_add(bucket_seconds).is_none() {
+        return Err(RequestError::BadTimeRange);
+    }
+    Ok((start_bucket, bucket_count))
+}
+
+async fn nft_sales(
+    database: Extension<MongoDb>,
+    NftSalesQuery { collection, milestones }: NftSalesQuery,
+) -> ApiResult<NftSalesResponse> {
+    let collection = collection
+        .map(|collection| Address::from_str(&collection))
+        .transpose()
+        .map_err(RequestError::from)?;
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let hrp = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(ledger_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?
+        .parameters
+        .bech32_hrp
+        .parse()?;
+    let bech32 = |address: Address| {
+        iota_sdk::types::block::address::Address::from(address)
+            .to_bech32(hrp)
+            .to_string()
+    };
+
+    let outputs = database.collection::<OutputCollection>();
+    let mut items = Vec::new();
+    for milestone_index in (ledger_index.0.saturating_sub(milestones - 1)..=ledger_index.0).rev() {
+        let milestone_index = MilestoneIndex(milestone_index);
+        // Only transactions that create NFT outputs can contain sales.
+        let mut transactions = BTreeMap::<String, (Vec<LedgerSpent>, Vec<LedgerOutput>)>::new();
+        let mut milestone_timestamp = None;
+        let mut created = outputs.get_created_outputs(milestone_index).await?;
+        while let Some(output) = created.try_next().await? {
+            milestone_timestamp = Some(output.booked.milestone_timestamp);
+            transactions
+                .entry(output.output_id.transaction_id.to_hex())
+                .or_default()
+                .1
+                .push(output);
+        }
+        transactions.retain(|_, (_, created)| created.iter().any(|output| matches!(output.output, Output::Nft(_))));
+        let milestone_timestamp = match milestone_timestamp.filter(|_| !transactions.is_empty()) {
+            Some(milestone_timestamp) => milestone_timestamp,
+            None => continue,
+        };
+        let mut consumed = outputs.get_consumed_outputs(milestone_index).await?;
+        while let Some(spent) = consumed.try_next().await? {
+            if let Some((inputs, _)) = transactions.get_mut(&spent.spent_metadata.transaction_id.to_hex()) {
+                inputs.push(spent);
+            }
+        }
+
+        for (transaction_id, (consumed, created)) in transactions {
+            for sale in ledger::nft_sales(&consumed, &created, milestone_timestamp) {
+                if collection.is_some() && sale.collection != collection {
+                    continue;
+                }
+                items.push(NftSaleDto {
+                    nft_id: iota_sdk::types::block::output::NftId::from(sale.nft_id).to_string(),
+                    collection: sale.collection.map(bech32),
+                    seller: bech32(sale.seller),
+                    buyer: bech32(sale.buyer),
+                    price: sale.price.0.to_string(),
+                    transaction_id: transaction_id.clone(),
+                    milestone_index,
+                    milestone_timestamp,
+                });
+            }
+        }
+    }
+
+    Ok(NftSalesResponse { items, ledger_index })
+}
+
+/// The maximum number of transactions a transfer graph is built from.
+const MAX_GRAPH_TRANSACTIONS: usize = 1000;
+
+async fn transfer_graph(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    TransferGraphQuery {
+        depth,
+        start_index,
+        end_index,
+        format,
+    }: TransferGraphQuery,
+) -> ApiResult<TransferGraphResponse> {
+    let address = Address::from_str(&address).map_err(RequestError::from)?;
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let start_index = start_index.unwrap_or_default();
+    let end_index = end_index.unwrap_or(ledger_index);
+    let hrp = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(end_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?
+        .parameters
+        .bech32_hrp
+        .parse()?;
+    let to_bech32 = |address: Address| {
+        iota_sdk::types::block::address::Address::from(address)
+            .to_bech32(hrp)
+            .to_string()
+    };
+
+    let mut graph = TransferGraph::default();
+    let mut visited_addresses = HashSet::from([address]);
+    let mut visited_transactions = HashSet::new();
+    let mut frontier = vec![address];
+    'depth: for _ in 0..depth {
+        // Collect the transactions that touched the current addresses within the range.
+        let mut transactions = Vec::new();
+        for address in frontier.drain(..) {
+            let mut updates = database
+                .collection::<LedgerUpdateCollection>()
+                .get_ledger_updates_by_address(
+                    &address,
+                    MAX_GRAPH_TRANSACTIONS,
+                    Some((end_index, None)),
+                    SortOrder::Newest,
+                    ledger_index,
+                )
+                .await?;
+            while let Some(update) = updates.try_next().await? {
+                if update.at.milestone_index < start_index {
+                    break;
+                }
+                let transaction = if update.is_spent {
+                    match database
+                        .collection::<OutputCollection>()
+                        .get_output_metadata(&update.output_id, ledger_index)
+                        .await?
+                        .and_then(|metadata| metadata.spent_metadata)
+                    {
+                        Some(spent) => (spent.transaction_id, spent.spent),
+                        None => continue,
+                    }
+                } else {
+                    (update.output_id.transaction_id, update.at)
+                };
+                if visited_transactions.insert(transaction.0) {
+                    transactions.push(transaction);
+                }
+            }
+        }
+
+        for (transaction_id, at) in transactions {
+            if graph.transaction_count() == MAX_GRAPH_TRANSACTIONS {
+                graph.truncated = true;
+                break 'depth;
+            }
+            // Outputs created at genesis or by migrations are not part of a transaction block.
+            let payload = match database
+                .collection::<BlockCollection>()
+                .get_block_for_transaction(&transaction_id)
+                .await?
+                .and_then(|included| included.block.payload)
+            {
+                Some(Payload::Transaction(payload)) => payload,
+                _ => continue,
+            };
+            let TransactionEssence::Regular { inputs, outputs, .. } = payload.essence;
+            let transaction = transaction_id.to_hex();
+
+            for input in inputs.iter() {
+                let output_id = match input {
+                    Input::Utxo(output_id) => output_id,
+                    _ => continue,
+                };
+                let output = match database.collection::<OutputCollection>().get_output(output_id).await? {
+                    Some(output) => output,
+                    None => continue,
+                };
+                if let Some(&address) = output.owning_address(at.milestone_timestamp) {
+                    graph.add_input(&transaction, at.milestone_index, to_bech32(address), output.amount().0);
+                    if visited_addresses.insert(address) {
+                        frontier.push(address);
+                    }
+                }
+            }
+            for output in outputs.iter() {
+                if let Some(&address) = output.owning_address(at.milestone_timestamp) {
+                    graph.add_output(&transaction, at.milestone_index, to_bech32(address), output.amount().0);
+                    if visited_addresses.insert(address) {
+                        frontier.push(address);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(TransferGraphResponse {
+        format,
+        graph: graph.render(format),
+    })
+}
+
+const MAX_RELATED_ENTITIES: usize = 1000;
+
+async fn entity_relationships(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    LedgerIndex { ledger_index }: LedgerIndex,
+) -> ApiResult<EntityRelationshipsResponse> {
+    let address = Address::from_str(&address).map_err(RequestError::from)?;
+    let ledger_index = resolve_past_ledger_index(&database, ledger_index).await?;
+    let hrp = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(ledger_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?
+        .parameters
+        .bech32_hrp
+        .parse()?;
+    let address_node = |address: Address| EntityNodeDto {
+        id: iota_sdk::types::block::address::Address::from(address)
+            .to_bech32(hrp)
+            .to_string(),
+        kind: match address {
+            Address::Ed25519(_) => "ed25519",
+            Address::Alias(_) => "alias",
+            Address::Nft(_) => "nft",
+        }
+        .to_string(),
+    };
+    let entity_node = |entity: IndexedId| match entity {
+        IndexedId::Alias(id) => address_node(Address::Alias(AliasAddress(id))),
+        IndexedId::Nft(id) => address_node(Address::Nft(NftAddress(id))),
+        IndexedId::Foundry(id) => EntityNodeDto {
+            id: iota_sdk::types::block::output::FoundryId::from(id).to_string(),
+            kind: entity.kind().to_string(),
+        },
+    };
+
+    let relationships = database
+        .collection::<OutputCollection>()
+        .get_entity_relationships(address, ledger_index, MAX_RELATED_ENTITIES)
+        .await?;
+    let truncated = relationships
+        .iter()
+        .map(|relationship| relationship.output_id)
+        .collect::<HashSet<_>>()
+        .len()
+        == MAX_RELATED_ENTITIES;
+    let root = address_node(address);
+    let mut nodes = BTreeSet::from([root.clone()]);
+    let mut edges = Vec::with_capacity(relationships.len());
+    for EntityRelationship {
+        address,
+        relation,
+        entity,
+        output_id,
+    } in relationships
+    {
+        let (from, to) = (address_node(address), entity_node(entity));
+        edges.push(EntityEdgeDto {
+            from: from.id.clone(),
+            to: to.id.clone(),
+            relation,
+            output_id: output_id.to_hex(),
+        });
+        nodes.extend([from, to]);
+    }
+
+    Ok(EntityRelationshipsResponse {
+        address: root.id,
+        ledger_index,
+        nodes: nodes.into_iter().collect(),
+        edges,
+        truncated,
+    })
+}
+
+/// Converts between milestone timestamps and indexes. A timestamp is converted to the last milestone issued at or before
+/// it, which is the milestone whose ledger state was current at that time.
+async fn convert_milestone(
+    database: Extension<MongoDb>,
+    query: MilestoneConversionQuery,
+) -> ApiResult<MilestoneConversionResponse> {
+    let milestones = database.collection::<MilestoneCollection>();
+    let at = match query {
+        MilestoneConversionQuery::Timestamp(timestamp) => milestones.find_last_milestone(timestamp).await?,
+        MilestoneConversionQuery::Index(milestone_index) => milestones
+            .get_milestone_timestamp(milestone_index)
+            .await?
+            .map(|milestone_timestamp| MilestoneIndexTimestamp {
+                milestone_index,
+                milestone_timestamp,
+            }),
+    }
+    .ok_or(MissingError::NoResults)?;
+    let period = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_period(at.milestone_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?;
+    Ok(MilestoneConversionResponse {
+        milestone_index: at.milestone_index,
+        milestone_timestamp: at.milestone_timestamp,
+        protocol_version: period.parameters.version,
+        protocol_start_index: period.start_index,
+        protocol_end_index: period.end_index,
+    })
+}
+
+async fn views(database: Extension<MongoDb>) -> ApiResult<ViewsResponse> {
+    let views = database.collection::<MaterializedViewCollection>().get_views().await?;
+    Ok(ViewsResponse {
+        views: views.into_iter().map(Into::into).collect(),
+    })
+}
+
+async fn view_results(
+    database: Extension<MongoDb>,
+    Path(name): Path<String>,
+    Pagination { page_size, page }: Pagination,
+) -> ApiResult<ViewResultsResponse> {
+    let collection = database.collection::<MaterializedViewCollection>();
+    let view = collection.get_view(&name).await?.ok_or(MissingError::NotFound)?;
+    let items = collection
+        .get_view_results(&name, page_size, page)
+        .await?
+        .map_ok(|doc| mongodb::bson::Bson::Document(doc).into_relaxed_extjson())
+        .try_collect()
+        .await?;
+
+    Ok(ViewResultsResponse {
+        view: view.into(),
+        items,
+    })
+}
+
+async fn ledger_snapshots(
+    database: Extension<MongoDb>,
+    MilestoneRange { start_index, end_index }: MilestoneRange,
+) -> ApiResult<LedgerSnapshotsResponse> {
+    let items = database
+        .collection::<LedgerSnapshotCollection>()
+        .get_snapshots(start_index, end_index)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Ok(LedgerSnapshotsResponse { items })
+}
+
+async fn address_filter(database: Extension<MongoDb>) -> ApiResult<AddressFilterResponse> {
+    let (_, bytes) = database
+        .collection::<AddressFilterCollection>()
+        .get_latest_filter()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    Ok(AddressFilterResponse(bytes))
+}
+
+async fn protocol_changes(database: Extension<MongoDb>) -> ApiResult<ProtocolChangesResponse> {
+    let updates = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_updates()
+        .await?;
+    let mut items = Vec::with_capacity(updates.len());
+    let mut previous = None;
+    for update in updates {
+        let protocol_version = update.parameters.version;
+        // The parameters are compared in the format of the core API, so that the fields are named the same way.
+        let parameters = serde_json::to_value(iota_sdk::types::block::protocol::ProtocolParameters::try_from(
+            update.parameters,
+        )?)?;
+        let mut changes = Vec::new();
+        diff_protocol_parameters(String::new(), previous.as_ref(), Some(&parameters), &mut changes);
+        items.push(ProtocolChangeDto {
+            milestone_index: update.tangle_index,
+            protocol_version,
+            changes,
+        });
+        previous = Some(parameters);
+    }
+    Ok(ProtocolChangesResponse { items })
+}
+
+/// Collects the parameters that differ from the previous version, descending into nested parameters. Parameters that
+/// exist in only one of the versions were added or removed, so the parameters of both versions are compared.
+fn diff_protocol_parameters(
+    field: String,
+    old: Option<&serde_json::Value>,
+    new: Option<&serde_json::Value>,
+    changes: &mut Vec<ProtocolParameterChangeDto>,
+) {
+    match (old, new) {
+        (None | Some(serde_json::Value::Object(_)), None | Some(serde_json::Value::Object(_))) => {
+            let (old, new) = (
+                old.and_then(serde_json::Value::as_object),
+                new.and_then(serde_json::Value::as_object),
+            );
+            let names = old
+                .into_iter()
+                .chain(new)
+                .flat_map(|parameters| parameters.keys())
+                .collect::<BTreeSet<_>>();
+            for name in names {
+                let field = if field.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{field}.{name}")
+                };
+                diff_protocol_parameters(
+                    field,
+                    old.and_then(|old| old.get(name)),
+                    new.and_then(|new| new.get(name)),
+                    changes,
+                );
+            }
+        }
+        _ if old != new => changes.push(ProtocolParameterChangeDto {
+            field,
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => (),
+    }
+}
+
+async fn unspent_output_digest_by_prefix(
+    database: Extension<MongoDb>,
+    _: Admin,
+    Path(prefix): Path<String>,
+    LedgerIndex { ledger_index }: LedgerIndex,
+) -> ApiResult<UnspentOutputDigestResponse> {
+    let [prefix] = prefix_hex::decode::<[u8; 1]>(&prefix).map_err(RequestError::from)?;
+    let ledger_index = resolve_past_ledger_index(&database, ledger_index).await?;
+    let digest = database
+        .collection::<OutputCollection>()
+        .get_unspent_output_digest(ledger_index, prefix)
+        .await?;
+    Ok(UnspentOutputDigestResponse::new(ledger_index, digest))
+}
+
+async fn unspent_output_ids_by_prefix(
+    database: Extension<MongoDb>,
+    _: Admin,
+    Path(prefix): Path<String>,
+    LedgerIndex { ledger_index }: LedgerIndex,
+) -> ApiResult<UnspentOutputIdsResponse> {
+    let [prefix] = prefix_hex::decode::<[u8; 1]>(&prefix).map_err(RequestError::from)?;
+    let ledger_index = resolve_past_ledger_index(&database, ledger_index).await?;
+    let items = database
+        .collection::<OutputCollection>()
+        .get_unspent_output_ids(ledger_index, Some(prefix))
+        .await?
+        .map_ok(|output_id| output_id.to_hex())
+        .try_collect()
+        .await?;
+    Ok(UnspentOutputIdsResponse { ledger_index, items })
+}
+
+async fn data_size(
+    database: Extension<MongoDb>,
+    _: Admin,
+    DataSizeQuery {
+        interval,
+        start_timestamp,
+        end_timestamp,
+    }: DataSizeQuery,
+) -> ApiResult<DataSizeResponse> {
+    let bucket_seconds = interval.seconds();
+    let (start_bucket, bucket_count) = bucket_range(&database, interval, start_timestamp, end_timestamp).await?;
+
+    let mut buckets = database
+        .collection::<MilestoneCollection>()
+        .get_data_size(
+            start_bucket.into(),
+            (start_bucket + bucket_count * bucket_seconds).into(),
+            bucket_seconds,
+        )
+        .await?;
+
+    // Fill in the buckets without any milestones so that the series is contiguous.
+    let mut items = Vec::with_capacity(bucket_count as usize);
+    let mut next = buckets.try_next().await?;
+    for i in 0..bucket_count {
+        let start_timestamp = (start_bucket + i * bucket_seconds).into();
+        match next {
+            Some(bucket) if bucket.start_timestamp == start_timestamp => {
+                items.push(bucket.into());
+                next = buckets.try_next().await?;
+            }
+            _ => items.push(DataSizeDto::empty(start_timestamp)),
+        }
+    }
+
+    Ok(DataSizeResponse { items })
+}
+
+async fn storage_forecast(
+    database: Extension<MongoDb>,
+    _: Admin,
+    StorageForecastQuery { window, horizons }: StorageForecastQuery,
+) -> ApiResult<StorageForecastResponse> {
+    let samples = database.collection::<StorageSampleCollection>();
+    let latest = samples.get_latest_sample().await?.ok_or(MissingError::NoResults)?;
+    let start = samples
+        .get_sample_since(time_before(latest.sampled_at, window))
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let collections = latest
+        .growth_since(&start)
+        .into_iter()
+        .map(|growth| CollectionForecastDto::new(growth, &horizons))
+        .collect::<Vec<_>>();
+    let total = horizons
+        .iter()
+        .enumerate()
+        .map(|(i, horizon)| {
+            StorageProjectionDto::new(
+                *horizon,
+                collections.iter().map(|c| c.projections[i].count).sum(),
+                collections.iter().map(|c| c.projections[i].size).sum(),
+            )
+        })
+        .collect();
+    Ok(StorageForecastResponse {
+        sampled_at: latest.sampled_at.timestamp_millis() / 1000,
+        growth_since: start.sampled_at.timestamp_millis() / 1000,
+        ledger_index: latest.ledger_index,
+        collections,
+        total,
+    })
+}
+
+async fn native_token_distribution(
+    database: Extension<MongoDb>,
+    Path(token_id): Path<String>,
+) -> ApiResult<NativeTokenDistributionResponse> {
+    let token_id = NativeTokenId::from_str(&token_id).map_err(RequestError::from)?;
+    let distribution = database
+        .collection::<TokenDistributionCollection>()
+        .get_distribution(token_id)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+
+    Ok(NativeTokenDistributionResponse::new(token_id, distribution))
+}
+
+async fn pending_transactions(
+    database: Extension<MongoDb>,
+    Pagination { page_size, page }: Pagination,
+) -> ApiResult<PendingTransactionsResponse> {
+    let items = database
+        .collection::<PendingTransactionCollection>()
+        .get_pending_transactions(page_size, page)
+        .await?
+        .map_ok(PendingTransactionResponse::from)
+        .try_collect()
+        .await?;
+
+    Ok(PendingTransactionsResponse { items })
+}
+
+async fn pending_transaction(
+    database: Extension<MongoDb>,
+    Path(transaction_id): Path<String>,
+) -> ApiResult<PendingTransactionResponse> {
+    let transaction_id = TransactionId::from_str(&transaction_id).map_err(RequestError::from)?;
+    let pending = database
+        .collection::<PendingTransactionCollection>()
+        .get_pending_transaction(&transaction_id)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+
+    Ok(pending.into())
+}
+
+/// Resolves the ledger index like [`resolve_ledger_index`], but rejects indexes beyond the current ledger index,
+/// whose ledger state is not known yet.
+async fn resolve_past_ledger_index(
+    database: &MongoDb,
+    ledger_index: Option<MilestoneIndex>,
+) -> ApiResult<MilestoneIndex> {
+    let current = resolve_ledger_index(database, None).await?;
+    match ledger_index {
+        Some(ledger_index) if ledger_index > current => {
+            Err(RequestError::FutureMilestone(ledger_index, current).into())
+        }
+        Some(ledger_index) => Ok(ledger_index),
+        None => Ok(current),
+    }
+}
+
 /// This is just a helper fn to either unwrap an optional ledger index param or fetch the latest
 /// index from the database.
 async fn resolve_ledger_index(database: &MongoDb, ledger_index: Option<MilestoneIndex>) -> ApiResult<MilestoneIndex> {
@@ -382,3 +1910,154 @@ async fn resolve_ledger_index(database: &MongoDb, ledger_index: Option<Milestone
             .ok_or(MissingError::NoResults)?
     })
 }
+
+async fn alias_history(
+    database: Extension<MongoDb>,
+    Path(alias_id): Path<String>,
+    pagination: OutputHistoryPagination,
+) -> ApiResult<OutputHistoryResponse> {
+    let id = AliasId::from_str(&alias_id).map_err(RequestError::from)?;
+    output_history(&database, id, alias_id, pagination).await
+}
+
+async fn nft_history(
+    database: Extension<MongoDb>,
+    Path(nft_id): Path<String>,
+    pagination: OutputHistoryPagination,
+) -> ApiResult<OutputHistoryResponse> {
+    let id = NftId::from_str(&nft_id).map_err(RequestError::from)?;
+    output_history(&database, id, nft_id, pagination).await
+}
+
+/// Gets a page of the outputs that represented an alias or NFT over its lifetime.
+async fn output_history(
+    database: &MongoDb,
+    id: impl Into<IndexedId>,
+    id_str: String,
+    OutputHistoryPagination {
+        page_size,
+        sort,
+        cursor,
+    }: OutputHistoryPagination,
+) -> ApiResult<OutputHistoryResponse> {
+    let mut record_stream = database
+        .collection::<OutputCollection>()
+        // Get one extra record so that we can create the cursor.
+        .get_indexed_output_history(id, page_size + 1, cursor, sort)
+        .await?;
+
+    let items = record_stream
+        .by_ref()
+        .take(page_size)
+        .map_ok(Into::into)
+        .try_collect::<Vec<_>>()
+        .await?;
+    if items.is_empty() && cursor.is_none() {
+        return Err(MissingError::NoResults.into());
+    }
+
+    // If any record is left, use it to make the cursor
+    let cursor = record_stream.try_next().await?.map(|rec| {
+        OutputHistoryCursor {
+            milestone_index: rec.booked.milestone_index,
+            output_id: rec.output_id,
+            page_size,
+        }
+        .to_string()
+    });
+
+    Ok(OutputHistoryResponse {
+        id: id_str,
+        items,
+        cursor,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    fn change(
+        field: &str,
+        old: Option<serde_json::Value>,
+        new: Option<serde_json::Value>,
+    ) -> ProtocolParameterChangeDto {
+        ProtocolParameterChangeDto {
+            field: field.to_string(),
+            old,
+            new,
+        }
+    }
+
+    #[test]
+    fn diffs_protocol_parameters() {
+        let first = json!({ "protocolVersion": 2, "rentStructure": { "vByteCost": 100, "vByteFactorKey": 10 } });
+        let second = json!({ "protocolVersion": 3, "rentStructure": { "vByteCost": 250, "vByteFactorKey": 10 } });
+
+        let mut changes = Vec::new();
+        diff_protocol_parameters(String::new(), None, Some(&first), &mut changes);
+        assert_eq!(
+            changes,
+            vec![
+                change("protocolVersion", None, Some(json!(2))),
+                change("rentStructure.vByteCost", None, Some(json!(100))),
+                change("rentStructure.vByteFactorKey", None, Some(json!(10))),
+            ]
+        );
+
+        let mut changes = Vec::new();
+        diff_protocol_parameters(String::new(), Some(&first), Some(&second), &mut changes);
+        assert_eq!(
+            changes,
+            vec![
+                change("protocolVersion", Some(json!(2)), Some(json!(3))),
+                change("rentStructure.vByteCost", Some(json!(100)), Some(json!(250))),
+            ]
+        );
+
+        let mut changes = Vec::new();
+        diff_protocol_parameters(String::new(), Some(&second), Some(&second), &mut changes);
+        assert_eq!(changes, vec![]);
+
+        // Parameters that only exist in one of the versions were added or removed.
+        let third = json!({ "protocolVersion": 3, "rentStructure": { "vByteCost": 250 }, "tokenSupply": "100" });
+        let mut changes = Vec::new();
+        diff_protocol_parameters(String::new(), Some(&second), Some(&third), &mut changes);
+        assert_eq!(
+            changes,
+            vec![
+                change("rentStructure.vByteFactorKey", Some(json!(10)), None),
+                change("tokenSupply", None, Some(json!("100"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn computes_bucket_bounds() {
+        let hour = VolumeInterval::Hour.seconds();
+        assert_eq!(
+            bucket_bounds(VolumeInterval::Hour, Some(hour + 1), 3 * hour + 1).unwrap(),
+            (hour, 3)
+        );
+        assert_eq!(
+            bucket_bounds(VolumeInterval::Hour, None, 100 * hour).unwrap(),
+            (77 * hour, 24)
+        );
+        assert_eq!(bucket_bounds(VolumeInterval::Hour, None, hour).unwrap(), (0, 2));
+        assert!(matches!(
+            bucket_bounds(VolumeInterval::Hour, Some(3 * hour), hour),
+            Err(RequestError::BadTimeRange)
+        ));
+        assert!(matches!(
+            bucket_bounds(VolumeInterval::Hour, Some(0), MAX_VOLUME_BUCKETS * hour),
+            Err(RequestError::BadTimeRange)
+        ));
+        assert!(matches!(
+            bucket_bounds(VolumeInterval::Hour, None, u32::MAX),
+            Err(RequestError::BadTimeRange)
+        ));
+    }
+}
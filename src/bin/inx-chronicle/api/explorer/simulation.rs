@@ -0,0 +1,249 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validation of a transaction against the ledger state of a past milestone.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use iota_sdk::types::block::{
+    address::Address,
+    output::{InputsCommitment, Output, OutputId, TokenId},
+    payload::transaction::{RegularTransactionEssence, TransactionId},
+    semantic::{semantic_validation, ConflictReason, ValidationContext},
+    unlock::Unlocks,
+};
+use primitive_types::U256;
+use serde::Serialize;
+
+/// The reason why a transaction would not be accepted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    NetworkMismatch,
+    UnsupportedInput,
+    InputNotFound,
+    InputSpent,
+    InputsCommitmentMismatch,
+    AmountMismatch,
+    NativeTokenMismatch,
+    TimelockNotExpired,
+    StorageDepositReturnUnfulfilled,
+    /// The first conflict found by the validation that nodes run, including the signature checks.
+    Conflict,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    pub kind: IssueKind,
+    /// The index of the input that caused the issue, if it is caused by a single input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_index: Option<usize>,
+    pub message: String,
+}
+
+impl Issue {
+    pub fn new(kind: IssueKind, input_index: impl Into<Option<usize>>, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            input_index: input_index.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks a transaction whose inputs were all found unspent against the ledger state at the given milestone
+/// timestamp.
+///
+/// Unlike the validation of the nodes, which stops at the first conflict, all balance and unlock condition problems
+/// are reported. Signatures can only be checked if the unlocks are given, in which case the conflict reason of the
+/// nodes' validation is reported as well.
+pub fn check_transaction(
+    essence: &RegularTransactionEssence,
+    inputs: &[(OutputId, Output)],
+    signed: Option<(TransactionId, &Unlocks)>,
+    milestone_timestamp: u32,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    if essence.inputs_commitment() != &InputsCommitment::new(inputs.iter().map(|(_, output)| output)) {
+        issues.push(Issue::new(
+            IssueKind::InputsCommitmentMismatch,
+            None,
+            "the inputs commitment does not match the consumed outputs",
+        ));
+    }
+
+    let mut input_amount = 0u128;
+    let mut input_tokens = BTreeMap::<TokenId, U256>::new();
+    let mut storage_deposit_returns = HashMap::<Address, u128>::new();
+    for (index, (_, output)) in inputs.iter().enumerate() {
+        input_amount += output.amount() as u128;
+        for token in output.native_tokens().into_iter().flat_map(|tokens| tokens.iter()) {
+            let amount = input_tokens.entry(*token.token_id()).or_default();
+            *amount = amount.saturating_add(token.amount());
+        }
+        if let Some(unlock_conditions) = output.unlock_conditions() {
+            if unlock_conditions.is_time_locked(milestone_timestamp) {
+                issues.push(Issue::new(
+                    IssueKind::TimelockNotExpired,
+                    index,
+                    "the timelock of the input has not expired at the milestone",
+                ));
+            }
+            if !unlock_conditions.is_expired(milestone_timestamp) {
+                if let Some(storage_deposit_return) = unlock_conditions.storage_deposit_return() {
+                    *storage_deposit_returns
+                        .entry(*storage_deposit_return.return_address())
+                        .or_default() += storage_deposit_return.amount() as u128;
+                }
+            }
+        }
+    }
+
+    let mut output_amount = 0u128;
+    let mut output_tokens = BTreeMap::<TokenId, U256>::new();
+    let mut simple_deposits = HashMap::<Address, u128>::new();
+    for output in essence.outputs() {
+        output_amount += output.amount() as u128;
+        for token in output.native_tokens().into_iter().flat_map(|tokens| tokens.iter()) {
+            let amount = output_tokens.entry(*token.token_id()).or_default();
+            *amount = amount.saturating_add(token.amount());
+        }
+        if let Output::Basic(basic) = output {
+            if let Some(address) = basic.simple_deposit_address() {
+                *simple_deposits.entry(*address).or_default() += basic.amount() as u128;
+            }
+        }
+    }
+
+    if input_amount != output_amount {
+        issues.push(Issue::new(
+            IssueKind::AmountMismatch,
+            None,
+            format!("the inputs hold {input_amount} base tokens, but the outputs hold {output_amount}"),
+        ));
+    }
+
+    // Foundries mint and melt native tokens, so the amounts only have to match if none is involved.
+    let foundry_involved = inputs.iter().map(|(_, output)| output).any(Output::is_foundry)
+        || essence.outputs().iter().any(Output::is_foundry);
+    if !foundry_involved {
+        let token_ids = input_tokens.keys().chain(output_tokens.keys()).collect::<BTreeSet<_>>();
+        for token_id in token_ids {
+            let consumed = input_tokens.get(token_id).copied().unwrap_or_default();
+            let created = output_tokens.get(token_id).copied().unwrap_or_default();
+            if consumed != created {
+                issues.push(Issue::new(
+                    IssueKind::NativeTokenMismatch,
+                    None,
+                    format!("the inputs hold {consumed} of native token {token_id}, but the outputs hold {created}"),
+                ));
+            }
+        }
+    }
+
+    for (address, required) in storage_deposit_returns {
+        let returned = simple_deposits.get(&address).copied().unwrap_or_default();
+        if returned < required {
+            issues.push(Issue::new(
+                IssueKind::StorageDepositReturnUnfulfilled,
+                None,
+                format!("{required} base tokens have to be returned, but only {returned} are"),
+            ));
+        }
+    }
+
+    if let Some((transaction_id, unlocks)) = signed {
+        let inputs = inputs
+            .iter()
+            .map(|(output_id, output)| (output_id, output))
+            .collect::<Vec<_>>();
+        let context = ValidationContext::new(
+            &transaction_id,
+            essence,
+            inputs.iter().copied(),
+            unlocks,
+            milestone_timestamp,
+        );
+        match semantic_validation(context, &inputs, unlocks) {
+            Ok(ConflictReason::None) => (),
+            Ok(reason) => issues.push(Issue::new(IssueKind::Conflict, None, reason.to_string())),
+            Err(e) => issues.push(Issue::new(IssueKind::Conflict, None, e.to_string())),
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod test {
+    use iota_sdk::types::block::{
+        address::Ed25519Address,
+        input::{Input, UtxoInput},
+        output::{
+            unlock_condition::{AddressUnlockCondition, TimelockUnlockCondition},
+            BasicOutputBuilder,
+        },
+        protocol::protocol_parameters,
+        rand::output::rand_output_id,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn basic_output(amount: u64, timelock: Option<u32>) -> Output {
+        let mut builder = BasicOutputBuilder::new_with_amount(amount)
+            .add_unlock_condition(AddressUnlockCondition::new(Ed25519Address::from([0; 32])));
+        if let Some(timestamp) = timelock {
+            builder = builder.add_unlock_condition(TimelockUnlockCondition::new(timestamp).unwrap());
+        }
+        builder.finish_output(protocol_parameters().token_supply()).unwrap()
+    }
+
+    fn essence(inputs: &[(OutputId, Output)], outputs: Vec<Output>) -> RegularTransactionEssence {
+        let protocol_parameters = protocol_parameters();
+        RegularTransactionEssence::builder(
+            protocol_parameters.network_id(),
+            InputsCommitment::new(inputs.iter().map(|(_, output)| output)),
+        )
+        .with_inputs(
+            inputs
+                .iter()
+                .map(|(output_id, _)| Input::Utxo(UtxoInput::from(*output_id)))
+                .collect::<Vec<_>>(),
+        )
+        .with_outputs(outputs)
+        .finish_with_params(&protocol_parameters)
+        .unwrap()
+    }
+
+    #[test]
+    fn balanced_transaction() {
+        let inputs = [(rand_output_id(), basic_output(1_000_000, None))];
+        let essence = essence(&inputs, vec![basic_output(1_000_000, None)]);
+        assert_eq!(check_transaction(&essence, &inputs, None, 100), Vec::new());
+    }
+
+    #[test]
+    fn reports_all_issues() {
+        let inputs = [(rand_output_id(), basic_output(2_000_000, Some(200)))];
+        let essence = essence(&inputs, vec![basic_output(1_000_000, None)]);
+        let kinds = |issues: Vec<Issue>| issues.into_iter().map(|issue| issue.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds(check_transaction(&essence, &inputs, None, 100)),
+            vec![IssueKind::TimelockNotExpired, IssueKind::AmountMismatch]
+        );
+        // The timelock has expired by then.
+        assert_eq!(
+            kinds(check_transaction(&essence, &inputs, None, 200)),
+            vec![IssueKind::AmountMismatch]
+        );
+
+        let other_inputs = [(inputs[0].0, basic_output(1_000_000, None))];
+        assert_eq!(
+            kinds(check_transaction(&essence, &other_inputs, None, 200)),
+            vec![IssueKind::InputsCommitmentMismatch]
+        );
+    }
+}
@@ -1,13 +1,18 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::str::FromStr;
+
 use async_trait::async_trait;
 use axum::{
+    body::HttpBody,
     extract::{FromRequest, Query},
-    Extension,
+    BoxError, Extension, Json,
 };
 use chronicle::model::tangle::MilestoneTimestamp;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing_subscriber::EnvFilter;
 
 use super::{
     config::ApiConfigData,
@@ -17,6 +22,7 @@ use super::{
 
 #[derive(Debug, Copy, Clone, Deserialize, PartialEq, Eq)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+#[cfg_attr(not(feature = "explorer"), allow(dead_code))]
 pub struct Pagination {
     pub page_size: usize,
     pub page: usize,
@@ -63,14 +69,42 @@ impl<B: Send> FromRequest<B> for ListRoutesQuery {
     }
 }
 
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct LogFilterBody {
+    directives: String,
+}
+
+/// A log filter, parsed from directives such as `info,chronicle::inx=trace`.
+pub struct LogFilterRequest(pub EnvFilter);
+
+#[async_trait]
+impl<B> FromRequest<B> for LogFilterRequest
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Json(LogFilterBody { directives }) = Json::<LogFilterBody>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        Ok(Self(EnvFilter::try_new(directives).map_err(RequestError::from)?))
+    }
+}
+
 #[derive(Copy, Clone, Default, Deserialize)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+#[cfg_attr(not(feature = "inx"), allow(dead_code))]
 pub struct TimeRangeQuery {
     start_timestamp: Option<u32>,
     end_timestamp: Option<u32>,
 }
 
 #[derive(Copy, Clone)]
+#[cfg_attr(not(feature = "inx"), allow(dead_code))]
 pub struct TimeRange {
     pub start_timestamp: Option<MilestoneTimestamp>,
     pub end_timestamp: Option<MilestoneTimestamp>,
@@ -98,6 +132,95 @@ impl<B: Send> FromRequest<B> for TimeRange {
     }
 }
 
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldsQuery {
+    fields: Option<String>,
+}
+
+/// The fields of a response selected with the `fields` query parameter, as dot-separated paths into the JSON
+/// response. Without the parameter, every field is selected.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fields(Option<Vec<Vec<String>>>);
+
+impl FromStr for Fields {
+    type Err = RequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|path| {
+                let path = path.trim().split('.').map(ToString::to_string).collect::<Vec<_>>();
+                if path.iter().any(String::is_empty) {
+                    Err(RequestError::BadFields(s.to_string()))
+                } else {
+                    Ok(path)
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(|paths| Self(Some(paths)))
+    }
+}
+
+impl Fields {
+    /// Whether the field at the dot-separated `path`, or any field nested in it, is selected.
+    pub fn includes(&self, path: &str) -> bool {
+        match &self.0 {
+            None => true,
+            Some(paths) => paths.iter().any(|selected| {
+                selected
+                    .iter()
+                    .zip(path.split('.'))
+                    .all(|(selected, segment)| selected == segment)
+            }),
+        }
+    }
+
+    /// Serializes the response, leaving out the fields that are not selected. The selection applies to every
+    /// element of an array.
+    pub fn prune(&self, response: impl Serialize) -> Result<Value, ApiError> {
+        let value = serde_json::to_value(response)?;
+        Ok(match &self.0 {
+            None => value,
+            Some(paths) => prune(value, &paths.iter().map(Vec::as_slice).collect::<Vec<_>>()),
+        })
+    }
+}
+
+fn prune(value: Value, paths: &[&[String]]) -> Value {
+    if paths.iter().any(|path| path.is_empty()) {
+        return value;
+    }
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    let nested = paths
+                        .iter()
+                        .filter(|path| path[0] == name)
+                        .map(|path| &path[1..])
+                        .collect::<Vec<_>>();
+                    (!nested.is_empty()).then(|| (name, prune(value, &nested)))
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(|value| prune(value, paths)).collect()),
+        value => value,
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for Fields {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(FieldsQuery { fields }) = Query::<FieldsQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        Ok(fields.as_deref().map(str::parse).transpose()?.unwrap_or_default())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use axum::{
@@ -127,4 +250,44 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn prune_fields() {
+        let response = serde_json::json!({
+            "metadata": { "blockId": "0x01", "isSpent": false },
+            "output": { "type": 3, "amount": "10", "unlockConditions": [{ "type": 0 }, { "type": 2 }] },
+        });
+        assert_eq!(Fields::default().prune(&response).unwrap(), response);
+        assert_eq!(
+            "metadata.isSpent, output.unlockConditions.type"
+                .parse::<Fields>()
+                .unwrap()
+                .prune(&response)
+                .unwrap(),
+            serde_json::json!({
+                "metadata": { "isSpent": false },
+                "output": { "unlockConditions": [{ "type": 0 }, { "type": 2 }] },
+            })
+        );
+        assert_eq!(
+            "metadata,output.missing"
+                .parse::<Fields>()
+                .unwrap()
+                .prune(&response)
+                .unwrap(),
+            serde_json::json!({ "metadata": { "blockId": "0x01", "isSpent": false }, "output": {} })
+        );
+        assert!("metadata,,output".parse::<Fields>().is_err());
+        assert!("output.".parse::<Fields>().is_err());
+    }
+
+    #[test]
+    fn includes_fields() {
+        let fields = "metadata.blockId".parse::<Fields>().unwrap();
+        assert!(fields.includes("metadata"));
+        assert!(fields.includes("metadata.blockId.extra"));
+        assert!(!fields.includes("metadata.isSpent"));
+        assert!(!fields.includes("output"));
+        assert!(Fields::default().includes("output"));
+    }
 }
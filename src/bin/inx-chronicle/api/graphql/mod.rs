@@ -0,0 +1,68 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A GraphQL API over blocks, outputs and addresses, so that related data can be fetched with one nested query
+//! instead of chaining requests to the REST routes.
+
+mod schema;
+
+use async_graphql::{EmptyMutation, EmptySubscription, Request, Response, Schema};
+use axum::{routing::post, Extension, Json};
+use chronicle::db::{mongodb::collections::MilestoneCollection, MongoDb};
+
+use self::schema::{ChronicleSchema, LedgerIndex, QueryRoot};
+use super::{error::MissingError, router::Router, ApiConfigData, ApiResult};
+
+/// The deepest nesting of fields a query may have.
+const MAX_DEPTH: usize = 10;
+/// The highest complexity a query may have, where every field counts as one and lists count as many as they may
+/// contain.
+const MAX_COMPLEXITY: usize = 10_000;
+
+pub fn routes() -> Router {
+    Router::new().route("/", post(graphql)).layer(Extension(schema()))
+}
+
+fn schema() -> ChronicleSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+async fn graphql(
+    database: Extension<MongoDb>,
+    Extension(config): Extension<ApiConfigData>,
+    Extension(schema): Extension<ChronicleSchema>,
+    Json(request): Json<Request>,
+) -> ApiResult<Json<Response>> {
+    // All outputs of a query are resolved against the same ledger index, so that milestones being ingested while the
+    // query runs cannot make its results inconsistent.
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let request = request.data(database.0).data(config).data(LedgerIndex(ledger_index));
+    Ok(Json(schema.execute(request).await))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_too_complex(response: &Response) -> bool {
+        response.errors.iter().any(|e| e.message.contains("too complex"))
+    }
+
+    #[tokio::test]
+    async fn limits_complexity_of_pages() {
+        let query = |first: usize| {
+            format!(
+                r#"{{ address(address: "iota1qp") {{ ledgerUpdates(first: {first}) {{ items {{ output {{ block {{ blockId }} }} }} }} }} }}"#
+            )
+        };
+        assert!(!is_too_complex(&schema().execute(query(100)).await));
+        assert!(is_too_complex(&schema().execute(query(5000)).await));
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Json, Object, Schema, SimpleObject};
+use chronicle::{
+    db::{
+        mongodb::collections::{
+            BlockCollection, LedgerUpdateCollection, MilestoneCollection, OutputCollection, OutputMetadataResult,
+            SortOrder,
+        },
+        MongoDb,
+    },
+    model::{
+        metadata::BlockMetadata,
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+        utxo::{Address, Output, OutputId},
+        BlockId,
+    },
+};
+use futures::{StreamExt, TryStreamExt};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::api::{
+    dto::{BlockDto, BlockMetadataDto, OutputDto, OutputMetadataDto},
+    error::{MissingError, RequestError},
+    ApiConfigData, ApiError, DEFAULT_PAGE_SIZE,
+};
+
+pub type ChronicleSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// The milestone that the outputs of a request are resolved against, so that all of them are from the same ledger
+/// state.
+#[derive(Copy, Clone, Debug)]
+pub struct LedgerIndex(pub MilestoneIndex);
+
+trait GraphQlResult<T> {
+    /// Converts the error into one that hides internal errors from the client, like the REST routes do.
+    fn graphql(self) -> async_graphql::Result<T>;
+}
+
+impl<T, E: Into<ApiError>> GraphQlResult<T> for Result<T, E> {
+    fn graphql(self) -> async_graphql::Result<T> {
+        self.map_err(|e| e.into().into_graphql_error())
+    }
+}
+
+/// The JSON value of a DTO, which keeps the format of the REST routes instead of mapping every field to GraphQL.
+fn json(dto: impl Serialize) -> async_graphql::Result<Json<Value>> {
+    serde_json::to_value(dto).map(Json).graphql()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// A block by its id.
+    async fn block(&self, ctx: &Context<'_>, block_id: String) -> async_graphql::Result<Option<BlockNode>> {
+        let block_id = BlockId::from_str(&block_id).map_err(RequestError::from).graphql()?;
+        BlockNode::find(ctx.data::<MongoDb>()?, block_id).await
+    }
+
+    /// An output by its id.
+    async fn output(&self, ctx: &Context<'_>, output_id: String) -> async_graphql::Result<Option<OutputNode>> {
+        let output_id = OutputId::from_str(&output_id).map_err(RequestError::from).graphql()?;
+        OutputNode::find(ctx, output_id).await
+    }
+
+    /// An address given in bech32, whose balance and ledger updates can be queried.
+    async fn address(&self, address: String) -> async_graphql::Result<AddressNode> {
+        Ok(AddressNode {
+            address: Address::from_str(&address).map_err(RequestError::from).graphql()?,
+            bech32: address,
+        })
+    }
+}
+
+pub struct BlockNode {
+    block_id: BlockId,
+    metadata: BlockMetadata,
+}
+
+impl BlockNode {
+    async fn find(database: &MongoDb, block_id: BlockId) -> async_graphql::Result<Option<Self>> {
+        Ok(database
+            .collection::<BlockCollection>()
+            .get_block_metadata(&block_id)
+            .await
+            .graphql()?
+            .map(|metadata| Self { block_id, metadata }))
+    }
+}
+
+#[Object]
+impl BlockNode {
+    async fn block_id(&self) -> String {
+        self.block_id.to_hex()
+    }
+
+    /// The block in the JSON format of the core API. It is only fetched if it is requested.
+    async fn block(&self, ctx: &Context<'_>) -> async_graphql::Result<Json<Value>> {
+        let block = ctx
+            .data::<MongoDb>()?
+            .collection::<BlockCollection>()
+            .get_block(&self.block_id)
+            .await
+            .graphql()?
+            .ok_or(MissingError::NoResults)
+            .graphql()?;
+        json(BlockDto::from(block))
+    }
+
+    /// The metadata of the block in the JSON format of the core API.
+    async fn metadata(&self) -> async_graphql::Result<Json<Value>> {
+        json(BlockMetadataDto::new(self.block_id, self.metadata.clone()))
+    }
+}
+
+pub struct OutputNode {
+    output: Output,
+    metadata: OutputMetadataResult,
+    ledger_index: MilestoneIndex,
+}
+
+impl OutputNode {
+    async fn find(ctx: &Context<'_>, output_id: OutputId) -> async_graphql::Result<Option<Self>> {
+        let LedgerIndex(ledger_index) = *ctx.data::<LedgerIndex>()?;
+        Ok(ctx
+            .data::<MongoDb>()?
+            .collection::<OutputCollection>()
+            .get_output_with_metadata(&output_id, ledger_index)
+            .await
+            .graphql()?
+            .map(|res| Self {
+                output: res.output,
+                metadata: res.metadata,
+                ledger_index,
+            }))
+    }
+}
+
+#[Object]
+impl OutputNode {
+    async fn output_id(&self) -> String {
+        self.metadata.output_id.to_hex()
+    }
+
+    /// The output in the JSON format of the core API.
+    async fn output(&self) -> async_graphql::Result<Json<Value>> {
+        json(OutputDto::from(self.output.clone()))
+    }
+
+    /// The metadata of the output in the JSON format of the core API.
+    async fn metadata(&self) -> async_graphql::Result<Json<Value>> {
+        json(OutputMetadataDto::new(&self.metadata, self.ledger_index))
+    }
+
+    /// The block whose transaction created the output.
+    async fn block(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<BlockNode>> {
+        BlockNode::find(ctx.data::<MongoDb>()?, self.metadata.block_id).await
+    }
+
+    /// The block whose transaction spent the output, if it was spent.
+    async fn spending_block(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<BlockNode>> {
+        let spent = match &self.metadata.spent_metadata {
+            Some(spent) => spent,
+            None => return Ok(None),
+        };
+        Ok(ctx
+            .data::<MongoDb>()?
+            .collection::<BlockCollection>()
+            .get_block_metadata_for_transaction(&spent.transaction_id)
+            .await
+            .graphql()?
+            .map(|res| BlockNode {
+                block_id: res.block_id,
+                metadata: res.metadata,
+            }))
+    }
+}
+
+pub struct AddressNode {
+    address: Address,
+    bech32: String,
+}
+
+#[derive(SimpleObject)]
+pub struct Balance {
+    total_balance: String,
+    available_balance: String,
+    ledger_index: u32,
+}
+
+pub struct LedgerUpdate {
+    milestone_index: MilestoneIndex,
+    milestone_timestamp: u32,
+    output_id: OutputId,
+    is_spent: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct LedgerUpdatePage {
+    items: Vec<LedgerUpdate>,
+    /// Passed as `after` to request the next page. Unset on the last page.
+    cursor: Option<String>,
+}
+
+#[Object]
+impl AddressNode {
+    async fn address(&self) -> &str {
+        &self.bech32
+    }
+
+    /// The balance of the address at the ledger index that the query is resolved against.
+    async fn balance(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Balance>> {
+        let database = ctx.data::<MongoDb>()?;
+        let LedgerIndex(ledger_index) = *ctx.data::<LedgerIndex>()?;
+        let milestone_timestamp = database
+            .collection::<MilestoneCollection>()
+            .get_milestone_timestamp(ledger_index)
+            .await
+            .graphql()?
+            .ok_or(MissingError::NoResults)
+            .graphql()?;
+        Ok(database
+            .collection::<OutputCollection>()
+            .get_address_balance(
+                self.address,
+                MilestoneIndexTimestamp {
+                    milestone_index: ledger_index,
+                    milestone_timestamp,
+                },
+            )
+            .await
+            .graphql()?
+            .map(|res| Balance {
+                total_balance: res.total_balance,
+                available_balance: res.available_balance,
+                ledger_index: ledger_index.0,
+            }))
+    }
+
+    /// The outputs that were created for and spent by the address, newest first unless `oldestFirst` is set.
+    #[graphql(complexity = "first.unwrap_or(DEFAULT_PAGE_SIZE).saturating_mul(child_complexity)")]
+    async fn ledger_updates(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<usize>,
+        after: Option<String>,
+        #[graphql(default)] oldest_first: bool,
+    ) -> async_graphql::Result<LedgerUpdatePage> {
+        let page_size = first
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .min(ctx.data::<ApiConfigData>()?.max_page_size);
+        let cursor = after.as_deref().map(parse_cursor).transpose().graphql()?;
+        let order = if oldest_first {
+            SortOrder::Oldest
+        } else {
+            SortOrder::Newest
+        };
+        let LedgerIndex(ledger_index) = *ctx.data::<LedgerIndex>()?;
+
+        let mut records = ctx
+            .data::<MongoDb>()?
+            .collection::<LedgerUpdateCollection>()
+            .get_ledger_updates_by_address(
+                &self.address,
+                // Get one extra record so that we can create the cursor.
+                page_size + 1,
+                cursor.map(|(milestone_index, output_id, is_spent)| (milestone_index, Some((output_id, is_spent)))),
+                order,
+                ledger_index,
+            )
+            .await
+            .graphql()?;
+        let items = records
+            .by_ref()
+            .take(page_size)
+            .map_ok(|rec| LedgerUpdate {
+                milestone_index: rec.at.milestone_index,
+                milestone_timestamp: rec.at.milestone_timestamp.0,
+                output_id: rec.output_id,
+                is_spent: rec.is_spent,
+            })
+            .try_collect()
+            .await
+            .graphql()?;
+        let cursor = records
+            .try_next()
+            .await
+            .graphql()?
+            .map(|rec| format!("{}.{}.{}", rec.at.milestone_index, rec.output_id.to_hex(), rec.is_spent));
+
+        Ok(LedgerUpdatePage { items, cursor })
+    }
+}
+
+/// Parses a cursor of the form `<milestoneIndex>.<outputId>.<isSpent>`.
+fn parse_cursor(cursor: &str) -> Result<(MilestoneIndex, OutputId, bool), ApiError> {
+    let parts: Vec<_> = cursor.split('.').collect();
+    match parts[..] {
+        [ms, o, sp] => Ok((
+            ms.parse().map_err(RequestError::from)?,
+            o.parse().map_err(RequestError::from)?,
+            sp.parse().map_err(RequestError::from)?,
+        )),
+        _ => Err(RequestError::BadPagingState.into()),
+    }
+}
+
+#[Object]
+impl LedgerUpdate {
+    async fn milestone_index(&self) -> u32 {
+        self.milestone_index.0
+    }
+
+    async fn milestone_timestamp(&self) -> u32 {
+        self.milestone_timestamp
+    }
+
+    async fn output_id(&self) -> String {
+        self.output_id.to_hex()
+    }
+
+    async fn is_spent(&self) -> bool {
+        self.is_spent
+    }
+
+    /// The updated output.
+    async fn output(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<OutputNode>> {
+        OutputNode::find(ctx, self.output_id).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cursor() {
+        let output_id =
+            OutputId::from_str("0x00000000000000000000000000000000000000000000000000000000000000000100").unwrap();
+        let (milestone_index, parsed_output_id, is_spent) =
+            parse_cursor(&format!("17.{}.true", output_id.to_hex())).unwrap();
+        assert_eq!(milestone_index, MilestoneIndex(17));
+        assert_eq!(parsed_output_id, output_id);
+        assert!(is_spent);
+        assert!(parse_cursor("17.true").is_err());
+    }
+}
@@ -5,21 +5,20 @@ use std::{fmt::Display, str::FromStr};
 
 use async_trait::async_trait;
 use axum::{
-    extract::{FromRequest, Query},
+    extract::{FromRequest, Path, Query},
     Extension,
 };
 use chronicle::{
-    db::mongodb::collections::{AliasOutputsQuery, BasicOutputsQuery, FoundryOutputsQuery, NftOutputsQuery, SortOrder},
-    model::{
-        tangle::MilestoneIndex,
-        utxo::{Address, OutputId, Tag},
+    db::mongodb::collections::{
+        AliasOutputsQuery, BasicOutputsQuery, FoundryOutputsQuery, IndexedOutputsPosition, IndexedOutputsSort,
+        IndexedOutputsSortKey, NftOutputsQuery, OutputsQuery, StorageDepositReturnOutputsQuery,
     },
+    model::utxo::{Address, OutputId, Tag},
 };
 use mongodb::bson;
-use primitive_types::U256;
 use serde::Deserialize;
 
-use crate::api::{config::ApiConfigData, error::RequestError, ApiError, DEFAULT_PAGE_SIZE};
+use crate::api::{auth::Auth, config::ApiConfigData, error::RequestError, ApiError, DEFAULT_PAGE_SIZE};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IndexedOutputsPagination<Q>
@@ -28,26 +27,57 @@ where
 {
     pub query: Q,
     pub page_size: usize,
-    pub cursor: Option<(MilestoneIndex, OutputId)>,
-    pub sort: SortOrder,
+    pub cursor: Option<IndexedOutputsPosition>,
+    pub sort: IndexedOutputsSort,
     pub include_spent: bool,
+    /// Whether to return the query plan instead of the outputs. Requires authentication.
+    pub explain: bool,
 }
 
 #[derive(Clone)]
 pub struct IndexedOutputsCursor {
-    pub milestone_index: MilestoneIndex,
+    /// The key the outputs were sorted by, so that a cursor is not applied to a different order.
+    pub sort_key: IndexedOutputsSortKey,
+    /// The value the outputs are sorted by, which is the booked milestone index or the amount of the output.
+    pub sort_value: u64,
     pub output_id: OutputId,
     pub page_size: usize,
 }
 
+impl IndexedOutputsCursor {
+    /// Gets the first output of the page, whose value is that of the key the outputs are sorted by.
+    fn position(&self, sort_key: IndexedOutputsSortKey) -> Result<IndexedOutputsPosition, ApiError> {
+        if self.sort_key != sort_key {
+            return Err(ApiError::from(RequestError::BadPagingState));
+        }
+        Ok(match sort_key {
+            IndexedOutputsSortKey::BookedIndex => IndexedOutputsPosition::BookedIndex(
+                u32::try_from(self.sort_value)
+                    .map_err(|_| RequestError::BadPagingState)?
+                    .into(),
+                self.output_id,
+            ),
+            IndexedOutputsSortKey::Amount => IndexedOutputsPosition::Amount(self.sort_value, self.output_id),
+        })
+    }
+}
+
 impl FromStr for IndexedOutputsCursor {
     type Err = ApiError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parts: Vec<_> = s.split('.').collect();
         Ok(match parts[..] {
-            [ms, o, ps] => IndexedOutputsCursor {
-                milestone_index: ms.parse().map_err(RequestError::from)?,
+            [k, v, o, ps] => IndexedOutputsCursor {
+                sort_key: k.parse().map_err(RequestError::from)?,
+                sort_value: v.parse().map_err(RequestError::from)?,
+                output_id: o.parse().map_err(RequestError::from)?,
+                page_size: ps.parse().map_err(RequestError::from)?,
+            },
+            // Cursors from before the outputs could be sorted by other keys.
+            [v, o, ps] => IndexedOutputsCursor {
+                sort_key: IndexedOutputsSortKey::BookedIndex,
+                sort_value: v.parse().map_err(RequestError::from)?,
                 output_id: o.parse().map_err(RequestError::from)?,
                 page_size: ps.parse().map_err(RequestError::from)?,
             },
@@ -60,21 +90,113 @@ impl Display for IndexedOutputsCursor {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}.{}.{}",
-            self.milestone_index,
+            "{}.{}.{}.{}",
+            self.sort_key,
+            self.sort_value,
             self.output_id.to_hex(),
             self.page_size
         )
     }
 }
 
+/// Parses the `sortBy` query parameter and the order of the chosen key, which is given by `sort` for the booked
+/// milestone and by `amountOrder` for the amount.
+fn parse_sort(
+    sort_by: Option<&str>,
+    sort: Option<&str>,
+    amount_order: Option<&str>,
+) -> Result<IndexedOutputsSort, RequestError> {
+    Ok(match sort_by.map_or(Ok(Default::default()), str::parse)? {
+        IndexedOutputsSortKey::BookedIndex => {
+            if amount_order.is_some() {
+                return Err(RequestError::SortParameter("amountOrder", "booked"));
+            }
+            IndexedOutputsSort::BookedIndex(sort.map_or(Ok(Default::default()), str::parse)?)
+        }
+        IndexedOutputsSortKey::Amount => {
+            if sort.is_some() {
+                return Err(RequestError::SortParameter("sort", "amount"));
+            }
+            IndexedOutputsSort::Amount(amount_order.map_or(Ok(Default::default()), str::parse)?)
+        }
+    })
+}
+
+#[derive(Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct OutputsPaginationQuery {
+    pub has_native_tokens: Option<bool>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
+    pub unlockable_by_address: Option<String>,
+    pub created_before: Option<u32>,
+    pub created_after: Option<u32>,
+    pub page_size: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub sort_by: Option<String>,
+    pub amount_order: Option<String>,
+    pub include_spent: Option<bool>,
+    pub explain: Option<bool>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for IndexedOutputsPagination<OutputsQuery> {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<OutputsPaginationQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        let sort = parse_sort(
+            query.sort_by.as_deref(),
+            query.sort.as_deref(),
+            query.amount_order.as_deref(),
+        )?;
+
+        let (cursor, page_size) = if let Some(cursor) = query.cursor {
+            let cursor: IndexedOutputsCursor = cursor.parse()?;
+            (Some(cursor.position(sort.key())?), cursor.page_size)
+        } else {
+            (None, query.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
+        };
+
+        let explain = query.explain.unwrap_or_default();
+        if explain {
+            Auth::validate_jwt(req, &config).await?;
+        }
+
+        Ok(IndexedOutputsPagination {
+            query: OutputsQuery {
+                has_native_tokens: query.has_native_tokens,
+                min_native_token_count: query.min_native_token_count,
+                max_native_token_count: query.max_native_token_count,
+                unlockable_by_address: query
+                    .unlockable_by_address
+                    .map(|address| Address::from_str(&address))
+                    .transpose()
+                    .map_err(RequestError::from)?,
+                created_before: query.created_before.map(Into::into),
+                created_after: query.created_after.map(Into::into),
+            },
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+            sort,
+            include_spent: query.include_spent.unwrap_or_default(),
+            explain,
+        })
+    }
+}
+
 #[derive(Clone, Deserialize, Default)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
 pub struct BasicOutputsPaginationQuery {
     pub address: Option<String>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub has_storage_deposit_return: Option<bool>,
     pub storage_deposit_return_address: Option<String>,
     pub has_timelock: Option<bool>,
@@ -88,10 +210,14 @@ pub struct BasicOutputsPaginationQuery {
     pub tag: Option<String>,
     pub created_before: Option<u32>,
     pub created_after: Option<u32>,
+    pub unlockable_by_address: Option<String>,
     pub page_size: Option<usize>,
     pub cursor: Option<String>,
     pub sort: Option<String>,
+    pub sort_by: Option<String>,
+    pub amount_order: Option<String>,
     pub include_spent: Option<bool>,
+    pub explain: Option<bool>,
 }
 
 #[async_trait]
@@ -104,18 +230,23 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<BasicOutputsQuery> {
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
+        let sort = parse_sort(
+            query.sort_by.as_deref(),
+            query.sort.as_deref(),
+            query.amount_order.as_deref(),
+        )?;
+
         let (cursor, page_size) = if let Some(cursor) = query.cursor {
             let cursor: IndexedOutputsCursor = cursor.parse()?;
-            (Some((cursor.milestone_index, cursor.output_id)), cursor.page_size)
+            (Some(cursor.position(sort.key())?), cursor.page_size)
         } else {
             (None, query.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
         };
 
-        let sort = query
-            .sort
-            .as_deref()
-            .map_or(Ok(Default::default()), str::parse)
-            .map_err(RequestError::SortOrder)?;
+        let explain = query.explain.unwrap_or_default();
+        if explain {
+            Auth::validate_jwt(req, &config).await?;
+        }
 
         Ok(IndexedOutputsPagination {
             query: BasicOutputsQuery {
@@ -125,16 +256,8 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<BasicOutputsQuery> {
                     .transpose()
                     .map_err(RequestError::from)?,
                 has_native_tokens: query.has_native_tokens,
-                min_native_token_count: query
-                    .min_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
-                max_native_token_count: query
-                    .max_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
+                min_native_token_count: query.min_native_token_count,
+                max_native_token_count: query.max_native_token_count,
                 has_storage_deposit_return: query.has_storage_deposit_return,
                 storage_deposit_return_address: query
                     .storage_deposit_return_address
@@ -164,11 +287,17 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<BasicOutputsQuery> {
                     .map_err(RequestError::from)?,
                 created_before: query.created_before.map(Into::into),
                 created_after: query.created_after.map(Into::into),
+                unlockable_by_address: query
+                    .unlockable_by_address
+                    .map(|address| Address::from_str(&address))
+                    .transpose()
+                    .map_err(RequestError::from)?,
             },
             page_size: page_size.min(config.max_page_size),
             cursor,
             sort,
             include_spent: query.include_spent.unwrap_or_default(),
+            explain,
         })
     }
 }
@@ -181,14 +310,18 @@ pub struct AliasOutputsPaginationQuery {
     pub issuer: Option<String>,
     pub sender: Option<String>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub created_before: Option<u32>,
     pub created_after: Option<u32>,
+    pub unlockable_by_address: Option<String>,
     pub page_size: Option<usize>,
     pub cursor: Option<String>,
     pub sort: Option<String>,
+    pub sort_by: Option<String>,
+    pub amount_order: Option<String>,
     pub include_spent: Option<bool>,
+    pub explain: Option<bool>,
 }
 
 #[async_trait]
@@ -201,18 +334,23 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<AliasOutputsQuery> {
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
+        let sort = parse_sort(
+            query.sort_by.as_deref(),
+            query.sort.as_deref(),
+            query.amount_order.as_deref(),
+        )?;
+
         let (cursor, page_size) = if let Some(cursor) = query.cursor {
             let cursor: IndexedOutputsCursor = cursor.parse()?;
-            (Some((cursor.milestone_index, cursor.output_id)), cursor.page_size)
+            (Some(cursor.position(sort.key())?), cursor.page_size)
         } else {
             (None, query.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
         };
 
-        let sort = query
-            .sort
-            .as_deref()
-            .map_or(Ok(Default::default()), str::parse)
-            .map_err(RequestError::SortOrder)?;
+        let explain = query.explain.unwrap_or_default();
+        if explain {
+            Auth::validate_jwt(req, &config).await?;
+        }
 
         Ok(IndexedOutputsPagination {
             query: AliasOutputsQuery {
@@ -237,23 +375,21 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<AliasOutputsQuery> {
                     .transpose()
                     .map_err(RequestError::from)?,
                 has_native_tokens: query.has_native_tokens,
-                min_native_token_count: query
-                    .min_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
-                max_native_token_count: query
-                    .max_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
+                min_native_token_count: query.min_native_token_count,
+                max_native_token_count: query.max_native_token_count,
                 created_before: query.created_before.map(Into::into),
                 created_after: query.created_after.map(Into::into),
+                unlockable_by_address: query
+                    .unlockable_by_address
+                    .map(|address| Address::from_str(&address))
+                    .transpose()
+                    .map_err(RequestError::from)?,
             },
             page_size: page_size.min(config.max_page_size),
             cursor,
             sort,
             include_spent: query.include_spent.unwrap_or_default(),
+            explain,
         })
     }
 }
@@ -263,14 +399,17 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<AliasOutputsQuery> {
 pub struct FoundryOutputsPaginationQuery {
     pub alias_address: Option<String>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub created_before: Option<u32>,
     pub created_after: Option<u32>,
     pub page_size: Option<usize>,
     pub cursor: Option<String>,
     pub sort: Option<String>,
+    pub sort_by: Option<String>,
+    pub amount_order: Option<String>,
     pub include_spent: Option<bool>,
+    pub explain: Option<bool>,
 }
 
 #[async_trait]
@@ -283,18 +422,23 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<FoundryOutputsQuery> {
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
+        let sort = parse_sort(
+            query.sort_by.as_deref(),
+            query.sort.as_deref(),
+            query.amount_order.as_deref(),
+        )?;
+
         let (cursor, page_size) = if let Some(cursor) = query.cursor {
             let cursor: IndexedOutputsCursor = cursor.parse()?;
-            (Some((cursor.milestone_index, cursor.output_id)), cursor.page_size)
+            (Some(cursor.position(sort.key())?), cursor.page_size)
         } else {
             (None, query.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
         };
 
-        let sort = query
-            .sort
-            .as_deref()
-            .map_or(Ok(Default::default()), str::parse)
-            .map_err(RequestError::SortOrder)?;
+        let explain = query.explain.unwrap_or_default();
+        if explain {
+            Auth::validate_jwt(req, &config).await?;
+        }
 
         Ok(IndexedOutputsPagination {
             query: FoundryOutputsQuery {
@@ -304,16 +448,8 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<FoundryOutputsQuery> {
                     .transpose()
                     .map_err(RequestError::from)?,
                 has_native_tokens: query.has_native_tokens,
-                min_native_token_count: query
-                    .min_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
-                max_native_token_count: query
-                    .max_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
+                min_native_token_count: query.min_native_token_count,
+                max_native_token_count: query.max_native_token_count,
                 created_before: query.created_before.map(Into::into),
                 created_after: query.created_after.map(Into::into),
             },
@@ -321,6 +457,7 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<FoundryOutputsQuery> {
             cursor,
             sort,
             include_spent: query.include_spent.unwrap_or_default(),
+            explain,
         })
     }
 }
@@ -332,8 +469,8 @@ pub struct NftOutputsPaginationQuery {
     pub issuer: Option<String>,
     pub sender: Option<String>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub has_storage_deposit_return: Option<bool>,
     pub storage_deposit_return_address: Option<String>,
     pub has_timelock: Option<bool>,
@@ -346,10 +483,14 @@ pub struct NftOutputsPaginationQuery {
     pub tag: Option<String>,
     pub created_before: Option<u32>,
     pub created_after: Option<u32>,
+    pub unlockable_by_address: Option<String>,
     pub page_size: Option<usize>,
     pub cursor: Option<String>,
     pub sort: Option<String>,
+    pub sort_by: Option<String>,
+    pub amount_order: Option<String>,
     pub include_spent: Option<bool>,
+    pub explain: Option<bool>,
 }
 
 #[async_trait]
@@ -362,18 +503,23 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<NftOutputsQuery> {
             .map_err(RequestError::from)?;
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
+        let sort = parse_sort(
+            query.sort_by.as_deref(),
+            query.sort.as_deref(),
+            query.amount_order.as_deref(),
+        )?;
+
         let (cursor, page_size) = if let Some(cursor) = query.cursor {
             let cursor: IndexedOutputsCursor = cursor.parse()?;
-            (Some((cursor.milestone_index, cursor.output_id)), cursor.page_size)
+            (Some(cursor.position(sort.key())?), cursor.page_size)
         } else {
             (None, query.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
         };
 
-        let sort = query
-            .sort
-            .as_deref()
-            .map_or(Ok(Default::default()), str::parse)
-            .map_err(RequestError::SortOrder)?;
+        let explain = query.explain.unwrap_or_default();
+        if explain {
+            Auth::validate_jwt(req, &config).await?;
+        }
 
         Ok(IndexedOutputsPagination {
             query: NftOutputsQuery {
@@ -393,16 +539,8 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<NftOutputsQuery> {
                     .transpose()
                     .map_err(RequestError::from)?,
                 has_native_tokens: query.has_native_tokens,
-                min_native_token_count: query
-                    .min_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
-                max_native_token_count: query
-                    .max_native_token_count
-                    .map(|c| U256::from_dec_str(&c))
-                    .transpose()
-                    .map_err(RequestError::from)?,
+                min_native_token_count: query.min_native_token_count,
+                max_native_token_count: query.max_native_token_count,
                 has_storage_deposit_return: query.has_storage_deposit_return,
                 storage_deposit_return_address: query
                     .storage_deposit_return_address
@@ -427,11 +565,75 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<NftOutputsQuery> {
                     .map_err(RequestError::from)?,
                 created_before: query.created_before.map(Into::into),
                 created_after: query.created_after.map(Into::into),
+                unlockable_by_address: query
+                    .unlockable_by_address
+                    .map(|address| Address::from_str(&address))
+                    .transpose()
+                    .map_err(RequestError::from)?,
             },
             page_size: page_size.min(config.max_page_size),
             cursor,
             sort,
             include_spent: query.include_spent.unwrap_or_default(),
+            explain,
+        })
+    }
+}
+
+#[derive(Clone, Deserialize, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct StorageDepositReturnOutputsPaginationQuery {
+    pub created_before: Option<u32>,
+    pub created_after: Option<u32>,
+    pub page_size: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub sort_by: Option<String>,
+    pub amount_order: Option<String>,
+    pub include_spent: Option<bool>,
+    pub explain: Option<bool>,
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for IndexedOutputsPagination<StorageDepositReturnOutputsQuery> {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Path(return_address) = Path::<String>::from_request(req).await.map_err(RequestError::from)?;
+        let Query(query) = Query::<StorageDepositReturnOutputsPaginationQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        let sort = parse_sort(
+            query.sort_by.as_deref(),
+            query.sort.as_deref(),
+            query.amount_order.as_deref(),
+        )?;
+
+        let (cursor, page_size) = if let Some(cursor) = query.cursor {
+            let cursor: IndexedOutputsCursor = cursor.parse()?;
+            (Some(cursor.position(sort.key())?), cursor.page_size)
+        } else {
+            (None, query.page_size.unwrap_or(DEFAULT_PAGE_SIZE))
+        };
+
+        let explain = query.explain.unwrap_or_default();
+        if explain {
+            Auth::validate_jwt(req, &config).await?;
+        }
+
+        Ok(IndexedOutputsPagination {
+            query: StorageDepositReturnOutputsQuery {
+                return_address: Address::from_str(&return_address).map_err(RequestError::from)?,
+                created_before: query.created_before.map(Into::into),
+                created_after: query.created_after.map(Into::into),
+            },
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+            sort,
+            include_spent: query.include_spent.unwrap_or_default(),
+            explain,
         })
     }
 }
@@ -441,6 +643,8 @@ mod test {
     use axum::{extract::RequestParts, http::Request};
     use pretty_assertions::assert_eq;
 
+    use chronicle::db::mongodb::collections::AmountOrder;
+
     use super::*;
     use crate::api::ApiConfig;
 
@@ -450,9 +654,13 @@ mod test {
         let output_id_str = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a20100";
         let page_size_str = "1337";
 
-        let cursor = format!("{milestone_index}.{output_id_str}.{page_size_str}",);
+        let cursor = format!("booked.{milestone_index}.{output_id_str}.{page_size_str}",);
         let parsed: IndexedOutputsCursor = cursor.parse().unwrap();
         assert_eq!(parsed.to_string(), cursor);
+
+        let cursor = format!("{milestone_index}.{output_id_str}.{page_size_str}",);
+        let parsed: IndexedOutputsCursor = cursor.parse().unwrap();
+        assert_eq!(parsed.sort_key, IndexedOutputsSortKey::BookedIndex);
     }
 
     #[tokio::test]
@@ -474,8 +682,113 @@ mod test {
                 query: Default::default(),
                 cursor: Default::default(),
                 sort: Default::default(),
-                include_spent: Default::default()
+                include_spent: Default::default(),
+                explain: Default::default(),
             }
         );
     }
+
+    #[tokio::test]
+    async fn sort_by_amount() {
+        let output_id_str = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a20100";
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri(format!(
+                    "/outputs/basic?sortBy=amount&amountOrder=smallest&cursor=amount.5000000000.{output_id_str}.10"
+                ))
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        let pagination = IndexedOutputsPagination::<BasicOutputsQuery>::from_request(&mut req)
+            .await
+            .unwrap();
+        assert_eq!(pagination.sort, IndexedOutputsSort::Amount(AmountOrder::Smallest));
+        assert_eq!(
+            pagination.cursor,
+            Some(IndexedOutputsPosition::Amount(
+                5000000000,
+                OutputId::from_str(output_id_str).unwrap()
+            ))
+        );
+
+        for uri in [
+            "/outputs/basic?sortBy=timestamp",
+            &format!("/outputs/basic?sortBy=amount&cursor=booked.5000000.{output_id_str}.10"),
+            &format!("/outputs/basic?cursor=amount.5000000000.{output_id_str}.10"),
+            "/outputs/basic?sortBy=amount&sort=oldest",
+            "/outputs/basic?amountOrder=largest",
+        ] {
+            let mut req = RequestParts::new(
+                Request::builder()
+                    .method("GET")
+                    .uri(uri)
+                    .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                    .body(())
+                    .unwrap(),
+            );
+            assert!(
+                IndexedOutputsPagination::<BasicOutputsQuery>::from_request(&mut req)
+                    .await
+                    .is_err(),
+                "{uri}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn node_query_parameters() {
+        let address = "rms1qpllaj0pyveqfkwxmnngz2c488hfdtmfrj3wfkgxtk4gtyrax0jaxzt70zy";
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri(format!(
+                    "/outputs?hasNativeTokens=true&minNativeTokenCount=2&maxNativeTokenCount=10&unlockableByAddress={address}"
+                ))
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            IndexedOutputsPagination::<OutputsQuery>::from_request(&mut req)
+                .await
+                .unwrap()
+                .query,
+            OutputsQuery {
+                has_native_tokens: Some(true),
+                min_native_token_count: Some(2),
+                max_native_token_count: Some(10),
+                unlockable_by_address: Some(Address::from_str(address).unwrap()),
+                ..Default::default()
+            }
+        );
+
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/outputs/basic?minNativeTokenCount=100000000000000000000")
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert!(IndexedOutputsPagination::<BasicOutputsQuery>::from_request(&mut req)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn explain_requires_auth() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/outputs/basic?explain=true")
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert!(IndexedOutputsPagination::<BasicOutputsQuery>::from_request(&mut req)
+            .await
+            .is_err());
+    }
 }
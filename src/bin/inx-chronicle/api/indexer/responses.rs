@@ -13,6 +13,46 @@ pub struct IndexerOutputsResponse {
     pub items: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Option<String>,
+    pub page_size: usize,
 }
 
 impl_success_response!(IndexerOutputsResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexerExplainResponse {
+    pub ledger_index: MilestoneIndex,
+    pub pipeline: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winning_plan: Option<serde_json::Value>,
+}
+
+impl_success_response!(IndexerExplainResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDepositReturnOutputsResponse {
+    #[serde(flatten)]
+    pub outputs: IndexerOutputsResponse,
+    /// The number of unspent outputs with a storage deposit to be returned to the address.
+    pub total_count: usize,
+    /// The sum of the storage deposits that can be reclaimed by the address.
+    pub total_return_amount: String,
+}
+
+impl_success_response!(StorageDepositReturnOutputsResponse);
+
+/// An indexer outputs response, or the query plan for it if explain mode was requested.
+pub enum IndexerOutputsOrExplainResponse<T = IndexerOutputsResponse> {
+    Outputs(T),
+    Explain(IndexerExplainResponse),
+}
+
+impl<T: axum::response::IntoResponse> axum::response::IntoResponse for IndexerOutputsOrExplainResponse<T> {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::Outputs(res) => res.into_response(),
+            Self::Explain(res) => res.into_response(),
+        }
+    }
+}
@@ -3,12 +3,13 @@
 
 use std::str::FromStr;
 
-use axum::{extract::Path, routing::get, Extension};
+use axum::{extract::Path, middleware::from_fn, routing::get, Extension};
 use chronicle::{
     db::{
         mongodb::collections::{
-            AliasOutputsQuery, BasicOutputsQuery, FoundryOutputsQuery, IndexedId, MilestoneCollection, NftOutputsQuery,
-            OutputCollection,
+            AliasOutputsQuery, ApplicationStateCollection, BasicOutputsQuery, FoundryOutputsQuery, IndexedId,
+            IndexedOutputsSortKey, MilestoneCollection, NftOutputsQuery, OutputCollection, OutputsQuery,
+            StorageDepositReturnOutputsQuery,
         },
         MongoDb,
     },
@@ -16,10 +17,17 @@ use chronicle::{
 };
 use mongodb::bson;
 
-use super::{extractors::IndexedOutputsPagination, responses::IndexerOutputsResponse};
+use super::{
+    extractors::IndexedOutputsPagination,
+    responses::{
+        IndexerExplainResponse, IndexerOutputsOrExplainResponse, IndexerOutputsResponse,
+        StorageDepositReturnOutputsResponse,
+    },
+};
 use crate::api::{
     error::{MissingError, RequestError},
     indexer::extractors::IndexedOutputsCursor,
+    query_pool,
     router::Router,
     ApiResult,
 };
@@ -28,6 +36,7 @@ pub fn routes() -> Router {
     Router::new().nest(
         "/outputs",
         Router::new()
+            .route("/", get(indexed_outputs::<OutputsQuery>))
             .route("/basic", get(indexed_outputs::<BasicOutputsQuery>))
             .nest(
                 "/alias",
@@ -46,7 +55,9 @@ pub fn routes() -> Router {
                 Router::new()
                     .route("/", get(indexed_outputs::<NftOutputsQuery>))
                     .route("/:nft_id", get(indexed_output_by_id::<NftId>)),
-            ),
+            )
+            .route("/storage-deposit-return/:address", get(storage_deposit_return_outputs))
+            .route_layer(from_fn(query_pool::limit)),
     )
 }
 
@@ -73,6 +84,7 @@ where
         ledger_index,
         items: vec![res.output_id.to_hex()],
         cursor: None,
+        page_size: 1,
     })
 }
 
@@ -84,8 +96,9 @@ async fn indexed_outputs<Q>(
         cursor,
         sort,
         include_spent,
+        explain,
     }: IndexedOutputsPagination<Q>,
-) -> ApiResult<IndexerOutputsResponse>
+) -> ApiResult<IndexerOutputsOrExplainResponse>
 where
     bson::Document: From<Q>,
 {
@@ -94,6 +107,43 @@ where
         .get_ledger_index()
         .await?
         .ok_or(MissingError::NoResults)?;
+
+    let details_version = match sort.key().details_version() {
+        Some(_) => {
+            database
+                .collection::<ApplicationStateCollection>()
+                .get_output_details_version()
+                .await?
+        }
+        None => None,
+    };
+
+    if explain {
+        let res = database
+            .collection::<OutputCollection>()
+            .explain_indexed_outputs(
+                query,
+                page_size + 1,
+                cursor,
+                sort,
+                include_spent,
+                ledger_index,
+                details_version,
+            )
+            .await?;
+        return Ok(IndexerOutputsOrExplainResponse::Explain(IndexerExplainResponse {
+            ledger_index,
+            pipeline: res
+                .pipeline
+                .into_iter()
+                .map(|stage| bson::Bson::Document(stage).into_relaxed_extjson())
+                .collect(),
+            winning_plan: res
+                .winning_plan
+                .map(|plan| bson::Bson::Document(plan).into_relaxed_extjson()),
+        }));
+    }
+
     let res = database
         .collection::<OutputCollection>()
         .get_indexed_outputs(
@@ -104,6 +154,7 @@ where
             sort,
             include_spent,
             ledger_index,
+            details_version,
         )
         .await?;
 
@@ -115,16 +166,46 @@ where
     // If any record is left, use it to make the cursor
     let cursor = iter.next().map(|rec| {
         IndexedOutputsCursor {
-            milestone_index: rec.booked_index,
+            sort_key: sort.key(),
+            sort_value: match sort.key() {
+                IndexedOutputsSortKey::BookedIndex => rec.booked_index.0 as u64,
+                IndexedOutputsSortKey::Amount => rec.amount,
+            },
             output_id: rec.output_id,
             page_size,
         }
         .to_string()
     });
 
-    Ok(IndexerOutputsResponse {
+    Ok(IndexerOutputsOrExplainResponse::Outputs(IndexerOutputsResponse {
         ledger_index,
         items,
         cursor,
-    })
+        page_size,
+    }))
+}
+
+async fn storage_deposit_return_outputs(
+    database: Extension<MongoDb>,
+    pagination: IndexedOutputsPagination<StorageDepositReturnOutputsQuery>,
+) -> ApiResult<IndexerOutputsOrExplainResponse<StorageDepositReturnOutputsResponse>> {
+    let return_address = pagination.query.return_address;
+    let outputs = match indexed_outputs(database.clone(), pagination).await? {
+        IndexerOutputsOrExplainResponse::Outputs(outputs) => outputs,
+        IndexerOutputsOrExplainResponse::Explain(explain) => {
+            return Ok(IndexerOutputsOrExplainResponse::Explain(explain));
+        }
+    };
+    // The totals cover all unspent outputs at the same ledger index, regardless of the requested page.
+    let totals = database
+        .collection::<OutputCollection>()
+        .get_storage_deposit_return_totals(return_address, outputs.ledger_index)
+        .await?;
+    Ok(IndexerOutputsOrExplainResponse::Outputs(
+        StorageDepositReturnOutputsResponse {
+            outputs,
+            total_count: totals.count,
+            total_return_amount: totals.amount,
+        },
+    ))
 }
@@ -0,0 +1,40 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::Extension;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::{
+    auth::Admin, error::UnimplementedError, extractors::LogFilterRequest, responses::impl_success_response, ApiResult,
+};
+use crate::log_filter::LogFilter;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilterResponse {
+    /// The directives that decide which logs are emitted.
+    pub directives: String,
+}
+
+impl_success_response!(LogFilterResponse);
+
+pub async fn log_filter(_: Admin, Extension(log_filter): Extension<Option<LogFilter>>) -> ApiResult<LogFilterResponse> {
+    let log_filter = log_filter.ok_or(UnimplementedError)?;
+    Ok(LogFilterResponse {
+        directives: log_filter.directives()?,
+    })
+}
+
+/// Replaces the log filter until the next restart, which again uses the filter from the environment.
+pub async fn set_log_filter(
+    _: Admin,
+    Extension(log_filter): Extension<Option<LogFilter>>,
+    LogFilterRequest(filter): LogFilterRequest,
+) -> ApiResult<LogFilterResponse> {
+    let log_filter = log_filter.ok_or(UnimplementedError)?;
+    log_filter.replace(filter)?;
+    let directives = log_filter.directives()?;
+    info!("Log filter changed to `{directives}` through the API.");
+    Ok(LogFilterResponse { directives })
+}
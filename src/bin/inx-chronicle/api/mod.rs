@@ -7,24 +7,47 @@
 mod error;
 mod extractors;
 mod secret_key;
-#[macro_use]
 mod responses;
+pub mod audit;
 mod auth;
 pub mod config;
+mod consistency;
 mod core;
+mod deadline;
+pub mod dto;
+#[cfg(feature = "explorer")]
+mod etag;
+#[cfg(feature = "explorer")]
 mod explorer;
+#[cfg(feature = "graphql")]
+mod graphql;
 mod indexer;
+mod logging;
 #[cfg(feature = "poi")]
 mod poi;
+#[cfg(feature = "inx")]
+mod progress;
+#[cfg(feature = "explorer")]
+mod query_cache;
+mod query_pool;
 mod router;
 mod routes;
+#[cfg(all(test, feature = "rand"))]
+mod snapshots;
+#[cfg(feature = "inx")]
+mod standby;
+mod tls;
+mod versioning;
 
-use axum::{Extension, Server};
+use std::sync::Arc;
+
+use axum::{middleware::from_fn, Extension, Server};
 use chronicle::db::MongoDb;
 use futures::Future;
 use hyper::Method;
 use tower_http::{
     catch_panic::CatchPanicLayer,
+    compression::CompressionLayer,
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
@@ -33,49 +56,174 @@ use tracing::info;
 pub use self::{
     config::{ApiConfig, ApiConfigData},
     error::{ApiError, ApiResult, AuthError, ConfigError},
+    routes::API_ROOT,
     secret_key::SecretKey,
+    versioning::ApiVersion,
 };
 
 pub const DEFAULT_PAGE_SIZE: usize = 100;
 
+/// The id of the running process, as opposed to the id of the database it uses.
+#[derive(Clone, Debug)]
+pub struct InstanceId(pub String);
+
 /// The Chronicle API actor
 #[derive(Debug)]
 pub struct ApiWorker {
     db: MongoDb,
     api_data: ApiConfigData,
+    #[cfg(feature = "search")]
+    search_client: Option<crate::search::SearchClient>,
+    #[cfg(feature = "inx")]
+    sync_progress: Option<crate::inx::SyncProgress>,
+    #[cfg(feature = "inx")]
+    standby: Option<crate::inx::Standby>,
+    #[cfg(feature = "explorer")]
+    name_resolver: Option<crate::naming::SharedNameResolver>,
+    log_filter: Option<crate::log_filter::LogFilter>,
+    instance_id: Option<InstanceId>,
+    tls: Option<Arc<tls::CertResolver>>,
+    audit_log: Option<audit::AuditLog>,
 }
 
 impl ApiWorker {
     /// Create a new Chronicle API actor from a mongo connection.
     pub fn new(db: MongoDb, config: ApiConfig) -> Result<Self, ConfigError> {
+        let api_data = ApiConfigData::try_from(config)?;
+        // Load the certificate right away, so that a broken one is reported on startup.
+        let tls = api_data
+            .tls
+            .clone()
+            .map(tls::CertResolver::new)
+            .transpose()?
+            .map(Arc::new);
+        let audit_log = audit::AuditLog::new(&db, &api_data.audit_log, api_data.jwt_secret_key.as_ref())?;
         Ok(Self {
             db,
-            api_data: config.try_into()?,
+            api_data,
+            #[cfg(feature = "search")]
+            search_client: None,
+            #[cfg(feature = "inx")]
+            sync_progress: None,
+            #[cfg(feature = "inx")]
+            standby: None,
+            #[cfg(feature = "explorer")]
+            name_resolver: None,
+            log_filter: None,
+            instance_id: None,
+            tls,
+            audit_log,
         })
     }
 
+    /// Enables the search route, which proxies queries to the given search engine.
+    #[cfg(feature = "search")]
+    pub fn set_search_client(&mut self, search_client: crate::search::SearchClient) {
+        self.search_client.replace(search_client);
+    }
+
+    /// Enables the progress routes, which report how far syncing with the node has come.
+    #[cfg(feature = "inx")]
+    pub fn set_sync_progress(&mut self, sync_progress: crate::inx::SyncProgress) {
+        self.sync_progress.replace(sync_progress);
+    }
+
+    /// Enables the promotion of a standby instance through the API. Until it is promoted, the API does not write to the
+    /// database.
+    #[cfg(feature = "inx")]
+    pub fn set_standby(&mut self, standby: crate::inx::Standby) {
+        if let Some(audit_log) = &mut self.audit_log {
+            audit_log.set_standby(standby.clone());
+        }
+        self.standby.replace(standby);
+    }
+
+    /// Enables resolving names to addresses in the search route, and adds the names of addresses to responses.
+    #[cfg(feature = "explorer")]
+    pub fn set_name_resolver(&mut self, name_resolver: crate::naming::SharedNameResolver) {
+        self.name_resolver.replace(name_resolver);
+    }
+
+    /// Enables changing which logs are emitted through the API.
+    pub fn set_log_filter(&mut self, log_filter: crate::log_filter::LogFilter) {
+        self.log_filter.replace(log_filter);
+    }
+
+    /// Sets the id of this process, which is reported by the info route.
+    pub fn set_instance_id(&mut self, instance_id: String) {
+        self.instance_id.replace(InstanceId(instance_id));
+    }
+
     pub async fn run(&self, shutdown_handle: impl Future<Output = ()>) -> eyre::Result<()> {
-        info!("Starting API server on port `{}`", self.api_data.port);
+        let addr = ([0, 0, 0, 0], self.api_data.port).into();
+        let service = self.routes().into_make_service();
+        match &self.tls {
+            None => {
+                info!("Starting API server on port `{}`", self.api_data.port);
+                Server::bind(&addr)
+                    .http1_only(!self.api_data.http2_enabled)
+                    .serve(service)
+                    .with_graceful_shutdown(shutdown_handle)
+                    .await?;
+            }
+            Some(resolver) => {
+                info!("Starting API server with TLS on port `{}`", self.api_data.port);
+                let incoming = tls::incoming(addr, resolver.clone(), self.api_data.http2_enabled).await?;
+                let server = Server::builder(incoming)
+                    .http1_only(!self.api_data.http2_enabled)
+                    .serve(service)
+                    .with_graceful_shutdown(shutdown_handle);
+                tokio::select! {
+                    res = server => res?,
+                    _ = resolver.watch(self.api_data.tls_reload_interval) => (),
+                }
+            }
+        }
+
+        Ok(())
+    }
 
-        let port = self.api_data.port;
-        let routes = routes::routes()
+    /// Assembles the routes together with the state and middleware they rely on.
+    fn routes(&self) -> router::Router {
+        let request_timeout = self.api_data.request_timeout;
+        let audit_log = self.audit_log.clone();
+        let routes = routes::routes();
+        #[cfg(feature = "search")]
+        let routes = routes.layer(Extension(self.search_client.clone()));
+        #[cfg(feature = "inx")]
+        let routes = routes
+            .layer(Extension(self.sync_progress.clone()))
+            .layer(Extension(self.standby.clone()));
+        #[cfg(feature = "explorer")]
+        let routes = routes.layer(Extension(self.name_resolver.clone()));
+        routes
+            .layer(from_fn(consistency::read_after_write))
+            .layer(Extension(self.log_filter.clone()))
+            .layer(Extension(self.instance_id.clone()))
+            .layer(Extension(query_pool::QueryPool::new(
+                self.api_data.query_concurrency,
+                self.api_data.query_queue_length,
+            )))
             .layer(Extension(self.db.clone()))
             .layer(Extension(self.api_data.clone()))
+            .layer(from_fn(move |req, next| {
+                deadline::request_deadline(req, next, request_timeout)
+            }))
+            .layer(from_fn(move |req, next| audit::audit(req, next, audit_log.clone())))
             .layer(CatchPanicLayer::new())
+            .layer(
+                CompressionLayer::new()
+                    .gzip(self.api_data.compression_enabled)
+                    .br(self.api_data.compression_enabled),
+            )
             .layer(TraceLayer::new_for_http())
             .layer(
                 CorsLayer::new()
                     .allow_origin(self.api_data.allow_origins.clone())
-                    .allow_methods(vec![Method::GET, Method::OPTIONS])
+                    .allow_methods(vec![Method::GET, Method::POST, Method::OPTIONS])
                     .allow_headers(Any)
+                    .expose_headers([consistency::LEDGER_INDEX_HEADER])
                     .allow_credentials(false),
-            );
-
-        Server::bind(&([0, 0, 0, 0], port).into())
-            .serve(routes.into_make_service())
-            .with_graceful_shutdown(shutdown_handle)
-            .await?;
-
-        Ok(())
+            )
     }
 }
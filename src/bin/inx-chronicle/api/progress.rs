@@ -0,0 +1,393 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Write;
+
+use axum::{
+    extract::Path,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use chronicle::{
+    db::{
+        mongodb::collections::{
+            CollectionWrites, NodeHealthCollection, NodeHealthSample, SyncReport, SyncReportCollection,
+        },
+        MongoDb,
+    },
+    inx::{schema_drift as unknown_enum_values, SchemaDrift},
+    model::tangle::{MilestoneIndex, MilestoneTimestamp},
+};
+use futures::TryStreamExt;
+use mongodb::bson::DateTime;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    auth::Admin,
+    error::{MissingError, UnimplementedError},
+    extractors::TimeRange,
+    responses::impl_success_response,
+    ApiResult,
+};
+use crate::inx::{progress::ProgressSnapshot, SyncPhase, SyncProgress};
+
+/// The content type of the Prometheus text exposition format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+/// The window of node health samples returned when no start timestamp is given, in seconds.
+const DEFAULT_NODE_HEALTH_WINDOW: u32 = 24 * 60 * 60;
+/// The maximum number of node health samples returned at once.
+const MAX_NODE_HEALTH_SAMPLES: usize = 10_000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgressResponse {
+    pub phase: SyncPhase,
+    pub outputs_imported: u64,
+    pub synced_milestone_index: Option<MilestoneIndex>,
+    pub target_milestone_index: Option<MilestoneIndex>,
+    pub milestones_behind: Option<u32>,
+    pub eta_seconds: Option<u64>,
+}
+
+impl_success_response!(SyncProgressResponse);
+
+impl From<ProgressSnapshot> for SyncProgressResponse {
+    fn from(value: ProgressSnapshot) -> Self {
+        Self {
+            phase: value.phase,
+            outputs_imported: value.outputs_imported,
+            synced_milestone_index: value.synced_index,
+            target_milestone_index: value.target_index,
+            milestones_behind: value.milestones_behind,
+            eta_seconds: value.eta.map(|eta| eta.as_secs()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDriftResponse {
+    pub unknown_enum_values: Vec<UnknownEnumValueDto>,
+}
+
+impl_success_response!(SchemaDriftResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownEnumValueDto {
+    pub type_name: String,
+    pub value: i32,
+    pub count: u64,
+}
+
+impl From<SchemaDrift> for UnknownEnumValueDto {
+    fn from(value: SchemaDrift) -> Self {
+        Self {
+            type_name: value.type_name.to_string(),
+            value: value.value,
+            count: value.count,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReportResponse {
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    /// The unix time in milliseconds at which syncing the milestone finished.
+    pub synced_at: i64,
+    pub catching_up: bool,
+    /// The milliseconds spent in each phase.
+    pub phases: SyncReportPhasesDto,
+    /// The milliseconds spent syncing the milestone once it was received.
+    pub total_time: u64,
+    pub collections: Vec<CollectionWritesDto>,
+    pub checkpoint_attempts: u32,
+    pub warnings: Vec<String>,
+}
+
+impl_success_response!(SyncReportResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReportPhasesDto {
+    pub receive: u64,
+    pub output_insert: u64,
+    pub cone_receive: u64,
+    pub block_insert: u64,
+    pub analytics: u64,
+    pub checkpoint: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionWritesDto {
+    pub name: String,
+    pub written: u64,
+    pub duplicates: u64,
+}
+
+impl From<CollectionWrites> for CollectionWritesDto {
+    fn from(value: CollectionWrites) -> Self {
+        Self {
+            name: value.name,
+            written: value.written,
+            duplicates: value.duplicates,
+        }
+    }
+}
+
+impl From<SyncReport> for SyncReportResponse {
+    fn from(value: SyncReport) -> Self {
+        Self {
+            milestone_index: value.milestone_index,
+            milestone_timestamp: value.milestone_timestamp,
+            synced_at: value.synced_at.timestamp_millis(),
+            catching_up: value.catching_up,
+            phases: SyncReportPhasesDto {
+                receive: value.phases.receive,
+                output_insert: value.phases.output_insert,
+                cone_receive: value.phases.cone_receive,
+                block_insert: value.phases.block_insert,
+                analytics: value.phases.analytics,
+                checkpoint: value.phases.checkpoint,
+            },
+            total_time: value.phases.total(),
+            collections: value.collections.into_iter().map(Into::into).collect(),
+            checkpoint_attempts: value.checkpoint_attempts,
+            warnings: value.warnings,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeHealthResponse {
+    pub items: Vec<NodeHealthSampleDto>,
+}
+
+impl_success_response!(NodeHealthResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeHealthSampleDto {
+    /// The unix time in seconds at which the sample was taken.
+    pub sampled_at: i64,
+    pub is_healthy: bool,
+    pub is_synced: bool,
+    pub is_almost_synced: bool,
+    pub latest_milestone_index: MilestoneIndex,
+    pub confirmed_milestone_index: MilestoneIndex,
+    pub synced_milestone_index: Option<MilestoneIndex>,
+    /// The number of confirmed milestones Chronicle was behind the node.
+    pub sync_lag: Option<u32>,
+    pub latency_ms: u64,
+    pub non_lazy_tips: Option<u32>,
+    pub semi_lazy_tips: Option<u32>,
+    pub selected_tips: Option<u32>,
+}
+
+impl From<NodeHealthSample> for NodeHealthSampleDto {
+    fn from(value: NodeHealthSample) -> Self {
+        Self {
+            sampled_at: value.sampled_at.timestamp_millis() / 1000,
+            is_healthy: value.is_healthy,
+            is_synced: value.is_synced,
+            is_almost_synced: value.is_almost_synced,
+            latest_milestone_index: value.latest_milestone_index,
+            confirmed_milestone_index: value.confirmed_milestone_index,
+            synced_milestone_index: value.synced_index,
+            sync_lag: value.sync_lag(),
+            latency_ms: value.latency,
+            non_lazy_tips: value.non_lazy_tips,
+            semi_lazy_tips: value.semi_lazy_tips,
+            selected_tips: value.selected_tips,
+        }
+    }
+}
+
+/// The sync progress in the Prometheus text exposition format.
+pub struct SyncProgressMetrics(ProgressSnapshot);
+
+impl SyncProgressMetrics {
+    fn render(&self) -> String {
+        fn gauge(out: &mut String, name: &str, help: &str, values: &[(Option<&str>, u64)]) {
+            // Writing to a `String` cannot fail.
+            writeln!(out, "# HELP {name} {help}").unwrap();
+            writeln!(out, "# TYPE {name} gauge").unwrap();
+            for (labels, value) in values {
+                match labels {
+                    Some(labels) => writeln!(out, "{name}{{{labels}}} {value}").unwrap(),
+                    None => writeln!(out, "{name} {value}").unwrap(),
+                }
+            }
+        }
+
+        let snapshot = &self.0;
+        let mut out = String::new();
+        let phases = SyncPhase::ALL
+            .iter()
+            .map(|phase| {
+                (
+                    format!("phase=\"{}\"", phase.as_str()),
+                    (*phase == snapshot.phase) as u64,
+                )
+            })
+            .collect::<Vec<_>>();
+        gauge(
+            &mut out,
+            "chronicle_sync_phase",
+            "Whether Chronicle is in the given sync phase.",
+            &phases
+                .iter()
+                .map(|(labels, value)| (Some(labels.as_str()), *value))
+                .collect::<Vec<_>>(),
+        );
+        gauge(
+            &mut out,
+            "chronicle_sync_outputs_imported",
+            "The number of unspent outputs imported during the initial bootstrap.",
+            &[(None, snapshot.outputs_imported)],
+        );
+        // Unknown values are left out rather than reported as zero, which would be indistinguishable from progress.
+        if let Some(index) = snapshot.synced_index {
+            gauge(
+                &mut out,
+                "chronicle_sync_synced_milestone_index",
+                "The newest milestone synced into the database.",
+                &[(None, index.0 as u64)],
+            );
+        }
+        if let Some(index) = snapshot.target_index {
+            gauge(
+                &mut out,
+                "chronicle_sync_target_milestone_index",
+                "The latest confirmed milestone of the node.",
+                &[(None, index.0 as u64)],
+            );
+        }
+        if let Some(behind) = snapshot.milestones_behind {
+            gauge(
+                &mut out,
+                "chronicle_sync_milestones_behind",
+                "The number of milestones the database is behind the node.",
+                &[(None, behind as u64)],
+            );
+        }
+        if let Some(eta) = snapshot.eta {
+            gauge(
+                &mut out,
+                "chronicle_sync_eta_seconds",
+                "The estimated time until the database has caught up with the node.",
+                &[(None, eta.as_secs())],
+            );
+        }
+        out
+    }
+}
+
+impl IntoResponse for SyncProgressMetrics {
+    fn into_response(self) -> Response {
+        ([(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], self.render()).into_response()
+    }
+}
+
+pub async fn progress(Extension(progress): Extension<Option<SyncProgress>>) -> ApiResult<SyncProgressResponse> {
+    let progress = progress.ok_or(UnimplementedError)?;
+    Ok(progress.snapshot().into())
+}
+
+pub async fn progress_metrics(Extension(progress): Extension<Option<SyncProgress>>) -> ApiResult<SyncProgressMetrics> {
+    let progress = progress.ok_or(UnimplementedError)?;
+    Ok(SyncProgressMetrics(progress.snapshot()))
+}
+
+/// Reports the values received from INX that are newer than the schema Chronicle was built against.
+pub async fn schema_drift(_: Admin) -> ApiResult<SchemaDriftResponse> {
+    Ok(SchemaDriftResponse {
+        unknown_enum_values: unknown_enum_values().into_iter().map(Into::into).collect(),
+    })
+}
+
+/// Reports how a recently synced milestone was synced, to find out why syncing it took long.
+pub async fn sync_report(
+    _: Admin,
+    database: Extension<MongoDb>,
+    Path(milestone_index): Path<MilestoneIndex>,
+) -> ApiResult<SyncReportResponse> {
+    Ok(database
+        .collection::<SyncReportCollection>()
+        .get_report(milestone_index)
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .into())
+}
+
+/// Reports the sampled status of the node over a time range, which defaults to the last day.
+pub async fn node_health(
+    _: Admin,
+    database: Extension<MongoDb>,
+    TimeRange {
+        start_timestamp,
+        end_timestamp,
+    }: TimeRange,
+) -> ApiResult<NodeHealthResponse> {
+    let end = end_timestamp.map_or_else(DateTime::now, |end| DateTime::from_millis(end.0 as i64 * 1000));
+    let start = start_timestamp.map_or_else(
+        || {
+            DateTime::from_millis(
+                end.timestamp_millis()
+                    .saturating_sub(DEFAULT_NODE_HEALTH_WINDOW as i64 * 1000),
+            )
+        },
+        |start| DateTime::from_millis(start.0 as i64 * 1000),
+    );
+    let items = database
+        .collection::<NodeHealthCollection>()
+        .get_samples(start, end, MAX_NODE_HEALTH_SAMPLES)
+        .await?
+        .map_ok(Into::into)
+        .try_collect()
+        .await?;
+    Ok(NodeHealthResponse { items })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn prometheus_text() {
+        let metrics = SyncProgressMetrics(ProgressSnapshot {
+            phase: SyncPhase::CatchingUp,
+            outputs_imported: 1500,
+            synced_index: Some(10.into()),
+            target_index: Some(20.into()),
+            milestones_behind: Some(10),
+            eta: Some(Duration::from_millis(42_500)),
+        });
+        let text = metrics.render();
+        assert!(text.contains("# TYPE chronicle_sync_phase gauge\n"));
+        assert!(text.contains("chronicle_sync_phase{phase=\"starting\"} 0\n"));
+        assert!(text.contains("chronicle_sync_phase{phase=\"catching_up\"} 1\n"));
+        assert!(text.contains("chronicle_sync_outputs_imported 1500\n"));
+        assert!(text.contains("chronicle_sync_milestones_behind 10\n"));
+        assert!(text.contains("chronicle_sync_eta_seconds 42\n"));
+
+        let metrics = SyncProgressMetrics(ProgressSnapshot {
+            phase: SyncPhase::Starting,
+            outputs_imported: 0,
+            synced_index: None,
+            target_index: None,
+            milestones_behind: None,
+            eta: None,
+        });
+        assert_eq!(metrics.render().matches("# TYPE").count(), 2);
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{
+    body::{boxed, Full},
+    extract::OriginalUri,
+    http::{
+        header::{HeaderName, CONTENT_TYPE},
+        HeaderValue, Method, Request, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chronicle::db::{
+    mongodb::collections::{MilestoneCollection, QueryCacheCollection},
+    MongoDb,
+};
+use tracing::warn;
+
+use super::config::ApiConfigData;
+
+/// The header that tells whether a response was served from the query cache.
+const CACHE_HEADER: HeaderName = HeaderName::from_static("x-query-cache");
+
+/// Serves the results of expensive queries from the [`QueryCacheCollection`], if the query cache is enabled.
+///
+/// A result is cached per route, parameters, and ledger index, so it is computed again once the ledger index advances.
+/// Errors reading the cache are logged, and the request is then handled without it.
+/// Only successful JSON responses are cached, and storing them does not hold up the response. A standby only serves
+/// cached results until it is promoted.
+pub async fn cached<B>(req: Request<B>, next: Next<B>) -> Response {
+    let ttl = req
+        .extensions()
+        .get::<ApiConfigData>()
+        .and_then(|config| config.query_cache_ttl);
+    let (ttl, db) = match (ttl, req.extensions().get::<MongoDb>().cloned()) {
+        (Some(ttl), Some(db)) => (ttl, db),
+        _ => return next.run(req).await,
+    };
+    if req.method() != Method::GET {
+        return next.run(req).await;
+    }
+    // The cache only speeds up the queries, so the request is handled as usual if it cannot be read.
+    let ledger_index = match db.collection::<MilestoneCollection>().get_ledger_index().await {
+        Ok(Some(ledger_index)) => ledger_index,
+        Ok(None) => return next.run(req).await,
+        Err(e) => {
+            warn!("Failed to read the ledger index for the query cache: {e}");
+            return next.run(req).await;
+        }
+    };
+    let uri = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| req.uri().clone(), |uri| uri.0.clone());
+    let key = cache_key(uri.path(), uri.query());
+    #[cfg(feature = "inx")]
+    let standby = req.extensions().get::<Option<crate::inx::Standby>>().cloned().flatten();
+
+    match db
+        .collection::<QueryCacheCollection>()
+        .get_result(&key, ledger_index)
+        .await
+    {
+        Ok(Some(result)) => {
+            return (
+                [
+                    (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+                    (CACHE_HEADER, HeaderValue::from_static("hit")),
+                ],
+                result,
+            )
+                .into_response();
+        }
+        Ok(None) => (),
+        Err(e) => warn!("Failed to read the query cache: {e}"),
+    }
+
+    let res = next.run(req).await;
+    let is_json = res
+        .headers()
+        .get(CONTENT_TYPE)
+        .map_or(false, |value| value.as_bytes().starts_with(b"application/json"));
+    if res.status() != StatusCode::OK || !is_json {
+        return res;
+    }
+    // The handler reads the ledger index itself. The result is only cached if the ledger index did not advance while
+    // it ran, because only then is it known which ledger index the result reflects.
+    match db.collection::<MilestoneCollection>().get_ledger_index().await {
+        Ok(Some(handler_ledger_index)) if handler_ledger_index == ledger_index => (),
+        _ => return res,
+    }
+    let (mut parts, body) = res.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to read a response to cache: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    // A standby must not write to the database until it is promoted.
+    #[cfg(feature = "inx")]
+    let may_write = standby.map_or(true, |standby| standby.is_promoted());
+    #[cfg(not(feature = "inx"))]
+    let may_write = true;
+    if let Some(result) = String::from_utf8(bytes.to_vec()).ok().filter(|_| may_write) {
+        tokio::spawn(async move {
+            if let Err(e) = db
+                .collection::<QueryCacheCollection>()
+                .upsert_result(&key, ledger_index, result, ttl)
+                .await
+            {
+                warn!("Failed to cache the result of `{key}`: {e}");
+            }
+        });
+    }
+    parts.headers.insert(CACHE_HEADER, HeaderValue::from_static("miss"));
+    Response::from_parts(parts, boxed(Full::from(bytes)))
+}
+
+/// Builds the key of a query from its path and parameters. The parameters are sorted, so that their order does not
+/// matter.
+fn cache_key(path: &str, query: Option<&str>) -> String {
+    let mut params = query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .collect::<Vec<_>>();
+    params.sort_unstable();
+    if params.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{}", params.join("&"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn cache_key_ignores_param_order() {
+        let path = "/api/explorer/v2/ledger/richest-addresses";
+        assert_eq!(cache_key(path, None), path);
+        assert_eq!(cache_key(path, Some("")), path);
+        assert_eq!(
+            cache_key(path, Some("top=10&ledgerIndex=5")),
+            cache_key(path, Some("ledgerIndex=5&top=10"))
+        );
+        assert_eq!(
+            cache_key(path, Some("top=10&&ledgerIndex=5")),
+            format!("{path}?ledgerIndex=5&top=10")
+        );
+        assert_ne!(cache_key(path, Some("top=10")), cache_key(path, Some("top=20")));
+    }
+}
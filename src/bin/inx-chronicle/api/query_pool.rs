@@ -0,0 +1,84 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use axum::{
+    http::{header::RETRY_AFTER, HeaderValue, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+
+use super::error::{ApiError, SaturatedError};
+
+/// The number of seconds after which a client may retry a rejected request, announced in the `Retry-After` header.
+const RETRY_AFTER_SECS: u32 = 1;
+
+/// Limits how many expensive queries run at once, so that a traffic spike queues up in the API instead of
+/// overloading the database.
+#[derive(Clone, Debug)]
+pub struct QueryPool {
+    /// The permits for running a query.
+    running: Arc<Semaphore>,
+    /// The permits for running a query or waiting for one.
+    admitted: Arc<Semaphore>,
+}
+
+impl QueryPool {
+    pub fn new(concurrency: usize, queue_length: usize) -> Self {
+        Self {
+            running: Arc::new(Semaphore::new(concurrency)),
+            admitted: Arc::new(Semaphore::new(concurrency + queue_length)),
+        }
+    }
+}
+
+/// Runs the request in the [`QueryPool`], or rejects it with `429 Too Many Requests` if the queue is full.
+pub async fn limit<B>(req: Request<B>, next: Next<B>) -> Response {
+    let pool = match req.extensions().get::<QueryPool>().cloned() {
+        Some(pool) => pool,
+        None => return next.run(req).await,
+    };
+    let _admitted = match pool.admitted.try_acquire_owned() {
+        Ok(permit) => permit,
+        Err(_) => {
+            let mut res = ApiError::from(SaturatedError).into_response();
+            res.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+            return res;
+        }
+    };
+    // Unwrap: the semaphore is never closed.
+    let _running = pool.running.acquire_owned().await.unwrap();
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, middleware::from_fn, routing::get, Extension, Router};
+    use hyper::StatusCode;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn rejects_when_saturated() {
+        let pool = QueryPool::new(1, 1);
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route_layer(from_fn(limit))
+            .layer(Extension(pool.clone()));
+        let request = || Request::builder().uri("/").body(Body::empty()).unwrap();
+
+        assert_eq!(app.clone().oneshot(request()).await.unwrap().status(), StatusCode::OK);
+
+        // One query runs and one waits, so the next one is rejected.
+        let _running = pool.running.clone().try_acquire_owned().unwrap();
+        let _waiting = pool.admitted.clone().try_acquire_many_owned(2).unwrap();
+        let res = app.oneshot(request()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(res.headers()[RETRY_AFTER], "1");
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use chronicle::model::tangle::MilestoneIndex;
 use serde::{Deserialize, Serialize};
 
 macro_rules! impl_success_response {
@@ -24,3 +25,19 @@ pub struct RoutesResponse {
 }
 
 impl_success_response!(RoutesResponse);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoResponse {
+    pub name: String,
+    pub version: String,
+    /// The id of the database, which is shared by all instances that were restored from the same database.
+    pub database_id: Option<String>,
+    /// The id of this process.
+    pub instance_id: Option<String>,
+    /// The id of the process that last took over writing to the database.
+    pub writer_instance_id: Option<String>,
+    pub ledger_index: Option<MilestoneIndex>,
+}
+
+impl_success_response!(InfoResponse);
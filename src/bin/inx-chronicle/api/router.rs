@@ -192,6 +192,12 @@ where
     pub fn into_make_service(self) -> IntoMakeService<axum::Router<B>> {
         self.inner.layer(Extension(self.root)).into_make_service()
     }
+
+    /// The routes that were added so far.
+    #[cfg(test)]
+    pub fn route_node(&self) -> &RouteNode {
+        &self.root
+    }
 }
 
 impl<B> Service<Request<B>> for Router<B>
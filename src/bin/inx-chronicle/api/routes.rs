@@ -5,13 +5,16 @@ use auth_helper::jwt::{BuildValidation, Claims, JsonWebToken, Validation};
 use axum::{
     handler::Handler,
     headers::{authorization::Bearer, Authorization},
-    http::HeaderValue,
+    http::{header::ACCEPT, HeaderMap, HeaderValue},
     middleware::from_extractor,
     routing::{get, post},
     Extension, Json, TypedHeader,
 };
 use chronicle::{
-    db::{mongodb::collections::MilestoneCollection, MongoDb},
+    db::{
+        mongodb::collections::{ApplicationStateCollection, MilestoneCollection},
+        MongoDb,
+    },
     model::tangle::MilestoneTimestamp,
 };
 use hyper::StatusCode;
@@ -24,14 +27,45 @@ use super::{
     config::ApiConfigData,
     error::{ApiError, MissingError, UnimplementedError},
     extractors::ListRoutesQuery,
-    responses::RoutesResponse,
+    responses::{InfoResponse, RoutesResponse},
     router::{RouteNode, Router},
     ApiResult, AuthError,
 };
 
 pub(crate) static BYTE_CONTENT_HEADER: HeaderValue = HeaderValue::from_static("application/vnd.iota.serializer-v1");
 
-const ALWAYS_AVAILABLE_ROUTES: &[&str] = &["/health", "/login", "/routes"];
+/// The path that all API routes are nested at.
+pub const API_ROOT: &str = "/api";
+
+/// Whether a request accepts the raw bytes of a response, in which case they are served instead of JSON. The `Accept`
+/// header may list several media types, of which the raw one must not be excluded with `q=0`.
+pub(crate) fn accepts_raw(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_range| {
+            let mut parts = media_range.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default();
+            let excluded = parts.any(|param| match param.split_once('=') {
+                Some((name, q)) => name.trim() == "q" && q.trim().parse::<f32>() == Ok(0.0),
+                None => false,
+            });
+            media_type
+                .as_bytes()
+                .eq_ignore_ascii_case(BYTE_CONTENT_HEADER.as_bytes())
+                && !excluded
+        })
+}
+
+const ALWAYS_AVAILABLE_ROUTES: &[&str] = &[
+    "/health",
+    "/login",
+    "/routes",
+    #[cfg(feature = "inx")]
+    "/standby",
+];
 
 // Similar to Hornet, we enforce that the latest known milestone is newer than 5 minutes. This should give Chronicle
 // sufficient time to catch up with the node that it is connected too. The current milestone interval is 5 seconds.
@@ -41,22 +75,80 @@ pub fn routes() -> Router {
     #[allow(unused_mut)]
     let mut router = Router::new()
         .nest("/core/v2", super::core::routes())
-        .nest("/explorer/v2", super::explorer::routes())
-        .nest("/indexer/v1", super::indexer::routes());
+        .nest("/indexer/v1", super::indexer::routes())
+        .route("/info", get(info));
+
+    #[cfg(feature = "explorer")]
+    {
+        use axum::middleware::from_fn;
+
+        use super::versioning::{self, ApiVersion};
+
+        for version in ApiVersion::ALL {
+            router = router.nest(
+                &version.explorer_prefix(),
+                super::explorer::routes()
+                    .route_layer(from_fn(versioning::deprecation))
+                    .layer(Extension(version)),
+            );
+        }
+    }
 
     #[cfg(feature = "poi")]
     {
         router = router.nest("/poi/v1", super::poi::routes());
     }
 
-    Router::new()
+    #[cfg(feature = "graphql")]
+    {
+        router = router.nest("/graphql/v1", super::graphql::routes());
+    }
+
+    #[allow(unused_mut)]
+    let mut root = Router::new();
+
+    #[cfg(feature = "inx")]
+    {
+        // Like the API, the progress is only public if its routes are configured to be.
+        root = root
+            .route("/progress", get(super::progress::progress))
+            .route("/progress/metrics", get(super::progress::progress_metrics))
+            .route("/progress/schema-drift", get(super::progress::schema_drift))
+            .route("/progress/reports/:milestone_index", get(super::progress::sync_report))
+            .route("/progress/node-health", get(super::progress::node_health))
+            .route_layer(from_extractor::<Auth>())
+            .route("/standby", get(super::standby::standby))
+            .route("/standby/promote", post(super::standby::promote));
+    }
+
+    let root = root
         .route("/health", get(health))
         .route("/login", post(login))
         .route("/routes", get(list_routes))
-        .nest("/api", router.route_layer(from_extractor::<Auth>()))
+        .route(
+            "/log-filter",
+            get(super::logging::log_filter).post(super::logging::set_log_filter),
+        );
+
+    root.nest(API_ROOT, router.route_layer(from_extractor::<Auth>()))
         .fallback(not_found.into_service())
 }
 
+async fn info(
+    database: Extension<MongoDb>,
+    Extension(instance_id): Extension<Option<super::InstanceId>>,
+) -> ApiResult<InfoResponse> {
+    let app_state = database.collection::<ApplicationStateCollection>();
+    Ok(InfoResponse {
+        name: chronicle::CHRONICLE_APP_NAME.to_string(),
+        version: std::env!("CARGO_PKG_VERSION").to_string(),
+        database_id: app_state.get_database_id().await?,
+        instance_id: instance_id.map(|id| id.0),
+        writer_instance_id: app_state.get_writer_instance_id().await?,
+        ledger_index: database.collection::<MilestoneCollection>().get_ledger_index().await?,
+    })
+}
+
 #[derive(Deserialize)]
 struct LoginInfo {
     password: String,
@@ -176,3 +268,40 @@ pub async fn not_found() -> MissingError {
 pub async fn not_implemented() -> UnimplementedError {
     UnimplementedError
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn accepts(values: &[&'static str]) -> bool {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(ACCEPT, HeaderValue::from_static(value));
+        }
+        accepts_raw(&headers)
+    }
+
+    #[test]
+    fn raw_content_negotiation() {
+        assert!(accepts(&["application/vnd.iota.serializer-v1"]));
+        assert!(accepts(&["application/json, Application/VND.iota.serializer-v1;q=0.9"]));
+        assert!(accepts(&["application/json", "application/vnd.iota.serializer-v1"]));
+        assert!(!accepts(&[]));
+        assert!(!accepts(&["application/json"]));
+        assert!(!accepts(&["*/*"]));
+        assert!(!accepts(&["application/vnd.iota.serializer-v1; q=0"]));
+        assert!(!accepts(&["application/vnd.iota.serializer-v2"]));
+    }
+
+    #[test]
+    fn routes_follow_features() {
+        let routes = routes().route_node().list_routes(None, Some(2));
+        let has = |prefix: &str| routes.iter().any(|route| route == prefix);
+        assert!(has("api/core"));
+        assert!(has("api/indexer"));
+        assert_eq!(has("api/explorer"), cfg!(feature = "explorer"));
+        assert_eq!(has("api/poi"), cfg!(feature = "poi"));
+        assert_eq!(has("api/graphql"), cfg!(feature = "graphql"));
+        assert_eq!(has("progress"), cfg!(feature = "inx"));
+    }
+}
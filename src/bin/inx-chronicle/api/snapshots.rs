@@ -0,0 +1,403 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Golden response snapshots for the API. The tests seed a database with the milestones in
+//! `tests/data/in_memory_data.json`, request every route that can be derived from that data, and compare the status
+//! and JSON body of each response with the snapshot that is stored in `tests/data/api_snapshots`.
+//!
+//! The tests need a running MongoDB instance, so they are ignored by default and run with `cargo ci-test-api`.
+//! Missing or changed snapshots fail the test, unless `UPDATE_SNAPSHOTS=1` is set, in which case the snapshots are
+//! (re)recorded and should be reviewed like any other change.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use auth_helper::jwt::{Claims, JsonWebToken};
+use chronicle::{
+    db::{
+        mongodb::collections::{
+            BlockCollection, BlockDocument, ConfigurationUpdateCollection, LedgerUpdateCollection, MilestoneCollection,
+            MilestoneDataSize, OutputCollection, ParentsCollection, ProtocolUpdateCollection,
+        },
+        MongoDb, MongoDbConfig,
+    },
+    model::{
+        block::Block,
+        fixtures::{load_fixtures, Fixture},
+        metadata::LedgerInclusionState,
+        payload::Payload,
+        utxo::{AliasId, NftId, Output},
+    },
+};
+use hyper::{Body, Request, StatusCode};
+use iota_sdk::types::block::address::ToBech32Ext;
+use packable::PackableExt;
+use serde_json::Value;
+use tower::Service;
+
+use super::{ApiConfig, ApiConfigData, ApiWorker};
+
+const DATABASE_NAME: &str = "chronicle_test_api_snapshots";
+
+/// Fields whose values differ between runs, and are therefore replaced before comparing.
+const REDACTED_FIELDS: &[&str] = &["databaseId"];
+
+async fn setup_database() -> eyre::Result<MongoDb> {
+    dotenvy::dotenv().ok();
+
+    let mut config = MongoDbConfig {
+        database_name: DATABASE_NAME.to_string(),
+        ..Default::default()
+    };
+    if let Ok(conn_str) = std::env::var("MONGODB_CONN_STR") {
+        config.conn_str = conn_str;
+    };
+
+    let db = MongoDb::connect(&config).await?;
+    db.clear().await?;
+    crate::build_indexes(&db).await?;
+    Ok(db)
+}
+
+/// Writes the fixtures the same way the INX worker writes a milestone.
+async fn seed(db: &MongoDb, fixtures: &BTreeMap<String, Fixture>) -> eyre::Result<()> {
+    for fixture in fixtures.values() {
+        let milestone = &fixture.milestone_data;
+        let milestone_index = milestone.at.milestone_index;
+
+        db.collection::<OutputCollection>()
+            .insert_unspent_outputs(&fixture.created)
+            .await?;
+        db.collection::<LedgerUpdateCollection>()
+            .insert_unspent_ledger_updates(&fixture.created)
+            .await?;
+        db.collection::<OutputCollection>()
+            .update_spent_outputs(&fixture.consumed)
+            .await?;
+        db.collection::<LedgerUpdateCollection>()
+            .insert_spent_ledger_updates(&fixture.consumed)
+            .await?;
+
+        let blocks = fixture
+            .cone
+            .values()
+            .map(|data| {
+                let block = Block::from(iota_sdk::types::block::Block::unpack_unverified(data.raw.clone()).unwrap());
+                (data.block_id, block, data.raw.clone(), data.metadata.clone())
+            })
+            .collect::<Vec<_>>();
+        db.collection::<ParentsCollection>()
+            .insert_relationships(blocks.iter().map(|(block_id, _, _, metadata)| (*block_id, metadata)))
+            .await?;
+        db.collection::<BlockCollection>()
+            .insert_blocks_with_metadata(blocks.into_iter().map(BlockDocument::from))
+            .await?;
+
+        db.collection::<ProtocolUpdateCollection>()
+            .upsert_protocol_parameters(milestone_index, milestone.protocol_params.clone())
+            .await?;
+        db.collection::<ConfigurationUpdateCollection>()
+            .upsert_node_configuration(milestone_index, milestone.node_config.clone())
+            .await?;
+        db.collection::<MilestoneCollection>()
+            .insert_milestone(
+                milestone.milestone_id,
+                milestone_index,
+                milestone.at.milestone_timestamp,
+                milestone.payload.clone(),
+                MilestoneDataSize::default(),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Builds the requested paths from the ids that are found in the fixtures, keyed by the name of their snapshot.
+fn cases(fixtures: &BTreeMap<String, Fixture>) -> BTreeMap<String, String> {
+    let (first, last) = (
+        fixtures.values().next().unwrap(),
+        fixtures.values().next_back().unwrap(),
+    );
+    let hrp = first.milestone_data.protocol_params.bech32_hrp.parse().unwrap();
+    let milestone_id = last.milestone_data.milestone_id.to_hex();
+    let milestone_index = last.milestone_data.at.milestone_index;
+    let milestone_timestamp = last.milestone_data.at.milestone_timestamp.0;
+
+    let (block_id, transaction_id) = fixtures
+        .values()
+        .flat_map(|fixture| fixture.cone.values())
+        .find_map(|data| {
+            if data.metadata.inclusion_state != LedgerInclusionState::Included {
+                return None;
+            }
+            let block = iota_sdk::types::block::Block::unpack_unverified(data.raw.clone()).unwrap();
+            match Block::from(block).payload {
+                Some(Payload::Transaction(payload)) => Some((data.block_id.to_hex(), payload.transaction_id.to_hex())),
+                _ => None,
+            }
+        })
+        .unwrap();
+    let parent_id = first.cone.values().next().unwrap().block_id.to_hex();
+
+    let created = fixtures
+        .values()
+        .flat_map(|fixture| &fixture.created)
+        .collect::<Vec<_>>();
+    let output_id = created[0].output_id.to_hex();
+    let address = iota_sdk::types::block::address::Address::from(*created[0].owning_address().unwrap())
+        .to_bech32(hrp)
+        .to_string();
+    let alias_id = created
+        .iter()
+        .find_map(|output| match &output.output {
+            Output::Alias(alias) if alias.alias_id == AliasId::implicit() => Some(AliasId::from(output.output_id)),
+            Output::Alias(alias) => Some(alias.alias_id),
+            _ => None,
+        })
+        .map(|alias_id| iota_sdk::types::block::output::AliasId::from(alias_id).to_string())
+        .unwrap();
+    let nft_id = created
+        .iter()
+        .find_map(|output| match &output.output {
+            Output::Nft(nft) if nft.nft_id == NftId::implicit() => Some(NftId::from(output.output_id)),
+            Output::Nft(nft) => Some(nft.nft_id),
+            _ => None,
+        })
+        .map(|nft_id| iota_sdk::types::block::output::NftId::from(nft_id).to_string())
+        .unwrap();
+    let prefix = &output_id[..4];
+
+    let mut cases = BTreeMap::new();
+    let mut case = |name: &str, path: String| {
+        assert!(cases.insert(name.to_string(), path).is_none(), "duplicate case {name}");
+    };
+
+    case("info", "/api/info".to_string());
+
+    let core = "/api/core/v2";
+    case("core_info", format!("{core}/info"));
+    case("core_block", format!("{core}/blocks/{block_id}"));
+    case("core_block_metadata", format!("{core}/blocks/{block_id}/metadata"));
+    case("core_output", format!("{core}/outputs/{output_id}"));
+    case("core_output_metadata", format!("{core}/outputs/{output_id}/metadata"));
+    case("core_receipts", format!("{core}/receipts"));
+    case("core_treasury", format!("{core}/treasury"));
+    case(
+        "core_included_block",
+        format!("{core}/transactions/{transaction_id}/included-block"),
+    );
+    case(
+        "core_included_block_metadata",
+        format!("{core}/transactions/{transaction_id}/included-block/metadata"),
+    );
+    case("core_milestone", format!("{core}/milestones/{milestone_id}"));
+    case(
+        "core_milestone_utxo_changes",
+        format!("{core}/milestones/{milestone_id}/utxo-changes"),
+    );
+    case(
+        "core_milestone_by_index",
+        format!("{core}/milestones/by-index/{milestone_index}"),
+    );
+    case(
+        "core_milestone_by_index_utxo_changes",
+        format!("{core}/milestones/by-index/{milestone_index}/utxo-changes"),
+    );
+
+    let indexer = "/api/indexer/v1/outputs";
+    case("indexer_outputs", indexer.to_string());
+    case("indexer_basic", format!("{indexer}/basic?address={address}"));
+    case("indexer_aliases", format!("{indexer}/alias"));
+    case("indexer_alias", format!("{indexer}/alias/{alias_id}"));
+    case("indexer_foundries", format!("{indexer}/foundry"));
+    case("indexer_nfts", format!("{indexer}/nft"));
+    case("indexer_nft", format!("{indexer}/nft/{nft_id}"));
+    case(
+        "indexer_storage_deposit_return",
+        format!("{indexer}/storage-deposit-return/{address}"),
+    );
+
+    let explorer = "/api/explorer/v3";
+    case("explorer_balance", format!("{explorer}/balance/{address}"));
+    case(
+        "explorer_block_children",
+        format!("{explorer}/blocks/{parent_id}/children"),
+    );
+    case(
+        "explorer_decoded_transaction",
+        format!("{explorer}/transactions/{transaction_id}/decoded"),
+    );
+    case("explorer_milestones", format!("{explorer}/milestones"));
+    case(
+        "explorer_milestone_convert",
+        format!("{explorer}/milestones/convert?timestamp={milestone_timestamp}"),
+    );
+    case(
+        "explorer_milestone_blocks",
+        format!("{explorer}/milestones/{milestone_id}/blocks"),
+    );
+    case(
+        "explorer_milestone_blocks_by_index",
+        format!("{explorer}/milestones/by-index/{milestone_index}/blocks"),
+    );
+    case(
+        "explorer_richest_addresses",
+        format!("{explorer}/ledger/richest-addresses"),
+    );
+    case(
+        "explorer_token_distribution",
+        format!("{explorer}/ledger/token-distribution"),
+    );
+    case(
+        "explorer_timelocked_value",
+        format!("{explorer}/ledger/timelocked-value"),
+    );
+    case("explorer_transfer_graph", format!("{explorer}/ledger/graph/{address}"));
+    case(
+        "explorer_entity_relationships",
+        format!("{explorer}/ledger/relationships/{address}"),
+    );
+    case("explorer_transfer_volume", format!("{explorer}/ledger/transfer-volume"));
+    case(
+        "explorer_active_addresses",
+        format!("{explorer}/ledger/active-addresses"),
+    );
+    case("explorer_nft_sales", format!("{explorer}/ledger/nft-sales"));
+    case(
+        "explorer_address_activity",
+        format!("{explorer}/ledger/activity/by-address/{address}"),
+    );
+    case("explorer_ledger_snapshots", format!("{explorer}/ledger/snapshots"));
+    case(
+        "explorer_unspent_outputs_by_prefix",
+        format!("{explorer}/ledger/unspent-outputs/by-prefix/{prefix}"),
+    );
+    case(
+        "explorer_unspent_outputs_by_prefix_digest",
+        format!("{explorer}/ledger/unspent-outputs/by-prefix/{prefix}/digest"),
+    );
+    case(
+        "explorer_ledger_updates_by_address",
+        format!("{explorer}/ledger/updates/by-address/{address}"),
+    );
+    case(
+        "explorer_ledger_updates_by_milestone",
+        format!("{explorer}/ledger/updates/by-milestone/{milestone_id}"),
+    );
+    case(
+        "explorer_alias_history",
+        format!("{explorer}/aliases/{alias_id}/history"),
+    );
+    case("explorer_nft_history", format!("{explorer}/nfts/{nft_id}/history"));
+    case("explorer_views", format!("{explorer}/views"));
+    case(
+        "explorer_pending_transactions",
+        format!("{explorer}/pending-transactions"),
+    );
+    case("explorer_data_size", format!("{explorer}/storage/data-size"));
+    // The previous version of the explorer API shares the handlers, so a single route is enough to cover it.
+    case("explorer_v2_milestones", "/api/explorer/v2/milestones".to_string());
+
+    #[cfg(feature = "poi")]
+    case(
+        "poi_referenced_block",
+        format!("/api/poi/v1/referenced-block/create/{block_id}"),
+    );
+
+    cases
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) {
+                    *value = Value::String("[redacted]".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact),
+        _ => (),
+    }
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/api_snapshots")).join(format!("{name}.json"))
+}
+
+#[tokio::test]
+#[ignore = "requires a MongoDB instance"]
+async fn test_api_snapshots() -> eyre::Result<()> {
+    let db = setup_database().await?;
+    let fixtures = load_fixtures();
+    seed(&db, &fixtures).await?;
+
+    let worker = ApiWorker::new(db.clone(), ApiConfig::default())?;
+    let jwt = {
+        let ApiConfigData {
+            jwt_secret_key,
+            jwt_expiration,
+            ..
+        } = &worker.api_data;
+        JsonWebToken::new(
+            Claims::new(ApiConfigData::ISSUER, "snapshots", ApiConfigData::AUDIENCE)?
+                .expires_after_duration(*jwt_expiration)?,
+            jwt_secret_key.as_ref(),
+        )?
+    };
+    let mut routes = worker.routes();
+
+    let update = std::env::var("UPDATE_SNAPSHOTS").map_or(false, |v| v == "1");
+    std::fs::create_dir_all(snapshot_path("").parent().unwrap())?;
+    let (mut missing, mut mismatches) = (BTreeSet::new(), BTreeSet::new());
+    for (name, path) in cases(&fixtures) {
+        let request = Request::get(&path)
+            .header(hyper::header::AUTHORIZATION, format!("Bearer {jwt}"))
+            .body(Body::empty())?;
+        let response = routes.call(request).await?;
+        let status = response.status();
+        let bytes = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|e| eyre::eyre!("failed to read the response body of {path}: {e}"))?;
+        let mut body = serde_json::from_slice::<Value>(&bytes).unwrap_or_else(|_| {
+            assert_eq!(status, StatusCode::NO_CONTENT, "{path} did not respond with JSON");
+            Value::Null
+        });
+        redact(&mut body);
+        let actual = serde_json::json!({ "path": path, "status": status.as_u16(), "body": body });
+
+        let file = snapshot_path(&name);
+        match std::fs::read(&file) {
+            _ if update => std::fs::write(&file, serde_json::to_string_pretty(&actual)? + "\n")?,
+            Ok(expected) => {
+                let expected = serde_json::from_slice::<Value>(&expected)?;
+                if expected != actual {
+                    eprintln!(
+                        "snapshot `{name}` changed:\n{}",
+                        pretty_assertions::Comparison::new(&expected, &actual)
+                    );
+                    mismatches.insert(name);
+                }
+            }
+            Err(_) => {
+                missing.insert(name);
+            }
+        }
+    }
+
+    db.drop().await?;
+
+    assert!(
+        missing.is_empty(),
+        "no snapshots are recorded for {missing:?}, rerun with `UPDATE_SNAPSHOTS=1` to record them"
+    );
+    assert!(
+        mismatches.is_empty(),
+        "the responses of {mismatches:?} differ from their snapshots, rerun with `UPDATE_SNAPSHOTS=1` if this is intended"
+    );
+    Ok(())
+}
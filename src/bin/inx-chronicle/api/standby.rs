@@ -0,0 +1,32 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::Extension;
+use serde::{Deserialize, Serialize};
+
+use super::{auth::Admin, error::UnimplementedError, responses::impl_success_response, ApiResult};
+use crate::inx::Standby;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StandbyResponse {
+    /// Whether the instance waits to be promoted before it syncs with its node.
+    pub standby: bool,
+}
+
+impl_success_response!(StandbyResponse);
+
+pub async fn standby(Extension(standby): Extension<Option<Standby>>) -> ApiResult<StandbyResponse> {
+    Ok(StandbyResponse {
+        standby: standby.map_or(false, |standby| !standby.is_promoted()),
+    })
+}
+
+/// Promotes a standby instance, which then continues syncing from the newest milestone in the database.
+pub async fn promote(_: Admin, Extension(standby): Extension<Option<Standby>>) -> ApiResult<StandbyResponse> {
+    let standby = standby.ok_or(UnimplementedError)?;
+    if standby.promote() {
+        tracing::info!("Promotion requested through the API.");
+    }
+    Ok(StandbyResponse { standby: false })
+}
@@ -0,0 +1,139 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::OriginalUri,
+    headers::{Date, Header},
+    http::{header::LINK, HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use super::{config::ApiConfigData, routes::API_ROOT};
+
+#[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+static DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+#[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+static SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// A version of the explorer API.
+///
+/// All versions are served by the same handlers. A handler whose response changes in a newer version takes the
+/// version as an [`Extension`](axum::Extension) and answers accordingly.
+#[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    V2,
+    V3,
+}
+
+impl ApiVersion {
+    #[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+    pub const ALL: [Self; 2] = [Self::V2, Self::V3];
+    pub const LATEST: Self = Self::V3;
+
+    /// The path that the explorer routes of this version are nested at, relative to [`API_ROOT`].
+    pub fn explorer_prefix(self) -> String {
+        format!("/explorer/{self}")
+    }
+}
+
+impl Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::V2 => write!(f, "v2"),
+            Self::V3 => write!(f, "v3"),
+        }
+    }
+}
+
+/// Announces the deprecation of the version a route belongs to.
+///
+/// The versions before the latest are deprecated once a date is configured with `--api-deprecated-since`. Their
+/// responses then carry a `Deprecation` header (RFC 9745), a `Link` to the same route in the latest version and, if
+/// configured, the `Sunset` (RFC 8594) after which the version may be removed.
+#[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+pub async fn deprecation<B>(req: Request<B>, next: Next<B>) -> Response {
+    let version = req.extensions().get::<ApiVersion>().copied();
+    let deprecation = req
+        .extensions()
+        .get::<ApiConfigData>()
+        .and_then(|config| config.deprecated_since.map(|since| (since, config.sunset)));
+    let (since, sunset) = match deprecation.filter(|_| version.map_or(false, |version| version < ApiVersion::LATEST)) {
+        Some((since, sunset)) => (since, sunset),
+        None => return next.run(req).await,
+    };
+    let path = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| req.uri().path().to_string(), |uri| uri.0.path().to_string());
+    let successor = version.and_then(|version| successor_path(&path, version));
+
+    let mut res = next.run(req).await;
+    let headers = res.headers_mut();
+    headers.insert(DEPRECATION.clone(), deprecation_value(since));
+    if let Some(successor) = successor {
+        if let Ok(value) = HeaderValue::from_str(&format!("<{successor}>; rel=\"successor-version\"")) {
+            headers.append(LINK, value);
+        }
+    }
+    if let Some(sunset) = sunset {
+        let mut values = Vec::with_capacity(1);
+        Date::from(sunset).encode(&mut values);
+        headers.extend(values.into_iter().map(|value| (SUNSET.clone(), value)));
+    }
+    res
+}
+
+/// Formats a date as the structured field date (`@` followed by the unix timestamp) of a `Deprecation` header.
+#[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+fn deprecation_value(since: SystemTime) -> HeaderValue {
+    let timestamp = since.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    // Unwrap: a timestamp is always a valid header value.
+    HeaderValue::from_str(&format!("@{timestamp}")).unwrap()
+}
+
+/// Moves a path from the explorer routes of a version to the ones of the latest version.
+#[cfg_attr(not(feature = "explorer"), allow(dead_code))]
+fn successor_path(path: &str, version: ApiVersion) -> Option<String> {
+    let rest = path
+        .strip_prefix(API_ROOT)?
+        .strip_prefix(&version.explorer_prefix())
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))?;
+    Some(format!("{API_ROOT}{}{rest}", ApiVersion::LATEST.explorer_prefix()))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn successor_paths() {
+        assert_eq!(
+            successor_path("/api/explorer/v2/balance/rms1abc", ApiVersion::V2).as_deref(),
+            Some("/api/explorer/v3/balance/rms1abc")
+        );
+        assert_eq!(successor_path("/api/explorer/balance", ApiVersion::V2), None);
+        // Only the version of the explorer routes is replaced, and only as a whole segment.
+        assert_eq!(successor_path("/api/core/v2/outputs/0x00", ApiVersion::V2), None);
+        assert_eq!(successor_path("/api/explorer/v2x/balance", ApiVersion::V2), None);
+        assert_eq!(
+            successor_path("/api/explorer/v2", ApiVersion::V2).as_deref(),
+            Some("/api/explorer/v3")
+        );
+    }
+
+    #[test]
+    fn deprecation_values() {
+        let since = humantime::parse_rfc3339_weak("2026-10-15T00:00:00Z").unwrap();
+        assert_eq!(deprecation_value(since), "@1792022400");
+        assert_eq!(deprecation_value(UNIX_EPOCH), "@0");
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::address_filter::config as address_filter;
+
+#[derive(Args, Debug)]
+pub struct AddressFilterArgs {
+    /// The number of milestones after which the filter of the known addresses is built again.
+    #[arg(long, value_name = "MILESTONES", default_value_t = address_filter::DEFAULT_INTERVAL)]
+    pub address_filter_interval: u32,
+    /// How often to check whether the address filter is due to be built again.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = address_filter::DEFAULT_POLL_INTERVAL)]
+    pub address_filter_poll_interval: std::time::Duration,
+    /// The share of unknown addresses that the address filter reports as known.
+    #[arg(long, value_name = "RATE", default_value_t = address_filter::DEFAULT_FALSE_POSITIVE_RATE)]
+    pub address_filter_false_positive_rate: f64,
+    /// Enable building the filter of the known addresses, which scans the ledger updates of every address.
+    #[arg(long, default_value_t = address_filter::DEFAULT_ENABLED)]
+    pub enable_address_filter: bool,
+}
+
+impl From<&AddressFilterArgs> for address_filter::AddressFilterConfig {
+    fn from(value: &AddressFilterArgs) -> Self {
+        Self {
+            enabled: value.enable_address_filter,
+            interval: value.address_filter_interval,
+            poll_interval: value.address_filter_poll_interval,
+            false_positive_rate: value.address_filter_false_positive_rate,
+        }
+    }
+}
@@ -159,8 +159,8 @@ impl FillAnalyticsCommand {
                 match input_source {
                     #[cfg(feature = "inx")]
                     InputSourceChoice::Inx => {
-                        tracing::info!("Connecting to INX at url `{}`.", config.inx.url);
-                        let inx = chronicle::inx::Inx::connect(config.inx.url.clone()).await?;
+                        tracing::info!("Connecting to INX at url `{}`.", config.inx.urls[0]);
+                        let inx = crate::inx::connect(&config.inx, &config.inx.urls[0]).await?;
                         fill_analytics(
                             &db,
                             &influx_db,
@@ -172,6 +172,25 @@ impl FillAnalyticsCommand {
                         )
                         .await?;
                     }
+                    #[cfg(feature = "archive")]
+                    InputSourceChoice::Archive => {
+                        let archive = config
+                            .pruning
+                            .archive
+                            .as_ref()
+                            .ok_or_else(|| eyre::eyre!("No archive configured, see `--archive-dir`."))?
+                            .open()?;
+                        fill_analytics(
+                            &db,
+                            &influx_db,
+                            &archive,
+                            start_milestone,
+                            end_milestone,
+                            *num_tasks,
+                            analytics,
+                        )
+                        .await?;
+                    }
                     InputSourceChoice::MongoDb => {
                         fill_analytics(
                             &db,
@@ -206,6 +225,9 @@ pub enum InputSourceChoice {
     MongoDb,
     #[cfg(feature = "inx")]
     Inx,
+    /// The milestones that were archived before pruning.
+    #[cfg(feature = "archive")]
+    Archive,
 }
 
 pub async fn fill_analytics<I: 'static + InputSource + Clone>(
@@ -266,8 +288,16 @@ pub async fn fill_analytics<I: 'static + InputSource + Clone>(
 
                         let analytics = analytics_choices
                             .iter()
-                            .map(|choice| Analytic::init(choice, &milestone.protocol_params, &ledger_state))
-                            .collect::<Vec<_>>();
+                            .map(|choice| {
+                                Ok(Analytic::init(
+                                    choice,
+                                    &milestone.protocol_params,
+                                    &ledger_state,
+                                    influx_db.config().analytics_spill_dir.as_deref(),
+                                )?
+                                .with_sampling_interval(influx_db.config().sampling_interval(choice)))
+                            })
+                            .collect::<eyre::Result<Vec<_>>>()?;
                         state = Some(AnalyticsState {
                             analytics,
                             prev_protocol_params: milestone.protocol_params.clone(),
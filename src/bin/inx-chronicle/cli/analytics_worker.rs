@@ -0,0 +1,26 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::analytics_worker::config as analytics_worker;
+
+#[derive(Args, Debug)]
+pub struct AnalyticsWorkerArgs {
+    /// Compute the analytics from the database in a separate worker instead of during INX ingestion. The worker
+    /// also runs with INX disabled, so the analytics can be moved to a process of their own.
+    #[arg(long, default_value_t = analytics_worker::DEFAULT_ENABLED)]
+    pub analytics_worker: bool,
+    /// How often the analytics worker checks for new milestones.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = analytics_worker::DEFAULT_POLL_INTERVAL)]
+    pub analytics_poll_interval: std::time::Duration,
+}
+
+impl From<&AnalyticsWorkerArgs> for analytics_worker::AnalyticsWorkerConfig {
+    fn from(value: &AnalyticsWorkerArgs) -> Self {
+        Self {
+            enabled: value.analytics_worker,
+            poll_interval: value.analytics_poll_interval,
+        }
+    }
+}
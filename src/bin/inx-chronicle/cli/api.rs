@@ -20,6 +20,69 @@ pub struct ApiArgs {
     /// Maximum number of results returned by a single API call.
     #[arg(long, value_name = "SIZE", default_value_t = api::DEFAULT_MAX_PAGE_SIZE)]
     pub max_page_size: usize,
+    /// Maximum time spent handling a single API call, including the database queries it issues.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = api::DEFAULT_REQUEST_TIMEOUT)]
+    pub api_request_timeout: std::time::Duration,
+    /// Maximum number of expensive API queries, such as the ledger analytics and the indexer, that run at once.
+    #[arg(long, value_name = "COUNT", default_value_t = api::DEFAULT_QUERY_CONCURRENCY)]
+    pub api_query_concurrency: usize,
+    /// Maximum number of expensive API queries that wait to run. Further ones are rejected with `429 Too Many
+    /// Requests`.
+    #[arg(long, value_name = "COUNT", default_value_t = api::DEFAULT_QUERY_QUEUE_LENGTH)]
+    pub api_query_queue_length: usize,
+    /// Disable the gzip and brotli compression of responses.
+    #[arg(long, default_value_t = !api::DEFAULT_COMPRESSION_ENABLED)]
+    pub disable_api_compression: bool,
+    /// Disable the `ETag` and `If-None-Match` handling of the explorer routes that only change with the ledger.
+    #[arg(long, default_value_t = !api::DEFAULT_ETAG_ENABLED)]
+    pub disable_api_etag: bool,
+    /// Store the results of the ledger analytics in the `query_cache` collection, and serve them from there until the
+    /// ledger index advances, so that repeated requests do not run the aggregations again.
+    #[arg(long, default_value_t = api::DEFAULT_QUERY_CACHE_ENABLED)]
+    pub api_query_cache: bool,
+    /// How long a cached result of the ledger analytics is served at most, in case the ledger index does not advance.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = api::DEFAULT_QUERY_CACHE_TTL)]
+    pub api_query_cache_ttl: std::time::Duration,
+    /// Send the ledger index that a response reflects in its `X-Ledger-Index` header, and let requests wait for the
+    /// ledger index to reach `?min_ledger_index=`, so that clients can read their own writes.
+    #[arg(long, default_value_t = api::DEFAULT_STRICT_CONSISTENCY)]
+    pub api_strict_consistency: bool,
+    /// How long a request waits for the ledger index to reach `?min_ledger_index=` before it fails with `503 Service
+    /// Unavailable`.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = api::DEFAULT_CONSISTENCY_MAX_WAIT)]
+    pub api_consistency_max_wait: std::time::Duration,
+    /// The date from which the API versions before the latest are deprecated (RFC 3339), announced in their
+    /// `Deprecation` header.
+    #[arg(long, value_name = "DATE")]
+    pub api_deprecated_since: Option<String>,
+    /// The date after which deprecated API versions may be removed (RFC 3339), announced in their `Sunset` header.
+    #[arg(long, value_name = "DATE")]
+    pub api_sunset: Option<String>,
+    /// Serve only HTTP/1.1.
+    #[arg(long, default_value_t = !api::DEFAULT_HTTP2_ENABLED)]
+    pub disable_api_http2: bool,
+    /// The PEM file with the certificate chain to serve the API over TLS with. Requires `--api-tls-key`.
+    #[arg(long, value_name = "FILEPATH", env = "API_TLS_CERT")]
+    pub api_tls_cert: Option<String>,
+    /// The PEM file with the private key of the TLS certificate.
+    #[arg(long, value_name = "FILEPATH", env = "API_TLS_KEY")]
+    pub api_tls_key: Option<String>,
+    /// How often the TLS certificate and key files are checked for changes. Changed files are served without a
+    /// restart.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = api::DEFAULT_TLS_RELOAD_INTERVAL)]
+    pub api_tls_reload_interval: std::time::Duration,
+    /// Record the requests handled by the API in the `api_audit_log` collection.
+    #[arg(long, default_value_t = api::DEFAULT_AUDIT_LOG_COLLECTION)]
+    pub api_audit_log_collection: bool,
+    /// Append the requests handled by the API to this file as JSON lines.
+    #[arg(long, value_name = "FILEPATH")]
+    pub api_audit_log_file: Option<String>,
+    /// A field that is left out of the API audit log. Can be given multiple times.
+    #[arg(long, value_name = "FIELD", value_enum)]
+    pub api_audit_log_redact: Vec<crate::api::audit::AuditField>,
+    /// How long requests are kept in the `api_audit_log` collection.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = api::DEFAULT_AUDIT_LOG_RETENTION)]
+    pub api_audit_log_retention: std::time::Duration,
     /// JWT arguments.
     #[command(flatten)]
     pub jwt: JwtArgs,
@@ -39,6 +102,25 @@ impl From<&ApiArgs> for api::ApiConfig {
             jwt_identity_file: value.jwt.jwt_identity.clone(),
             jwt_expiration: value.jwt.jwt_expiration,
             max_page_size: value.max_page_size,
+            request_timeout: value.api_request_timeout,
+            query_concurrency: value.api_query_concurrency,
+            query_queue_length: value.api_query_queue_length,
+            compression_enabled: !value.disable_api_compression,
+            etag_enabled: !value.disable_api_etag,
+            query_cache_enabled: value.api_query_cache,
+            query_cache_ttl: value.api_query_cache_ttl,
+            strict_consistency: value.api_strict_consistency,
+            consistency_max_wait: value.api_consistency_max_wait,
+            deprecated_since: value.api_deprecated_since.clone(),
+            sunset: value.api_sunset.clone(),
+            http2_enabled: !value.disable_api_http2,
+            tls_cert_file: value.api_tls_cert.clone(),
+            tls_key_file: value.api_tls_key.clone(),
+            tls_reload_interval: value.api_tls_reload_interval,
+            audit_log_collection: value.api_audit_log_collection,
+            audit_log_file: value.api_audit_log_file.clone(),
+            audit_log_redact: value.api_audit_log_redact.clone(),
+            audit_log_retention: value.api_audit_log_retention,
             public_routes: value.public_routes.clone(),
         }
     }
@@ -56,14 +138,10 @@ pub struct JwtArgs {
     #[arg(long, value_name = "SALT", env = "JWT_SALT", default_value = api::DEFAULT_JWT_SALT)]
     pub jwt_salt: String,
     /// The setting for when the (JWT) token expires.
-    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = api::DEFAULT_JWT_EXPIRATION)]
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = api::DEFAULT_JWT_EXPIRATION)]
     pub jwt_expiration: std::time::Duration,
 }
 
-fn parse_duration(arg: &str) -> Result<std::time::Duration, humantime::DurationError> {
-    arg.parse::<humantime::Duration>().map(Into::into)
-}
-
 /// Generate a JWT token using the available config.
 #[derive(Clone, Debug, PartialEq, Eq, Parser)]
 pub struct GenerateJWTCommand;
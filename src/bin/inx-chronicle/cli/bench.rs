@@ -0,0 +1,374 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chronicle::db::{
+    mongodb::collections::{BlockCollection, MilestoneCollection, OutputCollection, ProtocolUpdateCollection},
+    MongoDb,
+};
+use clap::{Parser, Subcommand};
+use eyre::{bail, eyre};
+use iota_sdk::types::block::address::ToBech32Ext;
+use rand::Rng;
+use tokio::{sync::Semaphore, task::JoinSet, time::MissedTickBehavior};
+use tracing::{info, warn};
+
+use crate::config::ChronicleConfig;
+
+/// Benchmarks a running Chronicle instance.
+#[derive(Debug, Subcommand)]
+pub enum BenchCommand {
+    /// Sends a query workload to the API and reports the latency percentiles per endpoint.
+    Api(BenchApiCommand),
+}
+
+impl BenchCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        match self {
+            Self::Api(cmd) => cmd.handle(config).await,
+        }
+    }
+}
+
+/// The kind of requests a benchmark sends.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Scenario {
+    /// All endpoints below.
+    Mixed,
+    /// Address balances and ledger updates.
+    Addresses,
+    /// Blocks and their metadata.
+    Blocks,
+    /// Outputs and their metadata.
+    Outputs,
+    /// Milestones by index.
+    Milestones,
+}
+
+/// The endpoints a benchmark can query.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Endpoint {
+    Balance,
+    LedgerUpdates,
+    Block,
+    BlockMetadata,
+    Output,
+    OutputMetadata,
+    Milestone,
+}
+
+impl Endpoint {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Balance => "explorer/balance",
+            Self::LedgerUpdates => "explorer/ledger/updates/by-address",
+            Self::Block => "core/blocks",
+            Self::BlockMetadata => "core/blocks/metadata",
+            Self::Output => "core/outputs",
+            Self::OutputMetadata => "core/outputs/metadata",
+            Self::Milestone => "core/milestones/by-index",
+        }
+    }
+}
+
+impl Scenario {
+    fn endpoints(&self) -> &'static [Endpoint] {
+        match self {
+            Self::Mixed => &[
+                Endpoint::Balance,
+                Endpoint::LedgerUpdates,
+                Endpoint::Block,
+                Endpoint::BlockMetadata,
+                Endpoint::Output,
+                Endpoint::OutputMetadata,
+                Endpoint::Milestone,
+            ],
+            Self::Addresses => &[Endpoint::Balance, Endpoint::LedgerUpdates],
+            Self::Blocks => &[Endpoint::Block, Endpoint::BlockMetadata],
+            Self::Outputs => &[Endpoint::Output, Endpoint::OutputMetadata],
+            Self::Milestones => &[Endpoint::Milestone],
+        }
+    }
+}
+
+/// Generates a query workload against the API of a running Chronicle instance, using addresses, blocks and outputs
+/// sampled from the database, and reports the latency percentiles per endpoint.
+///
+/// Requests are sent at a fixed rate regardless of how fast they are answered. Most requests go to a small hot set of
+/// the samples to mimic popular addresses and blocks, which lets caches come into play, while the rest are spread over
+/// all samples.
+#[derive(Clone, Debug, PartialEq, Parser)]
+pub struct BenchApiCommand {
+    /// The url of the API of the instance under test.
+    #[arg(long, value_name = "URL", default_value = "http://localhost:8042")]
+    url: String,
+    /// A JWT for the API, which is required for routes that are not public.
+    #[arg(long, value_name = "TOKEN", env = "BENCH_JWT")]
+    jwt: Option<String>,
+    /// The kind of requests to send.
+    #[arg(long, value_enum, default_value_t = Scenario::Mixed)]
+    scenario: Scenario,
+    /// The number of requests sent per second.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), default_value_t = 100)]
+    rps: u32,
+    /// How long to send requests for.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = "30s")]
+    duration: Duration,
+    /// The number of addresses, blocks and outputs sampled from the database.
+    #[arg(long, default_value_t = 1000)]
+    sample_size: usize,
+    /// The number of samples in the hot set.
+    #[arg(long, default_value_t = 10)]
+    hot_set_size: usize,
+    /// The share of requests that go to the hot set, between `0` and `1`.
+    #[arg(long, default_value_t = 0.8)]
+    hot_ratio: f64,
+    /// The maximum number of requests in flight. Requests that would exceed it are skipped.
+    #[arg(long, default_value_t = 256)]
+    max_in_flight: usize,
+}
+
+/// The keys that requests are made for.
+struct Workload {
+    addresses: Vec<String>,
+    block_ids: Vec<String>,
+    output_ids: Vec<String>,
+    milestones: Vec<String>,
+}
+
+impl Workload {
+    fn keys(&self, endpoint: Endpoint) -> &[String] {
+        match endpoint {
+            Endpoint::Balance | Endpoint::LedgerUpdates => &self.addresses,
+            Endpoint::Block | Endpoint::BlockMetadata => &self.block_ids,
+            Endpoint::Output | Endpoint::OutputMetadata => &self.output_ids,
+            Endpoint::Milestone => &self.milestones,
+        }
+    }
+}
+
+fn path(endpoint: Endpoint, key: &str) -> String {
+    match endpoint {
+        Endpoint::Balance => format!("/api/explorer/v2/balance/{key}"),
+        Endpoint::LedgerUpdates => format!("/api/explorer/v2/ledger/updates/by-address/{key}"),
+        Endpoint::Block => format!("/api/core/v2/blocks/{key}"),
+        Endpoint::BlockMetadata => format!("/api/core/v2/blocks/{key}/metadata"),
+        Endpoint::Output => format!("/api/core/v2/outputs/{key}"),
+        Endpoint::OutputMetadata => format!("/api/core/v2/outputs/{key}/metadata"),
+        Endpoint::Milestone => format!("/api/core/v2/milestones/by-index/{key}"),
+    }
+}
+
+/// Picks a key, from the first `hot_set_size` keys with probability `hot_ratio` and from all keys otherwise.
+fn pick<'a>(keys: &'a [String], hot_set_size: usize, hot_ratio: f64, rng: &mut impl Rng) -> &'a String {
+    let hot_set_size = hot_set_size.clamp(1, keys.len());
+    if rng.gen_bool(hot_ratio) {
+        &keys[rng.gen_range(0..hot_set_size)]
+    } else {
+        &keys[rng.gen_range(0..keys.len())]
+    }
+}
+
+/// Gets the latency below which the given percentage of the sorted latencies lie, using the nearest rank.
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Default)]
+struct EndpointStats {
+    latencies: Vec<Duration>,
+    errors: usize,
+}
+
+impl EndpointStats {
+    fn report(&mut self, name: &str) {
+        self.latencies.sort();
+        let ms = |percent| percentile(&self.latencies, percent).as_secs_f64() * 1000.0;
+        info!(
+            "{name}: {} requests, {} errors, p50 {:.1}ms, p90 {:.1}ms, p99 {:.1}ms, max {:.1}ms",
+            self.latencies.len(),
+            self.errors,
+            ms(50.0),
+            ms(90.0),
+            ms(99.0),
+            ms(100.0),
+        );
+    }
+}
+
+impl BenchApiCommand {
+    async fn sample(&self, db: &MongoDb) -> eyre::Result<Workload> {
+        let milestones = db.collection::<MilestoneCollection>();
+        let ledger_index = milestones
+            .get_ledger_index()
+            .await?
+            .ok_or_else(|| eyre!("no milestones in the database"))?;
+        let oldest = milestones
+            .get_oldest_milestone()
+            .await?
+            .ok_or_else(|| eyre!("no milestones in the database"))?;
+        let hrp = db
+            .collection::<ProtocolUpdateCollection>()
+            .get_protocol_parameters_for_ledger_index(ledger_index)
+            .await?
+            .ok_or_else(|| eyre!("no protocol parameters for ledger index {ledger_index}"))?
+            .parameters
+            .bech32_hrp
+            .parse()?;
+        let outputs = db.collection::<OutputCollection>();
+        let milestone_span = (ledger_index.0 - oldest.milestone_index.0 + 1) as usize;
+        Ok(Workload {
+            addresses: outputs
+                .get_sample_addresses(ledger_index, self.sample_size)
+                .await?
+                .into_iter()
+                .map(|address| {
+                    iota_sdk::types::block::address::Address::from(address)
+                        .to_bech32(hrp)
+                        .to_string()
+                })
+                .collect(),
+            block_ids: db
+                .collection::<BlockCollection>()
+                .get_sample_block_ids(self.sample_size)
+                .await?
+                .iter()
+                .map(|block_id| block_id.to_hex())
+                .collect(),
+            output_ids: outputs
+                .get_sample_output_ids(ledger_index, self.sample_size)
+                .await?
+                .iter()
+                .map(|output_id| output_id.to_hex())
+                .collect(),
+            milestones: (0..self.sample_size.min(milestone_span))
+                .map(|_| {
+                    rand::thread_rng()
+                        .gen_range(oldest.milestone_index.0..=ledger_index.0)
+                        .to_string()
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        if !(0.0..=1.0).contains(&self.hot_ratio) {
+            bail!("the hot ratio must be between 0 and 1");
+        }
+        info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let workload = self.sample(&db).await?;
+        let endpoints = self.scenario.endpoints();
+        if let Some(endpoint) = endpoints.iter().find(|&&endpoint| workload.keys(endpoint).is_empty()) {
+            bail!("nothing to sample from the database for {}", endpoint.name());
+        }
+
+        let http = reqwest::Client::new();
+        let url = self.url.trim_end_matches('/').to_string();
+        let in_flight = Arc::new(Semaphore::new(self.max_in_flight));
+        let mut requests = JoinSet::new();
+        let mut skipped = 0;
+        let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / self.rps as f64));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Burst);
+
+        info!(
+            "Sending {} requests per second to `{url}` for {}.",
+            self.rps,
+            humantime::format_duration(self.duration)
+        );
+        let start = Instant::now();
+        while start.elapsed() < self.duration {
+            interval.tick().await;
+            let permit = match in_flight.clone().try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let (endpoint, path) = {
+                let mut rng = rand::thread_rng();
+                let endpoint = endpoints[rng.gen_range(0..endpoints.len())];
+                let key = pick(workload.keys(endpoint), self.hot_set_size, self.hot_ratio, &mut rng);
+                (endpoint, path(endpoint, key))
+            };
+            let mut request = http.get(format!("{url}{path}"));
+            if let Some(jwt) = &self.jwt {
+                request = request.bearer_auth(jwt.trim_start_matches("Bearer "));
+            }
+            requests.spawn(async move {
+                let sent = Instant::now();
+                let success = match request.send().await {
+                    Ok(response) => response.status().is_success() && response.bytes().await.is_ok(),
+                    Err(_) => false,
+                };
+                drop(permit);
+                (endpoint, sent.elapsed(), success)
+            });
+        }
+
+        let mut stats = BTreeMap::<Endpoint, EndpointStats>::new();
+        let mut total = EndpointStats::default();
+        while let Some(res) = requests.join_next().await {
+            let (endpoint, latency, success) = res?;
+            for stats in [stats.entry(endpoint).or_default(), &mut total] {
+                stats.latencies.push(latency);
+                stats.errors += !success as usize;
+            }
+        }
+        let elapsed = start.elapsed();
+
+        for (endpoint, stats) in stats.iter_mut() {
+            stats.report(endpoint.name());
+        }
+        total.report("total");
+        info!(
+            "Completed {:.1} requests per second.",
+            total.latencies.len() as f64 / elapsed.as_secs_f64()
+        );
+        if skipped > 0 {
+            warn!(
+                "Skipped {skipped} requests because {} were already in flight.",
+                self.max_in_flight
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn latency_percentiles() {
+        let latencies = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+        assert_eq!(percentile(&latencies, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&latencies, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&latencies, 100.0), Duration::from_millis(100));
+        assert_eq!(percentile(&latencies[..1], 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn hot_keys() {
+        let keys = (0..100).map(|i| i.to_string()).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert!(keys[..5].contains(pick(&keys, 5, 1.0, &mut rng)));
+        }
+        // The hot set cannot be larger than the keys.
+        assert_eq!(pick(&keys[..1], 5, 1.0, &mut rng), "0");
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeSet;
+
+use chronicle::{
+    db::{
+        mongodb::collections::{ApplicationStateCollection, MilestoneCollection, OutputCollection},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+use clap::Parser;
+use eyre::{bail, eyre};
+use futures::TryStreamExt;
+use serde::{de::DeserializeOwned, Deserialize};
+use tracing::{info, warn};
+
+use crate::{
+    api::{ApiVersion, API_ROOT},
+    config::ChronicleConfig,
+};
+
+/// Compares the unspent output set of this instance with the one of another Chronicle instance, to validate
+/// migrations and replicas.
+///
+/// The output ids are split into buckets by their first byte. Only a digest of each bucket is exchanged, and the
+/// output ids are only requested for the buckets whose digests differ.
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct CompareCommand {
+    /// The url of the API of the other Chronicle instance.
+    #[arg(long, value_name = "URL")]
+    other_url: String,
+    /// A JWT for the API of the other instance, which is required for the comparison routes.
+    #[arg(long, value_name = "TOKEN", env = "OTHER_JWT")]
+    other_jwt: Option<String>,
+    /// The ledger index to compare at. Defaults to the lower of the ledger indexes of both instances.
+    #[arg(long)]
+    ledger_index: Option<MilestoneIndex>,
+    /// The maximum number of missing and extra output ids that are reported.
+    #[arg(long, default_value_t = 100)]
+    max_report: usize,
+}
+
+#[derive(Deserialize)]
+struct DigestResponse {
+    count: usize,
+    digest: String,
+}
+
+#[derive(Deserialize)]
+struct OutputIdsResponse {
+    items: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InfoResponse {
+    database_id: Option<String>,
+    writer_instance_id: Option<String>,
+    ledger_index: Option<MilestoneIndex>,
+}
+
+/// How the database of the other instance relates to the local one.
+#[derive(Debug, PartialEq, Eq)]
+enum Lineage {
+    /// The databases were created independently.
+    Unrelated,
+    /// Both were last written by the same process, so the other instance reads this database or an untouched copy.
+    SameWriter,
+    /// Both were restored from the same database, but are written by different processes since.
+    Diverged,
+}
+
+fn lineage(local_database_id: Option<&str>, local_writer_instance_id: Option<&str>, other: &InfoResponse) -> Lineage {
+    match (local_database_id, other.database_id.as_deref()) {
+        (Some(local), Some(other_id)) if local == other_id => {
+            if local_writer_instance_id == other.writer_instance_id.as_deref() {
+                Lineage::SameWriter
+            } else {
+                Lineage::Diverged
+            }
+        }
+        _ => Lineage::Unrelated,
+    }
+}
+
+/// A client for the comparison routes of another Chronicle instance.
+struct OtherChronicle {
+    http: reqwest::Client,
+    url: String,
+    jwt: Option<String>,
+}
+
+impl OtherChronicle {
+    async fn info(&self) -> eyre::Result<InfoResponse> {
+        self.send(self.http.get(format!("{}{API_ROOT}/info", self.url))).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, ledger_index: MilestoneIndex) -> eyre::Result<T> {
+        self.send(
+            self.http
+                .get(format!(
+                    "{}{API_ROOT}{}/ledger/unspent-outputs/{path}",
+                    self.url,
+                    ApiVersion::LATEST.explorer_prefix()
+                ))
+                .query(&[("ledgerIndex", ledger_index.0)]),
+        )
+        .await
+    }
+
+    async fn send<T: DeserializeOwned>(&self, mut request: reqwest::RequestBuilder) -> eyre::Result<T> {
+        if let Some(jwt) = &self.jwt {
+            request = request.bearer_auth(jwt.trim_start_matches("Bearer "));
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "other instance responded with status {status}: {}",
+                response.text().await.unwrap_or_default()
+            );
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// Splits two sets of output ids into the ones missing from `local` and the extra ones only in `local`.
+fn diff(local: Vec<String>, other: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let local = local.into_iter().collect::<BTreeSet<_>>();
+    let other = other.into_iter().collect::<BTreeSet<_>>();
+    (
+        other.difference(&local).cloned().collect(),
+        local.difference(&other).cloned().collect(),
+    )
+}
+
+impl CompareCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let outputs = db.collection::<OutputCollection>();
+        let local_ledger_index = db
+            .collection::<MilestoneCollection>()
+            .get_ledger_index()
+            .await?
+            .ok_or_else(|| eyre!("no milestones in the database"))?;
+        let other = OtherChronicle {
+            http: reqwest::Client::new(),
+            url: self.other_url.trim_end_matches('/').to_string(),
+            jwt: self.other_jwt.clone(),
+        };
+        let other_info = other.info().await?;
+        let ledger_index = self.ledger_index.unwrap_or_else(|| {
+            other_info
+                .ledger_index
+                .map_or(local_ledger_index, |other_index| other_index.min(local_ledger_index))
+        });
+        if ledger_index > local_ledger_index {
+            bail!("ledger index {ledger_index} is beyond the ledger index {local_ledger_index} of this instance");
+        }
+
+        let app_state = db.collection::<ApplicationStateCollection>();
+        let lineage = lineage(
+            app_state.get_database_id().await?.as_deref(),
+            app_state.get_writer_instance_id().await?.as_deref(),
+            &other_info,
+        );
+        match lineage {
+            Lineage::Unrelated => (),
+            Lineage::SameWriter => bail!(
+                "the other instance uses the same database as this one, or a copy of it that was not written to since"
+            ),
+            Lineage::Diverged => warn!(
+                "Both databases were restored from the same database, but are written independently since. Any \
+                 difference means that they diverged."
+            ),
+        }
+
+        info!(
+            "Comparing the unspent outputs at ledger index {ledger_index} with `{}`.",
+            other.url
+        );
+        let mut count = 0;
+        let mut missing = Vec::new();
+        let mut extra = Vec::new();
+        for digest in outputs.get_unspent_output_digests(ledger_index).await? {
+            count += digest.count;
+            let prefix = prefix_hex::encode([digest.prefix]);
+            let other_digest = other
+                .get::<DigestResponse>(&format!("by-prefix/{prefix}/digest"), ledger_index)
+                .await?;
+            if other_digest.count == digest.count && other_digest.digest == prefix_hex::encode(digest.digest) {
+                continue;
+            }
+            let local_ids = outputs
+                .get_unspent_output_ids(ledger_index, Some(digest.prefix))
+                .await?
+                .map_ok(|output_id| output_id.to_hex())
+                .try_collect()
+                .await?;
+            let other_ids = other
+                .get::<OutputIdsResponse>(&format!("by-prefix/{prefix}"), ledger_index)
+                .await?
+                .items;
+            let (bucket_missing, bucket_extra) = diff(local_ids, other_ids);
+            warn!(
+                "Bucket {prefix} differs: {} missing and {} extra outputs.",
+                bucket_missing.len(),
+                bucket_extra.len()
+            );
+            missing.extend(bucket_missing);
+            extra.extend(bucket_extra);
+        }
+
+        if missing.is_empty() && extra.is_empty() {
+            info!("The unspent output sets are equal ({count} outputs).");
+            return Ok(());
+        }
+        for output_id in missing.iter().take(self.max_report) {
+            info!("Missing: {output_id}");
+        }
+        for output_id in extra.iter().take(self.max_report) {
+            info!("Extra: {output_id}");
+        }
+        let verb = if lineage == Lineage::Diverged {
+            "diverged"
+        } else {
+            "differ"
+        };
+        bail!(
+            "the unspent output sets {verb}: {} outputs are missing and {} are extra in this instance",
+            missing.len(),
+            extra.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn diff_output_ids() {
+        let ids = |ids: &[&str]| ids.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            diff(ids(&["0x01", "0x02", "0x03"]), ids(&["0x03", "0x04", "0x01"])),
+            (ids(&["0x04"]), ids(&["0x02"]))
+        );
+        assert_eq!(diff(ids(&["0x01"]), ids(&["0x01"])), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn database_lineage() {
+        let other = InfoResponse {
+            database_id: Some("db".to_string()),
+            writer_instance_id: Some("writer".to_string()),
+            ledger_index: None,
+        };
+        assert_eq!(lineage(None, None, &other), Lineage::Unrelated);
+        assert_eq!(lineage(Some("other-db"), Some("writer"), &other), Lineage::Unrelated);
+        assert_eq!(lineage(Some("db"), Some("writer"), &other), Lineage::SameWriter);
+        assert_eq!(lineage(Some("db"), Some("other-writer"), &other), Lineage::Diverged);
+        assert_eq!(lineage(Some("db"), None, &other), Lineage::Diverged);
+    }
+}
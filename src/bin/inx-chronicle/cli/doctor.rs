@@ -0,0 +1,428 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime},
+};
+
+use chronicle::{
+    db::{
+        mongodb::collections::{
+            AppVersion, ApplicationStateCollection, BlockCollection, CollectionFragmentation, LedgerUpdateCollection,
+            MigrationVersion, MilestoneCollection, OutputCollection, ParentsCollection, PendingTransactionCollection,
+            QueryCacheCollection, TokenDistributionCollection,
+        },
+        MongoDb, MongoDbCollection,
+    },
+    model::tangle::MilestoneIndexTimestamp,
+};
+use clap::Parser;
+use eyre::bail;
+use tracing::{error, info, warn};
+
+use crate::{
+    config::ChronicleConfig,
+    migrations::{app_version, is_downgrade, LatestMigration, Migration},
+};
+
+/// The collections that `build-indexes` creates indexes for.
+const INDEXED_COLLECTIONS: &[&str] = &[
+    OutputCollection::NAME,
+    BlockCollection::NAME,
+    LedgerUpdateCollection::NAME,
+    MilestoneCollection::NAME,
+    ParentsCollection::NAME,
+    PendingTransactionCollection::NAME,
+    QueryCacheCollection::NAME,
+    TokenDistributionCollection::NAME,
+];
+
+/// Collections smaller than this are not reported as fragmented, as compacting them would gain next to nothing.
+const MIN_FRAGMENTED_SIZE: u64 = 1 << 30;
+
+/// Checks for common operational problems and explains how to fix the ones it finds.
+///
+/// The checks only read, so they can be run against a live instance. The command fails if a problem was found.
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct DoctorCommand {
+    /// How long ago the newest synced milestone may have been issued before syncing is reported as stalled.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = "10m")]
+    max_sync_lag: Duration,
+    /// How far the local clock may differ from the timestamp of the newest milestone of a synced node.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = "1m")]
+    max_clock_skew: Duration,
+    /// The share of its storage, in percent, that a collection may leave unused before it is reported as fragmented.
+    #[arg(long, value_name = "PERCENT", default_value_t = 50)]
+    max_free_storage: u8,
+}
+
+/// The outcome of a check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Finding {
+    Healthy(String),
+    /// The check does not apply to this instance.
+    Skipped(String),
+    Problem {
+        problem: String,
+        /// What the operator can do about the problem.
+        remedy: String,
+    },
+}
+
+impl Finding {
+    fn problem(problem: impl Into<String>, remedy: impl Into<String>) -> Self {
+        Self::Problem {
+            problem: problem.into(),
+            remedy: remedy.into(),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn check_indexes(index_names: &HashMap<String, HashSet<String>>) -> Finding {
+    // Every collection has the `_id_` index, so a collection without further ones never had its indexes built.
+    let missing = INDEXED_COLLECTIONS
+        .iter()
+        .filter(|&&name| index_names.get(name).map_or(true, |indexes| indexes.len() <= 1))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        Finding::Healthy("all collections are indexed".to_string())
+    } else {
+        Finding::problem(
+            format!("the indexes of {missing:?} are missing, which makes most API requests scan the collections"),
+            "run `inx-chronicle build-indexes`, or start the INX worker, which builds them on startup",
+        )
+    }
+}
+
+fn check_migration(
+    last_migration: Option<MigrationVersion>,
+    has_synced: bool,
+    stored_version: Option<AppVersion>,
+) -> eyre::Result<Finding> {
+    let latest = LatestMigration::version();
+    let current_version = app_version();
+    if let Some(stored) = stored_version {
+        if is_downgrade(&stored, &current_version)? {
+            return Ok(Finding::problem(
+                format!("the database was written by Chronicle {stored}, which is newer than {current_version}"),
+                "upgrade this instance to the version that wrote the database",
+            ));
+        }
+    }
+    Ok(match last_migration {
+        Some(last) if last == latest => Finding::Healthy(format!("the database is at migration {latest}")),
+        Some(last) => Finding::problem(
+            format!("the database is at migration {last}, but this version expects {latest}"),
+            "run `inx-chronicle migrate`, or start the INX worker, which migrates on startup",
+        ),
+        None if has_synced => Finding::problem(
+            format!("the database has no migration recorded, but this version expects {latest}"),
+            "run `inx-chronicle migrate`, or start the INX worker, which migrates on startup",
+        ),
+        None => Finding::Skipped("nothing has been synced yet".to_string()),
+    })
+}
+
+fn check_sync_lag(newest: Option<MilestoneIndexTimestamp>, now: u64, max_lag: Duration) -> Finding {
+    let newest = match newest {
+        Some(newest) => newest,
+        None => return Finding::Skipped("nothing has been synced yet".to_string()),
+    };
+    let lag = now.saturating_sub(newest.milestone_timestamp.0 as u64);
+    if lag <= max_lag.as_secs() {
+        Finding::Healthy(format!("milestone {} was synced", newest.milestone_index))
+    } else {
+        Finding::problem(
+            format!(
+                "the newest synced milestone {} was issued {} ago",
+                newest.milestone_index,
+                humantime::format_duration(Duration::from_secs(lag))
+            ),
+            "check that the INX worker is running and connected to a synced node, and look for errors in its logs",
+        )
+    }
+}
+
+#[cfg(feature = "inx")]
+fn check_clock_skew(node_synced: bool, node_milestone_timestamp: u32, now: u64, max_skew: Duration) -> Finding {
+    if !node_synced {
+        return Finding::Skipped("the node is not synced, so its newest milestone is not recent".to_string());
+    }
+    // Milestones of a synced node are issued every few seconds, so the newest one is about as old as the interval.
+    let skew = now.abs_diff(node_milestone_timestamp as u64);
+    if skew <= max_skew.as_secs() {
+        Finding::Healthy("the clock agrees with the node".to_string())
+    } else {
+        Finding::problem(
+            format!(
+                "the clock is {} {} the newest milestone of the node",
+                humantime::format_duration(Duration::from_secs(skew)),
+                if now > node_milestone_timestamp as u64 {
+                    "ahead of"
+                } else {
+                    "behind"
+                }
+            ),
+            "synchronize the clocks of this host and of the node, e.g. with NTP",
+        )
+    }
+}
+
+fn check_fragmentation(collections: &[CollectionFragmentation], max_free_storage: u8) -> Finding {
+    let fragmented = collections
+        .iter()
+        .filter(|c| {
+            c.storage_size >= MIN_FRAGMENTED_SIZE
+                && c.free_storage_size * 100 > c.storage_size * max_free_storage as u64
+        })
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+    if fragmented.is_empty() {
+        Finding::Healthy("no collection is fragmented".to_string())
+    } else {
+        Finding::problem(
+            format!("more than {max_free_storage}% of the storage of {fragmented:?} is unused"),
+            "run `db.runCommand({ compact: \"<collection>\" })` on every member of the deployment, ideally while \
+             the load is low",
+        )
+    }
+}
+
+impl DoctorCommand {
+    async fn run_checks(&self, config: &ChronicleConfig) -> eyre::Result<Vec<(&'static str, Finding)>> {
+        info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let app_state = db.collection::<ApplicationStateCollection>();
+        let newest = db.collection::<MilestoneCollection>().get_newest_milestone().await?;
+
+        let mut findings = vec![
+            ("indexes", check_indexes(&db.get_index_names().await?)),
+            (
+                "migration",
+                check_migration(
+                    app_state.get_last_migration().await?,
+                    app_state.get_starting_index().await?.is_some(),
+                    app_state.get_app_version().await?,
+                )?,
+            ),
+            ("sync", check_sync_lag(newest, unix_now(), self.max_sync_lag)),
+        ];
+
+        #[cfg(feature = "inx")]
+        findings.push(("clock", self.check_node(config).await));
+        #[cfg(feature = "influx")]
+        findings.push(("influx", check_influx(config).await));
+
+        findings.push((
+            "fragmentation",
+            check_fragmentation(&db.collection_fragmentation().await?, self.max_free_storage),
+        ));
+        Ok(findings)
+    }
+
+    #[cfg(feature = "inx")]
+    async fn check_node(&self, config: &ChronicleConfig) -> Finding {
+        if !config.inx.enabled {
+            return Finding::Skipped("INX is disabled".to_string());
+        }
+        let mut finding = None;
+        for url in &config.inx.urls {
+            let status = match crate::inx::connect(&config.inx, url).await {
+                Ok(mut inx) => inx.read_node_status().await.map_err(Into::into),
+                Err(e) => Err(e),
+            };
+            match status {
+                Ok(status) => {
+                    finding.get_or_insert_with(|| {
+                        check_clock_skew(
+                            status.is_synced,
+                            status.latest_milestone.milestone_info.milestone_timestamp,
+                            unix_now(),
+                            self.max_clock_skew,
+                        )
+                    });
+                }
+                // Failing over to an unreachable node would halt syncing.
+                Err(e) => {
+                    return Finding::problem(
+                        format!("the node is not reachable at `{url}`: {e}"),
+                        "check that the node is running with the INX plugin enabled, and that `--inx-url` points to it",
+                    )
+                }
+            }
+        }
+        finding.unwrap_or_else(|| Finding::Skipped("no INX url is configured".to_string()))
+    }
+
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        let findings = self.run_checks(config).await?;
+        let mut problems = 0;
+        for (check, finding) in findings {
+            match finding {
+                Finding::Healthy(message) => info!("[{check}] OK: {message}."),
+                Finding::Skipped(reason) => info!("[{check}] Skipped: {reason}."),
+                Finding::Problem { problem, remedy } => {
+                    problems += 1;
+                    error!("[{check}] Problem: {problem}.");
+                    warn!("[{check}] Remedy: {remedy}.");
+                }
+            }
+        }
+        if problems > 0 {
+            bail!("found {problems} problems");
+        }
+        info!("No problems found.");
+        Ok(())
+    }
+}
+
+#[cfg(feature = "influx")]
+async fn check_influx(config: &ChronicleConfig) -> Finding {
+    let influx = &config.influxdb;
+    let databases: &[(bool, &str)] = &[
+        #[cfg(feature = "analytics")]
+        (influx.analytics_enabled, &influx.analytics_database_name),
+        #[cfg(feature = "metrics")]
+        (influx.metrics_enabled, &influx.metrics_database_name),
+    ];
+    let mut checked = false;
+    for &(_, database) in databases.iter().filter(|(enabled, _)| *enabled) {
+        checked = true;
+        // The client is created directly instead of connecting through `InfluxDb`, which would change the retention
+        // policies of the databases.
+        let client = influxdb::Client::new(&influx.url, database).with_auth(&influx.username, &influx.password);
+        if let Err(e) = client.ping().await {
+            return Finding::problem(
+                format!("InfluxDb is not reachable at `{}`: {e}", influx.url),
+                "check that InfluxDb is running, and that `--influxdb-url`, `--influxdb-username` and \
+                 `--influxdb-password` are correct",
+            );
+        }
+    }
+    if checked {
+        Finding::Healthy("InfluxDb is reachable".to_string())
+    } else {
+        Finding::Skipped("analytics and metrics are disabled".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chronicle::model::tangle::{MilestoneIndex, MilestoneTimestamp};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn is_problem(finding: &Finding) -> bool {
+        matches!(finding, Finding::Problem { .. })
+    }
+
+    #[test]
+    fn missing_indexes() {
+        let mut index_names = INDEXED_COLLECTIONS
+            .iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    HashSet::from(["_id_".to_string(), "other".to_string()]),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert!(!is_problem(&check_indexes(&index_names)));
+
+        index_names.insert(OutputCollection::NAME.to_string(), HashSet::from(["_id_".to_string()]));
+        index_names.remove(BlockCollection::NAME);
+        let problem = match check_indexes(&index_names) {
+            Finding::Problem { problem, .. } => problem,
+            _ => panic!("missing indexes were not reported"),
+        };
+        assert!(problem.contains(OutputCollection::NAME));
+        assert!(problem.contains(BlockCollection::NAME));
+        assert!(!problem.contains(MilestoneCollection::NAME));
+    }
+
+    #[test]
+    fn migration_mismatch() {
+        let latest = LatestMigration::version();
+        assert!(!is_problem(&check_migration(Some(latest.clone()), true, None).unwrap()));
+        assert!(matches!(
+            check_migration(None, false, None).unwrap(),
+            Finding::Skipped(_)
+        ));
+        assert!(is_problem(&check_migration(None, true, None).unwrap()));
+
+        let mut older = latest.clone();
+        older.id -= 1;
+        assert!(is_problem(&check_migration(Some(older), true, None).unwrap()));
+
+        let other_build = AppVersion {
+            version: format!("{}+other.build", app_version().version),
+            schema_fingerprint: app_version().schema_fingerprint,
+        };
+        assert!(!is_problem(
+            &check_migration(Some(latest.clone()), true, Some(other_build)).unwrap()
+        ));
+
+        let newer = AppVersion {
+            version: "99.0.0".to_string(),
+            schema_fingerprint: app_version().schema_fingerprint,
+        };
+        assert!(is_problem(&check_migration(Some(latest), true, Some(newer)).unwrap()));
+    }
+
+    #[test]
+    fn stale_sync() {
+        let newest = MilestoneIndexTimestamp {
+            milestone_index: MilestoneIndex(10),
+            milestone_timestamp: MilestoneTimestamp(1000),
+        };
+        let max_lag = Duration::from_secs(600);
+        assert!(matches!(check_sync_lag(None, 2000, max_lag), Finding::Skipped(_)));
+        assert!(!is_problem(&check_sync_lag(Some(newest), 1600, max_lag)));
+        assert!(is_problem(&check_sync_lag(Some(newest), 1601, max_lag)));
+    }
+
+    #[test]
+    #[cfg(feature = "inx")]
+    fn clock_skew() {
+        let max_skew = Duration::from_secs(60);
+        assert!(!is_problem(&check_clock_skew(true, 1000, 1030, max_skew)));
+        assert!(matches!(
+            check_clock_skew(false, 1000, 5000, max_skew),
+            Finding::Skipped(_)
+        ));
+        let problem = match check_clock_skew(true, 1000, 900, max_skew) {
+            Finding::Problem { problem, .. } => problem,
+            _ => panic!("clock skew was not reported"),
+        };
+        assert_eq!(problem, "the clock is 1m 40s behind the newest milestone of the node");
+    }
+
+    #[test]
+    fn fragmentation() {
+        let collection = |name: &str, storage_size, free_storage_size| CollectionFragmentation {
+            name: name.to_string(),
+            storage_size,
+            free_storage_size,
+        };
+        let collections = [
+            collection("small", MIN_FRAGMENTED_SIZE - 1, MIN_FRAGMENTED_SIZE - 1),
+            collection("compact", 4 * MIN_FRAGMENTED_SIZE, MIN_FRAGMENTED_SIZE),
+        ];
+        assert!(!is_problem(&check_fragmentation(&collections, 50)));
+
+        let problem = match check_fragmentation(&collections, 20) {
+            Finding::Problem { problem, .. } => problem,
+            _ => panic!("fragmentation was not reported"),
+        };
+        assert_eq!(problem, "more than 20% of the storage of [\"compact\"] is unused");
+    }
+}
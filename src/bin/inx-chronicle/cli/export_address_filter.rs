@@ -0,0 +1,55 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use chronicle::db::{mongodb::collections::MilestoneCollection, MongoDb};
+use clap::Parser;
+use eyre::eyre;
+use tracing::info;
+
+use crate::{address_filter, config::ChronicleConfig};
+
+/// Exports a bloom filter of every address that ever owned an output, in the format served by the explorer API.
+///
+/// Pruned outputs no longer count towards the addresses.
+#[derive(Clone, Debug, PartialEq, Parser)]
+pub struct ExportAddressFilterCommand {
+    /// The file the filter is written to.
+    #[arg(long, value_name = "PATH")]
+    output: PathBuf,
+    /// The share of unknown addresses that the filter reports as known. Defaults to the rate of the address filter
+    /// workflow.
+    #[arg(long, value_name = "RATE")]
+    false_positive_rate: Option<f64>,
+}
+
+impl ExportAddressFilterCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        let false_positive_rate = self
+            .false_positive_rate
+            .unwrap_or(config.address_filter.false_positive_rate);
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(eyre!("the false positive rate must be between 0 and 1"));
+        }
+        info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let ledger_index = db
+            .collection::<MilestoneCollection>()
+            .get_ledger_index()
+            .await?
+            .ok_or_else(|| eyre!("no milestones are stored"))?;
+
+        let filter = address_filter::build_filter(&db, ledger_index, false_positive_rate).await?;
+        let bytes = filter.to_bytes();
+        std::fs::write(&self.output, &bytes)?;
+        info!(
+            "Exported the filter of {} addresses at milestone {ledger_index} with {} hash functions ({} bytes) to `{}`.",
+            filter.address_count(),
+            filter.hash_count(),
+            bytes.len(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use chronicle::{
+    db::{
+        mongodb::collections::{AddressActivitySpan, MilestoneCollection, OutputCollection, ProtocolUpdateCollection},
+        MongoDb,
+    },
+    model::utxo::Address,
+};
+use clap::Parser;
+use eyre::eyre;
+use futures::TryStreamExt;
+use iota_sdk::types::block::address::{Hrp, ToBech32Ext};
+use tracing::info;
+
+use crate::config::ChronicleConfig;
+
+/// How many addresses are written between progress reports.
+const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// Exports every address that ever owned an output as CSV, without holding the addresses in memory.
+///
+/// Pruned outputs no longer count towards the addresses and their activity.
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct ExportAddressesCommand {
+    /// The file the CSV is written to.
+    #[arg(long, value_name = "PATH")]
+    output: PathBuf,
+    /// Add the first and last activity of each address as milestone timestamps. This reads every owned output
+    /// instead of only the owning address index, so it takes considerably longer.
+    #[arg(long)]
+    with_activity: bool,
+}
+
+impl ExportAddressesCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let ledger_index = db
+            .collection::<MilestoneCollection>()
+            .get_ledger_index()
+            .await?
+            .ok_or_else(|| eyre!("no milestones are stored"))?;
+        let hrp: Hrp = db
+            .collection::<ProtocolUpdateCollection>()
+            .get_protocol_parameters_for_ledger_index(ledger_index)
+            .await?
+            .ok_or_else(|| eyre!("no protocol parameters for ledger index {ledger_index}"))?
+            .parameters
+            .bech32_hrp
+            .parse()?;
+
+        let mut writer = BufWriter::new(File::create(&self.output)?);
+        let outputs = db.collection::<OutputCollection>();
+        let mut count = 0;
+        if self.with_activity {
+            writeln!(writer, "address,first_activity,last_activity")?;
+            let spans = outputs.get_address_activity_spans().await?;
+            futures::pin_mut!(spans);
+            while let Some(AddressActivitySpan {
+                address,
+                first_activity,
+                last_activity,
+            }) = spans.try_next().await?
+            {
+                writeln!(
+                    writer,
+                    "{},{},{}",
+                    to_bech32(address, hrp),
+                    first_activity.0,
+                    last_activity.0
+                )?;
+                count += 1;
+                report_progress(count);
+            }
+        } else {
+            writeln!(writer, "address")?;
+            let mut addresses = outputs.get_distinct_addresses().await?;
+            while let Some(address) = addresses.try_next().await? {
+                writeln!(writer, "{}", to_bech32(address, hrp))?;
+                count += 1;
+                report_progress(count);
+            }
+        }
+        writer.flush()?;
+        info!("Exported {count} addresses to `{}`.", self.output.display());
+        Ok(())
+    }
+}
+
+fn to_bech32(address: Address, hrp: Hrp) -> String {
+    iota_sdk::types::block::address::Address::from(address)
+        .to_bech32(hrp)
+        .to_string()
+}
+
+fn report_progress(count: usize) {
+    if count % PROGRESS_INTERVAL == 0 {
+        info!("Exported {count} addresses.");
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use chronicle::db::influxdb::AnalyticsChoice;
+use std::path::PathBuf;
+
+use chronicle::db::influxdb::{config::AnalyticsSampling, AnalyticsChoice};
 
 use super::*;
 
@@ -16,4 +18,21 @@ pub struct InfluxAnalyticsArgs {
     /// Select a subset of analytics to compute. If unset, all analytics will be computed.
     #[arg(long, value_name = "ANALYTICS")]
     pub analytics: Vec<AnalyticsChoice>,
+    /// Measure an analytic only every few milestones, at the milestones whose index is a multiple of the given number
+    /// (e.g. `address-balance=100`). Activity adds up until the next measurement. Sampled points carry the
+    /// `sampling_interval` and the number of `sampled_milestones` they cover.
+    #[arg(long, value_name = "ANALYTIC=MILESTONES")]
+    pub analytics_sampling: Vec<AnalyticsSampling>,
+    /// Keep the state of analytics that grows with the ledger, such as the balance of every address, in a temporary
+    /// database within the given directory instead of in memory. This is slower, but lets a large ledger be analyzed
+    /// with little memory.
+    #[arg(long, value_name = "DIR")]
+    pub analytics_spill_dir: Option<PathBuf>,
+    /// How long analytics are kept before InfluxDb removes them. If unset, they are kept forever.
+    #[arg(long, value_name = "DURATION", value_parser = crate::cli::parse_duration)]
+    pub analytics_retention: Option<std::time::Duration>,
+    /// Average the analytics over intervals of the given length into the `downsampled` retention policy, where they
+    /// are kept forever.
+    #[arg(long, value_name = "DURATION", value_parser = crate::cli::parse_duration)]
+    pub analytics_downsampling: Option<std::time::Duration>,
 }
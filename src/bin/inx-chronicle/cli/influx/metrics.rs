@@ -11,4 +11,11 @@ pub struct InfluxMetricsArgs {
     /// Disable InfluxDb time-series metrics writes.
     #[arg(long, default_value_t = !influxdb::DEFAULT_METRICS_ENABLED)]
     pub disable_metrics: bool,
+    /// How long metrics are kept before InfluxDb removes them. If unset, they are kept forever.
+    #[arg(long, value_name = "DURATION", value_parser = crate::cli::parse_duration)]
+    pub metrics_retention: Option<std::time::Duration>,
+    /// Average the metrics over intervals of the given length into the `downsampled` retention policy, where they are
+    /// kept forever.
+    #[arg(long, value_name = "DURATION", value_parser = crate::cli::parse_duration)]
+    pub metrics_downsampling: Option<std::time::Duration>,
 }
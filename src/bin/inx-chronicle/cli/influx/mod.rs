@@ -6,6 +6,8 @@ mod analytics;
 #[cfg(feature = "metrics")]
 mod metrics;
 
+#[cfg(any(feature = "analytics", feature = "metrics"))]
+use chronicle::db::influxdb::RetentionConfig;
 use chronicle::db::influxdb::{config as influxdb, InfluxDbConfig};
 use clap::Args;
 
@@ -40,10 +42,24 @@ impl From<&InfluxDbArgs> for InfluxDbConfig {
             analytics_database_name: value.analytics_args.analytics_database_name.clone(),
             #[cfg(feature = "analytics")]
             analytics: value.analytics_args.analytics.clone(),
+            #[cfg(feature = "analytics")]
+            analytics_sampling: value.analytics_args.analytics_sampling.clone(),
+            #[cfg(feature = "analytics")]
+            analytics_spill_dir: value.analytics_args.analytics_spill_dir.clone(),
+            #[cfg(feature = "analytics")]
+            analytics_retention: RetentionConfig {
+                retention: value.analytics_args.analytics_retention,
+                downsampling_interval: value.analytics_args.analytics_downsampling,
+            },
             #[cfg(feature = "metrics")]
             metrics_enabled: !value.metrics_args.disable_metrics,
             #[cfg(feature = "metrics")]
             metrics_database_name: value.metrics_args.metrics_database_name.clone(),
+            #[cfg(feature = "metrics")]
+            metrics_retention: RetentionConfig {
+                retention: value.metrics_args.metrics_retention,
+                downsampling_interval: value.metrics_args.metrics_downsampling,
+            },
         }
     }
 }
@@ -3,17 +3,87 @@
 
 use clap::Args;
 
-use crate::inx::config as inx;
+use crate::inx::config::{self as inx, IntegrityCheckMode};
 
 #[derive(Args, Debug)]
 pub struct InxArgs {
-    /// The address of the node INX interface Chronicle tries to connect to - if enabled.
-    #[arg(long, value_name = "URL", env = "INX_URL", default_value = inx::DEFAULT_URL)]
-    pub inx_url: String,
+    /// The addresses of the node INX interfaces Chronicle tries to connect to - if enabled. Given as a comma-separated
+    /// list, the next address is used if a node is unreachable or keeps losing the connection.
+    #[arg(long, value_name = "URL", env = "INX_URL", value_delimiter = ',', default_value = inx::DEFAULT_URL)]
+    pub inx_url: Vec<String>,
+    /// How often the connection to a node may be lost without syncing a milestone before failing over to the next
+    /// address.
+    #[arg(long, value_name = "COUNT", default_value_t = inx::DEFAULT_FAILOVER_ERRORS)]
+    pub inx_failover_errors: usize,
+    /// The longest delay between reconnects after the connection to the node was lost. The delay starts at one second
+    /// and doubles with every reconnect until a milestone is synced again.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = inx::DEFAULT_RECONNECT_MAX_DELAY)]
+    pub inx_reconnect_max_delay: std::time::Duration,
+    /// The PEM file with the certificate authority that `https` INX addresses are verified with. The native root
+    /// certificates are used without it.
+    #[arg(long, value_name = "FILEPATH", env = "INX_TLS_CA_CERT")]
+    pub inx_tls_ca_cert: Option<String>,
+    /// The PEM file with the client certificate that is presented to `https` INX addresses. Requires
+    /// `--inx-tls-client-key`.
+    #[arg(long, value_name = "FILEPATH", env = "INX_TLS_CLIENT_CERT")]
+    pub inx_tls_client_cert: Option<String>,
+    /// The PEM file with the private key of the client certificate.
+    #[arg(long, value_name = "FILEPATH", env = "INX_TLS_CLIENT_KEY")]
+    pub inx_tls_client_key: Option<String>,
     /// Milestone at which synchronization should begin. If set to `1` Chronicle will try to sync back until the
     /// genesis block. If set to `0` Chronicle will start syncing from the most recent milestone it received.
     #[arg(long, value_name = "START", default_value_t = inx::DEFAULT_SYNC_START)]
     pub inx_sync_start: u32,
+    /// Record transactions as pending as soon as the node receives them, until a milestone references them.
+    #[arg(long, default_value_t = inx::DEFAULT_PENDING_TRANSACTIONS_ENABLED)]
+    pub inx_pending_transactions: bool,
+    /// How long a pending transaction is kept if no milestone references it.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = inx::DEFAULT_PENDING_TRANSACTION_TTL)]
+    pub inx_pending_transaction_ttl: std::time::Duration,
+    /// Report documents that are dropped as duplicates while syncing, which hints at data being sent more than once.
+    #[arg(long, default_value_t = inx::DEFAULT_AUDIT_DUPLICATES)]
+    pub inx_audit_duplicates: bool,
+    /// Check that the outputs a milestone spends and the milestone before it were written before the milestone is,
+    /// which catches bugs in the order of ingestion early. Violations are either logged or stop syncing. The checks
+    /// take an additional query per batch of spent outputs, so they are meant for staging environments.
+    #[arg(long, value_name = "MODE", value_enum, default_value_t = IntegrityCheckMode::default())]
+    pub inx_integrity_checks: IntegrityCheckMode,
+    /// How many milestones Chronicle has to be behind the node to sync in catch-up mode, which defers the indexing of
+    /// block parents and inline analytics until it is back within this distance. Set to `0` to disable catch-up mode.
+    #[arg(long, value_name = "MILESTONES", default_value_t = inx::DEFAULT_CATCH_UP_THRESHOLD)]
+    pub inx_catch_up_threshold: u32,
+    /// The insert batch size used in catch-up mode.
+    #[arg(long, value_name = "SIZE", default_value_t = inx::DEFAULT_CATCH_UP_BATCH_SIZE)]
+    pub inx_catch_up_batch_size: usize,
+    /// How many milestones are written at the same time, which speeds up catching up on large databases. The outputs
+    /// of a milestone are written while the blocks of the previous one still are, but milestones are always marked as
+    /// synced in order. Set to `1` to sync one milestone after the other.
+    #[arg(long, value_name = "MILESTONES", default_value_t = inx::DEFAULT_MILESTONES_IN_FLIGHT)]
+    pub inx_milestones_in_flight: usize,
+    /// Store blocks without their raw bytes to save space. Raw block responses are then reconstructed from the stored
+    /// blocks, and the outputs of their transactions from the stored outputs, so a transaction can no longer be
+    /// returned once one of its outputs was pruned. Blocks with a conflicting transaction keep their raw bytes.
+    #[arg(long, default_value_t = inx::DEFAULT_LEAN_STORAGE)]
+    pub inx_lean_storage: bool,
+    /// Start in standby, next to another instance that writes to the same database. Syncing starts from the newest
+    /// milestone in the database once the instance is promoted with `POST /standby/promote`. This requires the
+    /// database to be a replica set.
+    #[arg(long, default_value_t = inx::DEFAULT_STANDBY)]
+    pub inx_standby: bool,
+    /// How many of the most recent milestones keep a report of how they were synced, which can be requested with
+    /// `GET /progress/reports/{milestoneIndex}`. Set to `0` to disable the reports.
+    #[arg(long, value_name = "MILESTONES", default_value_t = inx::DEFAULT_SYNC_REPORT_RETENTION)]
+    pub inx_sync_report_retention: u32,
+    /// How often the status and the tip pool of the node are sampled, which can be requested with
+    /// `GET /progress/node-health` to compare the health of the node with the sync lag of Chronicle.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = inx::DEFAULT_NODE_HEALTH_INTERVAL)]
+    pub inx_node_health_interval: std::time::Duration,
+    /// How long samples of the node status are kept.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = inx::DEFAULT_NODE_HEALTH_RETENTION)]
+    pub inx_node_health_retention: std::time::Duration,
+    /// Disable sampling the status of the node.
+    #[arg(long, default_value_t = !inx::DEFAULT_NODE_HEALTH_ENABLED)]
+    pub inx_disable_node_health: bool,
     /// Disable the INX synchronization workflow.
     #[arg(long, default_value_t = !inx::DEFAULT_ENABLED)]
     pub disable_inx: bool,
@@ -23,8 +93,26 @@ impl From<&InxArgs> for inx::InxConfig {
     fn from(value: &InxArgs) -> Self {
         Self {
             enabled: !value.disable_inx,
-            url: value.inx_url.clone(),
+            urls: value.inx_url.clone(),
+            failover_errors: value.inx_failover_errors,
+            reconnect_max_delay: value.inx_reconnect_max_delay,
+            tls_ca_cert: value.inx_tls_ca_cert.clone(),
+            tls_client_cert: value.inx_tls_client_cert.clone(),
+            tls_client_key: value.inx_tls_client_key.clone(),
             sync_start_milestone: value.inx_sync_start.into(),
+            pending_transactions_enabled: value.inx_pending_transactions,
+            pending_transaction_ttl: value.inx_pending_transaction_ttl,
+            audit_duplicates: value.inx_audit_duplicates,
+            integrity_checks: value.inx_integrity_checks,
+            catch_up_threshold: value.inx_catch_up_threshold,
+            catch_up_batch_size: value.inx_catch_up_batch_size,
+            milestones_in_flight: value.inx_milestones_in_flight,
+            lean_storage: value.inx_lean_storage,
+            standby: value.inx_standby,
+            sync_report_retention: value.inx_sync_report_retention,
+            node_health_enabled: !value.inx_disable_node_health,
+            node_health_interval: value.inx_node_health_interval,
+            node_health_retention: value.inx_node_health_retention,
         }
     }
 }
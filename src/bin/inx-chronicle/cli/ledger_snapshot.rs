@@ -0,0 +1,29 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::ledger_snapshot::config as ledger_snapshot;
+
+#[derive(Args, Debug)]
+pub struct LedgerSnapshotArgs {
+    /// The number of milestones between two digests of the unspent output set.
+    #[arg(long, value_name = "MILESTONES", default_value_t = ledger_snapshot::DEFAULT_INTERVAL)]
+    pub ledger_snapshot_interval: u32,
+    /// How often to check whether a ledger snapshot is due.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = ledger_snapshot::DEFAULT_POLL_INTERVAL)]
+    pub ledger_snapshot_poll_interval: std::time::Duration,
+    /// Disable the ledger snapshot workflow.
+    #[arg(long, default_value_t = !ledger_snapshot::DEFAULT_ENABLED)]
+    pub disable_ledger_snapshots: bool,
+}
+
+impl From<&LedgerSnapshotArgs> for ledger_snapshot::LedgerSnapshotConfig {
+    fn from(value: &LedgerSnapshotArgs) -> Self {
+        Self {
+            enabled: !value.disable_ledger_snapshots,
+            interval: value.ledger_snapshot_interval,
+            poll_interval: value.ledger_snapshot_poll_interval,
+        }
+    }
+}
@@ -6,14 +6,45 @@ use clap::{Args, Parser, Subcommand};
 
 use crate::config::ChronicleConfig;
 
+#[cfg(feature = "explorer")]
+mod address_filter;
 #[cfg(feature = "analytics")]
 pub mod analytics;
+#[cfg(feature = "analytics")]
+mod analytics_worker;
 #[cfg(feature = "api")]
 mod api;
+#[cfg(feature = "api")]
+mod bench;
+#[cfg(feature = "api")]
+mod compare;
+mod doctor;
+#[cfg(feature = "explorer")]
+mod export_address_filter;
+mod export_addresses;
 #[cfg(feature = "influx")]
 mod influx;
 #[cfg(feature = "inx")]
 mod inx;
+#[cfg(feature = "explorer")]
+mod ledger_snapshot;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "explorer")]
+mod naming;
+mod orphaned_blocks;
+mod protocol_upgrade;
+mod pruning;
+#[cfg(feature = "search")]
+mod search;
+#[cfg(feature = "analytics")]
+mod spam_detection;
+mod storage_forecast;
+#[cfg(feature = "explorer")]
+mod token_distribution;
+#[cfg(feature = "explorer")]
+mod views;
+mod wait;
 
 /// Chronicle permanode storage as an INX plugin
 #[derive(Parser, Debug)]
@@ -31,10 +62,55 @@ pub struct ClArgs {
     #[cfg(feature = "inx")]
     #[command(flatten, next_help_heading = "INX")]
     pub inx: inx::InxArgs,
+    /// MQTT arguments.
+    #[cfg(feature = "mqtt")]
+    #[command(flatten, next_help_heading = "MQTT")]
+    pub mqtt: mqtt::MqttArgs,
     /// Rest API arguments.
     #[cfg(feature = "api")]
     #[command(flatten, next_help_heading = "API")]
     pub api: api::ApiArgs,
+    /// Naming service arguments.
+    #[cfg(feature = "explorer")]
+    #[command(flatten, next_help_heading = "Naming Service")]
+    pub naming: naming::NamingArgs,
+    /// Analytics worker arguments.
+    #[cfg(feature = "analytics")]
+    #[command(flatten, next_help_heading = "Analytics Worker")]
+    pub analytics_worker: analytics_worker::AnalyticsWorkerArgs,
+    /// Spam detection arguments.
+    #[cfg(feature = "analytics")]
+    #[command(flatten, next_help_heading = "Spam Detection")]
+    pub spam_detection: spam_detection::SpamDetectionArgs,
+    /// Materialized view arguments.
+    #[cfg(feature = "explorer")]
+    #[command(flatten, next_help_heading = "Materialized Views")]
+    pub views: views::ViewsArgs,
+    /// Native token distribution arguments.
+    #[cfg(feature = "explorer")]
+    #[command(flatten, next_help_heading = "Token Distribution")]
+    pub token_distribution: token_distribution::TokenDistributionArgs,
+    /// Ledger snapshot arguments.
+    #[cfg(feature = "explorer")]
+    #[command(flatten, next_help_heading = "Ledger Snapshots")]
+    pub ledger_snapshot: ledger_snapshot::LedgerSnapshotArgs,
+    /// Address filter arguments.
+    #[cfg(feature = "explorer")]
+    #[command(flatten, next_help_heading = "Address Filter")]
+    pub address_filter: address_filter::AddressFilterArgs,
+    /// Pruning arguments.
+    #[command(flatten, next_help_heading = "Pruning")]
+    pub pruning: pruning::PruningArgs,
+    /// Storage forecast arguments.
+    #[command(flatten, next_help_heading = "Storage Forecast")]
+    pub storage_forecast: storage_forecast::StorageForecastArgs,
+    /// Search engine arguments.
+    #[cfg(feature = "search")]
+    #[command(flatten, next_help_heading = "Search")]
+    pub search: search::SearchArgs,
+    /// Run even if the database was written by a newer version of Chronicle.
+    #[arg(long)]
+    pub allow_downgrade: bool,
     /// Subcommands.
     #[command(subcommand)]
     pub subcommand: Option<Subcommands>,
@@ -73,8 +149,28 @@ impl ClArgs {
             influxdb: (&self.influxdb).into(),
             #[cfg(feature = "inx")]
             inx: (&self.inx).into(),
+            #[cfg(feature = "mqtt")]
+            mqtt: (&self.mqtt).into(),
             #[cfg(feature = "api")]
             api: (&self.api).into(),
+            #[cfg(feature = "explorer")]
+            naming: (&self.naming).into(),
+            #[cfg(feature = "analytics")]
+            analytics_worker: (&self.analytics_worker).into(),
+            #[cfg(feature = "analytics")]
+            spam_detection: (&self.spam_detection).into(),
+            #[cfg(feature = "explorer")]
+            views: (&self.views).into(),
+            #[cfg(feature = "explorer")]
+            token_distribution: (&self.token_distribution).into(),
+            #[cfg(feature = "explorer")]
+            ledger_snapshot: (&self.ledger_snapshot).into(),
+            #[cfg(feature = "explorer")]
+            address_filter: (&self.address_filter).into(),
+            pruning: (&self.pruning).into(),
+            storage_forecast: (&self.storage_forecast).into(),
+            #[cfg(feature = "search")]
+            search: (&self.search).into(),
         }
     }
 
@@ -88,6 +184,14 @@ impl ClArgs {
                 Subcommands::GenerateJWT(cmd) => {
                     cmd.handle(&config.api)?;
                 }
+                #[cfg(feature = "api")]
+                Subcommands::Compare(cmd) => {
+                    cmd.handle(config).await?;
+                }
+                #[cfg(feature = "api")]
+                Subcommands::Bench(cmd) => {
+                    cmd.handle(config).await?;
+                }
                 #[cfg(feature = "analytics")]
                 Subcommands::FillAnalytics(cmd) => {
                     cmd.handle(config).await?;
@@ -102,6 +206,9 @@ impl ClArgs {
                         return Ok(PostCommand::Start);
                     }
                 }
+                Subcommands::CheckProtocolUpgrade(cmd) => {
+                    cmd.handle(config).await?;
+                }
                 Subcommands::BuildIndexes => {
                     tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
                     let db = chronicle::db::MongoDb::connect(&config.mongodb).await?;
@@ -114,6 +221,24 @@ impl ClArgs {
                     crate::migrations::migrate(&db).await?;
                     tracing::info!("Migration completed successfully.");
                 }
+                Subcommands::Doctor(cmd) => {
+                    cmd.handle(config).await?;
+                }
+                Subcommands::CollectOrphanedBlocks(cmd) => {
+                    cmd.handle(config).await?;
+                }
+                Subcommands::ExportAddresses(cmd) => {
+                    cmd.handle(config).await?;
+                }
+                #[cfg(feature = "explorer")]
+                Subcommands::ExportAddressFilter(cmd) => {
+                    cmd.handle(config).await?;
+                }
+                Subcommands::Wait(cmd) => {
+                    if cmd.handle(config).await? {
+                        return Ok(PostCommand::Start);
+                    }
+                }
                 _ => (),
             }
             Ok(PostCommand::Exit)
@@ -127,8 +252,15 @@ impl ClArgs {
 pub enum Subcommands {
     #[cfg(feature = "api")]
     GenerateJWT(api::GenerateJWTCommand),
+    #[cfg(feature = "api")]
+    Compare(compare::CompareCommand),
+    #[cfg(feature = "api")]
+    #[command(subcommand)]
+    Bench(bench::BenchCommand),
     #[cfg(feature = "analytics")]
     FillAnalytics(analytics::FillAnalyticsCommand),
+    /// Checks how Chronicle would handle proposed protocol parameters, using the data of the current ledger.
+    CheckProtocolUpgrade(protocol_upgrade::CheckProtocolUpgradeCommand),
     /// Clear the Chronicle database.
     #[cfg(debug_assertions)]
     ClearDatabase {
@@ -140,6 +272,17 @@ pub enum Subcommands {
     BuildIndexes,
     /// Migrate to a new version.
     Migrate,
+    CollectOrphanedBlocks(orphaned_blocks::CollectOrphanedBlocksCommand),
+    ExportAddresses(export_addresses::ExportAddressesCommand),
+    #[cfg(feature = "explorer")]
+    ExportAddressFilter(export_address_filter::ExportAddressFilterCommand),
+    Doctor(doctor::DoctorCommand),
+    /// Waits until the configured dependencies are reachable and healthy.
+    Wait(wait::WaitCommand),
+}
+
+fn parse_duration(arg: &str) -> Result<std::time::Duration, humantime::DurationError> {
+    arg.parse::<humantime::Duration>().map(Into::into)
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
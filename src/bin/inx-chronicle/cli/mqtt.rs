@@ -0,0 +1,34 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::mqtt::config as mqtt;
+
+#[derive(Args, Debug)]
+pub struct MqttArgs {
+    /// Publish the blocks, transactions and outputs of every synced milestone to an MQTT broker, on the topics of the
+    /// node event API.
+    #[arg(long, default_value_t = mqtt::DEFAULT_ENABLED)]
+    pub mqtt: bool,
+    /// The url of the MQTT broker. A username and password can be given as part of the url.
+    #[arg(long, value_name = "URL", env = "MQTT_URL", default_value = mqtt::DEFAULT_URL)]
+    pub mqtt_url: String,
+    /// The client id Chronicle connects to the MQTT broker with.
+    #[arg(long, value_name = "ID", default_value = mqtt::DEFAULT_CLIENT_ID)]
+    pub mqtt_client_id: String,
+    /// How many events are buffered while the MQTT broker is slow or unreachable. Further events are dropped.
+    #[arg(long, value_name = "COUNT", default_value_t = mqtt::DEFAULT_CAPACITY)]
+    pub mqtt_capacity: usize,
+}
+
+impl From<&MqttArgs> for mqtt::MqttConfig {
+    fn from(value: &MqttArgs) -> Self {
+        Self {
+            enabled: value.mqtt,
+            url: value.mqtt_url.clone(),
+            client_id: value.mqtt_client_id.clone(),
+            capacity: value.mqtt_capacity,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::naming::config::{self as naming, NamingConfig};
+
+#[derive(Args, Debug)]
+pub struct NamingArgs {
+    /// The url that resolves a name to an address, in which `{name}` is replaced by the name. The naming service has
+    /// to respond with a JSON object holding the bech32 `address`. Name resolution is disabled if it is not set.
+    #[arg(long, value_name = "URL", env = "NAMING_RESOLVE_URL")]
+    pub naming_resolve_url: Option<String>,
+    /// The url that looks up the name of an address, in which `{address}` is replaced by the bech32 address. The
+    /// naming service has to respond with a JSON object holding the `name`.
+    #[arg(long, value_name = "URL", env = "NAMING_REVERSE_URL")]
+    pub naming_reverse_url: Option<String>,
+    /// The key used to authenticate with the naming service.
+    #[arg(long, value_name = "KEY", env = "NAMING_API_KEY")]
+    pub naming_api_key: Option<String>,
+    /// How long to wait for the naming service before giving up on a lookup.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = naming::DEFAULT_TIMEOUT)]
+    pub naming_timeout: std::time::Duration,
+}
+
+impl From<&NamingArgs> for NamingConfig {
+    fn from(value: &NamingArgs) -> Self {
+        Self {
+            resolve_url: value.naming_resolve_url.clone(),
+            reverse_url: value.naming_reverse_url.clone(),
+            api_key: value.naming_api_key.clone(),
+            timeout: value.naming_timeout,
+        }
+    }
+}
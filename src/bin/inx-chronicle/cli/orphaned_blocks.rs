@@ -0,0 +1,120 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle::{
+    db::{
+        mongodb::collections::{BlockCollection, MilestoneCollection, OrphanedBlocksResult, ParentsCollection},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+use clap::Parser;
+use eyre::eyre;
+use futures::TryStreamExt;
+use tracing::info;
+
+use crate::{config::ChronicleConfig, pruning::PRUNE_BATCH_SIZE};
+
+/// Removes the blocks that were referenced by a milestone that is not stored.
+///
+/// These are left behind when Chronicle stops while writing a milestone and syncs from a later milestone afterwards.
+/// They are not served by any milestone and skew the statistics that are computed per milestone.
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct CollectOrphanedBlocksCommand {
+    /// Only report the orphaned blocks without removing them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl CollectOrphanedBlocksCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let ledger_index = db
+            .collection::<MilestoneCollection>()
+            .get_ledger_index()
+            .await?
+            .ok_or_else(|| eyre!("no milestones are stored"))?;
+
+        let orphaned = db
+            .collection::<BlockCollection>()
+            .get_orphaned_blocks(ledger_index)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        for OrphanedBlocksResult {
+            milestone_index,
+            block_count,
+        } in &orphaned
+        {
+            info!("{block_count} blocks were referenced by milestone {milestone_index}, which is not stored.");
+        }
+        let total = orphaned.iter().map(|res| res.block_count).sum::<u64>();
+        if self.dry_run || total == 0 {
+            info!(
+                "Found {total} orphaned blocks of {} milestones up to ledger index {ledger_index}.",
+                orphaned.len()
+            );
+            return Ok(());
+        }
+
+        let mut removed = 0;
+        for OrphanedBlocksResult { milestone_index, .. } in orphaned {
+            removed += remove_blocks(&db, milestone_index).await?;
+        }
+        info!("Removed {removed} orphaned blocks.");
+        Ok(())
+    }
+}
+
+/// Removes the blocks referenced by a milestone along with their relationships to their parents.
+async fn remove_blocks(db: &MongoDb, milestone_index: MilestoneIndex) -> eyre::Result<u64> {
+    let blocks = db.collection::<BlockCollection>();
+    let mut block_ids = blocks
+        .get_block_ids_referenced_by(milestone_index)
+        .await?
+        .try_chunks(PRUNE_BATCH_SIZE);
+    let mut count = 0;
+    while let Some(block_ids) = block_ids.try_next().await? {
+        // The relationships go first, like when pruning, so that none are left without their block.
+        db.collection::<ParentsCollection>()
+            .delete_relationships(&block_ids)
+            .await?;
+        count += blocks.delete_blocks(&block_ids).await?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb::bson::{doc, from_document};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn parse_dry_run() {
+        assert!(
+            !CollectOrphanedBlocksCommand::try_parse_from(["collect-orphaned-blocks"])
+                .unwrap()
+                .dry_run
+        );
+        assert!(
+            CollectOrphanedBlocksCommand::try_parse_from(["collect-orphaned-blocks", "--dry-run"])
+                .unwrap()
+                .dry_run
+        );
+    }
+
+    #[test]
+    fn orphaned_blocks_from_aggregation() {
+        // `$sum` counts as a 32-bit integer.
+        assert_eq!(
+            from_document::<OrphanedBlocksResult>(doc! { "_id": 42, "block_count": 7 }).unwrap(),
+            OrphanedBlocksResult {
+                milestone_index: 42.into(),
+                block_count: 7,
+            }
+        );
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use chronicle::{
+    db::{
+        mongodb::collections::{MilestoneCollection, OutputCollection, ProtocolUpdateCollection},
+        MongoDb,
+    },
+    model::{
+        ledger::{LedgerOutput, RentStructureBytes},
+        protocol::RentStructure,
+        tangle::MilestoneIndex,
+        ProtocolParameters,
+    },
+};
+use clap::Parser;
+use eyre::{bail, eyre};
+use futures::TryStreamExt;
+use packable::PackableExt;
+use tracing::{info, warn};
+
+use crate::config::ChronicleConfig;
+
+/// Checks how Chronicle would handle proposed protocol parameters, using the data of the current ledger, so that a
+/// network upgrade can be prepared for before the node applies it.
+///
+/// Every changed field is reported along with its effect on Chronicle. A changed rent structure is checked against
+/// all unspent outputs, to show how storage deposits would change and which outputs would no longer cover them.
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct CheckProtocolUpgradeCommand {
+    /// A file with the proposed protocol parameters, either as JSON (as returned by the `info` route) or as
+    /// hex-encoded bytes (as sent by the node).
+    #[arg(long, value_name = "PATH")]
+    file: PathBuf,
+    /// The ledger index whose unspent outputs are checked. Defaults to the ledger index of this instance.
+    #[arg(long)]
+    ledger_index: Option<MilestoneIndex>,
+}
+
+/// A field of the protocol parameters that would change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Change {
+    field: &'static str,
+    current: String,
+    proposed: String,
+    /// What the change means for the data that Chronicle stores or serves.
+    impact: &'static str,
+    /// Whether Chronicle would stop syncing.
+    breaking: bool,
+}
+
+/// Reads protocol parameters that are given either as JSON or as hex-encoded bytes.
+fn parse_parameters(input: &str) -> eyre::Result<ProtocolParameters> {
+    let input = input.trim();
+    let parameters = if input.starts_with("0x") {
+        let bytes = prefix_hex::decode::<Vec<u8>>(input).map_err(|e| eyre!("invalid hex: {e}"))?;
+        iota_sdk::types::block::protocol::ProtocolParameters::unpack_verified(bytes, &())
+            .map_err(|e| eyre!("invalid protocol parameters: {e:?}"))?
+    } else {
+        serde_json::from_str::<iota_sdk::types::block::protocol::ProtocolParameters>(input)?
+    };
+    Ok(parameters.into())
+}
+
+/// Lists the fields that differ between the current and the proposed protocol parameters.
+fn changes(current: &ProtocolParameters, proposed: &ProtocolParameters) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut check = |field, current: String, proposed: String, impact, breaking| {
+        if current != proposed {
+            changes.push(Change {
+                field,
+                current,
+                proposed,
+                impact,
+                breaking,
+            });
+        }
+    };
+    check(
+        "networkName",
+        current.network_name.clone(),
+        proposed.network_name.clone(),
+        "Chronicle refuses to sync a different network into the same database.",
+        true,
+    );
+    check(
+        "version",
+        current.version.to_string(),
+        proposed.version.to_string(),
+        "Blocks of the new version may not be readable by this version of Chronicle.",
+        proposed.version != iota_sdk::types::block::protocol::ProtocolParameters::default().protocol_version(),
+    );
+    check(
+        "bech32Hrp",
+        current.bech32_hrp.clone(),
+        proposed.bech32_hrp.clone(),
+        "Addresses are served with the new prefix, including those in responses that clients may have stored.",
+        false,
+    );
+    check(
+        "rentStructure",
+        format!("{:?}", current.rent_structure),
+        format!("{:?}", proposed.rent_structure),
+        "Storage deposits and the ledger size analytics are computed differently from the upgrade on.",
+        false,
+    );
+    check(
+        "tokenSupply",
+        current.token_supply.to_string(),
+        proposed.token_supply.to_string(),
+        "Outputs are validated against the new supply, and supply based analytics change their reference.",
+        false,
+    );
+    check(
+        "minPowScore",
+        current.min_pow_score.to_string(),
+        proposed.min_pow_score.to_string(),
+        "None, the value is only stored.",
+        false,
+    );
+    check(
+        "belowMaxDepth",
+        current.below_max_depth.to_string(),
+        proposed.below_max_depth.to_string(),
+        "None, the value is only stored.",
+        false,
+    );
+    changes
+}
+
+/// Computes the storage deposit that an output needs under the given rent structure.
+fn rent_cost(bytes: RentStructureBytes, rent_structure: &RentStructure) -> u64 {
+    rent_structure.v_byte_cost as u64
+        * (rent_structure.v_byte_factor_key as u64 * bytes.num_key_bytes
+            + rent_structure.v_byte_factor_data as u64 * bytes.num_data_bytes)
+}
+
+/// How a change of the rent structure affects the unspent outputs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct RentImpact {
+    outputs: usize,
+    current_deposit: u64,
+    proposed_deposit: u64,
+    /// The outputs whose amount no longer covers their storage deposit.
+    underfunded: usize,
+}
+
+impl RentImpact {
+    fn add(&mut self, output: &LedgerOutput, current: &RentStructure, proposed: &RentStructure) {
+        let amount = output.amount().0;
+        let proposed_cost = rent_cost(output.rent_structure, proposed);
+        self.outputs += 1;
+        self.current_deposit += rent_cost(output.rent_structure, current).min(amount);
+        self.proposed_deposit += proposed_cost.min(amount);
+        if amount < proposed_cost {
+            self.underfunded += 1;
+        }
+    }
+}
+
+impl CheckProtocolUpgradeCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        let proposed = parse_parameters(&std::fs::read_to_string(&self.file)?)?;
+
+        info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let local_ledger_index = db
+            .collection::<MilestoneCollection>()
+            .get_ledger_index()
+            .await?
+            .ok_or_else(|| eyre!("no milestones in the database"))?;
+        let ledger_index = self.ledger_index.unwrap_or(local_ledger_index);
+        if ledger_index > local_ledger_index {
+            bail!("ledger index {ledger_index} is beyond the ledger index {local_ledger_index} of this instance");
+        }
+        let current = db
+            .collection::<ProtocolUpdateCollection>()
+            .get_protocol_parameters_for_ledger_index(ledger_index)
+            .await?
+            .ok_or_else(|| eyre!("no protocol parameters for ledger index {ledger_index}"))?
+            .parameters;
+
+        let changes = changes(&current, &proposed);
+        if changes.is_empty() {
+            info!("The proposed protocol parameters equal the ones at ledger index {ledger_index}.");
+            return Ok(());
+        }
+        for change in &changes {
+            let message = format!(
+                "`{}` changes from `{}` to `{}`: {}",
+                change.field, change.current, change.proposed, change.impact
+            );
+            if change.breaking {
+                warn!("{message}");
+            } else {
+                info!("{message}");
+            }
+        }
+
+        if current.rent_structure != proposed.rent_structure {
+            info!("Checking the unspent outputs at ledger index {ledger_index} against the proposed rent structure.");
+            let mut impact = RentImpact::default();
+            let mut outputs = db
+                .collection::<OutputCollection>()
+                .get_unspent_output_stream(ledger_index)
+                .await?;
+            while let Some(output) = outputs.try_next().await? {
+                impact.add(&output, &current.rent_structure, &proposed.rent_structure);
+            }
+            info!(
+                "The storage deposit of {} unspent outputs changes from {} to {}.",
+                impact.outputs, impact.current_deposit, impact.proposed_deposit
+            );
+            if impact.underfunded > 0 {
+                warn!(
+                    "{} unspent outputs would no longer cover their storage deposit.",
+                    impact.underfunded
+                );
+            }
+        }
+
+        let breaking = changes.iter().filter(|change| change.breaking).count();
+        if breaking > 0 {
+            bail!(
+                "{breaking} of {} changes would stop Chronicle from syncing",
+                changes.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn parameters() -> ProtocolParameters {
+        iota_sdk::types::block::protocol::protocol_parameters().into()
+    }
+
+    #[test]
+    fn parse_json_and_bytes() {
+        let expected = iota_sdk::types::block::protocol::protocol_parameters();
+        let json = serde_json::to_string(&expected).unwrap();
+        assert_eq!(parse_parameters(&json).unwrap(), parameters());
+        let hex = prefix_hex::encode(expected.pack_to_vec());
+        assert_eq!(parse_parameters(&format!("{hex}\n")).unwrap(), parameters());
+        assert!(parse_parameters("0x00").is_err());
+    }
+
+    #[test]
+    fn report_changes() {
+        let current = parameters();
+        assert_eq!(changes(&current, &current), Vec::new());
+
+        let mut proposed = current.clone();
+        proposed.rent_structure.v_byte_cost *= 2;
+        proposed.network_name = "other".to_string();
+        let changes = changes(&current, &proposed);
+        assert_eq!(
+            changes.iter().map(|change| change.field).collect::<Vec<_>>(),
+            vec!["networkName", "rentStructure"]
+        );
+        assert!(changes[0].breaking);
+        assert!(!changes[1].breaking);
+    }
+
+    #[test]
+    fn rent_costs() {
+        let bytes = RentStructureBytes {
+            num_key_bytes: 10,
+            num_data_bytes: 100,
+        };
+        let rent_structure = RentStructure {
+            v_byte_cost: 100,
+            v_byte_factor_data: 1,
+            v_byte_factor_key: 10,
+        };
+        assert_eq!(rent_cost(bytes, &rent_structure), 20000);
+    }
+}
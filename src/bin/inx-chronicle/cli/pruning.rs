@@ -0,0 +1,75 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::pruning::config as pruning;
+
+#[derive(Args, Debug)]
+pub struct PruningArgs {
+    /// A JSON file containing retention rules per output kind or payload kind. Pruning is disabled without it. Pruned
+    /// data can no longer be served or used to recompute analytics.
+    #[arg(long, value_name = "FILEPATH", env = "RETENTION_FILE", default_value = None)]
+    pub retention_file: Option<String>,
+    /// How often to apply the retention rules.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = pruning::DEFAULT_INTERVAL)]
+    pub pruning_interval: std::time::Duration,
+    /// A directory that milestones are archived to before any of their data is pruned. Only data that was not pruned
+    /// yet can be archived.
+    #[cfg(feature = "archive")]
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "ARCHIVE_DIR",
+        conflicts_with = "archive_s3_endpoint"
+    )]
+    pub archive_dir: Option<std::path::PathBuf>,
+    /// The url of an S3-compatible service that milestones are archived to before any of their data is pruned.
+    #[cfg(feature = "archive")]
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "ARCHIVE_S3_ENDPOINT",
+        requires_all = ["archive_s3_bucket", "archive_s3_access_key", "archive_s3_secret_key"],
+    )]
+    pub archive_s3_endpoint: Option<String>,
+    /// The bucket that milestones are archived to.
+    #[cfg(feature = "archive")]
+    #[arg(long, value_name = "NAME", env = "ARCHIVE_S3_BUCKET")]
+    pub archive_s3_bucket: Option<String>,
+    /// The region of the archive bucket.
+    #[cfg(feature = "archive")]
+    #[arg(long, value_name = "REGION", env = "ARCHIVE_S3_REGION", default_value = pruning::DEFAULT_ARCHIVE_S3_REGION)]
+    pub archive_s3_region: String,
+    /// The id of the access key for the archive bucket.
+    #[cfg(feature = "archive")]
+    #[arg(long, value_name = "KEY", env = "ARCHIVE_S3_ACCESS_KEY")]
+    pub archive_s3_access_key: Option<String>,
+    /// The secret of the access key for the archive bucket.
+    #[cfg(feature = "archive")]
+    #[arg(long, value_name = "SECRET", env = "ARCHIVE_S3_SECRET_KEY")]
+    pub archive_s3_secret_key: Option<String>,
+}
+
+impl From<&PruningArgs> for pruning::PruningConfig {
+    fn from(value: &PruningArgs) -> Self {
+        Self {
+            retention_file: value.retention_file.clone(),
+            interval: value.pruning_interval,
+            #[cfg(feature = "archive")]
+            archive: if let Some(dir) = &value.archive_dir {
+                Some(pruning::ArchiveConfig::Directory(dir.clone()))
+            } else {
+                value.archive_s3_endpoint.as_ref().map(|endpoint| {
+                    pruning::ArchiveConfig::S3(chronicle::archive::S3Config {
+                        endpoint: endpoint.clone(),
+                        bucket: value.archive_s3_bucket.clone().unwrap_or_default(),
+                        region: value.archive_s3_region.clone(),
+                        access_key: value.archive_s3_access_key.clone().unwrap_or_default(),
+                        secret_key: value.archive_s3_secret_key.clone().unwrap_or_default(),
+                    })
+                })
+            },
+        }
+    }
+}
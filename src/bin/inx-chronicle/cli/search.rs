@@ -0,0 +1,41 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::search::config::{self as search, SearchEngine};
+
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    /// The search engine that searchable entities are mirrored into.
+    #[arg(long, value_name = "ENGINE", value_enum, default_value_t = SearchEngine::default())]
+    pub search_engine: SearchEngine,
+    /// The url pointing to the search engine.
+    #[arg(long, value_name = "URL", env = "SEARCH_URL", default_value = search::DEFAULT_URL)]
+    pub search_url: String,
+    /// The key used to authenticate with the search engine.
+    #[arg(long, value_name = "KEY", env = "SEARCH_API_KEY", default_value = None)]
+    pub search_api_key: Option<String>,
+    /// The name of the search engine index.
+    #[arg(long, value_name = "NAME", default_value = search::DEFAULT_INDEX_NAME)]
+    pub search_index_name: String,
+    /// How often to check for new milestones to mirror into the search engine.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = search::DEFAULT_POLL_INTERVAL)]
+    pub search_poll_interval: std::time::Duration,
+    /// Enable mirroring searchable entities into the search engine and the search API route.
+    #[arg(long, default_value_t = search::DEFAULT_ENABLED)]
+    pub enable_search: bool,
+}
+
+impl From<&SearchArgs> for search::SearchConfig {
+    fn from(value: &SearchArgs) -> Self {
+        Self {
+            enabled: value.enable_search,
+            engine: value.search_engine,
+            url: value.search_url.clone(),
+            api_key: value.search_api_key.clone(),
+            index_name: value.search_index_name.clone(),
+            poll_interval: value.search_poll_interval,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle::analytics::SpamThresholds;
+use clap::Args;
+
+use crate::spam_detection::config as spam_detection;
+
+#[derive(Args, Debug)]
+pub struct SpamDetectionArgs {
+    /// Check every milestone for spam, and write the flags to InfluxDb and the `spam_flags` collection.
+    #[arg(long, default_value_t = spam_detection::DEFAULT_ENABLED)]
+    pub spam_detection: bool,
+    /// How often the spam detection checks for new milestones.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = spam_detection::DEFAULT_POLL_INTERVAL)]
+    pub spam_poll_interval: std::time::Duration,
+    /// The number of previous milestones whose average block count a milestone is compared to.
+    #[arg(long, value_name = "MILESTONES", default_value_t = spam_detection::DEFAULT_WINDOW)]
+    pub spam_window: usize,
+    /// Flag milestones with more than this many times the average block count of the window.
+    #[arg(long, value_name = "FACTOR", default_value_t = spam_detection::DEFAULT_BLOCK_SPIKE_FACTOR)]
+    pub spam_block_spike_factor: f64,
+    /// Flag milestones in which at least this share of the blocks carry the same payload.
+    #[arg(long, value_name = "RATIO", default_value_t = spam_detection::DEFAULT_DUPLICATE_PAYLOAD_RATIO)]
+    pub spam_duplicate_payload_ratio: f64,
+    /// Flag milestones in which at least this share of the transactions are issued by the same address.
+    #[arg(long, value_name = "RATIO", default_value_t = spam_detection::DEFAULT_ISSUER_DOMINANCE_RATIO)]
+    pub spam_issuer_dominance_ratio: f64,
+    /// Never flag milestones with fewer blocks for a spike or duplicate payloads.
+    #[arg(long, value_name = "BLOCKS", default_value_t = spam_detection::DEFAULT_MIN_BLOCK_COUNT)]
+    pub spam_min_block_count: usize,
+    /// Never flag milestones with fewer transactions for issuer dominance.
+    #[arg(long, value_name = "TRANSACTIONS", default_value_t = spam_detection::DEFAULT_MIN_TRANSACTION_COUNT)]
+    pub spam_min_transaction_count: usize,
+}
+
+impl From<&SpamDetectionArgs> for spam_detection::SpamDetectionConfig {
+    fn from(value: &SpamDetectionArgs) -> Self {
+        Self {
+            enabled: value.spam_detection,
+            poll_interval: value.spam_poll_interval,
+            thresholds: SpamThresholds {
+                window: value.spam_window,
+                block_spike_factor: value.spam_block_spike_factor,
+                duplicate_payload_ratio: value.spam_duplicate_payload_ratio,
+                issuer_dominance_ratio: value.spam_issuer_dominance_ratio,
+                min_block_count: value.spam_min_block_count,
+                min_transaction_count: value.spam_min_transaction_count,
+            },
+        }
+    }
+}
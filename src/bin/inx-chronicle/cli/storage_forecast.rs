@@ -0,0 +1,37 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::storage_forecast::config as storage_forecast;
+
+#[derive(Args, Debug)]
+pub struct StorageForecastArgs {
+    /// How often to sample the storage used by every collection.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = storage_forecast::DEFAULT_INTERVAL)]
+    pub storage_sample_interval: std::time::Duration,
+    /// The period over which the growth of the collections is measured.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = storage_forecast::DEFAULT_WINDOW)]
+    pub storage_forecast_window: std::time::Duration,
+    /// How long storage samples are kept.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = storage_forecast::DEFAULT_RETENTION)]
+    pub storage_sample_retention: std::time::Duration,
+    /// How far ahead the storage usage is projected in the metrics.
+    #[arg(long, value_name = "DURATIONS", value_parser = super::parse_duration, value_delimiter = ',', default_value = storage_forecast::DEFAULT_HORIZONS)]
+    pub storage_forecast_horizons: Vec<std::time::Duration>,
+    /// Disable the storage forecast workflow.
+    #[arg(long, default_value_t = !storage_forecast::DEFAULT_ENABLED)]
+    pub disable_storage_forecast: bool,
+}
+
+impl From<&StorageForecastArgs> for storage_forecast::StorageForecastConfig {
+    fn from(value: &StorageForecastArgs) -> Self {
+        Self {
+            enabled: !value.disable_storage_forecast,
+            interval: value.storage_sample_interval,
+            window: value.storage_forecast_window,
+            retention: value.storage_sample_retention,
+            horizons: value.storage_forecast_horizons.clone(),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::token_distribution::config as token_distribution;
+
+#[derive(Args, Debug)]
+pub struct TokenDistributionArgs {
+    /// How often to recompute the holder statistics of every native token.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = token_distribution::DEFAULT_INTERVAL)]
+    pub token_distribution_interval: std::time::Duration,
+    /// Disable the native token distribution workflow.
+    #[arg(long, default_value_t = !token_distribution::DEFAULT_ENABLED)]
+    pub disable_token_distribution: bool,
+}
+
+impl From<&TokenDistributionArgs> for token_distribution::TokenDistributionConfig {
+    fn from(value: &TokenDistributionArgs) -> Self {
+        Self {
+            enabled: !value.disable_token_distribution,
+            interval: value.token_distribution_interval,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Args;
+
+use crate::views::config as views;
+
+#[derive(Args, Debug)]
+pub struct ViewsArgs {
+    /// A JSON file containing additional materialized view definitions.
+    #[arg(long, value_name = "FILEPATH", env = "VIEWS_FILE", default_value = None)]
+    pub views_file: Option<String>,
+    /// How often to check whether materialized views need to be refreshed.
+    #[arg(long, value_name = "DURATION", value_parser = super::parse_duration, default_value = views::DEFAULT_POLL_INTERVAL)]
+    pub views_poll_interval: std::time::Duration,
+    /// Disable the materialized view refresh workflow.
+    #[arg(long, default_value_t = !views::DEFAULT_ENABLED)]
+    pub disable_views: bool,
+}
+
+impl From<&ViewsArgs> for views::ViewsConfig {
+    fn from(value: &ViewsArgs) -> Self {
+        Self {
+            enabled: !value.disable_views,
+            definitions_file: value.views_file.clone(),
+            poll_interval: value.views_poll_interval,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+use eyre::{bail, eyre};
+use tokio::time::Instant;
+use tracing::{debug, info};
+
+use crate::config::ChronicleConfig;
+
+/// Waits until the configured dependencies are reachable and healthy.
+///
+/// This is meant for container setups where Chronicle may be started before the database or the node, so that the
+/// application does not fail on its first connection attempt.
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct WaitCommand {
+    /// The dependencies to wait for, separated by commas.
+    #[arg(long = "for", value_name = "DEPENDENCY", value_delimiter = ',', required = true)]
+    dependencies: Vec<Dependency>,
+    /// How long to wait in total before giving up.
+    #[arg(long, value_parser = super::parse_duration, default_value = "120s")]
+    timeout: Duration,
+    /// How long to wait between two attempts.
+    #[arg(long, value_parser = super::parse_duration, default_value = "2s")]
+    interval: Duration,
+    /// Run the application once all dependencies are available.
+    #[arg(short, long)]
+    run: bool,
+}
+
+/// A service that Chronicle depends on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Dependency {
+    /// The MongoDB server.
+    Mongodb,
+    /// The INX interface of the node, which must report itself as healthy.
+    #[cfg(feature = "inx")]
+    Inx,
+    /// The InfluxDB server.
+    #[cfg(feature = "influx")]
+    Influx,
+}
+
+impl std::fmt::Display for Dependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mongodb => write!(f, "MongoDB"),
+            #[cfg(feature = "inx")]
+            Self::Inx => write!(f, "INX"),
+            #[cfg(feature = "influx")]
+            Self::Influx => write!(f, "InfluxDB"),
+        }
+    }
+}
+
+impl Dependency {
+    /// Makes a single attempt to reach the dependency.
+    async fn check(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        match self {
+            Self::Mongodb => {
+                let db = chronicle::db::MongoDb::connect(&config.mongodb).await?;
+                db.get_databases().await?;
+            }
+            #[cfg(feature = "inx")]
+            Self::Inx => {
+                // Any healthy node is enough to start syncing.
+                let mut error = None;
+                for url in &config.inx.urls {
+                    let mut inx = match crate::inx::connect(&config.inx, url).await {
+                        Ok(inx) => inx,
+                        Err(e) => {
+                            error = Some(eyre!("node at `{url}` is not reachable: {e}"));
+                            continue;
+                        }
+                    };
+                    match inx.read_node_status().await {
+                        Ok(status) if status.is_healthy => return Ok(()),
+                        Ok(_) => error = Some(eyre!("node at `{url}` is not healthy")),
+                        Err(e) => error = Some(eyre!("node at `{url}` did not report its status: {e}")),
+                    }
+                }
+                if let Some(error) = error {
+                    return Err(error);
+                }
+            }
+            #[cfg(feature = "influx")]
+            Self::Influx => {
+                chronicle::db::influxdb::InfluxDb::connect(&config.influxdb).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WaitCommand {
+    /// Returns whether the application should be started afterwards.
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<bool> {
+        let deadline = Instant::now() + self.timeout;
+        for dependency in &self.dependencies {
+            info!("Waiting for {dependency}.");
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let error = match tokio::time::timeout(remaining, dependency.check(config)).await {
+                    Ok(Ok(())) => break,
+                    Ok(Err(e)) => e,
+                    Err(_) => eyre!("attempt did not complete"),
+                };
+                if Instant::now() + self.interval >= deadline {
+                    bail!(
+                        "{dependency} was not available within {}: {error}",
+                        humantime::format_duration(self.timeout)
+                    );
+                }
+                debug!("{dependency} is not available yet: {error}");
+                tokio::time::sleep(self.interval).await;
+            }
+            info!("{dependency} is available.");
+        }
+        Ok(self.run)
+    }
+}
+
+#[cfg(all(test, feature = "inx", feature = "influx"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_dependencies() {
+        let cmd = WaitCommand::try_parse_from(["wait", "--for", "mongodb,inx,influx", "--timeout", "1m"]).unwrap();
+        assert_eq!(
+            cmd.dependencies,
+            vec![Dependency::Mongodb, Dependency::Inx, Dependency::Influx]
+        );
+        assert_eq!(cmd.timeout, Duration::from_secs(60));
+        assert_eq!(cmd.interval, Duration::from_secs(2));
+        assert!(!cmd.run);
+
+        assert!(WaitCommand::try_parse_from(["wait"]).is_err());
+        assert!(WaitCommand::try_parse_from(["wait", "--for", "postgres"]).is_err());
+    }
+}
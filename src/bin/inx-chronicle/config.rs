@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use chronicle::db::MongoDbConfig;
+use thiserror::Error;
 
 /// Configuration of Chronicle.
 #[derive(Clone, Default, Debug)]
@@ -11,6 +12,567 @@ pub struct ChronicleConfig {
     pub influxdb: chronicle::db::influxdb::InfluxDbConfig,
     #[cfg(feature = "api")]
     pub api: crate::api::ApiConfig,
+    #[cfg(feature = "explorer")]
+    pub naming: super::naming::NamingConfig,
     #[cfg(feature = "inx")]
     pub inx: super::inx::InxConfig,
+    #[cfg(feature = "mqtt")]
+    pub mqtt: super::mqtt::MqttConfig,
+    #[cfg(feature = "analytics")]
+    pub analytics_worker: super::analytics_worker::AnalyticsWorkerConfig,
+    #[cfg(feature = "analytics")]
+    pub spam_detection: super::spam_detection::SpamDetectionConfig,
+    #[cfg(feature = "explorer")]
+    pub views: super::views::ViewsConfig,
+    #[cfg(feature = "explorer")]
+    pub token_distribution: super::token_distribution::TokenDistributionConfig,
+    #[cfg(feature = "explorer")]
+    pub ledger_snapshot: super::ledger_snapshot::LedgerSnapshotConfig,
+    #[cfg(feature = "explorer")]
+    pub address_filter: super::address_filter::AddressFilterConfig,
+    pub pruning: super::pruning::PruningConfig,
+    pub storage_forecast: super::storage_forecast::StorageForecastConfig,
+    #[cfg(feature = "search")]
+    pub search: super::search::SearchConfig,
+}
+
+/// A single problem found in the configuration, along with the argument that caused it.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("{source_arg}: {message}")]
+pub struct ConfigIssue {
+    /// The command-line argument (and environment variable, if any) that holds the invalid value.
+    pub source_arg: &'static str,
+    pub message: String,
+}
+
+/// All problems found while validating a [`ChronicleConfig`].
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub struct ConfigValidationError(pub Vec<ConfigIssue>);
+
+impl std::fmt::Display for ConfigValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid configuration ({} problem(s)):", self.0.len())?;
+        for issue in &self.0 {
+            write!(f, "\n  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ChronicleConfig {
+    /// Validates every section of the configuration and reports all problems at once.
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        let mut issues = Vec::new();
+        let mut report = |source_arg, message: String| issues.push(ConfigIssue { source_arg, message });
+
+        if let Err(e) = mongodb::options::ConnectionString::parse(&self.mongodb.conn_str) {
+            report(
+                "--mongodb-conn-str (MONGODB_CONN_STR)",
+                format!("invalid connection string `{}`: {e}", self.mongodb.conn_str),
+            );
+        }
+        if self.mongodb.database_name.is_empty() {
+            report(
+                "--mongodb-database-name (MONGODB_DB_NAME)",
+                "database name must not be empty".to_string(),
+            );
+        }
+
+        #[cfg(feature = "influx")]
+        if let Err(message) = check_url(&self.influxdb.url, &["http", "https"]) {
+            report("--influxdb-url (INFLUXDB_URL)", message);
+        }
+        #[cfg(feature = "analytics")]
+        if self.influxdb.analytics_enabled && self.influxdb.analytics_database_name.is_empty() {
+            report(
+                "--analytics-database-name",
+                "database name must not be empty while analytics are enabled".to_string(),
+            );
+        }
+        #[cfg(feature = "analytics")]
+        if self.analytics_worker.enabled {
+            if !self.influxdb.analytics_enabled {
+                report(
+                    "--analytics-worker",
+                    "the analytics worker requires analytics to be enabled".to_string(),
+                );
+            }
+            if self.analytics_worker.poll_interval.is_zero() {
+                report(
+                    "--analytics-poll-interval",
+                    "poll interval must be a positive duration".to_string(),
+                );
+            }
+        }
+        #[cfg(feature = "analytics")]
+        if self.spam_detection.enabled {
+            if !self.influxdb.analytics_enabled {
+                report(
+                    "--spam-detection",
+                    "the spam detection requires analytics to be enabled".to_string(),
+                );
+            }
+            if self.spam_detection.poll_interval.is_zero() {
+                report(
+                    "--spam-poll-interval",
+                    "poll interval must be a positive duration".to_string(),
+                );
+            }
+            let thresholds = &self.spam_detection.thresholds;
+            if !(thresholds.block_spike_factor > 0.0 && thresholds.block_spike_factor.is_finite()) {
+                report(
+                    "--spam-block-spike-factor",
+                    format!("factor `{}` must be positive", thresholds.block_spike_factor),
+                );
+            }
+            for (arg, ratio) in [
+                ("--spam-duplicate-payload-ratio", thresholds.duplicate_payload_ratio),
+                ("--spam-issuer-dominance-ratio", thresholds.issuer_dominance_ratio),
+            ] {
+                if !(ratio > 0.0 && ratio <= 1.0) {
+                    report(arg, format!("ratio `{ratio}` must be greater than 0 and at most 1"));
+                }
+            }
+        }
+        #[cfg(feature = "metrics")]
+        if self.influxdb.metrics_enabled && self.influxdb.metrics_database_name.is_empty() {
+            report(
+                "--metrics-database-name",
+                "database name must not be empty while metrics are enabled".to_string(),
+            );
+        }
+
+        #[cfg(feature = "inx")]
+        if self.inx.enabled {
+            if self.inx.urls.is_empty() {
+                report("--inx-url (INX_URL)", "at least one url is required".to_string());
+            }
+            for url in &self.inx.urls {
+                if let Err(message) = check_url(url, &["http", "https"]) {
+                    report("--inx-url (INX_URL)", message);
+                }
+            }
+            match (&self.inx.tls_client_cert, &self.inx.tls_client_key) {
+                (Some(_), None) => report(
+                    "--inx-tls-client-key (INX_TLS_CLIENT_KEY)",
+                    "key file must be set along with the client certificate file".to_string(),
+                ),
+                (None, Some(_)) => report(
+                    "--inx-tls-client-cert (INX_TLS_CLIENT_CERT)",
+                    "client certificate file must be set along with the key file".to_string(),
+                ),
+                _ => (),
+            }
+            if self.inx.reconnect_max_delay.is_zero() {
+                report(
+                    "--inx-reconnect-max-delay",
+                    "delay must be a positive duration".to_string(),
+                );
+            }
+            if self.inx.failover_errors == 0 {
+                report("--inx-failover-errors", "count must be greater than zero".to_string());
+            }
+            if self.inx.pending_transactions_enabled && self.inx.pending_transaction_ttl.is_zero() {
+                report(
+                    "--inx-pending-transaction-ttl",
+                    "time to live must be a positive duration".to_string(),
+                );
+            }
+            if self.inx.catch_up_threshold > 0 && self.inx.catch_up_batch_size == 0 {
+                report(
+                    "--inx-catch-up-batch-size",
+                    "batch size must be greater than zero".to_string(),
+                );
+            }
+            if self.inx.milestones_in_flight == 0 {
+                report(
+                    "--inx-milestones-in-flight",
+                    "count must be greater than zero".to_string(),
+                );
+            }
+            if self.inx.node_health_enabled && self.inx.node_health_interval.is_zero() {
+                report(
+                    "--inx-node-health-interval",
+                    "sample interval must be a positive duration".to_string(),
+                );
+            }
+        }
+
+        #[cfg(feature = "mqtt")]
+        if self.mqtt.enabled {
+            if let Err(message) = check_url(&self.mqtt.url, &["mqtt"]) {
+                report("--mqtt-url (MQTT_URL)", message);
+            }
+            if self.mqtt.client_id.is_empty() {
+                report("--mqtt-client-id", "client id must not be empty".to_string());
+            }
+            if self.mqtt.capacity == 0 {
+                report("--mqtt-capacity", "capacity must be greater than zero".to_string());
+            }
+        }
+
+        #[cfg(feature = "api")]
+        if self.api.enabled {
+            if self.api.port == 0 {
+                report("--api-port", "port must not be zero".to_string());
+            }
+            if self.api.max_page_size == 0 {
+                report(
+                    "--max-page-size",
+                    "maximum page size must be greater than zero".to_string(),
+                );
+            }
+            if self.api.request_timeout.is_zero() {
+                report(
+                    "--api-request-timeout",
+                    "request timeout must be a positive duration".to_string(),
+                );
+            }
+            if self.api.strict_consistency && self.api.consistency_max_wait >= self.api.request_timeout {
+                report(
+                    "--api-consistency-max-wait",
+                    "maximum wait must be shorter than the request timeout".to_string(),
+                );
+            }
+            if self.api.query_concurrency == 0 {
+                report(
+                    "--api-query-concurrency",
+                    "concurrency must be greater than zero".to_string(),
+                );
+            }
+            if self.api.query_cache_enabled && self.api.query_cache_ttl.is_zero() {
+                report(
+                    "--api-query-cache-ttl",
+                    "time to live must be a positive duration".to_string(),
+                );
+            }
+            if self.api.jwt_expiration.is_zero() {
+                report(
+                    "--jwt-expiration",
+                    "token expiration must be a positive duration".to_string(),
+                );
+            }
+            if self.api.jwt_salt.is_empty() {
+                report("--jwt-salt (JWT_SALT)", "salt must not be empty".to_string());
+            }
+            if let Err(e) = tower_http::cors::AllowOrigin::try_from(self.api.allow_origins.clone()) {
+                report("--allow-origin", e.to_string());
+            }
+            if let Some(Err(e)) = self.api.deprecated_since.as_deref().map(humantime::parse_rfc3339_weak) {
+                report("--api-deprecated-since", format!("invalid date: {e}"));
+            }
+            if let Some(Err(e)) = self.api.sunset.as_deref().map(humantime::parse_rfc3339_weak) {
+                report("--api-sunset", format!("invalid date: {e}"));
+            }
+            if self.api.sunset.is_some() && self.api.deprecated_since.is_none() {
+                report(
+                    "--api-sunset",
+                    "only deprecated versions have a sunset, which requires --api-deprecated-since".to_string(),
+                );
+            }
+            match (&self.api.tls_cert_file, &self.api.tls_key_file) {
+                (Some(_), None) => report(
+                    "--api-tls-key (API_TLS_KEY)",
+                    "key file must be set along with the certificate file".to_string(),
+                ),
+                (None, Some(_)) => report(
+                    "--api-tls-cert (API_TLS_CERT)",
+                    "certificate file must be set along with the key file".to_string(),
+                ),
+                (Some(_), Some(_)) if self.api.tls_reload_interval.is_zero() => report(
+                    "--api-tls-reload-interval",
+                    "reload interval must be a positive duration".to_string(),
+                ),
+                _ => (),
+            }
+        }
+
+        #[cfg(feature = "explorer")]
+        if self.api.enabled {
+            for (source_arg, url, placeholder) in [
+                (
+                    "--naming-resolve-url (NAMING_RESOLVE_URL)",
+                    &self.naming.resolve_url,
+                    "{name}",
+                ),
+                (
+                    "--naming-reverse-url (NAMING_REVERSE_URL)",
+                    &self.naming.reverse_url,
+                    "{address}",
+                ),
+            ] {
+                if let Some(url) = url {
+                    if let Err(message) = check_url(url, &["http", "https"]) {
+                        report(source_arg, message);
+                    } else if !url.contains(placeholder) {
+                        report(source_arg, format!("url must contain the `{placeholder}` placeholder"));
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "explorer")]
+        if self.views.enabled {
+            if self.views.poll_interval.is_zero() {
+                report(
+                    "--views-poll-interval",
+                    "poll interval must be a positive duration".to_string(),
+                );
+            }
+            if let Some(path) = &self.views.definitions_file {
+                if !std::path::Path::new(path).is_file() {
+                    report("--views-file (VIEWS_FILE)", format!("file `{path}` does not exist"));
+                }
+            }
+        }
+
+        #[cfg(feature = "explorer")]
+        if self.token_distribution.enabled && self.token_distribution.interval.is_zero() {
+            report(
+                "--token-distribution-interval",
+                "interval must be a positive duration".to_string(),
+            );
+        }
+
+        #[cfg(feature = "explorer")]
+        if self.ledger_snapshot.enabled {
+            if self.ledger_snapshot.interval == 0 {
+                report(
+                    "--ledger-snapshot-interval",
+                    "interval must be greater than zero".to_string(),
+                );
+            }
+            if self.ledger_snapshot.poll_interval.is_zero() {
+                report(
+                    "--ledger-snapshot-poll-interval",
+                    "poll interval must be a positive duration".to_string(),
+                );
+            }
+        }
+
+        #[cfg(feature = "explorer")]
+        if self.address_filter.enabled {
+            if self.address_filter.interval == 0 {
+                report(
+                    "--address-filter-interval",
+                    "interval must be greater than zero".to_string(),
+                );
+            }
+            if self.address_filter.poll_interval.is_zero() {
+                report(
+                    "--address-filter-poll-interval",
+                    "poll interval must be a positive duration".to_string(),
+                );
+            }
+            let rate = self.address_filter.false_positive_rate;
+            if !(rate > 0.0 && rate < 1.0) {
+                report(
+                    "--address-filter-false-positive-rate",
+                    format!("rate `{rate}` must be between 0 and 1"),
+                );
+            }
+        }
+
+        if let Some(path) = &self.pruning.retention_file {
+            if !std::path::Path::new(path).is_file() {
+                report(
+                    "--retention-file (RETENTION_FILE)",
+                    format!("file `{path}` does not exist"),
+                );
+            }
+            if self.pruning.interval.is_zero() {
+                report("--pruning-interval", "interval must be a positive duration".to_string());
+            }
+        }
+
+        #[cfg(feature = "archive")]
+        if let Some(super::pruning::config::ArchiveConfig::S3(s3)) = &self.pruning.archive {
+            if let Err(e) = check_url(&s3.endpoint, &["http", "https"]) {
+                report("--archive-s3-endpoint (ARCHIVE_S3_ENDPOINT)", e);
+            }
+            if s3.bucket.is_empty() || s3.bucket.contains('/') {
+                report(
+                    "--archive-s3-bucket (ARCHIVE_S3_BUCKET)",
+                    format!("invalid bucket name `{}`", s3.bucket),
+                );
+            }
+        }
+
+        if self.storage_forecast.enabled {
+            if self.storage_forecast.interval.is_zero() {
+                report(
+                    "--storage-sample-interval",
+                    "interval must be a positive duration".to_string(),
+                );
+            }
+            if self.storage_forecast.window.is_zero() {
+                report(
+                    "--storage-forecast-window",
+                    "window must be a positive duration".to_string(),
+                );
+            }
+            if self.storage_forecast.retention < self.storage_forecast.window {
+                report(
+                    "--storage-sample-retention",
+                    "retention must not be shorter than the forecast window".to_string(),
+                );
+            }
+        }
+
+        #[cfg(feature = "search")]
+        if self.search.enabled {
+            if let Err(message) = check_url(&self.search.url, &["http", "https"]) {
+                report("--search-url (SEARCH_URL)", message);
+            }
+            if self.search.index_name.is_empty() {
+                report("--search-index-name", "index name must not be empty".to_string());
+            }
+            if self.search.poll_interval.is_zero() {
+                report(
+                    "--search-poll-interval",
+                    "poll interval must be a positive duration".to_string(),
+                );
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigValidationError(issues))
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "archive",
+    feature = "explorer",
+    feature = "influx",
+    feature = "inx",
+    feature = "mqtt",
+    feature = "search"
+))]
+fn check_url(url: &str, schemes: &[&str]) -> Result<(), String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("invalid url `{url}`: {e}"))?;
+    if !schemes.contains(&parsed.scheme()) {
+        return Err(format!(
+            "unsupported scheme `{}` in `{url}`, expected one of: {}",
+            parsed.scheme(),
+            schemes.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(ChronicleConfig::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn reports_all_issues() {
+        let mut config = ChronicleConfig::default();
+        config.mongodb.conn_str = "localhost:27017".to_string();
+        config.mongodb.database_name = String::new();
+        #[allow(unused_mut)]
+        let mut expected = 2;
+        #[cfg(feature = "inx")]
+        {
+            config.inx.urls = vec!["ws://localhost:9029".to_string()];
+            expected += 1;
+        }
+        #[cfg(feature = "api")]
+        {
+            config.api.max_page_size = 0;
+            expected += 1;
+        }
+
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), expected);
+        assert!(err.to_string().contains("--mongodb-conn-str"));
+    }
+
+    #[cfg(feature = "inx")]
+    #[test]
+    fn disabled_sections_are_skipped() {
+        let mut config = ChronicleConfig::default();
+        config.inx.enabled = false;
+        config.inx.urls = vec!["not a url".to_string()];
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[cfg(feature = "inx")]
+    #[test]
+    fn every_inx_url_is_checked() {
+        let mut config = ChronicleConfig::default();
+        config.inx.urls = vec!["https://node-1:9029".to_string(), "ws://node-2:9029".to_string()];
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), 1);
+        assert!(err.to_string().contains("node-2"));
+    }
+
+    #[cfg(feature = "inx")]
+    #[test]
+    fn inx_tls_needs_client_certificate_and_key() {
+        let mut config = ChronicleConfig::default();
+        config.inx.urls = vec!["https://node:9029".to_string()];
+        config.inx.tls_client_key = Some("client.key".to_string());
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), 1);
+        assert_eq!(err.0[0].source_arg, "--inx-tls-client-cert (INX_TLS_CLIENT_CERT)");
+        config.inx.tls_client_cert = Some("client.pem".to_string());
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[cfg(feature = "explorer")]
+    #[test]
+    fn naming_urls_need_placeholders() {
+        let mut config = ChronicleConfig::default();
+        config.naming.resolve_url = Some("https://names.example.com/resolve/{name}".to_string());
+        config.naming.reverse_url = Some("https://names.example.com/reverse".to_string());
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), 1);
+        assert_eq!(err.0[0].source_arg, "--naming-reverse-url (NAMING_REVERSE_URL)");
+    }
+
+    #[cfg(feature = "api")]
+    #[test]
+    fn tls_needs_certificate_and_key() {
+        let mut config = ChronicleConfig::default();
+        config.api.tls_cert_file = Some("cert.pem".to_string());
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), 1);
+        assert_eq!(err.0[0].source_arg, "--api-tls-key (API_TLS_KEY)");
+        config.api.tls_key_file = Some("key.pem".to_string());
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn analytics_worker_requires_analytics() {
+        let mut config = ChronicleConfig::default();
+        config.analytics_worker.enabled = true;
+        assert_eq!(config.validate(), Ok(()));
+        config.influxdb.analytics_enabled = false;
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), 1);
+        assert_eq!(err.0[0].source_arg, "--analytics-worker");
+    }
+
+    #[cfg(feature = "analytics")]
+    #[test]
+    fn spam_detection_ratios_must_be_shares() {
+        let mut config = ChronicleConfig::default();
+        config.spam_detection.enabled = true;
+        assert_eq!(config.validate(), Ok(()));
+        config.spam_detection.thresholds.issuer_dominance_ratio = 1.5;
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.0.len(), 1);
+        assert_eq!(err.0[0].source_arg, "--spam-issuer-dominance-ratio");
+    }
 }
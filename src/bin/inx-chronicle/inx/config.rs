@@ -1,28 +1,145 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use chronicle::model::tangle::MilestoneIndex;
+use std::time::Duration;
+
+use chronicle::{inx::InxTlsConfig, model::tangle::MilestoneIndex};
 
 pub const DEFAULT_ENABLED: bool = true;
 pub const DEFAULT_URL: &str = "http://localhost:9029";
+pub const DEFAULT_FAILOVER_ERRORS: usize = 3;
+pub const DEFAULT_RECONNECT_MAX_DELAY: &str = "1m";
 pub const DEFAULT_SYNC_START: u32 = 0;
+pub const DEFAULT_PENDING_TRANSACTIONS_ENABLED: bool = false;
+pub const DEFAULT_PENDING_TRANSACTION_TTL: &str = "5m";
+pub const DEFAULT_AUDIT_DUPLICATES: bool = false;
+pub const DEFAULT_CATCH_UP_THRESHOLD: u32 = 100;
+pub const DEFAULT_CATCH_UP_BATCH_SIZE: usize = 5000;
+pub const DEFAULT_MILESTONES_IN_FLIGHT: usize = 4;
+pub const DEFAULT_LEAN_STORAGE: bool = false;
+pub const DEFAULT_STANDBY: bool = false;
+pub const DEFAULT_SYNC_REPORT_RETENTION: u32 = 60480;
+pub const DEFAULT_NODE_HEALTH_ENABLED: bool = true;
+pub const DEFAULT_NODE_HEALTH_INTERVAL: &str = "1m";
+pub const DEFAULT_NODE_HEALTH_RETENTION: &str = "7d";
+
+/// How the references between the documents of a milestone are checked while it is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum IntegrityCheckMode {
+    /// The references are not checked.
+    Off,
+    /// Violations are logged and noted in the sync report, but the milestone is still synced.
+    Warn,
+    /// Syncing stops at the first violation, before the milestone is checkpointed.
+    Reject,
+}
+
+impl Default for IntegrityCheckMode {
+    fn default() -> Self {
+        Self::Off
+    }
+}
 
 /// Configuration for an INX connection.
 #[derive(Clone, Debug)]
 pub struct InxConfig {
     pub enabled: bool,
-    /// The bind address of node's INX interface.
-    pub url: String,
+    /// The bind addresses of the INX interfaces of the nodes, in the order they are tried.
+    pub urls: Vec<String>,
+    /// How often the connection to an endpoint may be lost without syncing a milestone before the next one is used.
+    pub failover_errors: usize,
+    /// The longest delay between reconnects to INX, which grows exponentially up to this.
+    pub reconnect_max_delay: Duration,
+    /// The PEM file with the certificate authority that `https` endpoints are verified with, instead of the native
+    /// root certificates.
+    pub tls_ca_cert: Option<String>,
+    /// The PEM file with the client certificate that is presented to `https` endpoints.
+    pub tls_client_cert: Option<String>,
+    /// The PEM file with the private key of the client certificate.
+    pub tls_client_key: Option<String>,
     /// The milestone at which synchronization should begin.
     pub sync_start_milestone: MilestoneIndex,
+    /// Whether transactions are recorded as pending as soon as the node receives them.
+    pub pending_transactions_enabled: bool,
+    /// How long a pending transaction is kept if no milestone references it.
+    pub pending_transaction_ttl: Duration,
+    /// Whether documents that are dropped as duplicates while syncing are reported.
+    pub audit_duplicates: bool,
+    /// Whether spent outputs and the previous milestone are checked to be written before a milestone is.
+    pub integrity_checks: IntegrityCheckMode,
+    /// How many milestones Chronicle has to be behind the node to sync in catch-up mode, which defers the indexing of
+    /// block parents and inline analytics. `0` disables catch-up mode.
+    pub catch_up_threshold: u32,
+    /// The insert batch size used in catch-up mode.
+    pub catch_up_batch_size: usize,
+    /// How many milestones are written at the same time. While the blocks of a milestone are written, the outputs of
+    /// the next one already are, but milestones are still checkpointed in order. `1` syncs one milestone after the
+    /// other.
+    pub milestones_in_flight: usize,
+    /// Whether blocks are stored without their raw bytes, which are then reconstructed from the stored blocks and
+    /// outputs when requested.
+    pub lean_storage: bool,
+    /// Whether syncing waits until the instance is promoted, while another instance writes to the database.
+    pub standby: bool,
+    /// How many of the most recent milestones keep a sync report. `0` disables the reports.
+    pub sync_report_retention: u32,
+    /// Whether the status of the node is sampled periodically.
+    pub node_health_enabled: bool,
+    /// How often the status of the node is sampled.
+    pub node_health_interval: Duration,
+    /// How long samples of the node status are kept.
+    pub node_health_retention: Duration,
 }
 
 impl Default for InxConfig {
     fn default() -> Self {
         Self {
             enabled: DEFAULT_ENABLED,
-            url: DEFAULT_URL.to_string(),
+            urls: vec![DEFAULT_URL.to_string()],
+            failover_errors: DEFAULT_FAILOVER_ERRORS,
+            reconnect_max_delay: DEFAULT_RECONNECT_MAX_DELAY
+                .parse::<humantime::Duration>()
+                .unwrap()
+                .into(),
+            tls_ca_cert: None,
+            tls_client_cert: None,
+            tls_client_key: None,
             sync_start_milestone: DEFAULT_SYNC_START.into(),
+            pending_transactions_enabled: DEFAULT_PENDING_TRANSACTIONS_ENABLED,
+            pending_transaction_ttl: DEFAULT_PENDING_TRANSACTION_TTL
+                .parse::<humantime::Duration>()
+                .unwrap()
+                .into(),
+            audit_duplicates: DEFAULT_AUDIT_DUPLICATES,
+            integrity_checks: Default::default(),
+            catch_up_threshold: DEFAULT_CATCH_UP_THRESHOLD,
+            catch_up_batch_size: DEFAULT_CATCH_UP_BATCH_SIZE,
+            milestones_in_flight: DEFAULT_MILESTONES_IN_FLIGHT,
+            lean_storage: DEFAULT_LEAN_STORAGE,
+            standby: DEFAULT_STANDBY,
+            sync_report_retention: DEFAULT_SYNC_REPORT_RETENTION,
+            node_health_enabled: DEFAULT_NODE_HEALTH_ENABLED,
+            node_health_interval: DEFAULT_NODE_HEALTH_INTERVAL
+                .parse::<humantime::Duration>()
+                .unwrap()
+                .into(),
+            node_health_retention: DEFAULT_NODE_HEALTH_RETENTION
+                .parse::<humantime::Duration>()
+                .unwrap()
+                .into(),
         }
     }
 }
+
+impl InxConfig {
+    /// Reads the certificates for connections to `https` endpoints.
+    pub fn tls(&self) -> std::io::Result<InxTlsConfig> {
+        Ok(InxTlsConfig {
+            ca_cert: self.tls_ca_cert.as_ref().map(std::fs::read).transpose()?,
+            identity: match (&self.tls_client_cert, &self.tls_client_key) {
+                (Some(cert), Some(key)) => Some((std::fs::read(cert)?, std::fs::read(key)?)),
+                _ => None,
+            },
+        })
+    }
+}
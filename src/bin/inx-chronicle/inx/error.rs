@@ -1,12 +1,25 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use chronicle::model::tangle::MilestoneIndex;
+use chronicle::{model::tangle::MilestoneIndex, tangle::HookError};
 use thiserror::Error;
 
+use super::integrity::IntegrityViolation;
+
 #[derive(Debug, Error)]
 pub enum InxWorkerError {
-    #[error("expected INX address with format `http://<address>:<port>`, but found `{0}`")]
+    #[error("ingestion hook failed at milestone {index}: {source}")]
+    Hook {
+        index: MilestoneIndex,
+        #[source]
+        source: HookError,
+    },
+    #[error("integrity check failed at milestone {index}: {violation}")]
+    IntegrityViolation {
+        index: MilestoneIndex,
+        violation: IntegrityViolation,
+    },
+    #[error("expected INX address with format `http(s)://<address>:<port>`, but found `{0}`")]
     InvalidAddress(String),
     #[error("invalid unspent output stream: found ledger index {found}, expected {expected}")]
     InvalidUnspentOutputIndex {
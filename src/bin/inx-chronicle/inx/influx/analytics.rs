@@ -10,9 +10,8 @@ use chronicle::{
         mongodb::collections::{ApplicationStateCollection, OutputCollection},
         MongoDb,
     },
-    inx::Inx,
     model::tangle::MilestoneIndex,
-    tangle::Milestone,
+    tangle::{InputSource, Milestone, Tangle},
 };
 use futures::TryStreamExt;
 
@@ -49,9 +48,9 @@ impl AnalyticsInfo {
 }
 
 impl InxWorker {
-    pub async fn update_analytics<'a>(
+    pub async fn update_analytics<'a, I: InputSource>(
         &self,
-        milestone: &Milestone<'a, Inx>,
+        milestone: &Milestone<'a, I>,
         AnalyticsInfo {
             analytics_choices,
             state,
@@ -72,8 +71,16 @@ impl InxWorker {
 
                     let analytics = analytics_choices
                         .iter()
-                        .map(|choice| Analytic::init(choice, &milestone.protocol_params, &ledger_state))
-                        .collect::<Vec<_>>();
+                        .map(|choice| {
+                            Ok(Analytic::init(
+                                choice,
+                                &milestone.protocol_params,
+                                &ledger_state,
+                                influx_db.config().analytics_spill_dir.as_deref(),
+                            )?
+                            .with_sampling_interval(influx_db.config().sampling_interval(choice)))
+                        })
+                        .collect::<eyre::Result<Vec<_>>>()?;
                     *state = Some(AnalyticsState {
                         analytics,
                         prev_protocol_params: milestone.protocol_params.clone(),
@@ -84,9 +91,47 @@ impl InxWorker {
                 milestone
                     .update_analytics(&mut state.as_mut().unwrap().analytics, influx_db)
                     .await?;
+                // Lets the analytics worker continue from here if analytics are moved to it.
+                self.db
+                    .collection::<ApplicationStateCollection>()
+                    .set_analytics_index(milestone.at.milestone_index)
+                    .await?;
             }
         }
 
         Ok(())
     }
+
+    /// Computes the analytics that were deferred in catch-up mode from the milestones stored in the database, up to
+    /// and including `end_index`.
+    pub async fn catch_up_analytics(
+        &self,
+        end_index: MilestoneIndex,
+        analytics_info: &mut AnalyticsInfo,
+    ) -> eyre::Result<()> {
+        if !matches!(&self.influx_db, Some(influx_db) if influx_db.config().analytics_enabled) {
+            return Ok(());
+        }
+        let start_index = match self
+            .db
+            .collection::<ApplicationStateCollection>()
+            .get_analytics_index()
+            .await?
+        {
+            Some(analytics_index) => analytics_index + 1,
+            None => analytics_info.synced_index,
+        }
+        .max(analytics_info.synced_index);
+        if start_index > end_index {
+            return Ok(());
+        }
+
+        let tangle = Tangle::from(self.db.clone());
+        let mut milestone_stream = tangle.milestone_stream(start_index..=end_index).await?;
+        while let Some(milestone) = milestone_stream.try_next().await? {
+            self.update_analytics(&milestone, analytics_info).await?;
+        }
+
+        Ok(())
+    }
 }
@@ -6,14 +6,16 @@ pub mod analytics;
 
 use chronicle::{inx::Inx, tangle::Milestone};
 
-use super::{InxWorker, InxWorkerError};
+#[cfg(feature = "analytics")]
+use super::InxWorkerError;
+use super::InxWorker;
 
 impl InxWorker {
+    #[cfg_attr(not(feature = "analytics"), allow(unused_variables))]
     pub async fn update_influx<'a>(
         &self,
         milestone: &Milestone<'a, Inx>,
         #[cfg(feature = "analytics")] analytics_info: Option<&mut analytics::AnalyticsInfo>,
-        #[cfg(feature = "metrics")] milestone_start_time: std::time::Instant,
     ) -> eyre::Result<()> {
         #[cfg(all(feature = "analytics", feature = "metrics"))]
         let analytics_start_time = std::time::Instant::now();
@@ -41,22 +43,72 @@ impl InxWorker {
             }
         }
 
-        #[cfg(feature = "metrics")]
+        Ok(())
+    }
+
+    /// Records the documents that were dropped as duplicates while syncing a milestone, per collection.
+    #[cfg(feature = "metrics")]
+    pub async fn update_duplicate_metrics(
+        &self,
+        milestone_index: chronicle::model::tangle::MilestoneIndex,
+        duplicates: &std::collections::BTreeMap<&'static str, usize>,
+    ) -> eyre::Result<()> {
+        if let Some(influx_db) = &self.influx_db {
+            if influx_db.config().metrics_enabled {
+                let time = chrono::Utc::now();
+                for (collection, count) in duplicates {
+                    influx_db
+                        .metrics()
+                        .insert(chronicle::metrics::DuplicateMetrics {
+                            time,
+                            milestone_index,
+                            collection: collection.to_string(),
+                            duplicates: *count as u64,
+                            chronicle_version: std::env!("CARGO_PKG_VERSION").to_string(),
+                        })
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the time spent syncing a milestone, in total and per phase.
+    #[cfg(feature = "metrics")]
+    pub async fn update_sync_metrics<'a>(
+        &self,
+        milestone: &Milestone<'a, Inx>,
+        phases: &super::SyncPhases,
+    ) -> eyre::Result<()> {
         if let Some(influx_db) = &self.influx_db {
             if influx_db.config().metrics_enabled {
-                let elapsed = milestone_start_time.elapsed();
+                let time = chrono::Utc::now();
+                let chronicle_version = std::env!("CARGO_PKG_VERSION").to_string();
                 influx_db
                     .metrics()
                     .insert(chronicle::metrics::SyncMetrics {
-                        time: chrono::Utc::now(),
+                        time,
                         milestone_index: milestone.at.milestone_index,
-                        milestone_time: elapsed.as_millis() as u64,
-                        chronicle_version: std::env!("CARGO_PKG_VERSION").to_string(),
+                        milestone_time: phases.total().as_millis() as u64,
+                        chronicle_version: chronicle_version.clone(),
+                    })
+                    .await?;
+                influx_db
+                    .metrics()
+                    .insert(chronicle::metrics::SyncPhaseMetrics {
+                        time,
+                        milestone_index: milestone.at.milestone_index,
+                        receive_time: phases.receive.as_millis() as u64,
+                        output_insert_time: phases.output_insert.as_millis() as u64,
+                        cone_receive_time: phases.cone_receive.as_millis() as u64,
+                        block_insert_time: phases.block_insert.as_millis() as u64,
+                        analytics_time: phases.analytics.as_millis() as u64,
+                        checkpoint_time: phases.checkpoint.as_millis() as u64,
+                        chronicle_version,
                     })
                     .await?;
             }
         }
-
         Ok(())
     }
 }
@@ -0,0 +1,184 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks that the documents a milestone refers to were written before it, which catches bugs in the order of
+//! ingestion, like the outputs of a milestone being written before those of the previous one.
+
+use std::collections::HashSet;
+
+use chronicle::{
+    db::{
+        mongodb::collections::{MilestoneCollection, OutputCollection},
+        MongoDb,
+    },
+    model::{ledger::LedgerSpent, tangle::MilestoneIndex, utxo::OutputId},
+};
+use eyre::Result;
+use thiserror::Error;
+use tracing::warn;
+
+use super::{config::IntegrityCheckMode, InxWorkerError};
+
+#[derive(Debug, Error)]
+pub enum IntegrityViolation {
+    #[error("output {} booked at milestone {booked} is spent, but was never written", .output_id.to_hex())]
+    MissingSpentOutput {
+        output_id: OutputId,
+        booked: MilestoneIndex,
+    },
+    #[error("previous milestone {0} was never written")]
+    MissingPreviousMilestone(MilestoneIndex),
+}
+
+/// Finds the spent outputs that should have been written by a previous milestone. Outputs that are created and spent
+/// by the same milestone are written at the same time, so they are not checked.
+pub async fn check_spent_outputs(
+    db: &MongoDb,
+    milestone_index: MilestoneIndex,
+    consumed: &[LedgerSpent],
+) -> Result<Vec<IntegrityViolation>> {
+    let earlier = consumed
+        .iter()
+        .filter(|spent| spent.output.booked.milestone_index < milestone_index)
+        .map(|spent| spent.output.output_id)
+        .collect::<Vec<_>>();
+    if earlier.is_empty() {
+        return Ok(Vec::new());
+    }
+    let missing = db
+        .collection::<OutputCollection>()
+        .get_missing_output_ids(&earlier)
+        .await?;
+    Ok(missing_spent_outputs(consumed, &missing))
+}
+
+/// Reports the spent outputs that are missing, in the order they were consumed.
+fn missing_spent_outputs(consumed: &[LedgerSpent], missing: &HashSet<OutputId>) -> Vec<IntegrityViolation> {
+    consumed
+        .iter()
+        .filter(|spent| missing.contains(&spent.output.output_id))
+        .map(|spent| IntegrityViolation::MissingSpentOutput {
+            output_id: spent.output.output_id,
+            booked: spent.output.booked.milestone_index,
+        })
+        .collect()
+}
+
+/// Checks that the milestone before the given one was written. The first synced milestone has no previous one.
+pub async fn check_previous_milestone(
+    db: &MongoDb,
+    milestone_index: MilestoneIndex,
+    start_index: MilestoneIndex,
+) -> Result<Option<IntegrityViolation>> {
+    if milestone_index <= start_index {
+        return Ok(None);
+    }
+    let previous = milestone_index - 1;
+    Ok(db
+        .collection::<MilestoneCollection>()
+        .get_milestone_id(previous)
+        .await?
+        .is_none()
+        .then(|| IntegrityViolation::MissingPreviousMilestone(previous)))
+}
+
+/// Logs the violations found at a milestone and returns their number, or fails on the first one if they are rejected.
+pub fn handle_violations(
+    mode: IntegrityCheckMode,
+    milestone_index: MilestoneIndex,
+    violations: impl IntoIterator<Item = IntegrityViolation>,
+) -> Result<u64, InxWorkerError> {
+    let mut count = 0;
+    for violation in violations {
+        if mode == IntegrityCheckMode::Reject {
+            return Err(InxWorkerError::IntegrityViolation {
+                index: milestone_index,
+                violation,
+            });
+        }
+        warn!("Integrity check failed at milestone {milestone_index}: {violation}");
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn violations() -> Vec<IntegrityViolation> {
+        vec![
+            IntegrityViolation::MissingPreviousMilestone(9.into()),
+            IntegrityViolation::MissingPreviousMilestone(8.into()),
+        ]
+    }
+
+    #[test]
+    fn violations_are_counted_or_rejected() {
+        assert_eq!(
+            handle_violations(IntegrityCheckMode::Warn, 10.into(), violations()).unwrap(),
+            2
+        );
+        assert_eq!(
+            handle_violations(IntegrityCheckMode::Reject, 10.into(), Vec::new()).unwrap(),
+            0
+        );
+        assert!(matches!(
+            handle_violations(IntegrityCheckMode::Reject, 10.into(), violations()),
+            Err(InxWorkerError::IntegrityViolation {
+                violation: IntegrityViolation::MissingPreviousMilestone(MilestoneIndex(9)),
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn missing_spent_outputs_are_reported() {
+        use chronicle::model::{
+            ledger::{LedgerOutput, RentStructureBytes},
+            metadata::SpentMetadata,
+            payload::TransactionId,
+            utxo::Output,
+            BlockId,
+        };
+
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        let spent = |booked: u32| LedgerSpent {
+            output: LedgerOutput {
+                output_id: OutputId::rand(),
+                block_id: BlockId::rand(),
+                booked: MilestoneIndex(booked).with_timestamp(booked.into()),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output: Output::rand(&protocol_params),
+            },
+            spent_metadata: SpentMetadata {
+                transaction_id: TransactionId::rand(),
+                spent: MilestoneIndex(10).with_timestamp(10.into()),
+            },
+        };
+        let consumed = vec![spent(8), spent(9), spent(10)];
+
+        assert!(missing_spent_outputs(&consumed, &HashSet::new()).is_empty());
+        let violations = missing_spent_outputs(
+            &consumed,
+            &[consumed[1].output.output_id, consumed[0].output.output_id]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(violations.len(), 2);
+        assert!(matches!(
+            violations[0],
+            IntegrityViolation::MissingSpentOutput { output_id, booked: MilestoneIndex(8) }
+                if output_id == consumed[0].output.output_id
+        ));
+        assert!(matches!(
+            violations[1],
+            IntegrityViolation::MissingSpentOutput { output_id, booked: MilestoneIndex(9) }
+                if output_id == consumed[1].output.output_id
+        ));
+    }
+}
@@ -5,40 +5,155 @@ pub mod config;
 mod error;
 #[cfg(feature = "influx")]
 mod influx;
+mod integrity;
+mod pipeline;
+pub mod progress;
+mod standby;
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use chronicle::{
     db::{
-        mongodb::collections::{
-            ApplicationStateCollection, BlockCollection, ConfigurationUpdateCollection, LedgerUpdateCollection,
-            MilestoneCollection, OutputCollection, ProtocolUpdateCollection, TreasuryCollection,
+        mongodb::{
+            collections::{
+                ApplicationStateCollection, BlockCollection, BlockDocument, ConfigurationUpdateCollection,
+                LedgerUpdateCollection, MilestoneCollection, MilestoneDataSize, NodeHealthCollection, NodeHealthSample,
+                OutputCollection, ParentsCollection, PendingTransactionCollection, ProtocolUpdateCollection,
+                SyncReport, SyncReportCollection, SyncReportPhases, TreasuryCollection,
+            },
+            duplicates::DuplicateAudit,
+            MongoDbCollection,
         },
         MongoDb,
     },
     inx::{Inx, InxError},
     model::{
         ledger::{LedgerOutput, LedgerSpent},
-        metadata::LedgerInclusionState,
         payload::Payload,
         tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+        Block, TryFromWithContext,
     },
-    tangle::{Milestone, Tangle},
+    tangle::{IngestionHooks, Milestone, Tangle},
 };
 use eyre::{bail, Result};
 use futures::{StreamExt, TryStreamExt};
+use iota_sdk::types::block as iota;
+use mongodb::{bson::DateTime, error::TRANSIENT_TRANSACTION_ERROR, ClientSession};
+use packable::PackableExt;
 use tokio::{task::JoinSet, try_join};
-use tracing::{debug, info, instrument, trace_span, Instrument};
+use tracing::{debug, info, instrument, trace_span, warn, Instrument};
 
-pub use self::{config::InxConfig, error::InxWorkerError};
-use crate::migrations::{LatestMigration, Migration};
+use self::{
+    config::IntegrityCheckMode,
+    integrity::{check_previous_milestone, handle_violations},
+    pipeline::{Pipeline, PipelinedMilestone, WriteOptions, Written},
+};
+pub use self::{
+    config::InxConfig,
+    error::InxWorkerError,
+    progress::{SyncPhase, SyncProgress},
+    standby::Standby,
+};
+use crate::migrations::{app_version, LatestMigration, Migration};
 
 /// Batch size for insert operations.
 pub const INSERT_BATCH_SIZE: usize = 1000;
 
+/// How often the latest confirmed milestone of the node is requested while catching up.
+const PROGRESS_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the checkpoint transaction of a milestone is attempted if it fails with a transient error.
+const CHECKPOINT_ATTEMPTS: usize = 3;
+
+/// How long to wait before the first reconnect to INX. The delay doubles with every further reconnect until a milestone
+/// is synced again.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The time spent in each phase of syncing a milestone.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SyncPhases {
+    pub receive: Duration,
+    pub output_insert: Duration,
+    pub cone_receive: Duration,
+    pub block_insert: Duration,
+    pub analytics: Duration,
+    pub checkpoint: Duration,
+}
+
+impl SyncPhases {
+    /// The time spent syncing the milestone once it was received.
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    pub fn total(&self) -> Duration {
+        self.output_insert + self.cone_receive + self.block_insert + self.analytics + self.checkpoint
+    }
+}
+
+/// Returns the time elapsed since the last lap and starts a new one.
+fn lap(since: &mut Instant) -> Duration {
+    let now = Instant::now();
+    let elapsed = now - *since;
+    *since = now;
+    elapsed
+}
+
+/// The delay before a reconnect, which grows exponentially with the number of reconnects without progress.
+fn reconnect_delay(attempt: u32, max_delay: Duration) -> Duration {
+    INITIAL_RECONNECT_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(max_delay)
+}
+
+/// Whether the distance to the node calls for catch-up mode. A threshold of `0` disables catch-up mode, and it is not
+/// used while the distance is unknown.
+fn exceeds_catch_up_threshold(threshold: u32, milestones_behind: Option<u32>) -> bool {
+    threshold > 0 && matches!(milestones_behind, Some(behind) if behind > threshold)
+}
+
+/// Connects to the INX interface at the url, over TLS if it is an `https` url.
+pub async fn connect(config: &InxConfig, url: &str) -> Result<Inx> {
+    match url::Url::parse(url)?.scheme() {
+        "http" => Ok(Inx::connect(url.to_string()).await?),
+        "https" => Ok(Inx::connect_tls(url.to_string(), config.tls()?).await?),
+        _ => bail!(InxWorkerError::InvalidAddress(url.to_string())),
+    }
+}
+
+/// Whether an error was caused by the connection to the node rather than by the data or the database.
+fn is_connection_error(error: &eyre::Report) -> bool {
+    matches!(
+        error.downcast_ref::<InxError>(),
+        Some(InxError::StatusCode(_) | InxError::TonicError(_))
+    )
+}
+
+impl From<SyncPhases> for SyncReportPhases {
+    fn from(value: SyncPhases) -> Self {
+        Self {
+            receive: value.receive.as_millis() as u64,
+            output_insert: value.output_insert.as_millis() as u64,
+            cone_receive: value.cone_receive.as_millis() as u64,
+            block_insert: value.block_insert.as_millis() as u64,
+            analytics: value.analytics.as_millis() as u64,
+            checkpoint: value.checkpoint.as_millis() as u64,
+        }
+    }
+}
+
 pub struct InxWorker {
     db: MongoDb,
     config: InxConfig,
+    progress: SyncProgress,
+    /// Whether the current milestone is synced in catch-up mode.
+    catching_up: bool,
+    /// The first milestone whose deferred work has not been done yet.
+    catch_up_index: Option<MilestoneIndex>,
+    /// Whether milestones are checkpointed in a transaction, which requires a replica set.
+    transactions: bool,
+    /// The hooks that are notified of ingested milestones and outputs, which are the ones registered for the process
+    /// when the worker starts.
+    hooks: IngestionHooks,
+    /// The position of the current endpoint in the configured urls.
+    endpoint: usize,
     #[cfg(feature = "influx")]
     influx_db: Option<chronicle::db::influxdb::InfluxDb>,
 }
@@ -49,30 +164,130 @@ impl InxWorker {
         Self {
             db,
             config: inx_config,
+            progress: SyncProgress::default(),
+            catching_up: false,
+            catch_up_index: None,
+            transactions: false,
+            hooks: IngestionHooks::default(),
+            endpoint: 0,
             #[cfg(feature = "influx")]
             influx_db: None,
         }
     }
 
+    /// Gets a handle to the sync progress of this worker.
+    pub fn sync_progress(&self) -> SyncProgress {
+        self.progress.clone()
+    }
+
     #[cfg(feature = "influx")]
     pub fn set_influx_db(&mut self, influx_db: &chronicle::db::influxdb::InfluxDb) {
         self.influx_db.replace(influx_db.clone());
     }
 
-    async fn connect(&self) -> Result<Inx> {
-        let url = url::Url::parse(&self.config.url)?;
+    /// The url of the current endpoint.
+    fn url(&self) -> &str {
+        &self.config.urls[self.endpoint]
+    }
+
+    /// Syncs from the configured endpoints, and moves on to the next one if an endpoint is unreachable or its
+    /// connection fails repeatedly without any milestone being synced.
+    ///
+    /// Lost connections are retried with exponential backoff, and syncing resumes after the newest milestone in the
+    /// database. Only if no endpoint can be reached on startup, which hints at a misconfiguration, does this fail.
+    pub async fn run(&mut self) -> Result<()> {
+        self.hooks = IngestionHooks::registered();
+        // Whether any endpoint could be connected to since startup.
+        let mut connected = false;
+        // The number of endpoints in a row that could not be connected to.
+        let mut unreachable = 0;
+        // The number of times the connection to the current endpoint was lost without syncing a milestone.
+        let mut failures = 0;
+        // The number of reconnects without syncing a milestone.
+        let mut attempt = 0;
+        loop {
+            info!("Connecting to INX at bind address `{}`.", self.url());
+            let inx = match connect(&self.config, self.url()).await {
+                Ok(inx) => inx,
+                Err(e) => {
+                    unreachable += 1;
+                    if !connected && unreachable >= self.config.urls.len() {
+                        return Err(e);
+                    }
+                    warn!("Could not connect to INX at `{}`: {e}", self.url());
+                    self.next_endpoint();
+                    failures = 0;
+                    // The next endpoint is tried right away, unless all of them were tried.
+                    if unreachable % self.config.urls.len() == 0 {
+                        self.wait_to_reconnect(&mut attempt).await;
+                    }
+                    continue;
+                }
+            };
+            info!("Connected to INX.");
+            connected = true;
+            unreachable = 0;
 
-        if url.scheme() != "http" {
-            bail!(InxWorkerError::InvalidAddress(self.config.url.clone()));
+            let synced_index = self.progress.snapshot().synced_index;
+            let res = self.sync_from(inx).await;
+            if self.progress.snapshot().synced_index != synced_index {
+                failures = 0;
+                attempt = 0;
+            }
+            match res {
+                Ok(()) => warn!("INX stream at `{}` closed unexpectedly.", self.url()),
+                Err(e) if is_connection_error(&e) => warn!("Lost connection to INX at `{}`: {e}", self.url()),
+                Err(e) => return Err(e),
+            }
+            failures += 1;
+            if failures >= self.config.failover_errors {
+                self.next_endpoint();
+                failures = 0;
+            }
+            self.wait_to_reconnect(&mut attempt).await;
         }
+    }
 
-        Ok(Inx::connect(self.config.url.clone()).await?)
+    async fn wait_to_reconnect(&self, attempt: &mut u32) {
+        let delay = reconnect_delay(*attempt, self.config.reconnect_max_delay);
+        info!("Reconnecting to INX in {}.", humantime::format_duration(delay));
+        tokio::time::sleep(delay).await;
+        *attempt += 1;
     }
 
-    pub async fn run(&mut self) -> Result<()> {
-        let (start_index, inx) = self.init().await?;
+    fn next_endpoint(&mut self) {
+        self.endpoint = (self.endpoint + 1) % self.config.urls.len();
+        if self.config.urls.len() > 1 {
+            info!("Failing over to INX at `{}`.", self.url());
+        }
+    }
 
-        let tangle = Tangle::from(inx);
+    async fn sync_from(&mut self, inx: Inx) -> Result<()> {
+        let (start_index, mut inx) = self.init(inx).await?;
+
+        let db = self.db.clone();
+        let pending_inx = inx.clone();
+        let pending_transaction_ttl = self.config.pending_transaction_ttl;
+        let health_db = self.db.clone();
+        let health_inx = inx.clone();
+        let health_progress = self.progress.clone();
+        let (health_interval, health_retention) = (self.config.node_health_interval, self.config.node_health_retention);
+        tokio::select! {
+            res = self.sync_milestones(start_index, &mut inx) => res,
+            res = record_pending_transactions(db, pending_inx, pending_transaction_ttl),
+                if self.config.pending_transactions_enabled => res,
+            res = record_node_health(health_db, health_inx, health_progress, health_interval, health_retention),
+                if self.config.node_health_enabled => res,
+        }
+    }
+
+    async fn sync_milestones(&mut self, start_index: MilestoneIndex, inx: &mut Inx) -> Result<()> {
+        self.progress.set_phase(SyncPhase::CatchingUp);
+        self.progress
+            .set_synced_index(MilestoneIndex(start_index.0.saturating_sub(1)));
+        let mut last_refresh = Instant::now();
+
+        let tangle = Tangle::from(inx.clone());
 
         let mut stream = tangle.milestone_stream(start_index..).await?;
 
@@ -81,26 +296,130 @@ impl InxWorker {
 
         debug!("Started listening to ledger updates via INX.");
 
-        while let Some(milestone) = stream.try_next().await? {
-            self.handle_ledger_update(
-                milestone,
+        let mut pipeline = Pipeline::new(self.config.milestones_in_flight);
+        let mut receive_start = Instant::now();
+        loop {
+            let full = pipeline.is_full();
+            tokio::select! {
+                // A milestone whose writes are done is finished first, so that it is not held back by the next one.
+                biased;
+                res = pipeline.next(), if !pipeline.is_empty() => {
+                    // The stream was not polled while the pipeline was full.
+                    if full {
+                        receive_start = Instant::now();
+                    }
+                    // Unwrap: the pipeline was not empty.
+                    self.finish_milestone(
+                        res.unwrap()?,
+                        start_index,
+                        inx,
+                        &mut last_refresh,
+                        #[cfg(feature = "analytics")]
+                        analytics_info.as_mut(),
+                    )
+                    .await?;
+                }
+                milestone = stream.try_next(), if !full => {
+                    let milestone = match milestone? {
+                        Some(milestone) => milestone,
+                        None => break,
+                    };
+                    let receive = receive_start.elapsed();
+                    // The work deferred in catch-up mode needs every milestone before this one to be synced.
+                    if self.catch_up_index.is_some() && !self.is_behind_threshold() {
+                        while let Some(res) = pipeline.next().await {
+                            self.finish_milestone(
+                                res?,
+                                start_index,
+                                inx,
+                                &mut last_refresh,
+                                #[cfg(feature = "analytics")]
+                                analytics_info.as_mut(),
+                            )
+                            .await?;
+                        }
+                    }
+                    self.update_catch_up_mode(
+                        milestone.at.milestone_index,
+                        #[cfg(feature = "analytics")]
+                        analytics_info.as_mut(),
+                    )
+                    .await?;
+                    let options = WriteOptions {
+                        batch_size: self.batch_size(self.catching_up),
+                        // The parents are indexed from the stored blocks once catch-up mode ends.
+                        index_parents: !self.catching_up,
+                        remove_pending_transactions: self.config.pending_transactions_enabled,
+                        lean_storage: self.config.lean_storage,
+                        integrity_checks: self.config.integrity_checks,
+                    };
+                    let milestone = PipelinedMilestone {
+                        milestone,
+                        receive,
+                        catching_up: self.catching_up,
+                        audit: DuplicateAudit::default(),
+                    };
+                    pipeline.start(&self.db, inx, milestone, options);
+                    receive_start = Instant::now();
+                }
+            }
+        }
+
+        while let Some(res) = pipeline.next().await {
+            self.finish_milestone(
+                res?,
+                start_index,
+                inx,
+                &mut last_refresh,
                 #[cfg(feature = "analytics")]
                 analytics_info.as_mut(),
             )
             .await?;
         }
 
-        tracing::debug!("INX stream closed unexpectedly.");
+        Ok(())
+    }
 
+    /// Finishes a milestone that was written by the pipeline, in milestone order, and records its progress.
+    async fn finish_milestone<'a>(
+        &mut self,
+        (milestone, written): (PipelinedMilestone<'a>, Written),
+        start_index: MilestoneIndex,
+        inx: &mut Inx,
+        last_refresh: &mut Instant,
+        #[cfg(feature = "analytics")] analytics_info: Option<&mut influx::analytics::AnalyticsInfo>,
+    ) -> Result<()> {
+        let milestone_index = milestone.milestone.at.milestone_index;
+        // The previous milestone has to be checked before this one is checkpointed.
+        let mut integrity_violations = written.integrity_violations;
+        if self.config.integrity_checks != IntegrityCheckMode::Off {
+            let violation = check_previous_milestone(&self.db, milestone_index, start_index).await?;
+            integrity_violations += handle_violations(self.config.integrity_checks, milestone_index, violation)?;
+        }
+        let audit = milestone.audit.clone();
+        let mut report = audit
+            .scope(self.handle_ledger_update(
+                milestone,
+                written,
+                #[cfg(feature = "analytics")]
+                analytics_info,
+            ))
+            .await?;
+        // The previous run may have stopped halfway through any of the milestones it had in flight.
+        let is_resumed = milestone_index.0 < start_index.0.saturating_add(self.config.milestones_in_flight as u32);
+        self.report_duplicates(&audit, &mut report, is_resumed).await?;
+        if integrity_violations > 0 {
+            report.warnings.push(format!(
+                "Found {integrity_violations} integrity violations, which were logged."
+            ));
+        }
+        self.store_sync_report(&report).await?;
+        self.update_progress(inx, milestone_index, last_refresh).await;
         Ok(())
     }
 
     #[instrument(skip_all, err, level = "trace")]
-    async fn init(&mut self) -> Result<(MilestoneIndex, Inx)> {
-        info!("Connecting to INX at bind address `{}`.", &self.config.url);
-        let mut inx = self.connect().await?;
-        info!("Connected to INX.");
-
+    async fn init(&mut self, mut inx: Inx) -> Result<(MilestoneIndex, Inx)> {
         // Request the node status so we can get the pruning index and latest confirmed milestone
         let node_status = loop {
             match inx.read_node_status().await {
@@ -116,6 +435,8 @@ impl InxWorker {
             "The node has a pruning index of `{}` and a latest confirmed milestone index of `{}`.",
             node_status.tangle_pruning_index, node_status.confirmed_milestone.milestone_info.milestone_index,
         );
+        self.progress
+            .set_target_index(node_status.confirmed_milestone.milestone_info.milestone_index);
 
         // Check if there is an unfixable gap in our node data.
         let start_index = if let Some(MilestoneIndexTimestamp {
@@ -183,7 +504,17 @@ impl InxWorker {
                     .await?;
             }
         } else {
+            // Resyncing from scratch does not make this a different database, nor does it change its writer.
+            let app_state = self.db.collection::<ApplicationStateCollection>();
+            let database_id = app_state.get_database_id().await?;
+            let writer_instance_id = app_state.get_writer_instance_id().await?;
             self.db.clear().await?;
+            if let Some(database_id) = database_id {
+                app_state.set_database_id(&database_id).await?;
+            }
+            if let Some(writer_instance_id) = writer_instance_id {
+                app_state.set_writer_instance_id(&writer_instance_id).await?;
+            }
 
             let latest_version = LatestMigration::version();
             info!("Setting migration version to {}", latest_version);
@@ -191,7 +522,13 @@ impl InxWorker {
                 .collection::<ApplicationStateCollection>()
                 .set_last_migration(latest_version)
                 .await?;
+            // Clearing the database removed the recorded version as well.
+            self.db
+                .collection::<ApplicationStateCollection>()
+                .set_app_version(&app_version())
+                .await?;
             info!("Reading unspent outputs.");
+            self.progress.set_phase(SyncPhase::ImportingUnspentOutputs);
             let unspent_output_stream = inx
                 .read_unspent_outputs()
                 .instrument(trace_span!("inx_read_unspent_outputs"))
@@ -224,7 +561,12 @@ impl InxWorker {
                 // Convert batches to tasks
                 .try_fold(JoinSet::new(), |mut tasks, batch| async {
                     let db = self.db.clone();
-                    tasks.spawn(async move { insert_unspent_outputs(&db, &batch).await });
+                    let progress = self.progress.clone();
+                    tasks.spawn(async move {
+                        insert_unspent_outputs(&db, &batch).await?;
+                        progress.add_outputs_imported(batch.len() as u64);
+                        Result::<_>::Ok(())
+                    });
                     Result::<_>::Ok(tasks)
                 })
                 .await?;
@@ -271,119 +613,485 @@ impl InxWorker {
                 .await?;
         }
 
+        self.catch_up_index = self
+            .db
+            .collection::<ApplicationStateCollection>()
+            .get_catch_up_index()
+            .await?;
+
+        self.transactions = self.db.supports_transactions().await?;
+        if !self.transactions {
+            warn!(
+                "MongoDB does not support transactions, so a crash while checkpointing a milestone can leave its \
+                 protocol parameters and node configuration written without the milestone. Run MongoDB as a replica \
+                 set to avoid this."
+            );
+        }
+
         Ok((start_index, inx))
     }
 
-    #[instrument(skip_all, fields(milestone_index, created, consumed), err, level = "debug")]
-    async fn handle_ledger_update<'a>(
+    /// The insert batch size for a milestone.
+    fn batch_size(&self, catching_up: bool) -> usize {
+        if catching_up {
+            self.config.catch_up_batch_size
+        } else {
+            INSERT_BATCH_SIZE
+        }
+    }
+
+    /// Whether Chronicle is further behind the node than the catch-up threshold.
+    fn is_behind_threshold(&self) -> bool {
+        exceeds_catch_up_threshold(
+            self.config.catch_up_threshold,
+            self.progress.snapshot().milestones_behind,
+        )
+    }
+
+    /// Switches to catch-up mode while Chronicle is further behind the node than the configured threshold, and does
+    /// the deferred work once it is back within that distance.
+    async fn update_catch_up_mode(
         &mut self,
-        milestone: Milestone<'a, Inx>,
+        milestone_index: MilestoneIndex,
         #[cfg(feature = "analytics")] analytics_info: Option<&mut influx::analytics::AnalyticsInfo>,
     ) -> Result<()> {
-        #[cfg(feature = "metrics")]
-        let start_time = std::time::Instant::now();
+        let threshold = self.config.catch_up_threshold;
+        self.catching_up = self.is_behind_threshold();
 
-        let mut tasks = JoinSet::new();
+        if self.catching_up {
+            if self.catch_up_index.is_none() {
+                info!("Chronicle is more than {threshold} milestones behind the node, switching to catch-up mode.");
+                self.db
+                    .collection::<ApplicationStateCollection>()
+                    .set_catch_up_index(milestone_index)
+                    .await?;
+                self.catch_up_index = Some(milestone_index);
+            }
+        } else if let Some(catch_up_index) = self.catch_up_index {
+            let end_index = milestone_index - 1;
+            info!("Leaving catch-up mode, indexing the work deferred since milestone {catch_up_index}.");
+            let mut blocks = self
+                .db
+                .collection::<BlockCollection>()
+                .get_block_metadata_in_range(catch_up_index, end_index)
+                .await?
+                .try_chunks(INSERT_BATCH_SIZE)
+                .map_err(|e| e.1);
+            while let Some(batch) = blocks.try_next().await? {
+                self.db
+                    .collection::<ParentsCollection>()
+                    .insert_relationships(batch.iter().map(|(block_id, metadata)| (*block_id, metadata)))
+                    .await?;
+            }
+            #[cfg(feature = "analytics")]
+            if let Some(analytics_info) = analytics_info {
+                self.catch_up_analytics(end_index, analytics_info).await?;
+            }
+            self.db
+                .collection::<ApplicationStateCollection>()
+                .clear_catch_up_index()
+                .await?;
+            self.catch_up_index = None;
+            info!("Finished the work deferred in catch-up mode up to milestone {end_index}.");
+        }
 
-        for batch in milestone.ledger_updates().created_outputs().chunks(INSERT_BATCH_SIZE) {
-            let db = self.db.clone();
-            let batch = batch.to_vec();
-            tasks.spawn(async move { insert_unspent_outputs(&db, &batch).await });
+        Ok(())
+    }
+
+    /// Records a synced milestone and, while catching up, periodically refreshes how far the node is ahead.
+    async fn update_progress(&self, inx: &mut Inx, milestone_index: MilestoneIndex, last_refresh: &mut Instant) {
+        let was_synced = self.progress.is_synced();
+        self.progress.set_synced_index(milestone_index);
+        // Reaching the target only means being synced if the node has not moved on in the meantime.
+        let reached_target = !was_synced && self.progress.is_synced();
+        if !reached_target && (was_synced || last_refresh.elapsed() < PROGRESS_REFRESH_INTERVAL) {
+            return;
         }
+        *last_refresh = Instant::now();
+        match inx.read_node_status().await {
+            Ok(node_status) => self
+                .progress
+                .set_target_index(node_status.confirmed_milestone.milestone_info.milestone_index),
+            Err(e) => debug!("Failed to read the node status: {e}"),
+        }
+        let snapshot = self.progress.snapshot();
+        match (snapshot.phase, snapshot.milestones_behind, snapshot.eta) {
+            (SyncPhase::Synced, ..) => info!("Caught up with the node at milestone {milestone_index}."),
+            (_, Some(behind), Some(eta)) => info!(
+                "Catching up: {behind} milestones behind the node, about {} left.",
+                humantime::format_duration(Duration::from_secs(eta.as_secs()))
+            ),
+            (_, Some(behind), None) => info!("Catching up: {behind} milestones behind the node."),
+            _ => (),
+        }
+    }
 
-        for batch in milestone.ledger_updates().consumed_outputs().chunks(INSERT_BATCH_SIZE) {
-            let db = self.db.clone();
-            let batch = batch.to_vec();
-            tasks.spawn(async move { update_spent_outputs(&db, &batch).await });
+    /// Reports the documents that were dropped as duplicates while syncing a milestone. The first milestone synced
+    /// after a restart is expected to contain duplicates, as the previous run may have stopped halfway through it.
+    async fn report_duplicates(&self, audit: &DuplicateAudit, report: &mut SyncReport, is_resumed: bool) -> Result<()> {
+        let milestone_index = report.milestone_index;
+        let duplicates = audit.take();
+        for (collection, count) in &duplicates {
+            report.add_duplicates(collection, *count as u64);
+            if !is_resumed {
+                report
+                    .warnings
+                    .push(format!("Dropped {count} unexpected duplicates in `{collection}`."));
+            }
+        }
+        if !self.config.audit_duplicates || duplicates.is_empty() {
+            return Ok(());
+        }
+        for (collection, count) in &duplicates {
+            if is_resumed {
+                debug!("Dropped {count} duplicates in `{collection}` while resuming at milestone {milestone_index}.");
+            } else {
+                warn!("Dropped {count} unexpected duplicates in `{collection}` while syncing milestone {milestone_index}.");
+            }
+        }
+        #[cfg(feature = "metrics")]
+        self.update_duplicate_metrics(milestone_index, &duplicates).await?;
+        Ok(())
+    }
+
+    /// Stores the report of a synced milestone and removes the reports that fell out of the retention window.
+    async fn store_sync_report(&self, report: &SyncReport) -> Result<()> {
+        let retention = self.config.sync_report_retention;
+        if retention == 0 {
+            return Ok(());
         }
+        let reports = self.db.collection::<SyncReportCollection>();
+        reports.upsert_report(report).await?;
+        reports
+            .delete_reports_before(MilestoneIndex(report.milestone_index.0.saturating_sub(retention - 1)))
+            .await?;
+        Ok(())
+    }
 
-        while let Some(res) = tasks.join_next().await {
-            res??;
+    /// Finishes syncing a milestone once its outputs and blocks are written, by computing its analytics,
+    /// checkpointing it and notifying the hooks. Milestones are finished in order, after the previous one.
+    #[instrument(skip_all, fields(milestone_index, created, consumed), err, level = "debug")]
+    async fn handle_ledger_update<'a>(
+        &mut self,
+        milestone: PipelinedMilestone<'a>,
+        written: Written,
+        #[cfg(feature = "analytics")] analytics_info: Option<&mut influx::analytics::AnalyticsInfo>,
+    ) -> Result<SyncReport> {
+        let PipelinedMilestone {
+            milestone,
+            receive,
+            catching_up,
+            ..
+        } = milestone;
+        let mut phases = SyncPhases {
+            receive,
+            output_insert: written.output_insert,
+            cone_receive: written.cone_receive,
+            block_insert: written.block_insert,
+            ..Default::default()
+        };
+        let mut report = SyncReport {
+            milestone_index: milestone.at.milestone_index,
+            milestone_timestamp: milestone.at.milestone_timestamp,
+            synced_at: DateTime::now(),
+            catching_up,
+            phases: SyncReportPhases::default(),
+            collections: Vec::new(),
+            checkpoint_attempts: 0,
+            warnings: Vec::new(),
+        };
+        if catching_up {
+            report.warnings.push(
+                "Synced in catch-up mode, the parents and analytics of this milestone were deferred.".to_string(),
+            );
         }
+        let mut lap_start = Instant::now();
+
+        self.hooks
+            .notify_outputs(
+                milestone.at,
+                milestone.ledger_updates().created_outputs(),
+                milestone.ledger_updates().consumed_outputs(),
+                self.batch_size(catching_up),
+            )
+            .await
+            .map_err(|source| InxWorkerError::Hook {
+                index: milestone.at.milestone_index,
+                source,
+            })?;
+        phases.output_insert += lap(&mut lap_start);
+        let created = milestone.ledger_updates().created_outputs().len() as u64;
+        let consumed = milestone.ledger_updates().consumed_outputs().len() as u64;
+        report.add_written(OutputCollection::NAME, created + consumed);
+        report.add_written(LedgerUpdateCollection::NAME, created + consumed);
+        report.add_written(TreasuryCollection::NAME, written.treasury_payloads);
+        report.add_written(BlockCollection::NAME, written.data_size.block_count);
 
         // Record the result as part of the current span.
         tracing::Span::current().record("milestone_index", milestone.at.milestone_index.0);
-        tracing::Span::current().record("created", milestone.ledger_updates().created_outputs().len());
-        tracing::Span::current().record("consumed", milestone.ledger_updates().consumed_outputs().len());
+        tracing::Span::current().record("created", created);
+        tracing::Span::current().record("consumed", consumed);
 
-        self.handle_cone_stream(&milestone).await?;
+        let created_outputs = milestone.ledger_updates().created_outputs();
+        let protocol_params = milestone.protocol_params.clone().try_into()?;
+        let output_bytes = created_outputs
+            .iter()
+            .map(|output| {
+                let output = iota::output::Output::try_from_with_context(&protocol_params, output.output.clone())?;
+                Ok(output.packed_len() as u64)
+            })
+            .sum::<Result<u64, iota::Error>>()?;
+        let data_size = MilestoneDataSize {
+            output_count: created_outputs.len() as u64,
+            output_bytes,
+            ..written.data_size
+        };
+
+        // Analytics are computed from the database once catch-up mode ends.
+        #[cfg(feature = "influx")]
+        if !catching_up {
+            self.update_influx(
+                &milestone,
+                #[cfg(feature = "analytics")]
+                analytics_info,
+            )
+            .await?;
+        }
+        phases.analytics = lap(&mut lap_start);
+
+        // This acts as a checkpoint for the syncing and has to be done last, after everything else completed.
+        report.checkpoint_attempts = self.checkpoint(&milestone, data_size, written.last_blocks).await?;
+        phases.checkpoint = lap(&mut lap_start);
+        report.add_written(MilestoneCollection::NAME, 1);
+
+        self.hooks
+            .notify_milestone(milestone.milestone_id, milestone.at)
+            .await
+            .map_err(|source| InxWorkerError::Hook {
+                index: milestone.at.milestone_index,
+                source,
+            })?;
+
+        debug!(milestone_index = %milestone.at.milestone_index, ?phases, "Synced milestone.");
+        #[cfg(feature = "metrics")]
+        self.update_sync_metrics(&milestone, &phases).await?;
+
+        report.phases = phases.into();
+        report.synced_at = DateTime::now();
+        Ok(report)
+    }
+
+    /// Writes the last batch of blocks, the protocol parameters and the node configuration of a milestone, followed by
+    /// the milestone itself, which marks it as synced. If the database supports it, these writes are done in a single
+    /// transaction.
+    ///
+    /// The outputs and the other blocks of the milestone are written before, outside of the transaction, because they
+    /// can be too large for one. A crash can leave them partially written, but the milestone is then not marked as
+    /// synced, and rewriting them when it is synced again is idempotent. Returns how often the checkpoint was
+    /// attempted.
+    #[instrument(skip_all, err, level = "trace")]
+    async fn checkpoint<'a>(
+        &self,
+        milestone: &Milestone<'a, Inx>,
+        data_size: MilestoneDataSize,
+        last_blocks: Vec<BlockDocument>,
+    ) -> Result<u32> {
+        let mut session = if self.transactions {
+            Some(self.db.start_session().await?)
+        } else {
+            None
+        };
+        let mut attempt = 1;
+        loop {
+            if let Some(session) = &mut session {
+                session.start_transaction(None).await?;
+            }
+            let e = match self
+                .write_checkpoint(milestone, data_size, &last_blocks, session.as_mut())
+                .await
+            {
+                Ok(()) => return Ok(attempt as u32),
+                Err(e) => e,
+            };
+            let session = match &mut session {
+                Some(session) => session,
+                None => return Err(e.into()),
+            };
+            // The transaction is already aborted if the commit failed.
+            session.abort_transaction().await.ok();
+            if attempt < CHECKPOINT_ATTEMPTS && e.contains_label(TRANSIENT_TRANSACTION_ERROR) {
+                let milestone_index = milestone.at.milestone_index;
+                debug!("Retrying the checkpoint of milestone {milestone_index} after a transient error: {e}");
+                attempt += 1;
+            } else {
+                return Err(e.into());
+            }
+        }
+    }
+
+    /// Does the writes of [`Self::checkpoint`], and commits the transaction of the session if one is given.
+    async fn write_checkpoint<'a>(
+        &self,
+        milestone: &Milestone<'a, Inx>,
+        data_size: MilestoneDataSize,
+        last_blocks: &[BlockDocument],
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<(), mongodb::error::Error> {
+        let milestone_index = milestone.at.milestone_index;
+        self.db
+            .collection::<BlockCollection>()
+            .insert_blocks_with_session(last_blocks.iter().cloned(), session.as_deref_mut())
+            .await?;
         self.db
             .collection::<ProtocolUpdateCollection>()
-            .upsert_protocol_parameters(milestone.at.milestone_index, milestone.protocol_params.clone())
+            .upsert_protocol_parameters_with_session(
+                milestone_index,
+                milestone.protocol_params.clone(),
+                session.as_deref_mut(),
+            )
             .await?;
         self.db
             .collection::<ConfigurationUpdateCollection>()
-            .upsert_node_configuration(milestone.at.milestone_index, milestone.node_config.clone())
+            .upsert_node_configuration_with_session(
+                milestone_index,
+                milestone.node_config.clone(),
+                session.as_deref_mut(),
+            )
             .await?;
-
-        #[cfg(feature = "influx")]
-        self.update_influx(
-            &milestone,
-            #[cfg(feature = "analytics")]
-            analytics_info,
-            #[cfg(feature = "metrics")]
-            start_time,
-        )
-        .await?;
-
-        // This acts as a checkpoint for the syncing and has to be done last, after everything else completed.
         self.db
             .collection::<MilestoneCollection>()
-            .insert_milestone(
+            .insert_milestone_with_session(
                 milestone.milestone_id,
-                milestone.at.milestone_index,
+                milestone_index,
                 milestone.at.milestone_timestamp,
                 milestone.payload.clone(),
+                data_size,
+                session.as_deref_mut(),
             )
             .await?;
-
+        if let Some(session) = session {
+            session.commit_transaction().await?;
+        }
         Ok(())
     }
+}
 
-    #[instrument(skip_all, err, level = "trace")]
-    async fn handle_cone_stream<'a>(&mut self, milestone: &Milestone<'a, Inx>) -> Result<()> {
-        let cone_stream = milestone.cone_stream().await?;
-
-        let mut tasks = cone_stream
-            .try_chunks(INSERT_BATCH_SIZE)
-            .map_err(|e| e.1)
-            .try_fold(JoinSet::new(), |mut tasks, batch| async {
-                let db = self.db.clone();
-                tasks.spawn(async move {
-                    let payloads = batch
-                        .iter()
-                        .filter_map(|data| {
-                            if data.metadata.inclusion_state == LedgerInclusionState::Included {
-                                if let Some(Payload::TreasuryTransaction(payload)) = &data.block.payload {
-                                    return Some((
-                                        data.metadata.referenced_by_milestone_index,
-                                        payload.input_milestone_id,
-                                        payload.output_amount,
-                                    ));
-                                }
-                            }
-                            None
-                        })
-                        .collect::<Vec<_>>();
-                    if !payloads.is_empty() {
-                        db.collection::<TreasuryCollection>()
-                            .insert_treasury_payloads(payloads)
-                            .await?;
-                    }
-                    db.collection::<BlockCollection>()
-                        .insert_blocks_with_metadata(batch)
-                        .await?;
-                    Result::<_>::Ok(())
-                });
-                Ok(tasks)
-            })
-            .await?;
+/// Records the transactions the node receives as pending, until a milestone references them or they expire.
+async fn record_pending_transactions(db: MongoDb, mut inx: Inx, ttl: Duration) -> Result<()> {
+    let mut stream = inx.listen_to_blocks().await?;
 
-        while let Some(res) = tasks.join_next().await {
-            res??;
+    debug!("Started listening to blocks via INX.");
+
+    while let Some(message) = stream.try_next().await? {
+        // A block that cannot be read is skipped, rather than stopping the ingestion of milestones along with it.
+        let block = match message.block.inner_unverified() {
+            Ok(block) => block,
+            Err(e) => {
+                warn!("Skipping pending block {} that could not be read: {e}", message.block_id.to_hex());
+                continue;
+            }
+        };
+        if let Some(Payload::Transaction(payload)) = Block::from(block).payload {
+            db.collection::<PendingTransactionCollection>()
+                .insert_pending_transaction(message.block_id, *payload, ttl)
+                .await?;
         }
+    }
 
-        Ok(())
+    debug!("INX block stream closed unexpectedly.");
+
+    Ok(())
+}
+
+/// How many tips are requested from the node with every sample, as many as a new block can have parents.
+const SAMPLED_TIPS: u32 = 8;
+
+/// Periodically samples the status and the tip pool of the node, so that the sync lag can be correlated with the
+/// health of the node.
+async fn record_node_health(
+    db: MongoDb,
+    mut inx: Inx,
+    progress: SyncProgress,
+    interval: Duration,
+    retention: Duration,
+) -> Result<()> {
+    let collection = db.collection::<NodeHealthCollection>();
+    // The node reports the size of its tip pool at the sampling interval, and every sample records the latest report.
+    let listen_to_tips_metrics = |mut inx: Inx| async move {
+        match inx.listen_to_tips_metrics(interval).await {
+            Ok(stream) => Some(Box::pin(stream)),
+            Err(e) => {
+                debug!("Failed to listen to the tips metrics of the node: {e}");
+                None
+            }
+        }
+    };
+    let mut tips_metrics = listen_to_tips_metrics(inx.clone()).await;
+    let mut tips_metric = None;
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        let next_tips_metric = async {
+            match &mut tips_metrics {
+                Some(stream) => stream.next().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = interval.tick() => (),
+            res = next_tips_metric => {
+                match res {
+                    Some(Ok(metric)) => tips_metric = Some(metric),
+                    Some(Err(e)) => {
+                        debug!("Failed to receive the tips metrics of the node: {e}");
+                        (tips_metrics, tips_metric) = (None, None);
+                    }
+                    None => (tips_metrics, tips_metric) = (None, None),
+                }
+                continue;
+            }
+        }
+        // A stream of tips metrics that ended is opened again at the next sample.
+        if tips_metrics.is_none() {
+            tips_metrics = listen_to_tips_metrics(inx.clone()).await;
+        }
+
+        let start = Instant::now();
+        // A failed sample is retried at the next interval.
+        let node_status = match inx.read_node_status().await {
+            Ok(node_status) => node_status,
+            Err(e) => {
+                debug!("Failed to sample the node status: {e}");
+                continue;
+            }
+        };
+        let latency = start.elapsed().as_millis() as u64;
+        let selected_tips = match inx.request_tips(SAMPLED_TIPS, false).await {
+            Ok(tips) => Some(tips.len() as u32),
+            Err(e) => {
+                debug!("Failed to request tips from the node: {e}");
+                None
+            }
+        };
+        let sample = NodeHealthSample {
+            sampled_at: DateTime::now(),
+            is_healthy: node_status.is_healthy,
+            is_synced: node_status.is_synced,
+            is_almost_synced: node_status.is_almost_synced,
+            latest_milestone_index: node_status.latest_milestone.milestone_info.milestone_index,
+            confirmed_milestone_index: node_status.confirmed_milestone.milestone_info.milestone_index,
+            synced_index: progress.snapshot().synced_index,
+            latency,
+            non_lazy_tips: tips_metric.map(|metric| metric.non_lazy_pool_size),
+            semi_lazy_tips: tips_metric.map(|metric| metric.semi_lazy_pool_size),
+            selected_tips,
+        };
+        let res = async {
+            collection.insert_sample(&sample).await?;
+            collection.delete_samples_older_than(sample.sampled_at, retention).await
+        };
+        if let Err(e) = res.await {
+            warn!("Failed to record the node status: {e}");
+        }
     }
 }
 
@@ -420,3 +1128,50 @@ async fn update_spent_outputs(db: &MongoDb, outputs: &[LedgerSpent]) -> Result<(
     }
     .and(Ok(()))
 }
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_backs_off() {
+        let max_delay = Duration::from_secs(60);
+        assert_eq!(reconnect_delay(0, max_delay), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(1, max_delay), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(5, max_delay), Duration::from_secs(32));
+        assert_eq!(reconnect_delay(6, max_delay), max_delay);
+        assert_eq!(reconnect_delay(u32::MAX, max_delay), max_delay);
+    }
+
+    #[test]
+    fn catch_up_above_threshold() {
+        assert!(exceeds_catch_up_threshold(100, Some(101)));
+        assert!(!exceeds_catch_up_threshold(100, Some(100)));
+        assert!(!exceeds_catch_up_threshold(100, None));
+        assert!(!exceeds_catch_up_threshold(0, Some(u32::MAX)));
+    }
+
+    #[test]
+    fn laps_restart_after_each_phase() {
+        let start = Instant::now() - Duration::from_millis(50);
+        let mut lap_start = start;
+        assert!(lap(&mut lap_start) >= Duration::from_millis(50));
+        assert!(lap_start > start);
+        assert!(lap(&mut lap_start) < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn total_sync_time_excludes_receiving() {
+        let phases = SyncPhases {
+            receive: Duration::from_secs(60),
+            output_insert: Duration::from_millis(1),
+            cone_receive: Duration::from_millis(2),
+            block_insert: Duration::from_millis(3),
+            analytics: Duration::from_millis(4),
+            checkpoint: Duration::from_millis(5),
+        };
+        assert_eq!(phases.total(), Duration::from_millis(15));
+    }
+}
@@ -0,0 +1,472 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Overlaps the writes of consecutive milestones, so that a milestone's blocks can be written while the outputs of the
+//! next one are, instead of syncing one milestone after the other.
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use chronicle::{
+    db::{
+        mongodb::{
+            collections::{
+                BlockCollection, BlockDocument, MilestoneDataSize, ParentsCollection, PendingTransactionCollection,
+                TreasuryCollection,
+            },
+            duplicates::{self, DuplicateAudit},
+        },
+        MongoDb,
+    },
+    inx::Inx,
+    model::{
+        ledger::{LedgerOutput, LedgerSpent},
+        metadata::LedgerInclusionState,
+        payload::Payload,
+        tangle::MilestoneIndex,
+    },
+    tangle::{BlockData, InputSource, Milestone},
+};
+use eyre::Result;
+use futures::TryStreamExt;
+use tokio::{
+    sync::oneshot,
+    task::{JoinHandle, JoinSet},
+    try_join,
+};
+
+use super::{
+    config::IntegrityCheckMode,
+    insert_unspent_outputs,
+    integrity::{check_spent_outputs, handle_violations},
+    lap, update_spent_outputs,
+};
+
+/// How the data of a milestone is written.
+#[derive(Copy, Clone, Debug)]
+pub struct WriteOptions {
+    pub batch_size: usize,
+    /// Whether the parents of the blocks are indexed, which is deferred in catch-up mode.
+    pub index_parents: bool,
+    /// Whether referenced transactions are removed from the pending transactions.
+    pub remove_pending_transactions: bool,
+    /// Whether blocks are stored without their raw bytes.
+    pub lean_storage: bool,
+    /// Whether the spent outputs are checked to be written by a previous milestone.
+    pub integrity_checks: IntegrityCheckMode,
+}
+
+/// The result of writing the outputs and blocks of a milestone.
+#[derive(Clone, Debug, Default)]
+pub struct Written {
+    pub output_insert: Duration,
+    pub cone_receive: Duration,
+    pub block_insert: Duration,
+    /// The number and size of the blocks. The outputs are accounted for once the milestone is checkpointed.
+    pub data_size: MilestoneDataSize,
+    pub treasury_payloads: u64,
+    /// The number of integrity violations that were found and only logged.
+    pub integrity_violations: u64,
+    /// The blocks of the last batch, which are inserted when the milestone is checkpointed.
+    pub last_blocks: Vec<BlockDocument>,
+}
+
+/// A milestone whose outputs and blocks are written in the pipeline.
+pub struct PipelinedMilestone<'a> {
+    pub milestone: Milestone<'a, Inx>,
+    /// How long it took to receive the milestone.
+    pub receive: Duration,
+    /// Whether the milestone is synced in catch-up mode.
+    pub catching_up: bool,
+    /// The audit of the duplicates dropped while writing the milestone.
+    pub audit: DuplicateAudit,
+}
+
+struct InFlight<T> {
+    item: T,
+    writes: JoinHandle<Result<Written>>,
+}
+
+/// The milestones whose outputs and blocks are being written, in the order they were received.
+///
+/// The outputs of a milestone are only written once those of the previous milestone are, as a milestone can spend the
+/// outputs created by the one before it. Blocks are written as soon as they are received. Everything that depends on
+/// the previous milestone being synced, like analytics and the checkpoint, is left to the caller, which gets the
+/// milestones back in order.
+pub struct Pipeline<T> {
+    in_flight: VecDeque<InFlight<T>>,
+    /// Resolves once the outputs of the most recently started milestone are written.
+    outputs_written: Option<oneshot::Receiver<()>>,
+    max_in_flight: usize,
+}
+
+impl<T> Pipeline<T> {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            in_flight: VecDeque::with_capacity(max_in_flight),
+            outputs_written: None,
+            max_in_flight: max_in_flight.max(1),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.in_flight.is_empty()
+    }
+
+    /// Whether no further milestone may be started before the oldest one is finished.
+    pub fn is_full(&self) -> bool {
+        self.in_flight.len() >= self.max_in_flight
+    }
+
+    /// Spawns the writes of a milestone, which get the turn to write its outputs after the previous milestone.
+    fn spawn<F>(&mut self, item: T, writes: impl FnOnce(OutputsTurn) -> F)
+    where
+        F: Future<Output = Result<Written>> + Send + 'static,
+    {
+        let (outputs_written, next_outputs_written) = oneshot::channel();
+        let writes = tokio::spawn(writes(OutputsTurn {
+            previous_outputs_written: self.outputs_written.replace(next_outputs_written),
+            outputs_written,
+        }));
+        self.in_flight.push_back(InFlight { item, writes });
+    }
+
+    /// Waits until the outputs and blocks of the oldest milestone are written and returns it. This is cancel safe, the
+    /// milestone is only removed once it is returned.
+    pub async fn next(&mut self) -> Option<Result<(T, Written)>> {
+        let res = match (&mut self.in_flight.front_mut()?.writes).await {
+            Ok(res) => res,
+            Err(e) => Err(e.into()),
+        };
+        // Unwrap: the front was checked above.
+        let InFlight { item, .. } = self.in_flight.pop_front().unwrap();
+        Some(res.map(|written| (item, written)))
+    }
+}
+
+impl<'a> Pipeline<PipelinedMilestone<'a>> {
+    /// Starts writing the outputs and blocks of a milestone in the background.
+    pub fn start(&mut self, db: &MongoDb, inx: &Inx, milestone: PipelinedMilestone<'a>, options: WriteOptions) {
+        let milestone_index = milestone.milestone.at.milestone_index;
+        let created = batches(
+            milestone.milestone.ledger_updates().created_outputs(),
+            options.batch_size,
+        );
+        let consumed = batches(
+            milestone.milestone.ledger_updates().consumed_outputs(),
+            options.batch_size,
+        );
+        let (db, inx, audit) = (db.clone(), inx.clone(), milestone.audit.clone());
+        self.spawn(milestone, |turn| async move {
+            audit
+                .scope(write_milestone(
+                    db,
+                    inx,
+                    milestone_index,
+                    created,
+                    consumed,
+                    turn,
+                    options,
+                ))
+                .await
+        });
+    }
+}
+
+impl<T> Drop for Pipeline<T> {
+    fn drop(&mut self) {
+        // Milestones after a failed one must not be written, as syncing resumes from the failed one.
+        for in_flight in &self.in_flight {
+            in_flight.writes.abort();
+        }
+    }
+}
+
+/// The turn of a milestone to write its outputs, which comes once the previous milestone has written its outputs.
+struct OutputsTurn {
+    previous_outputs_written: Option<oneshot::Receiver<()>>,
+    outputs_written: oneshot::Sender<()>,
+}
+
+impl OutputsTurn {
+    /// Waits for the turn and runs the writes, after which the next milestone gets its turn. Fails without running the
+    /// writes if the previous milestone failed, and then fails the next milestone in turn.
+    async fn take<R>(self, writes: impl Future<Output = Result<R>>) -> Result<R> {
+        // The previous milestone failed if it was dropped without writing its outputs.
+        if let Some(previous_outputs_written) = self.previous_outputs_written {
+            previous_outputs_written.await?;
+        }
+        let res = writes.await?;
+        self.outputs_written.send(()).ok();
+        Ok(res)
+    }
+}
+
+async fn write_milestone(
+    db: MongoDb,
+    inx: Inx,
+    milestone_index: MilestoneIndex,
+    created: Vec<Vec<LedgerOutput>>,
+    consumed: Vec<Vec<LedgerSpent>>,
+    turn: OutputsTurn,
+    options: WriteOptions,
+) -> Result<Written> {
+    // The blocks are received while waiting for the outputs of the previous milestone.
+    let ((output_insert, integrity_violations), written) = try_join!(
+        turn.take(async {
+            let mut integrity_violations = 0;
+            if options.integrity_checks != IntegrityCheckMode::Off {
+                for batch in &consumed {
+                    let violations = check_spent_outputs(&db, milestone_index, batch).await?;
+                    integrity_violations += handle_violations(options.integrity_checks, milestone_index, violations)?;
+                }
+            }
+            let output_insert = write_outputs(&db, created, consumed).await?;
+            Result::<_>::Ok((output_insert, integrity_violations))
+        }),
+        write_cone(&db, &inx, milestone_index, options),
+    )?;
+    Ok(Written {
+        output_insert,
+        integrity_violations,
+        ..written
+    })
+}
+
+/// Copies the outputs in batches of the insert batch size, so that they can be written in the background.
+fn batches<T: Clone>(outputs: &[T], batch_size: usize) -> Vec<Vec<T>> {
+    outputs.chunks(batch_size).map(<[T]>::to_vec).collect()
+}
+
+/// Inserts the created outputs and updates the spent ones, and returns how long that took.
+async fn write_outputs(
+    db: &MongoDb,
+    created: Vec<Vec<LedgerOutput>>,
+    consumed: Vec<Vec<LedgerSpent>>,
+) -> Result<Duration> {
+    let start = Instant::now();
+    let mut tasks = JoinSet::new();
+    for batch in created {
+        let db = db.clone();
+        tasks.spawn(duplicates::inherit(
+            async move { insert_unspent_outputs(&db, &batch).await },
+        ));
+    }
+    for batch in consumed {
+        let db = db.clone();
+        tasks.spawn(duplicates::inherit(
+            async move { update_spent_outputs(&db, &batch).await },
+        ));
+    }
+    while let Some(res) = tasks.join_next().await {
+        res??;
+    }
+    Ok(start.elapsed())
+}
+
+/// Inserts the blocks referenced by a milestone and returns their number and size.
+async fn write_cone(
+    db: &MongoDb,
+    inx: &Inx,
+    milestone_index: MilestoneIndex,
+    options: WriteOptions,
+) -> Result<Written> {
+    let mut lap_start = Instant::now();
+    let cone_stream = inx.cone_stream(milestone_index).await?;
+
+    let (mut tasks, data_size, last_batch) = cone_stream
+        .try_chunks(options.batch_size)
+        .map_err(|e| e.1)
+        .try_fold(
+            (JoinSet::new(), MilestoneDataSize::default(), None::<Vec<BlockData>>),
+            |(mut tasks, mut data_size, previous_batch), batch| {
+                let db = db.clone();
+                async move {
+                    data_size.block_count += batch.len() as u64;
+                    data_size.block_bytes += batch.iter().map(|data| data.raw.len() as u64).sum::<u64>();
+                    // The blocks of the last batch are inserted when the milestone is checkpointed, so each batch is
+                    // only written once the next one arrives.
+                    if let Some(previous_batch) = previous_batch {
+                        tasks.spawn(duplicates::inherit(async move {
+                            let treasury_payloads = write_block_references(&db, &previous_batch, options).await?;
+                            db.collection::<BlockCollection>()
+                                .insert_blocks_with_metadata(block_documents(previous_batch, options))
+                                .await?;
+                            Result::<_>::Ok(treasury_payloads)
+                        }));
+                    }
+                    Ok((tasks, data_size, Some(batch)))
+                }
+            },
+        )
+        .await?;
+    let cone_receive = lap(&mut lap_start);
+
+    let mut treasury_payloads = 0;
+    while let Some(res) = tasks.join_next().await {
+        treasury_payloads += res??;
+    }
+    let mut last_blocks = Vec::new();
+    if let Some(last_batch) = last_batch {
+        treasury_payloads += write_block_references(db, &last_batch, options).await?;
+        last_blocks = block_documents(last_batch, options);
+    }
+    let block_insert = lap(&mut lap_start);
+
+    Ok(Written {
+        cone_receive,
+        block_insert,
+        data_size,
+        treasury_payloads,
+        last_blocks,
+        ..Default::default()
+    })
+}
+
+/// Writes what the blocks of a batch reference: the treasury payloads, the pending transactions they remove and the
+/// parents. Returns the number of treasury payloads.
+async fn write_block_references(db: &MongoDb, batch: &[BlockData], options: WriteOptions) -> Result<u64> {
+    let payloads = batch
+        .iter()
+        .filter_map(|data| {
+            if data.metadata.inclusion_state == LedgerInclusionState::Included {
+                if let Some(Payload::TreasuryTransaction(payload)) = &data.block.payload {
+                    return Some((
+                        data.metadata.referenced_by_milestone_index,
+                        payload.input_milestone_id,
+                        payload.output_amount,
+                    ));
+                }
+            }
+            None
+        })
+        .collect::<Vec<_>>();
+    let treasury_payloads = payloads.len() as u64;
+    if !payloads.is_empty() {
+        db.collection::<TreasuryCollection>()
+            .insert_treasury_payloads(payloads)
+            .await?;
+    }
+    // Once referenced, a transaction is no longer pending, regardless of whether it was included.
+    if options.remove_pending_transactions {
+        db.collection::<PendingTransactionCollection>()
+            .remove_transactions(batch.iter().filter_map(|data| match &data.block.payload {
+                Some(Payload::Transaction(payload)) => Some(payload.transaction_id),
+                _ => None,
+            }))
+            .await?;
+    }
+    if options.index_parents {
+        db.collection::<ParentsCollection>()
+            .insert_relationships(batch.iter().map(|data| (data.block_id, &data.metadata)))
+            .await?;
+    }
+    Ok(treasury_payloads)
+}
+
+/// Converts a batch of blocks to the documents that are stored.
+fn block_documents(batch: Vec<BlockData>, options: WriteOptions) -> Vec<BlockDocument> {
+    let blocks = batch.into_iter().map(BlockDocument::from);
+    if options.lean_storage {
+        blocks.map(BlockDocument::without_raw).collect()
+    } else {
+        blocks.collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Starts a milestone whose outputs are written once `gate` resolves, which records them as written.
+    fn start(
+        pipeline: &mut Pipeline<u32>,
+        index: u32,
+        gate: oneshot::Receiver<Result<()>>,
+        written: &Arc<Mutex<Vec<u32>>>,
+    ) {
+        let written = written.clone();
+        pipeline.spawn(index, |turn| async move {
+            turn.take(async move {
+                gate.await??;
+                written.lock().unwrap().push(index);
+                Ok(())
+            })
+            .await?;
+            Ok(Written::default())
+        });
+    }
+
+    #[tokio::test]
+    async fn milestones_are_returned_in_order() {
+        let mut pipeline = Pipeline::new(3);
+        for (index, delay) in [(1, 30), (2, 10), (3, 0)] {
+            pipeline.spawn(index, |_| async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                Ok(Written::default())
+            });
+        }
+        assert!(pipeline.is_full());
+        let mut indexes = Vec::new();
+        while let Some(res) = pipeline.next().await {
+            indexes.push(res.unwrap().0);
+        }
+        assert_eq!(indexes, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn dropping_aborts_writes() {
+        let mut pipeline = Pipeline::new(1);
+        let (_keep_pending, pending) = oneshot::channel::<()>();
+        let (aborted, on_abort) = oneshot::channel::<()>();
+        pipeline.spawn(1, |_| async move {
+            // The sender is dropped along with the aborted task, which wakes up the receiver.
+            let _aborted = aborted;
+            pending.await.ok();
+            Ok(Written::default())
+        });
+        tokio::task::yield_now().await;
+        drop(pipeline);
+        assert!(on_abort.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn outputs_wait_for_previous_milestone() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let mut pipeline = Pipeline::new(2);
+        let (open_first, first) = oneshot::channel();
+        let (open_second, second) = oneshot::channel();
+        start(&mut pipeline, 1, first, &written);
+        start(&mut pipeline, 2, second, &written);
+
+        open_second.send(Ok(())).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(written.lock().unwrap().is_empty());
+
+        open_first.send(Ok(())).unwrap();
+        assert_eq!(pipeline.next().await.unwrap().unwrap().0, 1);
+        assert_eq!(pipeline.next().await.unwrap().unwrap().0, 2);
+        assert_eq!(*written.lock().unwrap(), [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn failed_milestone_fails_the_next() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let mut pipeline = Pipeline::new(2);
+        let (open_first, first) = oneshot::channel();
+        let (open_second, second) = oneshot::channel();
+        start(&mut pipeline, 1, first, &written);
+        start(&mut pipeline, 2, second, &written);
+
+        open_second.send(Ok(())).unwrap();
+        open_first.send(Err(eyre::eyre!("failed to write"))).unwrap();
+        assert!(pipeline.next().await.unwrap().is_err());
+        assert!(pipeline.next().await.unwrap().is_err());
+        assert!(written.lock().unwrap().is_empty());
+    }
+}
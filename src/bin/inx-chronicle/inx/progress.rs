@@ -0,0 +1,187 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use chronicle::model::tangle::MilestoneIndex;
+use serde::{Deserialize, Serialize};
+
+/// The phases Chronicle goes through while syncing with the node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    /// Connecting to the node and checking the database.
+    Starting,
+    /// Importing the unspent outputs of the node into an empty database.
+    ImportingUnspentOutputs,
+    /// Syncing the historical milestones between the database and the node.
+    CatchingUp,
+    /// Following the confirmed milestones of the node as they arrive.
+    Synced,
+    /// Following the milestones that another instance stores in the database, until this one is promoted.
+    Standby,
+}
+
+impl Default for SyncPhase {
+    fn default() -> Self {
+        Self::Starting
+    }
+}
+
+#[cfg(feature = "api")]
+impl SyncPhase {
+    pub const ALL: [Self; 5] = [
+        Self::Starting,
+        Self::ImportingUnspentOutputs,
+        Self::CatchingUp,
+        Self::Synced,
+        Self::Standby,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::ImportingUnspentOutputs => "importing_unspent_outputs",
+            Self::CatchingUp => "catching_up",
+            Self::Synced => "synced",
+            Self::Standby => "standby",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProgressState {
+    phase: SyncPhase,
+    outputs_imported: u64,
+    synced_index: Option<MilestoneIndex>,
+    target_index: Option<MilestoneIndex>,
+    /// When catching up started, and the milestone that was synced at that time.
+    catch_up_start: Option<(Instant, MilestoneIndex)>,
+}
+
+/// A point-in-time view of the sync progress.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressSnapshot {
+    pub phase: SyncPhase,
+    pub outputs_imported: u64,
+    pub synced_index: Option<MilestoneIndex>,
+    pub target_index: Option<MilestoneIndex>,
+    pub milestones_behind: Option<u32>,
+    /// The estimated time until catching up completes, based on the average rate since it started.
+    pub eta: Option<Duration>,
+}
+
+/// A shared handle to the sync progress, which is updated by the INX worker and can be read from anywhere.
+#[derive(Clone, Debug, Default)]
+pub struct SyncProgress(Arc<RwLock<ProgressState>>);
+
+impl SyncProgress {
+    pub fn set_phase(&self, phase: SyncPhase) {
+        self.0.write().unwrap().phase = phase;
+    }
+
+    pub fn add_outputs_imported(&self, count: u64) {
+        self.0.write().unwrap().outputs_imported += count;
+    }
+
+    /// Sets the latest confirmed milestone of the node, which is the target of catching up.
+    pub fn set_target_index(&self, index: MilestoneIndex) {
+        let mut state = self.0.write().unwrap();
+        state.target_index = Some(index);
+        Self::update_phase(&mut state);
+    }
+
+    /// Sets the newest milestone in the database.
+    pub fn set_synced_index(&self, index: MilestoneIndex) {
+        let mut state = self.0.write().unwrap();
+        state.synced_index = Some(index);
+        Self::update_phase(&mut state);
+    }
+
+    /// Switches between catching up and synced once both the synced and the target index are known.
+    fn update_phase(state: &mut ProgressState) {
+        if !matches!(state.phase, SyncPhase::CatchingUp | SyncPhase::Synced) {
+            return;
+        }
+        if let (Some(synced), Some(target)) = (state.synced_index, state.target_index) {
+            if synced >= target {
+                state.phase = SyncPhase::Synced;
+            } else {
+                state.phase = SyncPhase::CatchingUp;
+                state.catch_up_start.get_or_insert((Instant::now(), synced));
+            }
+        }
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.0.read().unwrap().phase == SyncPhase::Synced
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let state = self.0.read().unwrap();
+        let milestones_behind = match (state.synced_index, state.target_index) {
+            (Some(synced), Some(target)) => Some(target.0.saturating_sub(synced.0)),
+            (None, Some(target)) => Some(target.0),
+            _ => None,
+        };
+        let eta = match (state.phase, state.catch_up_start, state.synced_index, milestones_behind) {
+            (SyncPhase::CatchingUp, Some((start_time, start_index)), Some(synced), Some(behind)) => {
+                let synced_since_start = synced.0.saturating_sub(start_index.0);
+                (synced_since_start > 0)
+                    .then(|| start_time.elapsed().mul_f64(behind as f64 / synced_since_start as f64))
+            }
+            (SyncPhase::Synced, ..) => Some(Duration::ZERO),
+            _ => None,
+        };
+        ProgressSnapshot {
+            phase: state.phase,
+            outputs_imported: state.outputs_imported,
+            synced_index: state.synced_index,
+            target_index: state.target_index,
+            milestones_behind,
+            eta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn phase_follows_indices() {
+        let progress = SyncProgress::default();
+        progress.set_phase(SyncPhase::ImportingUnspentOutputs);
+        progress.add_outputs_imported(1000);
+        progress.add_outputs_imported(500);
+        progress.set_target_index(20.into());
+        assert_eq!(progress.snapshot().phase, SyncPhase::ImportingUnspentOutputs);
+
+        progress.set_phase(SyncPhase::CatchingUp);
+        progress.set_synced_index(10.into());
+        let snapshot = progress.snapshot();
+        assert_eq!(snapshot.phase, SyncPhase::CatchingUp);
+        assert_eq!(snapshot.outputs_imported, 1500);
+        assert_eq!(snapshot.milestones_behind, Some(10));
+        // Nothing has been synced since catching up started, so there is no rate to estimate from yet.
+        assert_eq!(snapshot.eta, None);
+
+        progress.set_synced_index(15.into());
+        let snapshot = progress.snapshot();
+        assert_eq!(snapshot.milestones_behind, Some(5));
+        assert!(snapshot.eta.is_some());
+
+        progress.set_synced_index(20.into());
+        assert!(progress.is_synced());
+        assert_eq!(progress.snapshot().eta, Some(Duration::ZERO));
+
+        // The node moved on, so there is catching up to do again.
+        progress.set_target_index(25.into());
+        assert_eq!(progress.snapshot().phase, SyncPhase::CatchingUp);
+    }
+}
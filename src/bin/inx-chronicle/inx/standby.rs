@@ -0,0 +1,94 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use chronicle::db::{mongodb::collections::MilestoneCollection, MongoDb};
+use eyre::{bail, Result};
+use futures::TryStreamExt;
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+use super::{SyncPhase, SyncProgress};
+
+/// Keeps an instance from writing to the database while another one does, until it is promoted to take over.
+///
+/// Both instances are connected to the same database. The standby follows the milestones the primary stores, so that
+/// it can continue from the newest one once promoted.
+#[derive(Clone, Debug)]
+pub struct Standby(Arc<watch::Sender<bool>>);
+
+impl Standby {
+    pub fn new() -> Self {
+        Self(Arc::new(watch::channel(false).0))
+    }
+
+    /// Promotes the instance, which then starts syncing with its own node. Returns whether it was in standby before.
+    #[cfg(any(test, feature = "api"))]
+    pub fn promote(&self) -> bool {
+        !self.0.send_replace(true)
+    }
+
+    #[cfg(feature = "api")]
+    pub fn is_promoted(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Waits until the instance is promoted.
+    pub async fn promoted(&self) {
+        // The sender is kept alive by `self`, so this cannot fail.
+        self.0.subscribe().wait_for(|promoted| *promoted).await.ok();
+    }
+
+    /// Follows the milestones stored by the primary until the instance is promoted.
+    ///
+    /// Promotion is always requested by an operator. The instances share no lease, so a standby that promoted itself
+    /// when the primary merely stalled would end up writing to the database alongside it.
+    pub async fn follow(&self, db: &MongoDb, progress: &SyncProgress) -> Result<()> {
+        progress.set_phase(SyncPhase::Standby);
+        let milestones = db.collection::<MilestoneCollection>();
+        if let Some(index) = milestones.get_ledger_index().await? {
+            progress.set_synced_index(index);
+        }
+        let stored = milestones.watch_inserted_milestones().await?;
+        futures::pin_mut!(stored);
+        info!("Following the milestones stored by the primary until promoted.");
+
+        loop {
+            tokio::select! {
+                _ = self.promoted() => {
+                    info!("Promoted by request.");
+                    break;
+                }
+                next = stored.try_next() => match next? {
+                    Some(at) => {
+                        debug!("The primary stored milestone {}.", at.milestone_index);
+                        progress.set_synced_index(at.milestone_index);
+                    }
+                    None => bail!("the change stream of stored milestones ended"),
+                },
+            }
+        }
+
+        progress.set_phase(SyncPhase::Starting);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn promotes_once() {
+        let standby = Standby::new();
+        assert!(standby.clone().promote());
+        assert!(!standby.promote());
+        // Promotions before waiting are not missed.
+        tokio::time::timeout(Duration::from_secs(1), standby.promoted())
+            .await
+            .unwrap();
+    }
+}
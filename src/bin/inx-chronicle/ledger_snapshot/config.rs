@@ -0,0 +1,29 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_ENABLED: bool = true;
+/// A day of milestones at the usual rate of one milestone every ten seconds.
+pub const DEFAULT_INTERVAL: u32 = 8640;
+pub const DEFAULT_POLL_INTERVAL: &str = "1m";
+
+/// Configuration of the ledger snapshot worker.
+#[derive(Clone, Debug)]
+pub struct LedgerSnapshotConfig {
+    pub enabled: bool,
+    /// The number of milestones between two snapshots. Snapshots are taken at the multiples of the interval.
+    pub interval: u32,
+    /// How often to check whether a snapshot milestone was reached.
+    pub poll_interval: Duration,
+}
+
+impl Default for LedgerSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            interval: DEFAULT_INTERVAL,
+            poll_interval: DEFAULT_POLL_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+        }
+    }
+}
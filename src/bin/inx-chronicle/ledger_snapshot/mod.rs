@@ -0,0 +1,116 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use chronicle::{
+    db::{
+        mongodb::collections::{LedgerSnapshotCollection, MilestoneCollection, OutputCollection},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+use eyre::Result;
+use tracing::{debug, error, warn};
+
+pub use self::config::LedgerSnapshotConfig;
+
+/// Records a digest of the unspent output set whenever the ledger reaches a multiple of the snapshot interval, so
+/// that an archive can be audited without streaming all of its outputs.
+pub struct LedgerSnapshotWorker {
+    db: MongoDb,
+    config: LedgerSnapshotConfig,
+}
+
+/// Returns the first snapshot milestone after the latest recorded one. Without a recorded snapshot, the latest
+/// snapshot milestone at or before the ledger index is taken, since the history before it would take long to
+/// digest.
+fn next_snapshot_index(latest: Option<MilestoneIndex>, ledger_index: MilestoneIndex, interval: u32) -> MilestoneIndex {
+    match latest {
+        Some(latest) => MilestoneIndex((latest.0 / interval + 1) * interval),
+        None => MilestoneIndex(ledger_index.0 / interval * interval),
+    }
+}
+
+impl LedgerSnapshotWorker {
+    pub fn new(db: MongoDb, config: LedgerSnapshotConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let snapshots = self.db.collection::<LedgerSnapshotCollection>();
+        let mut latest = snapshots.get_latest_snapshot_index().await?;
+
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+            let milestones = self.db.collection::<MilestoneCollection>();
+            let (ledger_index, oldest) = match (
+                milestones.get_ledger_index().await?,
+                milestones.get_oldest_milestone().await?,
+            ) {
+                (Some(ledger_index), Some(oldest)) => (ledger_index, oldest),
+                _ => continue,
+            };
+            let mut index = next_snapshot_index(latest, ledger_index, self.config.interval);
+            while index <= ledger_index {
+                // The ledger state before the first stored milestone is unknown.
+                if index >= oldest.milestone_index {
+                    let start_time = std::time::Instant::now();
+                    // A failed snapshot is retried at the next interval.
+                    if let Err(e) = self.snapshot(index).await {
+                        error!("Failed to take the ledger snapshot at milestone {index}: {e}");
+                        break;
+                    }
+                    debug!(
+                        "Took the ledger snapshot at milestone {index} in {}ms.",
+                        start_time.elapsed().as_millis()
+                    );
+                }
+                latest = Some(index);
+                index += self.config.interval;
+            }
+        }
+    }
+
+    async fn snapshot(&self, index: MilestoneIndex) -> Result<()> {
+        let timestamp = match self
+            .db
+            .collection::<MilestoneCollection>()
+            .get_milestone_timestamp(index)
+            .await?
+        {
+            Some(timestamp) => timestamp,
+            None => {
+                warn!("Milestone {index} is missing, so no ledger snapshot is taken at it.");
+                return Ok(());
+            }
+        };
+        let digest = self
+            .db
+            .collection::<OutputCollection>()
+            .get_ledger_digest(index)
+            .await?;
+        self.db
+            .collection::<LedgerSnapshotCollection>()
+            .upsert_snapshot(index, timestamp, digest)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn snapshot_milestones() {
+        assert_eq!(next_snapshot_index(None, 25.into(), 10), 20);
+        assert_eq!(next_snapshot_index(None, 5.into(), 10), 0);
+        assert_eq!(next_snapshot_index(Some(20.into()), 25.into(), 10), 30);
+        // A changed interval continues at its next multiple.
+        assert_eq!(next_snapshot_index(Some(20.into()), 25.into(), 15), 30);
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Changes which logs are emitted while the application runs, so that a problem can be diagnosed without restarting
+/// with a different `RUST_LOG`, which would lose the state that caused it.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "api"), allow(dead_code))]
+pub struct LogFilter(reload::Handle<EnvFilter, Registry>);
+
+impl LogFilter {
+    /// Creates a handle to the given filter, along with the layer that applies it.
+    pub fn new(filter: EnvFilter) -> (Self, reload::Layer<EnvFilter, Registry>) {
+        let (layer, handle) = reload::Layer::new(filter);
+        (Self(handle), layer)
+    }
+
+    /// The directives of the current filter, e.g. `info,chronicle::inx=trace`.
+    #[cfg(feature = "api")]
+    pub fn directives(&self) -> Result<String, reload::Error> {
+        self.0.with_current(ToString::to_string)
+    }
+
+    /// Replaces the current filter.
+    #[cfg(feature = "api")]
+    pub fn replace(&self, filter: EnvFilter) -> Result<(), reload::Error> {
+        self.0.reload(filter)
+    }
+}
+
+#[cfg(all(test, feature = "api"))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn replace_filter() {
+        let (log_filter, layer) = LogFilter::new(EnvFilter::new("info"));
+        assert_eq!(log_filter.directives().unwrap(), "info");
+        log_filter.replace(EnvFilter::new("chronicle::inx=trace")).unwrap();
+        assert_eq!(log_filter.directives().unwrap(), "chronicle::inx=trace");
+        // The filter can no longer be changed once the layer is gone.
+        drop(layer);
+        assert!(log_filter.replace(EnvFilter::new("debug")).is_err());
+    }
+}
@@ -3,6 +3,10 @@
 
 //! Module that holds the entry point of the Chronicle application.
 
+#[cfg(feature = "explorer")]
+mod address_filter;
+#[cfg(feature = "analytics")]
+mod analytics_worker;
 /// Module containing the API.
 #[cfg(feature = "api")]
 mod api;
@@ -10,11 +14,28 @@ mod cli;
 mod config;
 #[cfg(feature = "inx")]
 mod inx;
+#[cfg(feature = "explorer")]
+mod ledger_snapshot;
+mod log_filter;
 mod migrations;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "explorer")]
+mod naming;
 mod process;
+mod pruning;
+#[cfg(feature = "search")]
+mod search;
+#[cfg(feature = "analytics")]
+mod spam_detection;
+mod storage_forecast;
+#[cfg(feature = "explorer")]
+mod token_distribution;
+#[cfg(feature = "explorer")]
+mod views;
 
 use bytesize::ByteSize;
-use chronicle::db::MongoDb;
+use chronicle::db::{mongodb::collections::ApplicationStateCollection, MongoDb};
 use clap::Parser;
 use tokio::task::JoinSet;
 use tracing::{debug, error, info};
@@ -22,7 +43,8 @@ use tracing_subscriber::{fmt::format::FmtSpan, layer::SubscriberExt, util::Subsc
 
 use self::{
     cli::{ClArgs, PostCommand},
-    migrations::check_migration_version,
+    log_filter::LogFilter,
+    migrations::{check_app_version, check_migration_version},
 };
 
 #[tokio::main]
@@ -32,7 +54,10 @@ async fn main() -> eyre::Result<()> {
     let cl_args = ClArgs::parse();
     let config = cl_args.get_config();
 
-    set_up_logging()?;
+    #[cfg_attr(not(feature = "api"), allow(unused_variables))]
+    let log_filter = set_up_logging()?;
+
+    config.validate()?;
 
     if cl_args.process_subcommands(&config).await? == PostCommand::Exit {
         return Ok(());
@@ -47,23 +72,87 @@ async fn main() -> eyre::Result<()> {
         ByteSize::b(db.size().await?)
     );
 
+    // Only the INX worker writes to the database, so only it records its version. A standby does so once promoted.
+    #[cfg(feature = "inx")]
+    let record_app_version = config.inx.enabled && !config.inx.standby;
+    #[cfg(not(feature = "inx"))]
+    let record_app_version = false;
+    check_app_version(&db, cl_args.allow_downgrade, record_app_version).await?;
     check_migration_version(&db).await?;
 
+    // Unlike the database id, the instance id identifies this process, so copies of a database can be told apart.
+    let instance_id = mongodb::bson::oid::ObjectId::new().to_hex();
+    info!("Instance id is `{instance_id}`.");
+
+    // Only the writer generates the database id, which is then copied along with any backup of the database.
+    let database_id = if record_app_version {
+        Some(register_writer(&db, &instance_id).await?)
+    } else {
+        db.collection::<ApplicationStateCollection>().get_database_id().await?
+    };
+    if let Some(database_id) = &database_id {
+        info!("Database id is `{database_id}`.");
+    }
+
     #[cfg(feature = "inx")]
-    build_indexes(&db).await?;
+    if record_app_version {
+        build_indexes(&db).await?;
+    }
+
+    // The retention of the InfluxDb databases is applied once on startup, instead of by every connection the workers
+    // open.
+    #[cfg(any(feature = "analytics", feature = "metrics"))]
+    {
+        #[allow(unused_mut)]
+        let mut influx_used = false;
+        #[cfg(all(feature = "inx", feature = "analytics"))]
+        {
+            influx_used |= config.inx.enabled && config.influxdb.analytics_enabled;
+        }
+        #[cfg(all(feature = "inx", feature = "metrics"))]
+        {
+            influx_used |= config.inx.enabled && config.influxdb.metrics_enabled;
+        }
+        #[cfg(feature = "analytics")]
+        {
+            influx_used |= config.analytics_worker.enabled || config.spam_detection.enabled;
+        }
+        #[cfg(feature = "metrics")]
+        {
+            influx_used |= config.storage_forecast.enabled && config.influxdb.metrics_enabled;
+        }
+        if influx_used {
+            chronicle::db::influxdb::InfluxDb::connect(&config.influxdb)
+                .await?
+                .apply_retention()
+                .await?;
+        }
+    }
 
     let mut tasks: JoinSet<eyre::Result<()>> = JoinSet::new();
 
+    // The workers that write to the database are held back while the instance is a standby.
+    #[allow(unused_mut)]
+    let mut writer_gate = WriterGate::default();
+
     let (shutdown_signal, _) = tokio::sync::broadcast::channel::<()>(1);
 
+    #[cfg(all(feature = "inx", feature = "api"))]
+    let mut sync_progress = None;
+    #[cfg(all(feature = "inx", feature = "api"))]
+    let mut standby_handle = None;
+
     #[cfg(feature = "inx")]
     if config.inx.enabled {
         #[cfg(feature = "influx")]
         #[allow(unused_mut)]
         let mut influx_required = false;
+        // Analytics are left to the analytics worker if it is enabled.
+        #[cfg(feature = "analytics")]
+        let analytics_inline = config.influxdb.analytics_enabled && !config.analytics_worker.enabled;
         #[cfg(feature = "analytics")]
         {
-            influx_required |= config.influxdb.analytics_enabled;
+            influx_required |= analytics_inline;
         }
         #[cfg(feature = "metrics")]
         {
@@ -73,7 +162,16 @@ async fn main() -> eyre::Result<()> {
         #[cfg(feature = "influx")]
         let influx_db = if influx_required {
             info!("Connecting to influx at `{}`", config.influxdb.url);
-            let influx_db = chronicle::db::influxdb::InfluxDb::connect(&config.influxdb).await?;
+            #[allow(unused_mut)]
+            let mut influx_config = config.influxdb.clone();
+            #[cfg(feature = "analytics")]
+            {
+                influx_config.analytics_enabled = analytics_inline;
+            }
+            #[allow(unused_mut)]
+            let mut influx_db = chronicle::db::influxdb::InfluxDb::connect(&influx_config).await?;
+            #[cfg(feature = "metrics")]
+            influx_db.set_instance(&instance_id, database_id.as_deref());
             #[cfg(feature = "analytics")]
             info!(
                 "Connected to influx database `{}`",
@@ -91,21 +189,100 @@ async fn main() -> eyre::Result<()> {
         if let Some(influx_db) = &influx_db {
             worker.set_influx_db(influx_db);
         }
+        #[cfg(feature = "mqtt")]
+        if config.mqtt.enabled {
+            let (publisher, connection) = mqtt::MqttPublisher::new(db.clone(), &config.mqtt)?;
+            chronicle::tangle::IngestionHooks::register(publisher);
+            let mut handle = shutdown_signal.subscribe();
+            tasks.spawn(async move {
+                tokio::select! {
+                    res = connection.run() => res?,
+                    _ = handle.recv() => {},
+                }
+                Ok(())
+            });
+        }
+        #[cfg(feature = "api")]
+        sync_progress.replace(worker.sync_progress());
+
+        let standby = config.inx.standby.then(inx::Standby::new);
+        #[cfg(feature = "api")]
+        {
+            standby_handle = standby.clone();
+        }
+        writer_gate.standby = standby.clone();
 
+        let worker_standby = standby.clone();
+        let allow_downgrade = cl_args.allow_downgrade;
+        let worker_db = db.clone();
+        let worker_instance_id = instance_id.clone();
         let mut handle = shutdown_signal.subscribe();
         tasks.spawn(async move {
+            if let Some(standby) = worker_standby {
+                let progress = worker.sync_progress();
+                tokio::select! {
+                    res = standby.follow(&worker_db, &progress) => res?,
+                    _ = handle.recv() => return Ok(()),
+                }
+                // Take over the duties of the writer, which were skipped on startup.
+                check_app_version(&worker_db, allow_downgrade, true).await?;
+                register_writer(&worker_db, &worker_instance_id).await?;
+                build_indexes(&worker_db).await?;
+            }
             tokio::select! {
                 res = worker.run() => res?,
                 _ = handle.recv() => {},
             }
             Ok(())
         });
+
+        let db = db.clone();
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = migrations::upgrade_output_details(&db) => res?,
+                _ = handle.recv() => return Ok(()),
+            }
+            // A finished task shuts the application down, so wait for the signal once the upgrade is done.
+            handle.recv().await.ok();
+            Ok(())
+        });
     }
 
+    #[cfg(feature = "search")]
+    let search_client = if config.search.enabled {
+        Some(search::SearchClient::new(&config.search)?)
+    } else {
+        None
+    };
+
     #[cfg(feature = "api")]
     if config.api.enabled {
         use futures::FutureExt;
-        let worker = api::ApiWorker::new(db.clone(), config.api.clone())?;
+        #[allow(unused_mut)]
+        let mut worker = api::ApiWorker::new(db.clone(), config.api.clone())?;
+        #[cfg(feature = "search")]
+        if let Some(search_client) = &search_client {
+            worker.set_search_client(search_client.clone());
+        }
+        worker.set_log_filter(log_filter.clone());
+        worker.set_instance_id(instance_id.clone());
+        #[cfg(feature = "explorer")]
+        if config.naming.is_enabled() {
+            worker.set_name_resolver(std::sync::Arc::new(naming::HttpNameResolver::new(&config.naming)?));
+        }
+        #[cfg(feature = "inx")]
+        if let Some(sync_progress) = &sync_progress {
+            worker.set_sync_progress(sync_progress.clone());
+        }
+        #[cfg(feature = "inx")]
+        if let Some(standby) = &standby_handle {
+            worker.set_standby(standby.clone());
+        }
         let mut handle = shutdown_signal.subscribe();
         tasks.spawn(async move {
             worker.run(handle.recv().then(|_| async {})).await?;
@@ -113,6 +290,169 @@ async fn main() -> eyre::Result<()> {
         });
     }
 
+    #[cfg(feature = "analytics")]
+    if config.analytics_worker.enabled {
+        #[allow(unused_mut)]
+        let mut influx_db = chronicle::db::influxdb::InfluxDb::connect(&config.influxdb).await?;
+        #[cfg(feature = "metrics")]
+        influx_db.set_instance(&instance_id, database_id.as_deref());
+        let mut worker = analytics_worker::AnalyticsWorker::new(db.clone(), influx_db, config.analytics_worker.clone());
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    #[cfg(feature = "analytics")]
+    if config.spam_detection.enabled {
+        let influx_db = chronicle::db::influxdb::InfluxDb::connect(&config.influxdb).await?;
+        let worker = spam_detection::SpamDetectionWorker::new(db.clone(), influx_db, config.spam_detection.clone());
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    #[cfg(feature = "explorer")]
+    if config.views.enabled {
+        let worker = views::ViewsWorker::new(db.clone(), config.views.clone());
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    #[cfg(feature = "explorer")]
+    if config.token_distribution.enabled {
+        let worker = token_distribution::TokenDistributionWorker::new(db.clone(), config.token_distribution.clone());
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    #[cfg(feature = "explorer")]
+    if config.ledger_snapshot.enabled {
+        let worker = ledger_snapshot::LedgerSnapshotWorker::new(db.clone(), config.ledger_snapshot.clone());
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    #[cfg(feature = "explorer")]
+    if config.address_filter.enabled {
+        let worker = address_filter::AddressFilterWorker::new(db.clone(), config.address_filter.clone());
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    if config.pruning.retention_file.is_some() {
+        let worker = pruning::PruningWorker::new(db.clone(), config.pruning.clone())?;
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    if config.storage_forecast.enabled {
+        #[allow(unused_mut)]
+        let mut worker = storage_forecast::StorageForecastWorker::new(db.clone(), config.storage_forecast.clone());
+        #[cfg(feature = "metrics")]
+        if config.influxdb.metrics_enabled {
+            let mut influx_db = chronicle::db::influxdb::InfluxDb::connect(&config.influxdb).await?;
+            influx_db.set_instance(&instance_id, database_id.as_deref());
+            worker.set_influx_db(influx_db);
+        }
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
+    #[cfg(feature = "search")]
+    if let Some(search_client) = search_client {
+        let worker = search::SearchWorker::new(db.clone(), search_client, config.search.clone());
+        let writer_gate = writer_gate.clone();
+        let mut handle = shutdown_signal.subscribe();
+        tasks.spawn(async move {
+            if !writer_gate.wait(&mut handle).await {
+                return Ok(());
+            }
+            tokio::select! {
+                res = worker.run() => res?,
+                _ = handle.recv() => {},
+            }
+            Ok(())
+        });
+    }
+
     let mut exit_code = Ok(());
 
     // We wait for either the interrupt signal to arrive or for a component of our system to signal a shutdown.
@@ -155,21 +495,53 @@ async fn main() -> eyre::Result<()> {
     exit_code
 }
 
-fn set_up_logging() -> eyre::Result<()> {
+fn set_up_logging() -> eyre::Result<LogFilter> {
     std::panic::set_hook(Box::new(|p| {
         error!("{}", p);
     }));
 
     let registry = tracing_subscriber::registry();
+    let (log_filter, filter) = LogFilter::new(EnvFilter::from_default_env());
 
     let registry = {
         registry
-            .with(EnvFilter::from_default_env())
+            .with(filter)
             .with(tracing_subscriber::fmt::layer().with_span_events(FmtSpan::CLOSE))
     };
 
     registry.init();
-    Ok(())
+    Ok(log_filter)
+}
+
+/// Holds back the workers that write to the database while the instance is a standby, so that only the instance it
+/// stands in for writes.
+#[derive(Clone, Default)]
+struct WriterGate {
+    #[cfg(feature = "inx")]
+    standby: Option<inx::Standby>,
+}
+
+impl WriterGate {
+    /// Waits until the instance may write to the database. Returns `false` if the application shuts down first.
+    #[cfg_attr(not(feature = "inx"), allow(unused_variables))]
+    async fn wait(&self, handle: &mut tokio::sync::broadcast::Receiver<()>) -> bool {
+        #[cfg(feature = "inx")]
+        if let Some(standby) = &self.standby {
+            return tokio::select! {
+                _ = standby.promoted() => true,
+                _ = handle.recv() => false,
+            };
+        }
+        true
+    }
+}
+
+/// Records this process as the writer of the database, and returns the id of the database.
+async fn register_writer(db: &MongoDb, instance_id: &str) -> eyre::Result<String> {
+    let app_state = db.collection::<ApplicationStateCollection>();
+    let database_id = app_state.get_or_init_database_id().await?;
+    app_state.set_writer_instance_id(instance_id).await?;
+    Ok(database_id)
 }
 
 async fn build_indexes(db: &MongoDb) -> eyre::Result<()> {
@@ -179,6 +551,10 @@ async fn build_indexes(db: &MongoDb) -> eyre::Result<()> {
     db.create_indexes::<collections::BlockCollection>().await?;
     db.create_indexes::<collections::LedgerUpdateCollection>().await?;
     db.create_indexes::<collections::MilestoneCollection>().await?;
+    db.create_indexes::<collections::ParentsCollection>().await?;
+    db.create_indexes::<collections::PendingTransactionCollection>().await?;
+    db.create_indexes::<collections::QueryCacheCollection>().await?;
+    db.create_indexes::<collections::TokenDistributionCollection>().await?;
     let end_indexes = db.get_index_names().await?;
     for (collection, indexes) in end_indexes {
         if let Some(old_indexes) = start_indexes.get(&collection) {
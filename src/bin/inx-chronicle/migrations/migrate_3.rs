@@ -0,0 +1,71 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use async_trait::async_trait;
+use chronicle::{
+    db::{
+        mongodb::collections::{BlockCollection, ParentsCollection},
+        MongoDb, MongoDbCollectionExt,
+    },
+    model::{metadata::BlockMetadata, BlockId},
+};
+use futures::{prelude::stream::TryStreamExt, StreamExt};
+use mongodb::{bson::doc, options::FindOptions};
+use serde::Deserialize;
+use tokio::task::JoinSet;
+
+use super::Migration;
+
+const INSERT_BATCH_SIZE: usize = 1000;
+
+pub struct Migrate;
+
+#[async_trait]
+impl Migration for Migrate {
+    const ID: usize = 3;
+    const APP_VERSION: &'static str = "1.0.0-rc.4";
+    const DATE: time::Date = time::macros::date!(2026 - 10 - 15);
+
+    async fn migrate(db: &MongoDb) -> eyre::Result<()> {
+        let blocks_stream = db
+            .collection::<BlockCollection>()
+            .find::<BlockDocument>(
+                doc! {},
+                FindOptions::builder()
+                    .projection(doc! { "_id": 1, "metadata": 1 })
+                    .build(),
+            )
+            .await?;
+        let mut batched_stream = blocks_stream.try_chunks(INSERT_BATCH_SIZE);
+
+        let mut tasks = JoinSet::new();
+
+        while let Some(batch) = batched_stream.next().await {
+            let batch = batch?;
+            while tasks.len() >= 100 {
+                if let Some(res) = tasks.join_next().await {
+                    res??;
+                }
+            }
+            let db = db.clone();
+            tasks.spawn(async move {
+                db.collection::<ParentsCollection>()
+                    .insert_relationships(batch.iter().map(|doc| (doc.block_id, &doc.metadata)))
+                    .await
+            });
+        }
+
+        while let Some(res) = tasks.join_next().await {
+            res??;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct BlockDocument {
+    #[serde(rename = "_id")]
+    block_id: BlockId,
+    metadata: BlockMetadata,
+}
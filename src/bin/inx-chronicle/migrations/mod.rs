@@ -1,20 +1,22 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::HashMap};
 
 use async_trait::async_trait;
 use chronicle::db::{
-    mongodb::collections::{ApplicationStateCollection, MigrationVersion},
+    mongodb::collections::{AppVersion, ApplicationStateCollection, MigrationVersion, OUTPUT_DETAILS_VERSION},
     MongoDb,
 };
 use eyre::bail;
+use semver::Version;
 
 pub mod migrate_0;
 pub mod migrate_1;
 pub mod migrate_2;
+pub mod migrate_3;
 
-pub type LatestMigration = migrate_2::Migrate;
+pub type LatestMigration = migrate_3::Migrate;
 
 /// The list of migrations, in order.
 const MIGRATIONS: &[&'static dyn DynMigration] = &[
@@ -23,6 +25,7 @@ const MIGRATIONS: &[&'static dyn DynMigration] = &[
     &migrate_0::Migrate,
     &migrate_1::Migrate,
     &migrate_2::Migrate,
+    &migrate_3::Migrate,
 ];
 
 fn build_migrations(migrations: &[&'static dyn DynMigration]) -> HashMap<Option<usize>, &'static dyn DynMigration> {
@@ -36,6 +39,36 @@ fn build_migrations(migrations: &[&'static dyn DynMigration]) -> HashMap<Option<
     map
 }
 
+#[cfg(feature = "inx")]
+const DETAILS_UPGRADE_BATCH_SIZE: usize = 1000;
+
+/// Upgrades all output details that were derived with an older version. Unlike a migration, this does not block
+/// startup, and outputs that are queried in the meantime are upgraded on access. Once all outputs are upgraded, the
+/// version is recorded, so that queries which depend on the new details can be served.
+#[cfg(feature = "inx")]
+pub async fn upgrade_output_details(db: &MongoDb) -> eyre::Result<()> {
+    use chronicle::db::mongodb::collections::OutputCollection;
+
+    let outputs = db.collection::<OutputCollection>();
+    let mut total = 0;
+    let mut last = None;
+    while let Some((count, last_id)) = outputs
+        .upgrade_outdated_details(last, DETAILS_UPGRADE_BATCH_SIZE)
+        .await?
+    {
+        total += count;
+        last = Some(last_id);
+        tracing::debug!("Upgraded the details of {total} outputs so far.");
+    }
+    if total > 0 {
+        tracing::info!("Upgraded the details of {total} outputs to version {OUTPUT_DETAILS_VERSION}.");
+    }
+    db.collection::<ApplicationStateCollection>()
+        .set_output_details_version(OUTPUT_DETAILS_VERSION)
+        .await?;
+    Ok(())
+}
+
 #[async_trait]
 pub trait Migration {
     const ID: usize;
@@ -110,6 +143,59 @@ pub async fn check_migration_version(db: &MongoDb) -> eyre::Result<()> {
     Ok(())
 }
 
+/// The version of this binary and the fingerprint of the schema it writes. The fingerprint changes with every
+/// migration and with every change to how output details are derived.
+pub fn app_version() -> AppVersion {
+    AppVersion {
+        version: std::env!("CARGO_PKG_VERSION").to_string(),
+        schema_fingerprint: format!("migration-{}.details-{}", LatestMigration::ID, OUTPUT_DETAILS_VERSION),
+    }
+}
+
+/// Compares the semantic versions of two Chronicle builds by precedence, i.e. numerically by their components and
+/// without regard to build metadata.
+fn cmp_versions(stored: &AppVersion, current: &AppVersion) -> eyre::Result<Ordering> {
+    Ok(Version::parse(&stored.version)?.cmp_precedence(&Version::parse(&current.version)?))
+}
+
+/// Whether the database was written by a newer Chronicle, or by a different build of the same version.
+pub fn is_downgrade(stored: &AppVersion, current: &AppVersion) -> eyre::Result<bool> {
+    Ok(match cmp_versions(stored, current)? {
+        Ordering::Greater => true,
+        Ordering::Equal => stored.schema_fingerprint != current.schema_fingerprint,
+        Ordering::Less => false,
+    })
+}
+
+/// Refuses to run against a database that was written by a newer Chronicle, unless `allow_downgrade` is set, because
+/// an older version does not know how to maintain the fields derived by the newer one. If `record` is set, the
+/// version of this binary is stored once it is the newest one that wrote to the database.
+pub async fn check_app_version(db: &MongoDb, allow_downgrade: bool, record: bool) -> eyre::Result<()> {
+    let current = app_version();
+    let app_state = db.collection::<ApplicationStateCollection>();
+    match app_state.get_app_version().await? {
+        Some(stored) if stored == current => return Ok(()),
+        Some(stored) if is_downgrade(&stored, &current)? => {
+            if !allow_downgrade {
+                bail!(
+                    "the database was written by Chronicle {stored}, which is newer than this version {current}; \
+                    use `--allow-downgrade` to run anyway"
+                );
+            }
+            tracing::warn!("Running Chronicle {current} against a database written by Chronicle {stored}.");
+            if cmp_versions(&stored, &current)? == Ordering::Greater {
+                // Keep the newer version, so that every downgraded start has to be allowed explicitly.
+                return Ok(());
+            }
+        }
+        _ => (),
+    }
+    if record {
+        app_state.set_app_version(&current).await?;
+    }
+    Ok(())
+}
+
 pub async fn migrate(db: &MongoDb) -> eyre::Result<()> {
     let migrations = build_migrations(MIGRATIONS);
 
@@ -137,3 +223,29 @@ pub async fn migrate(db: &MongoDb) -> eyre::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_downgrade() {
+        let version = |version: &str, schema_fingerprint: &str| AppVersion {
+            version: version.to_string(),
+            schema_fingerprint: schema_fingerprint.to_string(),
+        };
+        let current = version("1.0.0-rc.4", "migration-3.details-1");
+        assert!(!is_downgrade(&current, &current).unwrap());
+        assert!(!is_downgrade(&version("1.0.0-rc.3", "migration-2.details-0"), &current).unwrap());
+        assert!(is_downgrade(&version("1.0.0-rc.5", "migration-3.details-1"), &current).unwrap());
+        assert!(is_downgrade(&version("1.0.0", "migration-3.details-1"), &current).unwrap());
+        assert!(is_downgrade(&version("1.0.0-rc.4", "migration-3.details-2"), &current).unwrap());
+        assert!(is_downgrade(&version("1.0.0-rc.10", "migration-3.details-1"), &current).unwrap());
+        assert!(!is_downgrade(&version("1.0.0-rc.4+build.2", "migration-3.details-1"), &current).unwrap());
+        assert!(is_downgrade(&version("1.0.0-rc.4+build.2", "migration-3.details-2"), &current).unwrap());
+        let current = version("1.2.0", "migration-3.details-1");
+        assert!(is_downgrade(&version("1.10.0", "migration-3.details-1"), &current).unwrap());
+        assert!(!is_downgrade(&version("1.1.9", "migration-3.details-1"), &current).unwrap());
+        assert!(is_downgrade(&version("invalid", "migration-3.details-1"), &current).is_err());
+    }
+}
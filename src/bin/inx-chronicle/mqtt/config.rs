@@ -0,0 +1,30 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub const DEFAULT_ENABLED: bool = false;
+pub const DEFAULT_URL: &str = "mqtt://localhost:1883";
+pub const DEFAULT_CLIENT_ID: &str = "chronicle";
+pub const DEFAULT_CAPACITY: usize = 10000;
+
+/// Configuration of the MQTT publisher.
+#[derive(Clone, Debug)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    /// The url of the broker, which may contain the username and password.
+    pub url: String,
+    /// The client id Chronicle connects to the broker with.
+    pub client_id: String,
+    /// How many events are buffered while the broker is slow or unreachable, before further events are dropped.
+    pub capacity: usize,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            url: DEFAULT_URL.to_string(),
+            client_id: DEFAULT_CLIENT_ID.to_string(),
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+}
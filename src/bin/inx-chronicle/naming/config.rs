@@ -0,0 +1,37 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_TIMEOUT: &str = "2s";
+
+/// Configuration of the external naming service that maps human-readable names to addresses.
+#[derive(Clone, Debug)]
+pub struct NamingConfig {
+    /// The url that resolves a name to an address, in which `{name}` is replaced by the name. Name resolution is
+    /// disabled if it is not set.
+    pub resolve_url: Option<String>,
+    /// The url that looks up the name of an address, in which `{address}` is replaced by the bech32 address.
+    pub reverse_url: Option<String>,
+    /// The key used to authenticate with the naming service, if it requires one.
+    pub api_key: Option<String>,
+    /// How long to wait for the naming service before giving up on a lookup.
+    pub timeout: Duration,
+}
+
+impl NamingConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.resolve_url.is_some() || self.reverse_url.is_some()
+    }
+}
+
+impl Default for NamingConfig {
+    fn default() -> Self {
+        Self {
+            resolve_url: None,
+            reverse_url: None,
+            api_key: None,
+            timeout: DEFAULT_TIMEOUT.parse::<humantime::Duration>().unwrap().into(),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use std::{str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use chronicle::model::utxo::Address;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub use self::config::NamingConfig;
+
+#[derive(Debug, Error)]
+pub enum NamingError {
+    #[error("naming service request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("naming service responded with status {status}: {body}")]
+    Status { status: StatusCode, body: String },
+    #[error("naming service resolved to the invalid address `{address}`: {source}")]
+    InvalidAddress {
+        address: String,
+        source: iota_sdk::types::block::Error,
+    },
+}
+
+/// Maps human-readable names to addresses and back.
+#[async_trait]
+pub trait NameResolver: std::fmt::Debug + Send + Sync {
+    /// Resolves a name to a bech32 address, if the name is registered.
+    #[cfg_attr(not(feature = "search"), allow(dead_code))]
+    async fn resolve(&self, name: &str) -> Result<Option<String>, NamingError>;
+
+    /// Looks up the name of a bech32 address, if it has one.
+    async fn reverse(&self, address: &str) -> Result<Option<String>, NamingError>;
+}
+
+/// A name resolver that can be shared between the API handlers.
+pub type SharedNameResolver = Arc<dyn NameResolver>;
+
+/// Resolves names through a naming service that answers `GET` requests on configurable urls with a JSON object
+/// holding the `address` or `name`, and with `404 Not Found` for unknown names and addresses.
+#[derive(Clone, Debug)]
+pub struct HttpNameResolver {
+    http: reqwest::Client,
+    #[cfg_attr(not(feature = "search"), allow(dead_code))]
+    resolve_url: Option<String>,
+    reverse_url: Option<String>,
+    api_key: Option<String>,
+}
+
+impl HttpNameResolver {
+    pub fn new(config: &NamingConfig) -> Result<Self, NamingError> {
+        Ok(Self {
+            http: reqwest::Client::builder().timeout(config.timeout).build()?,
+            resolve_url: config.resolve_url.clone(),
+            reverse_url: config.reverse_url.clone(),
+            api_key: config.api_key.clone(),
+        })
+    }
+
+    async fn get<T: for<'de> Deserialize<'de>>(&self, url: String) -> Result<Option<T>, NamingError> {
+        let mut request = self.http.get(url);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(NamingError::Status {
+                status,
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(Some(response.json().await?))
+    }
+}
+
+/// Fills a url template, escaping the value so it cannot change the structure of the url. Spaces are
+/// percent-encoded rather than written as `+`, which only means a space in a query.
+fn fill(template: &str, placeholder: &str, value: &str) -> String {
+    template.replace(
+        placeholder,
+        // A `+` in the value is escaped as well, so any that is left stands for a space.
+        &url::form_urlencoded::byte_serialize(value.as_bytes())
+            .collect::<String>()
+            .replace('+', "%20"),
+    )
+}
+
+#[async_trait]
+impl NameResolver for HttpNameResolver {
+    async fn resolve(&self, name: &str) -> Result<Option<String>, NamingError> {
+        #[derive(Deserialize)]
+        struct ResolveResponse {
+            address: String,
+        }
+
+        let template = match &self.resolve_url {
+            Some(template) => template,
+            None => return Ok(None),
+        };
+        // The address is shown to users as the one the name stands for, so it must be a valid one.
+        match self.get::<ResolveResponse>(fill(template, "{name}", name)).await? {
+            Some(ResolveResponse { address }) => match Address::from_str(&address) {
+                Ok(_) => Ok(Some(address)),
+                Err(source) => Err(NamingError::InvalidAddress { address, source }),
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn reverse(&self, address: &str) -> Result<Option<String>, NamingError> {
+        #[derive(Deserialize)]
+        struct ReverseResponse {
+            name: String,
+        }
+
+        let template = match &self.reverse_url {
+            Some(template) => template,
+            None => return Ok(None),
+        };
+        Ok(self
+            .get::<ReverseResponse>(fill(template, "{address}", address))
+            .await?
+            .map(|res| res.name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn fill_url_template() {
+        assert_eq!(
+            fill("https://names.example/resolve/{name}", "{name}", "alice.iota"),
+            "https://names.example/resolve/alice.iota"
+        );
+        assert_eq!(
+            fill("https://names.example/resolve?name={name}", "{name}", "a/b&c=d"),
+            "https://names.example/resolve?name=a%2Fb%26c%3Dd"
+        );
+        assert_eq!(
+            fill("https://names.example/resolve/{name}", "{name}", "alice smith+bob"),
+            "https://names.example/resolve/alice%20smith%2Bbob"
+        );
+    }
+}
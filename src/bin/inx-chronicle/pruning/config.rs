@@ -0,0 +1,50 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_INTERVAL: &str = "1h";
+#[cfg(feature = "archive")]
+pub const DEFAULT_ARCHIVE_S3_REGION: &str = "us-east-1";
+
+/// Configuration of the pruning worker, which only runs if retention rules are given.
+#[derive(Clone, Debug)]
+pub struct PruningConfig {
+    /// A JSON file containing the retention rules.
+    pub retention_file: Option<String>,
+    /// How often the retention rules are applied.
+    pub interval: Duration,
+    /// Where milestones are archived before any of their data is pruned.
+    #[cfg(feature = "archive")]
+    pub archive: Option<ArchiveConfig>,
+}
+
+impl Default for PruningConfig {
+    fn default() -> Self {
+        Self {
+            retention_file: None,
+            interval: DEFAULT_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+            #[cfg(feature = "archive")]
+            archive: None,
+        }
+    }
+}
+
+/// The location of the cold archive.
+#[cfg(feature = "archive")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveConfig {
+    Directory(std::path::PathBuf),
+    S3(chronicle::archive::S3Config),
+}
+
+#[cfg(feature = "archive")]
+impl ArchiveConfig {
+    pub fn open(&self) -> Result<chronicle::archive::MilestoneArchive, chronicle::archive::ArchiveError> {
+        use chronicle::archive::{ArchiveLocation, MilestoneArchive};
+        Ok(MilestoneArchive::new(match self {
+            Self::Directory(dir) => ArchiveLocation::Directory(dir.clone()),
+            Self::S3(config) => ArchiveLocation::s3(config.clone())?,
+        }))
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use std::collections::HashSet;
+
+use chronicle::{
+    db::{
+        mongodb::collections::{
+            ApplicationStateCollection, BlockCollection, LedgerUpdateCollection, MilestoneCollection, OutputCollection,
+            ParentsCollection,
+        },
+        MongoDb,
+    },
+    model::{
+        payload::{TaggedDataPayload, TransactionPayload, TreasuryTransactionPayload},
+        tangle::MilestoneIndex,
+        utxo::{AliasOutput, BasicOutput, FoundryOutput, NftOutput, TreasuryOutput},
+    },
+};
+use eyre::{bail, Result};
+use futures::TryStreamExt;
+use serde::Deserialize;
+use tracing::{debug, error, info};
+
+pub use self::config::PruningConfig;
+#[cfg(feature = "archive")]
+use chronicle::{
+    archive::{ArchivedMilestone, MilestoneArchive},
+    tangle::InputSource,
+};
+
+/// The number of documents deleted at once.
+pub const PRUNE_BATCH_SIZE: usize = 1000;
+
+/// The kind of payload that stands for blocks without a payload in retention rules.
+const NO_PAYLOAD: &str = "none";
+
+/// What a retention rule applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RetentionTarget {
+    /// The spent outputs of a kind, along with their ledger updates.
+    OutputKind(String),
+    /// The blocks with a payload of a kind, or without a payload for `none`.
+    PayloadKind(String),
+}
+
+/// How long the data of a kind is kept.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionRule {
+    #[serde(flatten)]
+    pub target: RetentionTarget,
+    /// The number of milestones for which the data is kept. The data is kept forever if this is not set.
+    pub keep_milestones: Option<u32>,
+}
+
+/// Reads retention rules from JSON, such as
+/// `[{ "outputKind": "nft" }, { "outputKind": "basic", "keepMilestones": 60480 }]`.
+///
+/// Data that no rule applies to is kept forever.
+fn parse_rules(json: &str) -> Result<Vec<RetentionRule>> {
+    let rules: Vec<RetentionRule> = serde_json::from_str(json)?;
+    let mut targets = HashSet::new();
+    for rule in &rules {
+        match &rule.target {
+            RetentionTarget::OutputKind(kind) => {
+                if ![
+                    BasicOutput::KIND,
+                    AliasOutput::KIND,
+                    FoundryOutput::KIND,
+                    NftOutput::KIND,
+                    TreasuryOutput::KIND,
+                ]
+                .contains(&kind.as_str())
+                {
+                    bail!("unknown output kind `{kind}` in retention rules");
+                }
+            }
+            RetentionTarget::PayloadKind(kind) => {
+                // Milestone blocks are always kept, since the milestones are served from them.
+                if ![
+                    TransactionPayload::KIND,
+                    TaggedDataPayload::KIND,
+                    TreasuryTransactionPayload::KIND,
+                    NO_PAYLOAD,
+                ]
+                .contains(&kind.as_str())
+                {
+                    bail!("unknown or unprunable payload kind `{kind}` in retention rules");
+                }
+            }
+        }
+        if !targets.insert(&rule.target) {
+            bail!("duplicate retention rule for {:?}", rule.target);
+        }
+    }
+    Ok(rules)
+}
+
+/// Periodically deletes the data that is older than its retention rule allows.
+pub struct PruningWorker {
+    db: MongoDb,
+    config: PruningConfig,
+    #[cfg(feature = "archive")]
+    archive: Option<MilestoneArchive>,
+}
+
+impl PruningWorker {
+    pub fn new(db: MongoDb, config: PruningConfig) -> Result<Self> {
+        Ok(Self {
+            db,
+            #[cfg(feature = "archive")]
+            archive: config.archive.as_ref().map(|archive| archive.open()).transpose()?,
+            config,
+        })
+    }
+
+    /// Gets the retention rules from the retention file.
+    fn rules(&self) -> Result<Vec<RetentionRule>> {
+        match &self.config.retention_file {
+            Some(path) => parse_rules(&std::fs::read_to_string(path)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let rules = self.rules()?;
+        info!(
+            "Pruning with {} retention rules, of which {} keep data forever.",
+            rules.len(),
+            rules.iter().filter(|rule| rule.keep_milestones.is_none()).count()
+        );
+
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            let ledger_index = match self.db.collection::<MilestoneCollection>().get_ledger_index().await? {
+                Some(ledger_index) => ledger_index,
+                None => continue,
+            };
+            let prunable = rules
+                .iter()
+                .filter_map(|rule| {
+                    rule.keep_milestones
+                        .map(|keep| (rule, MilestoneIndex(ledger_index.0.saturating_sub(keep))))
+                })
+                .collect::<Vec<_>>();
+            #[cfg(feature = "archive")]
+            if let (Some(archive), Some(before)) = (&self.archive, prunable.iter().map(|(_, before)| *before).max()) {
+                // Nothing is pruned unless it was archived first.
+                if let Err(e) = self.archive_before(archive, before).await {
+                    error!("Failed to archive milestones before milestone {before}: {e}");
+                    continue;
+                }
+            }
+            for (rule, before) in prunable {
+                let start_time = std::time::Instant::now();
+                // A failed rule is retried at the next interval.
+                match self.prune(&rule.target, before).await {
+                    Ok(count) => debug!(
+                        "Pruned {count} documents for {:?} before milestone {before} in {}ms.",
+                        rule.target,
+                        start_time.elapsed().as_millis()
+                    ),
+                    Err(e) => error!("Failed to prune {:?}: {e}", rule.target),
+                }
+            }
+        }
+    }
+
+    /// Archives the milestones before the given one that are not archived yet.
+    #[cfg(feature = "archive")]
+    async fn archive_before(&self, archive: &MilestoneArchive, before: MilestoneIndex) -> Result<()> {
+        let start = match archive.manifest().await? {
+            Some(manifest) => manifest.last + 1,
+            None => match self.db.collection::<MilestoneCollection>().get_oldest_milestone().await? {
+                Some(oldest) => oldest.milestone_index,
+                None => return Ok(()),
+            },
+        };
+        if start >= before {
+            return Ok(());
+        }
+        let start_time = std::time::Instant::now();
+        let mut milestones = InputSource::milestone_stream(&self.db, start..before).await?;
+        while let Some(milestone) = milestones.try_next().await? {
+            archive
+                .write(&ArchivedMilestone::collect(&self.db, milestone).await?)
+                .await?;
+        }
+        debug!(
+            "Archived milestones {start} to {} in {}ms.",
+            before - 1,
+            start_time.elapsed().as_millis()
+        );
+        Ok(())
+    }
+
+    async fn prune(&self, target: &RetentionTarget, before: MilestoneIndex) -> Result<u64> {
+        let mut count = 0;
+        match target {
+            RetentionTarget::OutputKind(kind) => {
+                // This is recorded up front, since it only applies to outputs that are no longer stored, so that the
+                // outputs pruned by an interrupted run are reported as pruned too.
+                self.db
+                    .collection::<ApplicationStateCollection>()
+                    .set_output_pruning_index(kind, before)
+                    .await?;
+                let outputs = self.db.collection::<OutputCollection>();
+                let mut output_ids = outputs
+                    .get_prunable_output_ids(kind, before)
+                    .await?
+                    .try_chunks(PRUNE_BATCH_SIZE);
+                while let Some(output_ids) = output_ids.try_next().await? {
+                    // The ledger updates go first, so that no update is left without its output if pruning stops.
+                    self.db
+                        .collection::<LedgerUpdateCollection>()
+                        .delete_output_updates(&output_ids)
+                        .await?;
+                    count += outputs.delete_outputs(&output_ids).await?;
+                }
+            }
+            RetentionTarget::PayloadKind(kind) => {
+                let blocks = self.db.collection::<BlockCollection>();
+                let mut block_ids = blocks
+                    .get_prunable_block_ids((kind != NO_PAYLOAD).then(|| kind.as_str()), before)
+                    .await?
+                    .try_chunks(PRUNE_BATCH_SIZE);
+                while let Some(block_ids) = block_ids.try_next().await? {
+                    self.db
+                        .collection::<ParentsCollection>()
+                        .delete_relationships(&block_ids)
+                        .await?;
+                    count += blocks.delete_blocks(&block_ids).await?;
+                }
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn retention_rules() {
+        let rules = parse_rules(
+            r#"[
+                { "outputKind": "nft" },
+                { "outputKind": "basic", "keepMilestones": 60480 },
+                { "payloadKind": "none", "keepMilestones": 8640 }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules,
+            vec![
+                RetentionRule {
+                    target: RetentionTarget::OutputKind("nft".to_string()),
+                    keep_milestones: None,
+                },
+                RetentionRule {
+                    target: RetentionTarget::OutputKind("basic".to_string()),
+                    keep_milestones: Some(60480),
+                },
+                RetentionRule {
+                    target: RetentionTarget::PayloadKind("none".to_string()),
+                    keep_milestones: Some(8640),
+                },
+            ]
+        );
+
+        assert!(parse_rules(r#"[{ "outputKind": "account" }]"#).is_err());
+        assert!(parse_rules(r#"[{ "payloadKind": "milestone" }]"#).is_err());
+        assert!(parse_rules(r#"[{ "outputKind": "nft" }, { "outputKind": "nft", "keepMilestones": 1 }]"#).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use reqwest::{header::CONTENT_TYPE, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+
+use super::{
+    config::{SearchConfig, SearchEngine},
+    documents::SearchDocument,
+};
+
+#[derive(Debug, Error)]
+pub enum SearchError {
+    #[error("search engine request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("search engine responded with status {status}: {body}")]
+    Status { status: StatusCode, body: String },
+    #[error("search engine rejected some documents")]
+    Rejected,
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// A client for the external search engine, which hides the differences between the supported engines.
+#[derive(Clone, Debug)]
+pub struct SearchClient {
+    http: reqwest::Client,
+    engine: SearchEngine,
+    url: String,
+    api_key: Option<String>,
+    index_name: String,
+}
+
+impl SearchClient {
+    pub fn new(config: &SearchConfig) -> Result<Self, SearchError> {
+        Ok(Self {
+            http: reqwest::Client::builder().build()?,
+            engine: config.engine,
+            url: config.url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            index_name: config.index_name.clone(),
+        })
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        let request = self.http.post(format!("{}{path}", self.url));
+        match (&self.api_key, self.engine) {
+            (None, _) => request,
+            (Some(key), SearchEngine::Meilisearch) => request.bearer_auth(key),
+            (Some(key), SearchEngine::Elasticsearch) => request.header("Authorization", format!("ApiKey {key}")),
+        }
+    }
+
+    async fn send(request: RequestBuilder) -> Result<reqwest::Response, SearchError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SearchError::Status {
+                status,
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(response)
+    }
+
+    /// Adds the documents to the index, replacing the ones with the same ids.
+    pub async fn index_documents(&self, documents: &[SearchDocument]) -> Result<(), SearchError> {
+        if documents.is_empty() {
+            return Ok(());
+        }
+        match self.engine {
+            SearchEngine::Meilisearch => {
+                // Documents are added asynchronously by Meilisearch, so the response only acknowledges the task.
+                Self::send(
+                    self.post(&format!("/indexes/{}/documents?primaryKey=id", self.index_name))
+                        .json(documents),
+                )
+                .await?;
+            }
+            SearchEngine::Elasticsearch => {
+                #[derive(Deserialize)]
+                struct BulkResponse {
+                    errors: bool,
+                }
+
+                let mut body = String::new();
+                for document in documents {
+                    body.push_str(&json!({ "index": { "_index": self.index_name, "_id": document.id } }).to_string());
+                    body.push('\n');
+                    body.push_str(&serde_json::to_string(document)?);
+                    body.push('\n');
+                }
+                let response = Self::send(
+                    self.post("/_bulk")
+                        .header(CONTENT_TYPE, "application/x-ndjson")
+                        .body(body),
+                )
+                .await?;
+                if response.json::<BulkResponse>().await?.errors {
+                    return Err(SearchError::Rejected);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a typo-tolerant query against the index and returns the best matching documents.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchDocument>, SearchError> {
+        match self.engine {
+            SearchEngine::Meilisearch => {
+                #[derive(Deserialize)]
+                struct SearchResponse {
+                    hits: Vec<SearchDocument>,
+                }
+
+                let response = Self::send(
+                    self.post(&format!("/indexes/{}/search", self.index_name))
+                        .json(&json!({ "q": query, "limit": limit })),
+                )
+                .await?;
+                Ok(response.json::<SearchResponse>().await?.hits)
+            }
+            SearchEngine::Elasticsearch => {
+                #[derive(Deserialize)]
+                struct Hit {
+                    _source: SearchDocument,
+                }
+                #[derive(Deserialize)]
+                struct Hits {
+                    hits: Vec<Hit>,
+                }
+                #[derive(Deserialize)]
+                struct SearchResponse {
+                    hits: Hits,
+                }
+
+                let response = Self::send(self.post(&format!("/{}/_search", self.index_name)).json(&json!({
+                    "size": limit,
+                    "query": { "multi_match": {
+                        "query": query,
+                        "fields": [ "key", "text" ],
+                        "fuzziness": "AUTO",
+                    } },
+                })))
+                .await?;
+                Ok(response
+                    .json::<SearchResponse>()
+                    .await?
+                    .hits
+                    .hits
+                    .into_iter()
+                    .map(|hit| hit._source)
+                    .collect())
+            }
+        }
+    }
+}
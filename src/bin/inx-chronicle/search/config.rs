@@ -0,0 +1,50 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_ENABLED: bool = false;
+pub const DEFAULT_URL: &str = "http://localhost:7700";
+pub const DEFAULT_INDEX_NAME: &str = "chronicle";
+pub const DEFAULT_POLL_INTERVAL: &str = "5s";
+
+/// The external search engines that searchable entities can be mirrored into.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SearchEngine {
+    Meilisearch,
+    Elasticsearch,
+}
+
+impl Default for SearchEngine {
+    fn default() -> Self {
+        Self::Meilisearch
+    }
+}
+
+/// Configuration of the search engine sink.
+#[derive(Clone, Debug)]
+pub struct SearchConfig {
+    pub enabled: bool,
+    pub engine: SearchEngine,
+    /// The base url of the search engine.
+    pub url: String,
+    /// The key used to authenticate with the search engine, if it requires one.
+    pub api_key: Option<String>,
+    /// The name of the index that holds the searchable entities.
+    pub index_name: String,
+    /// How often the worker checks for new milestones to index.
+    pub poll_interval: Duration,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            engine: Default::default(),
+            url: DEFAULT_URL.to_string(),
+            api_key: None,
+            index_name: DEFAULT_INDEX_NAME.to_string(),
+            poll_interval: DEFAULT_POLL_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle::model::{
+    ledger::LedgerOutput,
+    payload::{Payload, TransactionEssence},
+    tangle::MilestoneIndex,
+    utxo::{Feature, NftId, Output},
+    Block, BlockId,
+};
+use iota_sdk::types::block::address::{Hrp, ToBech32Ext};
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of bytes of free-form text that is mirrored per entity.
+const MAX_TEXT_LEN: usize = 1024;
+
+/// The kinds of entities that are mirrored into the search engine.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchDocumentKind {
+    Address,
+    Block,
+    Tag,
+    Nft,
+}
+
+impl SearchDocumentKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Address => "address",
+            Self::Block => "block",
+            Self::Tag => "tag",
+            Self::Nft => "nft",
+        }
+    }
+}
+
+/// A searchable entity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchDocument {
+    /// The unique id of the document, which only consists of characters that all search engines accept.
+    pub id: String,
+    pub kind: SearchDocumentKind,
+    /// The canonical representation of the entity, such as a bech32 address or a hex block id.
+    pub key: String,
+    /// Human-readable text associated with the entity, such as a decoded tag or NFT metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// The milestone at which the entity was last seen.
+    pub milestone_index: MilestoneIndex,
+}
+
+impl SearchDocument {
+    fn new(kind: SearchDocumentKind, key: String, text: Option<String>, milestone_index: MilestoneIndex) -> Self {
+        Self {
+            id: format!("{}-{key}", kind.as_str()),
+            kind,
+            key,
+            text,
+            milestone_index,
+        }
+    }
+}
+
+/// Decodes bytes as text if they are valid UTF-8 without control characters, truncated to [`MAX_TEXT_LEN`].
+fn readable_text(bytes: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(bytes).ok()?.trim();
+    if text.is_empty() || text.chars().any(char::is_control) {
+        return None;
+    }
+    let mut end = text.len().min(MAX_TEXT_LEN);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(text[..end].to_string())
+}
+
+/// Gets the searchable entities of a referenced block: the block itself and its tag, if it has one.
+pub fn block_documents(block_id: BlockId, block: &Block, milestone_index: MilestoneIndex) -> Vec<SearchDocument> {
+    let mut documents = vec![SearchDocument::new(
+        SearchDocumentKind::Block,
+        block_id.to_hex(),
+        None,
+        milestone_index,
+    )];
+    let tagged_data = match &block.payload {
+        Some(Payload::TaggedData(payload)) => Some(payload),
+        Some(Payload::Transaction(payload)) => match &payload.essence {
+            TransactionEssence::Regular {
+                payload: Some(Payload::TaggedData(payload)),
+                ..
+            } => Some(payload),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some(tag) = tagged_data.map(|payload| payload.tag()).filter(|tag| !tag.is_empty()) {
+        documents.push(SearchDocument::new(
+            SearchDocumentKind::Tag,
+            prefix_hex::encode(tag),
+            readable_text(tag),
+            milestone_index,
+        ));
+    }
+    documents
+}
+
+/// Gets the searchable entities of a created output: its owning address and, for NFTs, the NFT and its metadata.
+pub fn output_documents(output: &LedgerOutput, hrp: &Hrp) -> Vec<SearchDocument> {
+    let milestone_index = output.booked.milestone_index;
+    let mut documents = Vec::new();
+    if let Some(address) = output.owning_address() {
+        documents.push(SearchDocument::new(
+            SearchDocumentKind::Address,
+            iota_sdk::types::block::address::Address::from(*address)
+                .to_bech32(*hrp)
+                .to_string(),
+            None,
+            milestone_index,
+        ));
+    }
+    if let Output::Nft(nft) = &output.output {
+        let nft_id = if nft.nft_id == NftId::implicit() {
+            NftId::from(output.output_id)
+        } else {
+            nft.nft_id
+        };
+        let metadata = nft.immutable_features.iter().find_map(|feature| match feature {
+            Feature::Metadata { data } => readable_text(data),
+            _ => None,
+        });
+        documents.push(SearchDocument::new(
+            SearchDocumentKind::Nft,
+            iota_sdk::types::block::output::NftId::from(nft_id).to_string(),
+            metadata,
+            milestone_index,
+        ));
+    }
+    documents
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use chronicle::model::{
+        ledger::RentStructureBytes,
+        tangle::MilestoneIndexTimestamp,
+        utxo::{Address, AddressUnlockCondition, OutputId},
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn readable_text_filters_binary() {
+        assert_eq!(readable_text(b" hello "), Some("hello".to_string()));
+        assert_eq!(readable_text(&[0xff, 0xfe]), None);
+        assert_eq!(readable_text(b"a\x00b"), None);
+        assert_eq!(
+            readable_text("é".repeat(MAX_TEXT_LEN).as_bytes()).map(|s| s.len()),
+            Some(MAX_TEXT_LEN)
+        );
+    }
+
+    #[test]
+    fn tagged_data_block() {
+        let block_id = BlockId::rand();
+        let mut block = Block::rand_tagged_data();
+        block.payload = Some(Payload::TaggedData(Box::new(
+            iota_sdk::types::block::payload::TaggedDataPayload::new(b"chronicle".to_vec(), Vec::new())
+                .unwrap()
+                .into(),
+        )));
+        let documents = block_documents(block_id, &block, 5.into());
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].id, format!("block-{}", block_id.to_hex()));
+        assert_eq!(documents[1].kind, SearchDocumentKind::Tag);
+        assert_eq!(documents[1].key, prefix_hex::encode(b"chronicle"));
+        assert_eq!(documents[1].text.as_deref(), Some("chronicle"));
+    }
+
+    #[test]
+    fn minted_nft() {
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        let mut nft = match Output::rand_nft(&protocol_params) {
+            Output::Nft(nft) => nft,
+            _ => unreachable!(),
+        };
+        nft.nft_id = NftId::implicit();
+        nft.address_unlock_condition = AddressUnlockCondition {
+            address: Address::rand_ed25519(),
+        };
+        nft.immutable_features = vec![Feature::Metadata {
+            data: br#"{"name":"Shimmer"}"#.to_vec().into_boxed_slice(),
+        }]
+        .into_boxed_slice();
+        let output_id = OutputId::rand();
+        let output = LedgerOutput {
+            output_id,
+            block_id: BlockId::rand(),
+            booked: MilestoneIndexTimestamp {
+                milestone_index: 7.into(),
+                milestone_timestamp: 0.into(),
+            },
+            output: Output::Nft(nft),
+            rent_structure: RentStructureBytes {
+                num_key_bytes: 0,
+                num_data_bytes: 100,
+            },
+        };
+
+        let documents = output_documents(&output, &"smr".parse().unwrap());
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].kind, SearchDocumentKind::Address);
+        assert!(documents[0].key.starts_with("smr1"));
+        assert_eq!(documents[1].kind, SearchDocumentKind::Nft);
+        assert_eq!(
+            documents[1].key,
+            iota_sdk::types::block::output::NftId::from(NftId::from(output_id)).to_string()
+        );
+        assert_eq!(documents[1].text.as_deref(), Some(r#"{"name":"Shimmer"}"#));
+        assert_eq!(documents[1].milestone_index, 7);
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+pub mod config;
+mod documents;
+
+use std::collections::BTreeMap;
+
+use chronicle::{
+    db::{
+        mongodb::collections::{
+            ApplicationStateCollection, BlockCollection, MilestoneCollection, OutputCollection,
+            ProtocolUpdateCollection,
+        },
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+use eyre::{eyre, Result};
+use futures::TryStreamExt;
+use iota_sdk::types::block::address::Hrp;
+use tracing::{debug, error, info};
+
+pub use self::{
+    client::{SearchClient, SearchError},
+    config::SearchConfig,
+    documents::{SearchDocument, SearchDocumentKind},
+};
+
+/// The maximum number of documents sent to the search engine in a single request.
+const BATCH_SIZE: usize = 1000;
+
+/// Mirrors the searchable entities of every milestone into the external search engine.
+pub struct SearchWorker {
+    db: MongoDb,
+    client: SearchClient,
+    config: SearchConfig,
+}
+
+impl SearchWorker {
+    pub fn new(db: MongoDb, client: SearchClient, config: SearchConfig) -> Self {
+        Self { db, client, config }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let app_state = self.db.collection::<ApplicationStateCollection>();
+        let milestones = self.db.collection::<MilestoneCollection>();
+        info!(
+            "Mirroring searchable entities into {:?} index `{}` at `{}`.",
+            self.config.engine, self.config.index_name, self.config.url
+        );
+
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+            let ledger_index = match milestones.get_ledger_index().await? {
+                Some(ledger_index) => ledger_index,
+                None => continue,
+            };
+            let next_index = match app_state.get_search_index().await? {
+                Some(search_index) => search_index + 1,
+                None => match milestones.get_oldest_milestone().await? {
+                    Some(oldest) => oldest.milestone_index,
+                    None => continue,
+                },
+            };
+            for index in next_index.0..=ledger_index.0 {
+                let start_time = std::time::Instant::now();
+                // The search engine being unavailable should not take down Chronicle, so retry on the next tick.
+                match self.index_milestone(index.into()).await {
+                    Ok(count) => debug!(
+                        "Mirrored {count} searchable entities of milestone {index} in {}ms.",
+                        start_time.elapsed().as_millis()
+                    ),
+                    Err(e) => {
+                        error!("Failed to mirror searchable entities of milestone {index}: {e}");
+                        break;
+                    }
+                }
+                app_state.set_search_index(index.into()).await?;
+            }
+        }
+    }
+
+    async fn index_milestone(&self, index: MilestoneIndex) -> Result<usize> {
+        let hrp = self
+            .db
+            .collection::<ProtocolUpdateCollection>()
+            .get_protocol_parameters_for_ledger_index(index)
+            .await?
+            .ok_or_else(|| eyre!("missing protocol parameters for milestone {index}"))?
+            .parameters
+            .bech32_hrp
+            .parse::<Hrp>()?;
+
+        // Entities can appear several times in a milestone, but only need to be sent once.
+        let mut documents = BTreeMap::new();
+        let mut blocks = self
+            .db
+            .collection::<BlockCollection>()
+            .get_referenced_blocks_in_white_flag_order_stream(index)
+            .await?;
+        while let Some((block_id, block, _, _)) = blocks.try_next().await? {
+            for document in documents::block_documents(block_id, &block, index) {
+                documents.insert(document.id.clone(), document);
+            }
+        }
+        let mut outputs = self
+            .db
+            .collection::<OutputCollection>()
+            .get_created_outputs(index)
+            .await?;
+        while let Some(output) = outputs.try_next().await? {
+            for document in documents::output_documents(&output, &hrp) {
+                documents.insert(document.id.clone(), document);
+            }
+        }
+
+        let documents = documents.into_values().collect::<Vec<_>>();
+        for batch in documents.chunks(BATCH_SIZE) {
+            self.client.index_documents(batch).await?;
+        }
+        Ok(documents.len())
+    }
+}
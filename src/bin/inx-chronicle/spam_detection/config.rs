@@ -0,0 +1,41 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use chronicle::analytics::SpamThresholds;
+
+pub const DEFAULT_ENABLED: bool = false;
+pub const DEFAULT_POLL_INTERVAL: &str = "5s";
+pub const DEFAULT_WINDOW: usize = 60;
+pub const DEFAULT_BLOCK_SPIKE_FACTOR: f64 = 3.0;
+pub const DEFAULT_DUPLICATE_PAYLOAD_RATIO: f64 = 0.5;
+pub const DEFAULT_ISSUER_DOMINANCE_RATIO: f64 = 0.8;
+pub const DEFAULT_MIN_BLOCK_COUNT: usize = 100;
+pub const DEFAULT_MIN_TRANSACTION_COUNT: usize = 50;
+
+/// Configuration of the spam detection worker.
+#[derive(Clone, Debug)]
+pub struct SpamDetectionConfig {
+    pub enabled: bool,
+    /// How often the database is checked for milestones that were not checked for spam yet.
+    pub poll_interval: Duration,
+    pub thresholds: SpamThresholds,
+}
+
+impl Default for SpamDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            poll_interval: DEFAULT_POLL_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+            thresholds: SpamThresholds {
+                window: DEFAULT_WINDOW,
+                block_spike_factor: DEFAULT_BLOCK_SPIKE_FACTOR,
+                duplicate_payload_ratio: DEFAULT_DUPLICATE_PAYLOAD_RATIO,
+                issuer_dominance_ratio: DEFAULT_ISSUER_DOMINANCE_RATIO,
+                min_block_count: DEFAULT_MIN_BLOCK_COUNT,
+                min_transaction_count: DEFAULT_MIN_TRANSACTION_COUNT,
+            },
+        }
+    }
+}
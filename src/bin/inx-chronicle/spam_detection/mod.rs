@@ -0,0 +1,113 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use chronicle::{
+    analytics::SpamDetectionAnalytics,
+    db::{
+        influxdb::InfluxDb,
+        mongodb::collections::{ApplicationStateCollection, MilestoneCollection, SpamFlagCollection},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+    tangle::Tangle,
+};
+use eyre::Result;
+use futures::TryStreamExt;
+use tracing::{debug, warn};
+
+pub use self::config::SpamDetectionConfig;
+
+/// Checks the milestones stored in MongoDB for spam waves, and writes the statistics of every milestone to InfluxDb
+/// and the flagged milestones to the [`SpamFlagCollection`].
+pub struct SpamDetectionWorker {
+    db: MongoDb,
+    influx_db: InfluxDb,
+    config: SpamDetectionConfig,
+}
+
+/// The first milestone to read, which goes back by the window once the worker starts, so that the baseline of a spike
+/// is known right away. The milestones that were checked before are only replayed to fill the window.
+fn first_index(next_index: MilestoneIndex, starting_index: MilestoneIndex, window: usize) -> MilestoneIndex {
+    MilestoneIndex(next_index.0.saturating_sub(window.try_into().unwrap_or(u32::MAX))).max(starting_index)
+}
+
+impl SpamDetectionWorker {
+    pub fn new(db: MongoDb, influx_db: InfluxDb, config: SpamDetectionConfig) -> Self {
+        Self { db, influx_db, config }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let app_state = self.db.collection::<ApplicationStateCollection>();
+        let milestones = self.db.collection::<MilestoneCollection>();
+        let spam_flags = self.db.collection::<SpamFlagCollection>();
+        let mut analytics = SpamDetectionAnalytics::new(self.config.thresholds);
+        let mut warmed_up = false;
+
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+            let ledger_index = match milestones.get_ledger_index().await? {
+                Some(ledger_index) => ledger_index,
+                None => continue,
+            };
+            let starting_index = match app_state.get_starting_index().await? {
+                Some(starting_index) => starting_index,
+                None => continue,
+            };
+            // Milestones before the ledger index are not checked when the detection is first enabled.
+            let mut next_index = match app_state.get_spam_detection_index().await? {
+                Some(spam_detection_index) => spam_detection_index + 1,
+                None => ledger_index,
+            }
+            .max(starting_index.milestone_index);
+            let check_index = next_index;
+            if !warmed_up {
+                next_index = first_index(
+                    next_index,
+                    starting_index.milestone_index,
+                    self.config.thresholds.window,
+                );
+                warmed_up = true;
+            }
+            if next_index > ledger_index {
+                continue;
+            }
+
+            let tangle = Tangle::from(self.db.clone());
+            let mut milestone_stream = tangle.milestone_stream(next_index..=ledger_index).await?;
+            while let Some(milestone) = milestone_stream.try_next().await? {
+                if milestone.at.milestone_index < check_index {
+                    milestone.replay_spam_detection(&mut analytics).await?;
+                    continue;
+                }
+                let measurement = milestone.detect_spam(&mut analytics, &self.influx_db).await?;
+                if measurement.flags.any() {
+                    warn!(
+                        "Milestone {} looks like spam: {:?} ({} blocks).",
+                        milestone.at.milestone_index, measurement.flags, measurement.block_count
+                    );
+                    spam_flags.upsert_flags(measurement.to_document(milestone.at)).await?;
+                } else {
+                    spam_flags.delete_flags(milestone.at.milestone_index).await?;
+                }
+                app_state.set_spam_detection_index(milestone.at.milestone_index).await?;
+            }
+            debug!("Checked milestones {check_index} to {ledger_index} for spam.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn first_index_goes_back_by_window() {
+        assert_eq!(first_index(100.into(), 1.into(), 60), MilestoneIndex(40));
+        assert_eq!(first_index(100.into(), 50.into(), 60), MilestoneIndex(50));
+        assert_eq!(first_index(10.into(), 0.into(), 60), MilestoneIndex(0));
+        assert_eq!(first_index(10.into(), 0.into(), usize::MAX), MilestoneIndex(0));
+    }
+}
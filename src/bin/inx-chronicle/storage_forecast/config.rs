@@ -0,0 +1,44 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_ENABLED: bool = true;
+pub const DEFAULT_INTERVAL: &str = "1h";
+pub const DEFAULT_WINDOW: &str = "7d";
+pub const DEFAULT_RETENTION: &str = "30d";
+pub const DEFAULT_HORIZONS: &str = "30d,90d,365d";
+
+/// Configuration of the storage forecast worker.
+#[derive(Clone, Debug)]
+pub struct StorageForecastConfig {
+    pub enabled: bool,
+    /// How often the storage usage of every collection is sampled.
+    pub interval: Duration,
+    /// The period over which the growth of the collections is measured.
+    pub window: Duration,
+    /// How long samples are kept.
+    pub retention: Duration,
+    /// How far ahead the storage usage is projected.
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    pub horizons: Vec<Duration>,
+}
+
+/// Parses a comma-separated list of durations.
+pub fn parse_horizons(s: &str) -> Result<Vec<Duration>, humantime::DurationError> {
+    s.split(',')
+        .map(|horizon| horizon.trim().parse::<humantime::Duration>().map(Into::into))
+        .collect()
+}
+
+impl Default for StorageForecastConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            interval: DEFAULT_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+            window: DEFAULT_WINDOW.parse::<humantime::Duration>().unwrap().into(),
+            retention: DEFAULT_RETENTION.parse::<humantime::Duration>().unwrap().into(),
+            horizons: parse_horizons(DEFAULT_HORIZONS).unwrap(),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use chronicle::db::{
+    mongodb::collections::{MilestoneCollection, StorageSample, StorageSampleCollection},
+    MongoDb,
+};
+use eyre::Result;
+use mongodb::bson::DateTime;
+use tracing::{debug, error};
+
+pub use self::config::StorageForecastConfig;
+
+/// Returns the time `duration` before `time`.
+pub fn time_before(time: DateTime, duration: std::time::Duration) -> DateTime {
+    DateTime::from_millis(
+        time.timestamp_millis()
+            .saturating_sub(duration.as_millis().try_into().unwrap_or(i64::MAX)),
+    )
+}
+
+/// Periodically samples the storage used by every collection, so that its growth can be projected.
+pub struct StorageForecastWorker {
+    db: MongoDb,
+    config: StorageForecastConfig,
+    #[cfg(feature = "metrics")]
+    influx_db: Option<chronicle::db::influxdb::InfluxDb>,
+}
+
+impl StorageForecastWorker {
+    pub fn new(db: MongoDb, config: StorageForecastConfig) -> Self {
+        Self {
+            db,
+            config,
+            #[cfg(feature = "metrics")]
+            influx_db: None,
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn set_influx_db(&mut self, influx_db: chronicle::db::influxdb::InfluxDb) {
+        self.influx_db.replace(influx_db);
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            // A failed sample is retried at the next interval.
+            match self.sample().await {
+                Ok(sample) => debug!(
+                    "Sampled the storage usage of {} collections at ledger index {:?}.",
+                    sample.collections.len(),
+                    sample.ledger_index
+                ),
+                Err(e) => error!("Failed to sample the storage usage: {e}"),
+            }
+        }
+    }
+
+    async fn sample(&self) -> Result<StorageSample> {
+        let samples = self.db.collection::<StorageSampleCollection>();
+        let sample = StorageSample {
+            sampled_at: DateTime::now(),
+            ledger_index: self.db.collection::<MilestoneCollection>().get_ledger_index().await?,
+            collections: self.db.collection_storage().await?,
+        };
+        samples.insert_sample(&sample).await?;
+        samples
+            .delete_samples_before(time_before(sample.sampled_at, self.config.retention))
+            .await?;
+        #[cfg(feature = "metrics")]
+        self.update_metrics(&sample).await?;
+        Ok(sample)
+    }
+
+    #[cfg(feature = "metrics")]
+    async fn update_metrics(&self, sample: &StorageSample) -> Result<()> {
+        if let Some(influx_db) = &self.influx_db {
+            let start = match self
+                .db
+                .collection::<StorageSampleCollection>()
+                .get_sample_since(time_before(sample.sampled_at, self.config.window))
+                .await?
+            {
+                Some(start) => start,
+                None => return Ok(()),
+            };
+            let time = chrono::Utc::now();
+            for growth in sample.growth_since(&start) {
+                for horizon in &self.config.horizons {
+                    let projected = growth.project(*horizon);
+                    influx_db
+                        .metrics()
+                        .insert(chronicle::metrics::StorageMetrics {
+                            time,
+                            collection: growth.storage.name.clone(),
+                            horizon: humantime::format_duration(*horizon).to_string(),
+                            count: growth.storage.count,
+                            size: growth.storage.size,
+                            documents_per_day: growth.documents_per_day,
+                            bytes_per_day: growth.bytes_per_day,
+                            projected_count: projected.count,
+                            projected_size: projected.size,
+                            chronicle_version: std::env!("CARGO_PKG_VERSION").to_string(),
+                        })
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_ENABLED: bool = true;
+pub const DEFAULT_INTERVAL: &str = "1h";
+
+/// Configuration of the native token distribution worker.
+#[derive(Clone, Debug)]
+pub struct TokenDistributionConfig {
+    pub enabled: bool,
+    /// How often the distribution of every native token is recomputed.
+    pub interval: Duration,
+}
+
+impl Default for TokenDistributionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            interval: DEFAULT_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use chronicle::db::{
+    mongodb::collections::{
+        MilestoneCollection, NativeTokenDistribution, OutputCollection, TokenDistributionCollection,
+    },
+    MongoDb,
+};
+use eyre::Result;
+use tracing::{debug, error};
+
+pub use self::config::TokenDistributionConfig;
+
+/// Periodically computes the holder statistics of every native token.
+pub struct TokenDistributionWorker {
+    db: MongoDb,
+    config: TokenDistributionConfig,
+}
+
+impl TokenDistributionWorker {
+    pub fn new(db: MongoDb, config: TokenDistributionConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let distributions = self.db.collection::<TokenDistributionCollection>();
+        let mut last_ledger_index = distributions.get_ledger_index().await?;
+
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            let ledger_index = match self.db.collection::<MilestoneCollection>().get_ledger_index().await? {
+                Some(ledger_index) => ledger_index,
+                None => continue,
+            };
+            if last_ledger_index == Some(ledger_index) {
+                continue;
+            }
+            let start_time = std::time::Instant::now();
+            // A failed computation is retried at the next interval.
+            match self.update(ledger_index).await {
+                Ok(count) => {
+                    debug!(
+                        "Computed the distribution of {count} native tokens at ledger index {ledger_index} in {}ms.",
+                        start_time.elapsed().as_millis()
+                    );
+                    last_ledger_index = Some(ledger_index);
+                }
+                Err(e) => error!("Failed to compute the native token distribution: {e}"),
+            }
+        }
+    }
+
+    async fn update(&self, ledger_index: chronicle::model::tangle::MilestoneIndex) -> Result<usize> {
+        let balances = self
+            .db
+            .collection::<OutputCollection>()
+            .get_native_token_balances(ledger_index)
+            .await?;
+        let count = balances.len();
+        self.db
+            .collection::<TokenDistributionCollection>()
+            .update_distributions(
+                ledger_index,
+                balances.into_iter().map(|(token_id, balances)| {
+                    (token_id, NativeTokenDistribution::from_balances(balances.into_values()))
+                }),
+            )
+            .await?;
+        Ok(count)
+    }
+}
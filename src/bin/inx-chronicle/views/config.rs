@@ -0,0 +1,27 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+pub const DEFAULT_ENABLED: bool = true;
+pub const DEFAULT_POLL_INTERVAL: &str = "10s";
+
+/// Configuration of the materialized view worker.
+#[derive(Clone, Debug)]
+pub struct ViewsConfig {
+    pub enabled: bool,
+    /// A JSON file containing additional view definitions.
+    pub definitions_file: Option<String>,
+    /// How often the worker checks whether views are due for a refresh.
+    pub poll_interval: Duration,
+}
+
+impl Default for ViewsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_ENABLED,
+            definitions_file: None,
+            poll_interval: DEFAULT_POLL_INTERVAL.parse::<humantime::Duration>().unwrap().into(),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod config;
+
+use std::collections::HashSet;
+
+use chronicle::db::{
+    mongodb::collections::{builtin_views, MaterializedViewCollection, MilestoneCollection, ViewDefinition},
+    MongoDb,
+};
+use eyre::{bail, Result};
+use time::OffsetDateTime;
+use tracing::{debug, error, info};
+
+pub use self::config::ViewsConfig;
+
+/// Keeps the registered materialized views up to date.
+pub struct ViewsWorker {
+    db: MongoDb,
+    config: ViewsConfig,
+}
+
+impl ViewsWorker {
+    pub fn new(db: MongoDb, config: ViewsConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Gets the built-in view definitions along with the ones from the definitions file.
+    fn definitions(&self) -> Result<Vec<ViewDefinition>> {
+        let mut definitions = builtin_views();
+        if let Some(path) = &self.config.definitions_file {
+            let file_definitions: Vec<ViewDefinition> = serde_json::from_reader(std::fs::File::open(path)?)?;
+            definitions.extend(file_definitions);
+        }
+        let mut names = HashSet::new();
+        for definition in &definitions {
+            if !names.insert(&definition.name) {
+                bail!("duplicate materialized view `{}`", definition.name);
+            }
+        }
+        Ok(definitions)
+    }
+
+    pub async fn run(&self) -> Result<()> {
+        let definitions = self.definitions()?;
+        let views = self.db.collection::<MaterializedViewCollection>();
+        for definition in &definitions {
+            views.register_view(definition).await?;
+        }
+        info!("Registered {} materialized views.", definitions.len());
+        let names = definitions.iter().map(|d| &d.name).collect::<HashSet<_>>();
+
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        loop {
+            interval.tick().await;
+            let ledger_index = match self.db.collection::<MilestoneCollection>().get_ledger_index().await? {
+                Some(ledger_index) => ledger_index,
+                None => continue,
+            };
+            for view in views.get_views().await? {
+                if !names.contains(&view.definition.name) || !view.is_due(ledger_index, OffsetDateTime::now_utc()) {
+                    continue;
+                }
+                let start_time = std::time::Instant::now();
+                // A failing view should not stop the others from being refreshed.
+                match views.refresh_view(&view, ledger_index).await {
+                    Ok(_) => debug!(
+                        "Refreshed materialized view `{}` at ledger index {} in {}ms.",
+                        view.definition.name,
+                        ledger_index,
+                        start_time.elapsed().as_millis()
+                    ),
+                    Err(e) => error!("Failed to refresh materialized view `{}`: {e}", view.definition.name),
+                }
+            }
+        }
+    }
+}
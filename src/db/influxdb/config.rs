@@ -4,6 +4,11 @@
 //! Holds the `InfluxDb` config and its defaults.
 
 use std::collections::HashSet;
+#[cfg(feature = "analytics")]
+use std::path::PathBuf;
+
+#[cfg(any(feature = "analytics", feature = "metrics"))]
+use super::RetentionConfig;
 
 /// The default InfluxDb URL to connect to.
 pub const DEFAULT_URL: &str = "http://localhost:8086";
@@ -43,12 +48,24 @@ pub struct InfluxDbConfig {
     /// The selected analytics to compute.
     #[cfg(feature = "analytics")]
     pub analytics: Vec<AnalyticsChoice>,
+    /// The analytics that are measured less often than every milestone.
+    #[cfg(feature = "analytics")]
+    pub analytics_sampling: Vec<AnalyticsSampling>,
+    /// The directory in which analytics whose state grows with the ledger keep it, instead of in memory.
+    #[cfg(feature = "analytics")]
+    pub analytics_spill_dir: Option<PathBuf>,
+    /// How long analytics are kept.
+    #[cfg(feature = "analytics")]
+    pub analytics_retention: RetentionConfig,
     /// Whether to enable influx metrics writes.
     #[cfg(feature = "metrics")]
     pub metrics_enabled: bool,
     /// The name of the database to insert metrics.
     #[cfg(feature = "metrics")]
     pub metrics_database_name: String,
+    /// How long metrics are kept.
+    #[cfg(feature = "metrics")]
+    pub metrics_retention: RetentionConfig,
 }
 
 impl Default for InfluxDbConfig {
@@ -63,14 +80,64 @@ impl Default for InfluxDbConfig {
             analytics_database_name: DEFAULT_ANALYTICS_DATABASE_NAME.to_string(),
             #[cfg(feature = "analytics")]
             analytics: Vec::new(),
+            #[cfg(feature = "analytics")]
+            analytics_sampling: Vec::new(),
+            #[cfg(feature = "analytics")]
+            analytics_spill_dir: None,
+            #[cfg(feature = "analytics")]
+            analytics_retention: RetentionConfig::default(),
             #[cfg(feature = "metrics")]
             metrics_enabled: DEFAULT_METRICS_ENABLED,
             #[cfg(feature = "metrics")]
             metrics_database_name: DEFAULT_METRICS_DATABASE_NAME.to_string(),
+            #[cfg(feature = "metrics")]
+            metrics_retention: RetentionConfig::default(),
         }
     }
 }
 
+#[cfg(feature = "analytics")]
+impl InfluxDbConfig {
+    /// Returns the number of milestones between measurements of the given analytic.
+    pub fn sampling_interval(&self, analytic: &AnalyticsChoice) -> u32 {
+        self.analytics_sampling
+            .iter()
+            .rev()
+            .find(|sampling| &sampling.analytic == analytic)
+            .map_or(1, |sampling| sampling.interval)
+    }
+}
+
+/// Measures an analytic only at milestones whose index is a multiple of the interval.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AnalyticsSampling {
+    /// The sampled analytic.
+    pub analytic: AnalyticsChoice,
+    /// The number of milestones between measurements.
+    pub interval: u32,
+}
+
+impl std::str::FromStr for AnalyticsSampling {
+    type Err = String;
+
+    /// Parses `<analytic>=<interval>`, such as `ledger-size=10`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use clap::ValueEnum;
+
+        let (analytic, interval) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `<analytic>=<milestones>`, found `{s}`"))?;
+        let analytic = AnalyticsChoice::from_str(analytic.trim(), true)?;
+        let interval = interval
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|&interval| interval > 0)
+            .ok_or_else(|| format!("invalid number of milestones `{interval}`"))?;
+        Ok(Self { analytic, interval })
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, clap::ValueEnum)]
 pub enum AnalyticsChoice {
@@ -82,8 +149,10 @@ pub enum AnalyticsChoice {
     LedgerOutputs,
     LedgerSize,
     MilestoneSize,
+    NftSales,
     OutputActivity,
     ProtocolParameters,
+    TimelockHorizon,
     TransactionSizeDistribution,
     UnclaimedTokens,
     UnlockConditions,
@@ -100,8 +169,10 @@ pub fn all_analytics() -> HashSet<AnalyticsChoice> {
         AnalyticsChoice::LedgerOutputs,
         AnalyticsChoice::LedgerSize,
         AnalyticsChoice::MilestoneSize,
+        AnalyticsChoice::NftSales,
         AnalyticsChoice::OutputActivity,
         AnalyticsChoice::ProtocolParameters,
+        AnalyticsChoice::TimelockHorizon,
         AnalyticsChoice::TransactionSizeDistribution,
         AnalyticsChoice::UnclaimedTokens,
         AnalyticsChoice::UnlockConditions,
@@ -121,3 +192,29 @@ pub fn all_interval_analytics() -> HashSet<IntervalAnalyticsChoice> {
     // Please keep the alphabetic order.
     [IntervalAnalyticsChoice::ActiveAddresses].into()
 }
+
+#[cfg(all(test, feature = "analytics"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_sampling() {
+        assert_eq!(
+            "ledger-size=10".parse::<AnalyticsSampling>(),
+            Ok(AnalyticsSampling {
+                analytic: AnalyticsChoice::LedgerSize,
+                interval: 10
+            })
+        );
+        assert!("ledger-size".parse::<AnalyticsSampling>().is_err());
+        assert!("ledger-size=0".parse::<AnalyticsSampling>().is_err());
+        assert!("unknown=10".parse::<AnalyticsSampling>().is_err());
+
+        let config = InfluxDbConfig {
+            analytics_sampling: vec!["address-balance=100".parse().unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(config.sampling_interval(&AnalyticsChoice::AddressBalance), 100);
+        assert_eq!(config.sampling_interval(&AnalyticsChoice::LedgerSize), 1);
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod config;
 mod measurement;
+pub mod retention;
 
 use std::ops::Deref;
 
@@ -12,16 +13,32 @@ use serde::de::DeserializeOwned;
 pub use self::{
     config::{AnalyticsChoice, InfluxDbConfig},
     measurement::InfluxDbMeasurement,
+    retention::RetentionConfig,
 };
 
 /// A wrapper for an InfluxDb [`Client`].
 #[derive(Clone, Debug)]
-pub struct InfluxClient(Client);
+pub struct InfluxClient {
+    client: Client,
+    /// The tags that are added to every inserted measurement.
+    tags: Vec<(&'static str, String)>,
+}
 
 impl InfluxClient {
+    #[cfg_attr(not(any(feature = "analytics", feature = "metrics")), allow(dead_code))]
+    fn new(client: Client) -> Self {
+        Self {
+            client,
+            tags: Vec::new(),
+        }
+    }
+
     /// Insert a measurement value.
     pub async fn insert<M: InfluxDbMeasurement>(&self, value: M) -> Result<(), influxdb::Error> {
-        self.query(value.into_query(M::NAME)).await?;
+        let query = self.tags.iter().fold(value.into_query(M::NAME), |query, (tag, value)| {
+            query.add_tag(*tag, value.as_str())
+        });
+        self.query(query).await?;
         Ok(())
     }
 
@@ -45,7 +62,7 @@ impl Deref for InfluxClient {
     type Target = Client;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.client
     }
 }
 
@@ -64,7 +81,7 @@ impl InfluxDb {
     pub async fn connect(config: &InfluxDbConfig) -> Result<Self, influxdb::Error> {
         #[cfg(feature = "analytics")]
         let analytics_client = {
-            let client = InfluxClient(
+            let client = InfluxClient::new(
                 Client::new(&config.url, &config.analytics_database_name).with_auth(&config.username, &config.password),
             );
             client.ping().await?;
@@ -72,7 +89,7 @@ impl InfluxDb {
         };
         #[cfg(feature = "metrics")]
         let metrics_client = {
-            let client = InfluxClient(
+            let client = InfluxClient::new(
                 Client::new(&config.url, &config.metrics_database_name).with_auth(&config.username, &config.password),
             );
             client.ping().await?;
@@ -87,6 +104,17 @@ impl InfluxDb {
         })
     }
 
+    /// Applies the retention config to the databases. This only needs to happen once, rather than for every
+    /// connection.
+    #[cfg(any(feature = "analytics", feature = "metrics"))]
+    pub async fn apply_retention(&self) -> Result<(), influxdb::Error> {
+        #[cfg(feature = "analytics")]
+        retention::apply(&self.analytics_client, &self.config.analytics_retention).await?;
+        #[cfg(feature = "metrics")]
+        retention::apply(&self.metrics_client, &self.config.metrics_retention).await?;
+        Ok(())
+    }
+
     /// Get the analytics client.
     #[cfg(feature = "analytics")]
     pub fn analytics(&self) -> &InfluxClient {
@@ -99,6 +127,16 @@ impl InfluxDb {
         &self.metrics_client
     }
 
+    /// Tags all metrics with the id of this process and, if known, the id of the database it works on. Processes that
+    /// work on databases restored from the same one then report the same database id, but different instance ids.
+    #[cfg(feature = "metrics")]
+    pub fn set_instance(&mut self, instance_id: &str, database_id: Option<&str>) {
+        self.metrics_client.tags.push(("instance_id", instance_id.to_string()));
+        if let Some(database_id) = database_id {
+            self.metrics_client.tags.push(("database_id", database_id.to_string()));
+        }
+    }
+
     /// Get the config used to create the connection.
     pub fn config(&self) -> &InfluxDbConfig {
         &self.config
@@ -0,0 +1,141 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keeps the InfluxDb databases from growing without bound.
+
+use std::time::Duration;
+
+use influxdb::{Client, ReadQuery};
+
+/// The retention policy that holds the measurements as they are written. It is the policy InfluxDb creates for every
+/// database, so altering it also prunes the measurements that were written before retention was configured.
+pub const RAW_RETENTION_POLICY: &str = "autogen";
+/// The retention policy that holds the downsampled measurements, which are kept forever.
+pub const DOWNSAMPLED_RETENTION_POLICY: &str = "downsampled";
+/// The continuous query that downsamples the measurements.
+pub const DOWNSAMPLING_QUERY: &str = "chronicle_downsampling";
+
+/// How long the measurements of a database are kept.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct RetentionConfig {
+    /// How long measurements are kept as they are written. If unset, they are kept forever.
+    pub retention: Option<Duration>,
+    /// The interval over which measurements are averaged into the downsampled retention policy. If unset, nothing is
+    /// downsampled.
+    pub downsampling_interval: Option<Duration>,
+}
+
+/// Applies the retention config to the database of the client.
+#[cfg_attr(not(any(feature = "analytics", feature = "metrics")), allow(dead_code))]
+pub(super) async fn apply(client: &Client, config: &RetentionConfig) -> Result<(), influxdb::Error> {
+    let database = client.database_name();
+    let raw_policy = retention_policy(config.retention);
+    // Every database has the default policy, unless an operator removed it.
+    if client
+        .query(ReadQuery::new(format!(
+            "ALTER RETENTION POLICY \"{RAW_RETENTION_POLICY}\" ON \"{database}\" {raw_policy} DEFAULT"
+        )))
+        .await
+        .is_err()
+    {
+        client
+            .query(ReadQuery::new(format!(
+                "CREATE RETENTION POLICY \"{RAW_RETENTION_POLICY}\" ON \"{database}\" {raw_policy} REPLICATION 1 \
+                 DEFAULT"
+            )))
+            .await?;
+    }
+
+    // A continuous query cannot be changed, so it is always recreated. Dropping it fails if it does not exist yet.
+    client
+        .query(ReadQuery::new(format!(
+            "DROP CONTINUOUS QUERY \"{DOWNSAMPLING_QUERY}\" ON \"{database}\""
+        )))
+        .await
+        .ok();
+    if let Some(interval) = config.downsampling_interval {
+        client
+            .query(ReadQuery::new(format!(
+                "CREATE RETENTION POLICY \"{DOWNSAMPLED_RETENTION_POLICY}\" ON \"{database}\" {} REPLICATION 1",
+                retention_policy(None)
+            )))
+            .await?;
+        client.query(downsampling_query(database, interval)).await?;
+    }
+    Ok(())
+}
+
+fn downsampling_query(database: &str, interval: Duration) -> ReadQuery {
+    // The client sends queries containing `SELECT` as a GET request, which InfluxDb rejects for statements that
+    // change the database, so the keyword is spelled in lowercase.
+    ReadQuery::new(format!(
+        "CREATE CONTINUOUS QUERY \"{DOWNSAMPLING_QUERY}\" ON \"{database}\" BEGIN select mean(*) INTO \
+         \"{database}\".\"{DOWNSAMPLED_RETENTION_POLICY}\".:MEASUREMENT FROM \
+         \"{database}\".\"{RAW_RETENTION_POLICY}\"./.*/ GROUP BY time({}), * END",
+        influx_duration(interval)
+    ))
+}
+
+/// The duration clauses of a retention policy. Altering a policy keeps its shard duration, which must not exceed the
+/// duration of the policy, so it is always given, chosen the way InfluxDb chooses it for new policies.
+fn retention_policy(duration: Option<Duration>) -> String {
+    const DAY: u64 = 24 * 60 * 60;
+    match duration {
+        Some(duration) => {
+            let shard_duration = match duration.as_secs() {
+                secs if secs < 2 * DAY => Duration::from_secs(60 * 60),
+                secs if secs <= 180 * DAY => Duration::from_secs(DAY),
+                _ => Duration::from_secs(7 * DAY),
+            };
+            format!(
+                "DURATION {} SHARD DURATION {}",
+                influx_duration(duration),
+                influx_duration(shard_duration)
+            )
+        }
+        None => format!(
+            "DURATION INF SHARD DURATION {}",
+            influx_duration(Duration::from_secs(7 * DAY))
+        ),
+    }
+}
+
+fn influx_duration(duration: Duration) -> String {
+    format!("{}s", duration.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use influxdb::Query;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn downsampling_query_is_not_a_select() {
+        let query = downsampling_query("chronicle_analytics", Duration::from_secs(3600))
+            .build()
+            .unwrap()
+            .get();
+        assert!(!query.contains("SELECT"));
+        assert_eq!(
+            query,
+            "CREATE CONTINUOUS QUERY \"chronicle_downsampling\" ON \"chronicle_analytics\" BEGIN select mean(*) INTO \
+             \"chronicle_analytics\".\"downsampled\".:MEASUREMENT FROM \
+             \"chronicle_analytics\".\"autogen\"./.*/ GROUP BY time(3600s), * END"
+        );
+    }
+
+    #[test]
+    fn shard_duration_fits_retention() {
+        assert_eq!(
+            retention_policy(Some(Duration::from_secs(60 * 60))),
+            "DURATION 3600s SHARD DURATION 3600s"
+        );
+        assert_eq!(
+            retention_policy(Some(Duration::from_secs(30 * 24 * 60 * 60))),
+            "DURATION 2592000s SHARD DURATION 86400s"
+        );
+        assert_eq!(retention_policy(None), "DURATION INF SHARD DURATION 604800s");
+    }
+}
@@ -9,15 +9,15 @@ use mongodb::{
     bson::{self, doc, Document},
     error::{Error, ErrorKind},
     options::{
-        AggregateOptions, CreateIndexOptions, DropIndexOptions, FindOneOptions, FindOptions, InsertManyOptions,
-        InsertOneOptions, ReplaceOptions, UpdateModifications, UpdateOptions,
+        AggregateOptions, CountOptions, CreateIndexOptions, DropIndexOptions, FindOneOptions, FindOptions,
+        InsertManyOptions, InsertOneOptions, ReplaceOptions, UpdateModifications, UpdateOptions,
     },
     results::{CreateIndexResult, InsertManyResult, InsertOneResult, UpdateResult},
-    Cursor, IndexModel,
+    ClientSession, Cursor, IndexModel,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
-use super::MongoDb;
+use super::{deadline::DeadlineExt, duplicates, MongoDb};
 
 const DUPLICATE_KEY_CODE: i32 = 11000;
 const INDEX_NOT_FOUND_CODE: i32 = 27;
@@ -91,6 +91,7 @@ pub trait MongoDbCollectionExt: MongoDbCollection {
     }
 
     /// Calls [`mongodb::Collection::aggregate()`] and coerces the document type.
+    /// The aggregation is bound by the [deadline](super::deadline) of the current task.
     async fn aggregate<T: DeserializeOwned>(
         &self,
         pipeline: impl IntoIterator<Item = Document> + Send + Sync,
@@ -98,28 +99,46 @@ pub trait MongoDbCollectionExt: MongoDbCollection {
     ) -> Result<Box<dyn Stream<Item = Result<T, Error>> + Unpin + Send>, Error> {
         Ok(Box::new(
             self.collection()
-                .aggregate(pipeline, options)
+                .aggregate(pipeline, options.into().with_deadline())
                 .await?
                 .map(|doc| Ok(bson::from_document::<T>(doc?)?)),
         ))
     }
 
     /// Calls [`mongodb::Collection::find()`] and coerces the document type.
+    /// The query is bound by the [deadline](super::deadline) of the current task.
     async fn find<T: Send + Sync>(
         &self,
         filter: impl Into<Option<Document>> + Send + Sync,
         options: impl Into<Option<FindOptions>> + Send + Sync,
     ) -> Result<Cursor<T>, Error> {
-        self.with_type().find(filter, options).await
+        self.with_type().find(filter, options.into().with_deadline()).await
     }
 
     /// Calls [`mongodb::Collection::find_one()`] and coerces the document type.
+    /// The query is bound by the [deadline](super::deadline) of the current task.
     async fn find_one<T: DeserializeOwned + Unpin + Send + Sync>(
         &self,
         filter: impl Into<Option<Document>> + Send + Sync,
         options: impl Into<Option<FindOneOptions>> + Send + Sync,
     ) -> Result<Option<T>, Error> {
-        self.with_type().find_one(filter, options).await
+        self.with_type().find_one(filter, options.into().with_deadline()).await
+    }
+
+    /// Calls [`mongodb::Collection::find_one_with_session()`] if a session is given, or
+    /// [`mongodb::Collection::find_one()`] otherwise, and coerces the document type.
+    /// The query is bound by the [deadline](super::deadline) of the current task.
+    async fn find_one_with_session<T: DeserializeOwned + Unpin + Send + Sync>(
+        &self,
+        filter: impl Into<Option<Document>> + Send + Sync,
+        options: impl Into<Option<FindOneOptions>> + Send + Sync,
+        session: Option<&mut ClientSession>,
+    ) -> Result<Option<T>, Error> {
+        let options = options.into().with_deadline();
+        match session {
+            Some(session) => self.with_type().find_one_with_session(filter, options, session).await,
+            None => self.with_type().find_one(filter, options).await,
+        }
     }
 
     /// Calls [`mongodb::Collection::insert_many()`] and coerces the document type.
@@ -140,6 +159,34 @@ pub trait MongoDbCollectionExt: MongoDbCollection {
         self.with_type().insert_one(doc, options).await
     }
 
+    /// Calls [`mongodb::Collection::insert_one_with_session()`] if a session is given, or
+    /// [`mongodb::Collection::insert_one()`] otherwise, and coerces the document type.
+    async fn insert_one_with_session<T: Serialize + Send + Sync>(
+        &self,
+        doc: impl Borrow<T> + Send + Sync,
+        options: impl Into<Option<InsertOneOptions>> + Send + Sync,
+        session: Option<&mut ClientSession>,
+    ) -> Result<InsertOneResult, Error> {
+        match session {
+            Some(session) => self.with_type().insert_one_with_session(doc, options, session).await,
+            None => self.with_type().insert_one(doc, options).await,
+        }
+    }
+
+    /// Calls [`mongodb::Collection::insert_many_with_session()`] if a session is given, or
+    /// [`mongodb::Collection::insert_many()`] otherwise, and coerces the document type.
+    async fn insert_many_with_session<T: Serialize + Send + Sync>(
+        &self,
+        docs: impl IntoIterator<Item = impl Borrow<T> + Send + Sync> + Send + Sync,
+        options: impl Into<Option<InsertManyOptions>> + Send + Sync,
+        session: Option<&mut ClientSession>,
+    ) -> Result<InsertManyResult, Error> {
+        match session {
+            Some(session) => self.with_type().insert_many_with_session(docs, options, session).await,
+            None => self.with_type().insert_many(docs, options).await,
+        }
+    }
+
     /// Calls [`mongodb::Collection::update_one()`].
     async fn update_one(
         &self,
@@ -150,6 +197,25 @@ pub trait MongoDbCollectionExt: MongoDbCollection {
         self.collection().update_one(doc, update, options).await
     }
 
+    /// Calls [`mongodb::Collection::update_one_with_session()`] if a session is given, or
+    /// [`mongodb::Collection::update_one()`] otherwise.
+    async fn update_one_with_session(
+        &self,
+        doc: Document,
+        update: impl Into<UpdateModifications> + Send + Sync,
+        options: impl Into<Option<UpdateOptions>> + Send + Sync,
+        session: Option<&mut ClientSession>,
+    ) -> Result<UpdateResult, Error> {
+        match session {
+            Some(session) => {
+                self.collection()
+                    .update_one_with_session(doc, update, options, session)
+                    .await
+            }
+            None => self.collection().update_one(doc, update, options).await,
+        }
+    }
+
     /// Calls [`mongodb::Collection::update_many()`].
     async fn update_many(
         &self,
@@ -173,7 +239,7 @@ pub trait MongoDbCollectionExt: MongoDbCollection {
     /// Returns the number of documents in the collection.
     async fn count(&self) -> Result<usize, Error> {
         self.collection()
-            .count_documents(None, None)
+            .count_documents(None, None::<CountOptions>.with_deadline())
             .await
             .map(|count| count as usize)
     }
@@ -196,7 +262,7 @@ pub trait InsertIgnoreDuplicatesExt<T> {
 }
 
 #[async_trait]
-impl<T: MongoDbCollectionExt + Send + Sync, D: Serialize + Send + Sync> InsertIgnoreDuplicatesExt<D> for T {
+impl<T: MongoDbCollection + Send + Sync, D: Serialize + Send + Sync> InsertIgnoreDuplicatesExt<D> for T {
     async fn insert_many_ignore_duplicates(
         &self,
         docs: impl IntoIterator<Item = impl Borrow<D> + Send + Sync> + Send + Sync,
@@ -209,6 +275,7 @@ impl<T: MongoDbCollectionExt + Send + Sync, D: Serialize + Send + Sync> InsertIg
                 ErrorKind::BulkWrite(b) => {
                     if let Some(write_errs) = &b.write_errors {
                         if write_errs.iter().all(|e| e.code == DUPLICATE_KEY_CODE) {
+                            duplicates::record(T::NAME, write_errs.len());
                             return Ok(InsertResult {
                                 _ignored: write_errs.len(),
                             });
@@ -0,0 +1,137 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    error::Error,
+    options::{FindOneOptions, FindOptions, ReplaceOptions},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+
+/// The maximum number of bytes of a filter that are stored in one document, which keeps the documents well below
+/// the size limit of MongoDB.
+const CHUNK_SIZE: usize = 8 << 20;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ChunkId {
+    milestone_index: MilestoneIndex,
+    chunk: u32,
+}
+
+/// A part of a serialized address filter.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressFilterChunkDocument {
+    #[serde(rename = "_id")]
+    id: ChunkId,
+    /// The number of chunks the filter is split into.
+    chunk_count: u32,
+    #[serde(with = "serde_bytes")]
+    bytes: Vec<u8>,
+}
+
+/// The address filter collection, which holds the serialized filters of the addresses known to the archive.
+///
+/// A filter is split into chunks, of which the first one is written last, so a filter is only found once it is
+/// complete. The latest two filters are kept, so that a filter that is being read is not removed by the next one.
+pub struct AddressFilterCollection {
+    collection: mongodb::Collection<AddressFilterChunkDocument>,
+}
+
+impl MongoDbCollection for AddressFilterCollection {
+    const NAME: &'static str = "address_filters";
+    type Document = AddressFilterChunkDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl AddressFilterCollection {
+    /// Stores the serialized filter of the addresses known at a milestone, and removes all but the previous filter.
+    #[instrument(skip(self, bytes), err, level = "trace")]
+    pub async fn upsert_filter(&self, milestone_index: MilestoneIndex, bytes: &[u8]) -> Result<(), Error> {
+        let previous = self.get_latest_filter_index().await?;
+        let chunks = bytes.chunks(CHUNK_SIZE).collect::<Vec<_>>();
+        let chunk_count = chunks.len().max(1) as u32;
+        for chunk in (0..chunk_count).rev() {
+            self.replace_one(
+                doc! { "_id": { "milestone_index": milestone_index, "chunk": chunk } },
+                AddressFilterChunkDocument {
+                    id: ChunkId { milestone_index, chunk },
+                    chunk_count,
+                    bytes: chunks.get(chunk as usize).map_or_else(Vec::new, |bytes| bytes.to_vec()),
+                },
+                ReplaceOptions::builder().upsert(true).build(),
+            )
+            .await?;
+        }
+        if let Some(previous) = previous.filter(|&previous| previous < milestone_index) {
+            self.collection
+                .delete_many(doc! { "_id.milestone_index": { "$lt": previous } }, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the milestone index of the latest complete filter.
+    pub async fn get_latest_filter_index(&self) -> Result<Option<MilestoneIndex>, Error> {
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(rename = "_id")]
+            id: ChunkId,
+        }
+
+        Ok(self
+            .find_one::<Res>(
+                doc! { "_id.chunk": 0 },
+                FindOneOptions::builder()
+                    .sort(doc! { "_id.milestone_index": -1 })
+                    .projection(doc! { "_id": 1 })
+                    .build(),
+            )
+            .await?
+            .map(|Res { id }| id.milestone_index))
+    }
+
+    /// Gets the latest complete filter along with the milestone index it was built at.
+    #[instrument(skip(self), err, level = "trace")]
+    pub async fn get_latest_filter(&self) -> Result<Option<(MilestoneIndex, Vec<u8>)>, Error> {
+        let milestone_index = match self.get_latest_filter_index().await? {
+            Some(milestone_index) => milestone_index,
+            None => return Ok(None),
+        };
+        let chunks = self
+            .find::<AddressFilterChunkDocument>(
+                doc! { "_id.milestone_index": milestone_index },
+                FindOptions::builder().sort(doc! { "_id.chunk": 1 }).build(),
+            )
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        if chunks
+            .first()
+            .map_or(true, |chunk| chunk.chunk_count as usize != chunks.len())
+        {
+            return Ok(None);
+        }
+        Ok(Some((
+            milestone_index,
+            chunks.into_iter().flat_map(|chunk| chunk.bytes).collect(),
+        )))
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mongodb::{
+    bson::{doc, DateTime},
+    error::Error,
+    options::IndexOptions,
+    IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::db::{
+    mongodb::{MongoDbCollection, MongoDbCollectionExt},
+    MongoDb,
+};
+
+/// A request that was handled by the API.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiAuditRecord {
+    /// The time the request was received.
+    pub requested_at: DateTime,
+    /// The time after which the record is removed from the collection.
+    pub expires_at: DateTime,
+    /// The HTTP method of the request.
+    pub method: String,
+    /// The path of the request, unless it is redacted.
+    pub path: Option<String>,
+    /// The hash of the query string of the request, unless it is redacted or the request had none.
+    pub params_hash: Option<String>,
+    /// The hash of the bearer token of the caller, unless it is redacted or the caller sent none.
+    pub caller: Option<String>,
+    /// The HTTP status of the response.
+    pub status: u16,
+    /// How long the request took to handle, in milliseconds.
+    pub duration_ms: u64,
+    /// The size of the response body in bytes, if it was known before it was sent.
+    pub response_size: Option<u64>,
+}
+
+/// The API audit log collection, which holds a record of every request handled by the API, if enabled.
+pub struct ApiAuditLogCollection {
+    collection: mongodb::Collection<ApiAuditRecord>,
+}
+
+#[async_trait::async_trait]
+impl MongoDbCollection for ApiAuditLogCollection {
+    const NAME: &'static str = "api_audit_log";
+    type Document = ApiAuditRecord;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+
+    async fn create_indexes(&self) -> Result<(), Error> {
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::ZERO)
+                        .name("api_audit_log_expiry_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl ApiAuditLogCollection {
+    /// Inserts a record.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn insert_record(&self, record: &ApiAuditRecord) -> Result<(), Error> {
+        self.insert_one::<ApiAuditRecord>(record, None).await?;
+        Ok(())
+    }
+}
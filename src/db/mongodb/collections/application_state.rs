@@ -1,7 +1,13 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use mongodb::{bson::doc, error::Error, options::UpdateOptions};
+use std::collections::BTreeMap;
+
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::UpdateOptions,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -9,7 +15,7 @@ use crate::{
         mongodb::{MongoDbCollection, MongoDbCollectionExt},
         MongoDb,
     },
-    model::tangle::MilestoneIndexTimestamp,
+    model::tangle::{MilestoneIndex, MilestoneIndexTimestamp},
 };
 
 /// The MongoDb document representation of singleton Application State.
@@ -17,6 +23,30 @@ use crate::{
 pub struct ApplicationStateDocument {
     pub starting_index: Option<MilestoneIndexTimestamp>,
     pub last_migration: Option<MigrationVersion>,
+    /// The last milestone whose entities were mirrored into the external search engine.
+    pub search_index: Option<MilestoneIndex>,
+    /// The last milestone whose analytics were computed.
+    pub analytics_index: Option<MilestoneIndex>,
+    /// The last milestone that was checked for spam.
+    pub spam_detection_index: Option<MilestoneIndex>,
+    /// The first milestone whose block parents were not indexed because it was synced in catch-up mode.
+    pub catch_up_index: Option<MilestoneIndex>,
+    /// The newest Chronicle version that wrote to the database.
+    pub app_version: Option<AppVersion>,
+    /// Uniquely identifies the database. It is generated once and copied along with backups, so two databases with the
+    /// same id were restored from the same database.
+    pub database_id: Option<String>,
+    /// The instance id of the process that writes to the database, which is generated anew whenever a writer starts.
+    /// Two databases with the same id but different writers were restored from the same database and are written
+    /// independently, so they can diverge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub writer_instance_id: Option<String>,
+    /// The milestone before which spent outputs were pruned, by output kind.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_pruning_indexes: Option<BTreeMap<String, MilestoneIndex>>,
+    /// The version of the output details that all outputs were upgraded to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_details_version: Option<u32>,
 }
 
 /// The migration version and associated metadata.
@@ -34,6 +64,21 @@ impl std::fmt::Display for MigrationVersion {
     }
 }
 
+/// A Chronicle version and the fingerprint of the schema it writes.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AppVersion {
+    /// The semantic version of Chronicle.
+    pub version: String,
+    /// Identifies the layout of the documents and derived fields written by this version.
+    pub schema_fingerprint: String,
+}
+
+impl std::fmt::Display for AppVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (schema {})", self.version, self.schema_fingerprint)
+    }
+}
+
 /// A collection to store singleton Application State.
 pub struct ApplicationStateCollection {
     collection: mongodb::Collection<ApplicationStateDocument>,
@@ -94,4 +139,220 @@ impl ApplicationStateCollection {
         .await?;
         Ok(())
     }
+
+    /// Gets the last milestone that was mirrored into the external search engine.
+    pub async fn get_search_index(&self) -> Result<Option<MilestoneIndex>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.search_index))
+    }
+
+    /// Set the last milestone that was mirrored into the external search engine.
+    pub async fn set_search_index(&self, search_index: MilestoneIndex) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "search_index": search_index }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the last milestone whose analytics were computed.
+    pub async fn get_analytics_index(&self) -> Result<Option<MilestoneIndex>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.analytics_index))
+    }
+
+    /// Set the last milestone whose analytics were computed.
+    pub async fn set_analytics_index(&self, analytics_index: MilestoneIndex) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "analytics_index": analytics_index }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the last milestone that was checked for spam.
+    pub async fn get_spam_detection_index(&self) -> Result<Option<MilestoneIndex>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.spam_detection_index))
+    }
+
+    /// Set the last milestone that was checked for spam.
+    pub async fn set_spam_detection_index(&self, spam_detection_index: MilestoneIndex) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "spam_detection_index": spam_detection_index }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the first milestone whose block parents were not indexed because it was synced in catch-up mode.
+    pub async fn get_catch_up_index(&self) -> Result<Option<MilestoneIndex>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.catch_up_index))
+    }
+
+    /// Set the first milestone whose block parents were not indexed because it was synced in catch-up mode.
+    pub async fn set_catch_up_index(&self, catch_up_index: MilestoneIndex) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "catch_up_index": catch_up_index }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Clears the catch-up index, once the work deferred while catching up is done.
+    pub async fn clear_catch_up_index(&self) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$unset": { "catch_up_index": "" }
+            },
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the newest Chronicle version that wrote to the database.
+    pub async fn get_app_version(&self) -> Result<Option<AppVersion>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.app_version))
+    }
+
+    /// Set the newest Chronicle version that wrote to the database.
+    pub async fn set_app_version(&self, app_version: &AppVersion) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "app_version": mongodb::bson::to_bson(app_version)? }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the id of the database.
+    pub async fn get_database_id(&self) -> Result<Option<String>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.database_id))
+    }
+
+    /// Set the id of the database.
+    pub async fn set_database_id(&self, database_id: &str) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "database_id": database_id }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the instance id of the process that writes to the database.
+    pub async fn get_writer_instance_id(&self) -> Result<Option<String>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.writer_instance_id))
+    }
+
+    /// Set the instance id of the process that writes to the database.
+    pub async fn set_writer_instance_id(&self, instance_id: &str) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "writer_instance_id": instance_id }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the id of the database, generating one if the database does not have an id yet.
+    pub async fn get_or_init_database_id(&self) -> Result<String, Error> {
+        let candidate = ObjectId::new().to_hex();
+        // The pipeline keeps an existing id, so concurrent callers agree on the same one.
+        self.update_one(
+            doc! {},
+            vec![doc! { "$set": { "database_id": { "$ifNull": [ "$database_id", &candidate ] } } }],
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(self.get_database_id().await?.unwrap_or(candidate))
+    }
+
+    /// Gets the milestone before which spent outputs of the given kind were pruned.
+    pub async fn get_output_pruning_index(&self, kind: &str) -> Result<Option<MilestoneIndex>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.output_pruning_indexes)
+            .and_then(|mut indexes| indexes.remove(kind)))
+    }
+
+    /// Records that the spent outputs of the given kind were pruned before a milestone. The index never moves back.
+    pub async fn set_output_pruning_index(&self, kind: &str, pruning_index: MilestoneIndex) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$max": { format!("output_pruning_indexes.{kind}"): pruning_index }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the version of the output details that all outputs were upgraded to.
+    pub async fn get_output_details_version(&self) -> Result<Option<u32>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.output_details_version))
+    }
+
+    /// Records that all outputs were upgraded to a version of the output details. The version never moves back.
+    pub async fn set_output_details_version(&self, version: u32) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$max": { "output_details_version": version }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
 }
@@ -1,28 +1,30 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashSet;
+
 use futures::{Stream, TryStreamExt};
 use mongodb::{
-    bson::doc,
+    bson::{de::Error as DeError, doc, Document},
     error::Error,
-    options::{IndexOptions, InsertManyOptions},
-    IndexModel,
+    options::{AggregateOptions, FindOneOptions, FindOptions, IndexOptions, InsertManyOptions},
+    ClientSession, IndexModel,
 };
 use packable::PackableExt;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Serialize};
 use tracing::instrument;
 
-use super::SortOrder;
+use super::{MilestoneCollection, OutputCollection, ProtocolUpdateCollection, SortOrder};
 use crate::{
     db::{
-        mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
+        mongodb::{deadline::DeadlineExt, InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
         MongoDb,
     },
     model::{
         metadata::{BlockMetadata, LedgerInclusionState},
-        payload::TransactionId,
+        payload::{Payload, TransactionEssence, TransactionId},
         tangle::MilestoneIndex,
-        utxo::OutputId,
+        utxo::{Output, OutputId},
         Block, BlockId,
     },
     tangle::BlockData,
@@ -35,9 +37,13 @@ pub struct BlockDocument {
     block_id: BlockId,
     /// The block.
     block: Block,
-    /// The raw bytes of the block.
-    #[serde(with = "serde_bytes")]
-    raw: Vec<u8>,
+    /// The raw bytes of the block, which are left out in lean mode.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "serde_bytes")]
+    raw: Option<Vec<u8>>,
+    /// The number of outputs of the transaction, if the block was stored without its raw bytes. The outputs are then
+    /// restored from the outputs collection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output_count: Option<u16>,
     /// The block's metadata.
     metadata: BlockMetadata,
 }
@@ -54,7 +60,8 @@ impl From<BlockData> for BlockDocument {
         Self {
             block_id,
             block,
-            raw,
+            raw: Some(raw),
+            output_count: None,
             metadata,
         }
     }
@@ -65,15 +72,39 @@ impl From<(BlockId, Block, Vec<u8>, BlockMetadata)> for BlockDocument {
         Self {
             block_id,
             block,
-            raw,
+            raw: Some(raw),
+            output_count: None,
             metadata,
         }
     }
 }
 
+impl BlockDocument {
+    /// Leaves out the raw bytes of the block, which are then reconstructed from the stored block when requested.
+    ///
+    /// The outputs of a transaction are not stored with the block, but are restored from the outputs collection, so
+    /// only the number of outputs is kept. Blocks with a conflicting transaction keep their raw bytes, since their
+    /// outputs were never created.
+    pub fn without_raw(mut self) -> Self {
+        if let Some(Payload::Transaction(payload)) = &self.block.payload {
+            if self.metadata.inclusion_state != LedgerInclusionState::Included {
+                return self;
+            }
+            let TransactionEssence::Regular { outputs, .. } = &payload.essence;
+            self.output_count = Some(outputs.len() as u16);
+        }
+        self.raw = None;
+        self
+    }
+}
+
 /// The stardust blocks collection.
 pub struct BlockCollection {
     collection: mongodb::Collection<BlockDocument>,
+    protocol_updates: ProtocolUpdateCollection,
+    /// The outputs of transactions in blocks that were stored without their raw bytes are restored from here. This is
+    /// not an [`OutputCollection`], which in turn holds a [`BlockCollection`].
+    outputs: mongodb::Collection<RestoredOutput>,
 }
 
 #[async_trait::async_trait]
@@ -81,8 +112,12 @@ impl MongoDbCollection for BlockCollection {
     const NAME: &'static str = "stardust_blocks";
     type Document = BlockDocument;
 
-    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
-        Self { collection }
+    fn instantiate(db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self {
+            collection,
+            protocol_updates: db.collection(),
+            outputs: db.db().collection(OutputCollection::NAME),
+        }
     }
 
     fn collection(&self) -> &mongodb::Collection<Self::Document> {
@@ -121,6 +156,19 @@ impl MongoDbCollection for BlockCollection {
         )
         .await?;
 
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "block.payload.kind": 1, "metadata.referenced_by_milestone_index": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("block_payload_kind_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
         Ok(())
     }
 }
@@ -139,10 +187,88 @@ pub struct IncludedBlockMetadataResult {
     pub metadata: BlockMetadata,
 }
 
+/// An output, along with the block and milestone that created it.
+#[derive(Debug, Clone)]
+#[allow(missing_docs)]
+pub struct CreatedOutputResult {
+    pub block_id: BlockId,
+    pub milestone_index: MilestoneIndex,
+    pub output: Output,
+}
+
+/// The raw bytes of a block or, if it was stored without them, the block and the milestone that referenced it.
 #[derive(Deserialize)]
 struct RawResult {
-    #[serde(with = "serde_bytes")]
-    raw: Vec<u8>,
+    #[serde(rename = "_id")]
+    block_id: BlockId,
+    #[serde(default, with = "serde_bytes")]
+    raw: Option<Vec<u8>>,
+    block: Option<Block>,
+    #[serde(default)]
+    output_count: Option<u16>,
+    milestone_index: MilestoneIndex,
+}
+
+impl RawResult {
+    /// Projects the fields of a [`RawResult`], including the block only if there are no raw bytes.
+    fn projection() -> Document {
+        doc! { "$project": {
+            "raw": 1,
+            "block": { "$cond": [{ "$eq": [{ "$type": "$raw" }, "missing"] }, "$block", "$$REMOVE"] },
+            "output_count": 1,
+            "milestone_index": "$metadata.referenced_by_milestone_index",
+        } }
+    }
+}
+
+/// An output that is restored into the transaction of a block that was stored without its raw bytes.
+#[derive(Deserialize)]
+struct RestoredOutput {
+    output: Output,
+}
+
+/// Unpacks the raw bytes of a block.
+fn unpack_block(raw: Vec<u8>) -> Result<Block, Error> {
+    Ok(iota_sdk::types::block::Block::unpack_unverified(raw)
+        .map_err(|e| DeError::custom(format!("invalid raw block: {e:?}")))?
+        .into())
+}
+
+/// Restores the outputs of the transaction in a block that was stored without its raw bytes. Fails if some of the
+/// outputs were pruned.
+async fn restore_outputs(
+    outputs: &mongodb::Collection<RestoredOutput>,
+    block_id: &BlockId,
+    block: &mut Block,
+    output_count: Option<u16>,
+) -> Result<(), Error> {
+    if let (Some(Payload::Transaction(payload)), Some(output_count)) = (&mut block.payload, output_count) {
+        let restored = outputs
+            .find(
+                doc! { "metadata.block_id": block_id },
+                Some(
+                    FindOptions::builder()
+                        .sort(doc! { "_id.index": 1 })
+                        .projection(doc! { "output": 1 })
+                        .build(),
+                )
+                .with_deadline(),
+            )
+            .await?
+            .map_ok(|res| res.output)
+            .try_collect::<Vec<_>>()
+            .await?;
+        if restored.len() != output_count as usize {
+            return Err(DeError::custom(format!(
+                "the outputs of transaction {} were pruned",
+                payload.transaction_id.to_hex()
+            ))
+            .into());
+        }
+        let TransactionEssence::Regular { outputs, .. } = &mut payload.essence;
+        *outputs = restored.into_boxed_slice();
+    }
+    Ok(())
 }
 
 #[derive(Deserialize)]
@@ -155,26 +281,93 @@ struct BlockIdResult {
 impl BlockCollection {
     /// Get a [`Block`] by its [`BlockId`].
     pub async fn get_block(&self, block_id: &BlockId) -> Result<Option<Block>, Error> {
-        Ok(self
-            .get_block_raw(block_id)
-            .await?
-            .map(|raw| iota_sdk::types::block::Block::unpack_unverified(raw).unwrap().into()))
+        match self.get_raw_result(doc! { "_id": block_id }).await? {
+            Some(res) => Ok(Some(self.resolve_block(res).await?)),
+            None => Ok(None),
+        }
     }
 
     /// Get the raw bytes of a [`Block`] by its [`BlockId`].
     pub async fn get_block_raw(&self, block_id: &BlockId) -> Result<Option<Vec<u8>>, Error> {
-        Ok(self
-            .aggregate(
-                [
-                    doc! { "$match": { "_id": block_id } },
-                    doc! { "$project": { "raw": 1 } },
-                ],
-                None,
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(default, with = "serde_bytes")]
+            raw: Option<Vec<u8>>,
+        }
+
+        // Most blocks are stored with their raw bytes, which are then read as they are, without running a pipeline.
+        match self
+            .find_one::<Res>(
+                doc! { "_id": block_id },
+                FindOneOptions::builder().projection(doc! { "raw": 1 }).build(),
             )
             .await?
-            .try_next()
+        {
+            Some(Res { raw: Some(raw) }) => Ok(Some(raw)),
+            Some(Res { raw: None }) => match self.get_raw_result(doc! { "_id": block_id }).await? {
+                Some(res) => Ok(Some(self.resolve_raw(res).await?)),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn get_raw_result(&self, filter: Document) -> Result<Option<RawResult>, Error> {
+        self.aggregate([doc! { "$match": filter }, RawResult::projection()], None)
             .await?
-            .map(|RawResult { raw }| raw))
+            .try_next()
+            .await
+    }
+
+    /// Returns the block, unpacking it from its raw bytes or restoring the outputs of its transaction if they were
+    /// left out.
+    async fn resolve_block(&self, res: RawResult) -> Result<Block, Error> {
+        match (res.raw, res.block) {
+            (Some(raw), _) => unpack_block(raw),
+            (None, Some(mut block)) => {
+                restore_outputs(&self.outputs, &res.block_id, &mut block, res.output_count).await?;
+                Ok(block)
+            }
+            (None, None) => unreachable!("the block is projected if there are no raw bytes"),
+        }
+    }
+
+    /// Returns the raw bytes of a block, reconstructing them from the stored block if they were left out.
+    async fn resolve_raw(&self, res: RawResult) -> Result<Vec<u8>, Error> {
+        match (res.raw, res.block) {
+            (Some(raw), _) => Ok(raw),
+            (None, Some(mut block)) => {
+                restore_outputs(&self.outputs, &res.block_id, &mut block, res.output_count).await?;
+                let parameters = self
+                    .protocol_updates
+                    .get_protocol_parameters_for_ledger_index(res.milestone_index)
+                    .await?
+                    .ok_or_else(|| {
+                        DeError::custom(format!("no protocol parameters for milestone {}", res.milestone_index))
+                    })?
+                    .parameters;
+                Ok(block.raw(parameters).map_err(DeError::custom)?)
+            }
+            (None, None) => unreachable!("the block is projected if there are no raw bytes"),
+        }
+    }
+
+    /// Get a [`Block`] by its [`BlockId`], leaving out its payload.
+    pub async fn get_block_without_payload(&self, block_id: &BlockId) -> Result<Option<Block>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "_id": block_id } },
+                doc! { "$replaceWith": {
+                    "protocol_version": "$block.protocol_version",
+                    "parents": "$block.parents",
+                    "nonce": "$block.nonce",
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_next()
+        .await
     }
 
     /// Get the metadata of a [`Block`] by its [`BlockId`].
@@ -191,38 +384,6 @@ impl BlockCollection {
         .await
     }
 
-    /// Get the children of a [`Block`] as a stream of [`BlockId`]s.
-    pub async fn get_block_children(
-        &self,
-        block_id: &BlockId,
-        block_referenced_index: MilestoneIndex,
-        below_max_depth: u8,
-        page_size: usize,
-        page: usize,
-    ) -> Result<impl Stream<Item = Result<BlockId, Error>>, Error> {
-        let max_referenced_index = block_referenced_index + below_max_depth as u32;
-
-        Ok(self
-            .aggregate(
-                [
-                    doc! { "$match": {
-                        "metadata.referenced_by_milestone_index": {
-                            "$gte": block_referenced_index,
-                            "$lte": max_referenced_index
-                        },
-                        "block.parents": block_id,
-                    } },
-                    doc! { "$sort": {"metadata.referenced_by_milestone_index": -1} },
-                    doc! { "$skip": (page_size * page) as i64 },
-                    doc! { "$limit": page_size as i64 },
-                    doc! { "$project": { "_id": 1 } },
-                ],
-                None,
-            )
-            .await?
-            .map_ok(|BlockIdResult { block_id }| block_id))
-    }
-
     /// Get the blocks that were referenced by the specified milestone (in White-Flag order).
     pub async fn get_referenced_blocks_in_white_flag_order(
         &self,
@@ -254,11 +415,22 @@ impl BlockCollection {
         struct QueryRes {
             #[serde(rename = "_id")]
             block_id: BlockId,
-            #[serde(with = "serde_bytes")]
-            raw: Vec<u8>,
+            block: Block,
+            #[serde(default, with = "serde_bytes")]
+            raw: Option<Vec<u8>>,
+            #[serde(default)]
+            output_count: Option<u16>,
             metadata: BlockMetadata,
         }
 
+        // The parameters are only needed to reconstruct the raw bytes of blocks that were stored without them.
+        let parameters = self
+            .protocol_updates
+            .get_protocol_parameters_for_ledger_index(index)
+            .await?
+            .map(|doc| doc.parameters);
+        let outputs = self.outputs.clone();
+
         Ok(self
             .aggregate::<QueryRes>(
                 [
@@ -268,18 +440,68 @@ impl BlockCollection {
                 None,
             )
             .await?
-            .map_ok(|r| {
-                (
-                    r.block_id,
-                    iota_sdk::types::block::Block::unpack_unverified(r.raw.clone())
-                        .unwrap()
-                        .into(),
-                    r.raw,
-                    r.metadata,
-                )
+            .and_then(move |mut r| {
+                let (outputs, parameters) = (outputs.clone(), parameters.clone());
+                // Boxed, so that the stream stays `Unpin`.
+                Box::pin(async move {
+                    let (block, raw) = match r.raw {
+                        Some(raw) => (unpack_block(raw.clone())?, raw),
+                        None => {
+                            restore_outputs(&outputs, &r.block_id, &mut r.block, r.output_count).await?;
+                            let parameters = parameters.ok_or_else(|| {
+                                DeError::custom(format!("no protocol parameters for milestone {index}"))
+                            })?;
+                            (r.block.clone(), r.block.raw(parameters).map_err(DeError::custom)?)
+                        }
+                    };
+                    Ok((r.block_id, block, raw, r.metadata))
+                })
             }))
     }
 
+    /// Get the metadata of the blocks that were referenced by the milestones in the given range.
+    pub async fn get_block_metadata_in_range(
+        &self,
+        start_index: MilestoneIndex,
+        end_index: MilestoneIndex,
+    ) -> Result<impl Stream<Item = Result<(BlockId, BlockMetadata), Error>>, Error> {
+        #[derive(Deserialize)]
+        struct QueryRes {
+            #[serde(rename = "_id")]
+            block_id: BlockId,
+            metadata: BlockMetadata,
+        }
+
+        Ok(self
+            .aggregate::<QueryRes>(
+                [
+                    doc! { "$match": {
+                        "metadata.referenced_by_milestone_index": { "$gte": start_index, "$lte": end_index }
+                    } },
+                    doc! { "$project": { "_id": 1, "metadata": 1 } },
+                ],
+                None,
+            )
+            .await?
+            .map_ok(|r| (r.block_id, r.metadata)))
+    }
+
+    /// Samples up to `count` ids of referenced blocks.
+    pub async fn get_sample_block_ids(&self, count: usize) -> Result<Vec<BlockId>, Error> {
+        self.aggregate::<BlockIdResult>(
+            [
+                doc! { "$match": { "metadata.referenced_by_milestone_index": { "$gt": 0 } } },
+                doc! { "$sample": { "size": count as i64 } },
+                doc! { "$project": { "_id": 1 } },
+            ],
+            None,
+        )
+        .await?
+        .map_ok(|res| res.block_id)
+        .try_collect()
+        .await
+    }
+
     /// Get the blocks that were applied by the specified milestone (in White-Flag order).
     pub async fn get_applied_blocks_in_white_flag_order(&self, index: MilestoneIndex) -> Result<Vec<BlockId>, Error> {
         let block_ids = self
@@ -305,18 +527,56 @@ impl BlockCollection {
     /// Inserts [`Block`]s together with their associated [`BlockMetadata`].
     #[instrument(skip_all, err, level = "trace")]
     pub async fn insert_blocks_with_metadata<I, B>(&self, blocks_with_metadata: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = B>,
+        I::IntoIter: Send + Sync,
+        BlockDocument: From<B>,
+    {
+        self.insert_blocks_with_session(blocks_with_metadata, None).await
+    }
+
+    /// Inserts [`Block`]s together with their associated [`BlockMetadata`], as part of the transaction of the session
+    /// if one is given.
+    ///
+    /// Within a transaction, a duplicate would abort the transaction instead of being ignored, so blocks that are
+    /// already stored are left out.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn insert_blocks_with_session<I, B>(
+        &self,
+        blocks_with_metadata: I,
+        session: Option<&mut ClientSession>,
+    ) -> Result<(), Error>
     where
         I: IntoIterator<Item = B>,
         I::IntoIter: Send + Sync,
         BlockDocument: From<B>,
     {
         let blocks_with_metadata = blocks_with_metadata.into_iter().map(BlockDocument::from);
+        let session = match session {
+            Some(session) => session,
+            None => {
+                self.insert_many_ignore_duplicates(
+                    blocks_with_metadata,
+                    InsertManyOptions::builder().ordered(false).build(),
+                )
+                .await?;
+                return Ok(());
+            }
+        };
 
-        self.insert_many_ignore_duplicates(
-            blocks_with_metadata,
-            InsertManyOptions::builder().ordered(false).build(),
-        )
-        .await?;
+        let mut blocks = blocks_with_metadata.collect::<Vec<_>>();
+        let block_ids = blocks.iter().map(|block| block.block_id).collect::<Vec<_>>();
+        let stored = self
+            .collection
+            .distinct_with_session("_id", doc! { "_id": { "$in": block_ids } }, None, session)
+            .await?
+            .into_iter()
+            .map(mongodb::bson::from_bson::<BlockId>)
+            .collect::<Result<HashSet<_>, _>>()?;
+        blocks.retain(|block| !stored.contains(&block.block_id));
+        if !blocks.is_empty() {
+            self.insert_many_with_session(blocks, None, Some(session)).await?;
+        }
 
         Ok(())
     }
@@ -326,13 +586,19 @@ impl BlockCollection {
         &self,
         transaction_id: &TransactionId,
     ) -> Result<Option<IncludedBlockResult>, Error> {
-        Ok(self.get_block_raw_for_transaction(transaction_id).await?.map(|raw| {
-            let block = iota_sdk::types::block::Block::unpack_unverified(raw).unwrap();
-            IncludedBlockResult {
-                block_id: block.id().into(),
-                block: block.into(),
-            }
-        }))
+        match self
+            .get_raw_result(doc! {
+                "metadata.inclusion_state": LedgerInclusionState::Included,
+                "block.payload.transaction_id": transaction_id,
+            })
+            .await?
+        {
+            Some(res) => Ok(Some(IncludedBlockResult {
+                block_id: res.block_id,
+                block: self.resolve_block(res).await?,
+            })),
+            None => Ok(None),
+        }
     }
 
     /// Finds the raw bytes of the block that included a transaction by [`TransactionId`].
@@ -340,21 +606,16 @@ impl BlockCollection {
         &self,
         transaction_id: &TransactionId,
     ) -> Result<Option<Vec<u8>>, Error> {
-        Ok(self
-            .aggregate(
-                [
-                    doc! { "$match": {
-                        "metadata.inclusion_state": LedgerInclusionState::Included,
-                        "block.payload.transaction_id": transaction_id,
-                    } },
-                    doc! { "$project": { "raw": 1 } },
-                ],
-                None,
-            )
+        match self
+            .get_raw_result(doc! {
+                "metadata.inclusion_state": LedgerInclusionState::Included,
+                "block.payload.transaction_id": transaction_id,
+            })
             .await?
-            .try_next()
-            .await?
-            .map(|RawResult { raw }| raw))
+        {
+            Some(res) => Ok(Some(self.resolve_raw(res).await?)),
+            None => Ok(None),
+        }
     }
 
     /// Finds the [`BlockMetadata`] that included a transaction by [`TransactionId`].
@@ -380,23 +641,56 @@ impl BlockCollection {
         .await
     }
 
+    /// Gets an [`Output`](crate::model::utxo::Output) from the block of the transaction that created it. Unlike the
+    /// outputs collection, this still has the output after it was spent and pruned, unless the block was stored without
+    /// its raw bytes.
+    pub async fn get_created_output(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<CreatedOutputResult>, Error> {
+        let res = match self
+            .get_raw_result(doc! {
+                "metadata.inclusion_state": LedgerInclusionState::Included,
+                "block.payload.transaction_id": &output_id.transaction_id,
+                "metadata.referenced_by_milestone_index": { "$lte": ledger_index },
+            })
+            .await?
+        {
+            Some(res) => res,
+            None => return Ok(None),
+        };
+        let (block_id, milestone_index) = (res.block_id, res.milestone_index);
+        Ok(match self.resolve_block(res).await?.payload {
+            Some(Payload::Transaction(payload)) => {
+                let TransactionEssence::Regular { outputs, .. } = payload.essence;
+                outputs
+                    .into_vec()
+                    .into_iter()
+                    .nth(output_id.index as usize)
+                    .map(|output| CreatedOutputResult {
+                        block_id,
+                        milestone_index,
+                        output,
+                    })
+            }
+            _ => None,
+        })
+    }
+
     /// Gets the spending transaction of an [`Output`](crate::model::utxo::Output) by [`OutputId`].
     pub async fn get_spending_transaction(&self, output_id: &OutputId) -> Result<Option<Block>, Error> {
-        self.aggregate(
-            [
-                doc! { "$match": {
-                    "metadata.inclusion_state": LedgerInclusionState::Included,
-                    "block.payload.essence.inputs.transaction_id": &output_id.transaction_id,
-                    "block.payload.essence.inputs.index": &(output_id.index as i32)
-                } },
-                doc! { "$project": { "raw": 1 } },
-            ],
-            None,
-        )
-        .await?
-        .map_ok(|RawResult { raw }| iota_sdk::types::block::Block::unpack_unverified(raw).unwrap().into())
-        .try_next()
-        .await
+        match self
+            .get_raw_result(doc! {
+                "metadata.inclusion_state": LedgerInclusionState::Included,
+                "block.payload.essence.inputs.transaction_id": &output_id.transaction_id,
+                "block.payload.essence.inputs.index": &(output_id.index as i32)
+            })
+            .await?
+        {
+            Some(res) => Ok(Some(self.resolve_block(res).await?)),
+            None => Ok(None),
+        }
     }
 }
 
@@ -444,3 +738,103 @@ impl BlockCollection {
         .await
     }
 }
+
+/// Implements the pruning of blocks.
+impl BlockCollection {
+    /// Streams the ids of the blocks that were referenced before the given milestone and carry a payload of the given
+    /// kind, or no payload at all if no kind is given.
+    pub async fn get_prunable_block_ids(
+        &self,
+        payload_kind: Option<&str>,
+        before: MilestoneIndex,
+    ) -> Result<impl Stream<Item = Result<BlockId, Error>>, Error> {
+        let payload = match payload_kind {
+            Some(kind) => doc! { "block.payload.kind": kind },
+            // Also matches blocks without a payload, so that the index on the kind is used for them as well.
+            None => doc! { "block.payload.kind": null },
+        };
+        Ok(self
+            .find::<BlockIdResult>(
+                doc! {
+                    "$and": [
+                        { "metadata.referenced_by_milestone_index": { "$lt": before } },
+                        payload,
+                    ]
+                },
+                FindOptions::builder().projection(doc! { "_id": 1 }).build(),
+            )
+            .await?
+            .map_ok(|BlockIdResult { block_id }| block_id))
+    }
+
+    /// Deletes the blocks with the given ids and returns how many were deleted.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn delete_blocks(&self, block_ids: &[BlockId]) -> Result<u64, Error> {
+        Ok(self
+            .collection
+            .delete_many(doc! { "_id": { "$in": block_ids } }, None)
+            .await?
+            .deleted_count)
+    }
+}
+
+/// The blocks that were referenced by a milestone that is not stored.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct OrphanedBlocksResult {
+    /// The index of the missing milestone.
+    #[serde(rename = "_id")]
+    pub milestone_index: MilestoneIndex,
+    /// The number of blocks referenced by it.
+    pub block_count: u64,
+}
+
+/// Implements the collection of orphaned blocks.
+impl BlockCollection {
+    /// Streams the milestones up to the given ledger index that blocks were referenced by, but that are not stored,
+    /// such as the milestone that was being written when Chronicle stopped and that was never synced again.
+    ///
+    /// Milestones past the ledger index are left out, since their blocks may still be in the process of being written.
+    pub async fn get_orphaned_blocks(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<impl Stream<Item = Result<OrphanedBlocksResult, Error>>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "metadata.referenced_by_milestone_index": { "$lte": ledger_index } } },
+                doc! { "$group": {
+                    "_id": "$metadata.referenced_by_milestone_index",
+                    "block_count": { "$sum": 1 },
+                } },
+                doc! { "$lookup": {
+                    "from": MilestoneCollection::NAME,
+                    "let": { "index": "$_id" },
+                    "pipeline": [
+                        { "$match": { "$expr": { "$eq": [ "$at.milestone_index", "$$index" ] } } },
+                        { "$limit": 1 },
+                        { "$project": { "_id": 1 } },
+                    ],
+                    "as": "milestone",
+                } },
+                doc! { "$match": { "milestone": { "$size": 0 } } },
+                doc! { "$sort": { "_id": 1 } },
+                doc! { "$project": { "block_count": 1 } },
+            ],
+            AggregateOptions::builder().allow_disk_use(true).build(),
+        )
+        .await
+    }
+
+    /// Streams the ids of the blocks that were referenced by the given milestone.
+    pub async fn get_block_ids_referenced_by(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<impl Stream<Item = Result<BlockId, Error>>, Error> {
+        Ok(self
+            .find::<BlockIdResult>(
+                doc! { "metadata.referenced_by_milestone_index": index },
+                FindOptions::builder().projection(doc! { "_id": 1 }).build(),
+            )
+            .await?
+            .map_ok(|BlockIdResult { block_id }| block_id))
+    }
+}
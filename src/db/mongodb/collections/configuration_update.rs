@@ -5,6 +5,7 @@ use mongodb::{
     bson::doc,
     error::Error,
     options::{FindOneOptions, UpdateOptions},
+    ClientSession,
 };
 use serde::{Deserialize, Serialize};
 
@@ -68,12 +69,31 @@ impl ConfigurationUpdateCollection {
         ledger_index: MilestoneIndex,
         config: NodeConfiguration,
     ) -> Result<(), Error> {
-        let node_config = self.get_node_configuration_for_ledger_index(ledger_index).await?;
+        self.upsert_node_configuration_with_session(ledger_index, config, None)
+            .await
+    }
+
+    /// Inserts or updates a node configuration for a given ledger index, as part of the transaction of the session if
+    /// one is given.
+    pub async fn upsert_node_configuration_with_session(
+        &self,
+        ledger_index: MilestoneIndex,
+        config: NodeConfiguration,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<(), Error> {
+        let node_config = self
+            .find_one_with_session::<ConfigurationUpdateDocument>(
+                doc! { "_id": { "$lte": ledger_index } },
+                FindOneOptions::builder().sort(doc! { "_id": -1 }).build(),
+                session.as_deref_mut(),
+            )
+            .await?;
         if !matches!(node_config, Some(node_config) if node_config.config == config) {
-            self.update_one(
+            self.update_one_with_session(
                 doc! { "_id": ledger_index },
                 doc! { "$set": mongodb::bson::to_bson(&config)? },
                 UpdateOptions::builder().upsert(true).build(),
+                session,
             )
             .await?;
         }
@@ -0,0 +1,113 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    error::Error,
+    options::{FindOneOptions, FindOptions, ReplaceOptions},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::LedgerDigest;
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::tangle::{MilestoneIndex, MilestoneTimestamp},
+};
+
+/// The digest of the unspent output set at a snapshot milestone.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerSnapshotDocument {
+    #[serde(rename = "_id")]
+    milestone_index: MilestoneIndex,
+    milestone_timestamp: MilestoneTimestamp,
+    #[serde(flatten)]
+    digest: LedgerDigest,
+}
+
+/// A ledger snapshot digest as returned by queries.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[allow(missing_docs)]
+pub struct LedgerSnapshotResult {
+    #[serde(rename = "_id")]
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    #[serde(flatten)]
+    pub digest: LedgerDigest,
+}
+
+/// The stardust ledger snapshot collection, which holds a digest of the unspent output set at regular milestone
+/// intervals.
+pub struct LedgerSnapshotCollection {
+    collection: mongodb::Collection<LedgerSnapshotDocument>,
+}
+
+#[async_trait::async_trait]
+impl MongoDbCollection for LedgerSnapshotCollection {
+    const NAME: &'static str = "stardust_ledger_snapshots";
+    type Document = LedgerSnapshotDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl LedgerSnapshotCollection {
+    /// Stores the digest of the unspent output set at a milestone, replacing an earlier one.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn upsert_snapshot(
+        &self,
+        milestone_index: MilestoneIndex,
+        milestone_timestamp: MilestoneTimestamp,
+        digest: LedgerDigest,
+    ) -> Result<(), Error> {
+        self.replace_one(
+            doc! { "_id": milestone_index },
+            LedgerSnapshotDocument {
+                milestone_index,
+                milestone_timestamp,
+                digest,
+            },
+            ReplaceOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gets the index of the latest snapshot.
+    pub async fn get_latest_snapshot_index(&self) -> Result<Option<MilestoneIndex>, Error> {
+        Ok(self
+            .find_one::<LedgerSnapshotResult>(doc! {}, FindOneOptions::builder().sort(doc! { "_id": -1 }).build())
+            .await?
+            .map(|res| res.milestone_index))
+    }
+
+    /// Gets the snapshots between the given milestone indexes, inclusively, in ascending order.
+    pub async fn get_snapshots(
+        &self,
+        start_index: Option<MilestoneIndex>,
+        end_index: Option<MilestoneIndex>,
+    ) -> Result<Vec<LedgerSnapshotResult>, Error> {
+        self.find::<LedgerSnapshotResult>(
+            doc! {
+                "$nor": [
+                    { "_id": { "$lt": start_index } },
+                    { "_id": { "$gt": end_index } },
+                ]
+            },
+            FindOptions::builder().sort(doc! { "_id": 1 }).build(),
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+}
@@ -5,13 +5,13 @@ use futures::{Stream, TryStreamExt};
 use mongodb::{
     bson::{doc, Document},
     error::Error,
-    options::{FindOptions, IndexOptions, InsertManyOptions},
+    options::{FindOneOptions, FindOptions, IndexOptions, InsertManyOptions},
     IndexModel,
 };
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
-use super::SortOrder;
+use super::{OutputCollection, SortOrder};
 use crate::{
     db::{
         mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
@@ -19,6 +19,8 @@ use crate::{
     },
     model::{
         ledger::{LedgerOutput, LedgerSpent},
+        payload::TransactionId,
+        stringify,
         tangle::{MilestoneIndex, MilestoneIndexTimestamp, MilestoneTimestamp},
         utxo::{Address, OutputId},
     },
@@ -73,6 +75,32 @@ impl MongoDbCollection for LedgerUpdateCollection {
         )
         .await?;
 
+        self.create_index(
+            IndexModel::builder()
+                .keys(by_milestone())
+                .options(
+                    IndexOptions::builder()
+                        .name("ledger_update_milestone_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "_id.output_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("ledger_update_output_id_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
         Ok(())
     }
 }
@@ -93,6 +121,80 @@ pub struct LedgerUpdateByMilestoneRecord {
     pub is_spent: bool,
 }
 
+/// The activity of an address in a single milestone.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct AddressActivityRecord {
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub created_count: usize,
+    pub consumed_count: usize,
+    /// The amount of base tokens in the outputs the address received.
+    #[serde(with = "stringify")]
+    pub received: u64,
+    /// The amount of base tokens in the outputs the address spent.
+    #[serde(with = "stringify")]
+    pub sent: u64,
+}
+
+/// The tokens an address received and sent over a range of milestones.
+///
+/// The amounts are looked up from the outputs collection, so outputs that were pruned after they were spent are
+/// counted, but their amounts are missing from the sums.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct AddressFlowRecord {
+    pub created_count: usize,
+    pub consumed_count: usize,
+    /// The number of ledger updates whose output was pruned, and whose amount is therefore missing from the sums.
+    #[serde(default)]
+    pub pruned_count: usize,
+    /// The amount of base tokens in the outputs the address received.
+    #[serde(with = "stringify")]
+    pub received: u64,
+    /// The amount of base tokens in the outputs the address spent.
+    #[serde(with = "stringify")]
+    pub sent: u64,
+}
+
+/// The tokens an address received and sent in a range of milestones.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct BalanceChangeRecord {
+    /// The first milestone of the range, which is a multiple of the interval.
+    pub start_index: MilestoneIndex,
+    /// The amount of base tokens in the outputs the address received.
+    #[serde(with = "stringify")]
+    pub received: u64,
+    /// The amount of base tokens in the outputs the address spent.
+    #[serde(with = "stringify")]
+    pub sent: u64,
+}
+
+/// A transaction that credited or debited an address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct AddressTransactionRecord {
+    pub transaction_id: TransactionId,
+    /// The milestone that included the transaction.
+    pub at: MilestoneIndexTimestamp,
+    /// The amount of base tokens in the outputs the transaction created for the address.
+    #[serde(with = "stringify")]
+    pub received: u64,
+    /// The amount of base tokens in the outputs of the address the transaction consumed.
+    #[serde(with = "stringify")]
+    pub sent: u64,
+    /// Whether the transaction consumed any output of the address.
+    pub is_spent: bool,
+}
+
+impl AddressFlowRecord {
+    /// The amount of base tokens the balance of the address changed by.
+    pub fn net(&self) -> i128 {
+        self.received as i128 - self.sent as i128
+    }
+}
+
 fn newest() -> Document {
     doc! { "address": -1, "_id.milestone_index": -1, "_id.output_id": -1, "_id.is_spent": -1 }
 }
@@ -101,8 +203,22 @@ fn oldest() -> Document {
     doc! { "address": 1, "_id.milestone_index": 1, "_id.output_id": 1, "_id.is_spent": 1 }
 }
 
+fn by_milestone() -> Document {
+    doc! { "_id.milestone_index": 1, "_id.output_id": 1, "_id.is_spent": 1 }
+}
+
 /// Queries that are related to [`Output`](crate::model::utxo::Output)s.
 impl LedgerUpdateCollection {
+    /// Deletes the ledger updates of the given outputs.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn delete_output_updates(&self, output_ids: &[OutputId]) -> Result<(), Error> {
+        self.collection
+            .delete_many(doc! { "_id.output_id": { "$in": output_ids } }, None)
+            .await?;
+
+        Ok(())
+    }
+
     /// Inserts [`LedgerSpent`] updates.
     #[instrument(skip_all, err, level = "trace")]
     pub async fn insert_spent_ledger_updates<'a, I>(&self, outputs: I) -> Result<(), Error>
@@ -153,35 +269,48 @@ impl LedgerUpdateCollection {
         Ok(())
     }
 
-    /// Streams updates to the ledger for a given address.
+    /// Streams updates to the ledger for a given address, up to and including the given ledger index.
+    ///
+    /// The updates are totally ordered by milestone index, output id (transaction id, then output index) and
+    /// direction, so that oldest first lists the update that creates an output before the one that spends it. The
+    /// cursor is the position of the first update to return. Without an output id, it starts with the first update of
+    /// the milestone in the given order.
+    ///
+    /// Updates of milestones after the ledger index may still be in the process of being written, so they are left
+    /// out to keep the pages stable.
     pub async fn get_ledger_updates_by_address(
         &self,
         address: &Address,
         page_size: usize,
         cursor: Option<(MilestoneIndex, Option<(OutputId, bool)>)>,
         order: SortOrder,
+        ledger_index: MilestoneIndex,
     ) -> Result<impl Stream<Item = Result<LedgerUpdateByAddressRecord, Error>>, Error> {
         let (sort, cmp1, cmp2) = match order {
             SortOrder::Newest => (newest(), "$lt", "$lte"),
             SortOrder::Oldest => (oldest(), "$gt", "$gte"),
         };
 
-        let mut queries = vec![doc! { "address": address }];
+        let mut queries = vec![
+            doc! { "address": address },
+            doc! { "_id.milestone_index": { "$lte": ledger_index } },
+        ];
 
-        if let Some((milestone_index, rest)) = cursor {
-            let mut cursor_queries = vec![doc! { "_id.milestone_index": { cmp1: milestone_index } }];
-            if let Some((output_id, is_spent)) = rest {
-                cursor_queries.push(doc! {
+        match cursor {
+            Some((milestone_index, Some((output_id, is_spent)))) => queries.push(doc! { "$or": [
+                { "_id.milestone_index": { cmp1: milestone_index } },
+                {
                     "_id.milestone_index": milestone_index,
                     "_id.output_id": { cmp1: output_id }
-                });
-                cursor_queries.push(doc! {
+                },
+                {
                     "_id.milestone_index": milestone_index,
                     "_id.output_id": output_id,
                     "_id.is_spent": { cmp2: is_spent }
-                });
-            }
-            queries.push(doc! { "$or": cursor_queries });
+                },
+            ] }),
+            Some((milestone_index, None)) => queries.push(doc! { "_id.milestone_index": { cmp2: milestone_index } }),
+            None => (),
         }
 
         Ok(self
@@ -197,30 +326,363 @@ impl LedgerUpdateCollection {
             }))
     }
 
-    /// Streams updates to the ledger for a given milestone index (sorted by [`OutputId`]).
+    /// Streams the per-milestone activity of an address up to the given milestone index (sorted by milestone index).
+    ///
+    /// This is computed from the ledger updates of the address alone, so that it can be used to check analytics that
+    /// were recorded for the whole ledger.
+    pub async fn get_address_activity(
+        &self,
+        address: &Address,
+        end_index: MilestoneIndex,
+    ) -> Result<impl Stream<Item = Result<AddressActivityRecord, Error>>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "address": address,
+                    "_id.milestone_index": { "$lte": end_index },
+                } },
+                doc! { "$lookup": {
+                    "from": OutputCollection::NAME,
+                    "localField": "_id.output_id",
+                    "foreignField": "_id",
+                    "as": "output",
+                } },
+                doc! { "$set": {
+                    "amount": { "$toDecimal": { "$arrayElemAt": [ "$output.output.amount", 0 ] } },
+                } },
+                doc! { "$group": {
+                    "_id": "$_id.milestone_index",
+                    "milestone_timestamp": { "$first": "$milestone_timestamp" },
+                    "created_count": { "$sum": { "$cond": [ "$_id.is_spent", 0, 1 ] } },
+                    "consumed_count": { "$sum": { "$cond": [ "$_id.is_spent", 1, 0 ] } },
+                    "received": { "$sum": { "$cond": [ "$_id.is_spent", 0, "$amount" ] } },
+                    "sent": { "$sum": { "$cond": [ "$_id.is_spent", "$amount", 0 ] } },
+                } },
+                doc! { "$sort": { "_id": 1 } },
+                doc! { "$project": {
+                    "_id": 0,
+                    "milestone_index": "$_id",
+                    "milestone_timestamp": 1,
+                    "created_count": 1,
+                    "consumed_count": 1,
+                    "received": { "$toString": "$received" },
+                    "sent": { "$toString": "$sent" },
+                } },
+            ],
+            None,
+        )
+        .await
+    }
+
+    /// Sums up the tokens an address received and sent between two milestone indexes (inclusive), in a single
+    /// aggregation rather than by paging through the ledger updates of the address.
+    ///
+    /// Once spent outputs are pruned, the sums undercount the flow. The number of affected ledger updates is
+    /// reported in [`AddressFlowRecord::pruned_count`].
+    pub async fn get_address_flow(
+        &self,
+        address: &Address,
+        start_index: MilestoneIndex,
+        end_index: MilestoneIndex,
+    ) -> Result<AddressFlowRecord, Error> {
+        Ok(self
+            .aggregate(
+                [
+                    doc! { "$match": {
+                        "address": address,
+                        "_id.milestone_index": { "$gte": start_index, "$lte": end_index },
+                    } },
+                    doc! { "$lookup": {
+                        "from": OutputCollection::NAME,
+                        "localField": "_id.output_id",
+                        "foreignField": "_id",
+                        "as": "output",
+                    } },
+                    doc! { "$set": {
+                        "amount": { "$toDecimal": { "$arrayElemAt": [ "$output.output.amount", 0 ] } },
+                    } },
+                    doc! { "$group": {
+                        "_id": null,
+                        "created_count": { "$sum": { "$cond": [ "$_id.is_spent", 0, 1 ] } },
+                        "consumed_count": { "$sum": { "$cond": [ "$_id.is_spent", 1, 0 ] } },
+                        "pruned_count": { "$sum": { "$cond": [ { "$eq": [ { "$size": "$output" }, 0 ] }, 1, 0 ] } },
+                        "received": { "$sum": { "$cond": [ "$_id.is_spent", 0, "$amount" ] } },
+                        "sent": { "$sum": { "$cond": [ "$_id.is_spent", "$amount", 0 ] } },
+                    } },
+                    doc! { "$project": {
+                        "_id": 0,
+                        "created_count": 1,
+                        "consumed_count": 1,
+                        "pruned_count": 1,
+                        "received": { "$toString": "$received" },
+                        "sent": { "$toString": "$sent" },
+                    } },
+                ],
+                None,
+            )
+            .await?
+            .try_next()
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Finds the milestone index that bounds the first `count` ranges of `interval` milestones, between two milestone
+    /// indexes (inclusive), in which an address has ledger updates. The ranges are walked from `start_index` in the
+    /// given order, with one seek through the address index per range, so that a page can be matched without scanning
+    /// the whole history of the address.
+    ///
+    /// Returns `None` if there are fewer ranges with updates than that.
+    async fn get_page_end(
+        &self,
+        address: &Address,
+        (start_index, end_index): (MilestoneIndex, MilestoneIndex),
+        order: SortOrder,
+        interval: u32,
+        count: usize,
+    ) -> Result<Option<MilestoneIndex>, Error> {
+        #[derive(Deserialize)]
+        struct PageEndId {
+            milestone_index: MilestoneIndex,
+        }
+
+        #[derive(Deserialize)]
+        struct PageEndResult {
+            _id: PageEndId,
+        }
+
+        let (mut start_index, mut end_index) = (start_index.0, end_index.0);
+        let mut page_end = None;
+        for _ in 0..count {
+            if start_index > end_index {
+                return Ok(None);
+            }
+            let milestone_index = match self
+                .find_one::<PageEndResult>(
+                    doc! {
+                        "address": address,
+                        "_id.milestone_index": { "$gte": start_index, "$lte": end_index },
+                    },
+                    FindOneOptions::builder()
+                        .sort(match order {
+                            SortOrder::Newest => newest(),
+                            SortOrder::Oldest => oldest(),
+                        })
+                        .projection(doc! { "_id.milestone_index": 1 })
+                        .build(),
+                )
+                .await?
+            {
+                Some(res) => res._id.milestone_index.0,
+                None => return Ok(None),
+            };
+            let range_start = milestone_index - milestone_index % interval;
+            match order {
+                SortOrder::Newest => {
+                    page_end = Some(range_start.into());
+                    match range_start.checked_sub(1) {
+                        Some(index) => end_index = index,
+                        None => start_index = end_index.saturating_add(1),
+                    }
+                }
+                SortOrder::Oldest => {
+                    let range_end = range_start.saturating_add(interval - 1);
+                    page_end = Some(range_end.into());
+                    match range_end.checked_add(1) {
+                        Some(index) => start_index = index,
+                        None => end_index = 0,
+                    }
+                }
+            }
+        }
+        Ok(page_end)
+    }
+
+    /// Streams the tokens an address received and sent between two milestone indexes (inclusive), summed up per range
+    /// of `interval` milestones. Only the first `count` ranges in which the address received or sent tokens are
+    /// returned, sorted by their start.
+    ///
+    /// Adding the changes up to a range to the balance of the address before the start gives the amount of base
+    /// tokens it owned at the end of the range.
+    pub async fn get_address_balance_changes(
+        &self,
+        address: &Address,
+        interval: u32,
+        start_index: MilestoneIndex,
+        end_index: MilestoneIndex,
+        count: usize,
+    ) -> Result<impl Stream<Item = Result<BalanceChangeRecord, Error>>, Error> {
+        let end_index = self
+            .get_page_end(address, (start_index, end_index), SortOrder::Oldest, interval, count)
+            .await?
+            .map_or(end_index, |page_end| page_end.min(end_index));
+
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "address": address,
+                    "_id.milestone_index": { "$gte": start_index, "$lte": end_index },
+                } },
+                doc! { "$lookup": {
+                    "from": OutputCollection::NAME,
+                    "localField": "_id.output_id",
+                    "foreignField": "_id",
+                    "as": "output",
+                } },
+                doc! { "$set": {
+                    "amount": { "$toDecimal": { "$arrayElemAt": [ "$output.output.amount", 0 ] } },
+                } },
+                doc! { "$group": {
+                    "_id": { "$subtract": [
+                        "$_id.milestone_index",
+                        { "$mod": [ "$_id.milestone_index", interval as i64 ] },
+                    ] },
+                    "received": { "$sum": { "$cond": [ "$_id.is_spent", 0, "$amount" ] } },
+                    "sent": { "$sum": { "$cond": [ "$_id.is_spent", "$amount", 0 ] } },
+                } },
+                doc! { "$sort": { "_id": 1 } },
+                doc! { "$project": {
+                    "_id": 0,
+                    "start_index": "$_id",
+                    "received": { "$toString": "$received" },
+                    "sent": { "$toString": "$sent" },
+                } },
+            ],
+            None,
+        )
+        .await
+    }
+
+    /// Streams the transactions that created outputs for an address or consumed outputs of it, up to the given ledger
+    /// index. They are sorted by the milestone that included them and their transaction id.
+    pub async fn get_address_transactions(
+        &self,
+        address: &Address,
+        page_size: usize,
+        cursor: Option<(MilestoneIndex, TransactionId)>,
+        order: SortOrder,
+        ledger_index: MilestoneIndex,
+    ) -> Result<impl Stream<Item = Result<AddressTransactionRecord, Error>>, Error> {
+        let (sort, cmp1, cmp2) = match order {
+            SortOrder::Newest => (doc! { "at.milestone_index": -1, "transaction_id": -1 }, "$lt", "$lte"),
+            SortOrder::Oldest => (doc! { "at.milestone_index": 1, "transaction_id": 1 }, "$gt", "$gte"),
+        };
+        let (mut start_index, mut end_index) = match (order, cursor) {
+            (SortOrder::Newest, Some((milestone_index, _))) => (0.into(), milestone_index.min(ledger_index)),
+            (SortOrder::Oldest, Some((milestone_index, _))) => (milestone_index, ledger_index),
+            (_, None) => (0.into(), ledger_index),
+        };
+
+        // Every milestone in which the address has updates includes at least one of its transactions, except for the
+        // one in the cursor, so the page ends within as many milestones with updates.
+        let page_end = self
+            .get_page_end(
+                address,
+                (start_index, end_index),
+                order,
+                1,
+                page_size + usize::from(cursor.is_some()),
+            )
+            .await?;
+        match (order, page_end) {
+            (SortOrder::Newest, Some(page_end)) => start_index = page_end,
+            (SortOrder::Oldest, Some(page_end)) => end_index = page_end,
+            (_, None) => (),
+        }
+
+        let mut queries = vec![doc! { "at.milestone_index": { "$gte": start_index, "$lte": end_index } }];
+        if let Some((milestone_index, transaction_id)) = cursor {
+            queries.push(doc! { "$or": [
+                { "at.milestone_index": { cmp1: milestone_index } },
+                {
+                    "at.milestone_index": milestone_index,
+                    "transaction_id": { cmp2: transaction_id },
+                },
+            ] });
+        }
+
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "address": address,
+                    "_id.milestone_index": { "$gte": start_index, "$lte": end_index },
+                } },
+                doc! { "$lookup": {
+                    "from": OutputCollection::NAME,
+                    "localField": "_id.output_id",
+                    "foreignField": "_id",
+                    "as": "output",
+                } },
+                doc! { "$set": { "output": { "$arrayElemAt": [ "$output", 0 ] } } },
+                doc! { "$set": { "amount": { "$toDecimal": "$output.output.amount" } } },
+                // An output is created by the transaction in its id, and consumed by the one that spent it.
+                doc! { "$project": {
+                    "_id": 0,
+                    "transaction_id": { "$cond": [
+                        "$_id.is_spent",
+                        "$output.metadata.spent_metadata.transaction_id",
+                        "$_id.output_id.transaction_id",
+                    ] },
+                    "at": {
+                        "milestone_index": "$_id.milestone_index",
+                        "milestone_timestamp": "$milestone_timestamp",
+                    },
+                    "received": { "$cond": [ "$_id.is_spent", { "$toDecimal": 0 }, "$amount" ] },
+                    "sent": { "$cond": [ "$_id.is_spent", "$amount", { "$toDecimal": 0 } ] },
+                    "is_spent": "$_id.is_spent",
+                } },
+                doc! { "$match": { "$and": queries } },
+                doc! { "$group": {
+                    "_id": { "transaction_id": "$transaction_id", "milestone_index": "$at.milestone_index" },
+                    "at": { "$first": "$at" },
+                    "received": { "$sum": "$received" },
+                    "sent": { "$sum": "$sent" },
+                    "is_spent": { "$max": "$is_spent" },
+                } },
+                doc! { "$project": {
+                    "_id": 0,
+                    "transaction_id": "$_id.transaction_id",
+                    "at": 1,
+                    "received": { "$toString": "$received" },
+                    "sent": { "$toString": "$sent" },
+                    "is_spent": 1,
+                } },
+                doc! { "$sort": sort },
+                doc! { "$limit": page_size as i64 },
+            ],
+            None,
+        )
+        .await
+    }
+
+    /// Streams updates to the ledger for a given milestone index (sorted by [`OutputId`], then by direction).
+    ///
+    /// The cursor is the position of the first update to return.
     pub async fn get_ledger_updates_by_milestone(
         &self,
         milestone_index: MilestoneIndex,
         page_size: usize,
         cursor: Option<(OutputId, bool)>,
     ) -> Result<impl Stream<Item = Result<LedgerUpdateByMilestoneRecord, Error>>, Error> {
-        let (cmp1, cmp2) = ("$gt", "$gte");
-
         let mut queries = vec![doc! { "_id.milestone_index": milestone_index }];
 
         if let Some((output_id, is_spent)) = cursor {
-            let mut cursor_queries = vec![doc! { "_id.output_id": { cmp1: output_id } }];
-            cursor_queries.push(doc! {
-                "_id.output_id": output_id,
-                "_id.is_spent": { cmp2: is_spent }
-            });
-            queries.push(doc! { "$or": cursor_queries });
+            queries.push(doc! { "$or": [
+                { "_id.output_id": { "$gt": output_id } },
+                {
+                    "_id.output_id": output_id,
+                    "_id.is_spent": { "$gte": is_spent }
+                },
+            ] });
         }
 
+        // The sort has to follow the cursor, rather than the address index, or pages would skip and repeat updates.
         Ok(self
             .find::<LedgerUpdateDocument>(
                 doc! { "$and": queries },
-                FindOptions::builder().limit(page_size as i64).sort(oldest()).build(),
+                FindOptions::builder()
+                    .limit(page_size as i64)
+                    .sort(by_milestone())
+                    .build(),
             )
             .await?
             .map_ok(|doc| LedgerUpdateByMilestoneRecord {
@@ -230,3 +692,35 @@ impl LedgerUpdateCollection {
             }))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use mongodb::bson::from_document;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn address_flow() {
+        let flow: AddressFlowRecord = from_document(doc! {
+            "created_count": 3,
+            "consumed_count": 2,
+            "pruned_count": 1,
+            "received": "1000000",
+            "sent": "2500000",
+        })
+        .unwrap();
+        assert_eq!(
+            flow,
+            AddressFlowRecord {
+                created_count: 3,
+                consumed_count: 2,
+                pruned_count: 1,
+                received: 1_000_000,
+                sent: 2_500_000,
+            }
+        );
+        assert_eq!(flow.net(), -1_500_000);
+        assert_eq!(AddressFlowRecord::default().net(), 0);
+    }
+}
@@ -0,0 +1,310 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use futures::{Stream, TryStreamExt};
+use mongodb::{
+    bson::{self, doc, Document},
+    error::Error,
+    options::{AggregateOptions, FindOptions, UpdateOptions},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::OutputCollection;
+use crate::{
+    db::{
+        mongodb::{deadline::DeadlineExt, MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+
+/// The prefix of the collections holding materialized view results.
+pub const VIEW_COLLECTION_PREFIX: &str = "view_";
+
+/// The definition of a materialized view.
+///
+/// The pipeline is run against the `source` collection and its results are written to a dedicated collection. When
+/// the view is `incremental`, the pipeline is given the `$$from_index` and `$$to_index` variables and its results are
+/// merged into the existing view. Otherwise, the whole view is replaced on every refresh.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ViewDefinition {
+    /// The unique name of the view.
+    pub name: String,
+    /// The name of the collection the pipeline is run against.
+    pub source: String,
+    /// The aggregation pipeline that produces the view documents.
+    pub pipeline: Vec<Document>,
+    /// When the view should be refreshed.
+    pub refresh: RefreshPolicy,
+    /// Whether the results are merged into the view by `_id` instead of replacing it.
+    #[serde(default)]
+    pub incremental: bool,
+}
+
+impl ViewDefinition {
+    /// The name of the collection that holds the view results.
+    pub fn collection_name(&self) -> String {
+        format!("{VIEW_COLLECTION_PREFIX}{}", self.name)
+    }
+}
+
+/// Describes when a materialized view should be refreshed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshPolicy {
+    /// Refresh after the given number of milestones have been ingested.
+    Milestones(u32),
+    /// Refresh after the given amount of time has passed.
+    Interval(#[serde(with = "humantime_serde")] Duration),
+}
+
+/// Information about the last refresh of a view.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewRefresh {
+    /// The ledger index the view was refreshed at.
+    pub ledger_index: MilestoneIndex,
+    /// The time the view was refreshed at.
+    #[serde(with = "time::serde::timestamp")]
+    pub time: OffsetDateTime,
+}
+
+/// A registered view and its refresh state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ViewDocument {
+    #[serde(rename = "_id")]
+    name: String,
+    definition: ViewDefinition,
+    last_refresh: Option<ViewRefresh>,
+}
+
+/// A registered view.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewResult {
+    pub definition: ViewDefinition,
+    pub last_refresh: Option<ViewRefresh>,
+}
+
+impl ViewResult {
+    /// Whether the view should be refreshed at the given ledger index and time.
+    pub fn is_due(&self, ledger_index: MilestoneIndex, now: OffsetDateTime) -> bool {
+        match (self.last_refresh, self.definition.refresh) {
+            (None, _) => true,
+            (Some(last), RefreshPolicy::Milestones(milestones)) => ledger_index.0 >= last.ledger_index.0 + milestones,
+            (Some(last), RefreshPolicy::Interval(interval)) => {
+                ledger_index > last.ledger_index && now - last.time >= interval
+            }
+        }
+    }
+}
+
+/// A collection to store the definitions and state of materialized views.
+pub struct MaterializedViewCollection {
+    db: mongodb::Database,
+    collection: mongodb::Collection<ViewDocument>,
+}
+
+impl MongoDbCollection for MaterializedViewCollection {
+    const NAME: &'static str = "materialized_views";
+    type Document = ViewDocument;
+
+    fn instantiate(db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self {
+            db: db.db(),
+            collection,
+        }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl MaterializedViewCollection {
+    /// Registers a view, or updates its definition if it already exists. Changing the definition resets the view so
+    /// that it is rebuilt on the next refresh.
+    pub async fn register_view(&self, definition: &ViewDefinition) -> Result<(), Error> {
+        let existing = self
+            .find_one::<ViewDocument>(doc! { "_id": &definition.name }, None)
+            .await?;
+        if existing.as_ref().map(|doc| &doc.definition) != Some(definition) {
+            self.db
+                .collection::<Document>(&definition.collection_name())
+                .drop(None)
+                .await?;
+            self.update_one(
+                doc! { "_id": &definition.name },
+                doc! { "$set": {
+                    "definition": bson::to_bson(definition)?,
+                    "last_refresh": bson::Bson::Null,
+                } },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Gets all registered views.
+    pub async fn get_views(&self) -> Result<Vec<ViewResult>, Error> {
+        self.find::<ViewDocument>(doc! {}, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+            .await?
+            .map_ok(Into::into)
+            .try_collect()
+            .await
+    }
+
+    /// Gets a registered view by name.
+    pub async fn get_view(&self, name: &str) -> Result<Option<ViewResult>, Error> {
+        Ok(self
+            .find_one::<ViewDocument>(doc! { "_id": name }, None)
+            .await?
+            .map(Into::into))
+    }
+
+    /// Runs the pipeline of a view and stores the results.
+    pub async fn refresh_view(&self, view: &ViewResult, ledger_index: MilestoneIndex) -> Result<ViewRefresh, Error> {
+        let definition = &view.definition;
+        let from_index = view.last_refresh.map_or(0.into(), |last| last.ledger_index + 1);
+        let mut pipeline = definition.pipeline.clone();
+        pipeline.push(if definition.incremental {
+            doc! { "$merge": {
+                "into": definition.collection_name(),
+                "on": "_id",
+                "whenMatched": "replace",
+                "whenNotMatched": "insert",
+            } }
+        } else {
+            doc! { "$out": definition.collection_name() }
+        });
+        self.db
+            .collection::<Document>(&definition.source)
+            .aggregate(
+                pipeline,
+                Some(
+                    AggregateOptions::builder()
+                        .let_vars(doc! { "from_index": from_index, "to_index": ledger_index })
+                        .build(),
+                )
+                .with_deadline(),
+            )
+            .await?;
+
+        let refresh = ViewRefresh {
+            ledger_index,
+            time: OffsetDateTime::now_utc(),
+        };
+        self.update_one(
+            doc! { "_id": &definition.name },
+            doc! { "$set": { "last_refresh": bson::to_bson(&refresh)? } },
+            None,
+        )
+        .await?;
+        Ok(refresh)
+    }
+
+    /// Gets a page of the results of a view.
+    pub async fn get_view_results(
+        &self,
+        name: &str,
+        page_size: usize,
+        page: usize,
+    ) -> Result<impl Stream<Item = Result<Document, Error>>, Error> {
+        self.db
+            .collection::<Document>(&format!("{VIEW_COLLECTION_PREFIX}{name}"))
+            .find(
+                None,
+                Some(
+                    FindOptions::builder()
+                        .sort(doc! { "_id": 1 })
+                        .skip((page_size * page) as u64)
+                        .limit(page_size as i64)
+                        .build(),
+                )
+                .with_deadline(),
+            )
+            .await
+    }
+}
+
+impl From<ViewDocument> for ViewResult {
+    fn from(value: ViewDocument) -> Self {
+        Self {
+            definition: value.definition,
+            last_refresh: value.last_refresh,
+        }
+    }
+}
+
+/// Returns the views that are maintained by Chronicle out of the box.
+pub fn builtin_views() -> Vec<ViewDefinition> {
+    vec![ViewDefinition {
+        name: "unspent_outputs_by_kind".to_string(),
+        source: OutputCollection::NAME.to_string(),
+        pipeline: vec![
+            doc! { "$match": { "metadata.spent_metadata": null } },
+            doc! { "$group": {
+                "_id": "$output.kind",
+                "count": { "$sum": 1 },
+                "amount": { "$sum": { "$toDecimal": "$output.amount" } },
+            } },
+            doc! { "$project": {
+                "count": 1,
+                "amount": { "$toString": "$amount" },
+            } },
+        ],
+        refresh: RefreshPolicy::Interval(Duration::from_secs(10 * 60)),
+        incremental: false,
+    }]
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn view(refresh: RefreshPolicy, last_refresh: Option<ViewRefresh>) -> ViewResult {
+        ViewResult {
+            definition: ViewDefinition {
+                name: "test".to_string(),
+                source: "test".to_string(),
+                pipeline: Vec::new(),
+                refresh,
+                incremental: false,
+            },
+            last_refresh,
+        }
+    }
+
+    #[test]
+    fn refresh_due() {
+        let time = OffsetDateTime::now_utc();
+        let last = Some(ViewRefresh {
+            ledger_index: 10.into(),
+            time,
+        });
+
+        assert!(view(RefreshPolicy::Milestones(5), None).is_due(1.into(), time));
+        assert!(!view(RefreshPolicy::Milestones(5), last).is_due(14.into(), time));
+        assert!(view(RefreshPolicy::Milestones(5), last).is_due(15.into(), time));
+
+        let interval = RefreshPolicy::Interval(Duration::from_secs(60));
+        assert!(!view(interval, last).is_due(11.into(), time + Duration::from_secs(30)));
+        assert!(!view(interval, last).is_due(10.into(), time + Duration::from_secs(90)));
+        assert!(view(interval, last).is_due(11.into(), time + Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn definition_round_trip() {
+        for definition in builtin_views() {
+            let bson = bson::to_document(&definition).unwrap();
+            assert_eq!(bson::from_document::<ViewDefinition>(bson).unwrap(), definition);
+            let json = serde_json::to_string(&definition).unwrap();
+            assert_eq!(serde_json::from_str::<ViewDefinition>(&json).unwrap(), definition);
+        }
+    }
+}
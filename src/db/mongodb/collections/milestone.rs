@@ -8,7 +8,7 @@ use mongodb::{
     bson::doc,
     error::Error,
     options::{FindOneOptions, FindOptions, IndexOptions},
-    IndexModel,
+    ClientSession, IndexModel,
 };
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
@@ -38,6 +38,54 @@ pub struct MilestoneDocument {
     at: MilestoneIndexTimestamp,
     /// The milestone's payload.
     payload: MilestonePayload,
+    /// The size of the data stored for the milestone, which is unknown for milestones synced by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data_size: Option<MilestoneDataSize>,
+}
+
+impl MilestoneDocument {
+    fn new(
+        milestone_id: MilestoneId,
+        milestone_index: MilestoneIndex,
+        milestone_timestamp: MilestoneTimestamp,
+        payload: MilestonePayload,
+        data_size: MilestoneDataSize,
+    ) -> Self {
+        Self {
+            milestone_id,
+            at: MilestoneIndexTimestamp {
+                milestone_index,
+                milestone_timestamp,
+            },
+            payload,
+            data_size: Some(data_size),
+        }
+    }
+}
+
+/// The size of the raw data that was stored for a milestone.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MilestoneDataSize {
+    /// The number of blocks referenced by the milestone.
+    pub block_count: u64,
+    /// The total size of the raw blocks.
+    pub block_bytes: u64,
+    /// The number of outputs created by the milestone.
+    pub output_count: u64,
+    /// The total packed size of the created outputs.
+    pub output_bytes: u64,
+}
+
+/// The size of the data stored for the milestones within a time bucket.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct DataSizeBucket {
+    /// The timestamp at which the bucket starts.
+    pub start_timestamp: MilestoneTimestamp,
+    /// The number of milestones whose data size is known.
+    pub milestone_count: u64,
+    /// The summed data size of these milestones.
+    #[serde(flatten)]
+    pub data_size: MilestoneDataSize,
 }
 
 /// The stardust milestones collection.
@@ -144,6 +192,7 @@ impl MilestoneCollection {
                      milestone_id,
                      at,
                      payload,
+                     ..
                  }| (milestone_id, at, payload),
             )
             .try_next()
@@ -193,28 +242,107 @@ impl MilestoneCollection {
     }
 
     /// Inserts the information of a milestone into the database.
-    #[instrument(skip(self, milestone_id, milestone_timestamp, payload), err, level = "trace")]
     pub async fn insert_milestone(
         &self,
         milestone_id: MilestoneId,
         milestone_index: MilestoneIndex,
         milestone_timestamp: MilestoneTimestamp,
         payload: MilestonePayload,
+        data_size: MilestoneDataSize,
     ) -> Result<(), Error> {
-        let milestone_document = MilestoneDocument {
-            at: MilestoneIndexTimestamp {
-                milestone_index,
-                milestone_timestamp,
-            },
+        self.insert_milestone_with_session(
             milestone_id,
+            milestone_index,
+            milestone_timestamp,
             payload,
-        };
+            data_size,
+            None,
+        )
+        .await
+    }
 
-        self.insert_one(milestone_document, None).await?;
+    /// Inserts the information of a milestone into the database, as part of the transaction of the session if one is
+    /// given.
+    #[instrument(
+        skip(self, milestone_id, milestone_timestamp, payload, data_size, session),
+        err,
+        level = "trace"
+    )]
+    pub async fn insert_milestone_with_session(
+        &self,
+        milestone_id: MilestoneId,
+        milestone_index: MilestoneIndex,
+        milestone_timestamp: MilestoneTimestamp,
+        payload: MilestonePayload,
+        data_size: MilestoneDataSize,
+        session: Option<&mut ClientSession>,
+    ) -> Result<(), Error> {
+        let milestone_document =
+            MilestoneDocument::new(milestone_id, milestone_index, milestone_timestamp, payload, data_size);
+
+        self.insert_one_with_session(milestone_document, None, session).await?;
 
         Ok(())
     }
 
+    /// Gets the size of the data stored for a milestone, if it is known.
+    pub async fn get_milestone_data_size(&self, index: MilestoneIndex) -> Result<Option<MilestoneDataSize>, Error> {
+        #[derive(Deserialize)]
+        struct DataSizeResult {
+            data_size: Option<MilestoneDataSize>,
+        }
+
+        Ok(self
+            .find_one::<DataSizeResult>(
+                doc! { "at.milestone_index": index },
+                FindOneOptions::builder().projection(doc! { "data_size": 1 }).build(),
+            )
+            .await?
+            .and_then(|res| res.data_size))
+    }
+
+    /// Gets the size of the data stored for the milestones between two timestamps, grouped into buckets of the given
+    /// size. Milestones synced before the size was recorded are not counted, and buckets without any counted
+    /// milestones are omitted.
+    pub async fn get_data_size(
+        &self,
+        start_timestamp: MilestoneTimestamp,
+        end_timestamp: MilestoneTimestamp,
+        bucket_seconds: u32,
+    ) -> Result<impl Stream<Item = Result<DataSizeBucket, Error>>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "at.milestone_timestamp": { "$gte": start_timestamp, "$lt": end_timestamp },
+                    "data_size": { "$exists": true },
+                } },
+                doc! { "$group": {
+                    "_id": { "$subtract": [
+                        "$at.milestone_timestamp",
+                        { "$mod": [ "$at.milestone_timestamp", bucket_seconds ] }
+                    ] },
+                    "milestone_count": { "$sum": 1 },
+                    "block_count": { "$sum": "$data_size.block_count" },
+                    "block_bytes": { "$sum": "$data_size.block_bytes" },
+                    "output_count": { "$sum": "$data_size.output_count" },
+                    "output_bytes": { "$sum": "$data_size.output_bytes" },
+                } },
+                doc! { "$sort": { "_id": 1 } },
+                doc! { "$project": {
+                    "_id": 0,
+                    "start_timestamp": "$_id",
+                    "milestone_count": 1,
+                    "block_count": 1,
+                    "block_bytes": 1,
+                    "output_count": 1,
+                    "output_bytes": 1,
+                } },
+            ],
+            None,
+        )
+        .await
+    }
+
     /// Find the starting milestone.
     pub async fn find_first_milestone(
         &self,
@@ -238,7 +366,7 @@ impl MilestoneCollection {
         .await
     }
 
-    /// Find the end milestone.
+    /// Find the end milestone, which is the last milestone issued at or before the timestamp.
     pub async fn find_last_milestone(
         &self,
         end_timestamp: MilestoneTimestamp,
@@ -293,6 +421,30 @@ impl MilestoneCollection {
         Ok(self.get_newest_milestone().await?.map(|ts| ts.milestone_index))
     }
 
+    /// Streams the index and timestamp of milestones as they are inserted by any writer of the database.
+    ///
+    /// This uses a change stream, which is only available if the database is a replica set.
+    pub async fn watch_inserted_milestones(
+        &self,
+    ) -> Result<impl Stream<Item = Result<MilestoneIndexTimestamp, Error>>, Error> {
+        #[derive(Deserialize)]
+        struct InsertedMilestone {
+            at: MilestoneIndexTimestamp,
+        }
+
+        Ok(self
+            .with_type::<InsertedMilestone>()
+            .watch(
+                [
+                    doc! { "$match": { "operationType": "insert" } },
+                    doc! { "$project": { "operationType": 1, "fullDocument.at": 1 } },
+                ],
+                None,
+            )
+            .await?
+            .try_filter_map(|event| async move { Ok(event.full_document.map(|doc| doc.at)) }))
+    }
+
     /// Streams all available receipt milestone options together with their corresponding `MilestoneIndex`.
     pub async fn get_all_receipts(
         &self,
@@ -1,19 +1,43 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+/// Module containing the address filter collection.
+mod address_filter;
+/// Module containing the API audit log collection.
+mod api_audit_log;
 mod application_state;
 /// Module containing the Block document model.
 mod block;
 /// Module containing the node configuration collection.
 mod configuration_update;
+/// Module containing the ledger snapshot digests collection.
+mod ledger_snapshot;
 /// Module containing the LedgerUpdate model.
 mod ledger_update;
+/// Module containing the materialized view framework.
+mod materialized_view;
 /// Module containing the Milestone document model.
 mod milestone;
+/// Module containing the node health collection.
+mod node_health;
 /// Module containing Block outputs.
 mod outputs;
+/// Module containing the block parents inverse index.
+mod parents;
+/// Module containing the pending transactions collection.
+mod pending_transaction;
 /// Module containing the protocol parameters collection.
 mod protocol_update;
+/// Module containing the query result cache collection.
+mod query_cache;
+/// Module containing the spam flags collection.
+mod spam_flag;
+/// Module containing the storage samples collection.
+mod storage_sample;
+/// Module containing the sync reports collection.
+mod sync_report;
+/// Module containing the native token distribution collection.
+mod token_distribution;
 /// Module containing the treasury model.
 mod treasury;
 
@@ -22,17 +46,42 @@ use std::str::FromStr;
 use thiserror::Error;
 
 pub use self::{
-    application_state::{ApplicationStateCollection, MigrationVersion},
-    block::BlockCollection,
+    address_filter::{AddressFilterChunkDocument, AddressFilterCollection},
+    api_audit_log::{ApiAuditLogCollection, ApiAuditRecord},
+    application_state::{AppVersion, ApplicationStateCollection, MigrationVersion},
+    block::{BlockCollection, BlockDocument, CreatedOutputResult, OrphanedBlocksResult},
     configuration_update::ConfigurationUpdateCollection,
-    ledger_update::{LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, LedgerUpdateCollection},
-    milestone::{MilestoneCollection, MilestoneResult, SyncData},
+    ledger_snapshot::{LedgerSnapshotCollection, LedgerSnapshotResult},
+    ledger_update::{
+        AddressActivityRecord, AddressFlowRecord, AddressTransactionRecord, BalanceChangeRecord,
+        LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, LedgerUpdateCollection,
+    },
+    materialized_view::{
+        builtin_views, MaterializedViewCollection, RefreshPolicy, ViewDefinition, ViewRefresh, ViewResult,
+        VIEW_COLLECTION_PREFIX,
+    },
+    milestone::{DataSizeBucket, MilestoneCollection, MilestoneDataSize, MilestoneResult, SyncData},
+    node_health::{NodeHealthCollection, NodeHealthSample},
     outputs::{
-        AddressStat, AliasOutputsQuery, BasicOutputsQuery, DistributionStat, FoundryOutputsQuery, IndexedId,
-        NftOutputsQuery, OutputCollection, OutputMetadataResult, OutputWithMetadataResult, OutputsResult,
-        UtxoChangesResult,
+        AddressActivityByKind, AddressActivitySpan, AddressStat, AliasOutputsQuery,
+        AmountOrder, BasicOutputsQuery, DailyAddressActivity, DistributionStat, EntityRelation,
+        EntityRelationship, FoundryOutputsQuery, IndexedId, IndexedOutputsExplanation, IndexedOutputsPosition,
+        IndexedOutputsSort, IndexedOutputsSortKey, LedgerDigest, NftOutputsQuery, OutputCollection,
+        OutputHistoryRecord, OutputMetadataResult, OutputWithMetadataResult, OutputsQuery, OutputsResult,
+        ParseAmountOrderError, ParseSortKeyError, StorageDepositReturnOutputsQuery, StorageDepositReturnTotals,
+        TagStat, TimelockedValueBucket, TransferVolumeBucket, UnspentOutputDigest, UtxoChangesResult,
+        AMOUNT_DETAILS_VERSION, OUTPUT_DETAILS_VERSION, TIMELOCK_HORIZONS, UNSPENT_OUTPUT_DIGEST_BUCKETS,
+    },
+    parents::ParentsCollection,
+    pending_transaction::{PendingTransactionCollection, PendingTransactionResult},
+    protocol_update::{ProtocolPeriod, ProtocolUpdateCollection},
+    query_cache::{QueryCacheCollection, QueryCacheDocument},
+    spam_flag::{SpamFlagCollection, SpamFlagDocument, SpamFlags},
+    storage_sample::{
+        CollectionFragmentation, CollectionGrowth, CollectionStorage, StorageSample, StorageSampleCollection,
     },
-    protocol_update::ProtocolUpdateCollection,
+    sync_report::{CollectionWrites, SyncReport, SyncReportCollection, SyncReportPhases},
+    token_distribution::{NativeTokenDistribution, TokenDistributionCollection, TokenDistributionResult, TOP_HOLDERS},
     treasury::{TreasuryCollection, TreasuryResult},
 };
 use crate::model::utxo::{AliasOutput, BasicOutput, FoundryOutput, NftOutput, Output};
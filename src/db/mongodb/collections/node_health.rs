@@ -0,0 +1,146 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use futures::Stream;
+use mongodb::{
+    bson::{doc, DateTime},
+    error::Error,
+    options::FindOptions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+
+/// The status of the connected node at a point in time.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeHealthSample {
+    /// The time the sample was taken.
+    #[serde(rename = "_id")]
+    pub sampled_at: DateTime,
+    /// Whether the node reported itself as healthy.
+    pub is_healthy: bool,
+    /// Whether the node reported itself as synced.
+    pub is_synced: bool,
+    /// Whether the node reported itself as almost synced.
+    pub is_almost_synced: bool,
+    /// The latest milestone seen by the node.
+    pub latest_milestone_index: MilestoneIndex,
+    /// The latest milestone confirmed by the node.
+    pub confirmed_milestone_index: MilestoneIndex,
+    /// The newest milestone Chronicle had synced at the time, if any.
+    pub synced_index: Option<MilestoneIndex>,
+    /// How long the node took to report its status, in milliseconds.
+    pub latency: u64,
+    /// The number of non-lazy tips in the tip pool of the node, if it reported its tip metrics.
+    #[serde(default)]
+    pub non_lazy_tips: Option<u32>,
+    /// The number of semi-lazy tips in the tip pool of the node, if it reported its tip metrics.
+    #[serde(default)]
+    pub semi_lazy_tips: Option<u32>,
+    /// The number of non-lazy tips the node selected when asked for tips, if it answered.
+    #[serde(default)]
+    pub selected_tips: Option<u32>,
+}
+
+impl NodeHealthSample {
+    /// The number of confirmed milestones Chronicle was behind the node, if it had synced any.
+    pub fn sync_lag(&self) -> Option<u32> {
+        self.synced_index
+            .map(|synced_index| self.confirmed_milestone_index.0.saturating_sub(synced_index.0))
+    }
+}
+
+/// The node health collection, which holds the periodically sampled status of the connected node.
+pub struct NodeHealthCollection {
+    collection: mongodb::Collection<NodeHealthSample>,
+}
+
+impl MongoDbCollection for NodeHealthCollection {
+    const NAME: &'static str = "node_health";
+    type Document = NodeHealthSample;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl NodeHealthCollection {
+    /// Inserts a sample.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn insert_sample(&self, sample: &NodeHealthSample) -> Result<(), Error> {
+        self.insert_one::<NodeHealthSample>(sample, None).await?;
+        Ok(())
+    }
+
+    /// Streams the samples taken between two times (inclusive), oldest first.
+    pub async fn get_samples(
+        &self,
+        start: DateTime,
+        end: DateTime,
+        limit: usize,
+    ) -> Result<impl Stream<Item = Result<NodeHealthSample, Error>>, Error> {
+        self.find::<NodeHealthSample>(
+            doc! { "_id": { "$gte": start, "$lte": end } },
+            FindOptions::builder()
+                .sort(doc! { "_id": 1 })
+                .limit(limit as i64)
+                .build(),
+        )
+        .await
+    }
+
+    /// Removes the samples that are older than the retention period at the given time.
+    pub async fn delete_samples_older_than(&self, time: DateTime, retention: Duration) -> Result<(), Error> {
+        let cutoff = DateTime::from_millis(
+            time.timestamp_millis()
+                .saturating_sub(retention.as_millis().try_into().unwrap_or(i64::MAX)),
+        );
+        self.collection
+            .delete_many(doc! { "_id": { "$lt": cutoff } }, None)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn sync_lag() {
+        let mut sample = NodeHealthSample {
+            sampled_at: DateTime::from_millis(0),
+            is_healthy: true,
+            is_synced: true,
+            is_almost_synced: true,
+            latest_milestone_index: 105.into(),
+            confirmed_milestone_index: 100.into(),
+            synced_index: Some(90.into()),
+            latency: 3,
+            non_lazy_tips: Some(8),
+            semi_lazy_tips: Some(2),
+            selected_tips: Some(8),
+        };
+        assert_eq!(sample.sync_lag(), Some(10));
+        // Chronicle may have synced a milestone before the sample of the node status arrives.
+        sample.synced_index = Some(101.into());
+        assert_eq!(sample.sync_lag(), Some(0));
+        sample.synced_index = None;
+        assert_eq!(sample.sync_lag(), None);
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::{Stream, StreamExt, TryStreamExt};
+use mongodb::{
+    bson::doc,
+    error::Error,
+    options::{AggregateOptions, Hint},
+};
+use serde::Deserialize;
+
+use super::OutputCollection;
+use crate::{
+    db::mongodb::MongoDbCollectionExt,
+    model::{tangle::MilestoneTimestamp, utxo::Address},
+};
+
+/// The index that every address export is read from.
+const OWNING_ADDRESS_INDEX: &str = "output_owning_address_index";
+
+/// The first and last time an address owned an output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct AddressActivitySpan {
+    pub address: Address,
+    /// When the first output of the address was created.
+    pub first_activity: MilestoneTimestamp,
+    /// When an output of the address was last created or spent.
+    pub last_activity: MilestoneTimestamp,
+}
+
+#[derive(Deserialize)]
+struct OwnedOutputResult {
+    address: Address,
+    booked: MilestoneTimestamp,
+    spent: Option<MilestoneTimestamp>,
+}
+
+impl From<OwnedOutputResult> for AddressActivitySpan {
+    fn from(value: OwnedOutputResult) -> Self {
+        Self {
+            address: value.address,
+            first_activity: value.booked,
+            last_activity: value.spent.unwrap_or(value.booked),
+        }
+    }
+}
+
+impl OutputCollection {
+    /// Streams every address that ever owned an output, each exactly once and in no particular order.
+    ///
+    /// The addresses are read with a distinct scan of the owning address index, so no output is loaded.
+    pub async fn get_distinct_addresses(&self) -> Result<impl Stream<Item = Result<Address, Error>>, Error> {
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(rename = "_id")]
+            address: Address,
+        }
+
+        Ok(self
+            .aggregate(
+                [
+                    doc! { "$match": { "details.address": { "$exists": true } } },
+                    doc! { "$sort": { "details.address": 1 } },
+                    doc! { "$group": { "_id": "$details.address" } },
+                ],
+                AggregateOptions::builder()
+                    .hint(Hint::Name(OWNING_ADDRESS_INDEX.to_string()))
+                    .build(),
+            )
+            .await?
+            .map_ok(|Res { address }| address))
+    }
+
+    /// Streams the activity span of every address that ever owned an output, in the order of the owning address
+    /// index.
+    ///
+    /// Unlike [`get_distinct_addresses`](Self::get_distinct_addresses), this walks every owned output, but it does so
+    /// in index order, so the spans are merged as they are streamed instead of being grouped by the server.
+    pub async fn get_address_activity_spans(
+        &self,
+    ) -> Result<impl Stream<Item = Result<AddressActivitySpan, Error>>, Error> {
+        let outputs = self
+            .aggregate::<OwnedOutputResult>(
+                [
+                    doc! { "$match": { "details.address": { "$exists": true } } },
+                    doc! { "$sort": { "details.address": 1 } },
+                    doc! { "$project": {
+                        "_id": 0,
+                        "address": "$details.address",
+                        "booked": "$metadata.booked.milestone_timestamp",
+                        "spent": "$metadata.spent_metadata.spent.milestone_timestamp",
+                    } },
+                ],
+                AggregateOptions::builder()
+                    .hint(Hint::Name(OWNING_ADDRESS_INDEX.to_string()))
+                    .build(),
+            )
+            .await?
+            .map_ok(AddressActivitySpan::from);
+        Ok(merge_activity_spans(outputs))
+    }
+}
+
+/// Merges the consecutive spans of the same address, which relies on the spans being sorted by address.
+fn merge_activity_spans<E>(
+    spans: impl Stream<Item = Result<AddressActivitySpan, E>>,
+) -> impl Stream<Item = Result<AddressActivitySpan, E>> {
+    futures::stream::unfold(
+        (Box::pin(spans.fuse()), None::<AddressActivitySpan>),
+        |(mut spans, mut current)| async move {
+            loop {
+                match spans.next().await {
+                    Some(Ok(span)) => match &mut current {
+                        Some(current) if current.address == span.address => {
+                            current.first_activity = current.first_activity.min(span.first_activity);
+                            current.last_activity = current.last_activity.max(span.last_activity);
+                        }
+                        _ => {
+                            if let Some(done) = current.replace(span) {
+                                return Some((Ok(done), (spans, current)));
+                            }
+                        }
+                    },
+                    Some(Err(e)) => return Some((Err(e), (spans, current))),
+                    None => return current.take().map(|done| (Ok(done), (spans, None))),
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::model::utxo::Ed25519Address;
+
+    fn span(address: u8, first: u32, last: u32) -> AddressActivitySpan {
+        AddressActivitySpan {
+            address: Address::Ed25519(Ed25519Address([address; 32])),
+            first_activity: first.into(),
+            last_activity: last.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_consecutive_spans() {
+        let spans = futures::stream::iter(
+            [
+                span(1, 10, 20),
+                span(1, 5, 15),
+                span(2, 30, 30),
+                span(3, 40, 50),
+                span(3, 45, 60),
+            ]
+            .map(Ok::<_, ()>),
+        );
+        assert_eq!(
+            merge_activity_spans(spans).try_collect::<Vec<_>>().await,
+            Ok(vec![span(1, 5, 20), span(2, 30, 30), span(3, 40, 60)])
+        );
+        assert_eq!(
+            merge_activity_spans(futures::stream::empty::<Result<_, ()>>())
+                .try_collect::<Vec<_>>()
+                .await,
+            Ok(vec![])
+        );
+    }
+}
@@ -0,0 +1,253 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crypto::hashes::{blake2b::Blake2b256, Digest};
+use futures::{Stream, TryStreamExt};
+use mongodb::{
+    bson::{self, doc},
+    error::Error,
+    options::FindOptions,
+};
+use serde::{Deserialize, Serialize};
+
+use super::OutputCollection;
+use crate::{
+    db::mongodb::MongoDbCollectionExt,
+    model::{
+        bytify,
+        payload::TransactionId,
+        stringify,
+        tangle::MilestoneIndex,
+        utxo::{OutputId, OutputIndex, TokenAmount},
+    },
+};
+
+/// The number of buckets the unspent output set is split into, one per value of the first byte of the output id.
+pub const UNSPENT_OUTPUT_DIGEST_BUCKETS: usize = 256;
+
+/// A digest over the unspent outputs whose id starts with the given byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnspentOutputDigest {
+    /// The first byte of the output ids in this bucket.
+    pub prefix: u8,
+    /// The number of unspent outputs in this bucket.
+    pub count: usize,
+    /// The BLAKE2b-256 hash of the ids of the unspent outputs in this bucket, in ascending order.
+    pub digest: [u8; 32],
+}
+
+/// A digest over the whole unspent output set at a ledger index.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerDigest {
+    /// The number of unspent outputs.
+    pub output_count: usize,
+    /// The sum of the amounts of the unspent outputs.
+    #[serde(with = "stringify")]
+    pub total_amount: u64,
+    /// The BLAKE2b-256 hash of the [`UnspentOutputDigest`]s of all buckets in prefix order, so that a mismatch can be
+    /// narrowed down to the differing buckets.
+    #[serde(with = "bytify")]
+    pub digest: [u8; 32],
+}
+
+impl LedgerDigest {
+    fn from_buckets(buckets: &[UnspentOutputDigest], total_amount: u64) -> Self {
+        let mut hasher = Blake2b256::new();
+        for bucket in buckets {
+            hasher.update(bucket.digest);
+        }
+        Self {
+            output_count: buckets.iter().map(|bucket| bucket.count).sum(),
+            total_amount,
+            digest: hasher.finalize().into(),
+        }
+    }
+}
+
+/// Builds the bucket digests from output ids that arrive in ascending order.
+struct UnspentOutputDigester {
+    buckets: Vec<(usize, Blake2b256)>,
+}
+
+impl UnspentOutputDigester {
+    fn new() -> Self {
+        Self {
+            buckets: (0..UNSPENT_OUTPUT_DIGEST_BUCKETS)
+                .map(|_| (0, Blake2b256::new()))
+                .collect(),
+        }
+    }
+
+    fn update(&mut self, output_id: &OutputId) {
+        let (count, hasher) = &mut self.buckets[output_id.transaction_id.0[0] as usize];
+        *count += 1;
+        hasher.update(output_id.transaction_id.0);
+        hasher.update(output_id.index.to_le_bytes());
+    }
+
+    fn finalize(self) -> Vec<UnspentOutputDigest> {
+        self.buckets
+            .into_iter()
+            .enumerate()
+            .map(|(prefix, (count, hasher))| UnspentOutputDigest {
+                prefix: prefix as u8,
+                count,
+                digest: hasher.finalize().into(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct OutputIdResult {
+    #[serde(rename = "_id")]
+    output_id: OutputId,
+}
+
+#[derive(Deserialize)]
+struct OutputAmountResult {
+    #[serde(rename = "_id")]
+    output_id: OutputId,
+    amount: TokenAmount,
+}
+
+impl OutputCollection {
+    /// Streams the ids of the outputs that were unspent at the given ledger index in ascending order, optionally
+    /// restricted to the ids that start with the given byte.
+    pub async fn get_unspent_output_ids(
+        &self,
+        ledger_index: MilestoneIndex,
+        prefix: Option<u8>,
+    ) -> Result<impl Stream<Item = Result<OutputId, Error>>, Error> {
+        let mut filter = doc! {
+            "metadata.booked.milestone_index": { "$lte": ledger_index },
+            "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+        };
+        if let Some(prefix) = prefix {
+            let bound = |byte: u8| {
+                let mut bytes = [0; TransactionId::LENGTH];
+                bytes[0] = byte;
+                bson::Bson::from(OutputId {
+                    transaction_id: TransactionId(bytes),
+                    index: OutputIndex::MIN,
+                })
+            };
+            let mut range = doc! { "$gte": bound(prefix) };
+            if let Some(next) = prefix.checked_add(1) {
+                range.insert("$lt", bound(next));
+            }
+            filter.insert("_id", range);
+        }
+        Ok(self
+            .find::<OutputIdResult>(
+                filter,
+                FindOptions::builder()
+                    .sort(doc! { "_id": 1 })
+                    .projection(doc! { "_id": 1 })
+                    .build(),
+            )
+            .await?
+            .map_ok(|res| res.output_id))
+    }
+
+    /// Computes the digest of a single bucket of the unspent output set at the given ledger index.
+    pub async fn get_unspent_output_digest(
+        &self,
+        ledger_index: MilestoneIndex,
+        prefix: u8,
+    ) -> Result<UnspentOutputDigest, Error> {
+        let mut digester = UnspentOutputDigester::new();
+        let mut output_ids = self.get_unspent_output_ids(ledger_index, Some(prefix)).await?;
+        while let Some(output_id) = output_ids.try_next().await? {
+            digester.update(&output_id);
+        }
+        Ok(digester.finalize().swap_remove(prefix as usize))
+    }
+
+    /// Computes a digest for each bucket of the unspent output set at the given ledger index, which allows two
+    /// databases to be compared without transferring every output id.
+    pub async fn get_unspent_output_digests(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<UnspentOutputDigest>, Error> {
+        let mut digester = UnspentOutputDigester::new();
+        let mut output_ids = self.get_unspent_output_ids(ledger_index, None).await?;
+        while let Some(output_id) = output_ids.try_next().await? {
+            digester.update(&output_id);
+        }
+        Ok(digester.finalize())
+    }
+
+    /// Computes the digest of the whole unspent output set at the given ledger index, along with the number and total
+    /// amount of the unspent outputs.
+    pub async fn get_ledger_digest(&self, ledger_index: MilestoneIndex) -> Result<LedgerDigest, Error> {
+        let mut digester = UnspentOutputDigester::new();
+        let mut total_amount = 0u64;
+        let mut outputs = self
+            .find::<OutputAmountResult>(
+                doc! {
+                    "metadata.booked.milestone_index": { "$lte": ledger_index },
+                    "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                },
+                FindOptions::builder()
+                    .sort(doc! { "_id": 1 })
+                    .projection(doc! { "_id": 1, "amount": "$output.amount" })
+                    .build(),
+            )
+            .await?;
+        while let Some(output) = outputs.try_next().await? {
+            digester.update(&output.output_id);
+            total_amount = total_amount.saturating_add(output.amount.0);
+        }
+        Ok(LedgerDigest::from_buckets(&digester.finalize(), total_amount))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn output_id(first_byte: u8, index: OutputIndex) -> OutputId {
+        let mut bytes = [7; TransactionId::LENGTH];
+        bytes[0] = first_byte;
+        OutputId {
+            transaction_id: TransactionId(bytes),
+            index,
+        }
+    }
+
+    fn digests(output_ids: &[OutputId]) -> Vec<UnspentOutputDigest> {
+        let mut digester = UnspentOutputDigester::new();
+        for output_id in output_ids {
+            digester.update(output_id);
+        }
+        digester.finalize()
+    }
+
+    #[test]
+    fn differences_are_confined_to_their_bucket() {
+        let all = [output_id(0, 0), output_id(0, 1), output_id(0xab, 0), output_id(0xff, 3)];
+        let expected = digests(&all);
+        assert_eq!(expected.len(), UNSPENT_OUTPUT_DIGEST_BUCKETS);
+        assert_eq!(expected, digests(&all));
+        assert_eq!(expected[0].count, 2);
+        assert_eq!(expected[0xab].count, 1);
+        assert_eq!(expected[0xff].count, 1);
+
+        let missing = digests(&[all[0], all[2], all[3]]);
+        let differing = expected
+            .iter()
+            .zip(&missing)
+            .filter(|(a, b)| a != b)
+            .map(|(a, _)| a.prefix)
+            .collect::<Vec<_>>();
+        assert_eq!(differing, vec![0]);
+
+        let ledger = LedgerDigest::from_buckets(&expected, 100);
+        assert_eq!(ledger.output_count, all.len());
+        assert_eq!(ledger, LedgerDigest::from_buckets(&digests(&all), 100));
+        assert_ne!(ledger.digest, LedgerDigest::from_buckets(&missing, 100).digest);
+    }
+}
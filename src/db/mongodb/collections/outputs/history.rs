@@ -0,0 +1,116 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::{Stream, TryStreamExt};
+use mongodb::{bson::doc, error::Error, options::FindOptions};
+use serde::Deserialize;
+
+use super::{IndexedId, OutputCollection};
+use crate::{
+    db::mongodb::{collections::SortOrder, MongoDbCollectionExt},
+    model::{
+        metadata::{OutputMetadata, SpentMetadata},
+        stringify,
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+        utxo::{Address, OutputId},
+        BlockId,
+    },
+};
+
+/// An output in the chain of outputs that represent an alias, foundry or NFT over its lifetime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct OutputHistoryRecord {
+    pub output_id: OutputId,
+    pub block_id: BlockId,
+    pub booked: MilestoneIndexTimestamp,
+    pub spent_metadata: Option<SpentMetadata>,
+}
+
+#[derive(Deserialize)]
+struct OutputHistoryResult {
+    #[serde(rename = "_id")]
+    output_id: OutputId,
+    metadata: OutputMetadata,
+}
+
+impl OutputCollection {
+    /// Gets the outputs that represented an alias, foundry or NFT over its lifetime, one per state transition. They
+    /// are sorted by the milestone that booked them and their output id.
+    pub async fn get_indexed_output_history(
+        &self,
+        id: impl Into<IndexedId>,
+        page_size: usize,
+        cursor: Option<(MilestoneIndex, OutputId)>,
+        order: SortOrder,
+    ) -> Result<impl Stream<Item = Result<OutputHistoryRecord, Error>>, Error> {
+        let id = id.into();
+        let (sort, cmp1, cmp2) = match order {
+            SortOrder::Newest => (doc! { "metadata.booked.milestone_index": -1, "_id": -1 }, "$lt", "$lte"),
+            SortOrder::Oldest => (doc! { "metadata.booked.milestone_index": 1, "_id": 1 }, "$gt", "$gte"),
+        };
+
+        let mut queries = vec![doc! { "output.kind": id.kind(), "details.indexed_id": id }];
+        if let Some((milestone_index, output_id)) = cursor {
+            queries.push(doc! { "$or": [
+                { "metadata.booked.milestone_index": { cmp1: milestone_index } },
+                {
+                    "metadata.booked.milestone_index": milestone_index,
+                    "_id": { cmp2: output_id },
+                },
+            ] });
+        }
+
+        Ok(self
+            .find::<OutputHistoryResult>(
+                doc! { "$and": queries },
+                FindOptions::builder()
+                    .projection(doc! { "_id": 1, "metadata": 1 })
+                    .sort(sort)
+                    .limit(page_size as i64)
+                    .build(),
+            )
+            .await?
+            .map_ok(|res| OutputHistoryRecord {
+                output_id: res.output_id,
+                block_id: res.metadata.block_id,
+                booked: res.metadata.booked,
+                spent_metadata: res.metadata.spent_metadata,
+            }))
+    }
+
+    /// Sums up the base tokens in the outputs an address owned at the given milestone index, which is the balance
+    /// that the balance changes of the address add up to.
+    pub async fn get_address_amount_at(
+        &self,
+        address: &Address,
+        milestone_index: MilestoneIndex,
+    ) -> Result<u64, Error> {
+        #[derive(Deserialize)]
+        struct AmountResult {
+            #[serde(with = "stringify")]
+            amount: u64,
+        }
+
+        Ok(self
+            .aggregate::<AmountResult>(
+                [
+                    doc! { "$match": {
+                        "details.address": address,
+                        "metadata.booked.milestone_index": { "$lte": milestone_index },
+                        "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": milestone_index } },
+                    } },
+                    doc! { "$group": {
+                        "_id": null,
+                        "amount": { "$sum": { "$toDecimal": "$output.amount" } },
+                    } },
+                    doc! { "$project": { "_id": 0, "amount": { "$toString": "$amount" } } },
+                ],
+                None,
+            )
+            .await?
+            .try_next()
+            .await?
+            .map_or(0, |res| res.amount))
+    }
+}
@@ -2,9 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use mongodb::bson::{self, doc};
-use primitive_types::U256;
 
-use super::queries::{AppendQuery, CreatedQuery, GovernorQuery, IssuerQuery, NativeTokensQuery, SenderQuery};
+use super::queries::{
+    AppendQuery, CreatedQuery, GovernorQuery, IssuerQuery, NativeTokensQuery, SenderQuery, UnlockableByAddressQuery,
+};
 use crate::{
     db::mongodb::collections::outputs::indexer::queries::AddressQuery,
     model::payload::{milestone::MilestoneTimestamp, transaction::output::Address},
@@ -15,11 +16,12 @@ use crate::{
 pub struct AliasOutputsQuery {
     pub state_controller: Option<Address>,
     pub governor: Option<Address>,
+    pub unlockable_by_address: Option<Address>,
     pub issuer: Option<Address>,
     pub sender: Option<Address>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<U256>,
-    pub max_native_token_count: Option<U256>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub created_before: Option<MilestoneTimestamp>,
     pub created_after: Option<MilestoneTimestamp>,
 }
@@ -30,6 +32,7 @@ impl From<AliasOutputsQuery> for bson::Document {
         queries.push(doc! { "output.kind": "alias" });
         queries.append_query(AddressQuery(query.state_controller));
         queries.append_query(GovernorQuery(query.governor));
+        queries.append_query(UnlockableByAddressQuery(query.unlockable_by_address));
         queries.append_query(IssuerQuery(query.issuer));
         queries.append_query(SenderQuery(query.sender));
         queries.append_query(NativeTokensQuery {
@@ -49,10 +52,9 @@ impl From<AliasOutputsQuery> for bson::Document {
 mod test {
     use mongodb::bson::{self, doc};
     use pretty_assertions::assert_eq;
-    use primitive_types::U256;
 
     use super::AliasOutputsQuery;
-    use crate::model::utxo::{Address, NativeTokenAmount};
+    use crate::model::utxo::Address;
 
     #[test]
     fn test_alias_query_everything() {
@@ -60,11 +62,12 @@ mod test {
         let query = AliasOutputsQuery {
             state_controller: Some(address),
             governor: Some(address),
+            unlockable_by_address: Some(address),
             issuer: Some(address),
             sender: Some(address),
             has_native_tokens: Some(true),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             created_before: Some(10000.into()),
             created_after: Some(1000.into()),
         };
@@ -73,6 +76,11 @@ mod test {
                 { "output.kind": "alias" },
                 { "details.address": address },
                 { "output.governor_address_unlock_condition.address": address },
+                { "$or": [
+                    { "details.address": address },
+                    { "output.governor_address_unlock_condition.address": address },
+                    { "output.expiration_unlock_condition.return_address": address },
+                ] },
                 { "output.features": {
                     "$elemMatch": {
                         "kind": "issuer",
@@ -86,16 +94,8 @@ mod test {
                     }
                 } },
                 { "output.native_tokens": { "$ne": [] } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$lt": bson::to_bson(&NativeTokenAmount::from(&U256::from(100))).unwrap() }
-                    }
-                } } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$gt": bson::to_bson(&NativeTokenAmount::from(&U256::from(1000))).unwrap() }
-                    }
-                } } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "metadata.booked.milestone_timestamp": { "$lt": 10000 } },
                 { "metadata.booked.milestone_timestamp": { "$gt": 1000 } },
             ]
@@ -107,8 +107,8 @@ mod test {
     fn test_alias_query_all_false() {
         let query = AliasOutputsQuery {
             has_native_tokens: Some(false),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             created_before: Some(10000.into()),
             ..Default::default()
         };
@@ -116,6 +116,8 @@ mod test {
             "$and": [
                 { "output.kind": "alias" },
                 { "output.native_tokens": { "$eq": [] } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "metadata.booked.milestone_timestamp": { "$lt": 10000 } }
             ]
         };
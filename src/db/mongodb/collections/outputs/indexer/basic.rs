@@ -2,11 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use mongodb::bson::{self, doc};
-use primitive_types::U256;
 
 use super::queries::{
     AddressQuery, AppendQuery, CreatedQuery, ExpirationQuery, NativeTokensQuery, SenderQuery,
-    StorageDepositReturnQuery, TagQuery, TimelockQuery,
+    StorageDepositReturnQuery, TagQuery, TimelockQuery, UnlockableByAddressQuery,
 };
 use crate::model::{payload::transaction::output::Tag, tangle::MilestoneTimestamp, utxo::Address};
 
@@ -14,9 +13,10 @@ use crate::model::{payload::transaction::output::Tag, tangle::MilestoneTimestamp
 #[allow(missing_docs)]
 pub struct BasicOutputsQuery {
     pub address: Option<Address>,
+    pub unlockable_by_address: Option<Address>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<U256>,
-    pub max_native_token_count: Option<U256>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub has_storage_deposit_return: Option<bool>,
     pub storage_deposit_return_address: Option<Address>,
     pub has_timelock: Option<bool>,
@@ -37,6 +37,7 @@ impl From<BasicOutputsQuery> for bson::Document {
         let mut queries = Vec::new();
         queries.push(doc! { "output.kind": "basic" });
         queries.append_query(AddressQuery(query.address));
+        queries.append_query(UnlockableByAddressQuery(query.unlockable_by_address));
         queries.append_query(NativeTokensQuery {
             has_native_tokens: query.has_native_tokens,
             min_native_token_count: query.min_native_token_count,
@@ -71,22 +72,19 @@ impl From<BasicOutputsQuery> for bson::Document {
 mod test {
     use mongodb::bson::{self, doc};
     use pretty_assertions::assert_eq;
-    use primitive_types::U256;
 
     use super::BasicOutputsQuery;
-    use crate::model::{
-        payload::transaction::output::Tag,
-        utxo::{Address, NativeTokenAmount},
-    };
+    use crate::model::{payload::transaction::output::Tag, utxo::Address};
 
     #[test]
     fn test_basic_query_everything() {
         let address = Address::rand_ed25519();
         let query = BasicOutputsQuery {
             address: Some(address),
+            unlockable_by_address: Some(address),
             has_native_tokens: Some(true),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             has_storage_deposit_return: Some(true),
             storage_deposit_return_address: Some(address),
             has_timelock: Some(true),
@@ -105,17 +103,14 @@ mod test {
             "$and": [
                 { "output.kind": "basic" },
                 { "details.address": address },
+                { "$or": [
+                    { "details.address": address },
+                    { "output.governor_address_unlock_condition.address": address },
+                    { "output.expiration_unlock_condition.return_address": address },
+                ] },
                 { "output.native_tokens": { "$ne": [] } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$lt": bson::to_bson(&NativeTokenAmount::from(&U256::from(100))).unwrap() }
-                    }
-                } } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$gt": bson::to_bson(&NativeTokenAmount::from(&U256::from(1000))).unwrap() }
-                    }
-                } } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "output.storage_deposit_return_unlock_condition": { "$exists": true } },
                 { "output.storage_deposit_return_unlock_condition.return_address": address },
                 { "output.timelock_unlock_condition": { "$exists": true } },
@@ -145,9 +140,10 @@ mod test {
         let address = Address::rand_ed25519();
         let query = BasicOutputsQuery {
             address: Some(address),
+            unlockable_by_address: None,
             has_native_tokens: Some(false),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             has_storage_deposit_return: Some(false),
             storage_deposit_return_address: Some(address),
             has_timelock: Some(false),
@@ -167,6 +163,8 @@ mod test {
                 { "output.kind": "basic" },
                 { "details.address": address },
                 { "output.native_tokens": { "$eq": [] } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "output.storage_deposit_return_unlock_condition": { "$exists": false } },
                 { "output.storage_deposit_return_unlock_condition.return_address": address },
                 { "output.timelock_unlock_condition": { "$exists": false } },
@@ -2,7 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use mongodb::bson::{self, doc};
-use primitive_types::U256;
 
 use super::queries::{AddressQuery, AppendQuery, CreatedQuery, NativeTokensQuery};
 use crate::model::{tangle::MilestoneTimestamp, utxo::Address};
@@ -12,8 +11,8 @@ use crate::model::{tangle::MilestoneTimestamp, utxo::Address};
 pub struct FoundryOutputsQuery {
     pub alias_address: Option<Address>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<U256>,
-    pub max_native_token_count: Option<U256>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub created_before: Option<MilestoneTimestamp>,
     pub created_after: Option<MilestoneTimestamp>,
 }
@@ -40,10 +39,9 @@ impl From<FoundryOutputsQuery> for bson::Document {
 mod test {
     use mongodb::bson::{self, doc};
     use pretty_assertions::assert_eq;
-    use primitive_types::U256;
 
     use super::FoundryOutputsQuery;
-    use crate::model::utxo::{Address, NativeTokenAmount};
+    use crate::model::utxo::Address;
 
     #[test]
     fn test_foundry_query_everything() {
@@ -51,8 +49,8 @@ mod test {
         let query = FoundryOutputsQuery {
             alias_address: Some(address),
             has_native_tokens: Some(true),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             created_before: Some(10000.into()),
             created_after: Some(1000.into()),
         };
@@ -61,16 +59,8 @@ mod test {
                 { "output.kind": "foundry" },
                 { "details.address": address },
                 { "output.native_tokens": { "$ne": [] } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$lt": bson::to_bson(&NativeTokenAmount::from(&U256::from(100))).unwrap() }
-                    }
-                } } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$gt": bson::to_bson(&NativeTokenAmount::from(&U256::from(1000))).unwrap() }
-                    }
-                } } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "metadata.booked.milestone_timestamp": { "$lt": 10000 } },
                 { "metadata.booked.milestone_timestamp": { "$gt": 1000 } },
             ]
@@ -83,8 +73,8 @@ mod test {
         let query = FoundryOutputsQuery {
             alias_address: None,
             has_native_tokens: Some(false),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             created_before: Some(10000.into()),
             created_after: Some(1000.into()),
         };
@@ -92,6 +82,8 @@ mod test {
             "$and": [
                 { "output.kind": "foundry" },
                 { "output.native_tokens": { "$eq": [] } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "metadata.booked.milestone_timestamp": { "$lt": 10000 } },
                 { "metadata.booked.milestone_timestamp": { "$gt": 1000 } },
             ]
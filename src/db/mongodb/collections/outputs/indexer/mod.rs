@@ -5,7 +5,11 @@ mod alias;
 mod basic;
 mod foundry;
 mod nft;
+mod outputs;
 mod queries;
+mod storage_deposit_return;
+
+use std::{fmt::Display, str::FromStr};
 
 use derive_more::From;
 use futures::TryStreamExt;
@@ -16,13 +20,19 @@ use mongodb::{
     IndexModel,
 };
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 pub use self::{
-    alias::AliasOutputsQuery, basic::BasicOutputsQuery, foundry::FoundryOutputsQuery, nft::NftOutputsQuery,
+    alias::AliasOutputsQuery,
+    basic::BasicOutputsQuery,
+    foundry::FoundryOutputsQuery,
+    nft::NftOutputsQuery,
+    outputs::OutputsQuery,
+    storage_deposit_return::{StorageDepositReturnOutputsQuery, StorageDepositReturnTotals},
 };
-use super::{OutputCollection, OutputDocument};
+use super::{OutputCollection, OutputDocument, AMOUNT_DETAILS_VERSION};
 use crate::{
-    db::mongodb::{collections::SortOrder, MongoDbCollectionExt},
+    db::mongodb::{collections::SortOrder, MongoDbCollection, MongoDbCollectionExt},
     model::{
         metadata::OutputMetadata,
         tangle::MilestoneIndex,
@@ -35,6 +45,124 @@ use crate::{
 pub struct OutputResult {
     pub output_id: OutputId,
     pub booked_index: MilestoneIndex,
+    #[serde(default)]
+    pub amount: u64,
+}
+
+/// The value by which indexed outputs are sorted and paginated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndexedOutputsSortKey {
+    /// The milestone at which the output was booked.
+    BookedIndex,
+    /// The amount of base tokens held by the output.
+    Amount,
+}
+
+impl Default for IndexedOutputsSortKey {
+    fn default() -> Self {
+        Self::BookedIndex
+    }
+}
+
+impl IndexedOutputsSortKey {
+    /// The version of the output details that introduced the key, if it is derived. Until all outputs were upgraded to
+    /// that version, the key is derived from the outputs while querying, as the others would be missing from the index.
+    pub fn details_version(&self) -> Option<u32> {
+        match self {
+            Self::BookedIndex => None,
+            Self::Amount => Some(AMOUNT_DETAILS_VERSION),
+        }
+    }
+}
+
+impl Display for IndexedOutputsSortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::BookedIndex => "booked",
+            Self::Amount => "amount",
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid sort key. Expected `booked` or `amount`, found `{0}`")]
+#[allow(missing_docs)]
+pub struct ParseSortKeyError(String);
+
+impl FromStr for IndexedOutputsSortKey {
+    type Err = ParseSortKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "booked" => Self::BookedIndex,
+            "amount" => Self::Amount,
+            _ => Err(ParseSortKeyError(s.to_string()))?,
+        })
+    }
+}
+
+/// The order of outputs that are sorted by amount.
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AmountOrder {
+    Largest,
+    Smallest,
+}
+
+impl Default for AmountOrder {
+    fn default() -> Self {
+        Self::Largest
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid amount order. Expected `largest` or `smallest`, found `{0}`")]
+#[allow(missing_docs)]
+pub struct ParseAmountOrderError(String);
+
+impl FromStr for AmountOrder {
+    type Err = ParseAmountOrderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "largest" => Self::Largest,
+            "smallest" => Self::Smallest,
+            _ => Err(ParseAmountOrderError(s.to_string()))?,
+        })
+    }
+}
+
+/// How indexed outputs are sorted. Outputs with the same value are sorted by their id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndexedOutputsSort {
+    /// By the milestone at which the outputs were booked.
+    BookedIndex(SortOrder),
+    /// By the amount of base tokens held by the outputs.
+    Amount(AmountOrder),
+}
+
+impl Default for IndexedOutputsSort {
+    fn default() -> Self {
+        Self::BookedIndex(Default::default())
+    }
+}
+
+impl IndexedOutputsSort {
+    /// The value by which the outputs are sorted.
+    pub fn key(&self) -> IndexedOutputsSortKey {
+        match self {
+            Self::BookedIndex(_) => IndexedOutputsSortKey::BookedIndex,
+            Self::Amount(_) => IndexedOutputsSortKey::Amount,
+        }
+    }
+}
+
+/// The first output of a page of indexed outputs, by the value the outputs are sorted by.
+#[allow(missing_docs)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndexedOutputsPosition {
+    BookedIndex(MilestoneIndex, OutputId),
+    Amount(u64, OutputId),
 }
 
 #[derive(Clone, Debug)]
@@ -43,7 +171,16 @@ pub struct OutputsResult {
     pub outputs: Vec<OutputResult>,
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, From)]
+/// The generated pipeline and the plan chosen by the query planner for an indexed outputs query.
+#[derive(Clone, Debug)]
+pub struct IndexedOutputsExplanation {
+    /// The aggregation pipeline that is sent to the database.
+    pub pipeline: Vec<bson::Document>,
+    /// The plan selected by the query planner, if it was reported.
+    pub winning_plan: Option<bson::Document>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, From)]
 #[serde(untagged)]
 #[allow(missing_docs)]
 pub enum IndexedId {
@@ -119,57 +256,25 @@ impl OutputCollection {
         }))
     }
 
-    /// Gets any indexed output kind that match the provided query.
+    /// Gets any indexed output kind that match the provided query. The details version is the one all outputs were
+    /// upgraded to, which decides whether the key the outputs are sorted by can be read from the index.
+    #[allow(clippy::too_many_arguments)]
     pub async fn get_indexed_outputs<Q>(
         &self,
         query: Q,
         page_size: usize,
-        cursor: Option<(MilestoneIndex, OutputId)>,
-        order: SortOrder,
+        cursor: Option<IndexedOutputsPosition>,
+        sort: IndexedOutputsSort,
         include_spent: bool,
         ledger_index: MilestoneIndex,
+        details_version: Option<u32>,
     ) -> Result<OutputsResult, Error>
     where
         bson::Document: From<Q>,
     {
-        let (sort, cmp1, cmp2) = match order {
-            SortOrder::Newest => (doc! { "metadata.booked.milestone_index": -1, "_id": -1 }, "$lt", "$lte"),
-            SortOrder::Oldest => (doc! { "metadata.booked.milestone_index": 1, "_id": 1 }, "$gt", "$gte"),
-        };
-
-        let query_doc = bson::Document::from(query);
-        let mut additional_queries = vec![doc! { "metadata.booked.milestone_index": { "$lte": ledger_index } }];
-        if !include_spent {
-            additional_queries.push(doc! {
-                "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
-            });
-        }
-        if let Some((start_ms, start_output_id)) = cursor {
-            additional_queries.push(doc! { "$or": [
-                doc! { "metadata.booked.milestone_index": { cmp1: start_ms } },
-                doc! {
-                    "metadata.booked.milestone_index": start_ms,
-                    "_id": { cmp2: start_output_id }
-                },
-            ] });
-        }
-        let match_doc = doc! { "$match": {
-            "$and": [
-                query_doc,
-                { "$and": additional_queries }
-            ]
-        } };
         let outputs = self
             .aggregate(
-                [
-                    match_doc,
-                    doc! { "$sort": sort },
-                    doc! { "$limit": page_size as i64 },
-                    doc! { "$replaceWith": {
-                        "output_id": "$_id",
-                        "booked_index": "$metadata.booked.milestone_index"
-                    } },
-                ],
+                indexed_outputs_pipeline(query, page_size, cursor, sort, include_spent, ledger_index, details_version),
                 None,
             )
             .await?
@@ -178,6 +283,59 @@ impl OutputCollection {
         Ok(OutputsResult { outputs })
     }
 
+    /// Explains how the database would execute [`get_indexed_outputs`](Self::get_indexed_outputs) for the provided
+    /// query, without returning any outputs.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn explain_indexed_outputs<Q>(
+        &self,
+        query: Q,
+        page_size: usize,
+        cursor: Option<IndexedOutputsPosition>,
+        sort: IndexedOutputsSort,
+        include_spent: bool,
+        ledger_index: MilestoneIndex,
+        details_version: Option<u32>,
+    ) -> Result<IndexedOutputsExplanation, Error>
+    where
+        bson::Document: From<Q>,
+    {
+        let pipeline = indexed_outputs_pipeline(query, page_size, cursor, sort, include_spent, ledger_index, details_version);
+        let mut explain = self
+            .db
+            .run_command(
+                doc! {
+                    "explain": {
+                        "aggregate": Self::NAME,
+                        "pipeline": pipeline.clone(),
+                        "cursor": {},
+                    },
+                    "verbosity": "queryPlanner",
+                },
+                None,
+            )
+            .await?;
+        // Depending on the server version and the pipeline, the query planner output is either at the top level or
+        // nested in the `$cursor` stage.
+        let query_planner = match explain.remove("queryPlanner") {
+            Some(Bson::Document(query_planner)) => Some(query_planner),
+            _ => explain
+                .get_array("stages")
+                .ok()
+                .and_then(|stages| stages.first())
+                .and_then(Bson::as_document)
+                .and_then(|stage| stage.get_document("$cursor").ok())
+                .and_then(|cursor| cursor.get_document("queryPlanner").ok())
+                .cloned(),
+        };
+        Ok(IndexedOutputsExplanation {
+            pipeline,
+            winning_plan: query_planner.and_then(|mut query_planner| match query_planner.remove("winningPlan") {
+                Some(Bson::Document(winning_plan)) => Some(winning_plan),
+                _ => None,
+            }),
+        })
+    }
+
     /// Creates indexer output indexes.
     pub async fn create_indexer_indexes(&self) -> Result<(), Error> {
         self.create_index(
@@ -351,6 +509,15 @@ impl OutputCollection {
         )
         .await?;
 
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "details.amount": -1, "_id": -1 })
+                .options(IndexOptions::builder().name("output_amount_index".to_string()).build())
+                .build(),
+            None,
+        )
+        .await?;
+
         self.create_index(
             IndexModel::builder()
                 .keys(doc! { "metadata.booked.milestone_timestamp": -1 })
@@ -380,3 +547,197 @@ impl OutputCollection {
         Ok(())
     }
 }
+
+fn indexed_outputs_pipeline<Q>(
+    query: Q,
+    page_size: usize,
+    cursor: Option<IndexedOutputsPosition>,
+    sort: IndexedOutputsSort,
+    include_spent: bool,
+    ledger_index: MilestoneIndex,
+    details_version: Option<u32>,
+) -> Vec<bson::Document>
+where
+    bson::Document: From<Q>,
+{
+    let (field, descending) = match sort {
+        IndexedOutputsSort::BookedIndex(order) => ("metadata.booked.milestone_index", order == SortOrder::Newest),
+        IndexedOutputsSort::Amount(order) => ("details.amount", order == AmountOrder::Largest),
+    };
+    let (direction, cmp1, cmp2) = if descending {
+        (-1, "$lt", "$lte")
+    } else {
+        (1, "$gt", "$gte")
+    };
+
+    let query_doc = bson::Document::from(query);
+    let mut additional_queries = vec![doc! { "metadata.booked.milestone_index": { "$lte": ledger_index } }];
+    if !include_spent {
+        additional_queries.push(doc! {
+            "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+        });
+    }
+    let cursor_query = cursor.map(|cursor| {
+        let (start, start_output_id) = match cursor {
+            IndexedOutputsPosition::BookedIndex(start_ms, output_id) => (Bson::from(start_ms), output_id),
+            // The amounts are stored as signed numbers.
+            IndexedOutputsPosition::Amount(amount, output_id) => {
+                (Bson::from(i64::try_from(amount).unwrap_or(i64::MAX)), output_id)
+            }
+        };
+        doc! { "$or": [
+            doc! { field: { cmp1: start.clone() } },
+            doc! {
+                field: start,
+                "_id": { cmp2: start_output_id }
+            },
+        ] }
+    });
+
+    // Outputs whose details were not upgraded yet would be missing from the results, so the key is derived from the
+    // outputs instead, which cannot use the index.
+    let derive_key = sort
+        .key()
+        .details_version()
+        .map_or(false, |version| details_version.map_or(true, |upgraded| upgraded < version));
+    let mut pipeline = Vec::new();
+    if derive_key {
+        pipeline.push(doc! { "$match": {
+            "$and": [
+                query_doc,
+                { "$and": additional_queries }
+            ]
+        } });
+        pipeline.push(doc! { "$set": { "details.amount": { "$toLong": "$output.amount" } } });
+        if let Some(cursor_query) = cursor_query {
+            pipeline.push(doc! { "$match": cursor_query });
+        }
+    } else {
+        additional_queries.extend(cursor_query);
+        pipeline.push(doc! { "$match": {
+            "$and": [
+                query_doc,
+                { "$and": additional_queries }
+            ]
+        } });
+    }
+    pipeline.extend([
+        doc! { "$sort": { field: direction, "_id": direction } },
+        doc! { "$limit": page_size as i64 },
+        doc! { "$replaceWith": {
+            "output_id": "$_id",
+            "booked_index": "$metadata.booked.milestone_index",
+            "amount": "$details.amount"
+        } },
+    ]);
+    pipeline
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use mongodb::bson::{self, doc};
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        indexed_outputs_pipeline, AmountOrder, BasicOutputsQuery, IndexedOutputsPosition, IndexedOutputsSort,
+        AMOUNT_DETAILS_VERSION,
+    };
+    use crate::{db::mongodb::collections::SortOrder, model::utxo::OutputId};
+
+    #[test]
+    fn test_sort_by_booked_index() {
+        let output_id = OutputId::rand();
+        let pipeline = indexed_outputs_pipeline(
+            BasicOutputsQuery::default(),
+            10,
+            Some(IndexedOutputsPosition::BookedIndex(5.into(), output_id)),
+            IndexedOutputsSort::BookedIndex(SortOrder::Newest),
+            false,
+            8.into(),
+            None,
+        );
+        assert_eq!(
+            pipeline[..3],
+            [
+                doc! { "$match": {
+                    "$and": [
+                        bson::Document::from(BasicOutputsQuery::default()),
+                        { "$and": [
+                            { "metadata.booked.milestone_index": { "$lte": 8 } },
+                            { "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": 8 } } },
+                            { "$or": [
+                                { "metadata.booked.milestone_index": { "$lt": 5 } },
+                                { "metadata.booked.milestone_index": 5, "_id": { "$lte": output_id } },
+                            ] },
+                        ] },
+                    ]
+                } },
+                doc! { "$sort": { "metadata.booked.milestone_index": -1, "_id": -1 } },
+                doc! { "$limit": 10i64 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_amount() {
+        let output_id = OutputId::rand();
+        let cursor = Some(IndexedOutputsPosition::Amount(1_000_000, output_id));
+        let cursor_query = doc! { "$or": [
+            { "details.amount": { "$gt": 1_000_000i64 } },
+            { "details.amount": 1_000_000i64, "_id": { "$gte": output_id } },
+        ] };
+
+        // Once all details are upgraded, the amount is read from them.
+        let pipeline = indexed_outputs_pipeline(
+            BasicOutputsQuery::default(),
+            10,
+            cursor,
+            IndexedOutputsSort::Amount(AmountOrder::Smallest),
+            true,
+            8.into(),
+            Some(AMOUNT_DETAILS_VERSION),
+        );
+        assert_eq!(
+            pipeline[..2],
+            [
+                doc! { "$match": {
+                    "$and": [
+                        bson::Document::from(BasicOutputsQuery::default()),
+                        { "$and": [
+                            { "metadata.booked.milestone_index": { "$lte": 8 } },
+                            cursor_query.clone(),
+                        ] },
+                    ]
+                } },
+                doc! { "$sort": { "details.amount": 1, "_id": 1 } },
+            ]
+        );
+
+        // Otherwise, it is derived from the outputs before the cursor is applied.
+        let pipeline = indexed_outputs_pipeline(
+            BasicOutputsQuery::default(),
+            10,
+            cursor,
+            IndexedOutputsSort::Amount(AmountOrder::Smallest),
+            true,
+            8.into(),
+            Some(AMOUNT_DETAILS_VERSION - 1),
+        );
+        assert_eq!(
+            pipeline[..4],
+            [
+                doc! { "$match": {
+                    "$and": [
+                        bson::Document::from(BasicOutputsQuery::default()),
+                        { "$and": [
+                            { "metadata.booked.milestone_index": { "$lte": 8 } },
+                        ] },
+                    ]
+                } },
+                doc! { "$set": { "details.amount": { "$toLong": "$output.amount" } } },
+                doc! { "$match": cursor_query },
+                doc! { "$sort": { "details.amount": 1, "_id": 1 } },
+            ]
+        );
+    }
+}
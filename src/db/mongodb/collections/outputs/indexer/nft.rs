@@ -2,11 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use mongodb::bson::{self, doc};
-use primitive_types::U256;
 
 use super::queries::{
     AddressQuery, AppendQuery, CreatedQuery, ExpirationQuery, IssuerQuery, NativeTokensQuery, SenderQuery,
-    StorageDepositReturnQuery, TagQuery, TimelockQuery,
+    StorageDepositReturnQuery, TagQuery, TimelockQuery, UnlockableByAddressQuery,
 };
 use crate::model::{payload::transaction::output::Tag, tangle::MilestoneTimestamp, utxo::Address};
 
@@ -14,11 +13,12 @@ use crate::model::{payload::transaction::output::Tag, tangle::MilestoneTimestamp
 #[allow(missing_docs)]
 pub struct NftOutputsQuery {
     pub address: Option<Address>,
+    pub unlockable_by_address: Option<Address>,
     pub issuer: Option<Address>,
     pub sender: Option<Address>,
     pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<U256>,
-    pub max_native_token_count: Option<U256>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
     pub has_storage_deposit_return: Option<bool>,
     pub storage_deposit_return_address: Option<Address>,
     pub has_timelock: Option<bool>,
@@ -38,6 +38,7 @@ impl From<NftOutputsQuery> for bson::Document {
         let mut queries = Vec::new();
         queries.push(doc! { "output.kind": "nft" });
         queries.append_query(AddressQuery(query.address));
+        queries.append_query(UnlockableByAddressQuery(query.unlockable_by_address));
         queries.append_query(IssuerQuery(query.issuer));
         queries.append_query(SenderQuery(query.sender));
         queries.append_query(NativeTokensQuery {
@@ -73,24 +74,21 @@ impl From<NftOutputsQuery> for bson::Document {
 mod test {
     use mongodb::bson::{self, doc};
     use pretty_assertions::assert_eq;
-    use primitive_types::U256;
 
     use super::NftOutputsQuery;
-    use crate::model::{
-        payload::transaction::output::Tag,
-        utxo::{Address, NativeTokenAmount},
-    };
+    use crate::model::{payload::transaction::output::Tag, utxo::Address};
 
     #[test]
     fn test_nft_query_everything() {
         let address = Address::rand_ed25519();
         let query = NftOutputsQuery {
             address: Some(address),
+            unlockable_by_address: Some(address),
             issuer: Some(address),
             sender: Some(address),
             has_native_tokens: Some(true),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             has_storage_deposit_return: Some(true),
             storage_deposit_return_address: Some(address),
             has_timelock: Some(true),
@@ -108,6 +106,11 @@ mod test {
             "$and": [
                 { "output.kind": "nft" },
                 { "details.address": address },
+                { "$or": [
+                    { "details.address": address },
+                    { "output.governor_address_unlock_condition.address": address },
+                    { "output.expiration_unlock_condition.return_address": address },
+                ] },
                 { "output.features": { "$elemMatch": {
                     "kind": "issuer",
                     "address": address
@@ -117,16 +120,8 @@ mod test {
                     "address": address
                 } } },
                 { "output.native_tokens": { "$ne": [] } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$lt": bson::to_bson(&NativeTokenAmount::from(&U256::from(100))).unwrap() }
-                    }
-                } } },
-                { "output.native_tokens": { "$not": {
-                    "$elemMatch": {
-                        "amount": { "$gt": bson::to_bson(&NativeTokenAmount::from(&U256::from(1000))).unwrap() }
-                    }
-                } } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "output.storage_deposit_return_unlock_condition": { "$exists": true } },
                 { "output.storage_deposit_return_unlock_condition.return_address": address },
                 { "output.timelock_unlock_condition": { "$exists": true } },
@@ -152,11 +147,12 @@ mod test {
         let address = Address::rand_ed25519();
         let query = NftOutputsQuery {
             address: Some(address),
+            unlockable_by_address: None,
             issuer: None,
             sender: None,
             has_native_tokens: Some(false),
-            min_native_token_count: Some(100.into()),
-            max_native_token_count: Some(1000.into()),
+            min_native_token_count: Some(100),
+            max_native_token_count: Some(1000),
             has_storage_deposit_return: Some(false),
             storage_deposit_return_address: Some(address),
             has_timelock: Some(false),
@@ -175,6 +171,8 @@ mod test {
                 { "output.kind": "nft" },
                 { "details.address": address },
                 { "output.native_tokens": { "$eq": [] } },
+                { "output.native_tokens.99": { "$exists": true } },
+                { "output.native_tokens.1000": { "$exists": false } },
                 { "output.storage_deposit_return_unlock_condition": { "$exists": false } },
                 { "output.storage_deposit_return_unlock_condition.return_address": address },
                 { "output.timelock_unlock_condition": { "$exists": false } },
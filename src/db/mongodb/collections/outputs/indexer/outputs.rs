@@ -0,0 +1,85 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use mongodb::bson::{self, doc};
+
+use super::queries::{AppendQuery, CreatedQuery, NativeTokensQuery, UnlockableByAddressQuery};
+use crate::model::{tangle::MilestoneTimestamp, utxo::Address};
+
+/// A query over the basic, alias, foundry and nft outputs at once.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct OutputsQuery {
+    pub has_native_tokens: Option<bool>,
+    pub min_native_token_count: Option<u32>,
+    pub max_native_token_count: Option<u32>,
+    pub unlockable_by_address: Option<Address>,
+    pub created_before: Option<MilestoneTimestamp>,
+    pub created_after: Option<MilestoneTimestamp>,
+}
+
+impl From<OutputsQuery> for bson::Document {
+    fn from(query: OutputsQuery) -> Self {
+        let mut queries = Vec::new();
+        queries.push(doc! { "output.kind": { "$in": ["basic", "alias", "foundry", "nft"] } });
+        queries.append_query(NativeTokensQuery {
+            has_native_tokens: query.has_native_tokens,
+            min_native_token_count: query.min_native_token_count,
+            max_native_token_count: query.max_native_token_count,
+        });
+        queries.append_query(UnlockableByAddressQuery(query.unlockable_by_address));
+        queries.append_query(CreatedQuery {
+            created_before: query.created_before,
+            created_after: query.created_after,
+        });
+        doc! { "$and": queries }
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use mongodb::bson::{self, doc};
+    use pretty_assertions::assert_eq;
+
+    use super::OutputsQuery;
+    use crate::model::utxo::Address;
+
+    #[test]
+    fn test_outputs_query_everything() {
+        let address = Address::rand_ed25519();
+        let query = OutputsQuery {
+            has_native_tokens: Some(true),
+            min_native_token_count: Some(1),
+            max_native_token_count: Some(64),
+            unlockable_by_address: Some(address),
+            created_before: Some(10000.into()),
+            created_after: Some(1000.into()),
+        };
+        let query_doc = doc! {
+            "$and": [
+                { "output.kind": { "$in": ["basic", "alias", "foundry", "nft"] } },
+                { "output.native_tokens": { "$ne": [] } },
+                { "output.native_tokens.0": { "$exists": true } },
+                { "output.native_tokens.64": { "$exists": false } },
+                { "$or": [
+                    { "details.address": address },
+                    { "output.governor_address_unlock_condition.address": address },
+                    { "output.expiration_unlock_condition.return_address": address },
+                ] },
+                { "metadata.booked.milestone_timestamp": { "$lt": 10000 } },
+                { "metadata.booked.milestone_timestamp": { "$gt": 1000 } },
+            ]
+        };
+        assert_eq!(query_doc, bson::Document::from(query));
+    }
+
+    #[test]
+    fn test_outputs_query_empty() {
+        let query_doc = doc! {
+            "$and": [
+                { "output.kind": { "$in": ["basic", "alias", "foundry", "nft"] } },
+            ]
+        };
+        assert_eq!(query_doc, bson::Document::from(OutputsQuery::default()));
+    }
+}
@@ -1,14 +1,9 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use mongodb::bson::{self, doc, Document};
-use primitive_types::U256;
+use mongodb::bson::{doc, Document};
 
-use crate::model::{
-    payload::transaction::output::Tag,
-    tangle::MilestoneTimestamp,
-    utxo::{Address, NativeTokenAmount},
-};
+use crate::model::{payload::transaction::output::Tag, tangle::MilestoneTimestamp, utxo::Address};
 
 /// Defines how a query is appended to a list of `$and` queries.
 pub(super) trait AppendToQuery {
@@ -79,55 +74,53 @@ impl AppendToQuery for TagQuery {
     }
 }
 
-/// Queries for native tokens.
+/// Queries for native tokens, where the counts refer to the number of distinct native tokens in an output.
 pub(super) struct NativeTokensQuery {
     pub(super) has_native_tokens: Option<bool>,
-    pub(super) min_native_token_count: Option<U256>,
-    pub(super) max_native_token_count: Option<U256>,
+    pub(super) min_native_token_count: Option<u32>,
+    pub(super) max_native_token_count: Option<u32>,
 }
 
 impl AppendToQuery for NativeTokensQuery {
     fn append_to(self, queries: &mut Vec<Document>) {
-        if let Some(false) = self.has_native_tokens {
-            queries.push(doc! {
-                "output.native_tokens": { "$eq": [] }
-            });
-        } else {
-            if matches!(self.has_native_tokens, Some(true))
-                || self.min_native_token_count.is_some()
-                || self.max_native_token_count.is_some()
-            {
+        if let Some(has_native_tokens) = self.has_native_tokens {
+            if has_native_tokens {
                 queries.push(doc! {
                     "output.native_tokens": { "$ne": [] }
                 });
-            }
-            if let Some(min_native_token_count) = self.min_native_token_count {
-                queries.push(doc! {
-                    "output.native_tokens": {
-                        "$not": {
-                            "$elemMatch": {
-                                "amount": {
-                                    "$lt": bson::to_bson(&NativeTokenAmount::from(&min_native_token_count)).unwrap()
-                                }
-                            }
-                        }
-                    }
-                });
-            }
-            if let Some(max_native_token_count) = self.max_native_token_count {
+            } else {
                 queries.push(doc! {
-                    "output.native_tokens": {
-                        "$not": {
-                            "$elemMatch": {
-                                "amount": {
-                                    "$gt": bson::to_bson(&NativeTokenAmount::from(&max_native_token_count)).unwrap()
-                                }
-                            }
-                        }
-                    }
+                    "output.native_tokens": { "$eq": [] }
                 });
             }
         }
+        // An output has at least `n` native tokens if the array has an element at index `n - 1`.
+        if let Some(min_native_token_count) = self.min_native_token_count.filter(|count| *count > 0) {
+            let field = format!("output.native_tokens.{}", min_native_token_count - 1);
+            queries.push(doc! { field: { "$exists": true } });
+        }
+        if let Some(max_native_token_count) = self.max_native_token_count {
+            let field = format!("output.native_tokens.{max_native_token_count}");
+            queries.push(doc! { field: { "$exists": false } });
+        }
+    }
+}
+
+/// Queries for outputs that can be unlocked by an address, either as their owner, which is the state controller for
+/// aliases, as the governor of an alias or as the return address of an expiration unlock condition.
+pub(super) struct UnlockableByAddressQuery(pub(super) Option<Address>);
+
+impl AppendToQuery for UnlockableByAddressQuery {
+    fn append_to(self, queries: &mut Vec<Document>) {
+        if let Some(address) = self.0 {
+            queries.push(doc! {
+                "$or": [
+                    { "details.address": address },
+                    { "output.governor_address_unlock_condition.address": address },
+                    { "output.expiration_unlock_condition.return_address": address },
+                ]
+            });
+        }
     }
 }
 
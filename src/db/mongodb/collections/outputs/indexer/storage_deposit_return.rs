@@ -0,0 +1,120 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{self, doc},
+    error::Error,
+};
+use serde::Deserialize;
+
+use super::{
+    queries::{AppendQuery, CreatedQuery, StorageDepositReturnQuery},
+    OutputCollection,
+};
+use crate::{
+    db::mongodb::MongoDbCollectionExt,
+    model::{
+        tangle::{MilestoneIndex, MilestoneTimestamp},
+        utxo::Address,
+    },
+};
+
+/// Queries for outputs of any kind whose storage deposit has to be returned to the given address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct StorageDepositReturnOutputsQuery {
+    pub return_address: Address,
+    pub created_before: Option<MilestoneTimestamp>,
+    pub created_after: Option<MilestoneTimestamp>,
+}
+
+impl From<StorageDepositReturnOutputsQuery> for bson::Document {
+    fn from(query: StorageDepositReturnOutputsQuery) -> Self {
+        let mut queries = Vec::new();
+        // Matching on the existence of the unlock condition allows the partial index on the return address to be used.
+        queries.append_query(StorageDepositReturnQuery {
+            has_storage_return_condition: Some(true),
+            storage_return_address: Some(query.return_address),
+        });
+        queries.append_query(CreatedQuery {
+            created_before: query.created_before,
+            created_after: query.created_after,
+        });
+        doc! { "$and": queries }
+    }
+}
+
+/// The storage deposits that can be reclaimed by an address.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct StorageDepositReturnTotals {
+    /// The number of unspent outputs with a storage deposit to be returned to the address.
+    pub count: usize,
+    /// The sum of the storage deposits to be returned to the address.
+    pub amount: String,
+}
+
+impl OutputCollection {
+    /// Sums the storage deposits of all unspent outputs that have to be returned to the given [`Address`].
+    pub async fn get_storage_deposit_return_totals(
+        &self,
+        return_address: Address,
+        ledger_index: MilestoneIndex,
+    ) -> Result<StorageDepositReturnTotals, Error> {
+        Ok(self
+            .aggregate(
+                [
+                    doc! { "$match": {
+                        "output.storage_deposit_return_unlock_condition": { "$exists": true },
+                        "output.storage_deposit_return_unlock_condition.return_address": return_address,
+                        "metadata.booked.milestone_index": { "$lte": ledger_index },
+                        "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                    } },
+                    doc! { "$group": {
+                        "_id": null,
+                        "count": { "$sum": 1 },
+                        "amount": { "$sum": { "$toDecimal": "$output.storage_deposit_return_unlock_condition.amount" } },
+                    } },
+                    doc! { "$project": {
+                        "count": 1,
+                        "amount": { "$toString": "$amount" },
+                    } },
+                ],
+                None,
+            )
+            .await?
+            .try_next()
+            .await?
+            .unwrap_or_else(|| StorageDepositReturnTotals {
+                count: 0,
+                amount: "0".to_string(),
+            }))
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use mongodb::bson::{self, doc};
+    use pretty_assertions::assert_eq;
+
+    use super::StorageDepositReturnOutputsQuery;
+    use crate::model::utxo::Address;
+
+    #[test]
+    fn test_storage_deposit_return_query() {
+        let address = Address::rand_ed25519();
+        let query = StorageDepositReturnOutputsQuery {
+            return_address: address,
+            created_before: Some(10000.into()),
+            created_after: None,
+        };
+        let query_doc = doc! {
+            "$and": [
+                { "output.storage_deposit_return_unlock_condition": { "$exists": true } },
+                { "output.storage_deposit_return_unlock_condition.return_address": address },
+                { "metadata.booked.milestone_timestamp": { "$lt": 10000 } },
+            ]
+        };
+        assert_eq!(query_doc, bson::Document::from(query));
+    }
+}
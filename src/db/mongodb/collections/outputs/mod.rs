@@ -1,23 +1,39 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+mod addresses;
+mod digest;
+mod history;
 mod indexer;
+mod native_tokens;
+mod pruning;
+mod relations;
 
-use std::borrow::Borrow;
+use std::{borrow::Borrow, collections::HashSet};
 
 use futures::{Stream, TryStreamExt};
 use mongodb::{
-    bson::{doc, to_bson, to_document},
-    error::Error,
-    options::{IndexOptions, InsertManyOptions},
+    bson::{doc, to_bson, to_document, Document},
+    error::{BulkWriteFailure, Error, ErrorKind},
+    options::{AggregateOptions, FindOneOptions, FindOptions, IndexOptions, InsertManyOptions},
     IndexModel,
 };
 use serde::{Deserialize, Serialize};
-use tracing::instrument;
+use tracing::{instrument, warn};
 
-pub use self::indexer::{
-    AliasOutputsQuery, BasicOutputsQuery, FoundryOutputsQuery, IndexedId, NftOutputsQuery, OutputsResult,
+pub use self::{
+    addresses::AddressActivitySpan,
+    digest::{LedgerDigest, UnspentOutputDigest, UNSPENT_OUTPUT_DIGEST_BUCKETS},
+    history::OutputHistoryRecord,
+    indexer::{
+        AliasOutputsQuery, AmountOrder, BasicOutputsQuery, FoundryOutputsQuery, IndexedId, IndexedOutputsExplanation,
+        IndexedOutputsPosition, IndexedOutputsSort, IndexedOutputsSortKey, NftOutputsQuery, OutputsQuery,
+        OutputsResult, ParseAmountOrderError, ParseSortKeyError, StorageDepositReturnOutputsQuery,
+        StorageDepositReturnTotals,
+    },
+    relations::{EntityRelation, EntityRelationship},
 };
+use super::{ApplicationStateCollection, BlockCollection, MilestoneCollection};
 use crate::{
     db::{
         mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
@@ -26,12 +42,21 @@ use crate::{
     model::{
         ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
         metadata::{OutputMetadata, SpentMetadata},
+        payload::transaction::output::Tag,
         tangle::{MilestoneIndex, MilestoneIndexTimestamp, MilestoneTimestamp},
         utxo::{Address, AliasId, NftId, Output, OutputId},
         BlockId,
     },
 };
 
+/// The version of the precalculated [`OutputDetails`] that added the amount as a number, by which the indexed outputs
+/// can be sorted.
+pub const AMOUNT_DETAILS_VERSION: u32 = 2;
+
+/// The version of the precalculated [`OutputDetails`]. This must be increased whenever the way the details are derived
+/// changes, so that outdated documents are upgraded lazily instead of by migrating the whole collection.
+pub const OUTPUT_DETAILS_VERSION: u32 = AMOUNT_DETAILS_VERSION;
+
 /// Chronicle Output record.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OutputDocument {
@@ -40,12 +65,18 @@ pub struct OutputDocument {
     output: Output,
     metadata: OutputMetadata,
     details: OutputDetails,
+    /// The version the details were derived with. Documents written before versioning was introduced have version 0.
+    #[serde(default)]
+    details_version: u32,
 }
 
 /// The stardust outputs collection.
 pub struct OutputCollection {
     db: mongodb::Database,
     collection: mongodb::Collection<OutputDocument>,
+    blocks: BlockCollection,
+    milestones: MilestoneCollection,
+    application_state: ApplicationStateCollection,
 }
 
 #[async_trait::async_trait]
@@ -57,6 +88,9 @@ impl MongoDbCollection for OutputCollection {
         Self {
             db: db.db(),
             collection,
+            blocks: db.collection(),
+            milestones: db.collection(),
+            application_state: db.collection(),
         }
     }
 
@@ -79,6 +113,19 @@ impl MongoDbCollection for OutputCollection {
         )
         .await?;
 
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "details_version": 1, "_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("output_details_version_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
         self.create_indexer_indexes().await?;
 
         Ok(())
@@ -94,13 +141,69 @@ struct OutputDetails {
     rent_structure: RentStructureBytes,
     #[serde(skip_serializing_if = "Option::is_none")]
     indexed_id: Option<IndexedId>,
+    /// The amount of the output, which is stored as a string in the output itself and so cannot be sorted by.
+    #[serde(default)]
+    amount: i64,
+}
+
+impl OutputDetails {
+    /// Derives the details of an output. The owning address of a spent output is determined at the time it was spent.
+    fn derive(
+        output_id: OutputId,
+        output: &Output,
+        spent_metadata: Option<&SpentMetadata>,
+        rent_structure: RentStructureBytes,
+    ) -> Self {
+        Self {
+            address: output
+                .owning_address(spent_metadata.map(|spent_metadata| spent_metadata.spent.milestone_timestamp))
+                .copied(),
+            is_trivial_unlock: output.is_trivial_unlock(),
+            rent_structure,
+            indexed_id: match output {
+                Output::Alias(output) => Some(
+                    if output.alias_id == AliasId::implicit() {
+                        AliasId::from(output_id)
+                    } else {
+                        output.alias_id
+                    }
+                    .into(),
+                ),
+                Output::Nft(output) => Some(
+                    if output.nft_id == NftId::implicit() {
+                        NftId::from(output_id)
+                    } else {
+                        output.nft_id
+                    }
+                    .into(),
+                ),
+                Output::Foundry(output) => Some(output.foundry_id.into()),
+                _ => None,
+            },
+            amount: output.amount().0.try_into().unwrap_or(i64::MAX),
+        }
+    }
+}
+
+impl OutputDocument {
+    /// Re-derives the details if they were derived with an older version. Returns whether the document changed.
+    fn upgrade_details(&mut self) -> bool {
+        if self.details_version >= OUTPUT_DETAILS_VERSION {
+            return false;
+        }
+        self.details = OutputDetails::derive(
+            self.output_id,
+            &self.output,
+            self.metadata.spent_metadata.as_ref(),
+            self.details.rent_structure,
+        );
+        self.details_version = OUTPUT_DETAILS_VERSION;
+        true
+    }
 }
 
 impl From<&LedgerOutput> for OutputDocument {
     fn from(rec: &LedgerOutput) -> Self {
-        let address = rec.owning_address().copied();
-        let is_trivial_unlock = rec.output.is_trivial_unlock();
-
         Self {
             output_id: rec.output_id,
             output: rec.output.clone(),
@@ -109,31 +212,8 @@ impl From<&LedgerOutput> for OutputDocument {
                 booked: rec.booked,
                 spent_metadata: None,
             },
-            details: OutputDetails {
-                address,
-                is_trivial_unlock,
-                rent_structure: rec.rent_structure,
-                indexed_id: match &rec.output {
-                    Output::Alias(output) => Some(
-                        if output.alias_id == AliasId::implicit() {
-                            AliasId::from(rec.output_id)
-                        } else {
-                            output.alias_id
-                        }
-                        .into(),
-                    ),
-                    Output::Nft(output) => Some(
-                        if output.nft_id == NftId::implicit() {
-                            NftId::from(rec.output_id)
-                        } else {
-                            output.nft_id
-                        }
-                        .into(),
-                    ),
-                    Output::Foundry(output) => Some(output.foundry_id.into()),
-                    _ => None,
-                },
-            },
+            details: OutputDetails::derive(rec.output_id, &rec.output, None, rec.rent_structure),
+            details_version: OUTPUT_DETAILS_VERSION,
         }
     }
 }
@@ -155,6 +235,10 @@ pub struct OutputMetadataResult {
     pub block_id: BlockId,
     pub booked: MilestoneIndexTimestamp,
     pub spent_metadata: Option<SpentMetadata>,
+    /// Set if the output was spent and then pruned, to the milestone before which spent outputs of its kind were
+    /// pruned. The spent metadata is not available in that case.
+    #[serde(default)]
+    pub pruned_before: Option<MilestoneIndex>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
@@ -229,6 +313,30 @@ impl OutputCollection {
         Ok(())
     }
 
+    /// Gets the ids of the given outputs that are not stored.
+    pub async fn get_missing_output_ids(&self, output_ids: &[OutputId]) -> Result<HashSet<OutputId>, Error> {
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(rename = "_id")]
+            output_id: OutputId,
+        }
+
+        let found = self
+            .find::<Res>(
+                doc! { "_id": { "$in": output_ids } },
+                FindOptions::builder().projection(doc! { "_id": 1 }).build(),
+            )
+            .await?
+            .map_ok(|Res { output_id }| output_id)
+            .try_collect::<HashSet<_>>()
+            .await?;
+        Ok(output_ids
+            .iter()
+            .filter(|output_id| !found.contains(output_id))
+            .copied()
+            .collect())
+    }
+
     /// Get an [`Output`] by [`OutputId`].
     pub async fn get_output(&self, output_id: &OutputId) -> Result<Option<Output>, Error> {
         self.aggregate(
@@ -243,37 +351,275 @@ impl OutputCollection {
         .await
     }
 
+    /// Get an [`Output`] that was booked by the given ledger index, together with the index it was booked at, which
+    /// determines the protocol parameters its raw bytes are packed with. Unlike
+    /// [`get_output_with_metadata`](Self::get_output_with_metadata), this reads only the output and its booked index.
+    pub async fn get_output_with_booked_index(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<(Output, MilestoneIndex)>, Error> {
+        #[derive(Deserialize)]
+        struct Booked {
+            milestone_index: MilestoneIndex,
+        }
+        #[derive(Deserialize)]
+        struct Metadata {
+            booked: Booked,
+        }
+        #[derive(Deserialize)]
+        struct Res {
+            output: Output,
+            metadata: Metadata,
+        }
+
+        Ok(self
+            .find_one::<Res>(
+                doc! {
+                    "_id": output_id,
+                    "metadata.booked.milestone_index": { "$lte": ledger_index }
+                },
+                FindOneOptions::builder()
+                    .projection(doc! { "output": 1, "metadata.booked.milestone_index": 1 })
+                    .build(),
+            )
+            .await?
+            .map(|res| (res.output, res.metadata.booked.milestone_index)))
+    }
+
     /// Get an [`Output`] with its [`OutputMetadata`] by [`OutputId`].
+    ///
+    /// If the details of the output are outdated, they are upgraded along the way. If the output was pruned after it
+    /// was spent, it is recovered as in [`Self::get_output_metadata`].
     pub async fn get_output_with_metadata(
         &self,
         output_id: &OutputId,
         ledger_index: MilestoneIndex,
     ) -> Result<Option<OutputWithMetadataResult>, Error> {
-        self.aggregate(
-            [
-                doc! { "$match": {
-                    "_id": output_id,
-                    "metadata.booked.milestone_index": { "$lte": ledger_index }
-                } },
-                doc! { "$project": {
-                    "output": "$output",
-                    "metadata": {
+        #[derive(Deserialize)]
+        struct Res {
+            output: Output,
+            metadata: OutputMetadataResult,
+            #[serde(default)]
+            details_version: u32,
+        }
+
+        let res = self
+            .aggregate::<Res>(
+                [
+                    doc! { "$match": {
+                        "_id": output_id,
+                        "metadata.booked.milestone_index": { "$lte": ledger_index }
+                    } },
+                    doc! { "$project": {
+                        "output": "$output",
+                        "metadata": {
+                            "output_id": "$_id",
+                            "block_id": "$metadata.block_id",
+                            "booked": "$metadata.booked",
+                            "spent_metadata": "$metadata.spent_metadata",
+                        },
+                        "details_version": "$details_version",
+                    } },
+                ],
+                None,
+            )
+            .await?
+            .try_next()
+            .await?;
+        let res = match res {
+            Some(res) => res,
+            None => return self.get_pruned_output(output_id, ledger_index).await,
+        };
+        if res.details_version < OUTPUT_DETAILS_VERSION {
+            // The upgrade is opportunistic, so a failure must not fail the query.
+            if let Err(e) = self.upgrade_output_details(output_id).await {
+                warn!("Failed to upgrade the details of output {}: {e}", output_id.to_hex());
+            }
+        }
+        let Res { output, metadata, .. } = res;
+        Ok(Some(OutputWithMetadataResult { output, metadata }))
+    }
+
+    /// Re-derives the details of an output if they are outdated.
+    pub async fn upgrade_output_details(&self, output_id: &OutputId) -> Result<(), Error> {
+        if let Some(mut doc) = self.find_one::<OutputDocument>(doc! { "_id": output_id }, None).await? {
+            if doc.upgrade_details() {
+                self.write_upgraded_details([doc]).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-derives the details of up to `batch_size` outdated outputs, in the order of their ids and starting after
+    /// the given one. Returns the number of upgraded outputs and the id of the last one, to continue after, or `None`
+    /// once all details are up to date.
+    pub async fn upgrade_outdated_details(
+        &self,
+        after: Option<OutputId>,
+        batch_size: usize,
+    ) -> Result<Option<(usize, OutputId)>, Error> {
+        let mut filter = doc! { "details_version": { "$not": { "$gte": OUTPUT_DETAILS_VERSION } } };
+        if let Some(after) = after {
+            filter.insert("_id", doc! { "$gt": after });
+        }
+        let docs = self
+            .find::<OutputDocument>(
+                filter,
+                FindOptions::builder()
+                    .sort(doc! { "_id": 1 })
+                    .limit(batch_size as i64)
+                    .build(),
+            )
+            .await?
+            .try_filter_map(|mut doc| async move { Ok(doc.upgrade_details().then(|| doc)) })
+            .try_collect::<Vec<_>>()
+            .await?;
+        let last = match docs.last() {
+            Some(doc) => doc.output_id,
+            None => return Ok(None),
+        };
+        let count = docs.len();
+        self.write_upgraded_details(docs).await?;
+        Ok(Some((count, last)))
+    }
+
+    /// Writes upgraded details, unless the documents were upgraded or replaced in the meantime.
+    async fn write_upgraded_details(&self, docs: impl IntoIterator<Item = OutputDocument>) -> Result<(), Error> {
+        // TODO: Replace `db.run_command` once the `BulkWrite` API lands in the Rust driver.
+        let update_docs = docs
+            .into_iter()
+            .map(|doc| {
+                Ok(doc! {
+                    "q": {
+                        "_id": doc.output_id,
+                        "details_version": { "$not": { "$gte": OUTPUT_DETAILS_VERSION } },
+                    },
+                    "u": { "$set": {
+                        "details": to_bson(&doc.details)?,
+                        "details_version": OUTPUT_DETAILS_VERSION,
+                    } },
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        if !update_docs.is_empty() {
+            let mut command = doc! {
+                "update": Self::NAME,
+                "updates": update_docs,
+                "ordered": false,
+            };
+            if let Some(ref write_concern) = self.db.write_concern() {
+                command.insert("writeConcern", to_bson(write_concern)?);
+            }
+            let selection_criteria = self.db.selection_criteria().cloned();
+            let response = self.db.run_command(command, selection_criteria).await?;
+            // The command succeeds even if some of the updates failed, which is only reported in the response.
+            let failure = mongodb::bson::from_document::<BulkWriteFailure>(response)?;
+            if failure.write_errors.is_some() || failure.write_concern_error.is_some() {
+                return Err(ErrorKind::BulkWrite(failure).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get an [`OutputMetadata`] by [`OutputId`].
+    ///
+    /// If the output was pruned after it was spent, the metadata that is known from the block of the creating
+    /// transaction is returned instead, along with the index before which its kind was pruned.
+    pub async fn get_output_metadata(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputMetadataResult>, Error> {
+        if let Some(metadata) = self.get_stored_output_metadata(output_id, ledger_index).await? {
+            return Ok(Some(metadata));
+        }
+        Ok(self
+            .get_pruned_output(output_id, ledger_index)
+            .await?
+            .map(|res| res.metadata))
+    }
+
+    /// Get the [`OutputMetadataResult`]s of several outputs with a single query, in no particular order.
+    ///
+    /// Outputs that do not exist at the ledger index are left out. Pruned outputs are resolved as in
+    /// [`Self::get_output_metadata`], which takes a query per output, but is rare.
+    pub async fn get_outputs_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputMetadataResult>, Error> {
+        let mut results = self
+            .aggregate::<OutputMetadataResult>(
+                [
+                    doc! { "$match": {
+                        "_id": { "$in": output_ids },
+                        "metadata.booked.milestone_index": { "$lte": ledger_index }
+                    } },
+                    doc! { "$project": {
                         "output_id": "$_id",
                         "block_id": "$metadata.block_id",
                         "booked": "$metadata.booked",
                         "spent_metadata": "$metadata.spent_metadata",
-                    },
-                } },
-            ],
-            None,
-        )
-        .await?
-        .try_next()
-        .await
+                    } },
+                ],
+                None,
+            )
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        let mut found = results.iter().map(|res| res.output_id).collect::<HashSet<_>>();
+        for output_id in output_ids {
+            if found.insert(*output_id) {
+                if let Some(res) = self.get_pruned_output(output_id, ledger_index).await? {
+                    results.push(res.metadata);
+                }
+            }
+        }
+        Ok(results)
     }
 
-    /// Get an [`OutputMetadata`] by [`OutputId`].
-    pub async fn get_output_metadata(
+    /// Gets an output that is no longer stored, and its metadata, from the block of its creating transaction.
+    async fn get_pruned_output(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputWithMetadataResult>, Error> {
+        let created = match self.blocks.get_created_output(output_id, ledger_index).await? {
+            Some(created) => created,
+            None => return Ok(None),
+        };
+        // An output that was created but is no longer stored can only be missing because it was pruned.
+        let pruned_before = match self
+            .application_state
+            .get_output_pruning_index(created.output.kind())
+            .await?
+        {
+            Some(pruned_before) => pruned_before,
+            None => return Ok(None),
+        };
+        let milestone_timestamp = match self.milestones.get_milestone_timestamp(created.milestone_index).await? {
+            Some(milestone_timestamp) => milestone_timestamp,
+            None => return Ok(None),
+        };
+        Ok(Some(OutputWithMetadataResult {
+            output: created.output,
+            metadata: OutputMetadataResult {
+                output_id: *output_id,
+                block_id: created.block_id,
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: created.milestone_index,
+                    milestone_timestamp,
+                },
+                spent_metadata: None,
+                pruned_before: Some(pruned_before),
+            },
+        }))
+    }
+
+    async fn get_stored_output_metadata(
         &self,
         output_id: &OutputId,
         ledger_index: MilestoneIndex,
@@ -551,46 +897,144 @@ impl OutputCollection {
         &self,
         start_date: time::Date,
         end_date: time::Date,
-    ) -> Result<usize, Error> {
-        #[derive(Deserialize)]
-        struct Res {
-            count: usize,
-        }
-
-        let (start_timestamp, end_timestamp) = (
+    ) -> Result<AddressActivityByKind, Error> {
+        self.get_address_activity_in_range(
             MilestoneTimestamp::from(start_date.midnight().assume_utc()),
             MilestoneTimestamp::from(end_date.midnight().assume_utc()),
-        );
+        )
+        .await
+    }
 
+    /// Counts the distinct addresses of each kind that owned an output which was created or spent in the given time
+    /// range.
+    pub async fn get_address_activity_in_range(
+        &self,
+        start_timestamp: MilestoneTimestamp,
+        end_timestamp: MilestoneTimestamp,
+    ) -> Result<AddressActivityByKind, Error> {
+        let mut pipeline = vec![address_activity_match(start_timestamp, end_timestamp)];
+        pipeline.extend([
+            doc! { "$group": { "_id": "$details.address" } },
+            doc! { "$group": address_kind_counts(doc! { "_id": null }) },
+        ]);
         Ok(self
-            .aggregate::<Res>(
-                [
-                    doc! { "$match": { "$or": [
-                        { "metadata.booked.milestone_timestamp": {
-                            "$gte": start_timestamp,
-                            "$lt": end_timestamp
-                        } },
-                        { "metadata.spent_metadata.spent.milestone_timestamp": {
-                            "$gte": start_timestamp,
-                            "$lt": end_timestamp
-                        } },
-                    ] } },
-                    doc! { "$group": {
-                        "_id": "$details.address",
-                    } },
-                    doc! { "$group": {
-                        "_id": null,
-                        "count": { "$sum": 1 }
-                    } },
-                ],
-                None,
-            )
+            .aggregate::<AddressActivityByKind>(pipeline, AggregateOptions::builder().allow_disk_use(true).build())
             .await?
-            .map_ok(|r| r.count)
             .try_next()
             .await?
             .unwrap_or_default())
     }
+
+    /// Counts the distinct addresses of each kind that were active on each day of the given time range. Days without
+    /// any activity are omitted.
+    pub async fn get_daily_address_activity_in_range(
+        &self,
+        start_timestamp: MilestoneTimestamp,
+        end_timestamp: MilestoneTimestamp,
+    ) -> Result<Vec<DailyAddressActivity>, Error> {
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(rename = "_id")]
+            start_timestamp: MilestoneTimestamp,
+            #[serde(flatten)]
+            activity: AddressActivityByKind,
+        }
+
+        const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+        let mut pipeline = vec![address_activity_match(start_timestamp, end_timestamp)];
+        pipeline.extend([
+            // An output can make its owner active on two different days: when it was created and when it was spent.
+            doc! { "$project": {
+                "_id": 0,
+                "address": "$details.address",
+                "days": { "$setUnion": [{ "$map": {
+                    "input": { "$filter": {
+                        "input": [
+                            "$metadata.booked.milestone_timestamp",
+                            "$metadata.spent_metadata.spent.milestone_timestamp",
+                        ],
+                        "cond": { "$and": [
+                            { "$gte": [ "$$this", start_timestamp ] },
+                            { "$lt": [ "$$this", end_timestamp ] },
+                        ] },
+                    } },
+                    "in": { "$subtract": [ "$$this", { "$mod": [ "$$this", SECONDS_PER_DAY ] } ] },
+                } }] },
+            } },
+            doc! { "$unwind": "$days" },
+            doc! { "$group": { "_id": { "day": "$days", "address": "$address" } } },
+            doc! { "$replaceWith": "$_id" },
+            doc! { "$set": { "_id": "$address" } },
+            doc! { "$group": address_kind_counts(doc! { "_id": "$day" }) },
+            doc! { "$sort": { "_id": 1 } },
+        ]);
+        self.aggregate::<Res>(pipeline, AggregateOptions::builder().allow_disk_use(true).build())
+            .await?
+            .map_ok(|res| DailyAddressActivity {
+                start_timestamp: res.start_timestamp,
+                activity: res.activity,
+            })
+            .try_collect()
+            .await
+    }
+}
+
+/// Matches all outputs that were created or spent in the given time range.
+fn address_activity_match(start_timestamp: MilestoneTimestamp, end_timestamp: MilestoneTimestamp) -> Document {
+    doc! { "$match": { "$or": [
+        { "metadata.booked.milestone_timestamp": {
+            "$gte": start_timestamp,
+            "$lt": end_timestamp
+        } },
+        { "metadata.spent_metadata.spent.milestone_timestamp": {
+            "$gte": start_timestamp,
+            "$lt": end_timestamp
+        } },
+    ] } }
+}
+
+/// Extends a `$group` stage to count the documents by the kind of the address in their `_id` field. Documents without
+/// an address are not counted.
+fn address_kind_counts(mut group: Document) -> Document {
+    for (field, kind) in [
+        ("ed25519_count", "ed25519"),
+        ("alias_count", "alias"),
+        ("nft_count", "nft"),
+    ] {
+        group.insert(
+            field,
+            doc! { "$sum": { "$cond": [
+                { "$ne": [ { "$type": format!("$_id.{kind}") }, "missing" ] }, 1, 0
+            ] } },
+        );
+    }
+    group
+}
+
+/// The number of distinct active addresses of each kind.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct AddressActivityByKind {
+    pub ed25519_count: usize,
+    pub alias_count: usize,
+    pub nft_count: usize,
+}
+
+impl AddressActivityByKind {
+    /// The number of distinct active addresses of any kind.
+    pub fn total(&self) -> usize {
+        self.ed25519_count + self.alias_count + self.nft_count
+    }
+}
+
+/// The address activity within a single day.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyAddressActivity {
+    /// The timestamp at which the day starts.
+    pub start_timestamp: MilestoneTimestamp,
+    /// The number of distinct addresses of each kind that were active on that day.
+    pub activity: AddressActivityByKind,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -605,6 +1049,17 @@ pub struct AddressStat {
     pub balance: String,
 }
 
+/// The unspent basic and NFT outputs that carry a tag.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagStat {
+    /// The tag.
+    pub tag: Tag,
+    /// The number of outputs with the tag.
+    pub output_count: u64,
+    /// The total amount of the outputs with the tag.
+    pub total_amount: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TokenDistribution {
     pub distribution: Vec<DistributionStat>,
@@ -621,7 +1076,98 @@ pub struct DistributionStat {
     pub total_balance: String,
 }
 
+/// The horizons by which timelocked value is bucketed, as names and seconds after the time of measurement.
+pub const TIMELOCK_HORIZONS: [(&str, u32); 5] = [
+    ("day", 24 * 60 * 60),
+    ("week", 7 * 24 * 60 * 60),
+    ("month", 30 * 24 * 60 * 60),
+    ("quarter", 90 * 24 * 60 * 60),
+    ("year", 365 * 24 * 60 * 60),
+];
+
+/// The unspent outputs that unlock within a timelock horizon, but not within the previous one.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockedValueBucket {
+    /// The time after which the outputs unlock, exclusive.
+    pub unlocks_after: MilestoneTimestamp,
+    /// The time until which the outputs unlock, inclusive, or `None` for the bucket past the longest horizon.
+    pub unlocks_until: Option<MilestoneTimestamp>,
+    /// The number of outputs.
+    pub count: u64,
+    /// The total amount of the outputs.
+    pub amount: u64,
+}
+
+/// The base token transfer volume within a time bucket.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferVolumeBucket {
+    /// The timestamp at which the bucket starts.
+    pub start_timestamp: MilestoneTimestamp,
+    /// The total amount of tokens transferred.
+    pub volume: String,
+    /// The largest amount of tokens transferred by a single transaction.
+    pub max_transfer: String,
+    /// The number of transactions.
+    pub transaction_count: usize,
+}
+
 impl OutputCollection {
+    /// Samples up to `count` distinct addresses that own unspent outputs at the ledger index.
+    pub async fn get_sample_addresses(
+        &self,
+        ledger_index: MilestoneIndex,
+        count: usize,
+    ) -> Result<Vec<Address>, Error> {
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(rename = "_id")]
+            address: Address,
+        }
+
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "details.address": { "$exists": true },
+                    "metadata.booked.milestone_index": { "$lte": ledger_index },
+                    "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                } },
+                doc! { "$sample": { "size": count as i64 } },
+                doc! { "$group": { "_id": "$details.address" } },
+            ],
+            None,
+        )
+        .await?
+        .map_ok(|Res { address }| address)
+        .try_collect()
+        .await
+    }
+
+    /// Samples up to `count` ids of outputs that were created up to the ledger index.
+    pub async fn get_sample_output_ids(
+        &self,
+        ledger_index: MilestoneIndex,
+        count: usize,
+    ) -> Result<Vec<OutputId>, Error> {
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(rename = "_id")]
+            output_id: OutputId,
+        }
+
+        self.aggregate(
+            [
+                doc! { "$match": { "metadata.booked.milestone_index": { "$lte": ledger_index } } },
+                doc! { "$sample": { "size": count as i64 } },
+                doc! { "$project": { "_id": 1 } },
+            ],
+            None,
+        )
+        .await?
+        .map_ok(|Res { output_id }| output_id)
+        .try_collect()
+        .await
+    }
+
     /// Create richest address statistics.
     pub async fn get_richest_addresses(
         &self,
@@ -655,6 +1201,45 @@ impl OutputCollection {
         Ok(RichestAddresses { top })
     }
 
+    /// Gets the most frequent tags among the unspent basic and NFT outputs at the ledger index.
+    pub async fn get_top_tags(&self, ledger_index: MilestoneIndex, top: usize) -> Result<Vec<TagStat>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "output.kind": { "$in": ["basic", "nft"] },
+                    "output.features.kind": "tag",
+                    "metadata.booked.milestone_index": { "$lte": ledger_index },
+                    "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                } },
+                // An output carries at most one tag feature.
+                doc! { "$set": { "tag": { "$arrayElemAt": [
+                    { "$filter": {
+                        "input": "$output.features",
+                        "cond": { "$eq": [ "$$this.kind", "tag" ] },
+                    } },
+                    0,
+                ] } } },
+                doc! { "$group" : {
+                    "_id": "$tag.data",
+                    "output_count": { "$sum": 1 },
+                    "total_amount": { "$sum": { "$toDecimal": "$output.amount" } },
+                } },
+                doc! { "$sort": { "output_count": -1, "total_amount": -1, "_id": 1 } },
+                doc! { "$limit": top as i64 },
+                doc! { "$project": {
+                    "_id": 0,
+                    "tag": "$_id",
+                    "output_count": 1,
+                    "total_amount": { "$toString": "$total_amount" },
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
     /// Create token distribution statistics.
     pub async fn get_token_distribution(&self, ledger_index: MilestoneIndex) -> Result<TokenDistribution, Error> {
         let distribution = self
@@ -689,4 +1274,153 @@ impl OutputCollection {
             .await?;
         Ok(TokenDistribution { distribution })
     }
+
+    /// Gets the value that is still timelocked at the ledger index and the given time, with one bucket for each of the
+    /// [`TIMELOCK_HORIZONS`] and one for the outputs that unlock later.
+    pub async fn get_timelocked_value(
+        &self,
+        ledger_index: MilestoneIndex,
+        at: MilestoneTimestamp,
+    ) -> Result<Vec<TimelockedValueBucket>, Error> {
+        #[derive(Deserialize)]
+        struct Res {
+            #[serde(rename = "_id")]
+            lower_bound: i64,
+            count: u64,
+            amount: u64,
+        }
+
+        let bounds = std::iter::once(at.0 as i64)
+            .chain(
+                TIMELOCK_HORIZONS
+                    .iter()
+                    .map(|(_, horizon)| at.0 as i64 + *horizon as i64),
+            )
+            .collect::<Vec<_>>();
+        // Bucket boundaries are inclusive at the bottom, but the horizons are inclusive at the top.
+        let boundaries = bounds
+            .iter()
+            .map(|bound| bound + 1)
+            .chain(std::iter::once(u32::MAX as i64 + 1))
+            .collect::<Vec<_>>();
+        let mut res = self
+            .aggregate::<Res>(
+                [
+                    doc! { "$match": {
+                        "output.timelock_unlock_condition.timestamp": { "$gt": at },
+                        "metadata.booked.milestone_index": { "$lte": ledger_index },
+                        "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                    } },
+                    doc! { "$bucket": {
+                        "groupBy": "$output.timelock_unlock_condition.timestamp",
+                        "boundaries": boundaries,
+                        "output": {
+                            "count": { "$sum": 1 },
+                            "amount": { "$sum": { "$toLong": "$output.amount" } },
+                        },
+                    } },
+                ],
+                None,
+            )
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        Ok(bounds
+            .iter()
+            .enumerate()
+            .map(|(i, &bound)| {
+                let mut bucket = TimelockedValueBucket {
+                    unlocks_after: (bound as u32).into(),
+                    unlocks_until: bounds.get(i + 1).map(|&until| (until as u32).into()),
+                    ..Default::default()
+                };
+                if let Some(pos) = res.iter().position(|r| r.lower_bound == bound + 1) {
+                    let r = res.swap_remove(pos);
+                    bucket.count = r.count;
+                    bucket.amount = r.amount;
+                }
+                bucket
+            })
+            .collect())
+    }
+
+    /// Get the base token transfer volume between two timestamps, grouped into buckets of the given size.
+    ///
+    /// The volume of a transaction is computed like the base token activity analytics: tokens that are sent back to
+    /// an input address are not counted. Outputs that were not created by a transaction, such as migrated funds, are
+    /// ignored. Buckets without transactions are omitted.
+    pub async fn get_transfer_volume(
+        &self,
+        start_timestamp: MilestoneTimestamp,
+        end_timestamp: MilestoneTimestamp,
+        bucket_seconds: u32,
+    ) -> Result<impl Stream<Item = Result<TransferVolumeBucket, Error>>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "metadata.booked.milestone_timestamp": { "$gte": start_timestamp, "$lt": end_timestamp }
+                } },
+                doc! { "$project": {
+                    "_id": 0,
+                    "transaction_id": "$_id.transaction_id",
+                    "address": "$details.address",
+                    "amount": { "$toDecimal": "$output.amount" },
+                    "milestone_timestamp": "$metadata.booked.milestone_timestamp",
+                    "is_input": { "$literal": false },
+                } },
+                doc! { "$unionWith": {
+                    "coll": Self::NAME,
+                    "pipeline": [
+                        { "$match": {
+                            "metadata.spent_metadata.spent.milestone_timestamp": {
+                                "$gte": start_timestamp,
+                                "$lt": end_timestamp
+                            }
+                        } },
+                        { "$project": {
+                            "_id": 0,
+                            "transaction_id": "$metadata.spent_metadata.transaction_id",
+                            "address": "$details.address",
+                            "amount": { "$multiply": [ { "$toDecimal": "$output.amount" }, -1 ] },
+                            "milestone_timestamp": "$metadata.spent_metadata.spent.milestone_timestamp",
+                            "is_input": { "$literal": true },
+                        } },
+                    ],
+                } },
+                doc! { "$group": {
+                    "_id": { "transaction_id": "$transaction_id", "address": "$address" },
+                    "delta": { "$sum": "$amount" },
+                    "milestone_timestamp": { "$first": "$milestone_timestamp" },
+                    "has_inputs": { "$max": "$is_input" },
+                } },
+                doc! { "$group": {
+                    "_id": "$_id.transaction_id",
+                    "volume": { "$sum": { "$max": [ "$delta", 0 ] } },
+                    "milestone_timestamp": { "$first": "$milestone_timestamp" },
+                    "has_inputs": { "$max": "$has_inputs" },
+                } },
+                doc! { "$match": { "has_inputs": true } },
+                doc! { "$group": {
+                    "_id": { "$subtract": [
+                        "$milestone_timestamp",
+                        { "$mod": [ "$milestone_timestamp", bucket_seconds ] }
+                    ] },
+                    "volume": { "$sum": "$volume" },
+                    "max_transfer": { "$max": "$volume" },
+                    "transaction_count": { "$sum": 1 },
+                } },
+                doc! { "$sort": { "_id": 1 } },
+                doc! { "$project": {
+                    "_id": 0,
+                    "start_timestamp": "$_id",
+                    "volume": { "$toString": "$volume" },
+                    "max_transfer": { "$toString": "$max_transfer" },
+                    "transaction_count": 1,
+                } },
+            ],
+            None,
+        )
+        .await
+    }
 }
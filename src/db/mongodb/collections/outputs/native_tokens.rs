@@ -0,0 +1,70 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error};
+use primitive_types::U256;
+use serde::Deserialize;
+
+use super::OutputCollection;
+use crate::{
+    db::mongodb::MongoDbCollectionExt,
+    model::{
+        tangle::MilestoneIndex,
+        utxo::{Address, NativeToken, NativeTokenId},
+    },
+};
+
+#[derive(Deserialize)]
+struct NativeTokenHolding {
+    #[serde(default)]
+    address: Option<Address>,
+    #[serde(default)]
+    native_tokens: Vec<NativeToken>,
+}
+
+impl OutputCollection {
+    /// Sums the native tokens held by each address in the outputs that were unspent at the given ledger index, per
+    /// token id.
+    pub async fn get_native_token_balances(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<HashMap<NativeTokenId, HashMap<Address, U256>>, Error> {
+        let mut holdings = self
+            .aggregate::<NativeTokenHolding>(
+                [
+                    doc! { "$match": {
+                        "output.native_tokens": { "$exists": true, "$ne": [] },
+                        "metadata.booked.milestone_index": { "$lte": ledger_index },
+                        "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                    } },
+                    doc! { "$project": {
+                        "_id": 0,
+                        "address": "$details.address",
+                        "native_tokens": "$output.native_tokens",
+                    } },
+                ],
+                None,
+            )
+            .await?;
+
+        let mut balances = HashMap::<_, HashMap<_, U256>>::new();
+        while let Some(holding) = holdings.try_next().await? {
+            let address = match holding.address {
+                Some(address) => address,
+                None => continue,
+            };
+            for native_token in holding.native_tokens {
+                let balance = balances
+                    .entry(native_token.token_id)
+                    .or_default()
+                    .entry(address)
+                    .or_default();
+                *balance = balance.saturating_add(native_token.amount.into());
+            }
+        }
+        Ok(balances)
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::{Stream, TryStreamExt};
+use mongodb::{bson::doc, error::Error, options::FindOptions};
+use serde::Deserialize;
+use tracing::instrument;
+
+use super::OutputCollection;
+use crate::{
+    db::mongodb::MongoDbCollectionExt,
+    model::{tangle::MilestoneIndex, utxo::OutputId},
+};
+
+#[derive(Deserialize)]
+struct OutputIdResult {
+    #[serde(rename = "_id")]
+    output_id: OutputId,
+}
+
+impl OutputCollection {
+    /// Streams the ids of the outputs of the given kind that were spent before the given milestone.
+    pub async fn get_prunable_output_ids(
+        &self,
+        kind: &str,
+        before: MilestoneIndex,
+    ) -> Result<impl Stream<Item = Result<OutputId, Error>>, Error> {
+        Ok(self
+            .find::<OutputIdResult>(
+                doc! {
+                    "output.kind": kind,
+                    "metadata.spent_metadata.spent.milestone_index": { "$lt": before }
+                },
+                FindOptions::builder().projection(doc! { "_id": 1 }).build(),
+            )
+            .await?
+            .map_ok(|OutputIdResult { output_id }| output_id))
+    }
+
+    /// Deletes the outputs with the given ids and returns how many were deleted.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn delete_outputs(&self, output_ids: &[OutputId]) -> Result<u64, Error> {
+        Ok(self
+            .collection
+            .delete_many(doc! { "_id": { "$in": output_ids } }, None)
+            .await?
+            .deleted_count)
+    }
+}
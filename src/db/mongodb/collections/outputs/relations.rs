@@ -0,0 +1,185 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::TryStreamExt;
+use mongodb::{bson::doc, error::Error, options::FindOptions};
+use serde::{Deserialize, Serialize};
+
+use super::{IndexedId, OutputCollection, OutputDocument};
+use crate::{
+    db::mongodb::MongoDbCollectionExt,
+    model::{
+        tangle::MilestoneIndex,
+        utxo::{Address, AliasId, Feature, NftId, Output, OutputId},
+    },
+};
+
+/// The ways in which an address can relate to an alias, foundry or NFT.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntityRelation {
+    /// The address is the state controller of the alias.
+    StateController,
+    /// The address is the governor of the alias.
+    Governor,
+    /// The address issued the alias or NFT.
+    Issuer,
+    /// The address owns the NFT.
+    Owner,
+    /// The alias address controls the foundry.
+    Controller,
+}
+
+/// A relationship between an address and an entity, as recorded in the entity's current output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct EntityRelationship {
+    pub address: Address,
+    pub relation: EntityRelation,
+    pub entity: IndexedId,
+    pub output_id: OutputId,
+}
+
+/// Lists all relationships between the addresses in an output and the entity it represents.
+fn relationships(output_id: OutputId, output: &Output) -> Vec<EntityRelationship> {
+    let issuer = |features: &[Feature]| {
+        features.iter().find_map(|feature| match feature {
+            Feature::Issuer { address } => Some((*address, EntityRelation::Issuer)),
+            _ => None,
+        })
+    };
+    let (entity, addresses): (IndexedId, Vec<_>) = match output {
+        Output::Alias(output) => (
+            if output.alias_id == AliasId::implicit() {
+                AliasId::from(output_id)
+            } else {
+                output.alias_id
+            }
+            .into(),
+            [
+                Some((
+                    output.state_controller_address_unlock_condition.address,
+                    EntityRelation::StateController,
+                )),
+                Some((
+                    output.governor_address_unlock_condition.address,
+                    EntityRelation::Governor,
+                )),
+                issuer(&output.immutable_features),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        ),
+        Output::Nft(output) => (
+            if output.nft_id == NftId::implicit() {
+                NftId::from(output_id)
+            } else {
+                output.nft_id
+            }
+            .into(),
+            [
+                Some((output.address_unlock_condition.address, EntityRelation::Owner)),
+                issuer(&output.immutable_features),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        ),
+        Output::Foundry(output) => (
+            output.foundry_id.into(),
+            vec![(
+                output.immutable_alias_address_unlock_condition.address,
+                EntityRelation::Controller,
+            )],
+        ),
+        _ => return Vec::new(),
+    };
+    addresses
+        .into_iter()
+        .map(|(address, relation)| EntityRelationship {
+            address,
+            relation,
+            entity,
+            output_id,
+        })
+        .collect()
+}
+
+impl OutputCollection {
+    /// Gets the relationships that involve the given address at the ledger index. These are the aliases, foundries and
+    /// NFTs the address controls, owns or issued, and, if the address belongs to an alias or NFT, the addresses that
+    /// control or issued that entity.
+    pub async fn get_entity_relationships(
+        &self,
+        address: Address,
+        ledger_index: MilestoneIndex,
+        limit: usize,
+    ) -> Result<Vec<EntityRelationship>, Error> {
+        let own_id: Option<IndexedId> = match address {
+            Address::Alias(address) => Some(address.0.into()),
+            Address::Nft(address) => Some(address.0.into()),
+            Address::Ed25519(_) => None,
+        };
+        let mut conditions = vec![
+            doc! { "details.address": address },
+            doc! { "output.governor_address_unlock_condition.address": address },
+            doc! { "output.immutable_features": { "$elemMatch": { "kind": "issuer", "address": address } } },
+        ];
+        if let Some(id) = own_id {
+            conditions.push(doc! { "details.indexed_id": id });
+        }
+        self.find::<OutputDocument>(
+            doc! {
+                "output.kind": { "$in": ["alias", "foundry", "nft"] },
+                "metadata.booked.milestone_index": { "$lte": ledger_index },
+                "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } },
+                "$or": conditions,
+            },
+            FindOptions::builder()
+                .sort(doc! { "metadata.booked.milestone_index": -1 })
+                .limit(limit as i64)
+                .build(),
+        )
+        .await?
+        .map_ok(|doc| {
+            relationships(doc.output_id, &doc.output)
+                .into_iter()
+                .filter(|r| r.address == address || Some(r.entity) == own_id)
+                .collect::<Vec<_>>()
+        })
+        .try_concat()
+        .await
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use super::*;
+    use crate::model::utxo::{AliasOutput, FoundryOutput};
+
+    #[test]
+    fn alias_and_foundry_relationships() {
+        let output_id = OutputId::rand();
+        let mut alias = AliasOutput::rand(&iota_sdk::types::block::protocol::protocol_parameters());
+        alias.alias_id = AliasId::implicit();
+        let relations = relationships(output_id, &Output::Alias(alias.clone()));
+        assert_eq!(
+            relations.iter().map(|r| r.relation).take(2).collect::<Vec<_>>(),
+            [EntityRelation::StateController, EntityRelation::Governor]
+        );
+        assert!(relations
+            .iter()
+            .all(|r| r.entity == IndexedId::Alias(AliasId::from(output_id))));
+        assert_eq!(
+            relations[0].address,
+            alias.state_controller_address_unlock_condition.address
+        );
+
+        let foundry = FoundryOutput::rand(&iota_sdk::types::block::protocol::protocol_parameters());
+        let relations = relationships(output_id, &Output::Foundry(foundry.clone()));
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].relation, EntityRelation::Controller);
+        assert_eq!(relations[0].entity, IndexedId::Foundry(foundry.foundry_id));
+    }
+}
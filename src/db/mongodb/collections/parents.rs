@@ -0,0 +1,198 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::{Stream, TryStreamExt};
+use mongodb::{
+    bson::doc,
+    error::Error,
+    options::{FindOptions, IndexOptions, InsertManyOptions},
+    IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::{metadata::BlockMetadata, tangle::MilestoneIndex, BlockId},
+};
+
+/// The [`Id`] of a [`ParentsDocument`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct Id {
+    parent_id: BlockId,
+    child_id: BlockId,
+}
+
+/// A parent-child relationship between two blocks.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ParentsDocument {
+    _id: Id,
+    /// The milestone index that referenced the child.
+    milestone_index: MilestoneIndex,
+}
+
+/// The stardust parents collection, which maps every block to its children.
+pub struct ParentsCollection {
+    collection: mongodb::Collection<ParentsDocument>,
+}
+
+#[async_trait::async_trait]
+impl MongoDbCollection for ParentsCollection {
+    const NAME: &'static str = "stardust_parents";
+    type Document = ParentsDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+
+    async fn create_indexes(&self) -> Result<(), Error> {
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "_id.parent_id": 1, "milestone_index": -1, "_id.child_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("parent_children_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "_id.child_id": 1 })
+                .options(IndexOptions::builder().name("child_parents_index".to_string()).build())
+                .build(),
+            None,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Creates a document for every edge between the given blocks and their parents.
+fn relationships<'a>(blocks: impl IntoIterator<Item = (BlockId, &'a BlockMetadata)>) -> Vec<ParentsDocument> {
+    blocks
+        .into_iter()
+        .flat_map(|(child_id, metadata)| {
+            metadata.parents.iter().map(move |&parent_id| ParentsDocument {
+                _id: Id { parent_id, child_id },
+                milestone_index: metadata.referenced_by_milestone_index,
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct ChildResult {
+    #[serde(rename = "_id")]
+    id: Id,
+}
+
+impl ParentsCollection {
+    /// Inserts the relationships between the given blocks and their parents.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn insert_relationships<'a, I>(&self, blocks: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (BlockId, &'a BlockMetadata)>,
+        I::IntoIter: Send + Sync,
+    {
+        let relationships = relationships(blocks);
+        if !relationships.is_empty() {
+            self.insert_many_ignore_duplicates(relationships, InsertManyOptions::builder().ordered(false).build())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the children of a block, most recently referenced first.
+    pub async fn get_block_children(
+        &self,
+        block_id: &BlockId,
+        page_size: usize,
+        page: usize,
+    ) -> Result<impl Stream<Item = Result<BlockId, Error>>, Error> {
+        Ok(self
+            .find::<ChildResult>(
+                doc! { "_id.parent_id": block_id },
+                FindOptions::builder()
+                    .sort(doc! { "milestone_index": -1, "_id.child_id": 1 })
+                    .skip((page_size * page) as u64)
+                    .limit(page_size as i64)
+                    .projection(doc! { "_id": 1 })
+                    .build(),
+            )
+            .await?
+            .map_ok(|ChildResult { id }| id.child_id))
+    }
+
+    /// Deletes the relationships of the given blocks to their parents.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn delete_relationships(&self, child_ids: &[BlockId]) -> Result<(), Error> {
+        self.collection
+            .delete_many(doc! { "_id.child_id": { "$in": child_ids } }, None)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use mongodb::bson::{self, Bson};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn one_document_per_parent() {
+        let blocks = [
+            (BlockId::rand(), BlockMetadata::rand()),
+            (BlockId::rand(), BlockMetadata::rand()),
+        ];
+        let documents = relationships(blocks.iter().map(|(block_id, metadata)| (*block_id, metadata)));
+        assert_eq!(
+            documents.len(),
+            blocks.iter().map(|(_, metadata)| metadata.parents.len()).sum::<usize>()
+        );
+        for (child_id, metadata) in &blocks {
+            let parents = documents
+                .iter()
+                .filter(|document| document._id.child_id == *child_id)
+                .inspect(|document| assert_eq!(document.milestone_index, metadata.referenced_by_milestone_index))
+                .map(|document| document._id.parent_id)
+                .collect::<Vec<_>>();
+            assert_eq!(parents, metadata.parents.to_vec());
+        }
+    }
+
+    #[test]
+    fn documents_match_the_indexed_fields() {
+        let metadata = BlockMetadata::rand();
+        let child_id = BlockId::rand();
+        let document = bson::to_document(&relationships([(child_id, &metadata)])[0]).unwrap();
+        assert_eq!(
+            document.get_document("_id").unwrap().get("parent_id"),
+            Some(&Bson::from(metadata.parents[0]))
+        );
+        assert_eq!(
+            document.get_document("_id").unwrap().get("child_id"),
+            Some(&Bson::from(child_id))
+        );
+        // The integer width depends on the index, so the value is compared instead.
+        assert_eq!(
+            bson::from_bson::<MilestoneIndex>(document.get("milestone_index").unwrap().clone()).unwrap(),
+            metadata.referenced_by_milestone_index
+        );
+    }
+}
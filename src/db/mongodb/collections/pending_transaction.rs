@@ -0,0 +1,202 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use futures::Stream;
+use mongodb::{
+    bson::{doc, DateTime},
+    error::Error,
+    options::{FindOptions, IndexOptions, InsertManyOptions},
+    IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::{
+        payload::{TransactionId, TransactionPayload},
+        BlockId,
+    },
+};
+
+/// A transaction that the node has seen but that has not been referenced by a milestone yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingTransactionDocument {
+    #[serde(rename = "_id")]
+    transaction_id: TransactionId,
+    block_id: BlockId,
+    payload: TransactionPayload,
+    received_at: DateTime,
+    /// The time after which MongoDB removes the document, in case it is never referenced.
+    expires_at: DateTime,
+}
+
+impl PendingTransactionDocument {
+    fn new(block_id: BlockId, payload: TransactionPayload, received_at: DateTime, ttl: Duration) -> Self {
+        Self {
+            transaction_id: payload.transaction_id,
+            block_id,
+            payload,
+            received_at,
+            expires_at: DateTime::from_millis(
+                received_at
+                    .timestamp_millis()
+                    .saturating_add(ttl.as_millis().try_into().unwrap_or(i64::MAX)),
+            ),
+        }
+    }
+}
+
+/// A pending transaction as returned by queries.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[allow(missing_docs)]
+pub struct PendingTransactionResult {
+    #[serde(rename = "_id")]
+    pub transaction_id: TransactionId,
+    pub block_id: BlockId,
+    pub payload: TransactionPayload,
+    pub received_at: DateTime,
+}
+
+/// The stardust pending transactions collection, which holds the transactions seen by the node until a milestone
+/// references them.
+pub struct PendingTransactionCollection {
+    collection: mongodb::Collection<PendingTransactionDocument>,
+}
+
+#[async_trait::async_trait]
+impl MongoDbCollection for PendingTransactionCollection {
+    const NAME: &'static str = "stardust_pending_transactions";
+    type Document = PendingTransactionDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+
+    async fn create_indexes(&self) -> Result<(), Error> {
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::ZERO)
+                        .name("pending_transaction_expiry_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "received_at": -1, "_id": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("pending_transaction_received_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl PendingTransactionCollection {
+    /// Records a transaction that was just seen by the node. It is removed once it is referenced by a milestone, or
+    /// after `ttl` has passed otherwise.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn insert_pending_transaction(
+        &self,
+        block_id: BlockId,
+        payload: TransactionPayload,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        // A transaction can be attached to several blocks, in which case the first one that was seen is kept.
+        self.insert_many_ignore_duplicates(
+            [PendingTransactionDocument::new(block_id, payload, DateTime::now(), ttl)],
+            InsertManyOptions::builder().ordered(false).build(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the given transactions, because a milestone referenced them.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn remove_transactions(
+        &self,
+        transaction_ids: impl IntoIterator<Item = TransactionId>,
+    ) -> Result<(), Error> {
+        let transaction_ids = transaction_ids.into_iter().collect::<Vec<_>>();
+        if !transaction_ids.is_empty() {
+            self.collection
+                .delete_many(doc! { "_id": { "$in": transaction_ids } }, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets a pending transaction by its id.
+    pub async fn get_pending_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<PendingTransactionResult>, Error> {
+        // Expired documents are only removed periodically by MongoDB, so they have to be filtered explicitly.
+        self.find_one(
+            doc! { "_id": transaction_id, "expires_at": { "$gt": DateTime::now() } },
+            None,
+        )
+        .await
+    }
+
+    /// Gets a page of the pending transactions, most recently received first.
+    pub async fn get_pending_transactions(
+        &self,
+        page_size: usize,
+        page: usize,
+    ) -> Result<impl Stream<Item = Result<PendingTransactionResult, Error>>, Error> {
+        self.find::<PendingTransactionResult>(
+            doc! { "expires_at": { "$gt": DateTime::now() } },
+            FindOptions::builder()
+                .sort(doc! { "received_at": -1, "_id": 1 })
+                .skip((page_size * page) as u64)
+                .limit(page_size as i64)
+                .build(),
+        )
+        .await
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn pending_transaction_expiry() {
+        let payload = TransactionPayload::rand(&iota_sdk::types::block::protocol::protocol_parameters());
+        let received_at = DateTime::from_millis(1_000);
+        let document =
+            PendingTransactionDocument::new(BlockId::rand(), payload.clone(), received_at, Duration::from_secs(300));
+        assert_eq!(document.transaction_id, payload.transaction_id);
+        assert_eq!(document.expires_at, DateTime::from_millis(301_000));
+
+        let document = PendingTransactionDocument::new(BlockId::rand(), payload, received_at, Duration::MAX);
+        assert_eq!(document.expires_at, DateTime::MAX);
+    }
+}
@@ -1,10 +1,12 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use futures::TryStreamExt;
 use mongodb::{
     bson::doc,
     error::Error,
-    options::{FindOneOptions, UpdateOptions},
+    options::{FindOneOptions, FindOptions, UpdateOptions},
+    ClientSession,
 };
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +26,16 @@ pub struct ProtocolUpdateDocument {
     pub parameters: ProtocolParameters,
 }
 
+/// The range of milestones during which a set of protocol parameters was in effect.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub struct ProtocolPeriod {
+    pub start_index: MilestoneIndex,
+    /// The last milestone of the period, if the parameters have been changed since.
+    pub end_index: Option<MilestoneIndex>,
+    pub parameters: ProtocolParameters,
+}
+
 /// The stardust protocol parameters collection.
 pub struct ProtocolUpdateCollection {
     collection: mongodb::Collection<ProtocolUpdateDocument>,
@@ -61,6 +73,25 @@ impl ProtocolUpdateCollection {
         .await
     }
 
+    /// Gets the period of the protocol parameters that were in effect at the given milestone index.
+    pub async fn get_protocol_period(&self, milestone_index: MilestoneIndex) -> Result<Option<ProtocolPeriod>, Error> {
+        let update = match self.get_protocol_parameters_for_ledger_index(milestone_index).await? {
+            Some(update) => update,
+            None => return Ok(None),
+        };
+        let next = self
+            .find_one::<ProtocolUpdateDocument>(
+                doc! { "_id": { "$gt": milestone_index } },
+                FindOneOptions::builder().sort(doc! { "_id": 1 }).build(),
+            )
+            .await?;
+        Ok(Some(ProtocolPeriod {
+            start_index: update.tangle_index,
+            end_index: next.map(|next| next.tangle_index - 1),
+            parameters: update.parameters,
+        }))
+    }
+
     /// Gets the protocol parameters for the given milestone index, if they were changed.
     pub async fn get_protocol_parameters_for_milestone_index(
         &self,
@@ -77,20 +108,47 @@ impl ProtocolUpdateCollection {
         self.find_one(doc! { "parameters.version": version as i32 }, None).await
     }
 
+    /// Gets every change of the protocol parameters in ascending milestone order.
+    pub async fn get_protocol_updates(&self) -> Result<Vec<ProtocolUpdateDocument>, Error> {
+        self.find::<ProtocolUpdateDocument>(doc! {}, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+            .await?
+            .try_collect()
+            .await
+    }
+
     /// Add the protocol parameters to the list if the protocol parameters have changed.
     pub async fn upsert_protocol_parameters(
         &self,
         ledger_index: MilestoneIndex,
         parameters: ProtocolParameters,
     ) -> Result<(), Error> {
-        let params = self.get_protocol_parameters_for_ledger_index(ledger_index).await?;
+        self.upsert_protocol_parameters_with_session(ledger_index, parameters, None)
+            .await
+    }
+
+    /// Add the protocol parameters to the list if the protocol parameters have changed, as part of the transaction of
+    /// the session if one is given.
+    pub async fn upsert_protocol_parameters_with_session(
+        &self,
+        ledger_index: MilestoneIndex,
+        parameters: ProtocolParameters,
+        mut session: Option<&mut ClientSession>,
+    ) -> Result<(), Error> {
+        let params = self
+            .find_one_with_session::<ProtocolUpdateDocument>(
+                doc! { "_id": { "$lte": ledger_index } },
+                FindOneOptions::builder().sort(doc! { "_id": -1 }).build(),
+                session.as_deref_mut(),
+            )
+            .await?;
         if !matches!(params, Some(params) if params.parameters == parameters) {
-            self.update_one(
+            self.update_one_with_session(
                 doc! { "_id": ledger_index },
                 doc! { "$set": {
                     "parameters": mongodb::bson::to_bson(&parameters)?
                 } },
                 UpdateOptions::builder().upsert(true).build(),
+                session,
             )
             .await?;
         }
@@ -0,0 +1,123 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mongodb::{
+    bson::{doc, DateTime},
+    error::Error,
+    options::{IndexOptions, ReplaceOptions},
+    IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+
+/// The computed result of a query at a ledger index.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryCacheDocument {
+    /// The route and the parameters of the query.
+    #[serde(rename = "_id")]
+    key: String,
+    /// The ledger index the result was computed at.
+    ledger_index: MilestoneIndex,
+    /// The result, as JSON.
+    result: String,
+    /// The time after which the result is no longer served and MongoDB removes the document.
+    expires_at: DateTime,
+}
+
+/// The query cache collection, which holds the results of expensive queries until the ledger index advances or they
+/// expire.
+///
+/// Only the latest result of a query is kept. A result is only served at the ledger index it was computed at, so it is
+/// invalidated as soon as the next milestone is synced and replaced once the query is run again.
+pub struct QueryCacheCollection {
+    collection: mongodb::Collection<QueryCacheDocument>,
+}
+
+#[async_trait::async_trait]
+impl MongoDbCollection for QueryCacheCollection {
+    const NAME: &'static str = "query_cache";
+    type Document = QueryCacheDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+
+    async fn create_indexes(&self) -> Result<(), Error> {
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "expires_at": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::ZERO)
+                        .name("query_cache_expiry_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl QueryCacheCollection {
+    /// Gets the result of a query, if it was computed at the given ledger index and has not expired yet.
+    #[instrument(skip(self), err, level = "trace")]
+    pub async fn get_result(&self, key: &str, ledger_index: MilestoneIndex) -> Result<Option<String>, Error> {
+        Ok(self
+            .find_one::<QueryCacheDocument>(
+                doc! {
+                    "_id": key,
+                    "ledger_index": ledger_index,
+                    // MongoDB only removes expired documents about once a minute.
+                    "expires_at": { "$gt": DateTime::now() },
+                },
+                None,
+            )
+            .await?
+            .map(|document| document.result))
+    }
+
+    /// Stores the result of a query computed at the given ledger index, replacing the previous one.
+    #[instrument(skip(self, result), err, level = "trace")]
+    pub async fn upsert_result(
+        &self,
+        key: &str,
+        ledger_index: MilestoneIndex,
+        result: String,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        let now = DateTime::now();
+        self.replace_one(
+            doc! { "_id": key },
+            QueryCacheDocument {
+                key: key.to_string(),
+                ledger_index,
+                result,
+                expires_at: DateTime::from_millis(
+                    now.timestamp_millis()
+                        .saturating_add(ttl.as_millis().try_into().unwrap_or(i64::MAX)),
+                ),
+            },
+            ReplaceOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::Stream;
+use mongodb::{
+    bson::doc,
+    error::Error,
+    options::{FindOptions, ReplaceOptions},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::SortOrder;
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::{
+        tangle::{MilestoneIndex, MilestoneTimestamp},
+        utxo::Address,
+    },
+};
+
+/// The anomalous patterns a milestone was flagged for.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpamFlags {
+    /// The milestone referenced far more blocks than the milestones before it.
+    pub block_spike: bool,
+    /// Many of the referenced blocks carried the same payload.
+    pub duplicate_payloads: bool,
+    /// Most of the transactions were issued by the same address.
+    pub issuer_dominance: bool,
+}
+
+impl SpamFlags {
+    /// Whether any pattern was flagged.
+    pub fn any(&self) -> bool {
+        self.block_spike || self.duplicate_payloads || self.issuer_dominance
+    }
+}
+
+/// A milestone that was flagged by the spam detection, along with the statistics it was flagged for.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpamFlagDocument {
+    #[serde(rename = "_id")]
+    pub milestone_index: MilestoneIndex,
+    pub milestone_timestamp: MilestoneTimestamp,
+    pub flags: SpamFlags,
+    pub block_count: u64,
+    /// The average number of blocks of the milestones before, if there were enough of them.
+    pub baseline_block_count: Option<f64>,
+    /// The number of blocks that carried the most common payload.
+    pub duplicate_payload_count: u64,
+    pub transaction_count: u64,
+    /// The address that issued the most transactions.
+    pub top_issuer: Option<Address>,
+    pub top_issuer_transaction_count: u64,
+}
+
+/// The spam flags collection, which holds the milestones that were flagged by the spam detection.
+pub struct SpamFlagCollection {
+    collection: mongodb::Collection<SpamFlagDocument>,
+}
+
+impl MongoDbCollection for SpamFlagCollection {
+    const NAME: &'static str = "spam_flags";
+    type Document = SpamFlagDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl SpamFlagCollection {
+    /// Stores a flagged milestone, replacing the flags of a previous run over the same milestone.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn upsert_flags(&self, document: SpamFlagDocument) -> Result<(), Error> {
+        self.replace_one(
+            doc! { "_id": document.milestone_index },
+            document,
+            ReplaceOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes the flags of a previous run over a milestone that is no longer flagged.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn delete_flags(&self, milestone_index: MilestoneIndex) -> Result<(), Error> {
+        self.collection
+            .delete_one(doc! { "_id": milestone_index }, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Gets the flagged milestones within a range of milestone indexes.
+    pub async fn get_flagged_milestones(
+        &self,
+        start_index: Option<MilestoneIndex>,
+        end_index: Option<MilestoneIndex>,
+        order: SortOrder,
+        page_size: usize,
+        cursor: Option<MilestoneIndex>,
+    ) -> Result<impl Stream<Item = Result<SpamFlagDocument, Error>>, Error> {
+        let (sort, cmp) = match order {
+            SortOrder::Newest => (doc! { "_id": -1 }, "$gt"),
+            SortOrder::Oldest => (doc! { "_id": 1 }, "$lt"),
+        };
+
+        self.find(
+            doc! {
+                "$nor": [
+                    { "_id": { "$lt": start_index } },
+                    { "_id": { "$gt": end_index } },
+                    { "_id": { cmp: cursor } },
+                ]
+            },
+            FindOptions::builder().sort(sort).limit(page_size as i64).build(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use mongodb::{
+    bson::{doc, DateTime},
+    error::Error,
+    options::FindOneOptions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::tangle::MilestoneIndex,
+};
+
+const MILLIS_PER_DAY: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// The number of documents in a collection and the storage it uses, including its indexes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct CollectionStorage {
+    pub name: String,
+    pub count: u64,
+    pub size: u64,
+}
+
+/// The storage of a collection that is allocated but unused, which MongoDB only returns to the operating system once
+/// the collection is compacted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct CollectionFragmentation {
+    pub name: String,
+    pub storage_size: u64,
+    pub free_storage_size: u64,
+}
+
+/// The storage used by every collection at a point in time.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageSample {
+    /// The time the sample was taken.
+    #[serde(rename = "_id")]
+    pub sampled_at: DateTime,
+    /// The ledger index at the time the sample was taken, if any milestone was synced.
+    pub ledger_index: Option<MilestoneIndex>,
+    /// The storage used by every collection.
+    pub collections: Vec<CollectionStorage>,
+}
+
+/// How fast a collection grew between two samples.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollectionGrowth {
+    /// The storage of the collection in the later sample.
+    pub storage: CollectionStorage,
+    /// The documents added per milestone, if the ledger index advanced between the samples.
+    pub documents_per_milestone: Option<f64>,
+    /// The bytes added per milestone, if the ledger index advanced between the samples.
+    pub bytes_per_milestone: Option<f64>,
+    /// The documents added per day.
+    pub documents_per_day: f64,
+    /// The bytes added per day.
+    pub bytes_per_day: f64,
+}
+
+impl CollectionGrowth {
+    /// Projects the number of documents and the storage size of the collection after `horizon`, assuming the growth
+    /// stays the same.
+    pub fn project(&self, horizon: Duration) -> CollectionStorage {
+        let days = horizon.as_millis() as f64 / MILLIS_PER_DAY;
+        let project = |value: u64, per_day: f64| (value as f64 + per_day * days).max(0.0) as u64;
+        CollectionStorage {
+            name: self.storage.name.clone(),
+            count: project(self.storage.count, self.documents_per_day),
+            size: project(self.storage.size, self.bytes_per_day),
+        }
+    }
+}
+
+impl StorageSample {
+    /// Computes the growth of every collection since an earlier sample. Collections that did not exist in the
+    /// earlier sample grew from nothing.
+    pub fn growth_since(&self, earlier: &StorageSample) -> Vec<CollectionGrowth> {
+        let days = (self.sampled_at.timestamp_millis() - earlier.sampled_at.timestamp_millis()) as f64 / MILLIS_PER_DAY;
+        let milestones = match (earlier.ledger_index, self.ledger_index) {
+            (Some(start), Some(end)) if end > start => Some((end.0 - start.0) as f64),
+            _ => None,
+        };
+        self.collections
+            .iter()
+            .map(|storage| {
+                let (count, size) = earlier
+                    .collections
+                    .iter()
+                    .find(|earlier| earlier.name == storage.name)
+                    .map_or((0, 0), |earlier| (earlier.count, earlier.size));
+                let documents = storage.count as f64 - count as f64;
+                let bytes = storage.size as f64 - size as f64;
+                let per_day = |delta: f64| if days > 0.0 { delta / days } else { 0.0 };
+                CollectionGrowth {
+                    storage: storage.clone(),
+                    documents_per_milestone: milestones.map(|milestones| documents / milestones),
+                    bytes_per_milestone: milestones.map(|milestones| bytes / milestones),
+                    documents_per_day: per_day(documents),
+                    bytes_per_day: per_day(bytes),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The storage samples collection, which holds the periodically sampled storage usage of every collection.
+pub struct StorageSampleCollection {
+    collection: mongodb::Collection<StorageSample>,
+}
+
+impl MongoDbCollection for StorageSampleCollection {
+    const NAME: &'static str = "storage_samples";
+    type Document = StorageSample;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl StorageSampleCollection {
+    /// Inserts a sample.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn insert_sample(&self, sample: &StorageSample) -> Result<(), Error> {
+        self.insert_one::<StorageSample>(sample, None).await?;
+        Ok(())
+    }
+
+    /// Gets the most recent sample.
+    pub async fn get_latest_sample(&self) -> Result<Option<StorageSample>, Error> {
+        self.find_one(doc! {}, FindOneOptions::builder().sort(doc! { "_id": -1 }).build())
+            .await
+    }
+
+    /// Gets the most recent sample taken at or before the given time, or the oldest sample if all of them were taken
+    /// later.
+    pub async fn get_sample_since(&self, time: DateTime) -> Result<Option<StorageSample>, Error> {
+        Ok(
+            match self
+                .find_one(
+                    doc! { "_id": { "$lte": time } },
+                    FindOneOptions::builder().sort(doc! { "_id": -1 }).build(),
+                )
+                .await?
+            {
+                Some(sample) => Some(sample),
+                None => {
+                    self.find_one(doc! {}, FindOneOptions::builder().sort(doc! { "_id": 1 }).build())
+                        .await?
+                }
+            },
+        )
+    }
+
+    /// Removes the samples taken before the given time.
+    pub async fn delete_samples_before(&self, time: DateTime) -> Result<(), Error> {
+        self.collection
+            .delete_many(doc! { "_id": { "$lt": time } }, None)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn sample(day: i64, ledger_index: u32, collections: &[(&str, u64, u64)]) -> StorageSample {
+        StorageSample {
+            sampled_at: DateTime::from_millis(day * MILLIS_PER_DAY as i64),
+            ledger_index: Some(ledger_index.into()),
+            collections: collections
+                .iter()
+                .map(|&(name, count, size)| CollectionStorage {
+                    name: name.to_string(),
+                    count,
+                    size,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn growth_and_projection() {
+        let earlier = sample(
+            1,
+            100,
+            &[("stardust_blocks", 1000, 10_000), ("stardust_outputs", 500, 8_000)],
+        );
+        let later = sample(
+            3,
+            300,
+            &[
+                ("stardust_blocks", 3000, 30_000),
+                ("stardust_outputs", 400, 6_000),
+                ("storage_samples", 10, 100),
+            ],
+        );
+        let growth = later.growth_since(&earlier);
+        assert_eq!(growth.len(), 3);
+
+        assert_eq!(growth[0].documents_per_milestone, Some(10.0));
+        assert_eq!(growth[0].bytes_per_milestone, Some(100.0));
+        assert_eq!(growth[0].bytes_per_day, 10_000.0);
+        assert_eq!(
+            growth[0].project(Duration::from_secs(10 * 24 * 60 * 60)),
+            CollectionStorage {
+                name: "stardust_blocks".to_string(),
+                count: 13_000,
+                size: 130_000,
+            }
+        );
+
+        // Shrinking collections are never projected below zero.
+        assert_eq!(growth[1].documents_per_day, -50.0);
+        assert_eq!(growth[1].project(Duration::from_secs(365 * 24 * 60 * 60)).size, 0);
+
+        assert_eq!(growth[2].documents_per_day, 5.0);
+
+        let same = later.growth_since(&later);
+        assert_eq!(same[0].documents_per_milestone, None);
+        assert_eq!(same[0].bytes_per_day, 0.0);
+    }
+}
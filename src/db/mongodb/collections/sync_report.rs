@@ -0,0 +1,194 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use mongodb::{
+    bson::{doc, DateTime},
+    error::Error,
+    options::ReplaceOptions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::tangle::{MilestoneIndex, MilestoneTimestamp},
+};
+
+/// The time spent in each phase of syncing a milestone, in milliseconds.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct SyncReportPhases {
+    pub receive: u64,
+    pub output_insert: u64,
+    pub cone_receive: u64,
+    pub block_insert: u64,
+    pub analytics: u64,
+    pub checkpoint: u64,
+}
+
+impl SyncReportPhases {
+    /// The time spent syncing the milestone once it was received, in milliseconds.
+    pub fn total(&self) -> u64 {
+        self.output_insert + self.cone_receive + self.block_insert + self.analytics + self.checkpoint
+    }
+}
+
+/// The documents written to a collection while syncing a milestone.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionWrites {
+    /// The name of the collection.
+    pub name: String,
+    /// The number of documents that were inserted or updated.
+    pub written: u64,
+    /// The number of documents that were dropped because they already existed.
+    pub duplicates: u64,
+}
+
+/// A report of how a milestone was synced, kept to find out after the fact why syncing it was slow.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// The index of the synced milestone.
+    #[serde(rename = "_id")]
+    pub milestone_index: MilestoneIndex,
+    /// The timestamp of the synced milestone.
+    pub milestone_timestamp: MilestoneTimestamp,
+    /// The time syncing the milestone finished.
+    pub synced_at: DateTime,
+    /// Whether the milestone was synced in catch-up mode.
+    pub catching_up: bool,
+    /// The time spent in each phase.
+    pub phases: SyncReportPhases,
+    /// The documents written to each collection.
+    pub collections: Vec<CollectionWrites>,
+    /// How often the checkpoint transaction was attempted.
+    pub checkpoint_attempts: u32,
+    /// The warnings raised while syncing the milestone.
+    pub warnings: Vec<String>,
+}
+
+impl SyncReport {
+    /// Adds written documents to the entry of a collection. Collections without any writes are left out.
+    pub fn add_written(&mut self, collection: &str, count: u64) {
+        if count > 0 {
+            self.entry(collection).written += count;
+        }
+    }
+
+    /// Adds dropped duplicates to the entry of a collection.
+    pub fn add_duplicates(&mut self, collection: &str, count: u64) {
+        if count > 0 {
+            self.entry(collection).duplicates += count;
+        }
+    }
+
+    fn entry(&mut self, collection: &str) -> &mut CollectionWrites {
+        match self.collections.iter().position(|writes| writes.name == collection) {
+            Some(index) => &mut self.collections[index],
+            None => {
+                self.collections.push(CollectionWrites {
+                    name: collection.to_string(),
+                    written: 0,
+                    duplicates: 0,
+                });
+                self.collections.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// The sync reports collection, which holds a report for every recently synced milestone.
+pub struct SyncReportCollection {
+    collection: mongodb::Collection<SyncReport>,
+}
+
+impl MongoDbCollection for SyncReportCollection {
+    const NAME: &'static str = "sync_reports";
+    type Document = SyncReport;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl SyncReportCollection {
+    /// Stores the report of a milestone, replacing the report of an earlier attempt to sync it.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn upsert_report(&self, report: &SyncReport) -> Result<(), Error> {
+        self.replace_one::<SyncReport>(
+            doc! { "_id": report.milestone_index },
+            report,
+            ReplaceOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the report of a milestone.
+    pub async fn get_report(&self, milestone_index: MilestoneIndex) -> Result<Option<SyncReport>, Error> {
+        self.find_one(doc! { "_id": milestone_index }, None).await
+    }
+
+    /// Removes the reports of the milestones before the given index.
+    pub async fn delete_reports_before(&self, milestone_index: MilestoneIndex) -> Result<(), Error> {
+        self.collection
+            .delete_many(doc! { "_id": { "$lt": milestone_index } }, None)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn collection_entries() {
+        let mut report = SyncReport {
+            milestone_index: 10.into(),
+            milestone_timestamp: 1000.into(),
+            synced_at: DateTime::from_millis(0),
+            catching_up: false,
+            phases: SyncReportPhases {
+                receive: 100,
+                output_insert: 5,
+                cone_receive: 20,
+                block_insert: 10,
+                analytics: 0,
+                checkpoint: 2,
+            },
+            collections: Vec::new(),
+            checkpoint_attempts: 1,
+            warnings: Vec::new(),
+        };
+        report.add_written("stardust_blocks", 10);
+        report.add_written("stardust_outputs", 4);
+        report.add_written("stardust_treasury", 0);
+        report.add_duplicates("stardust_blocks", 2);
+        report.add_written("stardust_blocks", 5);
+        assert_eq!(
+            report.collections,
+            vec![
+                CollectionWrites {
+                    name: "stardust_blocks".to_string(),
+                    written: 15,
+                    duplicates: 2,
+                },
+                CollectionWrites {
+                    name: "stardust_outputs".to_string(),
+                    written: 4,
+                    duplicates: 0,
+                },
+            ]
+        );
+        assert_eq!(report.phases.total(), 37);
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use mongodb::{
+    bson::doc,
+    error::Error,
+    options::{IndexOptions, ReplaceOptions},
+    IndexModel,
+};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    db::{
+        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        MongoDb,
+    },
+    model::{tangle::MilestoneIndex, utxo::NativeTokenId},
+};
+
+/// The number of largest holders whose combined share of a native token is reported.
+pub const TOP_HOLDERS: usize = 10;
+
+/// How a native token is distributed over the addresses holding it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NativeTokenDistribution {
+    /// The number of addresses holding the token.
+    pub holder_count: usize,
+    /// The total amount of the token held, as a decimal string.
+    pub total_amount: String,
+    /// The share of the total amount held by the [`TOP_HOLDERS`] largest holders, between 0 and 1.
+    pub top_holders_share: f64,
+    /// The Gini coefficient of the holdings, between 0 for equal holdings and 1 for a single holder owning everything.
+    pub gini: f64,
+}
+
+/// Converts a [`U256`] to the nearest [`f64`], which is precise enough for computing shares.
+fn to_f64(value: U256) -> f64 {
+    value
+        .0
+        .iter()
+        .rev()
+        .fold(0.0, |acc, &limb| acc * (u64::MAX as f64 + 1.0) + limb as f64)
+}
+
+impl NativeTokenDistribution {
+    /// Computes the distribution from the balances of the individual holders.
+    pub fn from_balances(balances: impl IntoIterator<Item = U256>) -> Self {
+        let mut balances = balances
+            .into_iter()
+            .filter(|balance| !balance.is_zero())
+            .collect::<Vec<_>>();
+        balances.sort_unstable();
+
+        let total = balances
+            .iter()
+            .fold(U256::zero(), |total, balance| total.saturating_add(*balance));
+        let total_f64 = to_f64(total);
+        let holder_count = balances.len();
+        let (top_holders_share, gini) = if total.is_zero() {
+            (0.0, 0.0)
+        } else {
+            let top = balances.iter().rev().take(TOP_HOLDERS).map(|b| to_f64(*b)).sum::<f64>();
+            // With balances in ascending order: G = 2 * sum(i * x_i) / (n * sum(x_i)) - (n + 1) / n
+            let n = holder_count as f64;
+            let weighted = balances
+                .iter()
+                .enumerate()
+                .map(|(i, b)| (i + 1) as f64 * to_f64(*b))
+                .sum::<f64>();
+            (
+                (top / total_f64).min(1.0),
+                (2.0 * weighted / (n * total_f64) - (n + 1.0) / n).clamp(0.0, 1.0),
+            )
+        };
+
+        Self {
+            holder_count,
+            total_amount: total.to_string(),
+            top_holders_share,
+            gini,
+        }
+    }
+}
+
+/// The distribution of a native token at a ledger index.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TokenDistributionDocument {
+    #[serde(rename = "_id")]
+    token_id: NativeTokenId,
+    ledger_index: MilestoneIndex,
+    #[serde(flatten)]
+    distribution: NativeTokenDistribution,
+}
+
+/// The distribution of a native token as returned by queries.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[allow(missing_docs)]
+pub struct TokenDistributionResult {
+    pub ledger_index: MilestoneIndex,
+    #[serde(flatten)]
+    pub distribution: NativeTokenDistribution,
+}
+
+/// The stardust native token distribution collection, which holds the periodically computed distribution of every
+/// native token.
+pub struct TokenDistributionCollection {
+    collection: mongodb::Collection<TokenDistributionDocument>,
+}
+
+#[async_trait::async_trait]
+impl MongoDbCollection for TokenDistributionCollection {
+    const NAME: &'static str = "stardust_token_distribution";
+    type Document = TokenDistributionDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+
+    async fn create_indexes(&self) -> Result<(), Error> {
+        self.create_index(
+            IndexModel::builder()
+                .keys(doc! { "ledger_index": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name("token_distribution_ledger_index".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl TokenDistributionCollection {
+    /// Replaces the stored distributions with the ones computed at the given ledger index. Tokens that are no longer
+    /// held by anyone are removed.
+    #[instrument(skip_all, err, level = "trace")]
+    pub async fn update_distributions(
+        &self,
+        ledger_index: MilestoneIndex,
+        distributions: impl IntoIterator<Item = (NativeTokenId, NativeTokenDistribution)>,
+    ) -> Result<(), Error> {
+        for (token_id, distribution) in distributions {
+            self.replace_one(
+                doc! { "_id": token_id },
+                TokenDistributionDocument {
+                    token_id,
+                    ledger_index,
+                    distribution,
+                },
+                ReplaceOptions::builder().upsert(true).build(),
+            )
+            .await?;
+        }
+        self.collection
+            .delete_many(doc! { "ledger_index": { "$ne": ledger_index } }, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Gets the latest computed distribution of a native token.
+    pub async fn get_distribution(&self, token_id: NativeTokenId) -> Result<Option<TokenDistributionResult>, Error> {
+        self.find_one(doc! { "_id": token_id }, None).await
+    }
+
+    /// Gets the ledger index at which the stored distributions were computed.
+    pub async fn get_ledger_index(&self) -> Result<Option<MilestoneIndex>, Error> {
+        Ok(self
+            .find_one::<TokenDistributionResult>(doc! {}, None)
+            .await?
+            .map(|res| res.ledger_index))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn distribution_of_balances() {
+        let empty = NativeTokenDistribution::from_balances([]);
+        assert_eq!(empty.holder_count, 0);
+        assert_eq!(empty.total_amount, "0");
+        assert_eq!(empty.gini, 0.0);
+
+        let single = NativeTokenDistribution::from_balances([U256::from(500), U256::zero()]);
+        assert_eq!(single.holder_count, 1);
+        assert_eq!(single.top_holders_share, 1.0);
+        assert_eq!(single.gini, 0.0);
+
+        let equal = NativeTokenDistribution::from_balances((0..20).map(|_| U256::from(7)));
+        assert_eq!(equal.holder_count, 20);
+        assert_eq!(equal.total_amount, "140");
+        assert!((equal.top_holders_share - 0.5).abs() < 1e-9);
+        assert!(equal.gini.abs() < 1e-9);
+
+        // One holder owns everything but a dust amount held by the others.
+        let skewed =
+            NativeTokenDistribution::from_balances((0..99).map(|_| U256::from(1)).chain([U256::from(1_000_000)]));
+        assert!(skewed.gini > 0.95);
+        assert!(skewed.top_holders_share > 0.99);
+
+        let huge = NativeTokenDistribution::from_balances([U256::MAX, U256::MAX]);
+        assert_eq!(huge.total_amount, U256::MAX.to_string());
+        assert_eq!(huge.holder_count, 2);
+    }
+}
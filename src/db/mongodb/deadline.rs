@@ -0,0 +1,106 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-task deadlines that bound how long MongoDB spends on a query.
+
+use std::{future::Future, time::Duration};
+
+use mongodb::options::{AggregateOptions, CountOptions, FindOneOptions, FindOptions};
+use tokio::time::Instant;
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// The smallest time limit sent to the server, as a `maxTimeMS` of zero means no limit at all.
+const MIN_MAX_TIME: Duration = Duration::from_millis(1);
+
+/// Runs the future with a deadline that every query issued from it is bound by via `maxTimeMS`.
+///
+/// Nested deadlines can only shorten the time left, never extend it.
+pub async fn with_deadline<F: Future>(deadline: Instant, fut: F) -> F::Output {
+    let deadline = match DEADLINE.try_with(|outer| *outer) {
+        Ok(outer) => outer.min(deadline),
+        Err(_) => deadline,
+    };
+    DEADLINE.scope(deadline, fut).await
+}
+
+/// Gets the time left until the deadline of the current task, if it has one.
+pub fn remaining_time() -> Option<Duration> {
+    DEADLINE
+        .try_with(|deadline| deadline.saturating_duration_since(Instant::now()))
+        .ok()
+}
+
+/// Clamps an existing time limit to the time left until the deadline.
+fn bounded_max_time(max_time: Option<Duration>, remaining: Option<Duration>) -> Option<Duration> {
+    match (max_time, remaining) {
+        (Some(max_time), Some(remaining)) => Some(max_time.min(remaining).max(MIN_MAX_TIME)),
+        (None, Some(remaining)) => Some(remaining.max(MIN_MAX_TIME)),
+        (max_time, None) => max_time,
+    }
+}
+
+/// Query options whose server-side execution time can be bounded by the deadline of the current task.
+pub(crate) trait DeadlineExt: Sized {
+    /// Sets `maxTimeMS` to the time left until the deadline, if it is shorter than the existing limit.
+    fn with_deadline(self) -> Self;
+}
+
+macro_rules! impl_deadline_ext {
+    ($($t:ty),*) => {
+        $(
+            impl DeadlineExt for Option<$t> {
+                fn with_deadline(self) -> Self {
+                    let remaining = remaining_time();
+                    if remaining.is_none() {
+                        return self;
+                    }
+                    let mut options = self.unwrap_or_default();
+                    options.max_time = bounded_max_time(options.max_time, remaining);
+                    Some(options)
+                }
+            }
+        )*
+    };
+}
+impl_deadline_ext!(AggregateOptions, CountOptions, FindOneOptions, FindOptions);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bounded_max_time_clamps() {
+        let second = Duration::from_secs(1);
+        assert_eq!(bounded_max_time(None, None), None);
+        assert_eq!(bounded_max_time(Some(second), None), Some(second));
+        assert_eq!(bounded_max_time(None, Some(second)), Some(second));
+        assert_eq!(bounded_max_time(Some(second), Some(2 * second)), Some(second));
+        assert_eq!(bounded_max_time(Some(2 * second), Some(second)), Some(second));
+        assert_eq!(bounded_max_time(None, Some(Duration::ZERO)), Some(MIN_MAX_TIME));
+    }
+
+    #[tokio::test]
+    async fn deadline_is_scoped() {
+        assert_eq!(remaining_time(), None);
+        assert!(None::<FindOptions>.with_deadline().is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        with_deadline(deadline, async {
+            let remaining = remaining_time().unwrap();
+            assert!(remaining <= Duration::from_secs(60));
+            let options = None::<FindOptions>.with_deadline().unwrap();
+            assert!(options.max_time.unwrap() <= remaining);
+
+            // An inner deadline cannot extend the outer one.
+            with_deadline(deadline + Duration::from_secs(60), async {
+                assert!(remaining_time().unwrap() <= Duration::from_secs(60));
+            })
+            .await;
+        })
+        .await;
+        assert_eq!(remaining_time(), None);
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Auditing of the documents that are dropped on insert because they already exist.
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+tokio::task_local! {
+    static AUDIT: DuplicateAudit;
+}
+
+/// Counts the duplicate documents dropped by
+/// [`insert_many_ignore_duplicates`](super::InsertIgnoreDuplicatesExt::insert_many_ignore_duplicates) per collection,
+/// for the inserts issued from within [`DuplicateAudit::scope`].
+#[derive(Clone, Debug, Default)]
+pub struct DuplicateAudit(Arc<Mutex<BTreeMap<&'static str, usize>>>);
+
+impl DuplicateAudit {
+    /// Runs the future, counting the duplicates dropped by the inserts it issues. Tasks spawned from the future are
+    /// only covered if they are wrapped in [`inherit`].
+    pub async fn scope<F: Future>(&self, fut: F) -> F::Output {
+        AUDIT.scope(self.clone(), fut).await
+    }
+
+    /// Takes the counts recorded so far, leaving the audit empty.
+    pub fn take(&self) -> BTreeMap<&'static str, usize> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+
+    fn add(&self, collection: &'static str, count: usize) {
+        *self.0.lock().unwrap().entry(collection).or_default() += count;
+    }
+}
+
+/// Carries the audit of the current task, if there is one, over to a future that is about to be spawned.
+pub fn inherit<F: Future>(fut: F) -> impl Future<Output = F::Output> {
+    let audit = AUDIT.try_with(Clone::clone).ok();
+    async move {
+        match audit {
+            Some(audit) => audit.scope(fut).await,
+            None => fut.await,
+        }
+    }
+}
+
+/// Records dropped duplicates with the audit of the current task, if there is one.
+pub(crate) fn record(collection: &'static str, count: usize) {
+    if count > 0 {
+        AUDIT.try_with(|audit| audit.add(collection, count)).ok();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_within_scope() {
+        let audit = DuplicateAudit::default();
+        record("outside", 1);
+        audit
+            .scope(async {
+                record("stardust_blocks", 2);
+                record("stardust_blocks", 3);
+                record("stardust_outputs", 0);
+                tokio::spawn(inherit(async { record("stardust_parents", 1) }))
+                    .await
+                    .unwrap();
+                tokio::spawn(async { record("stardust_parents", 1) }).await.unwrap();
+            })
+            .await;
+        assert_eq!(
+            audit.take(),
+            BTreeMap::from([("stardust_blocks", 5), ("stardust_parents", 1)])
+        );
+        assert!(audit.take().is_empty());
+    }
+}
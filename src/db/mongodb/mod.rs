@@ -7,6 +7,8 @@ mod collection;
 /// Module containing the collections in the database.
 pub mod collections;
 pub mod config;
+pub mod deadline;
+pub mod duplicates;
 
 use std::collections::{HashMap, HashSet};
 
@@ -15,10 +17,21 @@ use mongodb::{
     bson::{doc, Document},
     error::Error,
     options::ClientOptions,
-    Client,
+    Client, ClientSession,
 };
 
 pub use self::collection::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt};
+use self::collections::{CollectionFragmentation, CollectionStorage};
+
+/// Reads a numeric statistic, which MongoDB reports with varying types.
+fn stat(stats: &Document, key: &str) -> u64 {
+    match stats.get(key) {
+        Some(mongodb::bson::Bson::Int32(i)) => *i as u64,
+        Some(mongodb::bson::Bson::Int64(i)) => *i as u64,
+        Some(mongodb::bson::Bson::Double(f)) => *f as u64,
+        _ => 0,
+    }
+}
 
 /// A handle to the underlying `MongoDB` database.
 #[derive(Clone, Debug)]
@@ -90,8 +103,8 @@ impl MongoDb {
 
     /// Returns the storage size of the database.
     pub async fn size(&self) -> Result<u64, Error> {
-        Ok(
-            match self
+        Ok(stat(
+            &self
                 .db()
                 .run_command(
                     doc! {
@@ -101,16 +114,64 @@ impl MongoDb {
                     },
                     None,
                 )
-                .await?
-                .get("storageSize")
-                .unwrap()
-            {
-                mongodb::bson::Bson::Int32(i) => *i as u64,
-                mongodb::bson::Bson::Int64(i) => *i as u64,
-                mongodb::bson::Bson::Double(f) => *f as u64,
-                _ => unreachable!(),
-            },
-        )
+                .await?,
+            "storageSize",
+        ))
+    }
+
+    /// Returns the number of documents and the storage size, including indexes, of every collection.
+    pub async fn collection_storage(&self) -> Result<Vec<CollectionStorage>, Error> {
+        let mut res = Vec::new();
+        // Views have no storage of their own.
+        for name in self.db().list_collection_names(doc! { "type": "collection" }).await? {
+            if name.starts_with("system.") {
+                continue;
+            }
+            let stats = self
+                .db()
+                .run_command(doc! { "collStats": &name, "scale": 1 }, None)
+                .await?;
+            res.push(CollectionStorage {
+                count: stat(&stats, "count"),
+                size: stat(&stats, "storageSize") + stat(&stats, "totalIndexSize"),
+                name,
+            });
+        }
+        res.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(res)
+    }
+
+    /// Returns the allocated and the unused storage of every collection, excluding its indexes.
+    pub async fn collection_fragmentation(&self) -> Result<Vec<CollectionFragmentation>, Error> {
+        let mut res = Vec::new();
+        for name in self.db().list_collection_names(doc! { "type": "collection" }).await? {
+            if name.starts_with("system.") {
+                continue;
+            }
+            let stats = self
+                .db()
+                .run_command(doc! { "collStats": &name, "scale": 1 }, None)
+                .await?;
+            res.push(CollectionFragmentation {
+                storage_size: stat(&stats, "storageSize"),
+                free_storage_size: stat(&stats, "freeStorageSize"),
+                name,
+            });
+        }
+        res.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(res)
+    }
+
+    /// Returns whether the deployment supports multi-document transactions, which requires a replica set or a sharded
+    /// cluster.
+    pub async fn supports_transactions(&self) -> Result<bool, Error> {
+        let hello = self.db().run_command(doc! { "hello": 1 }, None).await?;
+        Ok(hello.contains_key("setName") || matches!(hello.get_str("msg"), Ok("isdbgrid")))
+    }
+
+    /// Starts a session in which multi-document transactions can be run.
+    pub async fn start_session(&self) -> Result<ClientSession, Error> {
+        self.client.start_session(None).await
     }
 
     /// Returns the names of all available databases.
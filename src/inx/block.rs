@@ -4,7 +4,7 @@
 use inx::proto;
 use iota_sdk::types::block as iota;
 
-use super::{InxError, RawMessage};
+use super::{drift::check_enum, InxError, RawMessage};
 use crate::{
     maybe_missing,
     model::{
@@ -62,6 +62,16 @@ impl TryFrom<inx::proto::BlockMetadata> for BlockMetadataMessage {
     type Error = crate::inx::InxError;
 
     fn try_from(value: inx::proto::BlockMetadata) -> Result<Self, Self::Error> {
+        check_enum(
+            "inx.BlockMetadata.LedgerInclusionState",
+            value.ledger_inclusion_state,
+            proto::block_metadata::LedgerInclusionState::is_valid,
+        );
+        check_enum(
+            "inx.BlockMetadata.ConflictReason",
+            value.conflict_reason,
+            proto::block_metadata::ConflictReason::is_valid,
+        );
         let inclusion_state = value.ledger_inclusion_state().into();
         let conflict_reason = value.conflict_reason().into();
 
@@ -1,17 +1,48 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::Duration;
+
 use futures::stream::{Stream, StreamExt};
-use inx::{client::InxClient, proto};
+use inx::{
+    client::InxClient,
+    proto,
+    tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity},
+};
 
 use super::{
-    block::BlockWithMetadataMessage,
+    block::{BlockMessage, BlockWithMetadataMessage},
     ledger::UnspentOutputMessage,
     milestone::{MilestoneAndProtocolParametersMessage, MilestoneMessage},
     node::NodeConfigurationMessage,
     request::MilestoneRequest,
     InxError, LedgerUpdateMessage, MilestoneRangeRequest, NodeStatusMessage, RawProtocolParametersMessage,
+    TipsMetricMessage,
 };
+use crate::model::BlockId;
+
+/// The PEM-encoded certificates of a TLS connection to the INX interface of a node.
+#[derive(Clone, Debug, Default)]
+pub struct InxTlsConfig {
+    /// The certificate of the authority that signed the certificate of the node. The native root certificates are
+    /// trusted if this is not set.
+    pub ca_cert: Option<Vec<u8>>,
+    /// The client certificate and its private key, if the node authenticates its clients.
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl From<InxTlsConfig> for ClientTlsConfig {
+    fn from(value: InxTlsConfig) -> Self {
+        let mut config = ClientTlsConfig::new();
+        if let Some(ca_cert) = value.ca_cert {
+            config = config.ca_certificate(Certificate::from_pem(ca_cert));
+        }
+        if let Some((cert, key)) = value.identity {
+            config = config.identity(Identity::from_pem(cert, key));
+        }
+        config
+    }
+}
 
 /// An INX client connection.
 #[derive(Clone, Debug)]
@@ -35,6 +66,17 @@ impl Inx {
         })
     }
 
+    /// Connect to the INX interface of a node over TLS.
+    pub async fn connect_tls(address: String, tls: InxTlsConfig) -> Result<Self, InxError> {
+        let channel = Endpoint::from_shared(address)?
+            .tls_config(tls.into())?
+            .connect()
+            .await?;
+        Ok(Self {
+            inx: InxClient::new(channel),
+        })
+    }
+
     /// Convenience wrapper that listen to ledger updates as a stream of
     /// [`MilestoneAndProtocolParametersMessages`](MilestoneAndProtocolParametersMessage).
     pub async fn listen_to_confirmed_milestones(
@@ -62,11 +104,50 @@ impl Inx {
             .map(unpack_proto_msg))
     }
 
+    /// Convenience wrapper that listens to all blocks the node receives as a stream of
+    /// [`BlockMessages`](BlockMessage).
+    pub async fn listen_to_blocks(&mut self) -> Result<impl Stream<Item = Result<BlockMessage, InxError>>, InxError> {
+        Ok(self
+            .inx
+            .listen_to_blocks(proto::NoParams {})
+            .await?
+            .into_inner()
+            .map(unpack_proto_msg))
+    }
+
     /// Convenience wrapper that reads the status of the node into a [`NodeStatusMessage`].
     pub async fn read_node_status(&mut self) -> Result<NodeStatusMessage, InxError> {
         NodeStatusMessage::try_from(self.inx.read_node_status(proto::NoParams {}).await?.into_inner())
     }
 
+    /// Convenience wrapper that listens to the tip pool sizes of the node, reported at the given interval, as a stream
+    /// of [`TipsMetricMessages`](TipsMetricMessage).
+    pub async fn listen_to_tips_metrics(
+        &mut self,
+        interval: Duration,
+    ) -> Result<impl Stream<Item = Result<TipsMetricMessage, InxError>>, InxError> {
+        Ok(self
+            .inx
+            .listen_to_tips_metrics(proto::TipsMetricRequest {
+                interval_in_milliseconds: interval.as_millis().try_into().unwrap_or(u32::MAX),
+            })
+            .await?
+            .into_inner()
+            .map(|msg| Ok(msg.map_err(InxError::StatusCode)?.into())))
+    }
+
+    /// Convenience wrapper that requests up to `count` tips from the node, which are semi-lazy only if allowed.
+    pub async fn request_tips(&mut self, count: u32, allow_semi_lazy: bool) -> Result<Vec<BlockId>, InxError> {
+        self.inx
+            .request_tips(proto::TipsRequest { count, allow_semi_lazy })
+            .await?
+            .into_inner()
+            .tips
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+
     /// Convenience wrapper that reads the configuration of the node into a [`NodeConfigurationMessage`].
     pub async fn read_node_configuration(&mut self) -> Result<NodeConfigurationMessage, InxError> {
         NodeConfigurationMessage::try_from(self.inx.read_node_configuration(proto::NoParams {}).await?.into_inner())
@@ -0,0 +1,114 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of INX messages that use a newer schema than the one Chronicle was built against.
+//!
+//! The protobuf decoder maps enum values it does not know to the default variant, so a newer node could make data go
+//! missing without any error. Such values are counted per enum type, so operators can learn that their node is newer
+//! than Chronicle.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use once_cell::sync::Lazy;
+
+static UNKNOWN_ENUM_VALUES: Lazy<Mutex<BTreeMap<(&'static str, i32), u64>>> = Lazy::new(Default::default);
+
+/// An enum value received from INX that Chronicle does not know.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaDrift {
+    /// The name of the protobuf enum type.
+    pub type_name: &'static str,
+    /// The unknown value.
+    pub value: i32,
+    /// How often the value was received since Chronicle started.
+    pub count: u64,
+}
+
+/// Returns the unknown enum values received from INX so far.
+pub fn schema_drift() -> Vec<SchemaDrift> {
+    UNKNOWN_ENUM_VALUES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(&(type_name, value), &count)| SchemaDrift {
+            type_name,
+            value,
+            count,
+        })
+        .collect()
+}
+
+/// Records the value of an enum field if it is not a known variant. A warning is logged the first time the value is
+/// seen, and again whenever its count reaches a power of ten.
+pub(crate) fn check_enum(type_name: &'static str, value: i32, is_valid: fn(i32) -> bool) {
+    if is_valid(value) {
+        return;
+    }
+    let count = {
+        let mut unknown = UNKNOWN_ENUM_VALUES.lock().unwrap();
+        let count = unknown.entry((type_name, value)).or_default();
+        *count += 1;
+        *count
+    };
+    if is_power_of_ten(count) {
+        tracing::warn!(
+            type_name,
+            value,
+            count,
+            "Received an unknown enum value from INX, which was read as the default. The node is likely newer than \
+             Chronicle."
+        );
+    }
+}
+
+fn is_power_of_ten(mut n: u64) -> bool {
+    while n >= 10 && n % 10 == 0 {
+        n /= 10;
+    }
+    n == 1
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn counts_unknown_values() {
+        let is_valid = |value| (0..3).contains(&value);
+        check_enum("test.Known", 1, is_valid);
+        check_enum("test.Unknown", 7, is_valid);
+        check_enum("test.Unknown", 7, is_valid);
+        check_enum("test.Unknown", -1, is_valid);
+        let drift = schema_drift()
+            .into_iter()
+            .filter(|drift| drift.type_name.starts_with("test."))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            drift,
+            vec![
+                SchemaDrift {
+                    type_name: "test.Unknown",
+                    value: -1,
+                    count: 1
+                },
+                SchemaDrift {
+                    type_name: "test.Unknown",
+                    value: 7,
+                    count: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn powers_of_ten() {
+        assert!(is_power_of_ten(1));
+        assert!(is_power_of_ten(10));
+        assert!(is_power_of_ten(1000));
+        assert!(!is_power_of_ten(0));
+        assert!(!is_power_of_ten(20));
+        assert!(!is_power_of_ten(101));
+    }
+}
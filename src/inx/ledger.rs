@@ -4,7 +4,7 @@
 use iota_sdk::types::block as iota;
 use packable::PackableExt;
 
-use super::InxError;
+use super::{drift::check_enum, InxError};
 use crate::{
     maybe_missing,
     model::{
@@ -86,6 +86,7 @@ impl From<inx::proto::ledger_update::Marker> for MarkerMessage {
 impl From<inx::proto::ledger_update::Marker> for LedgerUpdateMessage {
     fn from(value: inx::proto::ledger_update::Marker) -> Self {
         use inx::proto::ledger_update::marker::MarkerType as proto;
+        check_enum("inx.LedgerUpdate.Marker.MarkerType", value.marker_type, proto::is_valid);
         match value.marker_type() {
             proto::Begin => Self::Begin(value.into()),
             proto::End => Self::End(value.into()),
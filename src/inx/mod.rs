@@ -5,6 +5,7 @@
 
 mod block;
 mod client;
+mod drift;
 mod error;
 mod id;
 mod ledger;
@@ -16,11 +17,12 @@ mod request;
 
 pub use self::{
     block::{BlockMessage, BlockMetadataMessage, BlockWithMetadataMessage},
-    client::Inx,
+    client::{Inx, InxTlsConfig},
+    drift::{schema_drift, SchemaDrift},
     error::InxError,
     ledger::{LedgerUpdateMessage, MarkerMessage, UnspentOutputMessage},
     milestone::MilestoneAndProtocolParametersMessage,
-    node::{NodeConfigurationMessage, NodeStatusMessage},
+    node::{NodeConfigurationMessage, NodeStatusMessage, TipsMetricMessage},
     protocol::RawProtocolParametersMessage,
     raw::RawMessage,
     request::MilestoneRangeRequest,
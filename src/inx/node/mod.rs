@@ -5,5 +5,6 @@
 
 mod config;
 mod status;
+mod tips;
 
-pub use self::{config::NodeConfigurationMessage, status::NodeStatusMessage};
+pub use self::{config::NodeConfigurationMessage, status::NodeStatusMessage, tips::TipsMetricMessage};
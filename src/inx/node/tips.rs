@@ -0,0 +1,31 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use inx::proto;
+
+/// The [`TipsMetricMessage`] type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TipsMetricMessage {
+    /// The number of non-lazy tips in the tip pool of the node.
+    pub non_lazy_pool_size: u32,
+    /// The number of semi-lazy tips in the tip pool of the node.
+    pub semi_lazy_pool_size: u32,
+}
+
+impl From<proto::TipsMetric> for TipsMetricMessage {
+    fn from(value: proto::TipsMetric) -> Self {
+        Self {
+            non_lazy_pool_size: value.non_lazy_pool_size,
+            semi_lazy_pool_size: value.semi_lazy_pool_size,
+        }
+    }
+}
+
+impl From<TipsMetricMessage> for proto::TipsMetric {
+    fn from(value: TipsMetricMessage) -> Self {
+        Self {
+            non_lazy_pool_size: value.non_lazy_pool_size,
+            semi_lazy_pool_size: value.semi_lazy_pool_size,
+        }
+    }
+}
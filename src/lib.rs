@@ -11,6 +11,8 @@
 
 #[cfg(feature = "analytics")]
 pub mod analytics;
+#[cfg(feature = "archive")]
+pub mod archive;
 pub mod db;
 #[cfg(feature = "inx")]
 pub mod inx;
@@ -20,6 +20,59 @@ pub struct SyncMetrics {
     pub chronicle_version: String,
 }
 
+/// The time spent in each phase of syncing a milestone, in milliseconds.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, InfluxDbWriteable)]
+#[allow(missing_docs)]
+pub struct SyncPhaseMetrics {
+    pub time: DateTime<Utc>,
+    pub milestone_index: MilestoneIndex,
+    /// Waiting for the milestone and its ledger updates from INX.
+    pub receive_time: u64,
+    /// Inserting the created outputs and updating the consumed ones.
+    pub output_insert_time: u64,
+    /// Receiving and converting the blocks of the milestone cone.
+    pub cone_receive_time: u64,
+    /// Inserting the blocks that were still pending once the cone was received.
+    pub block_insert_time: u64,
+    pub analytics_time: u64,
+    /// Storing the protocol parameters, node configuration and the milestone itself.
+    pub checkpoint_time: u64,
+    #[influxdb(tag)]
+    pub chronicle_version: String,
+}
+
+/// The number of documents of a collection that were dropped as duplicates while syncing a milestone.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, InfluxDbWriteable)]
+#[allow(missing_docs)]
+pub struct DuplicateMetrics {
+    pub time: DateTime<Utc>,
+    pub milestone_index: MilestoneIndex,
+    #[influxdb(tag)]
+    pub collection: String,
+    pub duplicates: u64,
+    #[influxdb(tag)]
+    pub chronicle_version: String,
+}
+
+/// The storage used by a collection, how fast it grows, and the storage projected for a horizon.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, InfluxDbWriteable)]
+#[allow(missing_docs)]
+pub struct StorageMetrics {
+    pub time: DateTime<Utc>,
+    #[influxdb(tag)]
+    pub collection: String,
+    #[influxdb(tag)]
+    pub horizon: String,
+    pub count: u64,
+    pub size: u64,
+    pub documents_per_day: f64,
+    pub bytes_per_day: f64,
+    pub projected_count: u64,
+    pub projected_size: u64,
+    #[influxdb(tag)]
+    pub chronicle_version: String,
+}
+
 #[cfg(feature = "analytics")]
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, InfluxDbWriteable)]
 #[allow(missing_docs)]
@@ -35,6 +88,18 @@ impl InfluxDbMeasurement for SyncMetrics {
     const NAME: &'static str = "sync_metrics";
 }
 
+impl InfluxDbMeasurement for SyncPhaseMetrics {
+    const NAME: &'static str = "sync_phase_metrics";
+}
+
+impl InfluxDbMeasurement for DuplicateMetrics {
+    const NAME: &'static str = "duplicate_metrics";
+}
+
+impl InfluxDbMeasurement for StorageMetrics {
+    const NAME: &'static str = "storage_metrics";
+}
+
 #[cfg(feature = "analytics")]
 impl InfluxDbMeasurement for AnalyticsMetrics {
     const NAME: &'static str = "analytics_metrics";
@@ -33,3 +33,28 @@ pub struct BlockMetadata {
     /// The index of this block in white flag order.
     pub white_flag_index: u32,
 }
+
+#[cfg(feature = "rand")]
+mod rand {
+    use iota_sdk::types::block::rand::{bool::rand_bool, number::rand_number};
+
+    use super::*;
+
+    impl BlockMetadata {
+        /// Generates a random [`BlockMetadata`] of an included block.
+        pub fn rand() -> Self {
+            let milestone_index = rand_number::<u32>().into();
+            Self {
+                parents: BlockId::rand_parents(),
+                is_solid: true,
+                should_promote: rand_bool(),
+                should_reattach: rand_bool(),
+                referenced_by_milestone_index: milestone_index,
+                milestone_index,
+                inclusion_state: LedgerInclusionState::Included,
+                conflict_reason: ConflictReason::None,
+                white_flag_index: rand_number(),
+            }
+        }
+    }
+}
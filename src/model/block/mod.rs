@@ -8,6 +8,7 @@ use std::str::FromStr;
 use iota::protocol::ProtocolParameters;
 use iota_sdk::types::block as iota;
 use mongodb::bson::{spec::BinarySubtype, Binary, Bson};
+use packable::PackableExt;
 use serde::{Deserialize, Serialize};
 
 use self::payload::Payload;
@@ -82,6 +83,14 @@ pub struct Block {
     pub nonce: u64,
 }
 
+impl Block {
+    /// Converts the [`Block`] into its raw byte representation.
+    pub fn raw(self, ctx: crate::model::ProtocolParameters) -> Result<Vec<u8>, iota::Error> {
+        let block = iota::Block::try_from_with_context(&ctx.try_into()?, self)?;
+        Ok(block.pack_to_vec())
+    }
+}
+
 impl From<iota::Block> for Block {
     fn from(value: iota::Block) -> Self {
         Self {
@@ -100,6 +109,7 @@ impl TryFromWithContext<Block> for iota::Block {
         let mut builder = iota::BlockBuilder::new(iota::parent::Parents::from_vec(
             value.parents.into_vec().into_iter().map(Into::into).collect(),
         )?)
+        .with_protocol_version(value.protocol_version)
         .with_nonce(value.nonce);
         if let Some(payload) = value.payload {
             let payload: iota_sdk::types::block::payload::Payload = payload.try_into_with_context(ctx)?;
@@ -304,4 +314,13 @@ mod test {
         let bson = to_bson(&block).unwrap();
         assert_eq!(block, from_bson::<Block>(bson).unwrap());
     }
+
+    #[test]
+    fn test_block_raw() {
+        let ctx = iota_sdk::types::block::protocol::protocol_parameters();
+        let block = Block::rand_tagged_data();
+        let raw = block.clone().raw(ctx.clone().into()).unwrap();
+        let unpacked = iota::Block::unpack_unverified(raw).unwrap();
+        assert_eq!(block, Block::from(unpacked));
+    }
 }
@@ -293,12 +293,12 @@ impl From<MilestoneOption> for iota::option::dto::MilestoneOptionDto {
 pub struct MigratedFundsEntry {
     /// The tail transaction hash of the bundle in which these funds were migrated.
     #[serde(with = "bytify")]
-    tail_transaction_hash: [u8; Self::TAIL_TRANSACTION_HASH_LENGTH],
+    pub tail_transaction_hash: [u8; Self::TAIL_TRANSACTION_HASH_LENGTH],
     /// The target address.
-    address: Address,
+    pub address: Address,
     /// The amount of tokens that have been migrated.
     #[serde(with = "stringify")]
-    amount: u64,
+    pub amount: u64,
 }
 
 impl MigratedFundsEntry {
@@ -352,6 +352,16 @@ mod rand {
 
     use super::*;
 
+    impl MilestoneIndexTimestamp {
+        /// Generates a random [`MilestoneIndexTimestamp`].
+        pub fn rand() -> Self {
+            Self {
+                milestone_index: rand_number::<u32>().into(),
+                milestone_timestamp: rand_number::<u32>().into(),
+            }
+        }
+    }
+
     impl MilestonePayload {
         /// Generates a random [`MilestonePayload`].
         pub fn rand(ctx: &iota_sdk::types::block::protocol::ProtocolParameters) -> Self {
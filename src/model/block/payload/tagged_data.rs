@@ -11,15 +11,22 @@ use serde::{Deserialize, Serialize};
 /// Represents the tagged data payload for data blocks.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TaggedDataPayload {
+    /// The tag of the payload.
     #[serde(with = "serde_bytes")]
-    tag: Box<[u8]>,
+    pub tag: Box<[u8]>,
+    /// The data of the payload.
     #[serde(with = "serde_bytes")]
-    data: Box<[u8]>,
+    pub data: Box<[u8]>,
 }
 
 impl TaggedDataPayload {
     /// A `&str` representation of the type.
     pub const KIND: &'static str = "tagged_data";
+
+    /// The tag of the payload.
+    pub fn tag(&self) -> &[u8] {
+        &self.tag
+    }
 }
 
 impl<T: Borrow<iota::TaggedDataPayload>> From<T> for TaggedDataPayload {
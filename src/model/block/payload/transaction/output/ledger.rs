@@ -3,10 +3,17 @@
 
 //! Ledger output types
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use super::{Output, OutputId, TokenAmount};
-use crate::model::{block::BlockId, metadata::SpentMetadata, tangle::MilestoneIndexTimestamp, utxo::Address};
+use super::{Feature, NftId, Output, OutputId, TokenAmount};
+use crate::model::{
+    block::BlockId,
+    metadata::SpentMetadata,
+    tangle::{MilestoneIndexTimestamp, MilestoneTimestamp},
+    utxo::Address,
+};
 
 /// An unspent output according to the ledger.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -88,6 +95,97 @@ impl RentStructureBytes {
     }
 }
 
+/// A sale of an NFT, i.e. a transfer of the NFT that is accompanied by a base token payment in the same transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftSale {
+    /// The id of the sold NFT.
+    pub nft_id: NftId,
+    /// The issuer of the NFT, which identifies the collection it belongs to.
+    pub collection: Option<Address>,
+    /// The previous owner of the NFT. For marketplace sales, this is the escrow address of the marketplace.
+    pub seller: Address,
+    /// The new owner of the NFT.
+    pub buyer: Address,
+    /// The price paid for the NFT.
+    pub price: TokenAmount,
+}
+
+/// Detects the NFT sales in a transaction.
+///
+/// The payment is the amount of base tokens received by addresses other than the buyers, not counting the deposits of
+/// the NFT outputs themselves. This also covers marketplaces that settle escrowed NFTs and funds in a single
+/// transaction. If a transaction transfers several NFTs, the payment is split evenly between them.
+pub fn nft_sales(
+    consumed: &[LedgerSpent],
+    created: &[LedgerOutput],
+    milestone_timestamp: MilestoneTimestamp,
+) -> Vec<NftSale> {
+    let mut previous_owners = HashMap::new();
+    let mut balance_deltas: HashMap<Address, i128> = HashMap::new();
+    for spent in consumed {
+        let address = match spent.owning_address() {
+            Some(&address) => address,
+            None => continue,
+        };
+        match &spent.output.output {
+            Output::Nft(nft) => {
+                let nft_id = if nft.nft_id == NftId::implicit() {
+                    NftId::from(spent.output_id())
+                } else {
+                    nft.nft_id
+                };
+                previous_owners.insert(nft_id, address);
+            }
+            output => *balance_deltas.entry(address).or_default() -= output.amount().0 as i128,
+        }
+    }
+
+    let mut transfers = Vec::new();
+    for output in created {
+        let address = match output.output.owning_address(milestone_timestamp) {
+            Some(&address) => address,
+            None => continue,
+        };
+        match &output.output {
+            Output::Nft(nft) => {
+                if let Some(&seller) = previous_owners.get(&nft.nft_id) {
+                    if seller != address {
+                        transfers.push((nft, seller, address));
+                    }
+                }
+            }
+            output => *balance_deltas.entry(address).or_default() += output.amount().0 as i128,
+        }
+    }
+    if transfers.is_empty() {
+        return Vec::new();
+    }
+
+    let payment = balance_deltas
+        .iter()
+        .filter(|(address, _)| !transfers.iter().any(|(_, _, buyer)| buyer == *address))
+        .map(|(_, delta)| (*delta).max(0))
+        .sum::<i128>();
+    if payment == 0 {
+        return Vec::new();
+    }
+    let price = TokenAmount((payment / transfers.len() as i128) as u64);
+
+    transfers
+        .into_iter()
+        .map(|(nft, seller, buyer)| NftSale {
+            nft_id: nft.nft_id,
+            collection: nft.immutable_features.iter().find_map(|feature| match feature {
+                Feature::Issuer { address } => Some(*address),
+                _ => None,
+            }),
+            seller,
+            buyer,
+            price,
+        })
+        .collect()
+}
+
 #[cfg(feature = "inx")]
 mod inx {
     use packable::PackableExt;
@@ -136,6 +234,40 @@ mod inx {
     }
 }
 
+#[cfg(feature = "rand")]
+mod rand {
+    use super::*;
+    use crate::model::TryFromWithContext;
+
+    impl LedgerOutput {
+        /// Generates a random [`LedgerOutput`].
+        pub fn rand(ctx: &iota_sdk::types::block::protocol::ProtocolParameters) -> Self {
+            let output = Output::rand(ctx);
+            // Unwrap: the random output is valid.
+            let rent_structure = RentStructureBytes::compute(
+                &iota_sdk::types::block::output::Output::try_from_with_context(ctx, output.clone()).unwrap(),
+            );
+            Self {
+                output_id: OutputId::rand(),
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp::rand(),
+                output,
+                rent_structure,
+            }
+        }
+    }
+
+    impl LedgerSpent {
+        /// Generates a random [`LedgerSpent`].
+        pub fn rand(ctx: &iota_sdk::types::block::protocol::ProtocolParameters) -> Self {
+            Self {
+                output: LedgerOutput::rand(ctx),
+                spent_metadata: SpentMetadata::rand(),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[cfg(feature = "rand")]
@@ -172,4 +304,122 @@ mod test {
             );
         }
     }
+
+    #[cfg(feature = "rand")]
+    mod nft_sales {
+        use pretty_assertions::assert_eq;
+
+        use super::super::*;
+        use crate::model::utxo::{AddressUnlockCondition, BasicOutput, NftOutput};
+
+        fn ledger_output(output: Output) -> LedgerOutput {
+            LedgerOutput {
+                output_id: OutputId::rand(),
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 1000.into(),
+                },
+                output,
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+            }
+        }
+
+        fn spent(output: Output) -> LedgerSpent {
+            LedgerSpent {
+                output: ledger_output(output),
+                spent_metadata: SpentMetadata {
+                    transaction_id: OutputId::rand().transaction_id,
+                    spent: MilestoneIndexTimestamp {
+                        milestone_index: 2.into(),
+                        milestone_timestamp: 2000.into(),
+                    },
+                },
+            }
+        }
+
+        fn basic(address: Address, amount: u64) -> Output {
+            Output::Basic(BasicOutput {
+                amount: amount.into(),
+                native_tokens: Vec::new().into_boxed_slice(),
+                address_unlock_condition: AddressUnlockCondition { address },
+                storage_deposit_return_unlock_condition: None,
+                timelock_unlock_condition: None,
+                expiration_unlock_condition: None,
+                features: Vec::new().into_boxed_slice(),
+            })
+        }
+
+        fn nft(nft_id: NftId, issuer: Address, address: Address) -> Output {
+            Output::Nft(NftOutput {
+                amount: 50.into(),
+                native_tokens: Vec::new().into_boxed_slice(),
+                nft_id,
+                address_unlock_condition: AddressUnlockCondition { address },
+                storage_deposit_return_unlock_condition: None,
+                timelock_unlock_condition: None,
+                expiration_unlock_condition: None,
+                features: Vec::new().into_boxed_slice(),
+                immutable_features: vec![Feature::Issuer { address: issuer }].into_boxed_slice(),
+            })
+        }
+
+        #[test]
+        fn direct_sale() {
+            let (seller, buyer, issuer) = (Address::rand_ed25519(), Address::rand_ed25519(), Address::rand_nft());
+            let nft_id = NftId::rand();
+            let consumed = [spent(nft(nft_id, issuer, seller)), spent(basic(buyer, 1000))];
+            let created = [
+                ledger_output(nft(nft_id, issuer, buyer)),
+                ledger_output(basic(seller, 900)),
+                ledger_output(basic(buyer, 100)),
+            ];
+            assert_eq!(
+                nft_sales(&consumed, &created, 2000.into()),
+                vec![NftSale {
+                    nft_id,
+                    collection: Some(issuer),
+                    seller,
+                    buyer,
+                    price: 900.into(),
+                }]
+            );
+        }
+
+        #[test]
+        fn escrow_settlement() {
+            let (escrow, seller, buyer, issuer) = (
+                Address::rand_ed25519(),
+                Address::rand_ed25519(),
+                Address::rand_ed25519(),
+                Address::rand_nft(),
+            );
+            let nft_id = NftId::rand();
+            let consumed = [spent(nft(nft_id, issuer, escrow)), spent(basic(escrow, 1000))];
+            let created = [
+                ledger_output(nft(nft_id, issuer, buyer)),
+                ledger_output(basic(seller, 950)),
+                ledger_output(basic(escrow, 50)),
+            ];
+            let sales = nft_sales(&consumed, &created, 2000.into());
+            assert_eq!(sales.len(), 1);
+            assert_eq!(sales[0].seller, escrow);
+            assert_eq!(sales[0].price, 950.into());
+        }
+
+        #[test]
+        fn transfer_without_payment() {
+            let (sender, receiver, issuer) = (Address::rand_ed25519(), Address::rand_ed25519(), Address::rand_nft());
+            let nft_id = NftId::rand();
+            let consumed = [spent(nft(nft_id, issuer, sender)), spent(basic(sender, 1000))];
+            let created = [
+                ledger_output(nft(nft_id, issuer, receiver)),
+                ledger_output(basic(sender, 1000)),
+            ];
+            assert!(nft_sales(&consumed, &created, 2000.into()).is_empty());
+        }
+    }
 }
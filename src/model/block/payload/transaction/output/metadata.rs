@@ -23,3 +23,31 @@ pub struct OutputMetadata {
     pub booked: MilestoneIndexTimestamp,
     pub spent_metadata: Option<SpentMetadata>,
 }
+
+#[cfg(feature = "rand")]
+mod rand {
+    use iota_sdk::types::block::rand::bool::rand_bool;
+
+    use super::*;
+
+    impl SpentMetadata {
+        /// Generates a random [`SpentMetadata`].
+        pub fn rand() -> Self {
+            Self {
+                transaction_id: TransactionId::rand(),
+                spent: MilestoneIndexTimestamp::rand(),
+            }
+        }
+    }
+
+    impl OutputMetadata {
+        /// Generates a random [`OutputMetadata`], which is spent half of the time.
+        pub fn rand() -> Self {
+            Self {
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp::rand(),
+                spent_metadata: rand_bool().then(SpentMetadata::rand),
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@
 use std::{borrow::Borrow, mem::size_of, str::FromStr};
 
 use iota_sdk::types::block::output as iota;
+use mongodb::bson::{spec::BinarySubtype, Binary, Bson};
 use primitive_types::U256;
 use serde::{Deserialize, Serialize};
 
@@ -31,7 +32,7 @@ impl From<NativeTokenAmount> for U256 {
 }
 
 /// A unique native token identifier.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct NativeTokenId(#[serde(with = "bytify")] pub [u8; Self::LENGTH]);
 
@@ -59,6 +60,16 @@ impl FromStr for NativeTokenId {
     }
 }
 
+impl From<NativeTokenId> for Bson {
+    fn from(val: NativeTokenId) -> Self {
+        Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: val.0.to_vec(),
+        }
+        .into()
+    }
+}
+
 /// Defines information about the underlying token.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "kind")]
@@ -13,7 +13,15 @@ use crate::model::tangle::MilestoneTimestamp;
 /// Defines a unix timestamp until which the output can not be unlocked.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TimelockUnlockCondition {
-    timestamp: MilestoneTimestamp,
+    /// The unix timestamp until which the output is locked.
+    pub timestamp: MilestoneTimestamp,
+}
+
+impl TimelockUnlockCondition {
+    /// Gets the time until which the output can not be unlocked.
+    pub fn timestamp(&self) -> MilestoneTimestamp {
+        self.timestamp
+    }
 }
 
 impl<T: Borrow<iota::TimelockUnlockCondition>> From<T> for TimelockUnlockCondition {
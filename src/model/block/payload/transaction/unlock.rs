@@ -3,9 +3,11 @@
 
 //! Module containing the [`Unlock`] types.
 
+use crypto::hashes::{blake2b::Blake2b256, Digest};
 use iota_sdk::types::block::unlock as iota;
 use serde::{Deserialize, Serialize};
 
+use super::output::{Address, AliasAddress, AliasId, Ed25519Address, NftAddress, NftId, Output, OutputId};
 use crate::model::signature::Signature;
 
 /// The different types of [`Unlock`]s.
@@ -34,6 +36,47 @@ pub enum Unlock {
     },
 }
 
+/// Resolves the [`Address`] that authorized each of the `unlocks` of a transaction.
+///
+/// The `inputs` are the consumed outputs in the order of the transaction inputs. Unlocks that do not point at a
+/// signature or at an alias or nft input resolve to `None`.
+pub fn unlock_addresses(unlocks: &[Unlock], inputs: &[(OutputId, Output)]) -> Vec<Option<Address>> {
+    let signer = |index: u16| match unlocks.get(index as usize)? {
+        Unlock::Signature {
+            signature: Signature::Ed25519 { public_key, .. },
+        } => Some(Address::Ed25519(Ed25519Address(Blake2b256::digest(public_key).into()))),
+        _ => None,
+    };
+    unlocks
+        .iter()
+        .enumerate()
+        .map(|(index, unlock)| match *unlock {
+            Unlock::Signature { .. } => signer(index as u16),
+            Unlock::Reference { index } => signer(index),
+            Unlock::Alias { index } => match inputs.get(index as usize)? {
+                (output_id, Output::Alias(output)) => Some(Address::Alias(AliasAddress(
+                    if output.alias_id == AliasId::implicit() {
+                        (*output_id).into()
+                    } else {
+                        output.alias_id
+                    },
+                ))),
+                _ => None,
+            },
+            Unlock::Nft { index } => match inputs.get(index as usize)? {
+                (output_id, Output::Nft(output)) => {
+                    Some(Address::Nft(NftAddress(if output.nft_id == NftId::implicit() {
+                        (*output_id).into()
+                    } else {
+                        output.nft_id
+                    })))
+                }
+                _ => None,
+            },
+        })
+        .collect()
+}
+
 impl From<&iota::Unlock> for Unlock {
     fn from(value: &iota::Unlock) -> Self {
         match value {
@@ -165,4 +208,37 @@ mod test {
         let bson = to_bson(&unlock).unwrap();
         assert_eq!(unlock, from_bson::<Unlock>(bson).unwrap());
     }
+
+    #[test]
+    fn test_unlock_addresses() {
+        let ctx = iota_sdk::types::block::protocol::protocol_parameters();
+        let signature = Signature::rand();
+        let Signature::Ed25519 { public_key, .. } = signature;
+        let signer = Address::Ed25519(Ed25519Address(Blake2b256::digest(public_key).into()));
+        let mut alias = crate::model::utxo::AliasOutput::rand(&ctx);
+        alias.alias_id = AliasId::implicit();
+        let alias_output_id = OutputId::rand();
+        let inputs = vec![
+            (OutputId::rand(), Output::rand_basic(&ctx)),
+            (alias_output_id, Output::Alias(alias)),
+            (OutputId::rand(), Output::rand_basic(&ctx)),
+            (OutputId::rand(), Output::rand_basic(&ctx)),
+        ];
+        let unlocks = [
+            Unlock::Signature { signature },
+            Unlock::Reference { index: 0 },
+            Unlock::Alias { index: 1 },
+            Unlock::Nft { index: 1 },
+        ];
+
+        assert_eq!(
+            unlock_addresses(&unlocks, &inputs),
+            vec![
+                Some(signer),
+                Some(signer),
+                Some(Address::Alias(AliasAddress(alias_output_id.into()))),
+                None
+            ]
+        );
+    }
 }
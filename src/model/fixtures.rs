@@ -0,0 +1,67 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The milestones in `tests/data/in_memory_data.json`, which are shared by the snapshot tests of the model and the
+//! API.
+
+use std::{collections::BTreeMap, fs::File, io::BufReader};
+
+use mongodb::bson::{from_bson, Bson};
+use serde::Deserialize;
+
+use crate::model::{
+    block::BlockId,
+    ledger::{LedgerOutput, LedgerSpent},
+    metadata::BlockMetadata,
+    node::NodeConfiguration,
+    payload::{MilestoneId, MilestonePayload},
+    protocol::ProtocolParameters,
+    tangle::MilestoneIndexTimestamp,
+};
+
+/// A milestone as it is stored in the fixtures.
+#[derive(Deserialize)]
+pub struct MilestoneFixture {
+    /// The id of the milestone.
+    pub milestone_id: MilestoneId,
+    /// The index and timestamp of the milestone.
+    pub at: MilestoneIndexTimestamp,
+    /// The milestone payload.
+    pub payload: MilestonePayload,
+    /// The protocol parameters at the milestone.
+    pub protocol_params: ProtocolParameters,
+    /// The node configuration at the milestone.
+    pub node_config: NodeConfiguration,
+}
+
+/// A block of the past cone of a milestone.
+#[derive(Deserialize)]
+pub struct BlockFixture {
+    /// The id of the block.
+    pub block_id: BlockId,
+    /// The packed block.
+    #[serde(with = "serde_bytes")]
+    pub raw: Vec<u8>,
+    /// The metadata of the block.
+    pub metadata: BlockMetadata,
+}
+
+/// A milestone, its past cone and the outputs it created and consumed.
+#[derive(Deserialize)]
+pub struct Fixture {
+    /// The milestone.
+    pub milestone_data: MilestoneFixture,
+    /// The blocks of the past cone, keyed by their white-flag index.
+    pub cone: BTreeMap<String, BlockFixture>,
+    /// The outputs created by the milestone.
+    pub created: Vec<LedgerOutput>,
+    /// The outputs consumed by the milestone.
+    pub consumed: Vec<LedgerSpent>,
+}
+
+/// Loads the fixtures, keyed by milestone index.
+pub fn load_fixtures() -> BTreeMap<String, Fixture> {
+    let file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/in_memory_data.json")).unwrap();
+    let data: Bson = serde_json::from_reader(BufReader::new(file)).unwrap();
+    from_bson(data).unwrap()
+}
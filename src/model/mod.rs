@@ -4,11 +4,16 @@
 //! Module that contains the types.
 
 pub mod block;
+#[cfg(any(test, feature = "rand"))]
+pub mod fixtures;
 pub mod node;
 pub mod protocol;
 pub mod signature;
 pub mod util;
 
+#[cfg(test)]
+mod snapshots;
+
 pub use block::*;
 pub use node::*;
 pub use protocol::*;
@@ -0,0 +1,217 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serialization tests for the model. A change to how a type is serialized makes the documents that are already in
+//! the database unreadable, so the BSON and JSON of the milestones, blocks and outputs in
+//! `tests/data/in_memory_data.json` are compared with the snapshots that are stored in `tests/data/model_snapshots`,
+//! and random instances of every type must survive a round-trip through both formats.
+//!
+//! Missing or changed snapshots fail the test, unless `UPDATE_SNAPSHOTS=1` is set, in which case the snapshots are
+//! (re)recorded and should be reviewed like any other change.
+
+use std::{
+    any::Any,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    path::PathBuf,
+};
+
+use mongodb::bson::{from_bson, to_bson};
+use packable::PackableExt;
+use pretty_assertions::assert_eq;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::model::{
+    block::Block,
+    fixtures::{load_fixtures, Fixture},
+    payload::{Payload, TransactionEssence, TransactionPayload},
+    utxo::Output,
+};
+
+/// The outputs of a transaction are stored in the outputs collection instead of with the transaction, so they are not
+/// serialized and have to be re-added before deserializing. Returns them and the JSON pointer of the essence.
+fn transaction_outputs<T: 'static>(value: &T) -> Option<(&[Output], &'static str)> {
+    let value = value as &dyn Any;
+    let (payload, pointer) = match value.downcast_ref::<Block>() {
+        Some(Block {
+            payload: Some(Payload::Transaction(payload)),
+            ..
+        }) => (payload.as_ref(), "/payload/essence"),
+        _ => (value.downcast_ref::<TransactionPayload>()?, "/essence"),
+    };
+    let TransactionEssence::Regular { outputs, .. } = &payload.essence;
+    Some((outputs, pointer))
+}
+
+fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug + 'static>(value: &T) {
+    let (mut bson, mut json) = (to_bson(value).unwrap(), serde_json::to_value(value).unwrap());
+    if let Some((outputs, pointer)) = transaction_outputs(value) {
+        let essence = pointer
+            .split('/')
+            .skip(1)
+            .fold(bson.as_document_mut().unwrap(), |doc, key| {
+                doc.get_document_mut(key).unwrap()
+            });
+        essence.insert("outputs", to_bson(outputs).unwrap());
+        json.pointer_mut(pointer)
+            .unwrap()
+            .as_object_mut()
+            .unwrap()
+            .insert("outputs".to_string(), serde_json::to_value(outputs).unwrap());
+    }
+    assert_eq!(&from_bson::<T>(bson).unwrap(), value);
+    assert_eq!(&serde_json::from_value::<T>(json).unwrap(), value);
+}
+
+/// The BSON of a value, as it is stored in the database, and its JSON.
+fn serialize<T: Serialize + DeserializeOwned + PartialEq + Debug + 'static>(value: &T) -> Value {
+    assert_round_trip(value);
+    serde_json::json!({
+        "bson": to_bson(value).unwrap().into_canonical_extjson(),
+        "json": serde_json::to_value(value).unwrap(),
+    })
+}
+
+/// Serializes the first instance of every kind of value in the fixtures, keyed by the name of its snapshot.
+fn cases(fixtures: &BTreeMap<String, Fixture>) -> BTreeMap<String, Value> {
+    let mut cases = BTreeMap::new();
+    let mut case = |name: &str, value: Value| {
+        cases.entry(name.to_string()).or_insert(value);
+    };
+
+    let milestone = &fixtures.values().next().unwrap().milestone_data;
+    case("milestone_id", serialize(&milestone.milestone_id));
+    case("milestone_index_timestamp", serialize(&milestone.at));
+    case("milestone_payload", serialize(&milestone.payload));
+    case("protocol_parameters", serialize(&milestone.protocol_params));
+    case("node_configuration", serialize(&milestone.node_config));
+
+    for data in fixtures.values().flat_map(|fixture| fixture.cone.values()) {
+        let block = Block::from(iota_sdk::types::block::Block::unpack_unverified(data.raw.clone()).unwrap());
+        let kind = match &block.payload {
+            None => "block_without_payload",
+            Some(Payload::Transaction(_)) => "block_transaction",
+            Some(Payload::Milestone(_)) => "block_milestone",
+            Some(Payload::TreasuryTransaction(_)) => "block_treasury_transaction",
+            Some(Payload::TaggedData(_)) => "block_tagged_data",
+        };
+        case(kind, serialize(&block));
+        case("block_id", serialize(&data.block_id));
+        case("block_metadata", serialize(&data.metadata));
+    }
+
+    for output in fixtures.values().flat_map(|fixture| &fixture.created) {
+        let kind = match &output.output {
+            Output::Treasury(_) => "ledger_output_treasury",
+            Output::Basic(_) => "ledger_output_basic",
+            Output::Alias(_) => "ledger_output_alias",
+            Output::Foundry(_) => "ledger_output_foundry",
+            Output::Nft(_) => "ledger_output_nft",
+        };
+        case(kind, serialize(output));
+    }
+    if let Some(spent) = fixtures.values().flat_map(|fixture| &fixture.consumed).next() {
+        case("ledger_spent", serialize(spent));
+    }
+
+    cases
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/model_snapshots")).join(format!("{name}.json"))
+}
+
+#[test]
+fn serialization_snapshots() {
+    let update = std::env::var("UPDATE_SNAPSHOTS").map_or(false, |v| v == "1");
+    std::fs::create_dir_all(snapshot_path("").parent().unwrap()).unwrap();
+    let (mut missing, mut mismatches) = (BTreeSet::new(), BTreeSet::new());
+    for (name, actual) in cases(&load_fixtures()) {
+        let file = snapshot_path(&name);
+        match std::fs::read(&file) {
+            _ if update => std::fs::write(&file, serde_json::to_string_pretty(&actual).unwrap() + "\n").unwrap(),
+            Ok(expected) => {
+                let expected = serde_json::from_slice::<Value>(&expected).unwrap();
+                if expected != actual {
+                    eprintln!(
+                        "snapshot `{name}` changed:\n{}",
+                        pretty_assertions::Comparison::new(&expected, &actual)
+                    );
+                    mismatches.insert(name);
+                }
+            }
+            Err(_) => {
+                missing.insert(name);
+            }
+        }
+    }
+
+    assert!(
+        missing.is_empty(),
+        "no snapshots are recorded for {missing:?}, rerun with `UPDATE_SNAPSHOTS=1` to record them"
+    );
+    assert!(
+        mismatches.is_empty(),
+        "the serialization of {mismatches:?} differs from the snapshots, rerun with `UPDATE_SNAPSHOTS=1` if this is \
+         intended"
+    );
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn random_round_trips() {
+    use crate::model::{
+        block::BlockId,
+        ledger::{LedgerOutput, LedgerSpent},
+        metadata::{BlockMetadata, OutputMetadata, SpentMetadata},
+        payload::{MilestoneId, MilestonePayload, TaggedDataPayload, TransactionId, TreasuryTransactionPayload},
+        signature::Signature,
+        tangle::MilestoneIndexTimestamp,
+        utxo::{Address, Feature, Input, NativeToken, OutputId, TokenAmount, Unlock},
+    };
+
+    let ctx = iota_sdk::types::block::protocol::protocol_parameters();
+    for _ in 0..100 {
+        assert_round_trip(&BlockId::rand());
+        assert_round_trip(&Block::rand(&ctx));
+        assert_round_trip(&Block::rand_transaction(&ctx));
+        assert_round_trip(&Block::rand_milestone(&ctx));
+        assert_round_trip(&Block::rand_tagged_data());
+        assert_round_trip(&Block::rand_treasury_transaction(&ctx));
+        assert_round_trip(&Block::rand_no_payload());
+        assert_round_trip(&BlockMetadata::rand());
+
+        assert_round_trip(&MilestoneId::rand());
+        assert_round_trip(&MilestoneIndexTimestamp::rand());
+        assert_round_trip(&MilestonePayload::rand(&ctx));
+        assert_round_trip(&TaggedDataPayload::rand());
+        assert_round_trip(&TreasuryTransactionPayload::rand(&ctx));
+        assert_round_trip(&TransactionPayload::rand(&ctx));
+        assert_round_trip(&TransactionId::rand());
+        assert_round_trip(&Input::rand());
+        assert_round_trip(&Unlock::rand());
+        assert_round_trip(&Signature::rand());
+
+        assert_round_trip(&OutputId::rand());
+        assert_round_trip(&TokenAmount::rand(&ctx));
+        assert_round_trip(&Output::rand_basic(&ctx));
+        assert_round_trip(&Output::rand_alias(&ctx));
+        assert_round_trip(&Output::rand_foundry(&ctx));
+        assert_round_trip(&Output::rand_nft(&ctx));
+        assert_round_trip(&Output::rand_treasury(&ctx));
+        assert_round_trip(&Address::rand_ed25519());
+        assert_round_trip(&Address::rand_alias());
+        assert_round_trip(&Address::rand_nft());
+        assert_round_trip(&Feature::rand_sender());
+        assert_round_trip(&Feature::rand_issuer());
+        assert_round_trip(&Feature::rand_metadata());
+        assert_round_trip(&Feature::rand_tag());
+        assert_round_trip(&NativeToken::rand());
+
+        assert_round_trip(&SpentMetadata::rand());
+        assert_round_trip(&OutputMetadata::rand());
+        assert_round_trip(&LedgerOutput::rand(&ctx));
+        assert_round_trip(&LedgerSpent::rand(&ctx));
+    }
+}
@@ -54,7 +54,10 @@ pub mod stringify {
 pub mod bytify {
     use std::marker::PhantomData;
 
-    use serde::{de::Visitor, Deserializer, Serializer};
+    use serde::{
+        de::{SeqAccess, Visitor},
+        Deserializer, Serializer,
+    };
 
     /// Deserialize T from bytes
     pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
@@ -80,6 +83,18 @@ pub mod bytify {
             {
                 v.try_into().map_err(|_| serde::de::Error::custom("invalid bytes"))
             }
+
+            // Formats without bytes, such as JSON, represent them as a sequence of numbers.
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
         }
 
         deserializer.deserialize_bytes(Helper(PhantomData))
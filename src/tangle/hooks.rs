@@ -0,0 +1,209 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+#[cfg(feature = "inx")]
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+#[cfg(feature = "inx")]
+use once_cell::sync::Lazy;
+
+use crate::model::{
+    ledger::{LedgerOutput, LedgerSpent},
+    payload::MilestoneId,
+    tangle::MilestoneIndexTimestamp,
+};
+
+/// The error a hook can fail with. Returning an error stops the ingestion of the current milestone.
+pub type HookError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Callbacks that are invoked in-process while milestones are ingested, for applications that embed Chronicle.
+///
+/// The outputs of a milestone are reported after they have been written, but before the milestone is marked as synced,
+/// so they are reported at least once: if ingestion is interrupted, they are reported again when the milestone is
+/// synced after a restart. All methods do nothing by default.
+#[async_trait]
+pub trait IngestionHook: Send + Sync {
+    /// Called with a batch of outputs that were created by a milestone.
+    async fn outputs_created(&self, _at: MilestoneIndexTimestamp, _outputs: &[LedgerOutput]) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// Called with a batch of outputs that were spent by a milestone.
+    async fn outputs_spent(&self, _at: MilestoneIndexTimestamp, _outputs: &[LedgerSpent]) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    /// Called once a milestone and everything it references has been ingested.
+    async fn milestone_ingested(
+        &self,
+        _milestone_id: MilestoneId,
+        _at: MilestoneIndexTimestamp,
+    ) -> Result<(), HookError> {
+        Ok(())
+    }
+}
+
+/// The hooks that are registered for the process.
+#[cfg(feature = "inx")]
+static REGISTERED_HOOKS: Lazy<Mutex<IngestionHooks>> = Lazy::new(Default::default);
+
+/// The hooks an application registers to be notified of ingested milestones and outputs. Hooks are notified in the
+/// order they were added, and the first error stops the notification.
+#[derive(Clone, Default)]
+pub struct IngestionHooks {
+    hooks: Vec<Arc<dyn IngestionHook>>,
+}
+
+impl IngestionHooks {
+    /// Registers a hook for the whole process, after the ones that were already registered. The INX worker notifies
+    /// the hooks that were registered before it started.
+    #[cfg(feature = "inx")]
+    pub fn register(hook: impl IngestionHook + 'static) {
+        // A poisoned lock only means that another registration panicked, which leaves the hooks usable.
+        REGISTERED_HOOKS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .add(Arc::new(hook));
+    }
+
+    /// Gets the hooks that are registered for the process.
+    #[cfg(feature = "inx")]
+    pub fn registered() -> Self {
+        REGISTERED_HOOKS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Registers a hook after the ones that were already added.
+    pub fn with_hook(mut self, hook: impl IngestionHook + 'static) -> Self {
+        self.add(Arc::new(hook));
+        self
+    }
+
+    /// Registers a hook after the ones that were already added.
+    pub fn add(&mut self, hook: Arc<dyn IngestionHook>) {
+        self.hooks.push(hook);
+    }
+
+    /// Reports the outputs created and spent by a milestone, in batches of at most `batch_size` outputs.
+    pub async fn notify_outputs(
+        &self,
+        at: MilestoneIndexTimestamp,
+        created: &[LedgerOutput],
+        consumed: &[LedgerSpent],
+        batch_size: usize,
+    ) -> Result<(), HookError> {
+        for hook in &self.hooks {
+            for batch in created.chunks(batch_size) {
+                hook.outputs_created(at, batch).await?;
+            }
+            for batch in consumed.chunks(batch_size) {
+                hook.outputs_spent(at, batch).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports that a milestone has been ingested.
+    pub async fn notify_milestone(
+        &self,
+        milestone_id: MilestoneId,
+        at: MilestoneIndexTimestamp,
+    ) -> Result<(), HookError> {
+        for hook in &self.hooks {
+            hook.milestone_ingested(milestone_id, at).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use std::sync::Mutex;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::model::utxo::OutputId;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Event {
+        Created(Vec<OutputId>),
+        Spent(Vec<OutputId>),
+        Milestone(MilestoneId),
+    }
+
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+    #[async_trait]
+    impl IngestionHook for Recorder {
+        async fn outputs_created(
+            &self,
+            _at: MilestoneIndexTimestamp,
+            outputs: &[LedgerOutput],
+        ) -> Result<(), HookError> {
+            let ids = outputs.iter().map(|output| output.output_id).collect();
+            self.0.lock().unwrap().push(Event::Created(ids));
+            Ok(())
+        }
+
+        async fn outputs_spent(&self, _at: MilestoneIndexTimestamp, outputs: &[LedgerSpent]) -> Result<(), HookError> {
+            let ids = outputs.iter().map(|spent| spent.output.output_id).collect();
+            self.0.lock().unwrap().push(Event::Spent(ids));
+            Ok(())
+        }
+
+        async fn milestone_ingested(
+            &self,
+            milestone_id: MilestoneId,
+            _at: MilestoneIndexTimestamp,
+        ) -> Result<(), HookError> {
+            self.0.lock().unwrap().push(Event::Milestone(milestone_id));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn registered_hooks_see_outputs_and_milestones() {
+        let ctx = iota_sdk::types::block::protocol::protocol_parameters();
+        let created = std::iter::repeat_with(|| LedgerOutput::rand(&ctx))
+            .take(3)
+            .collect::<Vec<_>>();
+        let consumed = vec![LedgerSpent::rand(&ctx)];
+        let milestone_id = MilestoneId::rand();
+        let at = MilestoneIndexTimestamp::rand();
+
+        let recorder = Recorder::default();
+        let events = recorder.0.clone();
+        let hooks = IngestionHooks::default().with_hook(recorder);
+        hooks.notify_outputs(at, &created, &consumed, 2).await.unwrap();
+        hooks.notify_milestone(milestone_id, at).await.unwrap();
+
+        let ids = created.iter().map(|output| output.output_id).collect::<Vec<_>>();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                Event::Created(ids[..2].to_vec()),
+                Event::Created(ids[2..].to_vec()),
+                Event::Spent(vec![consumed[0].output.output_id]),
+                Event::Milestone(milestone_id),
+            ]
+        );
+    }
+
+    #[cfg(feature = "inx")]
+    #[tokio::test]
+    async fn registered_hooks_are_shared_by_the_process() {
+        let milestone_id = MilestoneId::rand();
+        let recorder = Recorder::default();
+        let events = recorder.0.clone();
+        IngestionHooks::register(recorder);
+
+        IngestionHooks::registered()
+            .notify_milestone(milestone_id, MilestoneIndexTimestamp::rand())
+            .await
+            .unwrap();
+        assert_eq!(*events.lock().unwrap(), vec![Event::Milestone(milestone_id)]);
+    }
+}
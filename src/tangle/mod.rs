@@ -3,6 +3,7 @@
 
 //! Defines types that allow for unified data processing.
 
+mod hooks;
 mod ledger_updates;
 mod milestone_stream;
 pub(crate) mod sources;
@@ -11,6 +12,7 @@ use std::ops::RangeBounds;
 use futures::{StreamExt, TryStreamExt};
 
 pub use self::{
+    hooks::{HookError, IngestionHook, IngestionHooks},
     ledger_updates::LedgerUpdateStore,
     milestone_stream::{Milestone, MilestoneStream},
     sources::{BlockData, InputSource, MilestoneData},
@@ -0,0 +1,65 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::{Bound, RangeBounds};
+
+use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
+
+use super::{BlockData, InputSource, MilestoneData};
+use crate::{
+    archive::{ArchiveError, MilestoneArchive},
+    model::tangle::MilestoneIndex,
+    tangle::ledger_updates::LedgerUpdateStore,
+};
+
+#[async_trait]
+impl InputSource for MilestoneArchive {
+    type Error = ArchiveError;
+
+    async fn milestone_stream(
+        &self,
+        range: impl RangeBounds<MilestoneIndex> + Send,
+    ) -> Result<BoxStream<Result<MilestoneData, Self::Error>>, Self::Error> {
+        let manifest = match self.manifest().await? {
+            Some(manifest) => manifest,
+            None => return Ok(Box::pin(futures::stream::empty())),
+        };
+        // Unbounded ranges end at the archived milestones, while milestones that were requested explicitly but are not
+        // archived are an error.
+        // Bounds that exclude the first or last possible index leave the range empty.
+        let start = match range.start_bound() {
+            Bound::Included(&idx) => Some(idx.0),
+            Bound::Excluded(&idx) => idx.0.checked_add(1),
+            Bound::Unbounded => Some(manifest.first.0),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&idx) => Some(idx.0),
+            Bound::Excluded(&idx) => idx.0.checked_sub(1),
+            Bound::Unbounded => Some(manifest.last.0),
+        };
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Ok(Box::pin(futures::stream::empty())),
+        };
+        Ok(Box::pin(futures::stream::iter(start..=end).then(
+            move |index| async move { Ok(self.read(index.into()).await?.milestone_data()) },
+        )))
+    }
+
+    async fn cone_stream(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<BoxStream<Result<BlockData, Self::Error>>, Self::Error> {
+        let blocks = self.read(index).await?.cone_blocks()?;
+        Ok(Box::pin(futures::stream::iter(blocks.into_iter().map(Ok))))
+    }
+
+    async fn ledger_updates(&self, index: MilestoneIndex) -> Result<LedgerUpdateStore, Self::Error> {
+        let milestone = self.read(index).await?;
+        Ok(LedgerUpdateStore::init(
+            milestone.consumed.clone(),
+            milestone.created.clone(),
+        ))
+    }
+}
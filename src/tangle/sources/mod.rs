@@ -1,6 +1,8 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "archive")]
+pub(crate) mod archive;
 #[cfg(feature = "inx")]
 pub(crate) mod inx;
 pub(crate) mod memory;
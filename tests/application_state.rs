@@ -0,0 +1,57 @@
+// Copyright 2024 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+#[cfg(feature = "rand")]
+mod test_rand {
+    use chronicle::db::mongodb::collections::ApplicationStateCollection;
+    use pretty_assertions::assert_eq;
+
+    use super::common::{setup_collection, setup_database, teardown};
+
+    #[tokio::test]
+    async fn test_database_id() {
+        let db = setup_database("test-database-id").await.unwrap();
+        let state_collection = setup_collection::<ApplicationStateCollection>(&db).await.unwrap();
+
+        assert_eq!(state_collection.get_database_id().await.unwrap(), None);
+
+        let database_id = state_collection.get_or_init_database_id().await.unwrap();
+        assert_eq!(
+            state_collection.get_database_id().await.unwrap().as_ref(),
+            Some(&database_id)
+        );
+        // An existing id is kept.
+        assert_eq!(state_collection.get_or_init_database_id().await.unwrap(), database_id);
+
+        state_collection.set_database_id("restored").await.unwrap();
+        assert_eq!(state_collection.get_or_init_database_id().await.unwrap(), "restored");
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_writer_instance_id() {
+        let db = setup_database("test-writer-instance-id").await.unwrap();
+        let state_collection = setup_collection::<ApplicationStateCollection>(&db).await.unwrap();
+
+        assert_eq!(state_collection.get_writer_instance_id().await.unwrap(), None);
+
+        let database_id = state_collection.get_or_init_database_id().await.unwrap();
+        state_collection.set_writer_instance_id("first").await.unwrap();
+        assert_eq!(
+            state_collection.get_writer_instance_id().await.unwrap().as_deref(),
+            Some("first")
+        );
+        // A restarted writer replaces the instance id, but keeps the database id.
+        state_collection.set_writer_instance_id("second").await.unwrap();
+        assert_eq!(
+            state_collection.get_writer_instance_id().await.unwrap().as_deref(),
+            Some("second")
+        );
+        assert_eq!(state_collection.get_or_init_database_id().await.unwrap(), database_id);
+
+        teardown(db).await;
+    }
+}
@@ -8,12 +8,19 @@ mod test_rand {
     use std::{collections::HashSet, fs::File, io::BufReader};
 
     use chronicle::{
-        db::{mongodb::collections::BlockCollection, MongoDbCollectionExt},
+        db::{
+            mongodb::collections::{
+                BlockCollection, BlockDocument, OutputCollection, ParentsCollection, ProtocolUpdateCollection,
+            },
+            MongoDbCollectionExt,
+        },
         model::{
+            ledger::{LedgerOutput, RentStructureBytes},
             metadata::{BlockMetadata, ConflictReason, LedgerInclusionState},
-            payload::Payload,
+            payload::{Payload, TransactionEssence},
+            tangle::MilestoneIndexTimestamp,
             utxo::OutputId,
-            Block, BlockId,
+            Block, BlockId, ProtocolParameters,
         },
     };
     use futures::TryStreamExt;
@@ -105,10 +112,174 @@ mod test_rand {
         teardown(db).await;
     }
 
+    #[tokio::test]
+    async fn test_lean_blocks() {
+        let db = setup_database("test-lean-blocks").await.unwrap();
+        let block_collection = setup_collection::<BlockCollection>(&db).await.unwrap();
+        let update_collection = setup_collection::<ProtocolUpdateCollection>(&db).await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+        let file = File::open("tests/data/blocks_ms_2418807.json").unwrap();
+        let test_data: mongodb::bson::Bson = serde_json::from_reader(BufReader::new(file)).unwrap();
+
+        let blocks: Vec<BlockTestData> = mongodb::bson::from_bson(test_data).unwrap();
+
+        let blocks = blocks
+            .into_iter()
+            .map(
+                |BlockTestData {
+                     block_id,
+                     raw,
+                     metadata,
+                 }| {
+                    let block: Block = iota_sdk::types::block::Block::unpack_unverified(raw.clone())
+                        .unwrap()
+                        .into();
+                    (block_id, block, raw, metadata)
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let mut parameters = ProtocolParameters::from(iota_sdk::types::block::protocol::protocol_parameters());
+        parameters.token_supply = 4_600_000_000_000_000;
+        update_collection
+            .upsert_protocol_parameters(0.into(), parameters)
+            .await
+            .unwrap();
+        // The outputs of included transactions are restored from the outputs collection.
+        let outputs = blocks
+            .iter()
+            .filter(|(_, _, _, metadata)| metadata.inclusion_state == LedgerInclusionState::Included)
+            .filter_map(|(block_id, block, _, metadata)| match &block.payload {
+                Some(Payload::Transaction(payload)) => Some((block_id, payload, metadata)),
+                _ => None,
+            })
+            .flat_map(|(block_id, payload, metadata)| {
+                let TransactionEssence::Regular { outputs, .. } = &payload.essence;
+                outputs.iter().enumerate().map(move |(index, output)| LedgerOutput {
+                    output_id: OutputId {
+                        transaction_id: payload.transaction_id,
+                        index: index as u16,
+                    },
+                    block_id: *block_id,
+                    booked: MilestoneIndexTimestamp {
+                        milestone_index: metadata.referenced_by_milestone_index,
+                        milestone_timestamp: 0.into(),
+                    },
+                    output: output.clone(),
+                    rent_structure: RentStructureBytes {
+                        num_key_bytes: 0,
+                        num_data_bytes: 100,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        assert!(!outputs.is_empty());
+        output_collection.insert_unspent_outputs(&outputs).await.unwrap();
+        block_collection
+            .insert_blocks_with_metadata(
+                blocks
+                    .iter()
+                    .cloned()
+                    .map(|block| BlockDocument::from(block).without_raw()),
+            )
+            .await
+            .unwrap();
+
+        for (block_id, block, raw, _) in &blocks {
+            assert_eq!(
+                block_collection.get_block(block_id).await.unwrap().as_ref(),
+                Some(block)
+            );
+            assert_eq!(
+                block_collection.get_block_raw(block_id).await.unwrap().as_ref(),
+                Some(raw)
+            );
+        }
+
+        let milestone_index = blocks[0].3.referenced_by_milestone_index;
+        let cone = block_collection
+            .get_referenced_blocks_in_white_flag_order_stream(milestone_index)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        for (block_id, _, raw, _) in cone {
+            assert_eq!(
+                blocks.iter().find(|(id, ..)| id == &block_id).map(|(_, _, raw, _)| raw),
+                Some(&raw)
+            );
+        }
+
+        // A transaction can no longer be restored once one of its outputs was pruned.
+        output_collection
+            .delete_outputs(&[outputs[0].output_id])
+            .await
+            .unwrap();
+        assert!(block_collection.get_block(&outputs[0].block_id).await.is_err());
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_blocks_transaction() {
+        let db = setup_database("test-blocks-transaction").await.unwrap();
+        let block_collection = setup_collection::<BlockCollection>(&db).await.unwrap();
+        // Transactions require a replica set.
+        if !db.supports_transactions().await.unwrap() {
+            teardown(db).await;
+            return;
+        }
+        let file = File::open("tests/data/blocks_ms_2418807.json").unwrap();
+        let test_data: mongodb::bson::Bson = serde_json::from_reader(BufReader::new(file)).unwrap();
+
+        let blocks: Vec<BlockTestData> = mongodb::bson::from_bson(test_data).unwrap();
+
+        let blocks = blocks
+            .into_iter()
+            .map(
+                |BlockTestData {
+                     block_id,
+                     raw,
+                     metadata,
+                 }| {
+                    let block: Block = iota_sdk::types::block::Block::unpack_unverified(raw.clone())
+                        .unwrap()
+                        .into();
+                    (block_id, block, raw, metadata)
+                },
+            )
+            .collect::<Vec<_>>();
+
+        // Blocks written before a restart must not abort the transaction.
+        block_collection
+            .insert_blocks_with_metadata(blocks.iter().take(blocks.len() / 2).cloned())
+            .await
+            .unwrap();
+
+        let mut session = db.start_session().await.unwrap();
+        session.start_transaction(None).await.unwrap();
+        block_collection
+            .insert_blocks_with_session(blocks.clone(), Some(&mut session))
+            .await
+            .unwrap();
+        session.commit_transaction().await.unwrap();
+
+        for (block_id, block, _, _) in &blocks {
+            assert_eq!(
+                block_collection.get_block(block_id).await.unwrap().as_ref(),
+                Some(block)
+            );
+        }
+
+        teardown(db).await;
+    }
+
     #[tokio::test]
     async fn test_block_children() {
         let db = setup_database("test-children").await.unwrap();
         let block_collection = setup_collection::<BlockCollection>(&db).await.unwrap();
+        let parents_collection = setup_collection::<ParentsCollection>(&db).await.unwrap();
 
         let parents = std::iter::repeat_with(BlockId::rand)
             .take(2)
@@ -151,14 +322,18 @@ mod test_rand {
             )
             .collect::<Vec<_>>();
 
+        parents_collection
+            .insert_relationships(blocks.iter().map(|(block_id, _, _, metadata)| (*block_id, metadata)))
+            .await
+            .unwrap();
         block_collection
             .insert_blocks_with_metadata(blocks.clone())
             .await
             .unwrap();
         assert_eq!(block_collection.count().await.unwrap(), 10);
 
-        let mut s = block_collection
-            .get_block_children(&parents[0], 1.into(), 15, 100, 0)
+        let mut s = parents_collection
+            .get_block_children(&parents[0], 100, 0)
             .await
             .unwrap();
 
@@ -10,14 +10,15 @@ mod test_rand {
     use chronicle::{
         db::{
             mongodb::collections::{
-                LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, LedgerUpdateCollection, SortOrder,
+                AddressActivityRecord, BalanceChangeRecord, LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord,
+                LedgerUpdateCollection, OutputCollection, SortOrder,
             },
             MongoDbCollectionExt,
         },
         model::{
             ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
             metadata::SpentMetadata,
-            tangle::MilestoneIndexTimestamp,
+            tangle::{MilestoneIndex, MilestoneIndexTimestamp},
             utxo::{AddressUnlockCondition, BasicOutput, Output, OutputId},
             BlockId,
         },
@@ -95,7 +96,7 @@ mod test_rand {
         assert_eq!(update_collection.count().await.unwrap(), 100);
 
         let mut s = update_collection
-            .get_ledger_updates_by_address(&address, 100, None, SortOrder::Newest)
+            .get_ledger_updates_by_address(&address, 100, None, SortOrder::Newest, 0.into())
             .await
             .unwrap();
 
@@ -175,6 +176,164 @@ mod test_rand {
         teardown(db).await;
     }
 
+    fn basic_output(address_unlock_condition: AddressUnlockCondition, milestone_index: u32) -> LedgerOutput {
+        LedgerOutput {
+            block_id: BlockId::rand(),
+            booked: MilestoneIndexTimestamp {
+                milestone_index: milestone_index.into(),
+                milestone_timestamp: (10000 + milestone_index).into(),
+            },
+            output: Output::Basic(BasicOutput {
+                amount: 100.into(),
+                native_tokens: Vec::new().into_boxed_slice(),
+                address_unlock_condition,
+                storage_deposit_return_unlock_condition: None,
+                timelock_unlock_condition: None,
+                expiration_unlock_condition: None,
+                features: Vec::new().into_boxed_slice(),
+            }),
+            output_id: OutputId::rand(),
+            rent_structure: RentStructureBytes {
+                num_key_bytes: 0,
+                num_data_bytes: 100,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ledger_updates_by_address_paging() {
+        let db = setup_database("test-ledger-updates-by-address-paging").await.unwrap();
+        let update_collection = setup_collection::<LedgerUpdateCollection>(&db).await.unwrap();
+
+        let address_unlock_condition = AddressUnlockCondition::rand();
+        let address = address_unlock_condition.address;
+        let ledger_index = MilestoneIndex(4);
+
+        let unspent_outputs = (1..=ledger_index.0)
+            .flat_map(|milestone_index| {
+                std::iter::repeat_with(move || basic_output(address_unlock_condition, milestone_index)).take(6)
+            })
+            .collect::<Vec<_>>();
+        // Every third output is spent in the milestone that created it, so both directions share a position.
+        let spent_outputs = unspent_outputs
+            .iter()
+            .step_by(3)
+            .map(|output| LedgerSpent {
+                output: output.clone(),
+                spent_metadata: SpentMetadata {
+                    transaction_id: OutputId::rand().transaction_id,
+                    spent: output.booked,
+                },
+            })
+            .collect::<Vec<_>>();
+        update_collection
+            .insert_unspent_ledger_updates(unspent_outputs.iter())
+            .await
+            .unwrap();
+        update_collection
+            .insert_spent_ledger_updates(spent_outputs.iter())
+            .await
+            .unwrap();
+        let total = unspent_outputs.len() + spent_outputs.len();
+
+        for order in [SortOrder::Newest, SortOrder::Oldest] {
+            let expected = update_collection
+                .get_ledger_updates_by_address(&address, total, None, order, ledger_index)
+                .await
+                .unwrap()
+                .map_ok(|rec| (rec.at.milestone_index, rec.output_id, rec.is_spent))
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(expected.len(), total);
+
+            let page_size = 5;
+            let mut cursor = None;
+            let mut paged = Vec::new();
+            loop {
+                let mut page = update_collection
+                    .get_ledger_updates_by_address(&address, page_size + 1, cursor, order, ledger_index)
+                    .await
+                    .unwrap()
+                    .map_ok(|rec| (rec.at.milestone_index, rec.output_id, rec.is_spent))
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap();
+                // The next milestone is written while the client is paging.
+                update_collection
+                    .insert_unspent_ledger_updates(
+                        std::iter::repeat_with(|| basic_output(address_unlock_condition, ledger_index.0 + 1))
+                            .take(2)
+                            .collect::<Vec<_>>()
+                            .iter(),
+                    )
+                    .await
+                    .unwrap();
+                cursor = (page.len() > page_size)
+                    .then(|| page.pop().unwrap())
+                    .map(|(milestone_index, output_id, is_spent)| (milestone_index, Some((output_id, is_spent))));
+                paged.extend(page);
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(paged, expected);
+        }
+
+        let first = update_collection
+            .get_ledger_updates_by_address(&address, total, Some((2.into(), None)), SortOrder::Oldest, ledger_index)
+            .await
+            .unwrap()
+            .try_next()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.at.milestone_index, MilestoneIndex(2));
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_ledger_updates_by_milestone_paging() {
+        let db = setup_database("test-ledger-updates-by-milestone-paging").await.unwrap();
+        let update_collection = setup_collection::<LedgerUpdateCollection>(&db).await.unwrap();
+
+        // Outputs owned by different addresses, whose order differs from the order of their ids.
+        let unspent_outputs = std::iter::repeat_with(|| basic_output(AddressUnlockCondition::rand(), 1))
+            .take(20)
+            .collect::<Vec<_>>();
+        update_collection
+            .insert_unspent_ledger_updates(unspent_outputs.iter())
+            .await
+            .unwrap();
+
+        let page_size = 3;
+        let mut cursor = None;
+        let mut paged = Vec::new();
+        loop {
+            let mut page = update_collection
+                .get_ledger_updates_by_milestone(1.into(), page_size + 1, cursor)
+                .await
+                .unwrap()
+                .map_ok(|rec| (rec.output_id, rec.is_spent))
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            cursor = (page.len() > page_size).then(|| page.pop().unwrap());
+            paged.extend(page);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(paged.len(), unspent_outputs.len());
+        assert_eq!(
+            paged.iter().map(|(output_id, _)| *output_id).collect::<HashSet<_>>(),
+            unspent_outputs.iter().map(|output| output.output_id).collect()
+        );
+
+        teardown(db).await;
+    }
+
     #[tokio::test]
     async fn test_insert_spent_ledger_updates() {
         let db = setup_database("test-insert-spent-ledger-updates").await.unwrap();
@@ -234,6 +393,238 @@ mod test_rand {
 
         assert_eq!(update_collection.count().await.unwrap(), 150);
 
+        teardown(db).await;
+    }
+    #[tokio::test]
+    async fn test_address_activity() {
+        let db = setup_database("test-ledger-updates-address-activity").await.unwrap();
+        let update_collection = setup_collection::<LedgerUpdateCollection>(&db).await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let address_unlock_condition = AddressUnlockCondition::rand();
+        let address = address_unlock_condition.address;
+
+        let unspent_outputs = [100, 200, 300]
+            .into_iter()
+            .map(|amount| LedgerOutput {
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 10000.into(),
+                },
+                output: Output::Basic(BasicOutput {
+                    amount: amount.into(),
+                    native_tokens: Vec::new().into_boxed_slice(),
+                    address_unlock_condition,
+                    storage_deposit_return_unlock_condition: None,
+                    timelock_unlock_condition: None,
+                    expiration_unlock_condition: None,
+                    features: Vec::new().into_boxed_slice(),
+                }),
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+            })
+            .collect::<Vec<_>>();
+        let spent_outputs = [LedgerSpent {
+            output: unspent_outputs[0].clone(),
+            spent_metadata: SpentMetadata {
+                transaction_id: OutputId::rand().transaction_id,
+                spent: MilestoneIndexTimestamp {
+                    milestone_index: 2.into(),
+                    milestone_timestamp: 20000.into(),
+                },
+            },
+        }];
+
+        output_collection
+            .insert_unspent_outputs(unspent_outputs.iter())
+            .await
+            .unwrap();
+        output_collection
+            .update_spent_outputs(spent_outputs.iter())
+            .await
+            .unwrap();
+        update_collection
+            .insert_unspent_ledger_updates(unspent_outputs.iter())
+            .await
+            .unwrap();
+        update_collection
+            .insert_spent_ledger_updates(spent_outputs.iter())
+            .await
+            .unwrap();
+
+        let activity = update_collection
+            .get_address_activity(&address, 1.into())
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            activity,
+            vec![AddressActivityRecord {
+                milestone_index: 1.into(),
+                milestone_timestamp: 10000.into(),
+                created_count: 3,
+                consumed_count: 0,
+                received: 600,
+                sent: 0,
+            }]
+        );
+
+        let activity = update_collection
+            .get_address_activity(&address, 2.into())
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(activity.len(), 2);
+        assert_eq!(
+            activity[1],
+            AddressActivityRecord {
+                milestone_index: 2.into(),
+                milestone_timestamp: 20000.into(),
+                created_count: 0,
+                consumed_count: 1,
+                received: 0,
+                sent: 100,
+            }
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_address_history_paging() {
+        let db = setup_database("test-address-history-paging").await.unwrap();
+        let update_collection = setup_collection::<LedgerUpdateCollection>(&db).await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let address_unlock_condition = AddressUnlockCondition::rand();
+        let address = address_unlock_condition.address;
+
+        let outputs = (1..=5)
+            .map(|milestone_index| LedgerOutput {
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: milestone_index.into(),
+                    milestone_timestamp: (milestone_index * 10000).into(),
+                },
+                output: Output::Basic(BasicOutput {
+                    amount: (milestone_index as u64 * 100).into(),
+                    native_tokens: Vec::new().into_boxed_slice(),
+                    address_unlock_condition,
+                    storage_deposit_return_unlock_condition: None,
+                    timelock_unlock_condition: None,
+                    expiration_unlock_condition: None,
+                    features: Vec::new().into_boxed_slice(),
+                }),
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+            })
+            .collect::<Vec<_>>();
+
+        output_collection.insert_unspent_outputs(outputs.iter()).await.unwrap();
+        update_collection
+            .insert_unspent_ledger_updates(outputs.iter())
+            .await
+            .unwrap();
+
+        let changes = update_collection
+            .get_address_balance_changes(&address, 2, 0.into(), 10.into(), 2)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                BalanceChangeRecord {
+                    start_index: 0.into(),
+                    received: 100,
+                    sent: 0,
+                },
+                BalanceChangeRecord {
+                    start_index: 2.into(),
+                    received: 500,
+                    sent: 0,
+                },
+            ]
+        );
+
+        let changes = update_collection
+            .get_address_balance_changes(&address, 2, 4.into(), 10.into(), 2)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            changes,
+            vec![BalanceChangeRecord {
+                start_index: 4.into(),
+                received: 900,
+                sent: 0,
+            }]
+        );
+        assert_eq!(
+            output_collection
+                .get_address_amount_at(&address, 3.into())
+                .await
+                .unwrap(),
+            600
+        );
+
+        let transactions = update_collection
+            .get_address_transactions(&address, 2, None, SortOrder::Newest, 4.into())
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            transactions
+                .iter()
+                .map(|rec| (rec.transaction_id, rec.at.milestone_index, rec.received))
+                .collect::<Vec<_>>(),
+            vec![
+                (outputs[3].output_id.transaction_id, 4.into(), 400),
+                (outputs[2].output_id.transaction_id, 3.into(), 300),
+            ]
+        );
+
+        let transactions = update_collection
+            .get_address_transactions(
+                &address,
+                2,
+                Some((2.into(), outputs[1].output_id.transaction_id)),
+                SortOrder::Oldest,
+                4.into(),
+            )
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            transactions
+                .iter()
+                .map(|rec| (rec.transaction_id, rec.at.milestone_index))
+                .collect::<Vec<_>>(),
+            vec![
+                (outputs[1].output_id.transaction_id, 2.into()),
+                (outputs[2].output_id.transaction_id, 3.into()),
+            ]
+        );
+
         teardown(db).await;
     }
 }
@@ -6,9 +6,10 @@ mod common;
 #[cfg(feature = "rand")]
 mod test_rand {
     use chronicle::{
-        db::mongodb::collections::MilestoneCollection,
+        db::mongodb::collections::{MilestoneCollection, MilestoneDataSize},
         model::payload::{MilestoneId, MilestonePayload},
     };
+    use futures::TryStreamExt;
     use pretty_assertions::assert_eq;
 
     use super::common::{setup_collection, setup_database, teardown};
@@ -27,6 +28,7 @@ mod test_rand {
                 milestone.essence.index,
                 milestone.essence.timestamp,
                 milestone.clone(),
+                MilestoneDataSize::default(),
             )
             .await
             .unwrap();
@@ -59,4 +61,126 @@ mod test_rand {
 
         teardown(db).await;
     }
+
+    #[tokio::test]
+    async fn test_milestone_data_size() {
+        let db = setup_database("test-milestone-data-size").await.unwrap();
+        let milestone_collection = setup_collection::<MilestoneCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        for (index, timestamp, blocks) in [(1, 100, 2), (2, 150, 3), (3, 250, 4)] {
+            let milestone = MilestonePayload::rand(&protocol_params);
+            milestone_collection
+                .insert_milestone(
+                    MilestoneId::rand(),
+                    index.into(),
+                    timestamp.into(),
+                    milestone,
+                    MilestoneDataSize {
+                        block_count: blocks,
+                        block_bytes: blocks * 100,
+                        output_count: 1,
+                        output_bytes: 150,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            milestone_collection.get_milestone_data_size(2.into()).await.unwrap(),
+            Some(MilestoneDataSize {
+                block_count: 3,
+                block_bytes: 300,
+                output_count: 1,
+                output_bytes: 150,
+            })
+        );
+
+        let buckets = milestone_collection
+            .get_data_size(0.into(), 300.into(), 200)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|bucket| (bucket.start_timestamp.0, bucket.milestone_count, bucket.data_size))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    0,
+                    2,
+                    MilestoneDataSize {
+                        block_count: 5,
+                        block_bytes: 500,
+                        output_count: 2,
+                        output_bytes: 300,
+                    }
+                ),
+                (
+                    200,
+                    1,
+                    MilestoneDataSize {
+                        block_count: 4,
+                        block_bytes: 400,
+                        output_count: 1,
+                        output_bytes: 150,
+                    }
+                ),
+            ]
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_milestone_transaction() {
+        let db = setup_database("test-milestone-transaction").await.unwrap();
+        let milestone_collection = setup_collection::<MilestoneCollection>(&db).await.unwrap();
+        // Transactions require a replica set.
+        if !db.supports_transactions().await.unwrap() {
+            teardown(db).await;
+            return;
+        }
+
+        let milestone = MilestonePayload::rand(&iota_sdk::types::block::protocol::protocol_parameters());
+        let mut session = db.start_session().await.unwrap();
+        session.start_transaction(None).await.unwrap();
+        milestone_collection
+            .insert_milestone_with_session(
+                MilestoneId::rand(),
+                milestone.essence.index,
+                milestone.essence.timestamp,
+                milestone.clone(),
+                MilestoneDataSize::default(),
+                Some(&mut session),
+            )
+            .await
+            .unwrap();
+        session.abort_transaction().await.unwrap();
+        assert_eq!(milestone_collection.get_ledger_index().await.unwrap(), None);
+
+        session.start_transaction(None).await.unwrap();
+        milestone_collection
+            .insert_milestone_with_session(
+                MilestoneId::rand(),
+                milestone.essence.index,
+                milestone.essence.timestamp,
+                milestone.clone(),
+                MilestoneDataSize::default(),
+                Some(&mut session),
+            )
+            .await
+            .unwrap();
+        session.commit_transaction().await.unwrap();
+        assert_eq!(
+            milestone_collection.get_ledger_index().await.unwrap(),
+            Some(milestone.essence.index)
+        );
+
+        teardown(db).await;
+    }
 }
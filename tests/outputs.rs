@@ -6,16 +6,25 @@ mod common;
 #[cfg(feature = "rand")]
 mod test_rand {
     use chronicle::{
-        db::mongodb::collections::{OutputCollection, OutputMetadataResult, OutputWithMetadataResult},
+        db::{
+            mongodb::collections::{
+                AddressActivityByKind, ApplicationStateCollection, BlockCollection, DailyAddressActivity,
+                LedgerSnapshotCollection, MilestoneCollection, MilestoneDataSize, OutputCollection,
+                OutputMetadataResult, OutputWithMetadataResult, TransferVolumeBucket, OUTPUT_DETAILS_VERSION,
+            },
+            MongoDbCollection,
+        },
         model::{
             ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
-            metadata::SpentMetadata,
-            payload::TransactionId,
+            metadata::{BlockMetadata, ConflictReason, LedgerInclusionState, SpentMetadata},
+            payload::{MilestoneId, MilestonePayload, Payload, TransactionEssence, TransactionId},
             tangle::MilestoneIndexTimestamp,
-            utxo::{Output, OutputId},
-            BlockId,
+            utxo::{Address, AddressUnlockCondition, BasicOutput, Output, OutputId},
+            Block, BlockId, ProtocolParameters,
         },
     };
+    use futures::TryStreamExt;
+    use mongodb::bson::{doc, Document};
     use pretty_assertions::assert_eq;
 
     use super::common::{setup_collection, setup_database, teardown};
@@ -75,6 +84,7 @@ mod test_rand {
                     block_id: output.block_id,
                     booked: output.booked,
                     spent_metadata: None,
+                    pruned_before: None,
                 }),
             );
         }
@@ -92,6 +102,7 @@ mod test_rand {
                         block_id: output.block_id,
                         booked: output.booked,
                         spent_metadata: None,
+                        pruned_before: None,
                     }
                 }),
             );
@@ -135,6 +146,7 @@ mod test_rand {
                     block_id: output.output.block_id,
                     booked: output.output.booked,
                     spent_metadata: Some(output.spent_metadata),
+                    pruned_before: None,
                 }),
             );
         }
@@ -152,6 +164,7 @@ mod test_rand {
                         block_id: output.output.block_id,
                         booked: output.output.booked,
                         spent_metadata: Some(output.spent_metadata),
+                        pruned_before: None,
                     }
                 }),
             );
@@ -170,4 +183,528 @@ mod test_rand {
 
         teardown(db).await;
     }
+
+    #[tokio::test]
+    async fn test_transfer_volume() {
+        let db = setup_database("test-transfer-volume").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let ledger_output = |output_id, address_unlock_condition, amount: u64, milestone_timestamp: u32| LedgerOutput {
+            output_id,
+            rent_structure: RentStructureBytes {
+                num_key_bytes: 0,
+                num_data_bytes: 100,
+            },
+            output: Output::Basic(BasicOutput {
+                amount: amount.into(),
+                native_tokens: Vec::new().into_boxed_slice(),
+                address_unlock_condition,
+                storage_deposit_return_unlock_condition: None,
+                timelock_unlock_condition: None,
+                expiration_unlock_condition: None,
+                features: Vec::new().into_boxed_slice(),
+            }),
+            block_id: BlockId::rand(),
+            booked: MilestoneIndexTimestamp {
+                milestone_index: 1.into(),
+                milestone_timestamp: milestone_timestamp.into(),
+            },
+        };
+
+        let (sender, receiver) = (AddressUnlockCondition::rand(), AddressUnlockCondition::rand());
+        let transaction_id = TransactionId::rand();
+        // An output that was not created by a transaction, which must not count towards the volume.
+        let migrated = ledger_output(OutputId::rand(), sender, 1000, 100);
+        let outputs = [
+            ledger_output((transaction_id, 0).into(), receiver, 600, 3700),
+            ledger_output((transaction_id, 1).into(), sender, 400, 3700),
+        ];
+        output_collection.insert_unspent_outputs([&migrated]).await.unwrap();
+        output_collection.insert_unspent_outputs(&outputs).await.unwrap();
+        output_collection
+            .update_spent_outputs([&LedgerSpent {
+                output: migrated,
+                spent_metadata: SpentMetadata {
+                    transaction_id,
+                    spent: MilestoneIndexTimestamp {
+                        milestone_index: 1.into(),
+                        milestone_timestamp: 3700.into(),
+                    },
+                },
+            }])
+            .await
+            .unwrap();
+
+        let buckets = output_collection
+            .get_transfer_volume(0.into(), 7200.into(), 3600)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            buckets,
+            vec![TransferVolumeBucket {
+                start_timestamp: 3600.into(),
+                volume: "600".to_string(),
+                max_transfer: "600".to_string(),
+                transaction_count: 1,
+            }]
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_ledger_digest() {
+        let db = setup_database("test-ledger-digest").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+        let snapshot_collection = setup_collection::<LedgerSnapshotCollection>(&db).await.unwrap();
+
+        let ledger_output = |amount: u64, milestone_index: u32| LedgerOutput {
+            output_id: OutputId::rand(),
+            rent_structure: RentStructureBytes {
+                num_key_bytes: 0,
+                num_data_bytes: 100,
+            },
+            output: Output::Basic(BasicOutput {
+                amount: amount.into(),
+                native_tokens: Vec::new().into_boxed_slice(),
+                address_unlock_condition: AddressUnlockCondition::rand(),
+                storage_deposit_return_unlock_condition: None,
+                timelock_unlock_condition: None,
+                expiration_unlock_condition: None,
+                features: Vec::new().into_boxed_slice(),
+            }),
+            block_id: BlockId::rand(),
+            booked: MilestoneIndexTimestamp {
+                milestone_index: milestone_index.into(),
+                milestone_timestamp: (milestone_index * 10).into(),
+            },
+        };
+
+        let (spent, created) = (ledger_output(1000, 1), ledger_output(600, 2));
+        output_collection
+            .insert_unspent_outputs([&spent, &created])
+            .await
+            .unwrap();
+        output_collection
+            .update_spent_outputs([&LedgerSpent {
+                output: spent,
+                spent_metadata: SpentMetadata {
+                    transaction_id: TransactionId::rand(),
+                    spent: MilestoneIndexTimestamp {
+                        milestone_index: 2.into(),
+                        milestone_timestamp: 20.into(),
+                    },
+                },
+            }])
+            .await
+            .unwrap();
+
+        let first = output_collection.get_ledger_digest(1.into()).await.unwrap();
+        assert_eq!((first.output_count, first.total_amount), (1, 1000));
+        let second = output_collection.get_ledger_digest(2.into()).await.unwrap();
+        assert_eq!((second.output_count, second.total_amount), (1, 600));
+        assert_ne!(first.digest, second.digest);
+        assert_eq!(output_collection.get_ledger_digest(2.into()).await.unwrap(), second);
+
+        snapshot_collection
+            .upsert_snapshot(1.into(), 10.into(), first)
+            .await
+            .unwrap();
+        snapshot_collection
+            .upsert_snapshot(2.into(), 20.into(), second)
+            .await
+            .unwrap();
+        assert_eq!(
+            snapshot_collection.get_latest_snapshot_index().await.unwrap(),
+            Some(2.into())
+        );
+        let snapshots = snapshot_collection.get_snapshots(Some(2.into()), None).await.unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].digest, second);
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_prune_spent_outputs() {
+        let db = setup_database("test-prune-spent-outputs").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        let outputs = std::iter::repeat_with(|| Output::rand_basic(&protocol_params))
+            .take(3)
+            .map(|output| LedgerOutput {
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output,
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 10.into(),
+                },
+            })
+            .collect::<Vec<_>>();
+        output_collection.insert_unspent_outputs(&outputs).await.unwrap();
+        // The first output is spent early, the second one late and the third one not at all.
+        let spent = outputs[..2]
+            .iter()
+            .zip([2, 5])
+            .map(|(output, milestone_index)| LedgerSpent {
+                output: output.clone(),
+                spent_metadata: SpentMetadata {
+                    transaction_id: TransactionId::rand(),
+                    spent: MilestoneIndexTimestamp {
+                        milestone_index: milestone_index.into(),
+                        milestone_timestamp: (milestone_index * 10).into(),
+                    },
+                },
+            })
+            .collect::<Vec<_>>();
+        output_collection.update_spent_outputs(&spent).await.unwrap();
+
+        let prunable = output_collection
+            .get_prunable_output_ids("basic", 5.into())
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(prunable, vec![outputs[0].output_id]);
+        assert_eq!(
+            output_collection
+                .get_prunable_output_ids("nft", 5.into())
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap(),
+            Vec::new()
+        );
+
+        assert_eq!(output_collection.delete_outputs(&prunable).await.unwrap(), 1);
+        assert_eq!(output_collection.get_output(&outputs[0].output_id).await.unwrap(), None);
+        assert!(output_collection
+            .get_output(&outputs[1].output_id)
+            .await
+            .unwrap()
+            .is_some());
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_address_activity() {
+        let db = setup_database("test-address-activity").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        const DAY: u32 = 24 * 60 * 60;
+
+        let ledger_output = |address, milestone_timestamp: u32| LedgerOutput {
+            output_id: OutputId::rand(),
+            rent_structure: RentStructureBytes {
+                num_key_bytes: 0,
+                num_data_bytes: 100,
+            },
+            output: Output::Basic(BasicOutput {
+                amount: 100.into(),
+                native_tokens: Vec::new().into_boxed_slice(),
+                address_unlock_condition: AddressUnlockCondition { address },
+                storage_deposit_return_unlock_condition: None,
+                timelock_unlock_condition: None,
+                expiration_unlock_condition: None,
+                features: Vec::new().into_boxed_slice(),
+            }),
+            block_id: BlockId::rand(),
+            booked: MilestoneIndexTimestamp {
+                milestone_index: 1.into(),
+                milestone_timestamp: milestone_timestamp.into(),
+            },
+        };
+
+        let ed25519 = Address::rand_ed25519();
+        let spent = ledger_output(ed25519, 100);
+        output_collection
+            .insert_unspent_outputs([
+                &spent,
+                &ledger_output(ed25519, 150),
+                &ledger_output(Address::rand_nft(), 200),
+                &ledger_output(Address::rand_alias(), DAY + 200),
+            ])
+            .await
+            .unwrap();
+        output_collection
+            .update_spent_outputs([&LedgerSpent {
+                output: spent,
+                spent_metadata: SpentMetadata {
+                    transaction_id: TransactionId::rand(),
+                    spent: MilestoneIndexTimestamp {
+                        milestone_index: 2.into(),
+                        milestone_timestamp: (DAY + 100).into(),
+                    },
+                },
+            }])
+            .await
+            .unwrap();
+
+        let activity = |ed25519_count, alias_count, nft_count| AddressActivityByKind {
+            ed25519_count,
+            alias_count,
+            nft_count,
+        };
+        assert_eq!(
+            output_collection
+                .get_address_activity_in_range(0.into(), (2 * DAY).into())
+                .await
+                .unwrap(),
+            activity(1, 1, 1)
+        );
+        assert_eq!(
+            output_collection
+                .get_address_activity_in_range(DAY.into(), (2 * DAY).into())
+                .await
+                .unwrap(),
+            activity(1, 1, 0)
+        );
+        assert_eq!(
+            output_collection
+                .get_daily_address_activity_in_range(0.into(), (2 * DAY).into())
+                .await
+                .unwrap(),
+            vec![
+                DailyAddressActivity {
+                    start_timestamp: 0.into(),
+                    activity: activity(1, 0, 1),
+                },
+                DailyAddressActivity {
+                    start_timestamp: DAY.into(),
+                    activity: activity(1, 1, 0),
+                },
+            ]
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_output_details() {
+        let db = setup_database("test-upgrade-output-details").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+
+        let outputs = std::iter::repeat_with(|| Output::rand(&protocol_params))
+            .take(10)
+            .map(|output| LedgerOutput {
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output,
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 12345.into(),
+                },
+            })
+            .collect::<Vec<_>>();
+        output_collection.insert_unspent_outputs(&outputs).await.unwrap();
+
+        // Simulate documents that were written before the details were versioned.
+        let raw = output_collection.collection().clone_with_type::<Document>();
+        raw.update_many(doc! {}, doc! { "$unset": { "details_version": "" } }, None)
+            .await
+            .unwrap();
+        let outdated = || async {
+            raw.count_documents(doc! { "details_version": { "$exists": false } }, None)
+                .await
+                .unwrap()
+        };
+        assert_eq!(outdated().await, 10);
+
+        // Querying an output upgrades it.
+        output_collection
+            .get_output_with_metadata(&outputs[0].output_id, 1.into())
+            .await
+            .unwrap();
+        assert_eq!(outdated().await, 9);
+
+        let (count, last) = output_collection.upgrade_outdated_details(None, 5).await.unwrap().unwrap();
+        assert_eq!(count, 5);
+        let (count, last) = output_collection
+            .upgrade_outdated_details(Some(last), 5)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(
+            output_collection.upgrade_outdated_details(Some(last), 5).await.unwrap(),
+            None
+        );
+        assert_eq!(
+            raw.count_documents(doc! { "details_version": OUTPUT_DETAILS_VERSION }, None)
+                .await
+                .unwrap(),
+            10
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_pruned_output_metadata() {
+        let db = setup_database("test-pruned-output-metadata").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+        let block_collection = setup_collection::<BlockCollection>(&db).await.unwrap();
+        let milestone_collection = setup_collection::<MilestoneCollection>(&db).await.unwrap();
+        let application_state = setup_collection::<ApplicationStateCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+
+        // The transaction that created the output was stored, but the output itself was pruned.
+        let block = Block::rand_transaction(&protocol_params);
+        let payload = match &block.payload {
+            Some(Payload::Transaction(payload)) => payload,
+            _ => unreachable!(),
+        };
+        let TransactionEssence::Regular { outputs, .. } = &payload.essence;
+        let output_id = OutputId {
+            transaction_id: payload.transaction_id,
+            index: 0,
+        };
+        let kind = outputs[0].kind().to_string();
+        let block_id = BlockId::rand();
+        let raw = block
+            .clone()
+            .raw(ProtocolParameters::from(protocol_params.clone()))
+            .unwrap();
+        let metadata = BlockMetadata {
+            parents: block.parents.clone(),
+            is_solid: true,
+            should_promote: false,
+            should_reattach: false,
+            referenced_by_milestone_index: 1.into(),
+            milestone_index: 0.into(),
+            inclusion_state: LedgerInclusionState::Included,
+            conflict_reason: ConflictReason::None,
+            white_flag_index: 0,
+        };
+        block_collection
+            .insert_blocks_with_metadata([(block_id, block.clone(), raw, metadata)])
+            .await
+            .unwrap();
+        milestone_collection
+            .insert_milestone(
+                MilestoneId::rand(),
+                1.into(),
+                12345.into(),
+                MilestonePayload::rand(&protocol_params),
+                MilestoneDataSize::default(),
+            )
+            .await
+            .unwrap();
+
+        // Without pruning, the output is simply unknown.
+        assert_eq!(
+            output_collection
+                .get_output_metadata(&output_id, 1.into())
+                .await
+                .unwrap(),
+            None
+        );
+
+        application_state
+            .set_output_pruning_index(&kind, 1.into())
+            .await
+            .unwrap();
+        // The pruning index never moves back.
+        application_state
+            .set_output_pruning_index(&kind, 0.into())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output_collection
+                .get_output_metadata(&output_id, 1.into())
+                .await
+                .unwrap(),
+            Some(OutputMetadataResult {
+                output_id,
+                block_id,
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 12345.into(),
+                },
+                spent_metadata: None,
+                pruned_before: Some(1.into()),
+            }),
+        );
+        // The transaction does not have an output at this index.
+        assert_eq!(
+            output_collection
+                .get_output_metadata(
+                    &OutputId {
+                        index: outputs.len() as u16,
+                        ..output_id
+                    },
+                    1.into()
+                )
+                .await
+                .unwrap(),
+            None
+        );
+        // The transaction was not yet included at this ledger index.
+        assert_eq!(
+            output_collection
+                .get_output_metadata(&output_id, 0.into())
+                .await
+                .unwrap(),
+            None
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_missing_output_ids() {
+        let db = setup_database("test-missing-output-ids").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_sdk::types::block::protocol::protocol_parameters();
+        let outputs = std::iter::repeat_with(|| LedgerOutput {
+            output_id: OutputId::rand(),
+            rent_structure: RentStructureBytes {
+                num_key_bytes: 0,
+                num_data_bytes: 100,
+            },
+            output: Output::rand(&protocol_params),
+            block_id: BlockId::rand(),
+            booked: MilestoneIndexTimestamp {
+                milestone_index: 1.into(),
+                milestone_timestamp: 12345.into(),
+            },
+        })
+        .take(3)
+        .collect::<Vec<_>>();
+        output_collection.insert_unspent_outputs(&outputs[..2]).await.unwrap();
+
+        let output_ids = outputs.iter().map(|output| output.output_id).collect::<Vec<_>>();
+        assert_eq!(
+            output_collection.get_missing_output_ids(&output_ids).await.unwrap(),
+            [outputs[2].output_id].into_iter().collect()
+        );
+        assert!(output_collection
+            .get_missing_output_ids(&output_ids[..2])
+            .await
+            .unwrap()
+            .is_empty());
+
+        teardown(db).await;
+    }
 }